@@ -0,0 +1,240 @@
+//! Deployment profiles: `development`, `staging` and `production`.
+//!
+//! `APP_ENVIRONMENT` (or `environment` in the file) selects one; each profile
+//! supplies the defaults of the settings that differ between deployments and
+//! decides which startup guards apply. An explicit value in any layer still
+//! wins over the profile default.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::AppConfig;
+
+/// Deployment profile selected by `APP_ENVIRONMENT`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Environment {
+    #[default]
+    Development,
+    Staging,
+    Production,
+}
+
+/// Settings and guards that depend on the profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Profile {
+    /// Default of `server.verbose_errors`: internal error messages in API responses
+    pub verbose_errors: bool,
+    /// Default of `jwt.cookie_secure`: `Secure` flag of the auth cookie
+    pub secure_cookies: bool,
+    /// Rejects example JWT secrets, short secrets and access tokens over 24 h
+    pub strict_secrets: bool,
+    /// Refuses to start with pending migrations unless `database.allow_pending_migrations`
+    pub require_migrations: bool,
+    /// Allows loading the demo data, whose admin password is public, and is
+    /// the default of `jwt.demo_login`
+    pub allow_demo_seed: bool,
+}
+
+impl Environment {
+    /// Every profile, from least to most strict
+    pub const ALL: [Environment; 3] = [Environment::Development, Environment::Staging, Environment::Production];
+
+    /// Name used in the configuration and in `/system/status`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Environment::Development => "development",
+            Environment::Staging => "staging",
+            Environment::Production => "production",
+        }
+    }
+
+    pub fn is_production(&self) -> bool {
+        *self == Environment::Production
+    }
+
+    /// Defaults and guards of this profile
+    pub fn profile(&self) -> Profile {
+        match self {
+            Environment::Development => Profile {
+                verbose_errors: true,
+                secure_cookies: false,
+                strict_secrets: false,
+                require_migrations: false,
+                allow_demo_seed: true,
+            },
+            Environment::Staging => Profile {
+                verbose_errors: true,
+                secure_cookies: true,
+                strict_secrets: false,
+                require_migrations: false,
+                allow_demo_seed: true,
+            },
+            Environment::Production => Profile {
+                verbose_errors: false,
+                secure_cookies: true,
+                strict_secrets: true,
+                require_migrations: true,
+                allow_demo_seed: false,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `APP_ENVIRONMENT` names no known profile
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Entorno desconocido {0:?} (se esperaba development, staging o production)")]
+pub struct UnknownEnvironment(pub String);
+
+impl FromStr for Environment {
+    type Err = UnknownEnvironment;
+
+    /// Case-insensitive, also accepting `dev`, `stage` and `prod`
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "development" | "dev" => Ok(Environment::Development),
+            "staging" | "stage" => Ok(Environment::Staging),
+            "production" | "prod" => Ok(Environment::Production),
+            other => Err(UnknownEnvironment(other.to_string())),
+        }
+    }
+}
+
+impl Serialize for Environment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Environment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl AppConfig {
+    /// Fills the settings no layer set with the defaults of the profile
+    pub(super) fn apply_profile_defaults(&mut self) {
+        let profile = self.environment.profile();
+        self.server.verbose_errors.get_or_insert(profile.verbose_errors);
+        self.jwt.cookie_secure.get_or_insert(profile.secure_cookies);
+        self.jwt.demo_login.get_or_insert(profile.allow_demo_seed);
+    }
+
+    /// Settings that look wrong for the profile without making it unusable,
+    /// e.g. production pointing at a database on `localhost`; logged at startup
+    pub fn profile_warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.environment.is_production() {
+            return warnings;
+        }
+
+        for (key, url) in [
+            ("database.url", self.database.url.as_deref()),
+            ("database.replica_url", self.database.replica_url.as_deref()),
+            ("server.base_url", Some(self.server.base_url.as_str())),
+        ] {
+            if url.is_some_and(is_local_url) {
+                warnings.push(format!("{} apunta a este mismo equipo en el entorno production", key));
+            }
+        }
+        if self.server.verbose_errors == Some(true) {
+            warnings.push("server.verbose_errors expone los errores internos en el entorno production".to_string());
+        }
+        if self.jwt.cookie_secure == Some(false) {
+            warnings.push("jwt.cookie_secure desactivado: la cookie de sesión viaja sin HTTPS en production".to_string());
+        }
+        warnings
+    }
+}
+
+/// Whether the host of `url` is the local machine (`localhost`, `127.0.0.0/8` or `::1`)
+fn is_local_url(url: &str) -> bool {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host_port = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host_port.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host_port.split(':').next().unwrap_or_default(),
+    };
+
+    host.eq_ignore_ascii_case("localhost") || host.starts_with("127.") || host == "::1"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsing_is_case_insensitive_with_aliases() {
+        assert_eq!("Production".parse(), Ok(Environment::Production));
+        assert_eq!(" prod ".parse(), Ok(Environment::Production));
+        assert_eq!("STAGE".parse(), Ok(Environment::Staging));
+        assert_eq!("dev".parse(), Ok(Environment::Development));
+        assert_eq!("preview".parse::<Environment>(), Err(UnknownEnvironment("preview".to_string())));
+
+        for environment in Environment::ALL {
+            assert_eq!(environment.as_str().parse(), Ok(environment));
+            assert_eq!(serde_json::to_value(environment).unwrap(), environment.as_str());
+        }
+    }
+
+    #[test]
+    fn test_profile_defaults() {
+        let development = Environment::Development.profile();
+        assert!(development.verbose_errors && !development.secure_cookies && development.allow_demo_seed);
+
+        let staging = Environment::Staging.profile();
+        assert!(staging.secure_cookies && !staging.strict_secrets && !staging.require_migrations);
+
+        let production = Environment::Production.profile();
+        assert!(!production.verbose_errors && production.secure_cookies);
+        assert!(production.strict_secrets && production.require_migrations && !production.allow_demo_seed);
+    }
+
+    #[test]
+    fn test_profile_defaults_fill_only_unset_values() {
+        let mut config = AppConfig { environment: Environment::Production, ..Default::default() };
+        config.jwt.cookie_secure = Some(false);
+
+        config.apply_profile_defaults();
+
+        assert_eq!(config.server.verbose_errors, Some(false));
+        assert_eq!(config.jwt.demo_login, Some(false));
+        assert_eq!(config.jwt.cookie_secure, Some(false), "explicit value wins");
+    }
+
+    #[test]
+    fn test_local_url_heuristic() {
+        assert!(is_local_url("postgres://sai:pw@localhost/sai"));
+        assert!(is_local_url("postgres://127.0.0.1:5432/sai"));
+        assert!(is_local_url("postgres://sai@[::1]:5432/sai"));
+        assert!(is_local_url("http://LOCALHOST:8080"));
+        assert!(!is_local_url("postgres://sai:pw@db.internal/sai"));
+        assert!(!is_local_url("postgres://localhost.example.com/sai"));
+        assert!(!is_local_url("https://sai.edu.py"));
+    }
+
+    #[test]
+    fn test_production_with_local_database_warns() {
+        let mut config = AppConfig { environment: Environment::Production, ..Default::default() };
+        config.database.url = Some("postgres://sai:pw@localhost/sai".to_string());
+        config.server.base_url = "https://sai.edu.py".to_string();
+        config.apply_profile_defaults();
+
+        let warnings = config.profile_warnings();
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("database.url"));
+
+        config.environment = Environment::Development;
+        assert!(config.profile_warnings().is_empty(), "local services are expected in development");
+    }
+}
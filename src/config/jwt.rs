@@ -0,0 +1,245 @@
+//! `[jwt]` section: signing algorithm and keys, token lifetimes and the
+//! flags of the `auth_token` cookie.
+//!
+//! HMAC algorithms (`HS256`, the default) sign with `jwt.secret`; RSA and EC
+//! algorithms read PEM keys from `jwt.private_key_path` and
+//! `jwt.public_key_path`, so switching to `RS256` is a configuration change.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey};
+use serde::{Deserialize, Serialize};
+
+use super::{record_source_read, redacted};
+
+/// Longest access token lifetime accepted in production
+pub const MAX_PRODUCTION_ACCESS_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Secret used when none is configured; production refuses to start with it
+const DEVELOPMENT_SECRET: &str = "your-secret-key";
+
+/// `SameSite` attribute of the auth cookie
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SameSitePolicy {
+    #[default]
+    Strict,
+    Lax,
+    None,
+}
+
+/// `[jwt]`: token signing
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JwtConfig {
+    /// `JWT_SECRET` (required for the HMAC algorithms)
+    pub secret: Option<String>,
+    /// Signing algorithm, e.g. `HS256` or `RS256`
+    pub algorithm: Algorithm,
+    /// PEM private key, for RSA and EC algorithms
+    pub private_key_path: Option<PathBuf>,
+    /// PEM public key, for RSA and EC algorithms
+    pub public_key_path: Option<PathBuf>,
+    /// `JWT_EXPIRATION`: access token lifetime, in seconds
+    pub access_ttl_secs: u64,
+    /// Refresh token lifetime, in seconds
+    pub refresh_ttl_secs: u64,
+    /// `iss` claim issued and required
+    pub issuer: Option<String>,
+    /// `aud` claim issued and required
+    pub audience: Option<String>,
+    /// Clock skew tolerated when checking `exp`, in seconds
+    pub leeway_secs: u64,
+    /// `Secure` flag of the auth cookie; defaults to the profile's
+    pub cookie_secure: Option<bool>,
+    /// `HttpOnly` flag of the auth cookie
+    pub cookie_http_only: bool,
+    /// `SameSite` attribute of the auth cookie
+    pub cookie_same_site: SameSitePolicy,
+    /// `AUTH_DEMO_LOGIN`: accept the `admin` / `password` demo login; defaults
+    /// to the profile's `allow_demo_seed`
+    pub demo_login: Option<bool>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            secret: None,
+            algorithm: Algorithm::HS256,
+            private_key_path: None,
+            public_key_path: None,
+            access_ttl_secs: 3600,
+            refresh_ttl_secs: 7 * 24 * 60 * 60,
+            issuer: None,
+            audience: None,
+            leeway_secs: 60,
+            cookie_secure: None,
+            cookie_http_only: true,
+            cookie_same_site: SameSitePolicy::Strict,
+            demo_login: None,
+        }
+    }
+}
+
+impl fmt::Debug for JwtConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtConfig")
+            .field("secret", &redacted(&self.secret))
+            .field("algorithm", &self.algorithm)
+            .field("private_key_path", &self.private_key_path)
+            .field("public_key_path", &self.public_key_path)
+            .field("access_ttl_secs", &self.access_ttl_secs)
+            .field("refresh_ttl_secs", &self.refresh_ttl_secs)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("leeway_secs", &self.leeway_secs)
+            .field("cookie_secure", &self.cookie_secure)
+            .field("cookie_http_only", &self.cookie_http_only)
+            .field("cookie_same_site", &self.cookie_same_site)
+            .field("demo_login", &self.demo_login)
+            .finish()
+    }
+}
+
+/// Errors while loading the signing keys
+#[derive(Debug, thiserror::Error)]
+pub enum JwtKeyError {
+    #[error("{0:?} necesita jwt.private_key_path y jwt.public_key_path")]
+    MissingKeyPath(Algorithm),
+    #[error("No se pudo leer la clave {}: {source}", .path.display())]
+    Read { path: PathBuf, source: std::io::Error },
+    #[error("Clave inválida en {} para {algorithm:?}: {source}", .path.display())]
+    Invalid {
+        path: PathBuf,
+        algorithm: Algorithm,
+        source: jsonwebtoken::errors::Error,
+    },
+}
+
+/// Keys to sign and verify tokens with the configured algorithm
+#[derive(Clone)]
+pub struct JwtKeys {
+    pub algorithm: Algorithm,
+    pub encoding: EncodingKey,
+    pub decoding: DecodingKey,
+}
+
+impl JwtConfig {
+    /// Whether the algorithm signs with `secret` instead of a key pair
+    pub fn is_hmac(&self) -> bool {
+        matches!(self.algorithm, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512)
+    }
+
+    /// Whether tokens are signed with the development secret or one from the examples
+    pub fn has_default_secret(&self) -> bool {
+        self.is_hmac()
+            && self
+                .secret
+                .as_deref()
+                .is_none_or(|secret| super::validate::KNOWN_DEFAULT_SECRETS.contains(&secret.trim()))
+    }
+
+    /// Builds the signing keys, reading the PEM files of asymmetric algorithms
+    ///
+    /// Without `secret`, HMAC algorithms fall back to a development secret
+    /// that [`AppConfig::validate`](super::AppConfig::validate) rejects in production.
+    pub fn keys(&self) -> Result<JwtKeys, JwtKeyError> {
+        record_source_read();
+        if self.is_hmac() {
+            let secret = self.secret.as_deref().unwrap_or(DEVELOPMENT_SECRET).as_bytes();
+            return Ok(JwtKeys {
+                algorithm: self.algorithm,
+                encoding: EncodingKey::from_secret(secret),
+                decoding: DecodingKey::from_secret(secret),
+            });
+        }
+
+        let (Some(private_path), Some(public_path)) = (&self.private_key_path, &self.public_key_path) else {
+            return Err(JwtKeyError::MissingKeyPath(self.algorithm));
+        };
+        let encoding = self.read_key(private_path, |pem| match self.algorithm {
+            Algorithm::ES256 | Algorithm::ES384 => EncodingKey::from_ec_pem(pem),
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(pem),
+            _ => EncodingKey::from_rsa_pem(pem),
+        })?;
+        let decoding = self.read_key(public_path, |pem| match self.algorithm {
+            Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem),
+            Algorithm::EdDSA => DecodingKey::from_ed_pem(pem),
+            _ => DecodingKey::from_rsa_pem(pem),
+        })?;
+
+        Ok(JwtKeys { algorithm: self.algorithm, encoding, decoding })
+    }
+
+    fn read_key<K>(
+        &self,
+        path: &Path,
+        parse: impl FnOnce(&[u8]) -> jsonwebtoken::errors::Result<K>,
+    ) -> Result<K, JwtKeyError> {
+        let pem = std::fs::read(path).map_err(|source| JwtKeyError::Read { path: path.to_path_buf(), source })?;
+        parse(&pem).map_err(|source| JwtKeyError::Invalid {
+            path: path.to_path_buf(),
+            algorithm: self.algorithm,
+            source,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_keys_from_secret() {
+        let config = JwtConfig { secret: Some("s3cret".to_string()), ..Default::default() };
+
+        let keys = config.keys().unwrap();
+
+        assert!(config.is_hmac());
+        assert_eq!(keys.algorithm, Algorithm::HS256);
+    }
+
+    #[test]
+    fn test_default_secrets_detected() {
+        let secret = |secret: Option<&str>| JwtConfig { secret: secret.map(str::to_string), ..Default::default() };
+
+        assert!(secret(None).has_default_secret());
+        assert!(secret(Some(DEVELOPMENT_SECRET)).has_default_secret());
+        assert!(secret(Some(" changeme ")).has_default_secret());
+        assert!(!secret(Some("0123456789abcdef0123456789abcdef")).has_default_secret());
+        // Key pairs have no secret to leak
+        assert!(!JwtConfig { algorithm: Algorithm::RS256, ..Default::default() }.has_default_secret());
+    }
+
+    #[test]
+    fn test_asymmetric_algorithm_needs_key_paths() {
+        let config = JwtConfig { algorithm: Algorithm::RS256, ..Default::default() };
+
+        assert!(matches!(config.keys(), Err(JwtKeyError::MissingKeyPath(Algorithm::RS256))));
+    }
+
+    #[test]
+    fn test_unreadable_or_invalid_key_file() {
+        let garbage = std::env::temp_dir().join(format!("sai-jwt-{}.pem", uuid::Uuid::new_v4()));
+        std::fs::write(&garbage, "not a key").unwrap();
+        let mut config = JwtConfig {
+            algorithm: Algorithm::RS256,
+            private_key_path: Some(PathBuf::from("/nonexistent/sai/private.pem")),
+            public_key_path: Some(garbage.clone()),
+            ..Default::default()
+        };
+        assert!(matches!(config.keys(), Err(JwtKeyError::Read { .. })));
+
+        config.private_key_path = Some(garbage.clone());
+        let result = config.keys();
+        std::fs::remove_file(&garbage).unwrap();
+        assert!(matches!(result, Err(JwtKeyError::Invalid { .. })));
+    }
+
+    #[test]
+    fn test_secret_not_in_debug_output() {
+        let config = JwtConfig { secret: Some("s3cret".to_string()), ..Default::default() };
+
+        assert!(!format!("{:?}", config).contains("s3cret"));
+    }
+}
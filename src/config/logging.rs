@@ -0,0 +1,76 @@
+//! `[logging]` section: per-target levels, file output and rotation.
+//!
+//! The values are applied by [`crate::logging`], which also handles the
+//! temporary level override of `PUT /api/admin/log-level`.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::DEFAULT_LOG_LEVEL;
+
+/// When the log file starts a new file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    /// One file per UTC day: `sai.log.2024-03-01`
+    #[default]
+    Daily,
+    /// `sai.log` until it reaches `max_file_size`, then `sai.log.1`, `sai.log.2`, ...
+    Size,
+}
+
+/// `[logging]`: levels, file output and rotation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// `RUST_LOG` or `LOG_LEVEL`: default level and per-target levels,
+    /// e.g. `info,sqlx=warn,sai::services=debug`
+    pub level: String,
+    /// `LOG_FILE`: also write JSON lines to this file; unset logs to the console only
+    pub file: Option<PathBuf>,
+    /// `LOG_ROTATION`: daily or size
+    pub rotation: Rotation,
+    /// `LOG_MAX_FILE_SIZE`: size that starts a new file with `rotation = "size"`, in bytes
+    pub max_file_size: u64,
+    /// `LOG_MAX_FILES`: rotated files kept besides the current one
+    pub max_files: usize,
+    /// `LOG_OVERRIDE_TTL`: how long a level set through the API lasts unless
+    /// the request says otherwise, in seconds
+    pub override_ttl_secs: u64,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: DEFAULT_LOG_LEVEL.to_string(),
+            file: None,
+            rotation: Rotation::Daily,
+            max_file_size: 10 * 1024 * 1024,
+            max_files: 7,
+            override_ttl_secs: 15 * 60,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = LoggingConfig::default();
+
+        assert_eq!(config.level, "info");
+        assert_eq!(config.file, None);
+        assert_eq!(config.rotation, Rotation::Daily);
+        assert_eq!(config.max_files, 7);
+        assert_eq!(config.override_ttl_secs, 900);
+    }
+
+    #[test]
+    fn test_rotation_names() {
+        assert_eq!(serde_json::to_value(Rotation::Size).unwrap(), "size");
+        assert_eq!(serde_json::from_value::<Rotation>("daily".into()).unwrap(), Rotation::Daily);
+    }
+}
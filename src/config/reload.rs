@@ -0,0 +1,231 @@
+//! Reloading the configuration without restarting the server.
+//!
+//! `sai serve` re-reads the file and the environment on `SIGHUP` or
+//! `POST /api/admin/config/reload`. Only the sections in
+//! [`RELOADABLE_SECTIONS`] take the new values, except the
+//! [`RESTART_ONLY_KEYS`]; a change anywhere else (bind address, database, JWT
+//! keys, ...) is reported and ignored until the next restart. A file that cannot be read or a result that fails
+//! [`AppConfig::validate`] leaves the running configuration untouched.
+
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{default_file, AppConfig, ConfigError, MailConfig, ValidationReport};
+
+/// Sections that take effect on reload; every other section needs a restart
+pub const RELOADABLE_SECTIONS: &[&str] = &["mail", "features", "logging"];
+
+/// Keys of a reloadable section that still need a restart: the services read them once, when built
+pub const RESTART_ONLY_KEYS: &[&str] = &["mail.lowercase_local_part"];
+
+/// Outcome of a reload, as returned by the endpoint and recorded in the audit log
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReloadReport {
+    /// Keys whose new value is now in effect
+    pub applied: Vec<String>,
+    /// Keys that changed in a section that needs a restart; they keep the running value
+    pub ignored: Vec<String>,
+}
+
+/// A reload that did not change anything
+#[derive(Debug, thiserror::Error)]
+pub enum ReloadError {
+    #[error(transparent)]
+    Load(#[from] ConfigError),
+    #[error(transparent)]
+    Invalid(#[from] ValidationReport),
+}
+
+/// Configuration shared by the running server, replaced section by section on reload
+pub struct LiveConfig {
+    current: RwLock<Arc<AppConfig>>,
+    /// File given with `--config`; `None` looks up `SAI_CONFIG` or `sai.toml` again
+    file: Option<PathBuf>,
+}
+
+impl LiveConfig {
+    pub fn new(config: AppConfig) -> Self {
+        Self { current: RwLock::new(Arc::new(config)), file: None }
+    }
+
+    /// Reloads from `file` instead of `SAI_CONFIG` or `sai.toml`
+    pub fn with_file(mut self, file: Option<PathBuf>) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// Configuration in effect; later reloads do not change the returned value
+    pub fn current(&self) -> Arc<AppConfig> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Reads the file and the process environment again and applies the reloadable sections
+    pub fn reload(&self) -> Result<ReloadReport, ReloadError> {
+        self.reload_from(&env::vars().collect())
+    }
+
+    /// Same as [`reload`](Self::reload) with an explicit environment
+    pub fn reload_from(&self, vars: &HashMap<String, String>) -> Result<ReloadReport, ReloadError> {
+        let file = self.file.clone().or_else(|| default_file(vars));
+        self.apply(AppConfig::layered(file.as_deref(), vars)?)
+    }
+
+    /// Takes the reloadable sections of `loaded`, validating the result first
+    pub fn apply(&self, loaded: AppConfig) -> Result<ReloadReport, ReloadError> {
+        let mut current = self.current.write().unwrap();
+        let mut report = ReloadReport::default();
+        for key in changed_keys(&current, &loaded) {
+            let section = key.split('.').next().unwrap_or_default();
+            if RELOADABLE_SECTIONS.contains(&section) && !RESTART_ONLY_KEYS.contains(&key.as_str()) {
+                report.applied.push(key);
+            } else {
+                report.ignored.push(key);
+            }
+        }
+
+        let mut next = AppConfig::clone(&current);
+        next.mail = MailConfig { lowercase_local_part: current.mail.lowercase_local_part, ..loaded.mail };
+        next.features = loaded.features;
+        next.logging = loaded.logging;
+        next.validate()?;
+
+        *current = Arc::new(next);
+        Ok(report)
+    }
+}
+
+/// Dotted keys whose value differs; the values themselves are left out so
+/// that secrets never reach the report
+fn changed_keys(old: &AppConfig, new: &AppConfig) -> Vec<String> {
+    let old = serde_json::to_value(old).unwrap_or(Value::Null);
+    let new = serde_json::to_value(new).unwrap_or(Value::Null);
+    let mut keys = Vec::new();
+    collect_changes(&old, &new, "", &mut keys);
+    keys
+}
+
+fn collect_changes(old: &Value, new: &Value, prefix: &str, keys: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                let path = if prefix.is_empty() { name.clone() } else { format!("{}.{}", prefix, name) };
+                let (old, new) = (old.get(name).unwrap_or(&Value::Null), new.get(name).unwrap_or(&Value::Null));
+                collect_changes(old, new, &path, keys);
+            }
+        }
+        (old, new) if old != new => keys.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn write_toml(contents: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!("sai-reload-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    const RUNNING: &str = r#"
+[server]
+port = 8080
+
+[database]
+url = "postgres://sai:pw@db.internal/sai"
+
+[jwt]
+secret = "0123456789abcdef0123456789abcdef"
+
+[mail]
+host = "smtp.example.com"
+port = 587
+user = "sai"
+password = "old-password"
+from = "noreply@example.com"
+"#;
+
+    fn live(file: &std::path::Path) -> LiveConfig {
+        let config = AppConfig::layered(Some(file), &HashMap::new()).unwrap();
+        LiveConfig::new(config).with_file(Some(file.to_path_buf()))
+    }
+
+    #[test]
+    fn test_reloadable_change_is_applied() {
+        let file = write_toml(RUNNING);
+        let config = live(&file);
+
+        std::fs::write(
+            &file,
+            RUNNING.replace("old-password", "new-password") + "\n[features]\nparent_portal = true\n",
+        )
+        .unwrap();
+        let report = config.reload_from(&vars(&[])).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(report.applied, vec!["features.parent_portal", "mail.password"]);
+        assert!(report.ignored.is_empty());
+        assert_eq!(config.current().mail.password.as_deref(), Some("new-password"));
+        assert!(config.current().features.parent_portal);
+        assert!(!format!("{:?}", report).contains("new-password"));
+    }
+
+    #[test]
+    fn test_immutable_change_is_ignored() {
+        let file = write_toml(RUNNING);
+        let config = live(&file);
+
+        let report = config
+            .reload_from(&vars(&[("SAI__SERVER__PORT", "9090"), ("SAI__MAIL__PORT", "2525")]))
+            .unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert_eq!(report.applied, vec!["mail.port"]);
+        assert_eq!(report.ignored, vec!["server.port"]);
+        assert_eq!(config.current().server.port, 8080);
+        assert_eq!(config.current().mail.port, Some(2525));
+    }
+
+    #[test]
+    fn test_restart_only_mail_key_is_ignored() {
+        let file = write_toml(RUNNING);
+        let config = live(&file);
+
+        let report = config.reload_from(&vars(&[("SAI__MAIL__LOWERCASE_LOCAL_PART", "false")])).unwrap();
+        std::fs::remove_file(&file).unwrap();
+
+        assert!(report.applied.is_empty());
+        assert_eq!(report.ignored, vec!["mail.lowercase_local_part"]);
+        assert!(config.current().mail.lowercase_local_part);
+    }
+
+    #[test]
+    fn test_malformed_file_keeps_running_config() {
+        let file = write_toml(RUNNING);
+        let config = live(&file);
+        let before = config.current();
+
+        std::fs::write(&file, "[mail\nhost = ").unwrap();
+        let malformed = config.reload_from(&vars(&[]));
+        std::fs::write(&file, RUNNING.replace("noreply@example.com", "not an address")).unwrap();
+        let invalid = config.reload_from(&vars(&[]));
+        std::fs::remove_file(&file).unwrap();
+
+        assert!(matches!(malformed, Err(ReloadError::Load(_))));
+        assert!(matches!(invalid, Err(ReloadError::Invalid(ref report)) if report.has("mail.from")));
+        assert_eq!(*config.current(), *before);
+    }
+}
@@ -0,0 +1,265 @@
+//! `[server]` section: HTTP listener, request limits, connection tuning and
+//! public URL.
+//!
+//! Merges the former standalone `configs/server.rs`, which read its own
+//! `SERVER_*` variables; those variables now go through the layered
+//! configuration like every other historical variable.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::string_or_list;
+
+/// `[server]`: HTTP listener and public URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// `SERVER_HOST` (`HOST` is a deprecated alias)
+    pub host: String,
+    /// `SERVER_PORT` (`PORT` is a deprecated alias)
+    pub port: u16,
+    /// `APP_BASE_URL`: public URL of the API, used in the links sent by email
+    pub base_url: String,
+    /// `INSTITUTION_NAME`: name of the institution shown by `GET /auth/me`
+    pub institution_name: Option<String>,
+    /// `CORS_ALLOWED_ORIGINS`: a TOML list, or comma-separated in the environment
+    #[serde(deserialize_with = "string_or_list")]
+    pub cors_allowed_origins: Vec<String>,
+    /// Internal error messages in API responses; defaults to the profile's
+    pub verbose_errors: Option<bool>,
+    /// `SERVER_WORKERS`: worker threads; unset starts one per physical CPU
+    pub workers: Option<usize>,
+    /// `SERVER_MAX_PAYLOAD_SIZE`: largest JSON or form body accepted, in bytes
+    pub max_payload_size: usize,
+    /// `SERVER_CLIENT_REQUEST_TIMEOUT` (or `SERVER_TIMEOUT`): time a client has to
+    /// send the request headers, in seconds
+    pub timeout_secs: u64,
+    /// `SERVER_ENABLE_COMPRESSION`: compress responses when the client accepts it
+    pub enable_compression: bool,
+    /// `SERVER_KEEP_ALIVE`: idle time before a kept-alive connection is closed,
+    /// in seconds; 0 closes each connection after its response
+    pub keep_alive_secs: u64,
+    /// `SERVER_CLIENT_DISCONNECT_TIMEOUT`: time given to a connection to shut
+    /// down before it is dropped, in seconds; 0 disables it
+    pub client_disconnect_timeout_secs: u64,
+    /// `SERVER_BACKLOG`: connections waiting to be accepted before new ones are refused
+    pub backlog: u32,
+    /// `SERVER_MAX_CONNECTIONS`: concurrent connections per worker
+    pub max_connections: usize,
+    /// `SERVER_LB_IDLE_TIMEOUT`: idle timeout of the load balancer in front, in
+    /// seconds; only used to check that `keep_alive_secs` stays below it
+    pub lb_idle_timeout_secs: Option<u64>,
+    /// `SERVER_SOCKET_PATH`: unix domain socket to listen on, for a reverse
+    /// proxy on the same host; the TCP port is then skipped unless `listen_tcp` is set
+    pub socket_path: Option<PathBuf>,
+    /// `SERVER_SOCKET_MODE`: permission bits of the socket file, in octal
+    pub socket_mode: String,
+    /// `SERVER_LISTEN_TCP`: listen on `host:port` too when `socket_path` is set;
+    /// unset listens on TCP only without a socket
+    pub listen_tcp: Option<bool>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".to_string(),
+            port: 8080,
+            base_url: "http://localhost:8080".to_string(),
+            institution_name: None,
+            cors_allowed_origins: Vec::new(),
+            verbose_errors: None,
+            workers: None,
+            max_payload_size: 2 * 1024 * 1024,
+            timeout_secs: 30,
+            enable_compression: true,
+            keep_alive_secs: 5,
+            client_disconnect_timeout_secs: 1,
+            backlog: 1024,
+            max_connections: 25_000,
+            lb_idle_timeout_secs: None,
+            socket_path: None,
+            socket_mode: "660".to_string(),
+            listen_tcp: None,
+        }
+    }
+}
+
+/// Connection settings handed to the `HttpServer` builder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerTuning {
+    /// `None` disables keep-alive
+    pub keep_alive: Option<Duration>,
+    pub client_request_timeout: Duration,
+    pub client_disconnect_timeout: Duration,
+    pub backlog: u32,
+    /// Per worker
+    pub max_connections: usize,
+    /// `None` starts one worker per physical CPU
+    pub workers: Option<usize>,
+}
+
+impl fmt::Display for ServerTuning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.workers {
+            Some(workers) => write!(f, "{} workers", workers)?,
+            None => write!(f, "un worker por CPU")?,
+        }
+        match self.keep_alive {
+            Some(keep_alive) => write!(f, ", keep-alive {} s", keep_alive.as_secs())?,
+            None => write!(f, ", sin keep-alive")?,
+        }
+        write!(
+            f,
+            ", {} s para recibir los encabezados, {} s para cerrar la conexión, backlog {}, hasta {} conexiones por worker",
+            self.client_request_timeout.as_secs(),
+            self.client_disconnect_timeout.as_secs(),
+            self.backlog,
+            self.max_connections
+        )
+    }
+}
+
+impl ServerConfig {
+    /// Address the HTTP server binds to (`host:port`)
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.host, self.port)
+    }
+
+    /// Whether the server binds `host:port`
+    pub fn listens_on_tcp(&self) -> bool {
+        self.listen_tcp.unwrap_or(self.socket_path.is_none())
+    }
+
+    /// `socket_mode` as permission bits; accepts `660`, `0660` and `0o660`
+    pub fn socket_permissions(&self) -> Result<u32, String> {
+        let digits = self.socket_mode.trim();
+        let digits = digits.strip_prefix("0o").unwrap_or(digits);
+        u32::from_str_radix(digits, 8)
+            .ok()
+            .filter(|mode| *mode <= 0o777)
+            .ok_or_else(|| format!("{:?} no son permisos en octal, como 660", self.socket_mode))
+    }
+
+    /// Where the server listens, for the log: `http://host:port` and `unix:path`
+    pub fn endpoints(&self) -> Vec<String> {
+        let mut endpoints = Vec::new();
+        if let Some(path) = &self.socket_path {
+            endpoints.push(format!("unix:{}", path.display()));
+        }
+        if self.listens_on_tcp() {
+            endpoints.push(format!("http://{}", self.address()));
+        }
+        endpoints
+    }
+
+    /// Keep-alive, timeouts and connection limits to apply to the server
+    pub fn tuning(&self) -> ServerTuning {
+        ServerTuning {
+            keep_alive: (self.keep_alive_secs > 0).then(|| Duration::from_secs(self.keep_alive_secs)),
+            client_request_timeout: Duration::from_secs(self.timeout_secs),
+            client_disconnect_timeout: Duration::from_secs(self.client_disconnect_timeout_secs),
+            backlog: self.backlog,
+            max_connections: self.max_connections,
+            workers: self.workers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = ServerConfig::default();
+
+        assert_eq!(config.address(), "127.0.0.1:8080");
+        assert_eq!(config.max_payload_size, 2 * 1024 * 1024);
+        assert_eq!(config.timeout_secs, 30);
+        assert_eq!(config.workers, None);
+        assert!(config.enable_compression);
+    }
+
+    #[test]
+    fn test_address() {
+        let config = ServerConfig { host: "0.0.0.0".to_string(), port: 9090, ..Default::default() };
+
+        assert_eq!(config.address(), "0.0.0.0:9090");
+    }
+
+    #[test]
+    fn test_socket_skips_tcp_unless_requested() {
+        let mut config = ServerConfig::default();
+        assert!(config.listens_on_tcp());
+        assert_eq!(config.endpoints(), vec!["http://127.0.0.1:8080"]);
+
+        config.socket_path = Some(PathBuf::from("/run/sai/sai.sock"));
+        assert!(!config.listens_on_tcp());
+        assert_eq!(config.endpoints(), vec!["unix:/run/sai/sai.sock"]);
+
+        config.listen_tcp = Some(true);
+        assert_eq!(config.endpoints(), vec!["unix:/run/sai/sai.sock", "http://127.0.0.1:8080"]);
+    }
+
+    #[test]
+    fn test_socket_mode_is_octal() {
+        let mode = |value: &str| ServerConfig { socket_mode: value.to_string(), ..Default::default() }.socket_permissions();
+
+        assert_eq!(ServerConfig::default().socket_permissions(), Ok(0o660));
+        assert_eq!(mode("0600"), Ok(0o600));
+        assert_eq!(mode("0o666"), Ok(0o666));
+        assert!(mode("680").is_err());
+        assert!(mode("1777").is_err());
+        assert!(mode("rw-rw----").is_err());
+    }
+
+    #[test]
+    fn test_defaults_match_actix() {
+        let tuning = ServerConfig::default().tuning();
+
+        assert_eq!(tuning.keep_alive, Some(Duration::from_secs(5)));
+        assert_eq!(tuning.client_disconnect_timeout, Duration::from_secs(1));
+        assert_eq!(tuning.backlog, 1024);
+        assert_eq!(tuning.max_connections, 25_000);
+    }
+
+    #[test]
+    fn test_tuning_maps_every_setting() {
+        let config = ServerConfig {
+            workers: Some(4),
+            timeout_secs: 10,
+            keep_alive_secs: 55,
+            client_disconnect_timeout_secs: 3,
+            backlog: 256,
+            max_connections: 2_000,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.tuning(),
+            ServerTuning {
+                keep_alive: Some(Duration::from_secs(55)),
+                client_request_timeout: Duration::from_secs(10),
+                client_disconnect_timeout: Duration::from_secs(3),
+                backlog: 256,
+                max_connections: 2_000,
+                workers: Some(4),
+            }
+        );
+        assert_eq!(
+            config.tuning().to_string(),
+            "4 workers, keep-alive 55 s, 10 s para recibir los encabezados, 3 s para cerrar la conexión, backlog 256, hasta 2000 conexiones por worker"
+        );
+    }
+
+    #[test]
+    fn test_zero_keep_alive_disables_it() {
+        let tuning = ServerConfig { keep_alive_secs: 0, ..Default::default() }.tuning();
+
+        assert_eq!(tuning.keep_alive, None);
+        assert!(tuning.to_string().starts_with("un worker por CPU, sin keep-alive"));
+    }
+}
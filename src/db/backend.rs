@@ -0,0 +1,126 @@
+//! Database backends and the features each one supports.
+//!
+//! Postgres is the default and supports everything. SQLite (cargo feature
+//! `db-sqlite`) serves single-laptop installations: it covers the core tables
+//! through `db::sqlite` and lacks the Postgres-only features listed in
+//! [`Capabilities`]. Code that relies on one of them checks
+//! [`capabilities()`] and falls back instead of failing.
+
+use std::sync::OnceLock;
+
+/// Database engine behind `DATABASE_URL`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Postgres,
+    Sqlite,
+}
+
+/// Postgres-only features, as available on a backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// `pg_trgm` similarity; without it similar-course search compares trigrams in Rust
+    pub trigram_similarity: bool,
+    /// `SELECT ... FOR UPDATE`; SQLite serializes writers instead
+    pub row_locks: bool,
+    /// `LISTEN`/`NOTIFY`; without it the cross-instance bridge is not started
+    pub listen_notify: bool,
+    /// Server-side `statement_timeout`
+    pub statement_timeouts: bool,
+    /// Schemas, needed for schema-per-tenant deployments
+    pub schemas: bool,
+}
+
+/// Error resolving the backend of a connection URL
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum BackendError {
+    #[error("Unsupported database URL scheme {0:?} (expected postgres:// or sqlite:)")]
+    UnknownScheme(String),
+    #[error("SQLite support is not compiled in; rebuild with --features db-sqlite")]
+    SqliteNotCompiled,
+}
+
+static ACTIVE: OnceLock<Backend> = OnceLock::new();
+
+impl Backend {
+    /// Backend of a connection URL, e.g. `postgres://...` or `sqlite://sai.db`
+    pub fn from_url(url: &str) -> Result<Self, BackendError> {
+        let scheme = url.split(':').next().unwrap_or_default();
+        match scheme {
+            "postgres" | "postgresql" => Ok(Backend::Postgres),
+            "sqlite" if cfg!(feature = "db-sqlite") => Ok(Backend::Sqlite),
+            "sqlite" => Err(BackendError::SqliteNotCompiled),
+            other => Err(BackendError::UnknownScheme(other.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Backend::Postgres => "postgres",
+            Backend::Sqlite => "sqlite",
+        }
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        match self {
+            Backend::Postgres => Capabilities {
+                trigram_similarity: true,
+                row_locks: true,
+                listen_notify: true,
+                statement_timeouts: true,
+                schemas: true,
+            },
+            Backend::Sqlite => Capabilities {
+                trigram_similarity: false,
+                row_locks: false,
+                listen_notify: false,
+                statement_timeouts: false,
+                schemas: false,
+            },
+        }
+    }
+}
+
+/// Record the backend the process runs on; only the first call has an effect
+pub fn set_active(backend: Backend) {
+    let _ = ACTIVE.set(backend);
+}
+
+/// Backend the process runs on; Postgres until [`set_active`] says otherwise
+pub fn active() -> Backend {
+    ACTIVE.get().copied().unwrap_or(Backend::Postgres)
+}
+
+/// Capabilities of the active backend
+pub fn capabilities() -> Capabilities {
+    active().capabilities()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_from_url() {
+        assert_eq!(Backend::from_url("postgres://sai@localhost/sai"), Ok(Backend::Postgres));
+        assert_eq!(Backend::from_url("postgresql://localhost"), Ok(Backend::Postgres));
+        assert_eq!(
+            Backend::from_url("mysql://localhost/sai"),
+            Err(BackendError::UnknownScheme("mysql".to_string()))
+        );
+
+        let sqlite = Backend::from_url("sqlite://sai.db");
+        if cfg!(feature = "db-sqlite") {
+            assert_eq!(sqlite, Ok(Backend::Sqlite));
+        } else {
+            assert_eq!(sqlite, Err(BackendError::SqliteNotCompiled));
+        }
+    }
+
+    #[test]
+    fn test_sqlite_lacks_postgres_only_features() {
+        let sqlite = Backend::Sqlite.capabilities();
+
+        assert!(!sqlite.trigram_similarity && !sqlite.listen_notify && !sqlite.schemas);
+        assert!(Backend::Postgres.capabilities().trigram_similarity);
+    }
+}
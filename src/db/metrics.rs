@@ -0,0 +1,400 @@
+//! Connection pool metrics: pool occupancy and how long callers wait for a connection,
+//! plus per-operation query durations.
+//!
+//! sqlx 0.7 has no hook around `Pool::acquire`, so waits are measured by [`acquire`]
+//! and [`begin`], which `tx!` and the services' explicit transactions go through.
+//! Queries that hand `&PgPool` straight to sqlx are not timed unless they are wrapped
+//! in [`timed_query`]; sqlx's own slow-statement warning (above one second) still covers them.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use log::warn;
+use serde::Serialize;
+use sqlx::{pool::PoolConnection, Error as SqlxError, Postgres, Transaction};
+use tracing::Instrument;
+
+use super::DbPool;
+
+/// Upper bounds (in milliseconds) of the duration histogram buckets
+pub const BUCKETS_MS: [u64; 11] = [1, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+/// Threshold used when `DATABASE_SLOW_ACQUIRE_MS` is not set
+pub const DEFAULT_SLOW_ACQUIRE_THRESHOLD: Duration = Duration::from_millis(500);
+
+static ACQUIRE_WAITS: LazyLock<DurationHistogram> = LazyLock::new(DurationHistogram::default);
+
+static SLOW_ACQUIRE_THRESHOLD_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_ACQUIRE_THRESHOLD.as_millis() as u64);
+
+/// Threshold used when `DATABASE_SLOW_QUERY_MS` is not set
+pub const DEFAULT_SLOW_QUERY_THRESHOLD: Duration = Duration::from_millis(1000);
+
+static QUERY_DURATIONS: LazyLock<Mutex<BTreeMap<&'static str, Arc<DurationHistogram>>>> =
+    LazyLock::new(Default::default);
+
+static SLOW_QUERY_THRESHOLD_MS: AtomicU64 = AtomicU64::new(DEFAULT_SLOW_QUERY_THRESHOLD.as_millis() as u64);
+
+/// Histogram of durations, with cumulative bucket counts like Prometheus
+#[derive(Debug, Default)]
+pub struct DurationHistogram {
+    /// One counter per entry in `BUCKETS_MS`; durations above the last bound only count in `count`
+    buckets: [AtomicU64; BUCKETS_MS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+    slow: AtomicU64,
+}
+
+impl DurationHistogram {
+    /// Record one duration; `slow` marks it as above the warning threshold
+    pub fn observe(&self, duration: Duration, slow: bool) {
+        let millis = duration.as_secs_f64() * 1000.0;
+        for (bound, bucket) in BUCKETS_MS.iter().zip(&self.buckets) {
+            if millis <= *bound as f64 {
+                bucket.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros.fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        if slow {
+            self.slow.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> DurationSnapshot {
+        DurationSnapshot {
+            count: self.count.load(Ordering::Relaxed),
+            total_ms: self.sum_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+            slow: self.slow.load(Ordering::Relaxed),
+            buckets: BUCKETS_MS
+                .iter()
+                .zip(&self.buckets)
+                .map(|(le_ms, bucket)| DurationBucket { le_ms: *le_ms, count: bucket.load(Ordering::Relaxed) })
+                .collect(),
+        }
+    }
+}
+
+/// Durations recorded so far
+#[derive(Debug, Clone, Serialize)]
+pub struct DurationSnapshot {
+    pub count: u64,
+    pub total_ms: f64,
+    /// Durations above the slow threshold
+    pub slow: u64,
+    pub buckets: Vec<DurationBucket>,
+}
+
+/// Number of durations of at most `le_ms` milliseconds
+#[derive(Debug, Clone, Serialize)]
+pub struct DurationBucket {
+    pub le_ms: u64,
+    pub count: u64,
+}
+
+/// Pool occupancy plus the acquire-wait histogram
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub max_connections: u32,
+    pub acquire_wait: DurationSnapshot,
+}
+
+/// Current stats of `pool`
+pub fn pool_stats(pool: &DbPool) -> PoolStats {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+
+    PoolStats {
+        size,
+        idle,
+        in_use: size.saturating_sub(idle),
+        max_connections: pool.options().get_max_connections(),
+        acquire_wait: ACQUIRE_WAITS.snapshot(),
+    }
+}
+
+/// Waits longer than this are logged as warnings; zero disables the warning
+pub fn set_slow_acquire_threshold(threshold: Duration) {
+    SLOW_ACQUIRE_THRESHOLD_MS.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+pub fn slow_acquire_threshold() -> Duration {
+    Duration::from_millis(SLOW_ACQUIRE_THRESHOLD_MS.load(Ordering::Relaxed))
+}
+
+/// `pool.acquire()`, recording how long it waited.
+///
+/// Failed acquires are recorded too: a timeout is the longest wait of all.
+pub async fn acquire(pool: &DbPool) -> Result<PoolConnection<Postgres>, SqlxError> {
+    let started = Instant::now();
+    let result = pool.acquire().await;
+    record_acquire(pool, started.elapsed());
+    result
+}
+
+/// `pool.begin()` on a connection obtained through [`acquire`]
+pub async fn begin(pool: &DbPool) -> Result<Transaction<'static, Postgres>, SqlxError> {
+    let conn = acquire(pool).await?;
+    Transaction::begin(conn).await
+}
+
+fn record_acquire(pool: &DbPool, wait: Duration) {
+    let threshold = slow_acquire_threshold();
+    let slow = !threshold.is_zero() && wait > threshold;
+    ACQUIRE_WAITS.observe(wait, slow);
+
+    if slow {
+        warn!(
+            "Waited {} ms for a database connection (threshold {} ms; pool size {}, idle {}, max {})",
+            wait.as_millis(),
+            threshold.as_millis(),
+            pool.size(),
+            pool.num_idle(),
+            pool.options().get_max_connections()
+        );
+    }
+}
+
+/// Durations of the queries run through [`timed_query`] under one operation name
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryStats {
+    pub operation: &'static str,
+    pub durations: DurationSnapshot,
+}
+
+/// Queries slower than this are logged as warnings; zero disables the warning
+pub fn set_slow_query_threshold(threshold: Duration) {
+    SLOW_QUERY_THRESHOLD_MS.store(threshold.as_millis() as u64, Ordering::Relaxed);
+}
+
+pub fn slow_query_threshold() -> Duration {
+    Duration::from_millis(SLOW_QUERY_THRESHOLD_MS.load(Ordering::Relaxed))
+}
+
+/// Runs `query` inside a `db.query` span tagged with `operation` (e.g. `students.find_all`)
+/// and records its duration under that operation.
+///
+/// `sql` is only used for the slow-query warning, with its literals redacted. Failed
+/// queries are recorded too.
+pub async fn timed_query<T, E, F>(operation: &'static str, sql: &str, query: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    let span = tracing::debug_span!("db.query", operation);
+    let started = Instant::now();
+    let result = query.instrument(span).await;
+    record_query(operation, sql, started.elapsed());
+    result
+}
+
+/// Per-operation query durations, sorted by operation
+pub fn query_stats() -> Vec<QueryStats> {
+    let histograms = QUERY_DURATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    histograms
+        .iter()
+        .map(|(operation, histogram)| QueryStats { operation, durations: histogram.snapshot() })
+        .collect()
+}
+
+fn record_query(operation: &'static str, sql: &str, duration: Duration) {
+    let threshold = slow_query_threshold();
+    let slow = !threshold.is_zero() && duration > threshold;
+    // The lock only guards the map; observing happens outside of it
+    let histogram = {
+        let mut histograms = QUERY_DURATIONS.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(histograms.entry(operation).or_default())
+    };
+    histogram.observe(duration, slow);
+
+    if slow {
+        warn!(
+            "Slow query {} took {} ms (threshold {} ms): {}",
+            operation,
+            duration.as_millis(),
+            threshold.as_millis(),
+            redact_sql(sql)
+        );
+    }
+}
+
+/// `sql` on one line with its string literals replaced by `'?'`.
+///
+/// Bound values never appear in the SQL text; literals written into it (e.g. by
+/// dynamically built filters) might carry personal data.
+pub fn redact_sql(sql: &str) -> String {
+    let mut out = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut in_space = false;
+
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            // A doubled quote is an escaped quote inside the literal
+            loop {
+                match chars.next() {
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                    }
+                    Some('\'') | None => break,
+                    Some(_) => {}
+                }
+            }
+            out.push_str("'?'");
+            in_space = false;
+        } else if c.is_whitespace() {
+            if !in_space && !out.is_empty() {
+                out.push(' ');
+            }
+            in_space = true;
+        } else {
+            out.push(c);
+            in_space = false;
+        }
+    }
+
+    out.truncate(out.trim_end().len());
+    out
+}
+
+/// Stats in the Prometheus text exposition format
+pub fn render_prometheus(stats: &PoolStats) -> String {
+    let mut out = String::new();
+    let wait = &stats.acquire_wait;
+
+    // Writing to a String cannot fail
+    let _ = writeln!(out, "# HELP sai_db_pool_connections Open connections in the pool by state");
+    let _ = writeln!(out, "# TYPE sai_db_pool_connections gauge");
+    let _ = writeln!(out, "sai_db_pool_connections{{state=\"idle\"}} {}", stats.idle);
+    let _ = writeln!(out, "sai_db_pool_connections{{state=\"in_use\"}} {}", stats.in_use);
+    let _ = writeln!(out, "# HELP sai_db_pool_max_connections Configured maximum pool size");
+    let _ = writeln!(out, "# TYPE sai_db_pool_max_connections gauge");
+    let _ = writeln!(out, "sai_db_pool_max_connections {}", stats.max_connections);
+    let _ = writeln!(out, "# HELP sai_db_pool_acquire_wait_seconds Time spent waiting for a pooled connection");
+    let _ = writeln!(out, "# TYPE sai_db_pool_acquire_wait_seconds histogram");
+    for bucket in &wait.buckets {
+        let _ = writeln!(
+            out,
+            "sai_db_pool_acquire_wait_seconds_bucket{{le=\"{}\"}} {}",
+            bucket.le_ms as f64 / 1000.0,
+            bucket.count
+        );
+    }
+    let _ = writeln!(out, "sai_db_pool_acquire_wait_seconds_bucket{{le=\"+Inf\"}} {}", wait.count);
+    let _ = writeln!(out, "sai_db_pool_acquire_wait_seconds_sum {}", wait.total_ms / 1000.0);
+    let _ = writeln!(out, "sai_db_pool_acquire_wait_seconds_count {}", wait.count);
+    let _ = writeln!(out, "# HELP sai_db_pool_slow_acquires_total Acquire waits above the slow-acquire threshold");
+    let _ = writeln!(out, "# TYPE sai_db_pool_slow_acquires_total counter");
+    let _ = writeln!(out, "sai_db_pool_slow_acquires_total {}", wait.slow);
+    out
+}
+
+/// Query durations in the Prometheus text exposition format
+pub fn render_query_prometheus(stats: &[QueryStats]) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# HELP sai_db_query_duration_seconds Duration of timed queries by operation");
+    let _ = writeln!(out, "# TYPE sai_db_query_duration_seconds histogram");
+    for query in stats {
+        let durations = &query.durations;
+        for bucket in &durations.buckets {
+            let _ = writeln!(
+                out,
+                "sai_db_query_duration_seconds_bucket{{operation=\"{}\",le=\"{}\"}} {}",
+                query.operation,
+                bucket.le_ms as f64 / 1000.0,
+                bucket.count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "sai_db_query_duration_seconds_bucket{{operation=\"{}\",le=\"+Inf\"}} {}",
+            query.operation, durations.count
+        );
+        let _ = writeln!(
+            out,
+            "sai_db_query_duration_seconds_sum{{operation=\"{}\"}} {}",
+            query.operation,
+            durations.total_ms / 1000.0
+        );
+        let _ = writeln!(out, "sai_db_query_duration_seconds_count{{operation=\"{}\"}} {}", query.operation, durations.count);
+    }
+    let _ = writeln!(out, "# HELP sai_db_slow_queries_total Timed queries above the slow-query threshold");
+    let _ = writeln!(out, "# TYPE sai_db_slow_queries_total counter");
+    for query in stats {
+        let _ = writeln!(out, "sai_db_slow_queries_total{{operation=\"{}\"}} {}", query.operation, query.durations.slow);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let histogram = DurationHistogram::default();
+
+        histogram.observe(Duration::from_micros(500), false);
+        histogram.observe(Duration::from_millis(30), false);
+        histogram.observe(Duration::from_secs(10), true);
+
+        let snapshot = histogram.snapshot();
+        let count = |le_ms| snapshot.buckets.iter().find(|b| b.le_ms == le_ms).unwrap().count;
+        assert_eq!(count(1), 1);
+        assert_eq!(count(25), 1);
+        assert_eq!(count(50), 2);
+        assert_eq!(count(5000), 2);
+        assert_eq!(snapshot.count, 3);
+        assert_eq!(snapshot.slow, 1);
+        assert_eq!(snapshot.total_ms, 10_030.5);
+    }
+
+    #[test]
+    fn test_prometheus_rendering() {
+        let histogram = DurationHistogram::default();
+        histogram.observe(Duration::from_millis(3), false);
+        let stats = PoolStats { size: 4, idle: 1, in_use: 3, max_connections: 10, acquire_wait: histogram.snapshot() };
+
+        let text = render_prometheus(&stats);
+
+        assert!(text.contains("sai_db_pool_connections{state=\"in_use\"} 3\n"));
+        assert!(text.contains("sai_db_pool_max_connections 10\n"));
+        assert!(text.contains("sai_db_pool_acquire_wait_seconds_bucket{le=\"0.001\"} 0\n"));
+        assert!(text.contains("sai_db_pool_acquire_wait_seconds_bucket{le=\"0.005\"} 1\n"));
+        assert!(text.contains("sai_db_pool_acquire_wait_seconds_bucket{le=\"+Inf\"} 1\n"));
+        assert!(text.contains("sai_db_pool_acquire_wait_seconds_sum 0.003\n"));
+    }
+
+    #[test]
+    fn test_sql_literals_are_redacted() {
+        let sql = "SELECT *\n  FROM students\n WHERE section = 'A' AND guardian_info->>'name' ILIKE $1 AND note = 'O''Brien'\n";
+
+        assert_eq!(
+            redact_sql(sql),
+            "SELECT * FROM students WHERE section = '?' AND guardian_info->>'?' ILIKE $1 AND note = '?'"
+        );
+    }
+
+    #[test]
+    fn test_query_prometheus_rendering() {
+        let histogram = DurationHistogram::default();
+        histogram.observe(Duration::from_millis(120), false);
+        histogram.observe(Duration::from_millis(1500), true);
+        let stats = [QueryStats { operation: "students.find_all", durations: histogram.snapshot() }];
+
+        let text = render_query_prometheus(&stats);
+
+        assert!(text.contains("# TYPE sai_db_query_duration_seconds histogram\n"));
+        assert!(text.contains("sai_db_query_duration_seconds_bucket{operation=\"students.find_all\",le=\"0.1\"} 0\n"));
+        assert!(text.contains("sai_db_query_duration_seconds_bucket{operation=\"students.find_all\",le=\"0.25\"} 1\n"));
+        assert!(text.contains("sai_db_query_duration_seconds_bucket{operation=\"students.find_all\",le=\"+Inf\"} 2\n"));
+        assert!(text.contains("sai_db_query_duration_seconds_count{operation=\"students.find_all\"} 2\n"));
+        assert!(text.contains("sai_db_slow_queries_total{operation=\"students.find_all\"} 1\n"));
+    }
+}
@@ -0,0 +1,308 @@
+//! Postgres LISTEN/NOTIFY bridge between application instances.
+//!
+//! Every instance publishes cache invalidations and events with `pg_notify`
+//! and runs one listener that receives what any instance publishes, itself
+//! included. When the listener's connection drops it reconnects and
+//! subscribes again; notifications sent while it is disconnected are lost, so
+//! the bridge only carries data that can be recomputed or is best-effort.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, OnceLock};
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use sqlx::postgres::{PgListener, PgNotification, PgPoolOptions};
+use sqlx::Error as SqlxError;
+use uuid::Uuid;
+
+use super::{retry_with_backoff, DbPool, RetryPolicy};
+
+/// Channel for cache invalidations (`CacheInvalidation` payloads)
+pub const CACHE_INVALIDATION_CHANNEL: &str = "sai_cache_invalidation";
+
+/// Channel for application events (`Event` payloads)
+pub const EVENTS_CHANNEL: &str = "sai_events";
+
+/// Postgres rejects NOTIFY payloads of 8000 bytes or more
+pub const MAX_PAYLOAD_BYTES: usize = 7999;
+
+/// Identifies this process in the payloads it publishes
+static INSTANCE_ID: LazyLock<Uuid> = LazyLock::new(Uuid::new_v4);
+
+/// Pool used by `publish_in_background`, set once at startup
+static PUBLISHER: OnceLock<DbPool> = OnceLock::new();
+
+#[derive(Debug, thiserror::Error)]
+pub enum PubSubError {
+    #[error("Notification payload of {size} bytes exceeds the {max} byte limit")]
+    PayloadTooLarge { size: usize, max: usize },
+    #[error("Failed to serialize notification: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Database error: {0}")]
+    Database(#[from] SqlxError),
+}
+
+/// Cache entry that every instance must drop
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cache", rename_all = "snake_case")]
+pub enum CacheInvalidation {
+    /// Cached principals of a user (`GET /api/auth/me`)
+    Principal { user_id: Uuid },
+    /// Access tokens of a user signed before `token_version`
+    TokenVersion { user_id: Uuid, token_version: i32 },
+    /// Feature flag changed at `PUT /api/admin/features`; `None` restores the configured value
+    FeatureFlag { name: String, enabled: Option<bool> },
+}
+
+/// Application event forwarded to every instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub kind: String,
+    pub data: serde_json::Value,
+}
+
+/// Payload as sent over the channel
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope<T> {
+    origin: Uuid,
+    data: T,
+}
+
+/// Notification received by the listener
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub channel: String,
+    pub data: serde_json::Value,
+    /// Whether this instance published it
+    pub from_self: bool,
+}
+
+impl Notification {
+    fn decode(channel: &str, payload: &str) -> Result<Self, serde_json::Error> {
+        let envelope: Envelope<serde_json::Value> = serde_json::from_str(payload)?;
+
+        Ok(Notification {
+            channel: channel.to_string(),
+            data: envelope.data,
+            from_self: envelope.origin == *INSTANCE_ID,
+        })
+    }
+}
+
+/// Serializes `data` with this instance as origin, checking the payload limit
+pub fn encode_payload<T: Serialize>(data: &T) -> Result<String, PubSubError> {
+    let payload = serde_json::to_string(&Envelope { origin: *INSTANCE_ID, data })?;
+
+    if payload.len() > MAX_PAYLOAD_BYTES {
+        return Err(PubSubError::PayloadTooLarge { size: payload.len(), max: MAX_PAYLOAD_BYTES });
+    }
+
+    Ok(payload)
+}
+
+/// Publishes `data` on `channel` for every listening instance
+///
+/// Oversized payloads are rejected before reaching the database; publish an
+/// identifier and let receivers load the rest instead.
+pub async fn publish<T: Serialize>(pool: &DbPool, channel: &str, data: &T) -> Result<(), PubSubError> {
+    let payload = encode_payload(data)?;
+
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(channel)
+        .bind(payload)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Publishes an application event on `EVENTS_CHANNEL`
+pub async fn publish_event(pool: &DbPool, kind: &str, data: serde_json::Value) -> Result<(), PubSubError> {
+    publish(pool, EVENTS_CHANNEL, &Event { kind: kind.to_string(), data }).await
+}
+
+/// Sets the pool used by `publish_in_background`; later calls are ignored
+pub fn install_publisher(pool: DbPool) {
+    if PUBLISHER.set(pool).is_err() {
+        debug!("Notification publisher already installed");
+    }
+}
+
+/// Publishes from synchronous code (e.g. cache invalidation hooks) without waiting.
+///
+/// Does nothing until `install_publisher` is called, so single-instance
+/// setups and tests keep working without a listener.
+pub fn publish_in_background<T: Serialize>(channel: &'static str, data: &T) {
+    let Some(pool) = PUBLISHER.get() else {
+        return;
+    };
+
+    let payload = match encode_payload(data) {
+        Ok(payload) => payload,
+        Err(e) => {
+            warn!("Not publishing on {}: {}", channel, e);
+            return;
+        }
+    };
+
+    let pool = pool.clone();
+    actix_rt::spawn(async move {
+        let result = sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(channel)
+            .bind(payload)
+            .execute(&pool)
+            .await;
+        if let Err(e) = result {
+            warn!("Failed to publish on {}: {}", channel, e);
+        }
+    });
+}
+
+/// Running listener task
+pub struct ListenerHandle {
+    reconnects: Arc<AtomicU64>,
+    task: actix_rt::task::JoinHandle<()>,
+}
+
+impl ListenerHandle {
+    /// Times the listener reconnected after losing its connection
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Stops listening and waits until the connection is released
+    pub async fn stop(self) {
+        self.task.abort();
+        // The listener must be dropped inside the runtime, so wait for it
+        let _ = self.task.await;
+    }
+}
+
+/// Subscribes to `channels` and calls `handler` for every notification.
+///
+/// Returns once the first subscription succeeded (retried with `policy`).
+/// The listener uses its own connection, not one of `pool`'s, and after a
+/// lost connection it reconnects and subscribes again, retrying with the
+/// policy's delays, starting over each time its attempts run out.
+pub async fn spawn_listener<H>(
+    pool: &DbPool,
+    channels: &[&str],
+    policy: &RetryPolicy,
+    handler: H,
+) -> Result<ListenerHandle, SqlxError>
+where
+    H: Fn(Notification) + Send + Sync + 'static,
+{
+    let listener_pool = PgPoolOptions::new()
+        .max_connections(1)
+        .connect_lazy_with((*pool.connect_options()).clone());
+    let channels: Vec<String> = channels.iter().map(|c| c.to_string()).collect();
+
+    let mut listener = retry_with_backoff(policy, "Subscribing to notifications", || {
+        subscribe(&listener_pool, &channels)
+    })
+    .await?;
+    info!("Listening for notifications on {}", channels.join(", "));
+
+    let reconnects = Arc::new(AtomicU64::new(0));
+    let counter = reconnects.clone();
+    let policy = policy.clone();
+
+    let task = actix_rt::spawn(async move {
+        loop {
+            let lost = match listener.try_recv().await {
+                Ok(Some(notification)) => {
+                    dispatch(&handler, &notification);
+                    false
+                }
+                Ok(None) => {
+                    warn!("Notification listener lost its connection");
+                    true
+                }
+                Err(e) => {
+                    warn!("Notification listener failed: {}", e);
+                    true
+                }
+            };
+            if !lost {
+                continue;
+            }
+
+            // A fresh listener instead of sqlx's implicit reconnect, so a
+            // connection that failed with an error is never reused
+            drop(listener);
+            listener = loop {
+                let resubscribed = retry_with_backoff(&policy, "Resubscribing to notifications", || {
+                    subscribe(&listener_pool, &channels)
+                })
+                .await;
+                if let Ok(listener) = resubscribed {
+                    break listener;
+                }
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+            info!("Notification listener reconnected");
+        }
+    });
+
+    Ok(ListenerHandle { reconnects, task })
+}
+
+async fn subscribe(pool: &DbPool, channels: &[String]) -> Result<PgListener, SqlxError> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen_all(channels.iter().map(String::as_str)).await?;
+    Ok(listener)
+}
+
+fn dispatch<H: Fn(Notification)>(handler: &H, notification: &PgNotification) {
+    match Notification::decode(notification.channel(), notification.payload()) {
+        Ok(notification) => handler(notification),
+        Err(e) => warn!("Ignoring malformed notification on {}: {}", notification.channel(), e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_round_trip_marks_own_messages() {
+        let invalidation = CacheInvalidation::Principal { user_id: Uuid::new_v4() };
+        let payload = encode_payload(&invalidation).unwrap();
+
+        let notification = Notification::decode(CACHE_INVALIDATION_CHANNEL, &payload).unwrap();
+        assert!(notification.from_self);
+        assert_eq!(serde_json::from_value::<CacheInvalidation>(notification.data).unwrap(), invalidation);
+
+        let foreign = format!(r#"{{"origin":"{}","data":{{"kind":"x","data":null}}}}"#, Uuid::new_v4());
+        assert!(!Notification::decode(EVENTS_CHANNEL, &foreign).unwrap().from_self);
+    }
+
+    #[test]
+    fn test_payload_size_limit() {
+        let envelope_size = encode_payload(&"").unwrap().len();
+
+        let fits = "a".repeat(MAX_PAYLOAD_BYTES - envelope_size);
+        assert_eq!(encode_payload(&fits).unwrap().len(), MAX_PAYLOAD_BYTES);
+
+        let too_large = "a".repeat(MAX_PAYLOAD_BYTES - envelope_size + 1);
+        assert!(matches!(
+            encode_payload(&too_large),
+            Err(PubSubError::PayloadTooLarge { size, max: MAX_PAYLOAD_BYTES }) if size == MAX_PAYLOAD_BYTES + 1
+        ));
+    }
+
+    #[test]
+    fn test_cache_invalidation_format() {
+        let user_id = Uuid::new_v4();
+        let json = serde_json::to_value(CacheInvalidation::Principal { user_id }).unwrap();
+
+        assert_eq!(json, serde_json::json!({ "cache": "principal", "user_id": user_id }));
+
+        let json = serde_json::to_value(CacheInvalidation::TokenVersion { user_id, token_version: 3 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "cache": "token_version", "user_id": user_id, "token_version": 3 }));
+
+        let json = serde_json::to_value(CacheInvalidation::FeatureFlag { name: "webhooks".to_string(), enabled: None }).unwrap();
+        assert_eq!(json, serde_json::json!({ "cache": "feature_flag", "name": "webhooks", "enabled": null }));
+    }
+}
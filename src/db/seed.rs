@@ -0,0 +1,656 @@
+//! Seed data for demo and development databases.
+//!
+//! Every row created here is recorded in `seed_rows` under a natural key
+//! (`user:<email>`, `course:<code>`, ...). Seeding skips keys that are already
+//! recorded, so running it twice changes nothing, and [`unseed`] deletes
+//! exactly the recorded rows, newest first, leaving everything else alone.
+//!
+//! The institution itself is configured through the environment
+//! (`INSTITUTION_NAME`, `PROMOTION_*`), not stored, so it is not seeded.
+
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use log::{info, warn};
+use serde::Serialize;
+use sqlx::{Error as SqlxError, Row};
+use uuid::Uuid;
+
+use super::{DbError, DbPool};
+use crate::config::Environment;
+use crate::models::{
+    assessment::{AssessmentType, NewAssessment},
+    authentication::NewAuthentication,
+    course::CreateCourseDto,
+    enrollment::{EnrollmentStatus, NewEnrollment},
+    student::CreateStudentWithUserDto,
+    teacher::CreateTeacherWithUserDto,
+    user::CreateUserDto,
+    Assessment, Authentication, Course, Enrollment, Role, ScheduleSlot, Student, StudentStatus, Teacher,
+    TeacherStatus, User,
+};
+
+/// Password of every seeded account that can log in
+pub const DEMO_PASSWORD: &str = "SaiDemo2025!";
+
+/// Email of the seeded administrator
+pub const ADMIN_EMAIL: &str = "admin@demo.sai.edu.py";
+
+/// Monthly tuition of the demo payment plan, in guaraníes
+const MONTHLY_TUITION: f64 = 350_000.0;
+
+/// Months of the school year billed by the payment plan (February to November)
+const TUITION_MONTHS: std::ops::RangeInclusive<u32> = 2..=11;
+
+/// Which data to seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedSet {
+    /// Only the administrator account
+    Minimal,
+    /// The administrator plus a small school: teachers, students, courses,
+    /// enrollments, assessments and a payment plan
+    DemoSchool,
+}
+
+impl SeedSet {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SeedSet::Minimal => "minimal",
+            SeedSet::DemoSchool => "demo_school",
+        }
+    }
+}
+
+impl FromStr for SeedSet {
+    type Err = SeedError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "minimal" => Ok(SeedSet::Minimal),
+            "demo" | "demo_school" => Ok(SeedSet::DemoSchool),
+            other => Err(SeedError::UnknownSet(other.to_string())),
+        }
+    }
+}
+
+/// Error while seeding or unseeding
+#[derive(Debug, thiserror::Error)]
+pub enum SeedError {
+    #[error("Refusing to seed a production database")]
+    Production,
+    #[error("Unknown seed set {0:?} (expected minimal or demo)")]
+    UnknownSet(String),
+    #[error("Seeding failed: {0}")]
+    Database(#[from] DbError),
+}
+
+impl From<SqlxError> for SeedError {
+    fn from(err: SqlxError) -> Self {
+        SeedError::Database(err.into())
+    }
+}
+
+/// Rows created by one seeding run; all zero when everything was already seeded
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SeedSummary {
+    pub users: usize,
+    pub teachers: usize,
+    pub students: usize,
+    pub classrooms: usize,
+    pub courses: usize,
+    pub enrollments: usize,
+    pub assessments: usize,
+    pub payments: usize,
+}
+
+impl SeedSummary {
+    /// Total number of rows created
+    pub fn total(&self) -> usize {
+        self.users
+            + self.teachers
+            + self.students
+            + self.classrooms
+            + self.courses
+            + self.enrollments
+            + self.assessments
+            + self.payments
+    }
+}
+
+/// Tables seeded rows can live in; `unseed` only deletes from these
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeedTable {
+    Users,
+    Authentications,
+    Teachers,
+    Students,
+    Classrooms,
+    Courses,
+    Enrollments,
+    Assessments,
+    Payments,
+}
+
+impl SeedTable {
+    const ALL: [SeedTable; 9] = [
+        SeedTable::Users,
+        SeedTable::Authentications,
+        SeedTable::Teachers,
+        SeedTable::Students,
+        SeedTable::Classrooms,
+        SeedTable::Courses,
+        SeedTable::Enrollments,
+        SeedTable::Assessments,
+        SeedTable::Payments,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            SeedTable::Users => "users",
+            SeedTable::Authentications => "authentications",
+            SeedTable::Teachers => "teachers",
+            SeedTable::Students => "students",
+            SeedTable::Classrooms => "classrooms",
+            SeedTable::Courses => "courses",
+            SeedTable::Enrollments => "enrollments",
+            SeedTable::Assessments => "assessments",
+            SeedTable::Payments => "payments",
+        }
+    }
+
+    /// Column holding the recorded id; students and teachers are keyed by their user
+    fn key_column(&self) -> &'static str {
+        match self {
+            SeedTable::Teachers | SeedTable::Students => "user_id",
+            _ => "id",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|table| table.name() == name)
+    }
+}
+
+/// Refuse to seed production, where the known demo password would be a back door
+pub fn ensure_allowed(environment: Environment) -> Result<(), SeedError> {
+    if environment.profile().allow_demo_seed {
+        Ok(())
+    } else {
+        Err(SeedError::Production)
+    }
+}
+
+/// Id recorded under `key`, if that row was already seeded
+async fn seeded(pool: &DbPool, key: &str) -> Result<Option<Uuid>, SqlxError> {
+    sqlx::query_scalar("SELECT row_id FROM seed_rows WHERE seed_key = $1")
+        .bind(key)
+        .fetch_optional(pool)
+        .await
+}
+
+async fn record(pool: &DbPool, set: SeedSet, key: &str, table: SeedTable, id: Uuid) -> Result<(), SqlxError> {
+    sqlx::query("INSERT INTO seed_rows (seed, seed_key, table_name, row_id) VALUES ($1, $2, $3, $4)")
+        .bind(set.as_str())
+        .bind(key)
+        .bind(table.name())
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Seed `set`; see [`seed_minimal`] and [`seed_demo_school`]
+pub async fn seed(pool: &DbPool, set: SeedSet) -> Result<SeedSummary, SeedError> {
+    match set {
+        SeedSet::Minimal => seed_minimal(pool).await,
+        SeedSet::DemoSchool => seed_demo_school(pool).await,
+    }
+}
+
+/// Seed the administrator account (`ADMIN_EMAIL` / `DEMO_PASSWORD`)
+pub async fn seed_minimal(pool: &DbPool) -> Result<SeedSummary, SeedError> {
+    let mut summary = SeedSummary::default();
+    seed_admin(pool, SeedSet::Minimal, &mut summary).await?;
+
+    info!("Seeded {} row(s) (minimal)", summary.total());
+    Ok(summary)
+}
+
+/// Seed the administrator and a small demo school.
+///
+/// Three teachers each teach one 7th-grade course with a weekly schedule in
+/// its own classroom; six students are enrolled in every course, have one
+/// graded exam per course and a monthly tuition plan from February to November.
+pub async fn seed_demo_school(pool: &DbPool) -> Result<SeedSummary, SeedError> {
+    let set = SeedSet::DemoSchool;
+    let mut summary = SeedSummary::default();
+    let year = Utc::now().year();
+
+    seed_admin(pool, SeedSet::Minimal, &mut summary).await?;
+
+    let mut courses = Vec::new();
+    for (index, teacher) in DEMO_TEACHERS.iter().enumerate() {
+        let teacher_id = seed_teacher(pool, set, teacher, &mut summary).await?;
+        let classroom = format!("Aula {}", index + 1);
+        seed_classroom(pool, set, &classroom, &mut summary).await?;
+        let course_id = seed_course(pool, set, teacher, teacher_id, &classroom, year, &mut summary).await?;
+        courses.push(course_id);
+    }
+
+    for (index, student) in DEMO_STUDENTS.iter().enumerate() {
+        let enrollment_number = format!("DEMO-{}-{:03}", year, index + 1);
+        let student_id = seed_student(pool, set, student, &enrollment_number, year, &mut summary).await?;
+
+        for (course_index, course_id) in courses.iter().enumerate() {
+            let key = format!("enrollment:{}:{}", enrollment_number, DEMO_TEACHERS[course_index].course_code);
+            let enrollment_id = match seeded(pool, &key).await? {
+                Some(id) => id,
+                None => {
+                    let enrollment = Enrollment::create(
+                        pool,
+                        &NewEnrollment {
+                            student_id,
+                            course_id: *course_id,
+                            academic_year: year,
+                            status: Some(EnrollmentStatus::Active),
+                            notes: None,
+                            payment_info: None,
+                        },
+                    )
+                    .await?;
+                    record(pool, set, &key, SeedTable::Enrollments, enrollment.id).await?;
+                    summary.enrollments += 1;
+                    enrollment.id
+                }
+            };
+
+            let key = format!("assessment:{}:{}", enrollment_number, DEMO_TEACHERS[course_index].course_code);
+            if seeded(pool, &key).await?.is_none() {
+                let assessment = Assessment::create(
+                    pool,
+                    NewAssessment {
+                        enrollment_id,
+                        course_id: *course_id,
+                        assessment_type: AssessmentType::Exam,
+                        title: "Primer examen parcial".to_string(),
+                        description: None,
+                        // Spread the grades so reports have something to show
+                        score: 55.0 + ((index * 7 + course_index * 11) % 45) as f64,
+                        max_score: 100.0,
+                        weight: 0.3,
+                        assessment_date: date_in(year, 4, 15),
+                        is_final: false,
+                        period: 1,
+                        comments: None,
+                    },
+                )
+                .await?;
+                record(pool, set, &key, SeedTable::Assessments, assessment.id).await?;
+                summary.assessments += 1;
+            }
+        }
+
+        for month in TUITION_MONTHS {
+            let key = format!("payment:{}:{:02}", enrollment_number, month);
+            if seeded(pool, &key).await?.is_none() {
+                let id = insert_tuition(pool, student_id, year, month).await?;
+                record(pool, set, &key, SeedTable::Payments, id).await?;
+                summary.payments += 1;
+            }
+        }
+    }
+
+    info!("Seeded {} row(s) (demo school)", summary.total());
+    Ok(summary)
+}
+
+/// Delete every seeded row, newest first so children go before their parents.
+///
+/// # Returns
+///
+/// The number of rows deleted, not counting `seed_rows` itself
+pub async fn unseed(pool: &DbPool) -> Result<u64, SeedError> {
+    let rows = sqlx::query("SELECT id, table_name, row_id FROM seed_rows ORDER BY id DESC")
+        .fetch_all(pool)
+        .await?;
+
+    let deleted = crate::tx!(pool, |tx| {
+        let mut deleted = 0;
+        for row in &rows {
+            let id: i64 = row.try_get("id")?;
+            let table_name: String = row.try_get("table_name")?;
+            let row_id: Uuid = row.try_get("row_id")?;
+
+            match SeedTable::from_name(&table_name) {
+                Some(table) => {
+                    let sql = format!("DELETE FROM {} WHERE {} = $1", table.name(), table.key_column());
+                    deleted += sqlx::query(&sql).bind(row_id).execute(&mut **tx).await?.rows_affected();
+                }
+                None => warn!("Skipping seeded row in unknown table {}", table_name),
+            }
+            sqlx::query("DELETE FROM seed_rows WHERE id = $1").bind(id).execute(&mut **tx).await?;
+        }
+        Ok::<_, SqlxError>(deleted)
+    })
+    .await?;
+
+    info!("Removed {} seeded row(s)", deleted);
+    Ok(deleted)
+}
+
+/// Seed the set named in `SEED_ON_STARTUP`, for ephemeral preview environments.
+///
+/// Does nothing when the variable is unset; refuses in production.
+pub async fn seed_from_env(pool: &DbPool, environment: Environment) -> Result<Option<SeedSummary>, SeedError> {
+    seed_on_startup(pool, environment, std::env::var("SEED_ON_STARTUP").ok().as_deref()).await
+}
+
+/// Seed the set named in `jobs.seed_on_startup` of `AppConfig`; same rules as [`seed_from_env`]
+pub async fn seed_on_startup(pool: &DbPool, environment: Environment, set: Option<&str>) -> Result<Option<SeedSummary>, SeedError> {
+    let Some(value) = set.filter(|v| !v.trim().is_empty()) else {
+        return Ok(None);
+    };
+
+    ensure_allowed(environment)?;
+    seed(pool, value.parse()?).await.map(Some)
+}
+
+async fn seed_admin(pool: &DbPool, set: SeedSet, summary: &mut SeedSummary) -> Result<Uuid, SeedError> {
+    let user_key = format!("user:{}", ADMIN_EMAIL);
+    let user_id = match seeded(pool, &user_key).await? {
+        Some(id) => id,
+        None => {
+            let user = User::create(
+                pool,
+                CreateUserDto {
+                    document_id: "DEMO-ADMIN".to_string(),
+                    full_name: "Administrador Demo".to_string(),
+                    email: ADMIN_EMAIL.to_string(),
+                    phone: None,
+                    address: None,
+                    birth_date: NaiveDate::from_ymd_opt(1980, 1, 1).unwrap(),
+                    role: Role::Admin,
+                },
+            )
+            .await?;
+            record(pool, set, &user_key, SeedTable::Users, user.id).await?;
+            summary.users += 1;
+            user.id
+        }
+    };
+
+    let auth_key = format!("auth:{}", ADMIN_EMAIL);
+    if seeded(pool, &auth_key).await?.is_none() {
+        let auth = Authentication::create(pool, NewAuthentication { user_id, password: DEMO_PASSWORD.to_string() }).await?;
+        record(pool, set, &auth_key, SeedTable::Authentications, auth.id).await?;
+    }
+
+    Ok(user_id)
+}
+
+struct DemoTeacher {
+    first_name: &'static str,
+    last_name: &'static str,
+    specialization: &'static str,
+    course_code: &'static str,
+    course_name: &'static str,
+    /// Weekdays (1 = Monday) of the course's two weekly classes
+    days: [u8; 2],
+    start_time: &'static str,
+    end_time: &'static str,
+}
+
+const DEMO_TEACHERS: [DemoTeacher; 3] = [
+    DemoTeacher {
+        first_name: "María",
+        last_name: "González",
+        specialization: "Matemática",
+        course_code: "MAT-7",
+        course_name: "Matemática",
+        days: [1, 3],
+        start_time: "07:00",
+        end_time: "08:20",
+    },
+    DemoTeacher {
+        first_name: "Carlos",
+        last_name: "Benítez",
+        specialization: "Lengua y Literatura",
+        course_code: "CAS-7",
+        course_name: "Lengua Castellana",
+        days: [2, 4],
+        start_time: "07:00",
+        end_time: "08:20",
+    },
+    DemoTeacher {
+        first_name: "Laura",
+        last_name: "Giménez",
+        specialization: "Ciencias Naturales",
+        course_code: "CN-7",
+        course_name: "Ciencias Naturales",
+        days: [1, 5],
+        start_time: "08:40",
+        end_time: "10:00",
+    },
+];
+
+const DEMO_STUDENTS: [(&str, &str); 6] = [
+    ("Sofía", "Ramírez"),
+    ("Mateo", "Duarte"),
+    ("Valentina", "Ortiz"),
+    ("Santiago", "Cáceres"),
+    ("Camila", "Villalba"),
+    ("Joaquín", "Acosta"),
+];
+
+/// Email for a demo person, without accents: `sofia.ramirez@demo.sai.edu.py`
+fn demo_email(first_name: &str, last_name: &str) -> String {
+    let plain: String = format!("{}.{}", first_name, last_name)
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' => 'a',
+            'é' => 'e',
+            'í' => 'i',
+            'ó' => 'o',
+            'ú' | 'ü' => 'u',
+            'ñ' => 'n',
+            other => other,
+        })
+        .collect();
+    format!("{}@demo.sai.edu.py", plain)
+}
+
+fn date_in(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, 12, 0, 0).unwrap()
+}
+
+async fn seed_teacher(
+    pool: &DbPool,
+    set: SeedSet,
+    teacher: &DemoTeacher,
+    summary: &mut SeedSummary,
+) -> Result<Uuid, SeedError> {
+    let email = demo_email(teacher.first_name, teacher.last_name);
+    let key = format!("teacher:{}", email);
+    if let Some(id) = seeded(pool, &key).await? {
+        return Ok(id);
+    }
+
+    let (user, _) = Teacher::create_with_user(
+        pool,
+        CreateTeacherWithUserDto {
+            document_id: format!("DEMO-{}", teacher.course_code),
+            full_name: format!("{} {}", teacher.first_name, teacher.last_name),
+            email: email.clone(),
+            phone: None,
+            address: None,
+            birth_date: NaiveDate::from_ymd_opt(1985, 6, 1).unwrap(),
+            professional_id: format!("MEC-DEMO-{}", teacher.course_code),
+            specialization: teacher.specialization.to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2020, 2, 1).unwrap(),
+            education_level: "Licenciatura".to_string(),
+            subjects: vec![teacher.course_name.to_string()],
+            status: TeacherStatus::Active,
+        },
+    )
+    .await?;
+    record(pool, set, &format!("user:{}", email), SeedTable::Users, user.id).await?;
+    record(pool, set, &key, SeedTable::Teachers, user.id).await?;
+    summary.users += 1;
+    summary.teachers += 1;
+    Ok(user.id)
+}
+
+async fn seed_classroom(pool: &DbPool, set: SeedSet, name: &str, summary: &mut SeedSummary) -> Result<(), SeedError> {
+    let key = format!("classroom:{}", name);
+    if seeded(pool, &key).await?.is_some() {
+        return Ok(());
+    }
+
+    let id: Uuid = sqlx::query_scalar("INSERT INTO classrooms (name, capacity) VALUES ($1, 30) RETURNING id")
+        .bind(name)
+        .fetch_one(pool)
+        .await?;
+    record(pool, set, &key, SeedTable::Classrooms, id).await?;
+    summary.classrooms += 1;
+    Ok(())
+}
+
+async fn seed_course(
+    pool: &DbPool,
+    set: SeedSet,
+    teacher: &DemoTeacher,
+    teacher_id: Uuid,
+    classroom: &str,
+    year: i32,
+    summary: &mut SeedSummary,
+) -> Result<Uuid, SeedError> {
+    let key = format!("course:{}", teacher.course_code);
+    if let Some(id) = seeded(pool, &key).await? {
+        return Ok(id);
+    }
+
+    let schedule = teacher
+        .days
+        .iter()
+        .map(|day| ScheduleSlot {
+            day_of_week: *day,
+            start_time: teacher.start_time.to_string(),
+            end_time: teacher.end_time.to_string(),
+            classroom: classroom.to_string(),
+        })
+        .collect();
+    let course = Course::create(
+        pool,
+        CreateCourseDto {
+            code: teacher.course_code.to_string(),
+            name: teacher.course_name.to_string(),
+            description: None,
+            grade_level: "7".to_string(),
+            credits: 4.0,
+            teacher_id: Some(teacher_id),
+            academic_year: year,
+            schedule,
+        },
+    )
+    .await?;
+    record(pool, set, &key, SeedTable::Courses, course.id).await?;
+    summary.courses += 1;
+    Ok(course.id)
+}
+
+async fn seed_student(
+    pool: &DbPool,
+    set: SeedSet,
+    (first_name, last_name): &(&str, &str),
+    enrollment_number: &str,
+    year: i32,
+    summary: &mut SeedSummary,
+) -> Result<Uuid, SeedError> {
+    let key = format!("student:{}", enrollment_number);
+    if let Some(id) = seeded(pool, &key).await? {
+        return Ok(id);
+    }
+
+    let email = demo_email(first_name, last_name);
+    let (user, _) = Student::create_with_user(
+        pool,
+        CreateStudentWithUserDto {
+            document_id: enrollment_number.to_string(),
+            full_name: format!("{} {}", first_name, last_name),
+            email: email.clone(),
+            phone: None,
+            address: None,
+            birth_date: NaiveDate::from_ymd_opt(year - 13, 5, 10).unwrap(),
+            enrollment_number: enrollment_number.to_string(),
+            current_grade: "7".to_string(),
+            section: "A".to_string(),
+            academic_year: year,
+            guardian_info: None,
+            status: StudentStatus::Active,
+        },
+    )
+    .await?;
+    record(pool, set, &format!("user:{}", email), SeedTable::Users, user.id).await?;
+    record(pool, set, &key, SeedTable::Students, user.id).await?;
+    summary.users += 1;
+    summary.students += 1;
+    Ok(user.id)
+}
+
+/// One pending installment of the tuition plan, due on the 10th of `month`
+async fn insert_tuition(pool: &DbPool, student_id: Uuid, year: i32, month: u32) -> Result<Uuid, SqlxError> {
+    const MONTHS: [&str; 12] = [
+        "enero", "febrero", "marzo", "abril", "mayo", "junio",
+        "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre",
+    ];
+
+    sqlx::query_scalar(
+        r#"
+        INSERT INTO payments (student_id, concept, amount, currency, payment_date, payment_method, status)
+        VALUES ($1, $2, $3, 'PYG', $4, 'transfer', 'pending')
+        RETURNING id
+        "#,
+    )
+    .bind(student_id)
+    .bind(format!("Cuota de {} {}", MONTHS[month as usize - 1], year))
+    .bind(MONTHLY_TUITION)
+    .bind(date_in(year, month, 10))
+    .fetch_one(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_set_parsing() {
+        assert_eq!("minimal".parse::<SeedSet>().unwrap(), SeedSet::Minimal);
+        assert_eq!("Demo".parse::<SeedSet>().unwrap(), SeedSet::DemoSchool);
+        assert_eq!("demo_school".parse::<SeedSet>().unwrap(), SeedSet::DemoSchool);
+        assert!(matches!("full".parse::<SeedSet>(), Err(SeedError::UnknownSet(_))));
+    }
+
+    #[test]
+    fn test_production_is_refused() {
+        assert!(matches!(ensure_allowed(Environment::Production), Err(SeedError::Production)));
+        assert!(ensure_allowed(Environment::Development).is_ok());
+        assert!(ensure_allowed(Environment::Staging).is_ok());
+    }
+
+    #[test]
+    fn test_unseed_only_touches_known_tables() {
+        for table in SeedTable::ALL {
+            assert_eq!(SeedTable::from_name(table.name()), Some(table));
+        }
+        assert_eq!(SeedTable::from_name("users; DROP TABLE users"), None);
+        assert_eq!(SeedTable::Students.key_column(), "user_id");
+    }
+
+    #[test]
+    fn test_demo_emails_are_plain_ascii() {
+        assert_eq!(demo_email("Joaquín", "Acosta"), "joaquin.acosta@demo.sai.edu.py");
+        assert_eq!(demo_email("Laura", "Giménez"), "laura.gimenez@demo.sai.edu.py");
+    }
+}
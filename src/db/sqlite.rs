@@ -0,0 +1,660 @@
+//! SQLite backend for offline, single-laptop installations (feature `db-sqlite`).
+//!
+//! Covers the core CRUD paths of users, students, courses, enrollments,
+//! attendance, assessments (grades) and payments, with their own migrations in
+//! `src/models/migrations/sqlite`. The models keep their Postgres queries, so
+//! this module holds the SQLite versions of those paths and returns the same
+//! model types. Reporting extensions are Postgres-only.
+//!
+//! Enums are stored as text, UUIDs as 16-byte blobs and timestamps as RFC 3339
+//! text; JSON columns (schedules, guardian and payment details) as JSON text.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{NaiveDate, Utc};
+use log::info;
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteRow};
+use sqlx::types::Json;
+use sqlx::{Error as SqlxError, Executor, Row};
+use uuid::Uuid;
+
+use super::{Migration, MigrationSummary};
+use crate::models::{
+    assessment::NewAssessment,
+    attendance::{Attendance, AttendanceStatus, NewAttendance},
+    course::CreateCourseDto,
+    enrollment::{Enrollment, EnrollmentStatus, NewEnrollment},
+    payment::NewPayment,
+    student::CreateStudentDto,
+    user::CreateUserDto,
+    Assessment, Course, Payment, PaymentStatus, Student, User,
+};
+
+pub use sqlx::sqlite::SqlitePool;
+
+macro_rules! sqlite_migration {
+    ($version:literal) => {
+        Migration {
+            version: $version,
+            sql: include_str!(concat!("../models/migrations/sqlite/", $version, ".sql")),
+        }
+    };
+}
+
+/// Migrations in `src/models/migrations/sqlite`, in the order they must be applied
+pub const SQLITE_MIGRATIONS: &[Migration] = &[
+    sqlite_migration!("20250313_create_users_table"),
+    sqlite_migration!("20250313_create_students_table"),
+    sqlite_migration!("20250313_create_courses_table"),
+    sqlite_migration!("20250318_create_enrollments_table"),
+    sqlite_migration!("20250318_create_attendance_table"),
+    sqlite_migration!("20250318_create_assessments_table"),
+    sqlite_migration!("20250326_create_payments_table"),
+    sqlite_migration!("20250415_add_payment_payer_ruc"),
+];
+
+/// Open (creating if needed) the database at `url`, e.g. `sqlite://sai.db` or `sqlite::memory:`.
+///
+/// Foreign keys are enforced and file databases use WAL so reads do not block
+/// the single writer. An in-memory database lives in one connection, so its
+/// pool never opens a second one.
+pub async fn connect(url: &str) -> Result<SqlitePool, SqlxError> {
+    let in_memory = url.contains(":memory:");
+    let mut options = SqliteConnectOptions::from_str(url)?
+        .create_if_missing(true)
+        .foreign_keys(true)
+        .busy_timeout(Duration::from_secs(5));
+    if !in_memory {
+        options = options.journal_mode(SqliteJournalMode::Wal);
+    }
+
+    SqlitePoolOptions::new()
+        .max_connections(if in_memory { 1 } else { 5 })
+        // An idle in-memory connection must not be closed: its database would go with it
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+}
+
+/// Apply every pending SQLite migration, each in its own transaction
+pub async fn migrate(pool: &SqlitePool) -> Result<MigrationSummary, SqlxError> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version TEXT PRIMARY KEY,
+            applied_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )",
+    )
+    .execute(pool)
+    .await?;
+
+    let applied: Vec<String> = sqlx::query_scalar("SELECT version FROM schema_migrations")
+        .fetch_all(pool)
+        .await?;
+    let already_applied = SQLITE_MIGRATIONS.iter().filter(|m| applied.iter().any(|v| v == m.version)).count();
+
+    let mut applied_now = Vec::new();
+    for migration in SQLITE_MIGRATIONS.iter().filter(|m| !applied.iter().any(|v| v == m.version)) {
+        info!("Applying SQLite migration {}", migration.version);
+        let mut tx = pool.begin().await?;
+        (&mut *tx).execute(migration.sql).await?;
+        sqlx::query("INSERT INTO schema_migrations (version) VALUES ($1)")
+            .bind(migration.version)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        applied_now.push(migration.version.to_string());
+    }
+
+    Ok(MigrationSummary {
+        applied_now,
+        already_applied,
+        pending_after: Vec::new(),
+    })
+}
+
+/// Text form of an enum, as serde names it (`Admin`, `on_hold`, ...)
+fn enum_text<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value).expect("model enums serialize to JSON") {
+        serde_json::Value::String(text) => text,
+        // Variants with data, like `AssessmentType::Other`, are stored as JSON
+        other => other.to_string(),
+    }
+}
+
+fn enum_from_text<T: DeserializeOwned>(text: &str, column: &str) -> Result<T, SqlxError> {
+    serde_json::from_value(serde_json::Value::String(text.to_string()))
+        .or_else(|_| serde_json::from_str(text))
+        .map_err(|e| SqlxError::ColumnDecode {
+            index: column.to_string(),
+            source: Box::new(e),
+        })
+}
+
+fn attendance_status_text(status: &AttendanceStatus) -> &'static str {
+    match status {
+        AttendanceStatus::Present => "present",
+        AttendanceStatus::Absent => "absent",
+        AttendanceStatus::Late => "late",
+        AttendanceStatus::Excused => "excused",
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Users and students
+// ---------------------------------------------------------------------------
+
+fn user_from_row(row: &SqliteRow) -> Result<User, SqlxError> {
+    Ok(User {
+        id: row.try_get("id")?,
+        document_id: row.try_get("document_id")?,
+        full_name: row.try_get("full_name")?,
+        email: row.try_get("email")?,
+        phone: row.try_get("phone")?,
+        address: row.try_get("address")?,
+        birth_date: row.try_get("birth_date")?,
+        role: enum_from_text(&row.try_get::<String, _>("role")?, "role")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub async fn create_user(pool: &SqlitePool, dto: CreateUserDto) -> Result<User, SqlxError> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        r#"
+        INSERT INTO users (id, document_id, full_name, email, phone, address, birth_date, role, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(&dto.document_id)
+    .bind(&dto.full_name)
+    .bind(&dto.email)
+    .bind(&dto.phone)
+    .bind(&dto.address)
+    .bind(dto.birth_date)
+    .bind(enum_text(&dto.role))
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+
+    user_from_row(&row)
+}
+
+pub async fn find_user(pool: &SqlitePool, id: Uuid) -> Result<Option<User>, SqlxError> {
+    sqlx::query("SELECT * FROM users WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(user_from_row)
+        .transpose()
+}
+
+pub async fn find_user_by_email(pool: &SqlitePool, email: &str) -> Result<Option<User>, SqlxError> {
+    sqlx::query("SELECT * FROM users WHERE email = $1")
+        .bind(email)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(user_from_row)
+        .transpose()
+}
+
+fn student_from_row(row: &SqliteRow) -> Result<Student, SqlxError> {
+    Ok(Student {
+        user_id: row.try_get("user_id")?,
+        enrollment_number: row.try_get("enrollment_number")?,
+        current_grade: row.try_get("current_grade")?,
+        section: row.try_get("section")?,
+        academic_year: row.try_get("academic_year")?,
+        guardian_info: row.try_get::<Option<Json<_>>, _>("guardian_info")?.map(|json| json.0),
+        status: enum_from_text(&row.try_get::<String, _>("status")?, "status")?,
+    })
+}
+
+/// Student profile of an existing user
+pub async fn create_student(pool: &SqlitePool, dto: CreateStudentDto) -> Result<Student, SqlxError> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        r#"
+        INSERT INTO students (user_id, enrollment_number, current_grade, section, academic_year, guardian_info, status, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $8)
+        RETURNING *
+        "#,
+    )
+    .bind(dto.user_id)
+    .bind(&dto.enrollment_number)
+    .bind(&dto.current_grade)
+    .bind(&dto.section)
+    .bind(dto.academic_year)
+    .bind(dto.guardian_info.as_ref().map(Json))
+    .bind(enum_text(&dto.status))
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+
+    student_from_row(&row)
+}
+
+pub async fn find_student(pool: &SqlitePool, user_id: Uuid) -> Result<Option<Student>, SqlxError> {
+    sqlx::query("SELECT * FROM students WHERE user_id = $1")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(student_from_row)
+        .transpose()
+}
+
+// ---------------------------------------------------------------------------
+// Courses
+// ---------------------------------------------------------------------------
+
+fn course_from_row(row: &SqliteRow) -> Result<Course, SqlxError> {
+    Ok(Course {
+        id: row.try_get("id")?,
+        code: row.try_get("code")?,
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        grade_level: row.try_get("grade_level")?,
+        credits: row.try_get("credits")?,
+        teacher_id: row.try_get("teacher_id")?,
+        academic_year: row.try_get("academic_year")?,
+        schedule: row.try_get::<Json<_>, _>("schedule")?.0,
+    })
+}
+
+pub async fn create_course(pool: &SqlitePool, dto: CreateCourseDto) -> Result<Course, SqlxError> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        r#"
+        INSERT INTO courses (id, code, name, description, grade_level, credits, teacher_id, academic_year, schedule, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $10)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(&dto.code)
+    .bind(&dto.name)
+    .bind(&dto.description)
+    .bind(&dto.grade_level)
+    .bind(dto.credits)
+    .bind(dto.teacher_id)
+    .bind(dto.academic_year)
+    .bind(Json(&dto.schedule))
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+
+    course_from_row(&row)
+}
+
+pub async fn find_course(pool: &SqlitePool, id: Uuid) -> Result<Option<Course>, SqlxError> {
+    sqlx::query("SELECT * FROM courses WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .as_ref()
+        .map(course_from_row)
+        .transpose()
+}
+
+/// SQLite counterpart of `Course::find_similar`: `pg_trgm` is not available, so
+/// name and description similarity are computed with [`trigram_similarity`]
+///
+/// # Returns
+///
+/// Courses whose name similarity is above `threshold`, with the name and
+/// description scores, like the Postgres query
+pub async fn find_similar_courses(
+    pool: &SqlitePool,
+    course_id: Uuid,
+    threshold: f64,
+) -> Result<Vec<(Course, f64, f64)>, SqlxError> {
+    let Some(reference) = find_course(pool, course_id).await? else {
+        return Ok(Vec::new());
+    };
+    let rows = sqlx::query("SELECT * FROM courses WHERE id <> $1")
+        .bind(course_id)
+        .fetch_all(pool)
+        .await?;
+
+    let mut similar = Vec::new();
+    for row in &rows {
+        let course = course_from_row(row)?;
+        let name_score = trigram_similarity(&reference.name, &course.name);
+        if name_score > threshold {
+            let description_score = match (&reference.description, &course.description) {
+                (Some(a), Some(b)) => trigram_similarity(a, b),
+                _ => 0.0,
+            };
+            similar.push((course, name_score, description_score));
+        }
+    }
+    similar.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(similar)
+}
+
+/// Trigrams of `text` the way `pg_trgm` builds them: per lowercase
+/// alphanumeric word, padded with two spaces in front and one behind
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .flat_map(|word| {
+            let padded: Vec<char> = "  ".chars().chain(word.chars()).chain(" ".chars()).collect();
+            padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Shared trigrams over all distinct trigrams, like `pg_trgm`'s `similarity()`
+pub fn trigram_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+// ---------------------------------------------------------------------------
+// Enrollments
+// ---------------------------------------------------------------------------
+
+fn enrollment_from_row(row: &SqliteRow) -> Result<Enrollment, SqlxError> {
+    Ok(Enrollment {
+        id: row.try_get("id")?,
+        student_id: row.try_get("student_id")?,
+        course_id: row.try_get("course_id")?,
+        academic_year: row.try_get("academic_year")?,
+        enrollment_date: row.try_get("enrollment_date")?,
+        status: row.try_get("status")?,
+        completion_date: row.try_get("completion_date")?,
+        final_grade: row.try_get("final_grade")?,
+        notes: row.try_get("notes")?,
+        payment_info: row.try_get::<Option<Json<_>>, _>("payment_info")?.map(|json| json.0),
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+/// Enroll a student; a second enrollment in the same course and year fails on the unique key
+pub async fn create_enrollment(pool: &SqlitePool, new_enrollment: &NewEnrollment) -> Result<Enrollment, SqlxError> {
+    let now = Utc::now();
+    let status = new_enrollment.status.unwrap_or(EnrollmentStatus::Pending);
+    let row = sqlx::query(
+        r#"
+        INSERT INTO enrollments (id, student_id, course_id, academic_year, enrollment_date, status, notes, payment_info, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $5, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(new_enrollment.student_id)
+    .bind(new_enrollment.course_id)
+    .bind(new_enrollment.academic_year)
+    .bind(now)
+    .bind(status)
+    .bind(&new_enrollment.notes)
+    .bind(new_enrollment.payment_info.as_ref().map(Json))
+    .fetch_one(pool)
+    .await?;
+
+    enrollment_from_row(&row)
+}
+
+pub async fn enrollments_for_student(pool: &SqlitePool, student_id: Uuid) -> Result<Vec<Enrollment>, SqlxError> {
+    sqlx::query("SELECT * FROM enrollments WHERE student_id = $1 ORDER BY academic_year DESC, enrollment_date")
+        .bind(student_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(enrollment_from_row)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Attendance
+// ---------------------------------------------------------------------------
+
+fn attendance_from_row(row: &SqliteRow) -> Result<Attendance, SqlxError> {
+    let text: String = row.try_get("status")?;
+    let status = [
+        AttendanceStatus::Present,
+        AttendanceStatus::Absent,
+        AttendanceStatus::Late,
+        AttendanceStatus::Excused,
+    ]
+    .into_iter()
+    .find(|status| attendance_status_text(status) == text)
+    .ok_or_else(|| SqlxError::ColumnDecode {
+        index: "status".to_string(),
+        source: format!("unknown attendance status {:?}", text).into(),
+    })?;
+
+    Ok(Attendance {
+        id: row.try_get("id")?,
+        student_id: row.try_get("student_id")?,
+        course_id: row.try_get("course_id")?,
+        date: row.try_get("date")?,
+        status,
+        notes: row.try_get("notes")?,
+        minutes_late: row.try_get("minutes_late")?,
+        recorded_by: row.try_get("recorded_by")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub async fn record_attendance(pool: &SqlitePool, new_attendance: &NewAttendance) -> Result<Attendance, SqlxError> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        r#"
+        INSERT INTO attendance (id, student_id, course_id, date, status, notes, minutes_late, recorded_by, created_at, updated_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(new_attendance.student_id)
+    .bind(new_attendance.course_id)
+    .bind(new_attendance.date)
+    .bind(attendance_status_text(&new_attendance.status))
+    .bind(&new_attendance.notes)
+    .bind(new_attendance.minutes_late)
+    .bind(new_attendance.recorded_by)
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+
+    attendance_from_row(&row)
+}
+
+/// Attendance taken in a course on one day
+pub async fn attendance_for_course(
+    pool: &SqlitePool,
+    course_id: Uuid,
+    date: NaiveDate,
+) -> Result<Vec<Attendance>, SqlxError> {
+    sqlx::query("SELECT * FROM attendance WHERE course_id = $1 AND date = $2 ORDER BY created_at")
+        .bind(course_id)
+        .bind(date)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(attendance_from_row)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Assessments (grades)
+// ---------------------------------------------------------------------------
+
+fn assessment_from_row(row: &SqliteRow) -> Result<Assessment, SqlxError> {
+    Ok(Assessment {
+        id: row.try_get("id")?,
+        enrollment_id: row.try_get("enrollment_id")?,
+        course_id: row.try_get("course_id")?,
+        assessment_type: enum_from_text(&row.try_get::<String, _>("assessment_type")?, "assessment_type")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        score: row.try_get("score")?,
+        max_score: row.try_get("max_score")?,
+        weight: row.try_get("weight")?,
+        assessment_date: row.try_get("assessment_date")?,
+        is_final: row.try_get("is_final")?,
+        period: row.try_get("period")?,
+        comments: row.try_get("comments")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+/// Record a grade; scores outside `0..=max_score` are rejected by the table checks
+pub async fn create_assessment(pool: &SqlitePool, new_assessment: NewAssessment) -> Result<Assessment, SqlxError> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        r#"
+        INSERT INTO assessments (
+            id, enrollment_id, course_id, assessment_type, title, description, score, max_score,
+            weight, assessment_date, is_final, period, comments, created_at, updated_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $14)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(new_assessment.enrollment_id)
+    .bind(new_assessment.course_id)
+    .bind(enum_text(&new_assessment.assessment_type))
+    .bind(&new_assessment.title)
+    .bind(&new_assessment.description)
+    .bind(new_assessment.score)
+    .bind(new_assessment.max_score)
+    .bind(new_assessment.weight)
+    .bind(new_assessment.assessment_date)
+    .bind(new_assessment.is_final)
+    .bind(new_assessment.period)
+    .bind(&new_assessment.comments)
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+
+    assessment_from_row(&row)
+}
+
+pub async fn assessments_for_enrollment(pool: &SqlitePool, enrollment_id: Uuid) -> Result<Vec<Assessment>, SqlxError> {
+    sqlx::query("SELECT * FROM assessments WHERE enrollment_id = $1 ORDER BY assessment_date")
+        .bind(enrollment_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(assessment_from_row)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Payments
+// ---------------------------------------------------------------------------
+
+fn payment_from_row(row: &SqliteRow) -> Result<Payment, SqlxError> {
+    let status: String = row.try_get("status")?;
+
+    Ok(Payment {
+        id: row.try_get("id")?,
+        student_id: row.try_get("student_id")?,
+        concept: row.try_get("concept")?,
+        amount: row.try_get("amount")?,
+        currency: row.try_get("currency")?,
+        payment_date: row.try_get("payment_date")?,
+        payment_method: row.try_get("payment_method")?,
+        status: PaymentStatus::from_db(&status).ok_or_else(|| SqlxError::ColumnDecode {
+            index: "status".to_string(),
+            source: format!("unknown payment status {:?}", status).into(),
+        })?,
+        receipt_number: row.try_get("receipt_number")?,
+        payer_ruc: row.try_get("payer_ruc")?,
+        notes: row.try_get("notes")?,
+        base_payment_id: row.try_get("base_payment_id")?,
+    })
+}
+
+pub async fn create_payment(pool: &SqlitePool, new_payment: &NewPayment) -> Result<Payment, SqlxError> {
+    let now = Utc::now();
+    let row = sqlx::query(
+        r#"
+        INSERT INTO payments (
+            id, student_id, concept, amount, currency, payment_date, payment_method,
+            status, receipt_number, payer_ruc, notes, created_at, updated_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $12)
+        RETURNING *
+        "#,
+    )
+    .bind(Uuid::new_v4())
+    .bind(new_payment.student_id)
+    .bind(&new_payment.concept)
+    .bind(new_payment.amount)
+    .bind(new_payment.currency.as_deref().unwrap_or("PYG"))
+    .bind(new_payment.payment_date)
+    .bind(&new_payment.payment_method)
+    .bind(new_payment.status.as_ref().unwrap_or(&PaymentStatus::Pending).as_str())
+    .bind(&new_payment.receipt_number)
+    .bind(&new_payment.payer_ruc)
+    .bind(&new_payment.notes)
+    .bind(now)
+    .fetch_one(pool)
+    .await?;
+
+    payment_from_row(&row)
+}
+
+pub async fn payments_for_student(pool: &SqlitePool, student_id: Uuid) -> Result<Vec<Payment>, SqlxError> {
+    sqlx::query("SELECT * FROM payments WHERE student_id = $1 ORDER BY payment_date")
+        .bind(student_id)
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(payment_from_row)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trigram_similarity_matches_pg_trgm() {
+        // Values from `SELECT similarity(a, b)` on Postgres with pg_trgm
+        assert_eq!(trigram_similarity("Matemática", "Matemática"), 1.0);
+        assert_eq!(trigram_similarity("word", "two words"), 4.0 / 11.0);
+        assert_eq!(trigram_similarity("Historia", "Química"), 0.0);
+        assert_eq!(trigram_similarity("", ""), 0.0);
+    }
+
+    #[test]
+    fn test_trigram_similarity_ignores_case_and_punctuation() {
+        assert_eq!(trigram_similarity("Lengua y Literatura", "lengua, y literatura"), 1.0);
+    }
+
+    #[test]
+    fn test_migration_list_covers_directory() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/models/migrations/sqlite");
+        let mut on_disk: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .filter_map(|entry| entry.unwrap().file_name().into_string().unwrap().strip_suffix(".sql").map(str::to_string))
+            .collect();
+        on_disk.sort();
+        let mut embedded: Vec<String> = SQLITE_MIGRATIONS.iter().map(|m| m.version.to_string()).collect();
+        embedded.sort();
+
+        assert_eq!(embedded, on_disk);
+    }
+}
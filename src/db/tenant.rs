@@ -0,0 +1,283 @@
+//! Schema-per-tenant support for multi-institution deployments.
+//!
+//! Each institution gets its own schema, `tenant_<slug>`, migrated like
+//! `public`. A [`TenantPool`] wraps the shared pool: every transaction it begins
+//! runs `SET LOCAL search_path = tenant_<slug>` first, so unqualified table
+//! names resolve to the tenant's tables and the setting ends with the
+//! transaction instead of leaking to the next user of the connection. The
+//! services take a plain pool instead; [`connect`] gives them one whose
+//! connections are pinned to the tenant schema.
+//!
+//! `public` is never on a tenant's runtime `search_path`: a table missing
+//! from the tenant schema is an error, not a read of another institution's
+//! data. The only objects tenants use from `public` are the functions of
+//! the extensions (`pg_trgm`), which queries call schema-qualified
+//! (`public.similarity`).
+//!
+//! Requests get the state of their tenant from the `institution` claim of the
+//! access token (see `routes::tenant`). Nothing iterates tenants implicitly:
+//! admin operations that span institutions call [`list_tenants`] and open one
+//! `TenantPool` per tenant.
+
+use std::fmt;
+
+use log::info;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::{Error as SqlxError, Postgres, Transaction};
+use uuid::Uuid;
+
+use super::{metrics, DbPool, Migration, MigrationSummary};
+
+/// Prefix of every tenant schema
+pub const TENANT_SCHEMA_PREFIX: &str = "tenant_";
+
+/// Longest institution slug; keeps the schema name within Postgres' 63-byte limit
+pub const MAX_SLUG_LEN: usize = 50;
+
+/// Connections of each tenant pool opened by [`connect`]
+pub const TENANT_MAX_CONNECTIONS: u32 = 5;
+
+/// Error while resolving, provisioning or using a tenant schema
+#[derive(Debug, thiserror::Error)]
+pub enum TenantError {
+    #[error("Invalid institution identifier {0:?}: use 1 to 50 lowercase letters, digits or underscores")]
+    InvalidName(String),
+    #[error("Institution {0:?} has no schema; provision it first")]
+    MissingSchema(String),
+    #[error("Tenant database error: {0}")]
+    Database(#[from] SqlxError),
+}
+
+/// Institution slug, validated so it can be spliced into SQL as a schema name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TenantId(String);
+
+impl TenantId {
+    /// Validate `slug` (`colegio_san_jose`); the schema is `tenant_colegio_san_jose`
+    pub fn parse(slug: &str) -> Result<Self, TenantError> {
+        let valid = !slug.is_empty()
+            && slug.len() <= MAX_SLUG_LEN
+            && slug.starts_with(|c: char| c.is_ascii_lowercase())
+            && slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+        if valid {
+            Ok(TenantId(slug.to_string()))
+        } else {
+            Err(TenantError::InvalidName(slug.to_string()))
+        }
+    }
+
+    /// Tenant owning `schema`, if it is a tenant schema
+    pub fn from_schema(schema: &str) -> Option<Self> {
+        Self::parse(schema.strip_prefix(TENANT_SCHEMA_PREFIX)?).ok()
+    }
+
+    pub fn slug(&self) -> &str {
+        &self.0
+    }
+
+    pub fn schema(&self) -> String {
+        format!("{}{}", TENANT_SCHEMA_PREFIX, self.0)
+    }
+
+    /// Value for `search_path` at runtime: the tenant schema alone
+    fn search_path(&self) -> String {
+        self.schema()
+    }
+
+    /// Value for `search_path` while migrating: the tenant schema, then
+    /// `public`, whose extensions provide operator classes such as `gin_trgm_ops`
+    fn migration_search_path(&self) -> String {
+        format!("{},public", self.schema())
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The shared pool, scoped to one tenant's schema
+///
+/// Without a tenant it behaves like the plain pool, for single-institution
+/// deployments where everything lives in `public`.
+#[derive(Debug, Clone)]
+pub struct TenantPool {
+    pool: DbPool,
+    tenant: Option<TenantId>,
+}
+
+impl TenantPool {
+    pub fn new(pool: DbPool, tenant: Option<TenantId>) -> Self {
+        Self { pool, tenant }
+    }
+
+    pub fn tenant(&self) -> Option<&TenantId> {
+        self.tenant.as_ref()
+    }
+
+    /// The shared pool, with the default `search_path`
+    pub fn pool(&self) -> &DbPool {
+        &self.pool
+    }
+
+    /// Begin a transaction whose `search_path` is the tenant's schema.
+    ///
+    /// `SET LOCAL` only lasts until the transaction ends, which is why tenant
+    /// access always goes through a transaction. Fails with
+    /// [`TenantError::MissingSchema`] when the schema does not exist, instead of
+    /// letting queries fall through to `public`.
+    pub async fn begin(&self) -> Result<Transaction<'static, Postgres>, TenantError> {
+        self.begin_with(None).await
+    }
+
+    /// Like [`TenantPool::begin`], for a read-only `REPEATABLE READ` transaction:
+    /// every query sees the same snapshot, e.g. while exporting a backup
+    pub async fn begin_read_only(&self) -> Result<Transaction<'static, Postgres>, TenantError> {
+        self.begin_with(Some("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")).await
+    }
+
+    async fn begin_with(&self, characteristics: Option<&str>) -> Result<Transaction<'static, Postgres>, TenantError> {
+        let mut tx = metrics::begin(&self.pool).await?;
+
+        // Must be the first statement of the transaction
+        if let Some(characteristics) = characteristics {
+            sqlx::query(characteristics).execute(&mut *tx).await?;
+        }
+
+        if let Some(tenant) = &self.tenant {
+            // One round trip: switch the schema and check that it exists
+            let (exists, _): (bool, String) =
+                sqlx::query_as("SELECT to_regnamespace($2) IS NOT NULL, set_config('search_path', $1, true)")
+                    .bind(tenant.search_path())
+                    .bind(tenant.schema())
+                    .fetch_one(&mut *tx)
+                    .await?;
+
+            if !exists {
+                return Err(TenantError::MissingSchema(tenant.slug().to_string()));
+            }
+        }
+
+        Ok(tx)
+    }
+}
+
+/// Create the schema of `tenant` and apply every migration into it
+pub async fn provision(pool: &DbPool, tenant: &TenantId) -> Result<MigrationSummary, TenantError> {
+    provision_with(pool, tenant, super::MIGRATIONS).await
+}
+
+/// Like [`provision`], applying only `migrations` (in order).
+///
+/// Safe to re-run: the schema is created if missing and only pending
+/// migrations are applied, so it also brings an existing tenant up to date.
+pub async fn provision_with(
+    pool: &DbPool,
+    tenant: &TenantId,
+    migrations: &[Migration],
+) -> Result<MigrationSummary, TenantError> {
+    super::create_extensions(pool).await?;
+    sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {}", tenant.schema()))
+        .execute(pool)
+        .await?;
+
+    // Migrations run on their own connection with the tenant first in `search_path`,
+    // so `schema_migrations` and every table land in the tenant schema
+    let options = (*pool.connect_options()).clone().options([("search_path", tenant.migration_search_path())]);
+    let tenant_pool = PgPoolOptions::new().max_connections(1).connect_with(options).await?;
+    let summary = super::apply_migrations(&tenant_pool, migrations, false).await;
+    tenant_pool.close().await;
+    let summary = summary?;
+
+    info!(
+        "Provisioned tenant {}: {} migration(s) applied",
+        tenant,
+        summary.applied_now.len()
+    );
+    Ok(summary)
+}
+
+/// Pool of `tenant` for the services: each connection starts with the tenant
+/// schema as its whole `search_path`
+///
+/// Connects with the options of the shared `pool`, up to
+/// [`TENANT_MAX_CONNECTIONS`] connections. Fails with
+/// [`TenantError::MissingSchema`] when the tenant was never provisioned.
+pub async fn connect(pool: &DbPool, tenant: &TenantId) -> Result<DbPool, TenantError> {
+    let exists: bool = sqlx::query_scalar("SELECT to_regnamespace($1) IS NOT NULL")
+        .bind(tenant.schema())
+        .fetch_one(pool)
+        .await?;
+    if !exists {
+        return Err(TenantError::MissingSchema(tenant.slug().to_string()));
+    }
+
+    let options = (*pool.connect_options()).clone().options([("search_path", tenant.search_path())]);
+    let tenant_pool = PgPoolOptions::new()
+        .max_connections(TENANT_MAX_CONNECTIONS)
+        .connect_with(options)
+        .await?;
+
+    info!("Connected to the schema of tenant {}", tenant);
+    Ok(tenant_pool)
+}
+
+/// Whether `user_id` belongs to `tenant`, i.e. has a row in the tenant's own `users` table
+///
+/// Accounts sign in against `public`; login and token refresh call this
+/// before putting `tenant` in the `institution` claim. The table is
+/// schema-qualified so the query never depends on the `search_path`.
+pub async fn is_member(pool: &DbPool, tenant: &TenantId, user_id: Uuid) -> Result<bool, TenantError> {
+    let mut tx = TenantPool::new(pool.clone(), Some(tenant.clone())).begin().await?;
+    let member = sqlx::query_scalar(&format!("SELECT EXISTS (SELECT 1 FROM {}.users WHERE id = $1)", tenant.schema()))
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(member)
+}
+
+/// Every provisioned tenant, by slug
+pub async fn list_tenants(pool: &DbPool) -> Result<Vec<TenantId>, TenantError> {
+    let schemas: Vec<String> = sqlx::query_scalar(
+        "SELECT nspname::TEXT FROM pg_namespace WHERE starts_with(nspname, $1) ORDER BY nspname",
+    )
+    .bind(TENANT_SCHEMA_PREFIX)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(schemas.iter().filter_map(|schema| TenantId::from_schema(schema)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_slugs() {
+        let tenant = TenantId::parse("colegio_san_jose").unwrap();
+
+        assert_eq!(tenant.schema(), "tenant_colegio_san_jose");
+        assert_eq!(tenant.search_path(), "tenant_colegio_san_jose");
+        assert_eq!(tenant.migration_search_path(), "tenant_colegio_san_jose,public");
+        assert!(TenantId::parse("cnc2").is_ok());
+    }
+
+    #[test]
+    fn test_slugs_that_are_not_identifiers_are_rejected() {
+        for slug in ["", "Colegio", "2colegio", "san-jose", "x; DROP SCHEMA public", "colegio josé"] {
+            assert!(matches!(TenantId::parse(slug), Err(TenantError::InvalidName(_))), "{:?}", slug);
+        }
+        assert!(TenantId::parse(&"a".repeat(MAX_SLUG_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_tenant_from_schema() {
+        assert_eq!(TenantId::from_schema("tenant_cnc"), Some(TenantId::parse("cnc").unwrap()));
+        assert_eq!(TenantId::from_schema("public"), None);
+        assert_eq!(TenantId::from_schema("sai_test_1744000000_abc"), None);
+    }
+}
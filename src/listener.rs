@@ -0,0 +1,136 @@
+//! Unix domain socket listener for `server.socket_path`.
+//!
+//! Used when a reverse proxy on the same host talks to the API over a socket
+//! instead of a TCP port. A socket file left behind by a previous run is
+//! removed before binding; anything else at the path is left alone.
+
+use std::io;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+
+use log::info;
+
+/// Binds a listener at `path` and gives the socket file the permission bits in `mode`
+///
+/// # Errors
+///
+/// `AddrInUse` if another process still accepts connections on `path`,
+/// `AlreadyExists` if `path` is not a socket, or the error from binding.
+pub fn bind_unix(path: &Path, mode: u32) -> io::Result<UnixListener> {
+    remove_stale(path)?;
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(listener)
+}
+
+/// Removes the socket at `path` when nothing listens on it; `Ok(true)` if it was removed
+pub fn remove_stale(path: &Path) -> io::Result<bool> {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} existe y no es un socket", path.display()),
+        ));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("otro proceso ya atiende en {}", path.display()),
+        ));
+    }
+    std::fs::remove_file(path)?;
+    info!("Socket abandonado eliminado: {}", path.display());
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpResponse, HttpServer};
+    use std::io::{Read, Write};
+    use std::path::PathBuf;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sai-listener-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("sai.sock")
+    }
+
+    #[test]
+    fn test_stale_socket_removed() {
+        let path = socket_path("stale");
+        // Dropping the listener leaves the file behind, like a process that was killed
+        drop(UnixListener::bind(&path).unwrap());
+
+        assert!(remove_stale(&path).unwrap());
+        assert!(!path.exists());
+        assert!(!remove_stale(&path).unwrap());
+
+        bind_unix(&path, 0o660).unwrap();
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_live_socket_and_other_files_kept() {
+        let path = socket_path("live");
+        let _listener = bind_unix(&path, 0o660).unwrap();
+
+        assert_eq!(bind_unix(&path, 0o660).unwrap_err().kind(), io::ErrorKind::AddrInUse);
+        assert!(path.exists());
+
+        let file = path.with_file_name("sai.toml");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(remove_stale(&file).unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert!(file.exists());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_permissions_applied() {
+        for mode in [0o600, 0o660, 0o666] {
+            let path = socket_path(&format!("mode-{:o}", mode));
+            let _listener = bind_unix(&path, mode).unwrap();
+
+            let permissions = std::fs::metadata(&path).unwrap().permissions();
+            assert_eq!(permissions.mode() & 0o777, mode);
+            std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_server_answers_over_socket() {
+        let path = socket_path("http");
+        let server = HttpServer::new(|| App::new().route("/health", web::get().to(|| async { HttpResponse::Ok().body("ok") })))
+            .workers(1)
+            .listen_uds(bind_unix(&path, 0o660).unwrap())
+            .unwrap()
+            .run();
+        let handle = server.handle();
+        actix_rt::spawn(server);
+
+        let client_path = path.clone();
+        let response = actix_rt::task::spawn_blocking(move || {
+            let mut stream = UnixStream::connect(client_path).unwrap();
+            stream
+                .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        })
+        .await
+        .unwrap();
+        handle.stop(true).await;
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"), "{}", response);
+        assert!(response.ends_with("ok"), "{}", response);
+        // After shutdown the file is stale and the next start reclaims it
+        assert!(remove_stale(&path).unwrap());
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
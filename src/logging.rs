@@ -0,0 +1,665 @@
+//! Application logger.
+//!
+//! Records go through the `log` facade to the console and, when
+//! `logging.file` is set, as JSON lines to a [`RotatingFile`]. The level
+//! spec uses the `RUST_LOG` syntax with per-target directives
+//! (`info,sqlx=warn,sai::services=debug`); the most specific target wins.
+//!
+//! `PUT /api/admin/log-level` sets a temporary spec on top of the configured
+//! one. It reverts by itself once its duration is over, checked on the next
+//! record, so a forgotten `debug` does not stay on.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use env_logger::fmt::{Formatter, Target, WriteStyle};
+use env_logger::filter::{self, Filter};
+use log::{error, info, LevelFilter, Log, Metadata, Record};
+use serde::Serialize;
+use serde_json::json;
+
+use crate::config::{LoggingConfig, Rotation, DEFAULT_LOG_LEVEL};
+
+/// Date suffix of the files written with `rotation = "daily"`
+const DAILY_SUFFIX: &str = "%Y-%m-%d";
+
+/// Longest override accepted; longer durations are cut to this
+pub const MAX_OVERRIDE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A level spec that is not `level`, `target` or `target=level` separated by commas
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Nivel de log inválido {spec:?}: {reason}")]
+pub struct InvalidSpec {
+    pub spec: String,
+    pub reason: String,
+}
+
+/// Temporary level set through the API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LevelOverride {
+    pub level: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Levels in effect, as returned by `GET /api/admin/log-level`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LogLevelStatus {
+    /// Spec of the configuration (`logging.level`)
+    pub level: String,
+    /// Temporary spec that replaces it until `expires_at`
+    #[serde(rename = "override")]
+    pub level_override: Option<LevelOverride>,
+}
+
+/// Checks a level spec and builds its filter
+///
+/// `env_logger` silently skips the directives it cannot parse; a typo in the
+/// configuration or in the API should be an error instead.
+pub fn parse_spec(spec: &str) -> Result<Filter, InvalidSpec> {
+    let invalid = |reason: &str| InvalidSpec { spec: spec.to_string(), reason: reason.to_string() };
+
+    if spec.contains('/') {
+        return Err(invalid("los filtros por expresión regular (/...) no están soportados"));
+    }
+    let directives: Vec<&str> = spec.split(',').map(str::trim).filter(|d| !d.is_empty()).collect();
+    if directives.is_empty() {
+        return Err(invalid("no indica ningún nivel"));
+    }
+    for directive in directives {
+        let (target, level) = match directive.split_once('=') {
+            Some((target, level)) => (Some(target.trim()), Some(level.trim())),
+            None if directive.parse::<LevelFilter>().is_ok() => (None, None),
+            None => (Some(directive), None),
+        };
+        if let Some(target) = target {
+            let valid_target = !target.is_empty()
+                && target.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | ':' | '-'));
+            if !valid_target {
+                return Err(invalid(&format!("{:?} no es un módulo", target)));
+            }
+        }
+        if let Some(level) = level {
+            if level.parse::<LevelFilter>().is_err() {
+                return Err(invalid(&format!("{:?} no es un nivel (off, error, warn, info, debug, trace)", level)));
+            }
+        }
+    }
+
+    Ok(filter::Builder::new().parse(spec).build())
+}
+
+struct ActiveOverride {
+    spec: String,
+    filter: Filter,
+    until: Instant,
+    expires_at: DateTime<Utc>,
+}
+
+struct Filters {
+    spec: String,
+    base: Filter,
+    level_override: Option<ActiveOverride>,
+}
+
+impl Filters {
+    fn active(&self) -> &Filter {
+        self.level_override.as_ref().map_or(&self.base, |o| &o.filter)
+    }
+
+    fn max_level(&self) -> LevelFilter {
+        let base = self.base.filter();
+        self.level_override.as_ref().map_or(base, |o| base.max(o.filter.filter()))
+    }
+}
+
+/// `log` implementation with a replaceable filter and an optional file output
+pub struct Logger {
+    filters: RwLock<Filters>,
+    console: env_logger::Logger,
+    file: RwLock<Option<env_logger::Logger>>,
+}
+
+impl Logger {
+    /// Logger writing text to `console`, filtered by `spec`
+    pub fn new(spec: &str, console: Target) -> Result<Self, InvalidSpec> {
+        let base = parse_spec(spec)?;
+        let write_style = match console {
+            Target::Pipe(_) => WriteStyle::Never,
+            _ => WriteStyle::Auto,
+        };
+        Ok(Self {
+            filters: RwLock::new(Filters { spec: spec.to_string(), base, level_override: None }),
+            console: env_logger::Builder::new()
+                .filter_level(LevelFilter::Trace)
+                .target(console)
+                .write_style(write_style)
+                .build(),
+            file: RwLock::new(None),
+        })
+    }
+
+    /// Replaces the configured spec; a temporary override stays in effect
+    pub fn set_level(&self, spec: &str) -> Result<(), InvalidSpec> {
+        let base = parse_spec(spec)?;
+        let mut filters = self.filters.write().unwrap();
+        filters.spec = spec.to_string();
+        filters.base = base;
+        Ok(())
+    }
+
+    /// Also writes every record as a JSON line to `output`; `None` stops it
+    pub fn set_file(&self, output: Option<Box<dyn Write + Send>>) {
+        let logger = output.map(|output| {
+            env_logger::Builder::new()
+                .filter_level(LevelFilter::Trace)
+                .format(json_format)
+                .target(Target::Pipe(output))
+                .write_style(WriteStyle::Never)
+                .build()
+        });
+        *self.file.write().unwrap() = logger;
+    }
+
+    /// Uses `spec` instead of the configured one for `ttl` (at most [`MAX_OVERRIDE_TTL`])
+    pub fn set_override(&self, spec: &str, ttl: Duration) -> Result<LevelOverride, InvalidSpec> {
+        let filter = parse_spec(spec)?;
+        let ttl = ttl.min(MAX_OVERRIDE_TTL);
+        let expires_at = Utc::now() + chrono::Duration::from_std(ttl).expect("capped to one day");
+        self.filters.write().unwrap().level_override = Some(ActiveOverride {
+            spec: spec.to_string(),
+            filter,
+            until: Instant::now() + ttl,
+            expires_at,
+        });
+        Ok(LevelOverride { level: spec.to_string(), expires_at })
+    }
+
+    /// Goes back to the configured spec; `false` if there was no override
+    pub fn clear_override(&self) -> bool {
+        self.filters.write().unwrap().level_override.take().is_some()
+    }
+
+    pub fn status(&self) -> LogLevelStatus {
+        self.expire_override();
+        let filters = self.filters.read().unwrap();
+        LogLevelStatus {
+            level: filters.spec.clone(),
+            level_override: filters
+                .level_override
+                .as_ref()
+                .map(|o| LevelOverride { level: o.spec.clone(), expires_at: o.expires_at }),
+        }
+    }
+
+    /// Most verbose level any target can log at, for [`log::set_max_level`]
+    pub fn max_level(&self) -> LevelFilter {
+        self.filters.read().unwrap().max_level()
+    }
+
+    fn expire_override(&self) {
+        let expired = |filters: &Filters| filters.level_override.as_ref().is_some_and(|o| o.until <= Instant::now());
+        if expired(&self.filters.read().unwrap()) {
+            let mut filters = self.filters.write().unwrap();
+            if expired(&filters) {
+                filters.level_override = None;
+            }
+        }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.expire_override();
+        self.filters.read().unwrap().active().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        self.console.log(record);
+        if let Some(file) = self.file.read().unwrap().as_ref() {
+            file.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+        if let Some(file) = self.file.read().unwrap().as_ref() {
+            file.flush();
+        }
+    }
+}
+
+/// One JSON object per line: timestamp, level, target and message
+fn json_format(buf: &mut Formatter, record: &Record) -> io::Result<()> {
+    let line = json!({
+        "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "message": record.args().to_string(),
+    });
+    writeln!(buf, "{}", line)
+}
+
+/// File output that starts a new file by day or by size, keeping `max_files`
+/// rotated files
+///
+/// With daily rotation each day is written to `<file>.<YYYY-MM-DD>` (UTC);
+/// with size rotation the current file is `<file>` and the previous ones are
+/// `<file>.1` (newest) to `<file>.<max_files>`.
+pub struct RotatingFile {
+    path: PathBuf,
+    rotation: Rotation,
+    max_file_size: u64,
+    max_files: usize,
+    file: Option<File>,
+    size: u64,
+    day: Option<NaiveDate>,
+}
+
+impl RotatingFile {
+    /// Opens (or continues) the current file of `path` with the rotation of `config`
+    pub fn open(path: &Path, config: &LoggingConfig) -> io::Result<Self> {
+        Self::open_on(path, config, Utc::now().date_naive())
+    }
+
+    fn open_on(path: &Path, config: &LoggingConfig, today: NaiveDate) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let mut output = Self {
+            path: path.to_path_buf(),
+            rotation: config.rotation,
+            max_file_size: config.max_file_size,
+            max_files: config.max_files,
+            file: None,
+            size: 0,
+            day: None,
+        };
+        output.open_current(today)?;
+        Ok(output)
+    }
+
+    /// File written on `day`
+    pub fn current_path(&self, day: NaiveDate) -> PathBuf {
+        match self.rotation {
+            Rotation::Daily => daily_path(&self.path, day),
+            Rotation::Size => self.path.clone(),
+        }
+    }
+
+    fn open_current(&mut self, day: NaiveDate) -> io::Result<()> {
+        let path = self.current_path(day);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.size = file.metadata()?.len();
+        self.file = Some(file);
+        self.day = Some(day);
+        Ok(())
+    }
+
+    fn write_on(&mut self, buf: &[u8], day: NaiveDate) -> io::Result<usize> {
+        let roll = match self.rotation {
+            Rotation::Daily => self.day != Some(day),
+            Rotation::Size => self.size > 0 && self.size + buf.len() as u64 > self.max_file_size,
+        };
+        if roll || self.file.is_none() {
+            self.roll(day)?;
+        }
+
+        let written = self.file.as_mut().expect("log file opened above").write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn roll(&mut self, day: NaiveDate) -> io::Result<()> {
+        self.file = None;
+        match self.rotation {
+            Rotation::Daily => {
+                self.open_current(day)?;
+                self.remove_old_days()
+            }
+            Rotation::Size => {
+                self.shift_numbered()?;
+                self.open_current(day)
+            }
+        }
+    }
+
+    /// `<file>.<n>` becomes `<file>.<n + 1>`, dropping the oldest, and `<file>` becomes `<file>.1`
+    fn shift_numbered(&self) -> io::Result<()> {
+        if self.max_files == 0 {
+            return remove_if_exists(&self.path);
+        }
+        remove_if_exists(&numbered_path(&self.path, self.max_files))?;
+        for n in (1..self.max_files).rev() {
+            rename_if_exists(&numbered_path(&self.path, n), &numbered_path(&self.path, n + 1))?;
+        }
+        rename_if_exists(&self.path, &numbered_path(&self.path, 1))
+    }
+
+    /// Keeps the current day and the `max_files` most recent ones
+    fn remove_old_days(&self) -> io::Result<()> {
+        let (Some(dir), Some(name)) = (self.path.parent(), self.path.file_name().and_then(|name| name.to_str())) else {
+            return Ok(());
+        };
+        let dir = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+        let prefix = format!("{}.", name);
+
+        let mut days: Vec<NaiveDate> = fs::read_dir(dir)?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter_map(|file| NaiveDate::parse_from_str(file.strip_prefix(&prefix)?, DAILY_SUFFIX).ok())
+            .collect();
+        days.sort_unstable_by(|a, b| b.cmp(a));
+        for day in days.into_iter().skip(self.max_files + 1) {
+            remove_if_exists(&daily_path(&self.path, day))?;
+        }
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_on(buf, Utc::now().date_naive())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), |file| file.flush())
+    }
+}
+
+/// `sai.log` -> `sai.log.2024-03-01`
+pub fn daily_path(path: &Path, day: NaiveDate) -> PathBuf {
+    with_suffix(path, &day.format(DAILY_SUFFIX).to_string())
+}
+
+/// `sai.log` -> `sai.log.3`
+pub fn numbered_path(path: &Path, n: usize) -> PathBuf {
+    with_suffix(path, &n.to_string())
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Process-wide logger; before [`init`] it exists but does not receive records
+pub fn logger() -> &'static Logger {
+    LOGGER.get_or_init(|| {
+        let spec = ["RUST_LOG", "LOG_LEVEL"]
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|value| !value.trim().is_empty()))
+            .unwrap_or_else(|| DEFAULT_LOG_LEVEL.to_string());
+        Logger::new(&spec, Target::Stderr).unwrap_or_else(|e| {
+            eprintln!("{}; se usa {:?}", e, DEFAULT_LOG_LEVEL);
+            Logger::new(DEFAULT_LOG_LEVEL, Target::Stderr).expect("default level is valid")
+        })
+    })
+}
+
+/// Installs the logger with the level of `RUST_LOG` or `LOG_LEVEL`, before
+/// the configuration is read; [`apply`] then takes the `[logging]` section
+pub fn init() {
+    let logger = logger();
+    if log::set_logger(logger).is_ok() {
+        log::set_max_level(logger.max_level());
+    }
+}
+
+/// Applies the `[logging]` section and logs the resulting setup; a file that
+/// cannot be opened leaves the console output only
+pub fn apply(config: &LoggingConfig) {
+    let logger = logger();
+    if let Err(e) = logger.set_level(&config.level) {
+        error!("{}; se mantiene el nivel anterior", e);
+    }
+
+    let output = match &config.file {
+        Some(path) => match RotatingFile::open(path, config) {
+            Ok(file) => Some((path, file)),
+            Err(e) => {
+                error!("No se pudo abrir el archivo de log {}: {}; solo se registra en la consola", path.display(), e);
+                None
+            }
+        },
+        None => None,
+    };
+    let destination = match &output {
+        Some((path, _)) => match config.rotation {
+            Rotation::Daily => format!("consola y {} (diario, {} archivos anteriores)", path.display(), config.max_files),
+            Rotation::Size => format!(
+                "consola y {} (cada {} bytes, {} archivos anteriores)",
+                path.display(),
+                config.max_file_size,
+                config.max_files
+            ),
+        },
+        None => "consola".to_string(),
+    };
+    logger.set_file(output.map(|(_, file)| Box::new(file) as Box<dyn Write + Send>));
+    log::set_max_level(logger.max_level());
+
+    info!("Registro: nivel {:?}, salida en {}", logger.status().level, destination);
+}
+
+/// Uses `spec` for `ttl`, then goes back to `logging.level`
+pub fn set_override(spec: &str, ttl: Duration) -> Result<LevelOverride, InvalidSpec> {
+    let logger = logger();
+    let level_override = logger.set_override(spec, ttl)?;
+    log::set_max_level(logger.max_level());
+    info!("Nivel de log {:?} hasta {}", spec, level_override.expires_at.to_rfc3339());
+    Ok(level_override)
+}
+
+/// Ends the override early
+pub fn clear_override() {
+    let logger = logger();
+    if logger.clear_override() {
+        log::set_max_level(logger.max_level());
+        info!("Nivel de log restablecido a {:?}", logger.status().level);
+    }
+}
+
+/// Levels in effect
+pub fn status() -> LogLevelStatus {
+    logger().status()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Console output kept in memory
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Capture {
+        fn lines(&self) -> Vec<String> {
+            String::from_utf8(self.0.lock().unwrap().clone())
+                .unwrap()
+                .lines()
+                .map(str::to_string)
+                .collect()
+        }
+    }
+
+    fn capturing(spec: &str) -> (Logger, Capture) {
+        let capture = Capture::default();
+        let logger = Logger::new(spec, Target::Pipe(Box::new(capture.clone()))).unwrap();
+        (logger, capture)
+    }
+
+    fn emit(logger: &Logger, level: log::Level, target: &str, message: &str) {
+        logger.log(&Record::builder().level(level).target(target).args(format_args!("{}", message)).build());
+    }
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sai-logging-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn day(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, DAILY_SUFFIX).unwrap()
+    }
+
+    #[test]
+    fn test_spec_validation() {
+        assert!(parse_spec("info").is_ok());
+        assert!(parse_spec("info, sqlx=warn ,sai::services=debug").is_ok());
+        assert!(parse_spec("sai::routes").is_ok());
+
+        assert!(parse_spec("").is_err());
+        assert!(parse_spec("sqlx=loud").unwrap_err().reason.contains("loud"));
+        assert!(parse_spec("sai services=debug").is_err());
+        assert!(parse_spec("info/regex").is_err());
+    }
+
+    #[test]
+    fn test_per_target_levels() {
+        let (logger, capture) = capturing("info,sqlx=warn,sai::services=debug");
+
+        emit(&logger, log::Level::Info, "sqlx::query", "sqlx info");
+        emit(&logger, log::Level::Warn, "sqlx::query", "sqlx warn");
+        emit(&logger, log::Level::Debug, "sai::services::users", "services debug");
+        emit(&logger, log::Level::Debug, "sai::routes", "routes debug");
+        emit(&logger, log::Level::Info, "sai::routes", "routes info");
+
+        let lines = capture.lines();
+        assert_eq!(lines.len(), 3, "{:?}", lines);
+        assert!(lines[0].ends_with("sqlx warn"));
+        assert!(lines[1].ends_with("services debug"));
+        assert!(lines[2].ends_with("routes info"));
+        assert_eq!(logger.max_level(), LevelFilter::Debug);
+    }
+
+    #[test]
+    fn test_override_reverts_after_ttl() {
+        let (logger, capture) = capturing("info");
+
+        let level_override = logger.set_override("debug", Duration::from_millis(50)).unwrap();
+        assert_eq!(logger.status().level_override, Some(level_override));
+        emit(&logger, log::Level::Debug, "sai", "while overridden");
+
+        std::thread::sleep(Duration::from_millis(80));
+        emit(&logger, log::Level::Debug, "sai", "after expiry");
+        emit(&logger, log::Level::Info, "sai", "still info");
+
+        let lines = capture.lines();
+        assert_eq!(lines.len(), 2, "{:?}", lines);
+        assert!(lines[0].ends_with("while overridden"));
+        assert!(lines[1].ends_with("still info"));
+        assert_eq!(logger.status().level_override, None);
+        assert_eq!(logger.status().level, "info");
+    }
+
+    #[test]
+    fn test_override_can_be_cleared_and_rejects_bad_specs() {
+        let (logger, _) = capturing("warn");
+
+        assert!(logger.set_override("sai=verbose", Duration::from_secs(60)).is_err());
+        assert!(!logger.clear_override());
+
+        logger.set_override("sai=trace", Duration::from_secs(60)).unwrap();
+        assert_eq!(logger.max_level(), LevelFilter::Trace);
+        assert!(logger.clear_override());
+        assert_eq!(logger.max_level(), LevelFilter::Warn);
+    }
+
+    #[test]
+    fn test_file_output_is_json() {
+        let (logger, _) = capturing("info");
+        let file = Capture::default();
+        logger.set_file(Some(Box::new(file.clone())));
+
+        emit(&logger, log::Level::Warn, "sai::services", "saldo \"negativo\"");
+
+        let lines = file.lines();
+        assert_eq!(lines.len(), 1);
+        let line: serde_json::Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(line["level"], "WARN");
+        assert_eq!(line["target"], "sai::services");
+        assert_eq!(line["message"], "saldo \"negativo\"");
+        assert!(line["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+
+    #[test]
+    fn test_rotation_file_names() {
+        let path = Path::new("/var/log/sai/sai.log");
+
+        assert_eq!(daily_path(path, day("2024-03-01")), Path::new("/var/log/sai/sai.log.2024-03-01"));
+        assert_eq!(numbered_path(path, 2), Path::new("/var/log/sai/sai.log.2"));
+    }
+
+    #[test]
+    fn test_daily_rotation_keeps_max_files() {
+        let dir = temp_dir();
+        let path = dir.join("sai.log");
+        let config = LoggingConfig { rotation: Rotation::Daily, max_files: 1, ..Default::default() };
+        let mut file = RotatingFile::open_on(&path, &config, day("2024-03-01")).unwrap();
+
+        for date in ["2024-03-01", "2024-03-02", "2024-03-03"] {
+            file.write_on(format!("{}\n", date).as_bytes(), day(date)).unwrap();
+        }
+
+        let mut names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(names, vec!["sai.log.2024-03-02", "sai.log.2024-03-03"]);
+    }
+
+    #[test]
+    fn test_size_rotation_shifts_numbered_files() {
+        let dir = temp_dir();
+        let path = dir.join("sai.log");
+        let config = LoggingConfig { rotation: Rotation::Size, max_file_size: 10, max_files: 2, ..Default::default() };
+        let mut file = RotatingFile::open(&path, &config).unwrap();
+
+        for line in ["first...\n", "second..\n", "third...\n", "fourth..\n"] {
+            file.write_on(line.as_bytes(), day("2024-03-01")).unwrap();
+        }
+
+        let read = |path: PathBuf| fs::read_to_string(path).unwrap();
+        assert_eq!(read(path.clone()), "fourth..\n");
+        assert_eq!(read(numbered_path(&path, 1)), "third...\n");
+        assert_eq!(read(numbered_path(&path, 2)), "second..\n");
+        assert!(!numbered_path(&path, 3).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
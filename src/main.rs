@@ -1,75 +1,727 @@
-use actix_web::{web, App, HttpServer, HttpResponse, Responder};
+use actix_web::{http::KeepAlive, middleware, web, App, HttpServer, HttpResponse, Responder};
+use clap::{Parser, Subcommand};
 use dotenv::dotenv;
-use std::env;
-use log::{info, error};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+use log::{info, warn, error};
 
 // Importamos nuestra biblioteca sai
-use sai::{models, routes, services, utils, db};
-
-// Estructura para configuración de la aplicación
-struct AppState {
-    db_pool: db::DbPool,
-}
+use sai::{routes, services, db, AppState};
+use sai::config::{AppConfig, ConfigError};
+use sai::db::backend::Backend;
+use sai::services::admin::CreateAdminError;
 
 // Manejador simple para la ruta principal
 async fn index() -> impl Responder {
     HttpResponse::Ok().body("¡Bienvenido al Sistema Administrativo Integral (SAI)!")
 }
 
-// Manejador para verificar el estado del servidor
-async fn health_check(data: web::Data<AppState>) -> impl Responder {
-    // Usamos el método de verificación de conexión de nuestro módulo db
-    match db::helpers::transaction(&data.db_pool, |_tx| Box::pin(async { 
-        Ok::<_, sqlx::Error>(sqlx::query("SELECT 1").execute(&data.db_pool).await?)
-    })).await {
-        Ok(_) => HttpResponse::Ok().body("¡El servidor está en funcionamiento y conectado a la base de datos!"),
-        Err(e) => {
-            error!("Error al verificar la conexión a la base de datos: {}", e);
-            HttpResponse::InternalServerError().body("Error de conexión a la base de datos")
+// Respuesta de salud mientras se reintenta la conexión a la base de datos
+async fn connecting_health_check() -> impl Responder {
+    HttpResponse::ServiceUnavailable().json(serde_json::json!({
+        "status": db::connection_state().as_str(),
+        "database": "unreachable"
+    }))
+}
+
+/// Sistema Administrativo Integral: servidor HTTP y tareas de administración
+#[derive(Debug, PartialEq, Parser)]
+#[command(name = "sai", version, about)]
+struct Cli {
+    /// Archivo de configuración TOML, en lugar de SAI_CONFIG o ./sai.toml
+    #[arg(long, global = true, value_name = "ARCHIVO")]
+    config: Option<PathBuf>,
+
+    /// Valida la configuración, informa todos los problemas y termina
+    #[arg(long)]
+    check_config: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, PartialEq, Subcommand)]
+enum Command {
+    /// Inicia el servidor HTTP (acción por defecto)
+    Serve,
+    /// Aplica las migraciones pendientes y termina
+    Migrate {
+        /// Solo lista las migraciones pendientes
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Carga o elimina los datos de demostración y termina
+    Seed {
+        /// Conjunto a cargar (minimal o demo); por defecto minimal
+        #[arg(conflicts_with = "demo")]
+        set: Option<String>,
+        /// Carga la escuela de demostración completa
+        #[arg(long)]
+        demo: bool,
+        /// Elimina los datos de demostración
+        #[arg(long, conflicts_with_all = ["set", "demo"])]
+        remove: bool,
+    },
+    /// Crea un usuario administrador y termina
+    CreateAdmin {
+        /// Correo del administrador
+        #[arg(long)]
+        email: String,
+        /// Nombre completo
+        #[arg(long)]
+        name: String,
+        /// Genera una contraseña temporal en lugar de pedirla
+        #[arg(long)]
+        generate_password: bool,
+    },
+    /// Carga un respaldo en una base de datos vacía y termina
+    RestoreBackup {
+        /// Archivo .tar.gz del respaldo
+        archive: PathBuf,
+        /// Institución de destino
+        #[arg(long, value_name = "SLUG")]
+        institution: Option<String>,
+    },
+}
+
+/// Códigos de salida, según la convención de sysexits.h
+mod exit {
+    /// Argumentos inválidos
+    pub const USAGE: u8 = 64;
+    /// Datos de entrada inválidos (correo, contraseña, respaldo)
+    pub const DATA: u8 = 65;
+    /// Base de datos inaccesible
+    pub const UNAVAILABLE: u8 = 69;
+    /// Error interno
+    pub const SOFTWARE: u8 = 70;
+    /// No se pudo crear el registro pedido (ya existe)
+    pub const CANT_CREATE: u8 = 73;
+    /// Error de lectura o escritura
+    pub const IO: u8 = 74;
+    /// Configuración inválida o incompleta
+    pub const CONFIG: u8 = 78;
+}
+
+/// Error de un subcomando y el código de salida que lo representa
+#[derive(Debug)]
+struct CommandError {
+    code: u8,
+    message: String,
+}
+
+impl CommandError {
+    fn new(code: u8, message: impl Display) -> Self {
+        Self { code, message: message.to_string() }
+    }
+
+    fn config(err: ConfigError) -> Self {
+        if let ConfigError::Missing(missing) = &err {
+            for value in missing {
+                error!("Valor de configuración requerido no definido: {}", value);
+            }
         }
+        Self::new(exit::CONFIG, err)
     }
 }
 
-// Función principal que configura y ejecuta el servidor
+impl From<std::io::Error> for CommandError {
+    fn from(err: std::io::Error) -> Self {
+        Self::new(exit::IO, err)
+    }
+}
+
+// Punto de entrada: interpreta los argumentos y ejecuta el subcomando
 #[actix_web::main]
-async fn main() -> std::io::Result<()> {
+async fn main() -> ExitCode {
     // Configuración de variables de entorno
     dotenv().ok();
     
-    // Inicializar el logger
-    env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
+    // Inicializar el logger con RUST_LOG o LOG_LEVEL hasta leer la sección [logging]
+    sai::logging::init();
+    
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            // --help y --version también llegan como "error" de clap
+            return if e.use_stderr() { ExitCode::from(exit::USAGE) } else { ExitCode::SUCCESS };
+        }
+    };
+    
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e.message);
+            ExitCode::from(e.code)
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<(), CommandError> {
+    let command = cli.command.unwrap_or(Command::Serve);
+    let config = AppConfig::read(cli.config.as_deref()).map_err(CommandError::config)?;
+    sai::logging::apply(&config.logging);
+    
+    // El servidor valida toda la configuración antes de abrir el puerto; el resto de
+    // los subcomandos solo necesita la base de datos
+    if cli.check_config || command == Command::Serve {
+        if let Err(report) = config.validate() {
+            for problem in &report.problems {
+                error!("Configuración inválida: {}: {}", problem.key, problem.message);
+            }
+            return Err(CommandError::new(exit::CONFIG, report));
+        }
+        for warning in config.profile_warnings() {
+            warn!("Configuración sospechosa: {}", warning);
+        }
+        if cli.check_config {
+            println!("La configuración es válida");
+            return Ok(());
+        }
+    }
+    let backend = detect_backend(&config)?;
+    
+    match command {
+        Command::Migrate { dry_run } => {
+            #[cfg(feature = "db-sqlite")]
+            if backend == Backend::Sqlite {
+                return run_sqlite_migrate_command(&config).await;
+            }
+            run_migrate_command(&config, dry_run).await
+        }
+        // Con SQLite solo está disponible la capa de datos (db::sqlite): los servicios usan PostgreSQL
+        _ if backend == Backend::Sqlite => Err(CommandError::new(
+            exit::USAGE,
+            "El servidor, seed, create-admin y restore-backup requieren PostgreSQL; con SQLite use `migrate` y la API de db::sqlite",
+        )),
+        Command::Serve => serve(config, cli.config).await,
+        Command::Seed { set, demo, remove } => {
+            let set = if demo { Some("demo".to_string()) } else { set };
+            run_seed_command(&config, set.as_deref(), remove).await
+        }
+        Command::CreateAdmin { email, name, generate_password } => {
+            run_create_admin_command(&config, &email, &name, generate_password).await
+        }
+        Command::RestoreBackup { archive, institution } => {
+            run_restore_backup_command(&config, &archive, institution.as_deref()).await
+        }
+    }
+}
+
+// Motor de base de datos según el esquema de `database.url` (Postgres si no está definida)
+fn detect_backend(config: &AppConfig) -> Result<Backend, CommandError> {
+    let backend = match &config.database.url {
+        Some(url) => Backend::from_url(url).map_err(|e| CommandError::new(exit::CONFIG, e))?,
+        None => Backend::Postgres,
+    };
+    db::backend::set_active(backend);
+    Ok(backend)
+}
+
+// Conexión para los subcomandos: solo exige `database.url`
+async fn connect(config: &AppConfig) -> Result<db::DbManager, CommandError> {
+    let missing: Vec<String> = config.missing().into_iter().filter(|value| value.starts_with("database.")).collect();
+    if !missing.is_empty() {
+        return Err(CommandError::config(ConfigError::Missing(missing)));
+    }
+    db::DbManager::from_settings(&config.database)
+        .await
+        .map_err(|e| CommandError::new(exit::UNAVAILABLE, format!("No se pudo conectar a la base de datos: {}", e)))
+}
+
+// Subcomando `migrate [--dry-run]`: aplica (o lista) las migraciones pendientes
+async fn run_migrate_command(config: &AppConfig, dry_run: bool) -> Result<(), CommandError> {
+    let manager = connect(config).await?;
+    let summary = db::migrate(manager.get_pool(), dry_run)
+        .await
+        .map_err(|e| CommandError::new(exit::SOFTWARE, e))?;
+
+    let verb = if dry_run { "Pendiente" } else { "Aplicada" };
+    for version in &summary.applied_now {
+        println!("{}: {}", verb, version);
+    }
+    println!(
+        "{} ya aplicadas, {} aplicadas ahora, {} pendientes",
+        summary.already_applied,
+        if dry_run { 0 } else { summary.applied_now.len() },
+        summary.pending_after.len()
+    );
+    Ok(())
+}
+
+// Subcomando `migrate` sobre SQLite: crea el archivo si no existe y aplica las migraciones de SQLite
+#[cfg(feature = "db-sqlite")]
+async fn run_sqlite_migrate_command(config: &AppConfig) -> Result<(), CommandError> {
+    let url = config.database.url.clone().unwrap_or_default();
+    let pool = db::sqlite::connect(&url)
+        .await
+        .map_err(|e| CommandError::new(exit::UNAVAILABLE, e))?;
+    let summary = db::sqlite::migrate(&pool)
+        .await
+        .map_err(|e| CommandError::new(exit::SOFTWARE, e))?;
+
+    for version in &summary.applied_now {
+        println!("Aplicada: {}", version);
+    }
+    println!(
+        "{} ya aplicadas, {} aplicadas ahora",
+        summary.already_applied,
+        summary.applied_now.len()
+    );
+    Ok(())
+}
+
+// Recarga la configuración cada vez que el proceso recibe SIGHUP (p. ej. `systemctl reload sai`)
+#[cfg(unix)]
+fn reload_on_sighup(state: AppState) {
+    use actix_rt::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            error!("No se pudo escuchar SIGHUP; la configuración solo se recarga por la API: {}", e);
+            return;
+        }
+    };
+    actix_rt::spawn(async move {
+        while hangup.recv().await.is_some() {
+            info!("SIGHUP recibido: recargando la configuración");
+            // El resultado ya queda en el log y en la auditoría
+            let _ = state.reload_config(Default::default()).await;
+        }
+    });
+}
+
+// Subcomando `seed [minimal|demo] [--demo]` o `seed --remove`: carga (o elimina) los datos de demostración
+async fn run_seed_command(config: &AppConfig, set: Option<&str>, remove: bool) -> Result<(), CommandError> {
+    db::seed::ensure_allowed(config.environment).map_err(|e| CommandError::new(exit::CONFIG, e))?;
+    let set = match set {
+        Some(name) => name.parse().map_err(|e| CommandError::new(exit::USAGE, e))?,
+        None => db::seed::SeedSet::Minimal,
+    };
+
+    let manager = connect(config).await?;
+    db::migrate(manager.get_pool(), false)
+        .await
+        .map_err(|e| CommandError::new(exit::SOFTWARE, e))?;
+
+    if remove {
+        let deleted = db::seed::unseed(manager.get_pool())
+            .await
+            .map_err(|e| CommandError::new(exit::SOFTWARE, e))?;
+        println!("{} filas de demostración eliminadas", deleted);
+        return Ok(());
+    }
+
+    let summary = db::seed::seed(manager.get_pool(), set)
+        .await
+        .map_err(|e| CommandError::new(exit::SOFTWARE, e))?;
+    println!(
+        "{} filas creadas: {} usuarios, {} profesores, {} estudiantes, {} aulas, {} cursos, {} inscripciones, {} evaluaciones, {} pagos",
+        summary.total(),
+        summary.users,
+        summary.teachers,
+        summary.students,
+        summary.classrooms,
+        summary.courses,
+        summary.enrollments,
+        summary.assessments,
+        summary.payments
+    );
+    println!("Administrador: {} / {}", db::seed::ADMIN_EMAIL, db::seed::DEMO_PASSWORD);
+    Ok(())
+}
+
+// Subcomando `create-admin --email <correo> --name <nombre> [--generate-password]`
+async fn run_create_admin_command(
+    config: &AppConfig,
+    email: &str,
+    name: &str,
+    generate_password: bool,
+) -> Result<(), CommandError> {
+    let password = if generate_password { None } else { Some(prompt_password()?) };
+
+    let manager = connect(config).await?;
+    let created = services::AdminService::new(Arc::new(manager.get_pool().clone()))
+        .with_lowercase_email_local_part(config.mail.lowercase_local_part)
+        .create_admin(email, name, password.as_deref())
+        .await
+        .map_err(|e| {
+            let code = match e {
+                CreateAdminError::InvalidEmail(_) | CreateAdminError::EmptyName | CreateAdminError::WeakPassword => exit::DATA,
+                CreateAdminError::EmailTaken(_) => exit::CANT_CREATE,
+                CreateAdminError::Database(_) => exit::SOFTWARE,
+            };
+            CommandError::new(code, e)
+        })?;
+
+    println!("Administrador creado: {} ({})", created.user.email, created.user.id);
+    if let Some(password) = created.generated_password {
+        println!("Contraseña temporal: {}", password);
+        println!("Se pedirá cambiarla en el primer ingreso");
+    }
+    Ok(())
+}
+
+// Crea el primer administrador y entrega su contraseña temporal una sola vez: en
+// `jobs.bootstrap_password_file` (solo legible por el dueño) o, si no está definido, en el log
+async fn bootstrap_admin(pool: &sqlx::PgPool, email: &str, config: &AppConfig) {
+    let jobs = &config.jobs;
+    let name = jobs.bootstrap_admin_name.as_deref().unwrap_or("Administrador");
+    let service = services::AdminService::new(Arc::new(pool.clone()))
+        .with_lowercase_email_local_part(config.mail.lowercase_local_part);
+    let created = match service.bootstrap_admin(email, name).await {
+        Ok(Some(created)) => created,
+        Ok(None) => return,
+        Err(e) => {
+            error!("No se pudo crear el administrador inicial {}: {}", email, e);
+            return;
+        }
+    };
+    let password = created.generated_password.unwrap_or_default();
+
+    match &jobs.bootstrap_password_file {
+        Some(path) => match write_private_file(path, &password) {
+            Ok(()) => warn!(
+                "Administrador inicial {} creado; su contraseña temporal está en {}. Se pedirá cambiarla en el primer ingreso",
+                created.user.email,
+                path.display()
+            ),
+            Err(e) => error!(
+                "Administrador inicial {} creado, pero no se pudo escribir su contraseña en {} ({}); use el restablecimiento de contraseña",
+                created.user.email,
+                path.display(),
+                e
+            ),
+        },
+        None => warn!(
+            "Administrador inicial {} creado con la contraseña temporal {} (no se volverá a mostrar). Se pedirá cambiarla en el primer ingreso",
+            created.user.email, password
+        ),
+    }
+}
+
+// Escribe `contents` en `path` dejando el archivo con permisos 0600, aunque ya existiera
+fn write_private_file(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    writeln!(file, "{}", contents)
+}
+
+// Pide la contraseña dos veces sin mostrarla; sin terminal hay que usar --generate-password
+fn prompt_password() -> Result<String, CommandError> {
+    let no_terminal = |e: std::io::Error| {
+        CommandError::new(exit::USAGE, format!("No se pudo leer la contraseña ({}); use --generate-password", e))
+    };
+    let password = rpassword::prompt_password("Contraseña: ").map_err(no_terminal)?;
+    let confirmation = rpassword::prompt_password("Repita la contraseña: ").map_err(no_terminal)?;
+    if password != confirmation {
+        return Err(CommandError::new(exit::DATA, "Las contraseñas no coinciden"));
+    }
+    Ok(password)
+}
+
+// Subcomando `restore-backup <archivo> [--institution <slug>]`: carga un respaldo en una base vacía
+async fn run_restore_backup_command(config: &AppConfig, path: &Path, institution: Option<&str>) -> Result<(), CommandError> {
+    let tenant = institution
+        .map(db::tenant::TenantId::parse)
+        .transpose()
+        .map_err(|e| CommandError::new(exit::USAGE, e))?;
+    let archive = std::fs::read(path)?;
+
+    let manager = connect(config).await?;
+    let pool = manager.get_pool();
+    // El destino se migra (o se provee el esquema de la institución) antes de cargar los datos
+    match &tenant {
+        Some(tenant) => db::tenant::provision(pool, tenant)
+            .await
+            .map(drop)
+            .map_err(|e| CommandError::new(exit::SOFTWARE, e))?,
+        None => db::migrate(pool, false)
+            .await
+            .map(drop)
+            .map_err(|e| CommandError::new(exit::SOFTWARE, e))?,
+    }
+
+    let summary = services::AdminService::new(Arc::new(pool.clone()))
+        .restore_backup(tenant, &archive)
+        .await
+        .map_err(|e| {
+            let code = match e {
+                services::admin::BackupError::Database(_) => exit::SOFTWARE,
+                _ => exit::DATA,
+            };
+            CommandError::new(code, e)
+        })?;
+    for table in &summary.tables {
+        println!("{}: {} filas", table.name, table.rows);
+    }
+    println!("{} filas restauradas en {} tablas", summary.rows(), summary.tables.len());
+    Ok(())
+}
+
+// Abre en `$http` los listeners de la sección [server]: el socket unix de `socket_path`
+// (quitando el que haya dejado una ejecución anterior) y el puerto TCP si corresponde.
+// Es una macro porque el tipo de `HttpServer` depende de la fábrica de cada servidor
+macro_rules! bind_listeners {
+    ($http:expr, $server:expr) => {{
+        let server: &sai::config::ServerConfig = $server;
+        #[allow(unused_mut)]
+        let mut http = $http;
+        #[cfg(unix)]
+        if let Some(path) = &server.socket_path {
+            let mode = server
+                .socket_permissions()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+            http = http.listen_uds(sai::listener::bind_unix(path, mode)?)?;
+        }
+        if server.listens_on_tcp() {
+            http = http.bind(server.address())?;
+        }
+        http
+    }};
+}
+
+// Subcomando `serve`: configura y ejecuta el servidor
+async fn serve(config: AppConfig, config_file: Option<PathBuf>) -> Result<(), CommandError> {
+    info!("Entorno {}; configuración efectiva: {:?}", config.environment, config);
+    let server = config.server.clone();
+    let frontend = config.frontend.clone();
+    routes::response::set_verbose_errors(server.verbose_errors.unwrap_or(false));
+    routes::extractors::set_max_payload_size(server.max_payload_size);
+    
+    // Mientras se reintenta la conexión, los endpoints de salud responden "connecting"
+    let bootstrap = bind_listeners!(
+        HttpServer::new(|| {
+            App::new()
+                .route("/health", web::get().to(connecting_health_check))
+                .route("/system/health", web::get().to(connecting_health_check))
+        })
+        .workers(1),
+        &server
+    )
+    .run();
+    let bootstrap_handle = bootstrap.handle();
+    actix_rt::spawn(bootstrap);
     
     // Inicializar la conexión a la base de datos usando nuestro módulo db
-    // Esto incluye verificación de conexión e inicialización del esquema si es necesario
-    let pool = db::initialize_db().await;
+    // Esto incluye reintentos con espera exponencial (DATABASE_CONNECT_*), verificación
+    // de conexión e inicialización del esquema si es necesario.
+    // En producción se niega a arrancar con migraciones pendientes (ver ALLOW_PENDING_MIGRATIONS)
+    let manager = db::initialize_db_manager(&config).await;
+    bootstrap_handle.stop(true).await;
+    let manager = manager.map_err(|e| CommandError::new(exit::UNAVAILABLE, format!("No se pudo inicializar la base de datos: {}", e)))?;
+    let manager = Arc::new(manager);
+    let pools = manager.pools();
+    
+    // Entornos de vista previa efímeros: SEED_ON_STARTUP=minimal|demo carga los datos de demostración
+    match db::seed::seed_on_startup(pools.primary(), config.environment, config.jobs.seed_on_startup.as_deref()).await {
+        Ok(Some(summary)) => info!("Datos de demostración cargados: {} filas nuevas", summary.total()),
+        Ok(None) => {}
+        Err(e) => error!("No se pudieron cargar los datos de demostración: {}", e),
+    }
     
-    // Dirección del servidor
-    let host = env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
-    let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
-    let server_url = format!("{}:{}", host, port);
+    // Instalación nueva: SAI_BOOTSTRAP_ADMIN_EMAIL crea el primer administrador si no hay ninguno
+    if let Some(email) = config.jobs.bootstrap_admin_email.as_deref() {
+        bootstrap_admin(pools.primary(), email, &config).await;
+    }
+    
+    // Verificación de seguridad: en producción los hallazgos graves impiden iniciar
+    let check = sai::startup::self_check(&config, pools.primary()).await;
+    check.log();
+    if check.blocks_serving(config.environment) {
+        return Err(CommandError::new(
+            exit::CONFIG,
+            format!(
+                "La verificación de seguridad encontró {} problema(s) que impiden iniciar en production",
+                check.errors().count()
+            ),
+        ));
+    }
+    
+    // Estado compartido: pool, servicios y configuración, una sola instancia para todos los workers.
+    // Conserva el manager de la conexión inicial, que responde los endpoints de salud
+    let state = AppState::with_manager(manager, config).with_config_file(config_file);
+    if let Err(e) = state.services.features.load_overrides().await {
+        error!("No se pudieron leer los flags de funcionalidad guardados; se usan los de la configuración: {}", e);
+    }
+    // Versiones de token de las cuentas con sesiones cerradas: los tokens anteriores siguen rechazados
+    if let Err(e) = routes::auth::load_token_versions(pools.primary()).await {
+        error!("No se pudieron leer las versiones de token; los tokens revocados valen hasta vencer: {}", e);
+    }
+    
+    // Puente LISTEN/NOTIFY: invalidaciones de caché y eventos entre instancias.
+    // Sin él cada instancia sigue funcionando, pero con cachés que pueden quedar desactualizadas
+    let _bridge = if db::backend::capabilities().listen_notify {
+        db::pubsub::install_publisher(pools.primary().clone());
+        match db::pubsub::spawn_listener(
+            pools.primary(),
+            &[db::pubsub::CACHE_INVALIDATION_CHANNEL, db::pubsub::EVENTS_CHANNEL],
+            &db::RetryPolicy::from_env(),
+            {
+                let features = state.services.features.clone();
+                move |notification| routes::handle_bridge_notification(&features, notification)
+            },
+        )
+        .await
+        {
+            Ok(handle) => Some(handle),
+            Err(e) => {
+                error!("No se pudo iniciar el puente de notificaciones entre instancias: {}", e);
+                None
+            }
+        }
+    } else {
+        info!("{} no admite LISTEN/NOTIFY: puente entre instancias desactivado", db::backend::active().as_str());
+        None
+    };
     
-    info!("Iniciando servidor en http://{}", server_url);
+    // SIGHUP vuelve a leer la configuración: [mail], [features] y [logging] se aplican sin reiniciar
+    #[cfg(unix)]
+    reload_on_sighup(state.clone());
     
-    // Configuración y ejecución del servidor
-    HttpServer::new(move || {
+    if let Some(dir) = &frontend.dir {
+        if cfg!(feature = "frontend") {
+            info!("Interfaz web en /app desde {}", dir.display());
+        } else {
+            warn!("frontend.dir está definido, pero este binario se compiló sin la funcionalidad frontend: /app no se sirve");
+        }
+    }
+    
+    // Configuración y ejecución del servidor según la sección [server]
+    let compress = server.enable_compression;
+    let tuning = server.tuning();
+    let mut http = HttpServer::new(move || {
         App::new()
-            // Compartir el estado de la aplicación con los manejadores
-            .app_data(web::Data::new(AppState {
-                db_pool: pool.clone(),
-            }))
+            .wrap(middleware::Condition::new(compress, middleware::Compress::default()))
+            // Estado, pool y servicios con los tipos que extraen los manejadores
+            .configure(|cfg| routes::configure_app_data_with_state(cfg, &pools, &state))
             // Configuración de rutas básicas
             .route("/", web::get().to(index))
-            .route("/health", web::get().to(health_check))
-            // Register API routes with database pool available to all routes
-            .service(web::scope("")
-                .app_data(web::Data::clone(&web::Data::new(AppState {
-                    db_pool: pool.clone(),
-                })))
-                .service(routes::configure())
-                .service(routes::configure_system_routes())
-            )
+            .route("/health", web::get().to(routes::health_check))
+            .service(routes::configure())
+            .service(routes::configure_system_routes())
+            // Interfaz web en /app; /api y /system no llegan a sus archivos
+            .configure(|cfg| routes::configure_frontend(cfg, &frontend))
     })
-    .bind(&server_url)?
-    .run()
-    .await
+    .keep_alive(tuning.keep_alive.map_or(KeepAlive::Disabled, KeepAlive::Timeout))
+    .client_request_timeout(tuning.client_request_timeout)
+    .client_disconnect_timeout(tuning.client_disconnect_timeout)
+    // Antes de bind: el backlog se fija al abrir el socket
+    .backlog(tuning.backlog)
+    .max_connections(tuning.max_connections);
+    if let Some(workers) = tuning.workers {
+        http = http.workers(workers);
+    }
+    
+    info!(
+        "Iniciando servidor en {} con {}, cuerpos de hasta {} bytes y compresión {}",
+        server.endpoints().join(" y "),
+        tuning,
+        server.max_payload_size,
+        if server.enable_compression { "activada" } else { "desactivada" }
+    );
+    bind_listeners!(http, &server).run().await?;
+    // Al terminar no queda nadie escuchando en el socket
+    if let Some(path) = &server.socket_path {
+        let _ = std::fs::remove_file(path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(std::iter::once("sai").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn test_parse_valid_commands() {
+        let cases: &[(&[&str], Option<Command>)] = &[
+            (&[], None),
+            (&["serve"], Some(Command::Serve)),
+            (&["migrate"], Some(Command::Migrate { dry_run: false })),
+            (&["migrate", "--dry-run"], Some(Command::Migrate { dry_run: true })),
+            (&["seed"], Some(Command::Seed { set: None, demo: false, remove: false })),
+            (&["seed", "--demo"], Some(Command::Seed { set: None, demo: true, remove: false })),
+            (&["seed", "minimal"], Some(Command::Seed { set: Some("minimal".to_string()), demo: false, remove: false })),
+            (&["seed", "--remove"], Some(Command::Seed { set: None, demo: false, remove: true })),
+            (
+                &["create-admin", "--email", "admin@colegio.edu.py", "--name", "Ana Pérez"],
+                Some(Command::CreateAdmin {
+                    email: "admin@colegio.edu.py".to_string(),
+                    name: "Ana Pérez".to_string(),
+                    generate_password: false,
+                }),
+            ),
+            (
+                &["create-admin", "--name", "Ana", "--email", "a@b.py", "--generate-password"],
+                Some(Command::CreateAdmin { email: "a@b.py".to_string(), name: "Ana".to_string(), generate_password: true }),
+            ),
+            (
+                &["restore-backup", "respaldo.tar.gz", "--institution", "colegio"],
+                Some(Command::RestoreBackup {
+                    archive: PathBuf::from("respaldo.tar.gz"),
+                    institution: Some("colegio".to_string()),
+                }),
+            ),
+        ];
+
+        for (args, expected) in cases {
+            let cli = parse(args).unwrap_or_else(|e| panic!("{:?}: {}", args, e));
+            assert_eq!(&cli.command, expected, "{:?}", args);
+            assert_eq!(cli.config, None, "{:?}", args);
+            assert!(!cli.check_config, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_check_config_flag() {
+        let cli = parse(&["--check-config"]).unwrap();
+        assert!(cli.check_config);
+        assert_eq!(cli.command, None);
+
+        let cli = parse(&["--check-config", "--config", "/etc/sai.toml"]).unwrap();
+        assert!(cli.check_config);
+        assert_eq!(cli.config, Some(PathBuf::from("/etc/sai.toml")));
+    }
+
+    #[test]
+    fn test_config_flag_before_or_after_the_command() {
+        for args in [&["--config", "/etc/sai.toml", "migrate"][..], &["migrate", "--config", "/etc/sai.toml"][..]] {
+            let cli = parse(args).unwrap();
+            assert_eq!(cli.config, Some(PathBuf::from("/etc/sai.toml")), "{:?}", args);
+            assert_eq!(cli.command, Some(Command::Migrate { dry_run: false }));
+        }
+    }
+
+    #[test]
+    fn test_parse_invalid_commands() {
+        let cases: &[&[&str]] = &[
+            &["unknown"],
+            &["migrate", "--force"],
+            &["seed", "demo", "--demo"],
+            &["seed", "--demo", "--remove"],
+            &["create-admin", "--email", "a@b.py"],
+            &["create-admin", "--name", "Ana"],
+            &["restore-backup"],
+            &["--config"],
+        ];
+
+        for args in cases {
+            let err = parse(args).err().unwrap_or_else(|| panic!("{:?} should not parse", args));
+            assert!(err.use_stderr(), "{:?}", args);
+        }
+    }
 }
@@ -1,8 +1,10 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Postgres, Transaction};
+use sqlx::{FromRow, Pool, Postgres, QueryBuilder, Transaction};
 use uuid::Uuid;
 
+use crate::db::{DbError, FieldError};
+
 /// Represents the type of assessment
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -16,8 +18,61 @@ pub enum AssessmentType {
     Other(String),
 }
 
+impl AssessmentType {
+    /// Name stored in the `assessment_type` column; `Other` stores its own label
+    pub fn as_str(&self) -> &str {
+        match self {
+            AssessmentType::Quiz => "quiz",
+            AssessmentType::Test => "test",
+            AssessmentType::Assignment => "assignment",
+            AssessmentType::Project => "project",
+            AssessmentType::Exam => "exam",
+            AssessmentType::Participation => "participation",
+            AssessmentType::Other(label) => label,
+        }
+    }
+}
+
+impl From<String> for AssessmentType {
+    /// Known names map to their variant; anything else is kept as `Other`
+    fn from(text: String) -> Self {
+        match text.as_str() {
+            "quiz" => AssessmentType::Quiz,
+            "test" => AssessmentType::Test,
+            "assignment" => AssessmentType::Assignment,
+            "project" => AssessmentType::Project,
+            "exam" => AssessmentType::Exam,
+            "participation" => AssessmentType::Participation,
+            _ => AssessmentType::Other(text),
+        }
+    }
+}
+
+// Stored as text, like `EnrollmentStatus`
+impl sqlx::Type<Postgres> for AssessmentType {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <str as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <str as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for AssessmentType {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(<String as sqlx::Decode<Postgres>>::decode(value)?.into())
+    }
+}
+
+impl<'q> sqlx::Encode<'q, Postgres> for AssessmentType {
+    fn encode_by_ref(&self, buf: &mut sqlx::postgres::PgArgumentBuffer) -> sqlx::encode::IsNull {
+        <&str as sqlx::Encode<Postgres>>::encode(self.as_str(), buf)
+    }
+}
+
 /// Represents an assessment record in the database
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct Assessment {
     pub id: Uuid,
     pub enrollment_id: Uuid,
@@ -30,6 +85,8 @@ pub struct Assessment {
     pub weight: f64,
     pub assessment_date: DateTime<Utc>,
     pub is_final: bool,
+    /// Grading period (trimester) of the academic year, starting at 1
+    pub period: i16,
     pub comments: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -48,9 +105,16 @@ pub struct NewAssessment {
     pub weight: f64,
     pub assessment_date: DateTime<Utc>,
     pub is_final: bool,
+    /// Grading period (trimester); defaults to the first one
+    #[serde(default = "default_period")]
+    pub period: i16,
     pub comments: Option<String>,
 }
 
+fn default_period() -> i16 {
+    1
+}
+
 /// Represents the data needed to update an existing assessment
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct AssessmentUpdate {
@@ -62,6 +126,7 @@ pub struct AssessmentUpdate {
     pub weight: Option<f64>,
     pub assessment_date: Option<DateTime<Utc>>,
     pub is_final: Option<bool>,
+    pub period: Option<i16>,
     pub comments: Option<String>,
 }
 
@@ -84,7 +149,7 @@ impl Assessment {
     pub async fn create(
         pool: &Pool<Postgres>,
         new_assessment: NewAssessment,
-    ) -> Result<Self, sqlx::Error> {
+    ) -> Result<Self, DbError> {
         // Validate the new assessment data
         Self::validate_new_assessment(&new_assessment)?;
 
@@ -93,13 +158,13 @@ impl Assessment {
             r#"
             INSERT INTO assessments (
                 enrollment_id, course_id, assessment_type, title, description,
-                score, max_score, weight, assessment_date, is_final, comments
+                score, max_score, weight, assessment_date, is_final, period, comments
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
             RETURNING
                 id, enrollment_id, course_id, assessment_type as "assessment_type: AssessmentType",
                 title, description, score, max_score, weight, assessment_date,
-                is_final, comments, created_at, updated_at
+                is_final, period, comments, created_at, updated_at
             "#,
             new_assessment.enrollment_id,
             new_assessment.course_id,
@@ -111,6 +176,7 @@ impl Assessment {
             new_assessment.weight,
             new_assessment.assessment_date,
             new_assessment.is_final,
+            new_assessment.period,
             new_assessment.comments
         )
         .fetch_one(pool)
@@ -120,14 +186,14 @@ impl Assessment {
     }
 
     /// Get an assessment by its ID
-    pub async fn get_by_id(pool: &Pool<Postgres>, id: Uuid) -> Result<Self, sqlx::Error> {
+    pub async fn get_by_id(pool: &Pool<Postgres>, id: Uuid) -> Result<Self, DbError> {
         let assessment = sqlx::query_as!(
             Assessment,
             r#"
             SELECT
                 id, enrollment_id, course_id, assessment_type as "assessment_type: AssessmentType",
                 title, description, score, max_score, weight, assessment_date,
-                is_final, comments, created_at, updated_at
+                is_final, period, comments, created_at, updated_at
             FROM assessments
             WHERE id = $1
             "#,
@@ -135,7 +201,7 @@ impl Assessment {
         )
         .fetch_optional(pool)
         .await?
-        .ok_or(sqlx::Error::RowNotFound)?;
+        .ok_or_else(|| DbError::NotFound("Assessment not found".to_string()))?;
 
         Ok(assessment)
     }
@@ -144,78 +210,44 @@ impl Assessment {
     pub async fn get_by_filter(
         pool: &Pool<Postgres>,
         filter: AssessmentFilter,
-    ) -> Result<Vec<Self>, sqlx::Error> {
-        let mut query = "
-            SELECT
-                id, enrollment_id, course_id, assessment_type as \"assessment_type: AssessmentType\",
-                title, description, score, max_score, weight, assessment_date,
-                is_final, comments, created_at, updated_at
-            FROM assessments
-            WHERE 1 = 1"
-            .to_string();
-
-        let mut params = Vec::new();
-        let mut param_index = 1;
+    ) -> Result<Vec<Self>, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "SELECT id, enrollment_id, course_id, assessment_type, title, description, score, \
+             max_score, weight, assessment_date, is_final, period, comments, created_at, updated_at \
+             FROM assessments WHERE 1 = 1",
+        );
 
         if let Some(enrollment_id) = filter.enrollment_id {
-            query.push_str(&format!(" AND enrollment_id = ${}", param_index));
-            params.push(enrollment_id.to_string());
-            param_index += 1;
+            query.push(" AND enrollment_id = ").push_bind(enrollment_id);
         }
-
         if let Some(course_id) = filter.course_id {
-            query.push_str(&format!(" AND course_id = ${}", param_index));
-            params.push(course_id.to_string());
-            param_index += 1;
+            query.push(" AND course_id = ").push_bind(course_id);
         }
-
         if let Some(assessment_type) = filter.assessment_type {
-            query.push_str(&format!(" AND assessment_type = ${}", param_index));
-            params.push(format!("{:?}", assessment_type).to_lowercase());
-            param_index += 1;
+            query.push(" AND assessment_type = ").push_bind(assessment_type);
         }
-
         if let Some(title) = filter.title {
-            query.push_str(&format!(" AND title ILIKE ${}", param_index));
-            params.push(format!("%{}%", title));
-            param_index += 1;
+            query.push(" AND title ILIKE ").push_bind(format!("%{}%", title));
         }
-
         if let Some(is_final) = filter.is_final {
-            query.push_str(&format!(" AND is_final = ${}", param_index));
-            params.push(is_final.to_string());
-            param_index += 1;
+            query.push(" AND is_final = ").push_bind(is_final);
         }
-
         if let Some(min_score) = filter.min_score {
-            query.push_str(&format!(" AND score >= ${}", param_index));
-            params.push(min_score.to_string());
-            param_index += 1;
+            query.push(" AND score >= ").push_bind(min_score);
         }
-
         if let Some(max_score) = filter.max_score {
-            query.push_str(&format!(" AND score <= ${}", param_index));
-            params.push(max_score.to_string());
-            param_index += 1;
+            query.push(" AND score <= ").push_bind(max_score);
         }
-
         if let Some(start_date) = filter.start_date {
-            query.push_str(&format!(" AND assessment_date >= ${}", param_index));
-            params.push(start_date.to_rfc3339());
-            param_index += 1;
+            query.push(" AND assessment_date >= ").push_bind(start_date);
         }
-
         if let Some(end_date) = filter.end_date {
-            query.push_str(&format!(" AND assessment_date <= ${}", param_index));
-            params.push(end_date.to_rfc3339());
-            param_index += 1;
+            query.push(" AND assessment_date <= ").push_bind(end_date);
         }
 
-        query.push_str(" ORDER BY assessment_date DESC");
+        query.push(" ORDER BY assessment_date DESC");
 
-        let assessments = sqlx::query_as(&query)
-            .fetch_all(pool)
-            .await?;
+        let assessments = query.build_query_as::<Assessment>().fetch_all(pool).await?;
 
         Ok(assessments)
     }
@@ -225,12 +257,10 @@ impl Assessment {
         pool: &Pool<Postgres>,
         id: Uuid,
         update: AssessmentUpdate,
-    ) -> Result<Self, sqlx::Error> {
-        // Validate the update data
-        Self::validate_update(&update)?;
-
+    ) -> Result<Self, DbError> {
         let current = Self::get_by_id(pool, id).await?;
-        
+        Self::validate_update(&current, &update)?;
+
         let assessment = sqlx::query_as!(
             Assessment,
             r#"
@@ -245,12 +275,13 @@ impl Assessment {
                 assessment_date = COALESCE($7, assessment_date),
                 is_final = COALESCE($8, is_final),
                 comments = COALESCE($9, comments),
+                period = COALESCE($10, period),
                 updated_at = NOW()
-            WHERE id = $10
+            WHERE id = $11
             RETURNING
                 id, enrollment_id, course_id, assessment_type as "assessment_type: AssessmentType",
                 title, description, score, max_score, weight, assessment_date,
-                is_final, comments, created_at, updated_at
+                is_final, period, comments, created_at, updated_at
             "#,
             update.assessment_type as _,
             update.title,
@@ -261,6 +292,7 @@ impl Assessment {
             update.assessment_date,
             update.is_final,
             update.comments,
+            update.period,
             id
         )
         .fetch_one(pool)
@@ -270,7 +302,7 @@ impl Assessment {
     }
 
     /// Delete an assessment by its ID
-    pub async fn delete(pool: &Pool<Postgres>, id: Uuid) -> Result<(), sqlx::Error> {
+    pub async fn delete(pool: &Pool<Postgres>, id: Uuid) -> Result<(), DbError> {
         sqlx::query!("DELETE FROM assessments WHERE id = $1", id)
             .execute(pool)
             .await?;
@@ -279,62 +311,90 @@ impl Assessment {
     }
 
     /// Create multiple assessments in a transaction
+    ///
+    /// The first invalid or rejected assessment fails the whole call; the caller
+    /// is expected to roll the transaction back.
     pub async fn create_batch(
         tx: &mut Transaction<'_, Postgres>,
         assessments: Vec<NewAssessment>,
-    ) -> Result<Vec<Self>, sqlx::Error> {
-        let mut created_assessments = Vec::new();
+    ) -> Result<Vec<Self>, DbError> {
+        let mut created_assessments = Vec::with_capacity(assessments.len());
 
         for assessment in assessments {
-            // Validate each assessment
-            Self::validate_new_assessment(&assessment)?;
-
-            let created = sqlx::query_as!(
-                Assessment,
-                r#"
-                INSERT INTO assessments (
-                    enrollment_id, course_id, assessment_type, title, description,
-                    score, max_score, weight, assessment_date, is_final, comments
-                )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-                RETURNING
-                    id, enrollment_id, course_id, assessment_type as "assessment_type: AssessmentType",
-                    title, description, score, max_score, weight, assessment_date,
-                    is_final, comments, created_at, updated_at
-                "#,
-                assessment.enrollment_id,
-                assessment.course_id,
-                assessment.assessment_type as _,
-                assessment.title,
-                assessment.description,
-                assessment.score,
-                assessment.max_score,
-                assessment.weight,
-                assessment.assessment_date,
-                assessment.is_final,
-                assessment.comments
-            )
-            .fetch_one(&mut **tx)
-            .await?;
-
-            created_assessments.push(created);
+            created_assessments.push(Self::insert(tx, assessment).await?);
         }
 
         Ok(created_assessments)
     }
 
+    /// Create multiple assessments in a transaction, each one in its own savepoint
+    ///
+    /// A failing assessment only rolls back its own insert and its error is
+    /// returned at its position; the others stay in the transaction.
+    pub async fn create_batch_lenient(
+        tx: &mut Transaction<'_, Postgres>,
+        assessments: Vec<NewAssessment>,
+    ) -> Vec<Result<Self, DbError>> {
+        let mut results = Vec::with_capacity(assessments.len());
+
+        for assessment in assessments {
+            results.push(crate::savepoint!(tx, "assessment_item", |sp| { Self::insert(sp, assessment).await }).await);
+        }
+
+        results
+    }
+
+    /// Validate and insert one assessment inside a transaction
+    async fn insert(tx: &mut Transaction<'_, Postgres>, assessment: NewAssessment) -> Result<Self, DbError> {
+        Self::validate_new_assessment(&assessment)?;
+
+        let created = sqlx::query_as!(
+            Assessment,
+            r#"
+            INSERT INTO assessments (
+                enrollment_id, course_id, assessment_type, title, description,
+                score, max_score, weight, assessment_date, is_final, period, comments
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING
+                id, enrollment_id, course_id, assessment_type as "assessment_type: AssessmentType",
+                title, description, score, max_score, weight, assessment_date,
+                is_final, period, comments, created_at, updated_at
+            "#,
+            assessment.enrollment_id,
+            assessment.course_id,
+            assessment.assessment_type as _,
+            assessment.title,
+            assessment.description,
+            assessment.score,
+            assessment.max_score,
+            assessment.weight,
+            assessment.assessment_date,
+            assessment.is_final,
+            assessment.period,
+            assessment.comments
+        )
+        .fetch_one(&mut **tx)
+        .await?;
+
+        Ok(created)
+    }
+
     /// Calculate the weighted average of all assessments for a student in a course
+    ///
+    /// Returns `None` when there is nothing to average: the student has no
+    /// graded assessments in the course, or all of them weigh zero.
     pub async fn calculate_weighted_average(
         pool: &Pool<Postgres>,
         enrollment_id: Uuid,
         course_id: Uuid,
-    ) -> Result<f64, sqlx::Error> {
+    ) -> Result<Option<f64>, DbError> {
         let result = sqlx::query!(
             r#"
             SELECT 
-                SUM(score * weight) / SUM(weight) as weighted_average
+                (SUM(score * weight) / NULLIF(SUM(weight), 0))::float8 as weighted_average
             FROM assessments
-            WHERE enrollment_id = $1 AND course_id = $2
+            WHERE enrollment_id = $1 AND course_id = $2 AND score IS NOT NULL
             "#,
             enrollment_id,
             course_id
@@ -342,48 +402,41 @@ impl Assessment {
         .fetch_one(pool)
         .await?;
 
-        Ok(result.weighted_average.unwrap_or(0.0))
+        Ok(result.weighted_average)
     }
 
     /// Calculate the overall grade based on weighted average and grading scale
+    ///
+    /// `None` means the student has not been graded yet ([`UNGRADED`]), which
+    /// is not the same as failing.
     pub async fn calculate_grade(
         pool: &Pool<Postgres>,
         enrollment_id: Uuid,
         course_id: Uuid,
-    ) -> Result<String, sqlx::Error> {
+    ) -> Result<Option<String>, DbError> {
         let weighted_avg = Self::calculate_weighted_average(pool, enrollment_id, course_id).await?;
-        
-        // Apply grading scale
-        let grade = if weighted_avg >= 90.0 {
-            "A"
-        } else if weighted_avg >= 80.0 {
-            "B"
-        } else if weighted_avg >= 70.0 {
-            "C"
-        } else if weighted_avg >= 60.0 {
-            "D"
-        } else {
-            "F"
-        };
-        
-        Ok(grade.to_string())
+
+        Ok(weighted_avg.map(|avg| letter_grade(avg).to_string()))
     }
 
     /// Calculate statistics for assessments in a course
+    ///
+    /// Scores are percentages of each assessment's maximum. Without graded
+    /// assessments the count is 0 and every figure is `None`.
     pub async fn calculate_course_statistics(
         pool: &Pool<Postgres>,
         course_id: Uuid,
-    ) -> Result<CourseStatistics, sqlx::Error> {
+    ) -> Result<CourseStatistics, DbError> {
         let result = sqlx::query!(
             r#"
             SELECT 
-                AVG(score / max_score * 100) as average_score,
-                MIN(score / max_score * 100) as min_score,
-                MAX(score / max_score * 100) as max_score,
+                AVG(score / max_score * 100)::float8 as average_score,
+                MIN(score / max_score * 100)::float8 as min_score,
+                MAX(score / max_score * 100)::float8 as max_score,
                 PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY score / max_score * 100) as median_score,
-                COUNT(*) as assessment_count
+                COUNT(*) as "assessment_count!"
             FROM assessments
-            WHERE course_id = $1
+            WHERE course_id = $1 AND score IS NOT NULL AND max_score > 0
             "#,
             course_id
         )
@@ -391,34 +444,347 @@ impl Assessment {
         .await?;
 
         Ok(CourseStatistics {
-            average_score: result.average_score.unwrap_or(0.0),
-            min_score: result.min_score.unwrap_or(0.0),
-            max_score: result.max_score.unwrap_or(0.0),
-            median_score: result.median_score.unwrap_or(0.0),
-            assessment_count: result.assessment_count.unwrap_or(0) as i32,
+            average_score: result.average_score,
+            min_score: result.min_score,
+            max_score: result.max_score,
+            median_score: result.median_score,
+            assessment_count: result.assessment_count as i32,
         })
     }
 
     /// Calculate grade distribution for a course
+    ///
+    /// Enrollments without a weighted average are counted in
+    /// `ungraded_count` instead of as failing grades.
     pub async fn calculate_grade_distribution(
         pool: &Pool<Postgres>,
         course_id: Uuid,
-    ) -> Result<GradeDistribution, sqlx::Error> {
-        // Get all enrollments for this course
-        let enrollments = sqlx::query!(
-            "SELECT id FROM enrollments WHERE course_id = $1",
+    ) -> Result<GradeDistribution, DbError> {
+        // One weighted average per enrollment, NULL for the ones without data
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                (SUM(a.score * a.weight) / NULLIF(SUM(a.weight), 0))::float8 as weighted_average
+            FROM enrollments e
+            LEFT JOIN assessments a
+                ON a.enrollment_id = e.id AND a.course_id = e.course_id AND a.score IS NOT NULL
+            WHERE e.course_id = $1
+            GROUP BY e.id
+            "#,
             course_id
         )
         .fetch_all(pool)
         .await?;
 
-        let mut a_count = 0;
-        let mut b_count = 0;
-        let mut c_count = 0;
-        let mut d_count = 0;
-        let mut f_count = 0;
+        Ok(GradeDistribution::from_averages(rows.into_iter().map(|row| row.weighted_average)))
+    }
+
+    /// Validate the data of a new assessment before inserting it
+    fn validate_new_assessment(assessment: &NewAssessment) -> Result<(), DbError> {
+        check_fields(
+            AssessmentFields {
+                title: &assessment.title,
+                score: assessment.score,
+                max_score: assessment.max_score,
+                weight: assessment.weight,
+                assessment_date: assessment.assessment_date,
+                period: assessment.period,
+            },
+            Utc::now(),
+        )
+    }
+
+    /// Validate an update against the row it changes
+    ///
+    /// Fields left out keep their current value, so the rules apply to the
+    /// result: lowering `max_score` below the stored score is rejected even
+    /// when the score itself is not part of the update.
+    fn validate_update(current: &Assessment, update: &AssessmentUpdate) -> Result<(), DbError> {
+        check_fields(
+            AssessmentFields {
+                title: update.title.as_deref().unwrap_or(&current.title),
+                score: update.score.unwrap_or(current.score),
+                max_score: update.max_score.unwrap_or(current.max_score),
+                weight: update.weight.unwrap_or(current.weight),
+                assessment_date: update.assessment_date.unwrap_or(current.assessment_date),
+                period: update.period.unwrap_or(current.period),
+            },
+            Utc::now(),
+        )
+    }
+}
+
+/// How far in the future an assessment date may be, for clocks and time zones that disagree
+const FUTURE_DATE_TOLERANCE: Duration = Duration::hours(24);
+
+/// Values of an assessment the validation rules look at
+struct AssessmentFields<'a> {
+    title: &'a str,
+    score: f64,
+    max_score: f64,
+    weight: f64,
+    assessment_date: DateTime<Utc>,
+    period: i16,
+}
+
+/// Check every rule of an assessment and report all the invalid fields
+///
+/// - `title` is not blank
+/// - `max_score` is greater than 0, so percentages never divide by zero
+/// - `score` is between 0 and `max_score`
+/// - `weight` is a percentage of the course grade greater than 0 and at most
+///   100; a weight of zero would leave the assessment out of every average
+/// - `assessment_date` is at most [`FUTURE_DATE_TOLERANCE`] after `now`
+/// - `period` is 1 or greater
+fn check_fields(fields: AssessmentFields<'_>, now: DateTime<Utc>) -> Result<(), DbError> {
+    let mut errors = Vec::new();
+
+    if fields.title.trim().is_empty() {
+        errors.push(FieldError::new("title", "must not be empty"));
+    }
+    let valid_max_score = fields.max_score > 0.0;
+    if !valid_max_score {
+        errors.push(FieldError::new("max_score", "must be greater than 0"));
+    }
+    if fields.score.is_nan() || fields.score < 0.0 || (valid_max_score && fields.score > fields.max_score) {
+        errors.push(FieldError::new("score", format!("must be between 0 and max_score ({})", fields.max_score)));
+    }
+    if fields.weight.is_nan() || fields.weight <= 0.0 || fields.weight > 100.0 {
+        errors.push(FieldError::new("weight", "must be greater than 0 and at most 100"));
+    }
+    if fields.assessment_date > now + FUTURE_DATE_TOLERANCE {
+        errors.push(FieldError::new("assessment_date", "must not be in the future"));
+    }
+    if fields.period < 1 {
+        errors.push(FieldError::new("period", "must be 1 or greater"));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(DbError::Invalid(errors))
+    }
+}
+
+/// Grade shown for an enrollment without graded assessments
+pub const UNGRADED: &str = "sin calificar";
+
+/// Letter grade of a weighted average
+pub fn letter_grade(weighted_average: f64) -> &'static str {
+    if weighted_average >= 90.0 {
+        "A"
+    } else if weighted_average >= 80.0 {
+        "B"
+    } else if weighted_average >= 70.0 {
+        "C"
+    } else if weighted_average >= 60.0 {
+        "D"
+    } else {
+        "F"
+    }
+}
+
+/// Statistics of the graded assessments of a course, as percentages
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CourseStatistics {
+    pub average_score: Option<f64>,
+    pub min_score: Option<f64>,
+    pub max_score: Option<f64>,
+    pub median_score: Option<f64>,
+    pub assessment_count: i32,
+}
+
+/// Number of enrollments of a course with each letter grade
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GradeDistribution {
+    pub a_count: i32,
+    pub b_count: i32,
+    pub c_count: i32,
+    pub d_count: i32,
+    pub f_count: i32,
+    /// Enrollments without graded assessments, left out of the letter counts
+    pub ungraded_count: i32,
+}
+
+impl GradeDistribution {
+    /// Counts one weighted average per enrollment; `None` is ungraded
+    pub fn from_averages(averages: impl IntoIterator<Item = Option<f64>>) -> Self {
+        let mut distribution = Self::default();
+
+        for average in averages {
+            let count = match average.map(letter_grade) {
+                Some("A") => &mut distribution.a_count,
+                Some("B") => &mut distribution.b_count,
+                Some("C") => &mut distribution.c_count,
+                Some("D") => &mut distribution.d_count,
+                Some(_) => &mut distribution.f_count,
+                None => &mut distribution.ungraded_count,
+            };
+            *count += 1;
+        }
+
+        distribution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_assessment(weight: f64) -> NewAssessment {
+        NewAssessment {
+            enrollment_id: Uuid::new_v4(),
+            course_id: Uuid::new_v4(),
+            assessment_type: AssessmentType::Exam,
+            title: "Primer examen parcial".to_string(),
+            description: None,
+            score: 18.0,
+            max_score: 20.0,
+            weight,
+            assessment_date: Utc::now(),
+            is_final: false,
+            period: 1,
+            comments: None,
+        }
+    }
+
+    #[test]
+    fn test_letter_grade_boundaries() {
+        assert_eq!(letter_grade(90.0), "A");
+        assert_eq!(letter_grade(89.99), "B");
+        assert_eq!(letter_grade(60.0), "D");
+        assert_eq!(letter_grade(0.0), "F");
+    }
 
-        // Calculate grade for each enrollment
-        for enrollment in enrollments {
-            let grade = Self::calculate_grade(pool, enrollment.id, course_i
+    #[test]
+    fn test_distribution_counts_ungraded_apart() {
+        let distribution = GradeDistribution::from_averages([Some(95.0), None, Some(40.0), None, Some(72.5)]);
 
+        assert_eq!(
+            distribution,
+            GradeDistribution { a_count: 1, c_count: 1, f_count: 1, ungraded_count: 2, ..Default::default() }
+        );
+    }
+
+    #[test]
+    fn test_distribution_without_enrollments_is_empty() {
+        assert_eq!(GradeDistribution::from_averages([]), GradeDistribution::default());
+    }
+
+    /// Fields of the invalid result, or none if it passed
+    fn invalid_fields(result: Result<(), DbError>) -> Vec<std::borrow::Cow<'static, str>> {
+        match result {
+            Ok(()) => Vec::new(),
+            Err(DbError::Invalid(errors)) => errors.into_iter().map(|error| error.field).collect(),
+            Err(other) => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    fn stored(score: f64, max_score: f64) -> Assessment {
+        let assessment = new_assessment(25.0);
+        Assessment {
+            id: Uuid::new_v4(),
+            enrollment_id: assessment.enrollment_id,
+            course_id: assessment.course_id,
+            assessment_type: assessment.assessment_type,
+            title: assessment.title,
+            description: None,
+            score,
+            max_score,
+            weight: assessment.weight,
+            assessment_date: assessment.assessment_date,
+            is_final: false,
+            period: 1,
+            comments: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_valid_assessment() {
+        assert!(Assessment::validate_new_assessment(&new_assessment(25.0)).is_ok());
+        // Full marks and a date later today are fine
+        let mut assessment = new_assessment(100.0);
+        assessment.score = assessment.max_score;
+        assessment.assessment_date = Utc::now() + Duration::hours(3);
+        assert!(Assessment::validate_new_assessment(&assessment).is_ok());
+    }
+
+    #[test]
+    fn test_weight_must_be_a_positive_percentage() {
+        for weight in [0.0, -5.0, 100.5, f64::NAN] {
+            assert_eq!(
+                invalid_fields(Assessment::validate_new_assessment(&new_assessment(weight))),
+                vec!["weight"],
+                "{}",
+                weight
+            );
+        }
+    }
+
+    #[test]
+    fn test_score_within_max_score() {
+        for score in [-1.0, 20.5, 150.0, f64::NAN] {
+            let mut assessment = new_assessment(25.0);
+            assessment.score = score;
+            assert_eq!(invalid_fields(Assessment::validate_new_assessment(&assessment)), vec!["score"], "{}", score);
+        }
+    }
+
+    #[test]
+    fn test_max_score_must_be_positive() {
+        for max_score in [0.0, -20.0] {
+            let mut assessment = new_assessment(25.0);
+            assessment.score = 0.0;
+            assessment.max_score = max_score;
+            assert_eq!(invalid_fields(Assessment::validate_new_assessment(&assessment)), vec!["max_score"]);
+        }
+    }
+
+    #[test]
+    fn test_date_not_in_the_future() {
+        let mut assessment = new_assessment(25.0);
+        assessment.assessment_date = Utc::now() + Duration::days(3);
+
+        assert_eq!(invalid_fields(Assessment::validate_new_assessment(&assessment)), vec!["assessment_date"]);
+    }
+
+    #[test]
+    fn test_title_and_period() {
+        let mut assessment = new_assessment(25.0);
+        assessment.title = "  ".to_string();
+        assessment.period = 0;
+
+        assert_eq!(invalid_fields(Assessment::validate_new_assessment(&assessment)), vec!["title", "period"]);
+    }
+
+    #[test]
+    fn test_every_invalid_field_reported() {
+        let mut assessment = new_assessment(0.0);
+        assessment.title = String::new();
+        assessment.score = 150.0;
+
+        assert_eq!(
+            invalid_fields(Assessment::validate_new_assessment(&assessment)),
+            vec!["title", "score", "weight"]
+        );
+    }
+
+    #[test]
+    fn test_update_checked_against_current_row() {
+        let current = stored(18.0, 20.0);
+
+        // Shrinking max_score below the stored score
+        let update = AssessmentUpdate { max_score: Some(15.0), ..Default::default() };
+        assert_eq!(invalid_fields(Assessment::validate_update(&current, &update)), vec!["score"]);
+
+        // Together with a lower score it is fine
+        let update = AssessmentUpdate { max_score: Some(15.0), score: Some(12.0), ..Default::default() };
+        assert!(Assessment::validate_update(&current, &update).is_ok());
+
+        let update = AssessmentUpdate { weight: Some(0.0), ..Default::default() };
+        assert_eq!(invalid_fields(Assessment::validate_update(&current, &update)), vec!["weight"]);
+        assert!(Assessment::validate_update(&current, &AssessmentUpdate::default()).is_ok());
+    }
+}
@@ -1,25 +1,33 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgPool, Error as SqlxError, Postgres, Transaction};
+use sqlx::{Postgres, Transaction};
 use uuid::Uuid;
 
-use crate::db::{DbError, DbPool, DEFAULT_PAGE_SIZE};
+use crate::db::{DbError, DbPool, FieldError, DEFAULT_PAGE_SIZE};
+
+/// Unique constraint over student, course and date of every attendance record
+pub const DAILY_ATTENDANCE_CONSTRAINT: &str = "unique_student_course_date";
+
+/// Longest delay accepted for a late arrival, in minutes
+pub const MAX_MINUTES_LATE: i32 = 240;
+
+/// Longest note accepted on an attendance record, in characters
+pub const MAX_NOTES_LENGTH: usize = 500;
 
 /// Represents the status of a student's attendance
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "attendance_status", rename_all = "lowercase")]
+#[derive(Default)]
 pub enum AttendanceStatus {
+    #[default]
     Present,
     Absent,
     Late,
+    /// Justified absence; clients of the former `JustifiedAbsence` variant still parse
+    #[serde(alias = "JustifiedAbsence", alias = "justified_absence")]
     Excused,
 }
 
-impl Default for AttendanceStatus {
-    fn default() -> Self {
-        Self::Present
-    }
-}
 
 /// Represents a student's attendance record in the system
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
@@ -66,10 +74,22 @@ pub struct AttendanceFilter {
     pub date_to: Option<NaiveDate>,
     pub status: Option<AttendanceStatus>,
     pub recorded_by: Option<Uuid>,
+    /// Restricts results to the students whose guardian is this user
+    pub guardian_user_id: Option<Uuid>,
     pub page: Option<u32>,
     pub page_size: Option<u32>,
 }
 
+/// Number of attendance records per status for a filtered set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttendanceStatusCounts {
+    pub total: i64,
+    pub present: i64,
+    pub absent: i64,
+    pub late: i64,
+    pub excused: i64,
+}
+
 /// Attendance statistics for a course or student
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttendanceStatistics {
@@ -90,7 +110,7 @@ impl Attendance {
         let result = sqlx::query_as!(
             Attendance,
             r#"
-            INSERT INTO attendances (
+            INSERT INTO attendance (
                 student_id, course_id, date, status, notes, minutes_late, recorded_by, created_at, updated_at
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
@@ -119,7 +139,7 @@ impl Attendance {
         let result = sqlx::query_as!(
             Attendance,
             r#"
-            INSERT INTO attendances (
+            INSERT INTO attendance (
                 student_id, course_id, date, status, notes, minutes_late, recorded_by, created_at, updated_at
             )
             VALUES ($1, $2, $3, $4, $5, $6, $7, NOW(), NOW())
@@ -140,6 +160,11 @@ impl Attendance {
         Ok(result)
     }
 
+    /// Whether `error` is a second record for the same student, course and date
+    pub fn is_duplicate(error: &DbError) -> bool {
+        matches!(error, DbError::Conflict(message) if message.contains(DAILY_ATTENDANCE_CONSTRAINT))
+    }
+
     /// Retrieves an attendance record by ID
     pub async fn find_by_id(pool: &DbPool, id: Uuid) -> Result<Option<Attendance>, DbError> {
         let result = sqlx::query_as!(
@@ -148,7 +173,7 @@ impl Attendance {
             SELECT 
                 id, student_id, course_id, date, status as "status: AttendanceStatus", 
                 notes, minutes_late, recorded_by, created_at, updated_at
-            FROM attendances
+            FROM attendance
             WHERE id = $1
             "#,
             id
@@ -171,7 +196,7 @@ impl Attendance {
             SELECT 
                 id, student_id, course_id, date, status as "status: AttendanceStatus", 
                 notes, minutes_late, recorded_by, created_at, updated_at
-            FROM attendances
+            FROM attendance
             WHERE student_id = $1 AND date = $2
             "#,
             student_id,
@@ -183,74 +208,17 @@ impl Attendance {
         Ok(result)
     }
 
-    /// Filters attendance records based on provided criteria
+    /// Filters attendance records based on provided criteria.
+    ///
+    /// Date bounds are inclusive. Results are ordered by date (most recent first)
+    /// and paginated with `page`/`page_size`.
     pub async fn filter(
         pool: &DbPool,
-        filter: AttendanceFilter,
+        filter: &AttendanceFilter,
     ) -> Result<Vec<Attendance>, DbError> {
-        let mut query = String::from(
-            r#"
-            SELECT 
-                id, student_id, course_id, date, status as "status: AttendanceStatus", 
-                notes, minutes_late, recorded_by, created_at, updated_at
-            FROM attendances
-            WHERE 1=1
-            "#,
-        );
-
-        let mut params: Vec<Box<dyn sqlx::postgres::PgArguments>> = Vec::new();
-        let mut param_count = 1;
-
-        if let Some(student_id) = filter.student_id {
-            query.push_str(&format!(" AND student_id = ${}", param_count));
-            params.push(Box::new(student_id));
-            param_count += 1;
-        }
-
-        if let Some(course_id) = filter.course_id {
-            query.push_str(&format!(" AND course_id = ${}", param_count));
-            params.push(Box::new(course_id));
-            param_count += 1;
-        }
-
-        if let Some(date_from) = filter.date_from {
-            query.push_str(&format!(" AND date >= ${}", param_count));
-            params.push(Box::new(date_from));
-            param_count += 1;
-        }
-
-        if let Some(date_to) = filter.date_to {
-            query.push_str(&format!(" AND date <= ${}", param_count));
-            params.push(Box::new(date_to));
-            param_count += 1;
-        }
-
-        if let Some(status) = filter.status {
-            query.push_str(&format!(" AND status = ${}", param_count));
-            params.push(Box::new(status));
-            param_count += 1;
-        }
-
-        if let Some(recorded_by) = filter.recorded_by {
-            query.push_str(&format!(" AND recorded_by = ${}", param_count));
-            params.push(Box::new(recorded_by));
-            param_count += 1;
-        }
-
-        // Order by date (most recent first)
-        query.push_str(" ORDER BY date DESC");
-
-        // Add pagination
-        let page = filter.page.unwrap_or(1);
+        let page = filter.page.unwrap_or(1).max(1);
         let page_size = filter.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
         let offset = (page - 1) * page_size;
-        query.push_str(&format!(" LIMIT ${} OFFSET ${}", param_count, param_count + 1));
-        params.push(Box::new(page_size));
-        params.push(Box::new(offset));
-
-        // Unfortunately, we can't easily use the built query directly with sqlx::query_as! since it requires 
-        // static strings. In a real implementation, we would use a more flexible query builder or the 
-        // dynamic query capabilities of sqlx. For now, we'll simulate this with a simplified query.
 
         let result = sqlx::query_as!(
             Attendance,
@@ -258,22 +226,29 @@ impl Attendance {
             SELECT 
                 id, student_id, course_id, date, status as "status: AttendanceStatus", 
                 notes, minutes_late, recorded_by, created_at, updated_at
-            FROM attendances
+            FROM attendance
             WHERE ($1::uuid IS NULL OR student_id = $1)
               AND ($2::uuid IS NULL OR course_id = $2)
               AND ($3::date IS NULL OR date >= $3)
               AND ($4::date IS NULL OR date <= $4)
               AND ($5::attendance_status IS NULL OR status = $5)
               AND ($6::uuid IS NULL OR recorded_by = $6)
+              AND ($7::uuid IS NULL OR student_id IN (
+                  SELECT s.user_id
+                  FROM students s
+                  JOIN users u ON u.document_id = s.guardian_info->>'document_id'
+                  WHERE u.id = $7
+              ))
             ORDER BY date DESC
-            LIMIT $7 OFFSET $8
+            LIMIT $8 OFFSET $9
             "#,
             filter.student_id,
             filter.course_id,
             filter.date_from,
             filter.date_to,
-            filter.status as Option<AttendanceStatus>,
+            filter.status.clone() as Option<AttendanceStatus>,
             filter.recorded_by,
+            filter.guardian_user_id,
             page_size as i64,
             offset as i64
         )
@@ -283,6 +258,53 @@ impl Attendance {
         Ok(result)
     }
 
+    /// Counts the records matching a filter, grouped by status (pagination is ignored)
+    pub async fn count_by_status(
+        pool: &DbPool,
+        filter: &AttendanceFilter,
+    ) -> Result<AttendanceStatusCounts, DbError> {
+        let result = sqlx::query!(
+            r#"
+            SELECT 
+                COUNT(*) as "total!",
+                COUNT(*) FILTER (WHERE status = 'present') as "present!",
+                COUNT(*) FILTER (WHERE status = 'absent') as "absent!",
+                COUNT(*) FILTER (WHERE status = 'late') as "late!",
+                COUNT(*) FILTER (WHERE status = 'excused') as "excused!"
+            FROM attendance
+            WHERE ($1::uuid IS NULL OR student_id = $1)
+              AND ($2::uuid IS NULL OR course_id = $2)
+              AND ($3::date IS NULL OR date >= $3)
+              AND ($4::date IS NULL OR date <= $4)
+              AND ($5::attendance_status IS NULL OR status = $5)
+              AND ($6::uuid IS NULL OR recorded_by = $6)
+              AND ($7::uuid IS NULL OR student_id IN (
+                  SELECT s.user_id
+                  FROM students s
+                  JOIN users u ON u.document_id = s.guardian_info->>'document_id'
+                  WHERE u.id = $7
+              ))
+            "#,
+            filter.student_id,
+            filter.course_id,
+            filter.date_from,
+            filter.date_to,
+            filter.status.clone() as Option<AttendanceStatus>,
+            filter.recorded_by,
+            filter.guardian_user_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(AttendanceStatusCounts {
+            total: result.total,
+            present: result.present,
+            absent: result.absent,
+            late: result.late,
+            excused: result.excused,
+        })
+    }
+
     /// Updates an attendance record
     pub async fn update(
         pool: &DbPool,
@@ -298,7 +320,7 @@ impl Attendance {
         let result = sqlx::query_as!(
             Attendance,
             r#"
-            UPDATE attendances
+            UPDATE attendance
             SET 
                 status = COALESCE($1, status),
                 notes = COALESCE($2, notes),
@@ -321,9 +343,38 @@ impl Attendance {
         Ok(result)
     }
 
+    /// Marks as excused every present, absent or late record on a date
+    ///
+    /// When `course_id` is `None` the records of every course are updated.
+    /// Returns the number of records that changed.
+    pub async fn excuse_for_date(
+        pool: &DbPool,
+        course_id: Option<Uuid>,
+        date: NaiveDate,
+        notes: &str,
+    ) -> Result<u64, DbError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE attendance
+            SET status = $4, notes = $3, updated_at = NOW()
+            WHERE ($1::uuid IS NULL OR course_id = $1)
+              AND date = $2
+              AND status <> $4
+            "#,
+            course_id,
+            date,
+            notes,
+            AttendanceStatus::Excused as AttendanceStatus
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Deletes an attendance record
     pub async fn delete(pool: &DbPool, id: Uuid) -> Result<(), DbError> {
-        let result = sqlx::query!("DELETE FROM attendances WHERE id = $1", id)
+        let result = sqlx::query!("DELETE FROM attendance WHERE id = $1", id)
             .execute(pool)
             .await?;
 
@@ -343,26 +394,27 @@ impl Attendance {
         status: AttendanceStatus,
         recorded_by: Uuid,
     ) -> Result<Vec<Attendance>, DbError> {
-        let mut tx = pool.begin().await?;
-        let mut created_records = Vec::new();
-
-        for student_id in student_ids {
-            let new_attendance = NewAttendance {
-                student_id,
-                course_id,
-                date,
-                status: status.clone(),
-                notes: None,
-                minutes_late: None,
-                recorded_by,
-            };
-
-            let attendance = Self::create_in_transaction(&mut tx, new_attendance).await?;
-            created_records.push(attendance);
-        }
-
-        tx.commit().await?;
-        Ok(created_records)
+        crate::tx!(pool, |tx| {
+            let mut created_records = Vec::new();
+
+            for student_id in student_ids {
+                let new_attendance = NewAttendance {
+                    student_id,
+                    course_id,
+                    date,
+                    status: status.clone(),
+                    notes: None,
+                    minutes_late: None,
+                    recorded_by,
+                };
+
+                let attendance = Self::create_in_transaction(tx, new_attendance).await?;
+                created_records.push(attendance);
+            }
+
+            Ok(created_records)
+        })
+        .await
     }
 
     /// Gets attendance statistics for a student in a course
@@ -379,7 +431,7 @@ impl Attendance {
                 COUNT(*) FILTER (WHERE status = 'absent') as "absent_days!",
                 COUNT(*) FILTER (WHERE status = 'late') as "late_days!",
                 COUNT(*) FILTER (WHERE status = 'excused') as "excused_days!"
-            FROM attendances
+            FROM attendance
             WHERE student_id = $1 AND course_id = $2
             "#,
             student_id,
@@ -405,6 +457,97 @@ impl Attendance {
     }
 
     /// Validates a new attendance record
-    pub fn validate_new_attendance(
-        new_attendance: &NewAtten
+    ///
+    /// Every rejected field is reported at once, one entry per field.
+    pub fn validate_new_attendance(new_attendance: &NewAttendance) -> Result<(), DbError> {
+        let mut errors = Vec::new();
+
+        if new_attendance.date > Utc::now().date_naive() {
+            errors.push(FieldError::new("date", "cannot be in the future"));
+        }
+        match (&new_attendance.status, new_attendance.minutes_late) {
+            (AttendanceStatus::Late, Some(minutes)) if !(1..=MAX_MINUTES_LATE).contains(&minutes) => {
+                errors.push(FieldError::new(
+                    "minutes_late",
+                    format!("must be between 1 and {}", MAX_MINUTES_LATE),
+                ));
+            }
+            (AttendanceStatus::Late, _) | (_, None) => {}
+            (_, Some(_)) => errors.push(FieldError::new("minutes_late", "only applies to late arrivals")),
+        }
+        if new_attendance
+            .notes
+            .as_ref()
+            .is_some_and(|notes| notes.chars().count() > MAX_NOTES_LENGTH)
+        {
+            errors.push(FieldError::new(
+                "notes",
+                format!("must be at most {} characters", MAX_NOTES_LENGTH),
+            ));
+        }
 
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DbError::Invalid(errors))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_justified_absence_parses_as_excused() {
+        for old in ["\"JustifiedAbsence\"", "\"justified_absence\""] {
+            assert_eq!(serde_json::from_str::<AttendanceStatus>(old).unwrap(), AttendanceStatus::Excused);
+        }
+
+        // Only the canonical name is written back
+        assert_eq!(serde_json::to_string(&AttendanceStatus::Excused).unwrap(), "\"Excused\"");
+        assert!(serde_json::from_str::<AttendanceStatus>("\"Justified\"").is_err());
+    }
+
+    fn new_attendance(status: AttendanceStatus, minutes_late: Option<i32>) -> NewAttendance {
+        NewAttendance {
+            student_id: Uuid::new_v4(),
+            course_id: Uuid::new_v4(),
+            date: NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            status,
+            notes: None,
+            minutes_late,
+            recorded_by: Uuid::new_v4(),
+        }
+    }
+
+    fn invalid_fields(attendance: &NewAttendance) -> Vec<String> {
+        match Attendance::validate_new_attendance(attendance) {
+            Ok(()) => Vec::new(),
+            Err(DbError::Invalid(errors)) => errors.into_iter().map(|error| error.field.into_owned()).collect(),
+            Err(other) => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_minutes_late_only_for_late_arrivals() {
+        assert!(invalid_fields(&new_attendance(AttendanceStatus::Present, None)).is_empty());
+        assert!(invalid_fields(&new_attendance(AttendanceStatus::Late, None)).is_empty());
+        assert!(invalid_fields(&new_attendance(AttendanceStatus::Late, Some(15))).is_empty());
+
+        assert_eq!(invalid_fields(&new_attendance(AttendanceStatus::Late, Some(0))), ["minutes_late"]);
+        assert_eq!(invalid_fields(&new_attendance(AttendanceStatus::Late, Some(241))), ["minutes_late"]);
+        assert_eq!(invalid_fields(&new_attendance(AttendanceStatus::Absent, Some(10))), ["minutes_late"]);
+    }
+
+    #[test]
+    fn test_every_invalid_field_is_reported() {
+        let attendance = NewAttendance {
+            date: Utc::now().date_naive() + chrono::Duration::days(1),
+            notes: Some("x".repeat(MAX_NOTES_LENGTH + 1)),
+            ..new_attendance(AttendanceStatus::Present, Some(5))
+        };
+
+        assert_eq!(invalid_fields(&attendance), ["date", "minutes_late", "notes"]);
+    }
+}
@@ -0,0 +1,343 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Fragmentos de nombres de campo cuyo valor nunca debe mostrarse
+pub const SENSITIVE_KEY_FRAGMENTS: [&str; 4] = ["password", "token", "secret", "hash"];
+
+/// Valor con el que se reemplazan los campos sensibles
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Acciones genéricas sobre entidades registradas en la auditoría
+///
+/// Los eventos propios de un módulo usan nombres con punto (`backup.export`,
+/// `config.reload`), por eso `action` se guarda como texto; estas variantes
+/// cubren las operaciones comunes de la API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Create,
+    Update,
+    Delete,
+    Login,
+    Logout,
+}
+
+impl AuditAction {
+    /// Nombre con el que se guarda la acción en `audit_logs.action`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Create => "create",
+            AuditAction::Update => "update",
+            AuditAction::Delete => "delete",
+            AuditAction::Login => "login",
+            AuditAction::Logout => "logout",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "create" => Ok(AuditAction::Create),
+            "update" => Ok(AuditAction::Update),
+            "delete" => Ok(AuditAction::Delete),
+            "login" => Ok(AuditAction::Login),
+            "logout" => Ok(AuditAction::Logout),
+            _ => Err(format!("Acción de auditoría desconocida: {}", value)),
+        }
+    }
+}
+
+/// Evento de auditoría tal como se muestra a los administradores
+///
+/// El nombre del actor se obtiene con un JOIN a `users`, por lo que siempre
+/// refleja el nombre actual y no se duplica en la tabla de auditoría.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditLogEntry {
+    /// Identificador único del evento
+    pub id: Uuid,
+    /// Usuario que realizó la acción (nulo para procesos del sistema)
+    pub actor_id: Option<Uuid>,
+    /// Nombre del usuario que realizó la acción
+    pub actor_name: Option<String>,
+    /// Acción realizada (create, update, delete, ...)
+    pub action: String,
+    /// Tipo de entidad afectada
+    pub entity_type: String,
+    /// Entidad afectada
+    pub entity_id: Option<Uuid>,
+    /// Valor anterior (con campos sensibles ocultos)
+    pub old_value: Option<Value>,
+    /// Valor nuevo (con campos sensibles ocultos)
+    pub new_value: Option<Value>,
+    /// Dirección IP de origen
+    pub ip_address: Option<String>,
+    /// Agente de usuario de origen
+    pub user_agent: Option<String>,
+    /// Fecha y hora del evento
+    pub created_at: DateTime<Utc>,
+}
+
+/// Posición en el listado de auditoría para la paginación por conjunto de claves
+///
+/// Los eventos se ordenan por `(created_at, id)` descendente; el cursor apunta
+/// al último evento entregado y se serializa como `<microsegundos>_<uuid>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditCursor {
+    pub created_at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl AuditCursor {
+    /// Cursor que apunta a un evento
+    pub fn after(entry: &AuditLogEntry) -> Self {
+        Self {
+            created_at: entry.created_at,
+            id: entry.id,
+        }
+    }
+}
+
+impl fmt::Display for AuditCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.created_at.timestamp_micros(), self.id)
+    }
+}
+
+impl FromStr for AuditCursor {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (micros, id) = value
+            .split_once('_')
+            .ok_or_else(|| format!("Cursor de página inválido: {}", value))?;
+        let micros: i64 = micros
+            .parse()
+            .map_err(|_| format!("Cursor de página inválido: {}", value))?;
+        let created_at = Utc
+            .timestamp_micros(micros)
+            .single()
+            .ok_or_else(|| format!("Cursor de página inválido: {}", value))?;
+        let id = Uuid::parse_str(id).map_err(|_| format!("Cursor de página inválido: {}", value))?;
+
+        Ok(Self { created_at, id })
+    }
+}
+
+/// Datos de un evento de auditoría nuevo
+#[derive(Debug, Clone, Default)]
+pub struct NewAuditLogEntry {
+    /// Usuario que realiza la acción
+    pub actor_id: Option<Uuid>,
+    /// Acción realizada (`backup.export`, ...)
+    pub action: String,
+    /// Tipo de entidad afectada
+    pub entity_type: String,
+    pub entity_id: Option<Uuid>,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+/// Filtros para consultar eventos de auditoría
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+    pub actor_id: Option<Uuid>,
+    pub entity_type: Option<String>,
+    pub action: Option<String>,
+    /// Fecha inicial (inclusiva)
+    pub from: Option<NaiveDate>,
+    /// Fecha final (inclusiva)
+    pub to: Option<NaiveDate>,
+    /// Devolver solo eventos posteriores a este cursor en el orden del listado
+    pub after: Option<AuditCursor>,
+}
+
+impl AuditLogEntry {
+    /// Busca eventos de auditoría del más reciente al más antiguo
+    ///
+    /// Los campos sensibles de `old_value` y `new_value` se ocultan antes de devolverlos.
+    pub async fn query(
+        pool: &PgPool,
+        filter: &AuditLogFilter,
+        limit: i64,
+    ) -> Result<Vec<AuditLogEntry>, DbError> {
+        let mut entries = sqlx::query_as::<_, AuditLogEntry>(
+            r#"
+            SELECT a.id, a.actor_id, u.full_name AS actor_name, a.action, a.entity_type,
+                   a.entity_id, a.old_value, a.new_value, a.ip_address, a.user_agent, a.created_at
+            FROM audit_logs a
+            LEFT JOIN users u ON u.id = a.actor_id
+            WHERE ($1::uuid IS NULL OR a.actor_id = $1)
+              AND ($2::varchar IS NULL OR a.entity_type = $2)
+              AND ($3::varchar IS NULL OR a.action = $3)
+              AND ($4::date IS NULL OR a.created_at >= $4::date)
+              AND ($5::date IS NULL OR a.created_at < $5::date + 1)
+              AND ($6::timestamptz IS NULL OR (a.created_at, a.id) < ($6, $7))
+            ORDER BY a.created_at DESC, a.id DESC
+            LIMIT $8
+            "#,
+        )
+        .bind(filter.actor_id)
+        .bind(&filter.entity_type)
+        .bind(&filter.action)
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.after.map(|cursor| cursor.created_at))
+        .bind(filter.after.map(|cursor| cursor.id))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        for entry in &mut entries {
+            entry.redact();
+        }
+
+        Ok(entries)
+    }
+
+    /// Registra un evento de auditoría
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - Pool o transacción donde se registra el evento
+    /// * `entry` - Datos del evento
+    ///
+    /// # Returns
+    ///
+    /// El identificador del evento registrado
+    pub async fn record<'e>(executor: impl PgExecutor<'e>, entry: &NewAuditLogEntry) -> Result<Uuid, DbError> {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO audit_logs (actor_id, action, entity_type, entity_id, old_value, new_value, ip_address, user_agent)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#,
+        )
+        .bind(entry.actor_id)
+        .bind(&entry.action)
+        .bind(&entry.entity_type)
+        .bind(entry.entity_id)
+        .bind(&entry.old_value)
+        .bind(&entry.new_value)
+        .bind(&entry.ip_address)
+        .bind(&entry.user_agent)
+        .fetch_one(executor)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Oculta los campos sensibles de los valores anterior y nuevo
+    pub fn redact(&mut self) {
+        if let Some(value) = self.old_value.as_mut() {
+            redact_sensitive(value);
+        }
+        if let Some(value) = self.new_value.as_mut() {
+            redact_sensitive(value);
+        }
+    }
+}
+
+/// Indica si un nombre de campo corresponde a información sensible
+pub fn is_sensitive_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SENSITIVE_KEY_FRAGMENTS.iter().any(|fragment| key.contains(fragment))
+}
+
+/// Reemplaza recursivamente el valor de los campos sensibles por `REDACTED`
+pub fn redact_sensitive(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_sensitive_key(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_sensitive(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_sensitive),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redacts_nested_sensitive_fields() {
+        let mut value = json!({
+            "email": "ana@colegio.edu.py",
+            "password_hash": "$2b$12$abc",
+            "sessions": [{ "refresh_token": "xyz", "ip": "10.0.0.1" }],
+            "settings": { "api_secret": "s3cr3t" }
+        });
+
+        redact_sensitive(&mut value);
+
+        assert_eq!(value["email"], "ana@colegio.edu.py");
+        assert_eq!(value["password_hash"], REDACTED);
+        assert_eq!(value["sessions"][0]["refresh_token"], REDACTED);
+        assert_eq!(value["sessions"][0]["ip"], "10.0.0.1");
+        assert_eq!(value["settings"]["api_secret"], REDACTED);
+    }
+
+    #[test]
+    fn test_sensitive_keys_are_case_insensitive() {
+        assert!(is_sensitive_key("Password"));
+        assert!(is_sensitive_key("RESET_TOKEN"));
+        assert!(!is_sensitive_key("full_name"));
+    }
+
+    #[test]
+    fn test_audit_action_names() {
+        let actions = [
+            AuditAction::Create,
+            AuditAction::Update,
+            AuditAction::Delete,
+            AuditAction::Login,
+            AuditAction::Logout,
+        ];
+        for action in actions {
+            assert_eq!(action.to_string().parse::<AuditAction>(), Ok(action));
+            assert_eq!(serde_json::to_value(action).unwrap(), action.as_str());
+        }
+        assert!("config.reload".parse::<AuditAction>().is_err());
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = AuditCursor {
+            created_at: Utc.timestamp_micros(1_711_800_000_123_456).unwrap(),
+            id: Uuid::new_v4(),
+        };
+
+        assert_eq!(cursor.to_string().parse::<AuditCursor>(), Ok(cursor));
+    }
+
+    #[test]
+    fn test_invalid_cursor_is_rejected() {
+        assert!("".parse::<AuditCursor>().is_err());
+        assert!("abc_def".parse::<AuditCursor>().is_err());
+        assert!("1711800000_not-a-uuid".parse::<AuditCursor>().is_err());
+    }
+}
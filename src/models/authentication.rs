@@ -1,8 +1,21 @@
+use base64::Engine;
 use chrono::{DateTime, Utc};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use sqlx::{postgres::PgPool, types::Uuid, Error as SqlxError};
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgPool, types::Uuid, Error as SqlxError, FromRow, PgExecutor};
 use uuid::Uuid as UuidLib;
 
+use crate::db::DbError;
+use crate::models::user::UserStatus;
+use crate::utils::password;
+
+/// Hours a verification link stays valid
+pub const EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
+
+/// Random bytes in a refresh token, before encoding
+const REFRESH_TOKEN_BYTES: usize = 32;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Authentication {
     pub id: Uuid,
@@ -14,6 +27,11 @@ pub struct Authentication {
     pub last_login: Option<DateTime<Utc>>,
     pub is_locked: bool,
     pub failed_attempts: i32,
+    /// Set for accounts created with a temporary password until the user picks their own
+    pub must_change_password: bool,
+    pub email_verified: bool,
+    pub email_verification_token: Option<String>,
+    pub email_verification_expires: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -24,7 +42,7 @@ pub struct NewAuthentication {
     pub password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct AuthenticationUpdate {
     pub password: Option<String>,
     pub reset_token: Option<String>,
@@ -37,8 +55,10 @@ pub struct AuthenticationUpdate {
 
 impl Authentication {
     /// Create a new authentication record
-    pub async fn create(pool: &PgPool, new_auth: NewAuthentication) -> Result<Self, SqlxError> {
-        let password_hash = bcrypt::hash(&new_auth.password, bcrypt::DEFAULT_COST)
+    ///
+    /// Takes any executor so it can run inside a caller's transaction.
+    pub async fn create<'e, E: PgExecutor<'e>>(executor: E, new_auth: NewAuthentication) -> Result<Self, DbError> {
+        let password_hash = password::hash_password(&new_auth.password)
             .map_err(|e| SqlxError::Protocol(format!("Failed to hash password: {}", e)))?;
 
         let auth = sqlx::query_as!(
@@ -49,19 +69,52 @@ impl Authentication {
             )
             VALUES ($1, $2, 0, false, 0)
             RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
-                      last_login, is_locked, failed_attempts, created_at, updated_at
+                      last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                      email_verification_token, email_verification_expires, created_at, updated_at
             "#,
             new_auth.user_id,
             password_hash,
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
+        .await?;
+
+        Ok(auth)
+    }
+
+    /// Create an authentication record with a temporary password
+    ///
+    /// The user is asked to choose a new password on first login. Takes any
+    /// executor so it can run inside a caller's transaction.
+    pub async fn create_with_temporary_password<'e, E: PgExecutor<'e>>(
+        executor: E,
+        user_id: Uuid,
+        temporary_password: &str,
+    ) -> Result<Self, DbError> {
+        let password_hash = password::hash_password(temporary_password)
+            .map_err(|e| SqlxError::Protocol(format!("Failed to hash password: {}", e)))?;
+
+        let auth = sqlx::query_as!(
+            Authentication,
+            r#"
+            INSERT INTO authentications (
+                user_id, password_hash, token_version, is_locked, failed_attempts, must_change_password
+            )
+            VALUES ($1, $2, 0, false, 0, true)
+            RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
+                      last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                      email_verification_token, email_verification_expires, created_at, updated_at
+            "#,
+            user_id,
+            password_hash,
+        )
+        .fetch_one(executor)
         .await?;
 
         Ok(auth)
     }
 
     /// Find an authentication record by user_id
-    pub async fn find_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Self, SqlxError> {
+    pub async fn find_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Self, DbError> {
         let auth = sqlx::query_as!(
             Authentication,
             r#"
@@ -79,7 +132,7 @@ impl Authentication {
     pub async fn find_by_reset_token(
         pool: &PgPool,
         reset_token: &str,
-    ) -> Result<Self, SqlxError> {
+    ) -> Result<Self, DbError> {
         let auth = sqlx::query_as!(
             Authentication,
             r#"
@@ -99,10 +152,10 @@ impl Authentication {
         &self,
         pool: &PgPool,
         update: AuthenticationUpdate,
-    ) -> Result<Self, SqlxError> {
+    ) -> Result<Self, DbError> {
         let password_hash = match update.password {
             Some(password) => Some(
-                bcrypt::hash(&password, bcrypt::DEFAULT_COST)
+                password::hash_password(&password)
                     .map_err(|e| SqlxError::Protocol(format!("Failed to hash password: {}", e)))?,
             ),
             None => None,
@@ -120,10 +173,12 @@ impl Authentication {
                 last_login = COALESCE($5, last_login),
                 is_locked = COALESCE($6, is_locked),
                 failed_attempts = COALESCE($7, failed_attempts),
+                must_change_password = must_change_password AND $1::text IS NULL,
                 updated_at = now()
             WHERE id = $8
             RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
-                      last_login, is_locked, failed_attempts, created_at, updated_at
+                      last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                      email_verification_token, email_verification_expires, created_at, updated_at
             "#,
             password_hash,
             update.reset_token,
@@ -141,7 +196,7 @@ impl Authentication {
     }
 
     /// Delete an authentication record
-    pub async fn delete(self, pool: &PgPool) -> Result<(), SqlxError> {
+    pub async fn delete(self, pool: &PgPool) -> Result<(), DbError> {
         sqlx::query!(
             r#"
             DELETE FROM authentications WHERE id = $1
@@ -159,7 +214,7 @@ impl Authentication {
         &self,
         pool: &PgPool,
         success: bool,
-    ) -> Result<Self, SqlxError> {
+    ) -> Result<Self, DbError> {
         if success {
             let auth = sqlx::query_as!(
                 Authentication,
@@ -172,7 +227,8 @@ impl Authentication {
                     updated_at = now()
                 WHERE id = $1
                 RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
-                          last_login, is_locked, failed_attempts, created_at, updated_at
+                          last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                          email_verification_token, email_verification_expires, created_at, updated_at
                 "#,
                 self.id
             )
@@ -197,7 +253,8 @@ impl Authentication {
                     updated_at = now()
                 WHERE id = $3
                 RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
-                          last_login, is_locked, failed_attempts, created_at, updated_at
+                          last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                          email_verification_token, email_verification_expires, created_at, updated_at
                 "#,
                 new_failed_attempts,
                 is_locked,
@@ -211,7 +268,7 @@ impl Authentication {
     }
 
     /// Generate a password reset token
-    pub async fn generate_reset_token(&self, pool: &PgPool) -> Result<String, SqlxError> {
+    pub async fn generate_reset_token(&self, pool: &PgPool) -> Result<String, DbError> {
         // Generate a random token
         let reset_token = UuidLib::new_v4().to_string();
         
@@ -238,7 +295,7 @@ impl Authentication {
     }
 
     /// Clear the reset token
-    pub async fn clear_reset_token(&self, pool: &PgPool) -> Result<(), SqlxError> {
+    pub async fn clear_reset_token(&self, pool: &PgPool) -> Result<(), DbError> {
         sqlx::query!(
             r#"
             UPDATE authentications
@@ -257,7 +314,7 @@ impl Authentication {
     }
 
     /// Increment token version (invalidates all existing tokens)
-    pub async fn increment_token_version(&self, pool: &PgPool) -> Result<Self, SqlxError> {
+    pub async fn increment_token_version(&self, pool: &PgPool) -> Result<Self, DbError> {
         let auth = sqlx::query_as!(
             Authentication,
             r#"
@@ -267,7 +324,8 @@ impl Authentication {
                 updated_at = now()
             WHERE id = $1
             RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
-                      last_login, is_locked, failed_attempts, created_at, updated_at
+                      last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                      email_verification_token, email_verification_expires, created_at, updated_at
             "#,
             self.id
         )
@@ -277,14 +335,361 @@ impl Authentication {
         Ok(auth)
     }
 
-    /// Verify a password
+    /// Verify a password against the stored Argon2 or legacy bcrypt hash
     pub fn verify_password(&self, password: &str) -> bool {
-        bcrypt::verify(password, &self.password_hash).unwrap_or(false)
+        password::verify_password(password, &self.password_hash)
+    }
+
+    /// Replace a legacy hash with an Argon2id hash of the same password
+    ///
+    /// Called after a successful login with the password just verified, so
+    /// bcrypt hashes disappear as users sign in. Does nothing when the hash is
+    /// already Argon2id. Unlike a password change it keeps
+    /// `must_change_password` and the token version.
+    pub async fn upgrade_password_hash(&self, pool: &PgPool, password: &str) -> Result<Self, DbError> {
+        if !password::needs_rehash(&self.password_hash) {
+            return Ok(self.clone());
+        }
+        let password_hash = password::hash_password(password)
+            .map_err(|e| SqlxError::Protocol(format!("Failed to hash password: {}", e)))?;
+
+        let auth = sqlx::query_as!(
+            Authentication,
+            r#"
+            UPDATE authentications
+            SET 
+                password_hash = $1,
+                updated_at = now()
+            WHERE id = $2
+            RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
+                      last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                      email_verification_token, email_verification_expires, created_at, updated_at
+            "#,
+            password_hash,
+            self.id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(auth)
     }
 
     /// Check if the account is locked
     pub fn is_account_locked(&self) -> bool {
         self.is_locked
     }
+
+    /// Status derived from the verification state of the account
+    pub fn user_status(&self) -> UserStatus {
+        if self.email_verified {
+            UserStatus::Active
+        } else {
+            UserStatus::PendingEmailVerification
+        }
+    }
+
+    /// Generate an email verification token, replacing any previous one
+    pub async fn generate_email_verification_token(&self, pool: &PgPool) -> Result<String, DbError> {
+        let token = UuidLib::new_v4().simple().to_string();
+        let expires = Utc::now() + chrono::Duration::hours(EMAIL_VERIFICATION_TTL_HOURS);
+
+        sqlx::query!(
+            r#"
+            UPDATE authentications
+            SET 
+                email_verification_token = $1,
+                email_verification_expires = $2,
+                updated_at = now()
+            WHERE id = $3
+            "#,
+            token,
+            expires,
+            self.id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Verify an email with the token sent by mail
+    ///
+    /// Returns `None` when the token is unknown or expired. On success the
+    /// account is marked as verified and the token cleared, so a link can
+    /// only be used once.
+    pub async fn verify_email_token(pool: &PgPool, token: &str) -> Result<Option<Self>, DbError> {
+        let auth = sqlx::query_as!(
+            Authentication,
+            r#"
+            UPDATE authentications
+            SET 
+                email_verified = true,
+                email_verification_token = NULL,
+                email_verification_expires = NULL,
+                updated_at = now()
+            WHERE email_verification_token = $1 AND email_verification_expires > now()
+            RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
+                      last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                      email_verification_token, email_verification_expires, created_at, updated_at
+            "#,
+            token
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(auth)
+    }
+
+    /// Mark a user's email as verified without a token (manual verification by an admin)
+    pub async fn mark_email_verified(pool: &PgPool, user_id: Uuid) -> Result<Self, DbError> {
+        let auth = sqlx::query_as!(
+            Authentication,
+            r#"
+            UPDATE authentications
+            SET 
+                email_verified = true,
+                email_verification_token = NULL,
+                email_verification_expires = NULL,
+                updated_at = now()
+            WHERE user_id = $1
+            RETURNING id, user_id, password_hash, reset_token, reset_token_expires, token_version, 
+                      last_login, is_locked, failed_attempts, must_change_password, email_verified,
+                      email_verification_token, email_verification_expires, created_at, updated_at
+            "#,
+            user_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(auth)
+    }
+}
+
+/// A refresh token issued at login
+///
+/// Only the SHA-256 of the token is stored; the token itself is returned
+/// once, by [`RefreshToken::issue`] or [`RefreshToken::rotate`], and is
+/// never readable again.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RefreshToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    /// Login the token descends from, shared by every token rotated from it
+    pub family_id: Uuid,
+    /// Institution slug the session was opened for, `None` for `public`
+    pub institution: Option<String>,
+    pub issued_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub revoked: bool,
+}
+
+/// Outcome of [`RefreshToken::rotate`]
+#[derive(Debug)]
+pub enum Rotation {
+    /// The token was exchanged for `token`; `token_version` is the new version of the account
+    Rotated { token: String, stored: RefreshToken, token_version: i32 },
+    /// An already revoked token was presented again: its whole family was
+    /// revoked and the account moved to `token_version`
+    Reused { user_id: Uuid, token_version: i32 },
+    /// Unknown or expired token
+    Invalid,
+}
+
+impl RefreshToken {
+    /// Hex SHA-256 of `token`, as stored in `refresh_tokens.token_hash`
+    pub fn hash(token: &str) -> String {
+        format!("{:x}", Sha256::digest(token.as_bytes()))
+    }
+
+    /// Whether the token can still be exchanged for a new access token
+    pub fn is_active(&self) -> bool {
+        !self.revoked && self.expires_at > Utc::now()
+    }
+
+    /// Issue a new refresh token for `user_id` in `institution`, valid for
+    /// `ttl`, starting a new family
+    ///
+    /// Returns the token to hand to the client together with the stored row.
+    /// Takes any executor so it can run inside a caller's transaction.
+    pub async fn issue<'e, E: PgExecutor<'e>>(
+        executor: E,
+        user_id: Uuid,
+        institution: Option<&str>,
+        ttl: chrono::Duration,
+    ) -> Result<(String, Self), DbError> {
+        Self::issue_in_family(executor, user_id, institution, ttl, None).await
+    }
+
+    /// Issue a token in `family_id`, or in a new family when `None`
+    async fn issue_in_family<'e, E: PgExecutor<'e>>(
+        executor: E,
+        user_id: Uuid,
+        institution: Option<&str>,
+        ttl: chrono::Duration,
+        family_id: Option<Uuid>,
+    ) -> Result<(String, Self), DbError> {
+        let mut bytes = [0u8; REFRESH_TOKEN_BYTES];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes);
+
+        let stored = sqlx::query_as::<_, RefreshToken>(
+            r#"
+            INSERT INTO refresh_tokens (user_id, token_hash, expires_at, family_id, institution)
+            VALUES ($1, $2, $3, COALESCE($4, gen_random_uuid()), $5)
+            RETURNING id, user_id, token_hash, family_id, institution, issued_at, expires_at, revoked
+            "#,
+        )
+        .bind(user_id)
+        .bind(Self::hash(&token))
+        .bind(Utc::now() + ttl)
+        .bind(family_id)
+        .bind(institution)
+        .fetch_one(executor)
+        .await?;
+
+        Ok((token, stored))
+    }
+
+    /// Exchange `token`, issued for `institution`, for a new refresh token
+    ///
+    /// The old token is revoked, a new one of the same family valid for `ttl`
+    /// is issued and the `token_version` of the account is incremented, all
+    /// in one transaction, so a token can only be used once. A token issued
+    /// for another institution is [`Rotation::Invalid`] and stays usable.
+    ///
+    /// Presenting a token that was already revoked means it leaked or was
+    /// replayed: every token of its family is revoked and `token_version` is
+    /// incremented, which also rejects the access tokens issued so far.
+    pub async fn rotate(
+        pool: &PgPool,
+        token: &str,
+        institution: Option<&str>,
+        ttl: chrono::Duration,
+    ) -> Result<Rotation, DbError> {
+        let token_hash = Self::hash(token);
+
+        crate::tx!(pool, |tx| {
+            let active: Option<(Uuid, Uuid)> = sqlx::query_as(
+                r#"
+                UPDATE refresh_tokens
+                SET revoked = true
+                WHERE token_hash = $1 AND NOT revoked AND expires_at > now()
+                  AND institution IS NOT DISTINCT FROM $2
+                RETURNING user_id, family_id
+                "#,
+            )
+            .bind(&token_hash)
+            .bind(institution)
+            .fetch_optional(&mut **tx)
+            .await?;
+
+            let Some((user_id, family_id)) = active else {
+                let revoked: Option<(Uuid, Uuid)> = sqlx::query_as(
+                    "SELECT user_id, family_id FROM refresh_tokens WHERE token_hash = $1 AND revoked",
+                )
+                .bind(&token_hash)
+                .fetch_optional(&mut **tx)
+                .await?;
+                let Some((user_id, family_id)) = revoked else {
+                    return Ok(Rotation::Invalid);
+                };
+
+                sqlx::query("UPDATE refresh_tokens SET revoked = true WHERE family_id = $1 AND NOT revoked")
+                    .bind(family_id)
+                    .execute(&mut **tx)
+                    .await?;
+                let token_version = increment_token_version(tx, user_id).await?;
+                return Ok(Rotation::Reused { user_id, token_version });
+            };
+
+            let token_version = increment_token_version(tx, user_id).await?;
+            let (token, stored) = Self::issue_in_family(&mut **tx, user_id, institution, ttl, Some(family_id)).await?;
+            Ok::<_, DbError>(Rotation::Rotated { token, stored, token_version })
+        })
+        .await
+    }
+
+    /// Revoke `token`; returns whether an active token was revoked
+    pub async fn revoke(pool: &PgPool, token: &str) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET revoked = true
+            WHERE token_hash = $1 AND NOT revoked
+            "#,
+        )
+        .bind(Self::hash(token))
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke every active token of `user_id`, closing all of their sessions
+    ///
+    /// Returns the number of tokens revoked.
+    pub async fn revoke_all_for_user(pool: &PgPool, user_id: Uuid) -> Result<u64, DbError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET revoked = true
+            WHERE user_id = $1 AND NOT revoked
+            "#,
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Increment the `token_version` of `user_id` inside `tx` and return the new version
+async fn increment_token_version(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, user_id: Uuid) -> Result<i32, DbError> {
+    let token_version = sqlx::query_scalar(
+        r#"
+        UPDATE authentications
+        SET token_version = token_version + 1, updated_at = now()
+        WHERE user_id = $1
+        RETURNING token_version
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(token_version)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_token_hash_is_hex_sha256() {
+        assert_eq!(
+            RefreshToken::hash("abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(RefreshToken::hash("token").len(), 64);
+        assert_ne!(RefreshToken::hash("token"), RefreshToken::hash("token2"));
+    }
+
+    #[test]
+    fn test_refresh_token_activity() {
+        let token = RefreshToken {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            token_hash: RefreshToken::hash("token"),
+            family_id: Uuid::new_v4(),
+            institution: None,
+            issued_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::days(7),
+            revoked: false,
+        };
+        assert!(token.is_active());
+
+        assert!(!RefreshToken { revoked: true, ..token.clone() }.is_active());
+        assert!(!RefreshToken { expires_at: Utc::now() - chrono::Duration::seconds(1), ..token }.is_active());
+    }
+}
@@ -0,0 +1,220 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Tipo de evento del calendario académico
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type, PartialEq, Eq)]
+#[sqlx(type_name = "calendar_event_type", rename_all = "snake_case")]
+pub enum CalendarEventType {
+    /// Feriado decidido por la institución (día sin clases)
+    InstitutionalHoliday,
+    /// Feriado nacional (día sin clases)
+    NationalHoliday,
+    /// Receso escolar, como las vacaciones de invierno
+    Recess,
+    /// Período de exámenes
+    ExamPeriod,
+    /// Acto o actividad escolar con clases normales
+    SchoolEvent,
+}
+
+impl CalendarEventType {
+    /// Indica si durante el evento no se dictan clases
+    pub fn is_non_teaching(&self) -> bool {
+        matches!(
+            self,
+            CalendarEventType::InstitutionalHoliday
+                | CalendarEventType::NationalHoliday
+                | CalendarEventType::Recess
+        )
+    }
+}
+
+/// Evento del calendario académico
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CalendarEvent {
+    /// Identificador único del evento
+    pub id: Uuid,
+    /// Año académico al que pertenece
+    pub academic_year: i32,
+    /// Nombre del evento (p. ej. "Día del Maestro")
+    pub name: String,
+    /// Tipo de evento
+    pub event_type: CalendarEventType,
+    /// Primer día del evento
+    pub start_date: NaiveDate,
+    /// Último día del evento (inclusivo)
+    pub end_date: NaiveDate,
+    /// Fecha de creación del registro
+    pub created_at: DateTime<Utc>,
+    /// Última actualización del registro
+    pub updated_at: DateTime<Utc>,
+}
+
+/// DTO para la creación de un evento del calendario
+#[derive(Debug, Deserialize)]
+pub struct CreateCalendarEventDto {
+    pub academic_year: i32,
+    pub name: String,
+    pub event_type: CalendarEventType,
+    pub start_date: NaiveDate,
+    /// Si se omite, el evento dura un solo día
+    pub end_date: Option<NaiveDate>,
+}
+
+impl CalendarEvent {
+    /// Crea un nuevo evento en el calendario
+    pub async fn create(pool: &PgPool, dto: CreateCalendarEventDto) -> Result<CalendarEvent, DbError> {
+        let end_date = dto.end_date.unwrap_or(dto.start_date);
+
+        let event = sqlx::query_as!(
+            CalendarEvent,
+            r#"
+            INSERT INTO calendar_events (academic_year, name, event_type, start_date, end_date)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, academic_year, name, event_type as "event_type: CalendarEventType",
+                      start_date, end_date, created_at, updated_at
+            "#,
+            dto.academic_year,
+            dto.name,
+            dto.event_type as CalendarEventType,
+            dto.start_date,
+            end_date
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(event)
+    }
+
+    /// Lista los eventos de un año académico en orden cronológico
+    pub async fn find_by_year(pool: &PgPool, academic_year: i32) -> Result<Vec<CalendarEvent>, DbError> {
+        let events = sqlx::query_as!(
+            CalendarEvent,
+            r#"
+            SELECT id, academic_year, name, event_type as "event_type: CalendarEventType",
+                   start_date, end_date, created_at, updated_at
+            FROM calendar_events
+            WHERE academic_year = $1
+            ORDER BY start_date, end_date
+            "#,
+            academic_year
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Indica si la fecha está dentro del evento
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start_date <= date && date <= self.end_date
+    }
+
+    /// Días que abarca el evento, del primero al último
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.start_date
+            .iter_days()
+            .take_while(move |date| *date <= self.end_date)
+    }
+}
+
+/// Calendario académico de un año
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcademicCalendar {
+    /// Año académico
+    pub academic_year: i32,
+    /// Eventos del año en orden cronológico
+    pub events: Vec<CalendarEvent>,
+}
+
+impl AcademicCalendar {
+    /// Carga el calendario de un año académico
+    pub async fn load(pool: &PgPool, academic_year: i32) -> Result<AcademicCalendar, DbError> {
+        let events = CalendarEvent::find_by_year(pool, academic_year).await?;
+
+        Ok(AcademicCalendar { academic_year, events })
+    }
+
+    /// Feriado o receso que cae en la fecha, si lo hay
+    pub fn non_teaching_event(&self, date: NaiveDate) -> Option<&CalendarEvent> {
+        self.events
+            .iter()
+            .find(|event| event.event_type.is_non_teaching() && event.contains(date))
+    }
+
+    /// Indica si la fecha es feriado o receso
+    pub fn is_non_teaching_day(&self, date: NaiveDate) -> bool {
+        self.non_teaching_event(date).is_some()
+    }
+
+    /// Feriados y recesos del año, en orden cronológico
+    pub fn non_teaching_events(&self) -> impl Iterator<Item = &CalendarEvent> {
+        self.events.iter().filter(|event| event.event_type.is_non_teaching())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(name: &str, event_type: CalendarEventType, start: (u32, u32), end: (u32, u32)) -> CalendarEvent {
+        CalendarEvent {
+            id: Uuid::new_v4(),
+            academic_year: 2025,
+            name: name.to_string(),
+            event_type,
+            start_date: NaiveDate::from_ymd_opt(2025, start.0, start.1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2025, end.0, end.1).unwrap(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn calendar() -> AcademicCalendar {
+        AcademicCalendar {
+            academic_year: 2025,
+            events: vec![
+                event("Día del Maestro", CalendarEventType::InstitutionalHoliday, (4, 30), (4, 30)),
+                event("Exámenes parciales", CalendarEventType::ExamPeriod, (6, 16), (6, 20)),
+                event("Vacaciones de invierno", CalendarEventType::Recess, (7, 7), (7, 18)),
+            ],
+        }
+    }
+
+    fn date(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_holiday_is_non_teaching_day() {
+        let event = calendar().non_teaching_event(date(4, 30)).cloned().unwrap();
+        assert_eq!(event.name, "Día del Maestro");
+    }
+
+    #[test]
+    fn test_recess_covers_range_inclusively() {
+        let calendar = calendar();
+        assert!(calendar.is_non_teaching_day(date(7, 7)));
+        assert!(calendar.is_non_teaching_day(date(7, 18)));
+        assert!(!calendar.is_non_teaching_day(date(7, 19)));
+    }
+
+    #[test]
+    fn test_exam_period_has_classes() {
+        assert!(!calendar().is_non_teaching_day(date(6, 17)));
+    }
+
+    #[test]
+    fn test_event_dates() {
+        let calendar = calendar();
+        let recess = &calendar.events[2];
+        let dates: Vec<NaiveDate> = recess.dates().collect();
+        assert_eq!(dates.len(), 12);
+        assert_eq!(dates.first(), Some(&date(7, 7)));
+        assert_eq!(dates.last(), Some(&date(7, 18)));
+    }
+}
@@ -1,8 +1,7 @@
-use crate::models::{Course, ScheduleSlot, TeacherStatus};
-use anyhow::Result;
-use chrono::{DateTime, Utc};
+use crate::db::DbError;
+use crate::models::{patch::required, Course, JsonColumn, Patch, ScheduleSlot, TeacherStatus};
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Postgres, postgres::PgPool, FromRow};
+use sqlx::{Pool, Postgres, postgres::PgPool};
 use uuid::Uuid;
 
 /// Data Transfer Object para la creación de un nuevo curso
@@ -47,10 +46,48 @@ pub struct UpdateCourseDto {
     pub schedule: Option<Vec<ScheduleSlot>>,
 }
 
+/// Data Transfer Object para la actualización parcial (merge-patch) de un curso
+///
+/// `description` y `teacher_id` aceptan `null` para limpiar la columna; en el
+/// resto de los campos un `null` es un error de tipo.
+#[derive(Debug, Deserialize)]
+pub struct PatchCourseDto {
+    #[serde(default, deserialize_with = "required")]
+    pub code: Option<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Patch<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub grade_level: Option<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub credits: Option<f32>,
+    #[serde(default)]
+    pub teacher_id: Patch<Uuid>,
+    #[serde(default, deserialize_with = "required")]
+    pub academic_year: Option<i32>,
+    #[serde(default, deserialize_with = "required")]
+    pub schedule: Option<Vec<ScheduleSlot>>,
+}
+
+/// Profundidad máxima de la cadena de prerrequisitos; superarla indica un ciclo
+pub const MAX_PREREQUISITE_DEPTH: u32 = 10;
+
+/// Nodo del árbol de prerrequisitos de un curso
+#[derive(Debug, Clone, Serialize)]
+pub struct CourseNode {
+    /// Curso en este nivel del árbol
+    pub course: Course,
+    /// Distancia al curso consultado (0 es el propio curso)
+    pub depth: u32,
+    /// Si se consultó por un estudiante, indica si ya completó el curso
+    pub completed_by_student: Option<bool>,
+}
+
 /// Implementación de métodos para el modelo de Curso
 impl Course {
     /// Crea un nuevo curso en la base de datos
-    pub async fn create(db: &Pool<Postgres>, dto: CreateCourseDto) -> Result<Self> {
+    pub async fn create(db: &Pool<Postgres>, dto: CreateCourseDto) -> Result<Self, DbError> {
         // Generar un nuevo UUID para el curso
         let id = Uuid::new_v4();
         
@@ -69,7 +106,7 @@ impl Course {
             RETURNING 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             "#,
             id,
             dto.code,
@@ -88,14 +125,14 @@ impl Course {
     }
     
     /// Encuentra un curso por su ID
-    pub async fn find_by_id(db: &Pool<Postgres>, id: Uuid) -> Result<Option<Self>> {
+    pub async fn find_by_id(db: &Pool<Postgres>, id: Uuid) -> Result<Option<Self>, DbError> {
         let course = sqlx::query_as!(
             Course,
             r#"
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             WHERE id = $1
             "#,
@@ -108,14 +145,14 @@ impl Course {
     }
     
     /// Encuentra un curso por su código
-    pub async fn find_by_code(db: &Pool<Postgres>, code: &str) -> Result<Option<Self>> {
+    pub async fn find_by_code(db: &Pool<Postgres>, code: &str) -> Result<Option<Self>, DbError> {
         let course = sqlx::query_as!(
             Course,
             r#"
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             WHERE code = $1
             "#,
@@ -128,14 +165,14 @@ impl Course {
     }
     
     /// Encuentra cursos por grado/nivel
-    pub async fn find_by_grade_level(db: &Pool<Postgres>, grade_level: &str) -> Result<Vec<Self>> {
+    pub async fn find_by_grade_level(db: &Pool<Postgres>, grade_level: &str) -> Result<Vec<Self>, DbError> {
         let courses = sqlx::query_as!(
             Course,
             r#"
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             WHERE grade_level = $1
             ORDER BY name
@@ -149,14 +186,14 @@ impl Course {
     }
     
     /// Encuentra cursos por profesor asignado
-    pub async fn find_by_teacher(db: &Pool<Postgres>, teacher_id: Uuid) -> Result<Vec<Self>> {
+    pub async fn find_by_teacher(db: &Pool<Postgres>, teacher_id: Uuid) -> Result<Vec<Self>, DbError> {
         let courses = sqlx::query_as!(
             Course,
             r#"
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             WHERE teacher_id = $1
             ORDER BY name
@@ -170,14 +207,14 @@ impl Course {
     }
     
     /// Encuentra cursos por año académico
-    pub async fn find_by_academic_year(db: &Pool<Postgres>, academic_year: i32) -> Result<Vec<Self>> {
+    pub async fn find_by_academic_year(db: &Pool<Postgres>, academic_year: i32) -> Result<Vec<Self>, DbError> {
         let courses = sqlx::query_as!(
             Course,
             r#"
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             WHERE academic_year = $1
             ORDER BY name
@@ -191,14 +228,14 @@ impl Course {
     }
     
     /// Encuentra cursos sin profesor asignado
-    pub async fn find_unassigned_courses(db: &Pool<Postgres>) -> Result<Vec<Self>> {
+    pub async fn find_unassigned_courses(db: &Pool<Postgres>) -> Result<Vec<Self>, DbError> {
         let courses = sqlx::query_as!(
             Course,
             r#"
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             WHERE teacher_id IS NULL
             ORDER BY name
@@ -215,7 +252,7 @@ impl Course {
         db: &Pool<Postgres>, 
         page: u32, 
         page_size: u32
-    ) -> Result<Vec<Self>> {
+    ) -> Result<Vec<Self>, DbError> {
         let offset = (page - 1) * page_size;
         
         let courses = sqlx::query_as!(
@@ -224,7 +261,7 @@ impl Course {
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             ORDER BY name
             LIMIT $1 OFFSET $2
@@ -239,7 +276,7 @@ impl Course {
     }
     
     /// Busca cursos que coincidan con un término de búsqueda
-    pub async fn search(db: &Pool<Postgres>, term: &str) -> Result<Vec<Self>> {
+    pub async fn search(db: &Pool<Postgres>, term: &str) -> Result<Vec<Self>, DbError> {
         let search_term = format!("%{}%", term);
         
         let courses = sqlx::query_as!(
@@ -248,7 +285,7 @@ impl Course {
             SELECT 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             FROM courses 
             WHERE 
                 code ILIKE $1 OR 
@@ -266,7 +303,7 @@ impl Course {
     }
     
     /// Actualiza un curso existente
-    pub async fn update(&self, db: &Pool<Postgres>, dto: UpdateCourseDto) -> Result<Self> {
+    pub async fn update(&self, db: &Pool<Postgres>, dto: UpdateCourseDto) -> Result<Self, DbError> {
         // Preparar los valores para actualizar
         let code = dto.code.unwrap_or_else(|| self.code.clone());
         let name = dto.name.unwrap_or_else(|| self.name.clone());
@@ -297,7 +334,7 @@ impl Course {
             RETURNING 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             "#,
             code,
             name,
@@ -315,8 +352,56 @@ impl Course {
         Ok(updated_course)
     }
     
+    /// Aplica una actualización parcial (merge-patch) a un curso existente
+    ///
+    /// El cambio se resuelve en un único `UPDATE`, de modo que dos parches
+    /// concurrentes sobre campos distintos no se pisan entre sí.
+    pub async fn patch(&self, db: &Pool<Postgres>, dto: PatchCourseDto) -> Result<Self, DbError> {
+        // Los campos ausentes conservan su valor; `null` limpia los opcionales
+        let description_sent = !dto.description.is_undefined();
+        let teacher_id_sent = !dto.teacher_id.is_undefined();
+        let schedule_json = dto.schedule.as_ref().map(serde_json::to_value).transpose()?;
+        
+        let patched_course = sqlx::query_as!(
+            Course,
+            r#"
+            UPDATE courses 
+            SET 
+                code = COALESCE($1, code),
+                name = COALESCE($2, name),
+                description = CASE WHEN $3 THEN $4 ELSE description END,
+                grade_level = COALESCE($5, grade_level),
+                credits = COALESCE($6, credits),
+                teacher_id = CASE WHEN $7 THEN $8 ELSE teacher_id END,
+                academic_year = COALESCE($9, academic_year),
+                schedule = COALESCE($10, schedule)
+            WHERE id = $11
+            RETURNING 
+                id, code, name, description, grade_level, 
+                credits, teacher_id, academic_year, 
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
+            "#,
+            dto.code,
+            dto.name,
+            description_sent,
+            dto.description.into_option(),
+            dto.grade_level,
+            dto.credits,
+            teacher_id_sent,
+            dto.teacher_id.into_option(),
+            dto.academic_year,
+            schedule_json,
+            self.id
+        )
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| DbError::NotFound("Curso no encontrado".to_string()))?;
+        
+        Ok(patched_course)
+    }
+    
     /// Elimina un curso de la base de datos
-    pub async fn delete(&self, db: &Pool<Postgres>) -> Result<()> {
+    pub async fn delete(&self, db: &Pool<Postgres>) -> Result<(), DbError> {
         sqlx::query!(
             "DELETE FROM courses WHERE id = $1",
             self.id
@@ -328,7 +413,7 @@ impl Course {
     }
     
     /// Asigna un profesor a un curso
-    pub async fn assign_teacher(&self, db: &Pool<Postgres>, teacher_id: Uuid) -> Result<Self> {
+    pub async fn assign_teacher(&self, db: &Pool<Postgres>, teacher_id: Uuid) -> Result<Self, DbError> {
         // Verificar que el profesor exista y esté activo
         let teacher_exists = sqlx::query!(
             r#"
@@ -343,10 +428,10 @@ impl Course {
         
         if let Some(teacher) = teacher_exists {
             if teacher.status != TeacherStatus::Active {
-                return Err(anyhow::anyhow!("El profesor no está activo"));
+                return Err(DbError::Conflict("El profesor no está activo".to_string()));
             }
         } else {
-            return Err(anyhow::anyhow!("Profesor no encontrado"));
+            return Err(DbError::NotFound("Profesor no encontrado".to_string()));
         }
         
         // Actualizar el curso con el nuevo profesor
@@ -359,7 +444,7 @@ impl Course {
             RETURNING 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             "#,
             teacher_id,
             self.id
@@ -371,7 +456,7 @@ impl Course {
     }
     
     /// Elimina la asignación de profesor de un curso
-    pub async fn unassign_teacher(&self, db: &Pool<Postgres>) -> Result<Self> {
+    pub async fn unassign_teacher(&self, db: &Pool<Postgres>) -> Result<Self, DbError> {
         // Actualizar el curso para quitar el profesor
         let updated_course = sqlx::query_as!(
             Course,
@@ -382,7 +467,7 @@ impl Course {
             RETURNING 
                 id, code, name, description, grade_level, 
                 credits, teacher_id, academic_year, 
-                schedule as "schedule!: Vec<ScheduleSlot>"
+                schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"
             "#,
             self.id
         )
@@ -393,7 +478,7 @@ impl Course {
     }
     
     /// Obtiene el número total de cursos
-    pub async fn count(db: &Pool<Postgres>) -> Result<i64> {
+    pub async fn count(db: &Pool<Postgres>) -> Result<i64, DbError> {
         let result = sqlx::query!(
             "SELECT COUNT(*) as count FROM courses"
         )
@@ -404,7 +489,7 @@ impl Course {
     }
     
     /// Obtiene estadísticas sobre los cursos por grado
-    pub async fn stats_by_grade(db: &Pool<Postgres>) -> Result<Vec<(String, i64)>> {
+    pub async fn stats_by_grade(db: &Pool<Postgres>) -> Result<Vec<(String, i64)>, DbError> {
         let rows = sqlx::query!(
             r#"
             SELECT grade_level, COUNT(*) as count
@@ -424,7 +509,7 @@ impl Course {
     }
     
     /// Obtiene estadísticas sobre los cursos por año académico
-    pub async fn stats_by_academic_year(db: &Pool<Postgres>) -> Result<Vec<(i32, i64)>> {
+    pub async fn stats_by_academic_year(db: &Pool<Postgres>) -> Result<Vec<(i32, i64)>, DbError> {
         let rows = sqlx::query!(
             r#"
             SELECT academic_year, COUNT(*) as count
@@ -443,4 +528,208 @@ impl Course {
 
         Ok(stats)
     }
+
+    /// Busca cursos con nombre similar al de un curso dado usando `pg_trgm`
+    ///
+    /// Devuelve cada curso candidato junto con la similitud del nombre y de la
+    /// descripción (0.0 si alguno de los cursos no tiene descripción).
+    /// `similarity` va calificada con `public`, donde vive la extensión, porque
+    /// el `search_path` de una institución solo incluye su propio esquema.
+    pub async fn find_similar(
+        db: &Pool<Postgres>,
+        course_id: Uuid,
+        threshold: f64,
+    ) -> Result<Vec<(Self, f64, f64)>, DbError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                b.id, b.code, b.name, b.description, b.grade_level,
+                b.credits, b.teacher_id, b.academic_year,
+                b.schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>",
+                public.similarity(a.name, b.name)::float8 as "name_similarity!",
+                COALESCE(public.similarity(a.description, b.description), 0)::float8 as "description_similarity!"
+            FROM courses a
+            JOIN courses b ON a.id <> b.id
+            WHERE a.id = $1 AND public.similarity(a.name, b.name) > $2
+            ORDER BY public.similarity(a.name, b.name) DESC
+            "#,
+            course_id,
+            threshold as f32
+        )
+        .fetch_all(db)
+        .await?;
+
+        let similar = rows.into_iter()
+            .map(|row| {
+                let course = Course {
+                    id: row.id,
+                    code: row.code,
+                    name: row.name,
+                    description: row.description,
+                    grade_level: row.grade_level,
+                    credits: row.credits,
+                    teacher_id: row.teacher_id,
+                    academic_year: row.academic_year,
+                    schedule: row.schedule.into(),
+                };
+                (course, row.name_similarity, row.description_similarity)
+            })
+            .collect();
+
+        Ok(similar)
+    }
+
+    /// Obtiene el árbol completo de prerrequisitos de un curso (todos los niveles)
+    ///
+    /// Usa una CTE recursiva sobre la columna `prerequisites`. Si la cadena
+    /// supera `MAX_PREREQUISITE_DEPTH` niveles se asume un ciclo y se devuelve
+    /// un error. Si se indica `student_id`, cada nodo informa si el estudiante
+    /// ya completó el curso.
+    pub async fn get_full_prerequisite_tree(
+        pool: &PgPool,
+        course_id: Uuid,
+        student_id: Option<Uuid>,
+    ) -> Result<Vec<CourseNode>, DbError> {
+        let rows = sqlx::query!(
+            r#"
+            WITH RECURSIVE prereq_tree AS (
+                SELECT id, prerequisites, 0 AS depth
+                FROM courses
+                WHERE id = $1
+                UNION ALL
+                SELECT c.id, c.prerequisites, pt.depth + 1
+                FROM courses c
+                JOIN prereq_tree pt ON c.id = ANY(pt.prerequisites)
+                WHERE pt.depth <= $3
+            )
+            SELECT
+                c.id, c.code, c.name, c.description, c.grade_level,
+                c.credits, c.teacher_id, c.academic_year,
+                c.schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>",
+                pt.depth as "depth!",
+                CASE WHEN $2::uuid IS NULL THEN NULL
+                     ELSE EXISTS (
+                         SELECT 1 FROM enrollments e
+                         WHERE e.course_id = c.id
+                           AND e.student_id = $2
+                           AND e.status = 'completed'
+                     )
+                END as completed_by_student
+            FROM prereq_tree pt
+            JOIN courses c ON c.id = pt.id
+            ORDER BY pt.depth, c.name
+            "#,
+            course_id,
+            student_id,
+            MAX_PREREQUISITE_DEPTH as i32
+        )
+        .fetch_all(pool)
+        .await?;
+
+        if rows.is_empty() {
+            return Err(DbError::NotFound("Curso no encontrado".to_string()));
+        }
+
+        let depths: Vec<i32> = rows.iter().map(|row| row.depth).collect();
+        check_prerequisite_depth(&depths)?;
+
+        let tree = rows.into_iter()
+            .map(|row| CourseNode {
+                course: Course {
+                    id: row.id,
+                    code: row.code,
+                    name: row.name,
+                    description: row.description,
+                    grade_level: row.grade_level,
+                    credits: row.credits,
+                    teacher_id: row.teacher_id,
+                    academic_year: row.academic_year,
+                    schedule: row.schedule.into(),
+                },
+                depth: row.depth as u32,
+                completed_by_student: row.completed_by_student,
+            })
+            .collect();
+
+        Ok(tree)
+    }
+}
+
+/// Verifica que ningún nodo supere la profundidad máxima (indicio de un ciclo)
+fn check_prerequisite_depth(depths: &[i32]) -> Result<(), DbError> {
+    if depths.iter().any(|&depth| depth > MAX_PREREQUISITE_DEPTH as i32) {
+        return Err(DbError::Conflict(format!(
+            "Ciclo detectado en los prerrequisitos: la cadena supera {} niveles",
+            MAX_PREREQUISITE_DEPTH
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prerequisite_depth_within_limit() {
+        assert!(check_prerequisite_depth(&[0, 1, 2, MAX_PREREQUISITE_DEPTH as i32]).is_ok());
+    }
+
+    #[test]
+    fn test_prerequisite_depth_over_limit_is_cycle() {
+        let result = check_prerequisite_depth(&[0, 1, MAX_PREREQUISITE_DEPTH as i32 + 1]);
+
+        assert!(matches!(result, Err(DbError::Conflict(_))));
+    }
+
+    fn slot(day: u8, start: &str, end: &str) -> ScheduleSlot {
+        ScheduleSlot {
+            day_of_week: day,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            classroom: "Aula 1".to_string(),
+        }
+    }
+
+    fn invalid_fields(slots: &[ScheduleSlot]) -> Vec<String> {
+        match ScheduleSlot::validate_schedule(slots) {
+            Err(DbError::Invalid(errors)) => errors.into_iter().map(|error| error.field.into_owned()).collect(),
+            other => panic!("expected invalid fields, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_schedule_slot_invalid_fields() {
+        assert_eq!(invalid_fields(&[slot(0, "08:00", "09:00")]), ["schedule[0].day_of_week"]);
+        assert_eq!(invalid_fields(&[slot(8, "08:00", "09:00")]), ["schedule[0].day_of_week"]);
+        assert_eq!(invalid_fields(&[slot(1, "8h", "09:00")]), ["schedule[0].start_time"]);
+        assert_eq!(invalid_fields(&[slot(1, "08:00", "25:00")]), ["schedule[0].end_time"]);
+        assert_eq!(invalid_fields(&[slot(1, "09:00", "08:00")]), ["schedule[0].end_time"]);
+        assert_eq!(invalid_fields(&[slot(1, "09:00", "09:00")]), ["schedule[0].end_time"]);
+        assert_eq!(invalid_fields(&[]), ["schedule"]);
+        assert_eq!(invalid_fields(&[slot(1, "08:00", "09:00"), slot(1, "08:00", "09:00")]), ["schedule[1]"]);
+    }
+
+    #[test]
+    fn test_schedule_reports_every_error() {
+        let slots = [
+            slot(1, "08:00", "09:00"),
+            slot(9, "10:00", "11:00"),
+            slot(2, "noon", "10:00"),
+            slot(3, "11:00", "10:30"),
+        ];
+
+        assert_eq!(
+            invalid_fields(&slots),
+            ["schedule[1].day_of_week", "schedule[2].start_time", "schedule[3].end_time"]
+        );
+        assert_eq!(slot(0, "x", "y").validate().unwrap_err().len(), 3);
+    }
+
+    #[test]
+    fn test_back_to_back_slots_are_valid() {
+        let slots = [slot(1, "08:00", "09:00"), slot(1, "09:00", "10:00"), slot(7, "00:00", "23:59")];
+
+        assert!(ScheduleSlot::validate_schedule(&slots).is_ok());
+    }
 }
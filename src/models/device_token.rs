@@ -0,0 +1,176 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgRow, Error as SqlxError, PgPool, Row};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Plataforma de la aplicación que registró el dispositivo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Platform {
+    Ios,
+    Android,
+    Web,
+}
+
+impl Platform {
+    /// Representación de la plataforma tal como se almacena en la base de datos
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Ios => "ios",
+            Platform::Android => "android",
+            Platform::Web => "web",
+        }
+    }
+
+    /// Convierte el valor almacenado en la base de datos en una plataforma
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "ios" => Some(Platform::Ios),
+            "android" => Some(Platform::Android),
+            "web" => Some(Platform::Web),
+            _ => None,
+        }
+    }
+}
+
+/// Token de notificaciones push de un dispositivo del usuario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceToken {
+    /// Identificador único
+    pub id: Uuid,
+    /// Usuario dueño del dispositivo
+    pub user_id: Uuid,
+    /// Token de registro de Firebase Cloud Messaging
+    pub token: String,
+    /// Plataforma de la aplicación
+    pub platform: Platform,
+    /// Fecha del último registro del token
+    pub registered_at: DateTime<Utc>,
+}
+
+impl DeviceToken {
+    /// Registra el token de un dispositivo
+    ///
+    /// Si el token ya existía (por ejemplo, otro usuario inició sesión en el
+    /// mismo dispositivo) pasa a pertenecer al usuario indicado.
+    pub async fn register(
+        pool: &PgPool,
+        user_id: Uuid,
+        token: &str,
+        platform: Platform,
+    ) -> Result<DeviceToken, DbError> {
+        let row = sqlx::query(
+            r#"
+            INSERT INTO device_tokens (user_id, token, platform)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (token) DO UPDATE
+                SET user_id = EXCLUDED.user_id,
+                    platform = EXCLUDED.platform,
+                    registered_at = CURRENT_TIMESTAMP
+            RETURNING id, user_id, token, platform, registered_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(token)
+        .bind(platform.as_str())
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(&row)
+    }
+
+    /// Tokens registrados por un usuario
+    pub async fn find_by_user(pool: &PgPool, user_id: Uuid) -> Result<Vec<DeviceToken>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, user_id, token, platform, registered_at
+            FROM device_tokens
+            WHERE user_id = $1
+            ORDER BY registered_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Tokens de los usuarios con alguno de los correos indicados
+    pub async fn find_by_emails(pool: &PgPool, emails: &[String]) -> Result<Vec<DeviceToken>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT d.id, d.user_id, d.token, d.platform, d.registered_at
+            FROM device_tokens d
+            JOIN users u ON u.id = d.user_id
+            WHERE u.email = ANY($1)
+            "#,
+        )
+        .bind(emails)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Elimina un token del usuario
+    ///
+    /// # Returns
+    ///
+    /// `true` si el token existía y pertenecía al usuario
+    pub async fn delete_for_user(pool: &PgPool, user_id: Uuid, token: &str) -> Result<bool, DbError> {
+        let result = sqlx::query("DELETE FROM device_tokens WHERE user_id = $1 AND token = $2")
+            .bind(user_id)
+            .bind(token)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Elimina tokens que el proveedor informó como inválidos
+    pub async fn delete_tokens(pool: &PgPool, tokens: &[String]) -> Result<u64, DbError> {
+        let result = sqlx::query("DELETE FROM device_tokens WHERE token = ANY($1)")
+            .bind(tokens)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn from_row(row: &PgRow) -> Result<DeviceToken, DbError> {
+        let platform: String = row.try_get("platform")?;
+
+        Ok(DeviceToken {
+            id: row.try_get("id")?,
+            user_id: row.try_get("user_id")?,
+            token: row.try_get("token")?,
+            platform: Platform::from_db(&platform).ok_or_else(|| SqlxError::ColumnDecode {
+                index: "platform".to_string(),
+                source: format!("plataforma desconocida: {}", platform).into(),
+            })?,
+            registered_at: row.try_get("registered_at")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_platform_round_trip() {
+        for platform in [Platform::Ios, Platform::Android, Platform::Web] {
+            assert_eq!(Platform::from_db(platform.as_str()), Some(platform));
+        }
+        assert_eq!(Platform::from_db("windows"), None);
+    }
+
+    #[test]
+    fn test_platform_json_is_lowercase() {
+        assert_eq!(serde_json::to_string(&Platform::Ios).unwrap(), "\"ios\"");
+        assert_eq!(serde_json::from_str::<Platform>("\"android\"").unwrap(), Platform::Android);
+    }
+}
@@ -1,11 +1,14 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{Error, Pool, Postgres, Row};
+use sqlx::{Postgres, Transaction};
 use uuid::Uuid;
 
-use crate::db::DbPool;
+use crate::db::{DbError, DbPool};
 use crate::models::{Course, Student};
 
+/// Unique index over student, course and academic year of every enrollment that is not withdrawn
+pub const ACTIVE_ENROLLMENT_INDEX: &str = "enrollments_active_student_course_year_key";
+
 /// Status of a student's enrollment in a course
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -22,28 +25,83 @@ pub enum EnrollmentStatus {
     Pending,
 }
 
-impl std::fmt::Display for EnrollmentStatus {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl EnrollmentStatus {
+    /// Every status, in the order they are listed in errors
+    pub const ALL: [EnrollmentStatus; 5] = [
+        EnrollmentStatus::Active,
+        EnrollmentStatus::Withdrawn,
+        EnrollmentStatus::Completed,
+        EnrollmentStatus::OnHold,
+        EnrollmentStatus::Pending,
+    ];
+
+    /// Name stored in the `status` column and used in JSON
+    pub fn as_str(&self) -> &'static str {
         match self {
-            EnrollmentStatus::Active => write!(f, "active"),
-            EnrollmentStatus::Withdrawn => write!(f, "withdrawn"),
-            EnrollmentStatus::Completed => write!(f, "completed"),
-            EnrollmentStatus::OnHold => write!(f, "on_hold"),
-            EnrollmentStatus::Pending => write!(f, "pending"),
+            EnrollmentStatus::Active => "active",
+            EnrollmentStatus::Withdrawn => "withdrawn",
+            EnrollmentStatus::Completed => "completed",
+            EnrollmentStatus::OnHold => "on_hold",
+            EnrollmentStatus::Pending => "pending",
         }
     }
 }
 
-impl From<&str> for EnrollmentStatus {
-    fn from(s: &str) -> Self {
-        match s.to_lowercase().as_str() {
-            "active" => EnrollmentStatus::Active,
-            "withdrawn" => EnrollmentStatus::Withdrawn,
-            "completed" => EnrollmentStatus::Completed,
-            "on_hold" => EnrollmentStatus::OnHold,
-            "pending" => EnrollmentStatus::Pending,
-            _ => EnrollmentStatus::Active, // Default to active if unknown
-        }
+impl std::fmt::Display for EnrollmentStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Text that does not name an [`EnrollmentStatus`]
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Unknown enrollment status {0:?} (expected one of active, withdrawn, completed, on_hold, pending)")]
+pub struct UnknownEnrollmentStatus(pub String);
+
+impl std::str::FromStr for EnrollmentStatus {
+    type Err = UnknownEnrollmentStatus;
+
+    /// Exact names only: an unknown value is an error, never a default
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EnrollmentStatus::ALL
+            .into_iter()
+            .find(|status| status.as_str() == s)
+            .ok_or_else(|| UnknownEnrollmentStatus(s.to_string()))
+    }
+}
+
+// Stored as text (`VARCHAR` in Postgres, `TEXT` in SQLite), decoded strictly
+impl<DB: sqlx::Database> sqlx::Type<DB> for EnrollmentStatus
+where
+    str: sqlx::Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <str as sqlx::Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <str as sqlx::Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'r, DB: sqlx::Database> sqlx::Decode<'r, DB> for EnrollmentStatus
+where
+    &'r str: sqlx::Decode<'r, DB>,
+{
+    fn decode(value: <DB as sqlx::database::HasValueRef<'r>>::ValueRef) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(<&str as sqlx::Decode<DB>>::decode(value)?.parse()?)
+    }
+}
+
+impl<'q, DB: sqlx::Database> sqlx::Encode<'q, DB> for EnrollmentStatus
+where
+    &'q str: sqlx::Encode<'q, DB>,
+{
+    fn encode_by_ref(
+        &self,
+        buf: &mut <DB as sqlx::database::HasArguments<'q>>::ArgumentBuffer,
+    ) -> sqlx::encode::IsNull {
+        <&str as sqlx::Encode<DB>>::encode(self.as_str(), buf)
     }
 }
 
@@ -56,6 +114,8 @@ pub struct Enrollment {
     pub student_id: Uuid,
     /// Reference to the course the student is enrolled in
     pub course_id: Uuid,
+    /// Academic year of the enrollment; multi-year programs have one enrollment per year
+    pub academic_year: i32,
     /// Date when the student enrolled in the course
     pub enrollment_date: DateTime<Utc>,
     /// Current status of the enrollment (active, withdrawn, completed, etc.)
@@ -81,6 +141,8 @@ pub struct NewEnrollment {
     pub student_id: Uuid,
     /// ID of the course to enroll in
     pub course_id: Uuid,
+    /// Academic year of the enrollment
+    pub academic_year: i32,
     /// Initial status of the enrollment (defaults to Pending if not provided)
     pub status: Option<EnrollmentStatus>,
     /// Optional notes or comments about the enrollment
@@ -106,27 +168,30 @@ pub struct EnrollmentUpdate {
 
 impl Enrollment {
     /// Create a new enrollment in the database
-    pub async fn create(db: &DbPool, new_enrollment: &NewEnrollment) -> Result<Self, Error> {
+    ///
+    /// A second enrollment that is not withdrawn for the same student, course
+    /// and academic year fails on [`ACTIVE_ENROLLMENT_INDEX`]; see
+    /// [`Enrollment::is_duplicate`]. The index decides between two concurrent
+    /// requests, so there is no separate check beforehand.
+    pub async fn create(db: &DbPool, new_enrollment: &NewEnrollment) -> Result<Self, DbError> {
         // Validate student and course existence
         Self::validate_student_course(db, new_enrollment.student_id, new_enrollment.course_id).await?;
         
-        // Check if student is already enrolled in this course
-        Self::check_existing_enrollment(db, new_enrollment.student_id, new_enrollment.course_id).await?;
-        
         let status = new_enrollment.status.unwrap_or(EnrollmentStatus::Pending);
         
         let enrollment = sqlx::query_as!(
             Self,
             r#"
-            INSERT INTO enrollments (student_id, course_id, status, notes, payment_info)
-            VALUES ($1, $2, $3, $4, $5)
-            RETURNING id, student_id, course_id, enrollment_date, 
+            INSERT INTO enrollments (student_id, course_id, academic_year, status, notes, payment_info)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, student_id, course_id, academic_year, enrollment_date, 
                       status as "status: EnrollmentStatus", completion_date, final_grade, 
                       notes, payment_info, created_at, updated_at
             "#,
             new_enrollment.student_id,
             new_enrollment.course_id,
-            status.to_string(),
+            new_enrollment.academic_year,
+            status as EnrollmentStatus,
             new_enrollment.notes,
             new_enrollment.payment_info
         )
@@ -137,15 +202,15 @@ impl Enrollment {
     }
     
     /// Validate that both student and course exist
-    async fn validate_student_course(db: &DbPool, student_id: Uuid, course_id: Uuid) -> Result<(), Error> {
+    async fn validate_student_course(db: &DbPool, student_id: Uuid, course_id: Uuid) -> Result<(), DbError> {
         // Check if student exists
-        let student_exists = sqlx::query!("SELECT id FROM students WHERE id = $1", student_id)
+        let student_exists = sqlx::query!("SELECT user_id FROM students WHERE user_id = $1", student_id)
             .fetch_optional(db)
             .await?
             .is_some();
         
         if !student_exists {
-            return Err(Error::RowNotFound);
+            return Err(DbError::NotFound("Student not found".to_string()));
         }
         
         // Check if course exists
@@ -155,35 +220,23 @@ impl Enrollment {
             .is_some();
         
         if !course_exists {
-            return Err(Error::RowNotFound);
+            return Err(DbError::NotFound("Course not found".to_string()));
         }
         
         Ok(())
     }
     
-    /// Check if student is already enrolled in this course
-    async fn check_existing_enrollment(db: &DbPool, student_id: Uuid, course_id: Uuid) -> Result<(), Error> {
-        let existing = sqlx::query!(
-            "SELECT id FROM enrollments WHERE student_id = $1 AND course_id = $2 AND status != 'withdrawn'",
-            student_id,
-            course_id
-        )
-        .fetch_optional(db)
-        .await?;
-        
-        if existing.is_some() {
-            return Err(Error::RowNotFound); // Using RowNotFound as a placeholder for a custom error
-        }
-        
-        Ok(())
+    /// Whether `error` is a second live enrollment in the same course and year
+    pub fn is_duplicate(error: &DbError) -> bool {
+        matches!(error, DbError::Conflict(message) if message.contains(ACTIVE_ENROLLMENT_INDEX))
     }
     
     /// Retrieve an enrollment by its ID
-    pub async fn find_by_id(db: &DbPool, id: Uuid) -> Result<Self, Error> {
+    pub async fn find_by_id(db: &DbPool, id: Uuid) -> Result<Self, DbError> {
         let enrollment = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, student_id, course_id, enrollment_date, 
+            SELECT id, student_id, course_id, academic_year, enrollment_date, 
                    status as "status: EnrollmentStatus", completion_date, final_grade,
                    notes, payment_info, created_at, updated_at
             FROM enrollments
@@ -198,15 +251,16 @@ impl Enrollment {
     }
     
     /// Retrieve all enrollments for a specific student
-    pub async fn find_by_student(db: &DbPool, student_id: Uuid) -> Result<Vec<Self>, Error> {
+    pub async fn find_by_student(db: &DbPool, student_id: Uuid) -> Result<Vec<Self>, DbError> {
         let enrollments = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, student_id, course_id, enrollment_date, 
+            SELECT id, student_id, course_id, academic_year, enrollment_date, 
                    status as "status: EnrollmentStatus", completion_date, final_grade,
                    notes, payment_info, created_at, updated_at
             FROM enrollments
             WHERE student_id = $1
+            ORDER BY academic_year, enrollment_date
             "#,
             student_id
         )
@@ -216,12 +270,37 @@ impl Enrollment {
         Ok(enrollments)
     }
     
+    /// Retrieve the enrollments of a student for one academic year
+    pub async fn find_by_student_and_year(
+        db: &DbPool,
+        student_id: Uuid,
+        academic_year: i32,
+    ) -> Result<Vec<Self>, DbError> {
+        let enrollments = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, student_id, course_id, academic_year, enrollment_date, 
+                   status as "status: EnrollmentStatus", completion_date, final_grade,
+                   notes, payment_info, created_at, updated_at
+            FROM enrollments
+            WHERE student_id = $1 AND academic_year = $2
+            ORDER BY enrollment_date
+            "#,
+            student_id,
+            academic_year
+        )
+        .fetch_all(db)
+        .await?;
+        
+        Ok(enrollments)
+    }
+    
     /// Retrieve all enrollments for a specific course
-    pub async fn find_by_course(db: &DbPool, course_id: Uuid) -> Result<Vec<Self>, Error> {
+    pub async fn find_by_course(db: &DbPool, course_id: Uuid) -> Result<Vec<Self>, DbError> {
         let enrollments = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, student_id, course_id, enrollment_date, 
+            SELECT id, student_id, course_id, academic_year, enrollment_date, 
                    status as "status: EnrollmentStatus", completion_date, final_grade,
                    notes, payment_info, created_at, updated_at
             FROM enrollments
@@ -235,18 +314,36 @@ impl Enrollment {
         Ok(enrollments)
     }
     
+    /// Return which of the given students are actively enrolled in a course
+    pub async fn active_students(db: &DbPool, course_id: Uuid, student_ids: &[Uuid]) -> Result<Vec<Uuid>, DbError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT student_id
+            FROM enrollments
+            WHERE course_id = $1 AND student_id = ANY($2) AND status = $3
+            "#,
+            course_id,
+            student_ids,
+            EnrollmentStatus::Active as EnrollmentStatus
+        )
+        .fetch_all(db)
+        .await?;
+        
+        Ok(rows.into_iter().map(|row| row.student_id).collect())
+    }
+    
     /// Retrieve all enrollments with a specific status
-    pub async fn find_by_status(db: &DbPool, status: EnrollmentStatus) -> Result<Vec<Self>, Error> {
+    pub async fn find_by_status(db: &DbPool, status: EnrollmentStatus) -> Result<Vec<Self>, DbError> {
         let enrollments = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, student_id, course_id, enrollment_date, 
+            SELECT id, student_id, course_id, academic_year, enrollment_date, 
                    status as "status: EnrollmentStatus", completion_date, final_grade,
                    notes, payment_info, created_at, updated_at
             FROM enrollments
             WHERE status = $1
             "#,
-            status.to_string()
+            status as EnrollmentStatus
         )
         .fetch_all(db)
         .await?;
@@ -255,69 +352,38 @@ impl Enrollment {
     }
     
     /// Update an enrollment with new data
-    pub async fn update(db: &DbPool, id: Uuid, update: &EnrollmentUpdate) -> Result<Self, Error> {
-        let mut query = String::from("UPDATE enrollments SET updated_at = NOW()");
-        let mut params: Vec<String> = Vec::new();
-        let mut param_values: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send>> = Vec::new();
-        
-        let mut param_index = 1;
-        
-        // Conditionally add each field to the update query
-        if let Some(status) = &update.status {
-            query.push_str(&format!(", status = ${}", param_index));
-            params.push(status.to_string());
-            param_values.push(Box::new(status.to_string()));
-            param_index += 1;
-        }
-        
-        if let Some(completion_date) = &update.completion_date {
-            query.push_str(&format!(", completion_date = ${}", param_index));
-            params.push(completion_date.to_string());
-            param_values.push(Box::new(completion_date.clone()));
-            param_index += 1;
-        }
-        
-        if let Some(final_grade) = &update.final_grade {
-            query.push_str(&format!(", final_grade = ${}", param_index));
-            params.push(final_grade.to_string());
-            param_values.push(Box::new(*final_grade));
-            param_index += 1;
-        }
-        
-        if let Some(notes) = &update.notes {
-            query.push_str(&format!(", notes = ${}", param_index));
-            params.push(notes.to_string());
-            param_values.push(Box::new(notes.clone()));
-            param_index += 1;
-        }
-        
-        if let Some(payment_info) = &update.payment_info {
-            query.push_str(&format!(", payment_info = ${}", param_index));
-            params.push(payment_info.to_string());
-            param_values.push(Box::new(payment_info.clone()));
-            param_index += 1;
-        }
-        
-        // Add the WHERE clause and RETURNING statement
-        query.push_str(&format!(" WHERE id = ${} RETURNING id, student_id, course_id, enrollment_date, status as \"status: EnrollmentStatus\", completion_date, final_grade, notes, payment_info, created_at, updated_at", param_index));
-        params.push(id.to_string());
-        param_values.push(Box::new(id));
-        
-        // If there are no fields to update, just return the current enrollment
-        if param_index == 1 {
-            return Self::find_by_id(db, id).await;
-        }
-        
-        // Execute the query
-        let enrollment = sqlx::query_as::<_, Self>(&query)
-            .fetch_one(db)
-            .await?;
-        
+    pub async fn update(db: &DbPool, id: Uuid, update: &EnrollmentUpdate) -> Result<Self, DbError> {
+        // Fields left as `None` keep their current value
+        let enrollment = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE enrollments
+            SET status = COALESCE($2, status),
+                completion_date = COALESCE($3, completion_date),
+                final_grade = COALESCE($4, final_grade),
+                notes = COALESCE($5, notes),
+                payment_info = COALESCE($6, payment_info),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, student_id, course_id, academic_year, enrollment_date,
+                      status as "status: EnrollmentStatus", completion_date, final_grade,
+                      notes, payment_info, created_at, updated_at
+            "#,
+            id,
+            update.status.map(|status| status.as_str()),
+            update.completion_date,
+            update.final_grade,
+            update.notes,
+            update.payment_info
+        )
+        .fetch_one(db)
+        .await?;
+
         Ok(enrollment)
     }
     
     /// Delete an enrollment from the database
-    pub async fn delete(db: &DbPool, id: Uuid) -> Result<(), Error> {
+    pub async fn delete(db: &DbPool, id: Uuid) -> Result<(), DbError> {
         sqlx::query!("DELETE FROM enrollments WHERE id = $1", id)
             .execute(db)
             .await?;
@@ -325,8 +391,29 @@ impl Enrollment {
         Ok(())
     }
     
+    /// Lock and return the ids that exist among the given enrollment ids
+    pub async fn lock_existing(tx: &mut Transaction<'_, Postgres>, ids: &[Uuid]) -> Result<Vec<Uuid>, DbError> {
+        let rows = sqlx::query!(
+            "SELECT id FROM enrollments WHERE id = ANY($1) FOR UPDATE",
+            ids
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+        
+        Ok(rows.into_iter().map(|row| row.id).collect())
+    }
+    
+    /// Delete several enrollments inside a transaction
+    pub async fn delete_many(tx: &mut Transaction<'_, Postgres>, ids: &[Uuid]) -> Result<u64, DbError> {
+        let result = sqlx::query!("DELETE FROM enrollments WHERE id = ANY($1)", ids)
+            .execute(&mut **tx)
+            .await?;
+        
+        Ok(result.rows_affected())
+    }
+    
     /// Withdraw a student from a course (special case of update)
-    pub async fn withdraw(db: &DbPool, id: Uuid, notes: Option<String>) -> Result<Self, Error> {
+    pub async fn withdraw(db: &DbPool, id: Uuid, notes: Option<String>) -> Result<Self, DbError> {
         let update = EnrollmentUpdate {
             status: Some(EnrollmentStatus::Withdrawn),
             completion_date: None,
@@ -339,7 +426,7 @@ impl Enrollment {
     }
     
     /// Complete a student's enrollment with a final grade
-    pub async fn complete(db: &DbPool, id: Uuid, final_grade: Option<f64>) -> Result<Self, Error> {
+    pub async fn complete(db: &DbPool, id: Uuid, final_grade: Option<f64>) -> Result<Self, DbError> {
         let update = EnrollmentUpdate {
             status: Some(EnrollmentStatus::Completed),
             completion_date: Some(Utc::now()),
@@ -352,10 +439,14 @@ impl Enrollment {
     }
     
     /// Get enrollment with student and course details
-    pub async fn get_with_details(db: &DbPool, id: Uuid) -> Result<EnrollmentDetails, Error> {
+    pub async fn get_with_details(db: &DbPool, id: Uuid) -> Result<EnrollmentDetails, DbError> {
         let enrollment = Self::find_by_id(db, id).await?;
-        let student = Student::find_by_id(db, enrollment.student_id).await?;
-        let course = Course::find_by_id(db, enrollment.course_id).await?;
+        let student = Student::find_by_user_id(db, enrollment.student_id)
+            .await?
+            .ok_or_else(|| DbError::NotFound(format!("Student {} not found", enrollment.student_id)))?;
+        let course = Course::find_by_id(db, enrollment.course_id)
+            .await?
+            .ok_or_else(|| DbError::NotFound(format!("Course {} not found", enrollment.course_id)))?;
         
         Ok(EnrollmentDetails {
             enrollment,
@@ -376,3 +467,38 @@ pub struct EnrollmentDetails {
     pub course: Course,
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_round_trips_through_text_and_json() {
+        for status in EnrollmentStatus::ALL {
+            assert_eq!(status.to_string().parse::<EnrollmentStatus>(), Ok(status));
+
+            let json = serde_json::to_value(status).unwrap();
+            assert_eq!(json, status.as_str());
+            assert_eq!(serde_json::from_value::<EnrollmentStatus>(json).unwrap(), status);
+        }
+    }
+
+    #[test]
+    fn test_unknown_status_is_rejected() {
+        let error = "actve".parse::<EnrollmentStatus>().unwrap_err();
+        assert_eq!(error, UnknownEnrollmentStatus("actve".to_string()));
+        assert!(error.to_string().contains("on_hold"));
+        // Names are exact, as stored in the database
+        assert!("Active".parse::<EnrollmentStatus>().is_err());
+        assert!("".parse::<EnrollmentStatus>().is_err());
+
+        let error = serde_json::from_value::<NewEnrollment>(serde_json::json!({
+            "student_id": Uuid::new_v4(),
+            "course_id": Uuid::new_v4(),
+            "academic_year": 2025,
+            "status": "actve",
+        }))
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown variant `actve`"));
+    }
+}
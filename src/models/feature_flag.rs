@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Valor de un flag de funcionalidad guardado en la base de datos
+///
+/// Reemplaza el valor de la sección `[features]` de la configuración hasta
+/// que se elimina.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct FeatureFlagOverride {
+    /// Nombre del flag (`payments_gateway`, ...)
+    pub name: String,
+    pub enabled: bool,
+    /// Administrador que lo modificó por última vez
+    pub updated_by: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FeatureFlagOverride {
+    /// Todos los valores guardados
+    pub async fn find_all(pool: &PgPool) -> Result<Vec<FeatureFlagOverride>, DbError> {
+        sqlx::query_as::<_, FeatureFlagOverride>(
+            "SELECT name, enabled, updated_by, updated_at FROM feature_flags ORDER BY name",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Guarda el valor de un flag, reemplazando el anterior
+    ///
+    /// # Arguments
+    ///
+    /// * `executor` - Pool o transacción
+    /// * `name` - Nombre del flag
+    /// * `enabled` - Nuevo valor
+    /// * `updated_by` - Administrador que realiza el cambio
+    pub async fn upsert<'e>(
+        executor: impl PgExecutor<'e>,
+        name: &str,
+        enabled: bool,
+        updated_by: Option<Uuid>,
+    ) -> Result<FeatureFlagOverride, DbError> {
+        sqlx::query_as::<_, FeatureFlagOverride>(
+            r#"
+            INSERT INTO feature_flags (name, enabled, updated_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (name) DO UPDATE
+                SET enabled = EXCLUDED.enabled,
+                    updated_by = EXCLUDED.updated_by,
+                    updated_at = CURRENT_TIMESTAMP
+            RETURNING name, enabled, updated_by, updated_at
+            "#,
+        )
+        .bind(name)
+        .bind(enabled)
+        .bind(updated_by)
+        .fetch_one(executor)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Elimina el valor guardado; el flag vuelve al de la configuración
+    ///
+    /// # Returns
+    ///
+    /// `true` si había un valor guardado
+    pub async fn delete<'e>(executor: impl PgExecutor<'e>, name: &str) -> Result<bool, DbError> {
+        let result = sqlx::query("DELETE FROM feature_flags WHERE name = $1")
+            .bind(name)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
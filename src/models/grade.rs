@@ -0,0 +1,813 @@
+//! Calificaciones: escala de notas y resúmenes por curso y etapa
+//!
+//! Las calificaciones se calculan a partir de las evaluaciones
+//! (`assessments`) de cada inscripción. El promedio ponderado de una etapa es
+//! el porcentaje obtenido (`score / max_score * 100`) ponderado por el peso de
+//! cada evaluación, y se traduce a la nota y su denominación con la escala de
+//! calificaciones configurada.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Variable de entorno con la escala de calificaciones en formato JSON
+pub const GRADING_SCALE_ENV: &str = "GRADING_SCALE";
+
+/// Última etapa posible del año lectivo (la columna `assessments.period` va de 1 a 4)
+pub const MAX_PERIOD: i16 = 4;
+
+/// Estructura para almacenar calificaciones
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Grade {
+    /// Identificador único
+    pub id: Uuid,
+    /// Estudiante evaluado
+    pub student_id: Uuid,
+    /// Curso evaluado
+    pub course_id: Uuid,
+    /// Tipo de evaluación (examen, trabajo práctico, etc.)
+    pub evaluation_type: String,
+    /// Valor numérico de la calificación
+    pub value: f32,
+    /// Escala (1-5, 1-10, etc.)
+    pub scale: u8,
+    /// Fecha de la evaluación
+    pub evaluation_date: chrono::NaiveDate,
+    /// Profesor que asignó la calificación
+    pub teacher_id: Uuid,
+    /// Comentarios adicionales
+    pub comments: Option<String>,
+}
+
+/// Tramo de la escala: porcentaje mínimo para obtener una nota
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GradeBand {
+    /// Porcentaje mínimo (inclusivo) del tramo
+    pub min_percentage: f64,
+    /// Nota numérica
+    pub grade: u8,
+    /// Denominación de la nota (p. ej. "Sobresaliente")
+    pub label: String,
+}
+
+/// Escala de calificaciones de la institución
+///
+/// Por defecto es la escala del 1 al 5 con 60% como porcentaje mínimo de
+/// aprobación. Puede reemplazarse con la variable `GRADING_SCALE`, por ejemplo
+/// `[{"min_percentage": 70, "grade": 2, "label": "Aprobado"}, ...]`; el tramo
+/// con menor porcentaje mínimo se aplica también a los porcentajes inferiores.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GradingScale {
+    /// Tramos ordenados de mayor a menor porcentaje mínimo
+    bands: Vec<GradeBand>,
+}
+
+impl Default for GradingScale {
+    fn default() -> Self {
+        let band = |min_percentage: f64, grade: u8, label: &str| GradeBand {
+            min_percentage,
+            grade,
+            label: label.to_string(),
+        };
+
+        Self {
+            bands: vec![
+                band(90.0, 5, "Sobresaliente"),
+                band(80.0, 4, "Distinguido"),
+                band(70.0, 3, "Bueno"),
+                band(60.0, 2, "Aceptable"),
+                band(0.0, 1, "Insuficiente"),
+            ],
+        }
+    }
+}
+
+impl GradingScale {
+    /// Crea una escala a partir de sus tramos, en cualquier orden
+    ///
+    /// # Arguments
+    ///
+    /// * `bands` - Tramos de la escala
+    ///
+    /// # Returns
+    ///
+    /// La escala, o un mensaje de error si no tiene tramos o repite un porcentaje mínimo
+    pub fn new(mut bands: Vec<GradeBand>) -> Result<Self, String> {
+        if bands.is_empty() {
+            return Err("La escala de calificaciones no tiene tramos".to_string());
+        }
+        if bands.iter().any(|b| !(0.0..=100.0).contains(&b.min_percentage)) {
+            return Err("Los porcentajes mínimos deben estar entre 0 y 100".to_string());
+        }
+
+        bands.sort_by(|a, b| b.min_percentage.total_cmp(&a.min_percentage));
+        if bands.windows(2).any(|w| w[0].min_percentage == w[1].min_percentage) {
+            return Err("La escala repite un porcentaje mínimo".to_string());
+        }
+
+        Ok(Self { bands })
+    }
+
+    /// Carga la escala desde `GRADING_SCALE`, o la escala por defecto si no está definida
+    ///
+    /// Una escala inválida se registra en el log y se reemplaza por la escala por defecto.
+    pub fn from_env() -> Self {
+        let raw = match std::env::var(GRADING_SCALE_ENV) {
+            Ok(raw) if !raw.trim().is_empty() => raw,
+            _ => return Self::default(),
+        };
+
+        serde_json::from_str::<Vec<GradeBand>>(&raw)
+            .map_err(|e| e.to_string())
+            .and_then(Self::new)
+            .unwrap_or_else(|e| {
+                log::warn!("{} inválida, se usa la escala por defecto: {}", GRADING_SCALE_ENV, e);
+                Self::default()
+            })
+    }
+
+    /// Tramos de la escala, de mayor a menor porcentaje mínimo
+    pub fn bands(&self) -> &[GradeBand] {
+        &self.bands
+    }
+
+    /// Busca el tramo que corresponde a un porcentaje
+    ///
+    /// # Arguments
+    ///
+    /// * `percentage` - Porcentaje obtenido, de 0 a 100
+    ///
+    /// # Returns
+    ///
+    /// El tramo con el mayor porcentaje mínimo que no supera `percentage`
+    pub fn band_for(&self, percentage: f64) -> &GradeBand {
+        self.bands
+            .iter()
+            .find(|band| percentage >= band.min_percentage)
+            .unwrap_or_else(|| self.bands.last().expect("la escala tiene al menos un tramo"))
+    }
+}
+
+/// Promedio de una etapa traducido a la escala
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScaledGrade {
+    /// Porcentaje obtenido, redondeado a dos decimales
+    pub percentage: f64,
+    /// Nota en la escala
+    pub grade: u8,
+    /// Denominación de la nota
+    pub label: String,
+}
+
+impl ScaledGrade {
+    /// Traduce un porcentaje a la escala de calificaciones
+    pub fn new(percentage: f64, scale: &GradingScale) -> Self {
+        let band = scale.band_for(percentage);
+        Self {
+            percentage: (percentage * 100.0).round() / 100.0,
+            grade: band.grade,
+            label: band.label.clone(),
+        }
+    }
+}
+
+/// Resumen de las evaluaciones de una etapa
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeriodSummary {
+    /// Etapa (trimestre) del año lectivo
+    pub period: i16,
+    /// Cantidad de evaluaciones de la etapa
+    pub assessment_count: i64,
+    /// Promedio ponderado; `None` si las evaluaciones no tienen peso
+    pub average: Option<ScaledGrade>,
+}
+
+/// Calificaciones de un estudiante en un curso
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CourseGrades {
+    pub course_id: Uuid,
+    pub course_code: String,
+    pub course_name: String,
+    /// Resumen por etapa, en orden
+    pub periods: Vec<PeriodSummary>,
+    /// Calificación final, sólo si la inscripción está cerrada
+    pub final_grade: Option<ScaledGrade>,
+}
+
+/// Calificaciones de un estudiante en la vista de un curso
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StudentGrades {
+    pub student_id: Uuid,
+    pub student_name: String,
+    /// Resumen por etapa, en orden
+    pub periods: Vec<PeriodSummary>,
+    /// Calificación final, sólo si la inscripción está cerrada
+    pub final_grade: Option<ScaledGrade>,
+}
+
+/// Fila agregada por inscripción y etapa de la consulta de un estudiante
+///
+/// Las inscripciones sin evaluaciones producen una única fila con `period` nulo.
+#[derive(Debug, Clone, FromRow)]
+pub struct CoursePeriodRow {
+    pub course_id: Uuid,
+    pub course_code: String,
+    pub course_name: String,
+    pub period: Option<i16>,
+    pub assessment_count: i64,
+    pub weighted_average: Option<f64>,
+    pub final_grade: Option<f64>,
+    pub closed: bool,
+}
+
+/// Fila agregada por inscripción y etapa de la consulta de un curso
+#[derive(Debug, Clone, FromRow)]
+pub struct StudentPeriodRow {
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub period: Option<i16>,
+    pub assessment_count: i64,
+    pub weighted_average: Option<f64>,
+    pub final_grade: Option<f64>,
+    pub closed: bool,
+}
+
+/// Promedio ponderado por inscripción y etapa, común a las dos consultas.
+/// `$1` es la etapa opcional; las evaluaciones de otras etapas no se unen.
+const PERIOD_AGGREGATE: &str = r#"
+    a.period,
+    COUNT(a.id) AS assessment_count,
+    (SUM(a.score / NULLIF(a.max_score, 0) * 100 * a.weight)
+        / NULLIF(SUM(a.weight), 0))::float8 AS weighted_average,
+    e.final_grade::float8 AS final_grade,
+    (e.status = 'completed') AS closed
+"#;
+
+/// Calificaciones de un estudiante agrupadas por curso y etapa, en una sola consulta
+///
+/// # Arguments
+///
+/// * `pool` - Pool de conexiones a la base de datos
+/// * `student_id` - Usuario del estudiante
+/// * `academic_year` - Año lectivo de las inscripciones (opcional)
+/// * `period` - Etapa de las evaluaciones (opcional)
+/// * `teacher_id` - Limita el resultado a los cursos de este profesor (opcional)
+///
+/// # Returns
+///
+/// Una fila por inscripción y etapa, ordenadas por curso y etapa
+pub async fn find_student_period_rows(
+    pool: &PgPool,
+    student_id: Uuid,
+    academic_year: Option<i32>,
+    period: Option<i16>,
+    teacher_id: Option<Uuid>,
+) -> Result<Vec<CoursePeriodRow>, DbError> {
+    let sql = format!(
+        r#"
+        SELECT c.id AS course_id, c.code AS course_code, c.name AS course_name, {}
+        FROM enrollments e
+        JOIN courses c ON c.id = e.course_id
+        LEFT JOIN assessments a ON a.enrollment_id = e.id
+            AND ($1::smallint IS NULL OR a.period = $1)
+        WHERE e.student_id = $2
+          AND e.status <> 'withdrawn'
+          AND ($3::int IS NULL OR e.academic_year = $3)
+          AND ($4::uuid IS NULL OR c.teacher_id = $4)
+        GROUP BY c.id, c.code, c.name, e.id, a.period
+        ORDER BY c.code, a.period
+        "#,
+        PERIOD_AGGREGATE
+    );
+
+    sqlx::query_as::<_, CoursePeriodRow>(&sql)
+        .bind(period)
+        .bind(student_id)
+        .bind(academic_year)
+        .bind(teacher_id)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::from)
+}
+
+/// Calificaciones de los estudiantes de un curso agrupadas por etapa, en una sola consulta
+///
+/// # Arguments
+///
+/// * `pool` - Pool de conexiones a la base de datos
+/// * `course_id` - Curso
+/// * `period` - Etapa de las evaluaciones (opcional)
+///
+/// # Returns
+///
+/// Una fila por inscripción y etapa, ordenadas por nombre del estudiante y etapa
+pub async fn find_course_period_rows(
+    pool: &PgPool,
+    course_id: Uuid,
+    period: Option<i16>,
+) -> Result<Vec<StudentPeriodRow>, DbError> {
+    let sql = format!(
+        r#"
+        SELECT e.student_id, u.full_name AS student_name, {}
+        FROM enrollments e
+        JOIN users u ON u.id = e.student_id
+        LEFT JOIN assessments a ON a.enrollment_id = e.id
+            AND ($1::smallint IS NULL OR a.period = $1)
+        WHERE e.course_id = $2
+          AND e.status <> 'withdrawn'
+        GROUP BY e.id, e.student_id, u.full_name, a.period
+        ORDER BY u.full_name, e.student_id, a.period
+        "#,
+        PERIOD_AGGREGATE
+    );
+
+    sqlx::query_as::<_, StudentPeriodRow>(&sql)
+        .bind(period)
+        .bind(course_id)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::from)
+}
+
+/// Verifica si un usuario figura como encargado de un estudiante
+///
+/// # Arguments
+///
+/// * `pool` - Pool de conexiones a la base de datos
+/// * `guardian_user_id` - Usuario del padre, madre o tutor
+/// * `student_id` - Usuario del estudiante
+pub async fn is_guardian_of(pool: &PgPool, guardian_user_id: Uuid, student_id: Uuid) -> Result<bool, DbError> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM students s
+            JOIN users u ON u.document_id = s.guardian_info->>'document_id'
+            WHERE s.user_id = $1 AND u.id = $2
+        )
+        "#,
+    )
+    .bind(student_id)
+    .bind(guardian_user_id)
+    .fetch_one(pool)
+    .await
+    .map_err(DbError::from)
+}
+
+fn period_summary(
+    period: Option<i16>,
+    assessment_count: i64,
+    weighted_average: Option<f64>,
+    scale: &GradingScale,
+) -> Option<PeriodSummary> {
+    period.map(|period| PeriodSummary {
+        period,
+        assessment_count,
+        average: weighted_average.map(|avg| ScaledGrade::new(avg, scale)),
+    })
+}
+
+fn final_grade(closed: bool, final_grade: Option<f64>, scale: &GradingScale) -> Option<ScaledGrade> {
+    if closed {
+        final_grade.map(|grade| ScaledGrade::new(grade, scale))
+    } else {
+        None
+    }
+}
+
+/// Agrupa las filas de un estudiante por curso, conservando el orden de la consulta
+pub fn group_by_course(rows: Vec<CoursePeriodRow>, scale: &GradingScale) -> Vec<CourseGrades> {
+    let mut courses: Vec<CourseGrades> = Vec::new();
+
+    for row in rows {
+        let summary = period_summary(row.period, row.assessment_count, row.weighted_average, scale);
+
+        match courses.last_mut() {
+            Some(course) if course.course_id == row.course_id => course.periods.extend(summary),
+            _ => courses.push(CourseGrades {
+                course_id: row.course_id,
+                course_code: row.course_code,
+                course_name: row.course_name,
+                periods: summary.into_iter().collect(),
+                final_grade: final_grade(row.closed, row.final_grade, scale),
+            }),
+        }
+    }
+
+    courses
+}
+
+/// Agrupa las filas de un curso por estudiante, conservando el orden de la consulta
+pub fn group_by_student(rows: Vec<StudentPeriodRow>, scale: &GradingScale) -> Vec<StudentGrades> {
+    let mut students: Vec<StudentGrades> = Vec::new();
+
+    for row in rows {
+        let summary = period_summary(row.period, row.assessment_count, row.weighted_average, scale);
+
+        match students.last_mut() {
+            Some(student) if student.student_id == row.student_id => student.periods.extend(summary),
+            _ => students.push(StudentGrades {
+                student_id: row.student_id,
+                student_name: row.student_name,
+                periods: summary.into_iter().collect(),
+                final_grade: final_grade(row.closed, row.final_grade, scale),
+            }),
+        }
+    }
+
+    students
+}
+
+/// Curso de una planilla de calificaciones
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct CourseRef {
+    pub id: Uuid,
+    pub code: String,
+    pub name: String,
+    /// Profesor asignado, usado para autorizar el acceso a la planilla
+    pub teacher_id: Option<Uuid>,
+}
+
+/// Columna de la planilla: una evaluación tomada a los estudiantes del curso
+///
+/// Las evaluaciones se registran por inscripción; una columna agrupa las de
+/// igual tipo y título.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssessmentRef {
+    pub assessment_type: String,
+    pub title: String,
+    pub period: i16,
+    pub assessment_date: DateTime<Utc>,
+    pub max_score: f64,
+    pub weight: f64,
+}
+
+/// Fila de la planilla: las notas de un estudiante en cada evaluación
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StudentGradeRow {
+    pub student_id: Uuid,
+    pub full_name: String,
+    pub enrollment_number: String,
+    /// Puntaje de cada evaluación, en el orden de `GradeBook::assessments`;
+    /// `None` si el estudiante no la tiene calificada
+    pub scores: Vec<Option<f64>>,
+    /// Promedio ponderado (porcentaje) de las evaluaciones calificadas
+    pub weighted_average: f64,
+    /// Denominación del promedio según la escala de calificaciones
+    pub letter_grade: String,
+}
+
+/// Planilla de calificaciones de un curso: estudiantes × evaluaciones
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GradeBook {
+    pub course: CourseRef,
+    pub assessments: Vec<AssessmentRef>,
+    pub students: Vec<StudentGradeRow>,
+}
+
+/// Celda de la consulta de la planilla: un estudiante y una evaluación
+///
+/// Los estudiantes sin evaluaciones en el curso producen una fila con las
+/// columnas de la evaluación nulas.
+#[derive(Debug, Clone, FromRow)]
+pub struct GradeBookCell {
+    pub student_id: Uuid,
+    pub full_name: String,
+    pub enrollment_number: String,
+    pub ordinal: Option<i64>,
+    pub assessment_type: Option<String>,
+    pub title: Option<String>,
+    pub period: Option<i16>,
+    pub assessment_date: Option<DateTime<Utc>>,
+    pub max_score: Option<f64>,
+    pub weight: Option<f64>,
+    pub score: Option<f64>,
+}
+
+/// Busca el curso de una planilla
+pub async fn find_course_ref(pool: &PgPool, course_id: Uuid) -> Result<Option<CourseRef>, DbError> {
+    sqlx::query_as::<_, CourseRef>("SELECT id, code, name, teacher_id FROM courses WHERE id = $1")
+        .bind(course_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(DbError::from)
+}
+
+/// Celdas de la planilla de un curso, en una sola consulta
+///
+/// Cada estudiante inscrito se cruza con todas las evaluaciones del curso (de
+/// la etapa pedida), de modo que las evaluaciones que no rindió quedan con
+/// puntaje nulo.
+///
+/// # Arguments
+///
+/// * `pool` - Pool de conexiones a la base de datos
+/// * `course_id` - Curso
+/// * `period` - Etapa (trimestre) de las evaluaciones (opcional)
+///
+/// # Returns
+///
+/// Las celdas ordenadas por estudiante y por fecha de la evaluación
+pub async fn find_grade_book_cells(
+    pool: &PgPool,
+    course_id: Uuid,
+    period: Option<i16>,
+) -> Result<Vec<GradeBookCell>, DbError> {
+    sqlx::query_as::<_, GradeBookCell>(
+        r#"
+        WITH columns AS (
+            SELECT assessment_type::text AS assessment_type, title,
+                   MIN(period) AS period,
+                   MIN(assessment_date) AS assessment_date,
+                   MAX(max_score)::float8 AS max_score,
+                   MAX(weight)::float8 AS weight,
+                   ROW_NUMBER() OVER (ORDER BY MIN(assessment_date), title) AS ordinal
+            FROM assessments
+            WHERE course_id = $1
+              AND ($2::smallint IS NULL OR period = $2)
+            GROUP BY assessment_type, title
+        )
+        SELECT e.student_id, u.full_name,
+               COALESCE(s.enrollment_number, '') AS enrollment_number,
+               col.ordinal, col.assessment_type, col.title, col.period,
+               col.assessment_date, col.max_score, col.weight,
+               a.score::float8 AS score
+        FROM enrollments e
+        JOIN users u ON u.id = e.student_id
+        LEFT JOIN students s ON s.user_id = e.student_id
+        LEFT JOIN columns col ON TRUE
+        LEFT JOIN assessments a ON a.enrollment_id = e.id
+            AND a.assessment_type::text = col.assessment_type
+            AND a.title = col.title
+        WHERE e.course_id = $1
+          AND e.status <> 'withdrawn'
+        ORDER BY u.full_name, e.student_id, col.ordinal
+        "#,
+    )
+    .bind(course_id)
+    .bind(period)
+    .fetch_all(pool)
+    .await
+    .map_err(DbError::from)
+}
+
+impl GradeBook {
+    /// Arma la planilla a partir de las celdas de `find_grade_book_cells`
+    ///
+    /// # Arguments
+    ///
+    /// * `course` - Curso de la planilla
+    /// * `cells` - Celdas ordenadas por estudiante y evaluación
+    /// * `scale` - Escala usada para la denominación del promedio
+    pub fn from_cells(course: CourseRef, cells: Vec<GradeBookCell>, scale: &GradingScale) -> Self {
+        let mut assessments: Vec<AssessmentRef> = Vec::new();
+        let mut students: Vec<StudentGradeRow> = Vec::new();
+        // (suma de porcentajes ponderados, suma de pesos) del estudiante actual
+        let mut totals: Vec<(f64, f64)> = Vec::new();
+
+        for cell in cells {
+            if students.last().is_none_or(|row| row.student_id != cell.student_id) {
+                students.push(StudentGradeRow {
+                    student_id: cell.student_id,
+                    full_name: cell.full_name.clone(),
+                    enrollment_number: cell.enrollment_number.clone(),
+                    scores: Vec::new(),
+                    weighted_average: 0.0,
+                    letter_grade: String::new(),
+                });
+                totals.push((0.0, 0.0));
+            }
+
+            let (Some(ordinal), Some(title)) = (cell.ordinal, cell.title) else { continue };
+            let max_score = cell.max_score.unwrap_or(0.0);
+            let weight = cell.weight.unwrap_or(0.0);
+
+            // Las columnas son las mismas para todos los estudiantes
+            if ordinal as usize > assessments.len() {
+                assessments.push(AssessmentRef {
+                    assessment_type: cell.assessment_type.unwrap_or_default(),
+                    title,
+                    period: cell.period.unwrap_or(1),
+                    assessment_date: cell.assessment_date.unwrap_or_default(),
+                    max_score,
+                    weight,
+                });
+            }
+
+            let row = students.last_mut().expect("se agregó una fila por estudiante");
+            let total = totals.last_mut().expect("se agregó un total por estudiante");
+            row.scores.push(cell.score);
+            if let Some(score) = cell.score.filter(|_| max_score > 0.0) {
+                total.0 += score / max_score * 100.0 * weight;
+                total.1 += weight;
+            }
+        }
+
+        for (row, (weighted, weights)) in students.iter_mut().zip(totals) {
+            let average = if weights > 0.0 { weighted / weights } else { 0.0 };
+            row.weighted_average = (average * 100.0).round() / 100.0;
+            row.letter_grade = scale.band_for(average).label.clone();
+        }
+
+        Self { course, assessments, students }
+    }
+
+    /// Exporta la planilla en CSV para abrirla en una hoja de cálculo
+    ///
+    /// Una columna por evaluación con el título y el puntaje máximo, seguidas
+    /// del promedio ponderado y su denominación.
+    pub fn to_csv(&self) -> Result<Vec<u8>, csv::Error> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        let mut header = vec!["Estudiante".to_string(), "Matrícula".to_string()];
+        header.extend(self.assessments.iter().map(|a| format!("{} ({})", a.title, a.max_score)));
+        header.extend(["Promedio".to_string(), "Calificación".to_string()]);
+        writer.write_record(&header)?;
+
+        for row in &self.students {
+            let mut record = vec![row.full_name.clone(), row.enrollment_number.clone()];
+            record.extend(row.scores.iter().map(|score| score.map(|s| s.to_string()).unwrap_or_default()));
+            record.push(format!("{:.2}", row.weighted_average));
+            record.push(row.letter_grade.clone());
+            writer.write_record(&record)?;
+        }
+
+        writer
+            .into_inner()
+            .map_err(|e| csv::Error::from(e.into_error()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(course: Uuid, code: &str, period: Option<i16>, count: i64, avg: Option<f64>) -> CoursePeriodRow {
+        CoursePeriodRow {
+            course_id: course,
+            course_code: code.to_string(),
+            course_name: format!("Curso {}", code),
+            period,
+            assessment_count: count,
+            weighted_average: avg,
+            final_grade: None,
+            closed: false,
+        }
+    }
+
+    #[test]
+    fn test_default_scale_labels() {
+        let scale = GradingScale::default();
+
+        assert_eq!(scale.band_for(95.0).grade, 5);
+        assert_eq!(scale.band_for(90.0).label, "Sobresaliente");
+        assert_eq!(scale.band_for(89.99).grade, 4);
+        assert_eq!(scale.band_for(60.0).label, "Aceptable");
+        assert_eq!(scale.band_for(59.9).label, "Insuficiente");
+    }
+
+    #[test]
+    fn test_custom_scale_maps_labels() {
+        let bands: Vec<GradeBand> = serde_json::from_str(
+            r#"[
+                {"min_percentage": 70, "grade": 2, "label": "Aprobado"},
+                {"min_percentage": 0, "grade": 1, "label": "Reprobado"},
+                {"min_percentage": 95, "grade": 3, "label": "Excelente"}
+            ]"#,
+        )
+        .unwrap();
+        let scale = GradingScale::new(bands).unwrap();
+
+        assert_eq!(ScaledGrade::new(96.0, &scale).label, "Excelente");
+        assert_eq!(ScaledGrade::new(70.0, &scale).label, "Aprobado");
+        assert_eq!(ScaledGrade::new(69.5, &scale).grade, 1);
+    }
+
+    #[test]
+    fn test_invalid_scales_are_rejected() {
+        assert!(GradingScale::new(vec![]).is_err());
+
+        let band = |min| GradeBand { min_percentage: min, grade: 1, label: "X".to_string() };
+        assert!(GradingScale::new(vec![band(50.0), band(50.0)]).is_err());
+        assert!(GradingScale::new(vec![band(120.0)]).is_err());
+    }
+
+    #[test]
+    fn test_three_courses_in_two_periods() {
+        let (mat, cas, his) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let rows = vec![
+            row(cas, "CAS1", Some(1), 2, Some(75.0)),
+            row(cas, "CAS1", Some(2), 3, Some(91.256)),
+            row(his, "HIS1", Some(1), 1, Some(55.0)),
+            row(his, "HIS1", Some(2), 2, Some(62.0)),
+            row(mat, "MAT1", Some(1), 4, Some(88.0)),
+            row(mat, "MAT1", Some(2), 1, None),
+        ];
+
+        let courses = group_by_course(rows, &GradingScale::default());
+
+        assert_eq!(courses.len(), 3);
+        assert_eq!(courses[0].course_code, "CAS1");
+        assert_eq!(courses.iter().map(|c| c.periods.len()).collect::<Vec<_>>(), vec![2, 2, 2]);
+
+        let second = courses[0].periods[1].average.as_ref().unwrap();
+        assert_eq!(second.percentage, 91.26);
+        assert_eq!(second.grade, 5);
+        assert_eq!(courses[1].periods[0].average.as_ref().unwrap().label, "Insuficiente");
+        assert_eq!(courses[2].periods[0].assessment_count, 4);
+        assert_eq!(courses[2].periods[1].average, None);
+        assert!(courses.iter().all(|c| c.final_grade.is_none()));
+    }
+
+    #[test]
+    fn test_final_grade_only_when_closed() {
+        let course = Uuid::new_v4();
+        let mut closed = row(course, "MAT1", Some(1), 1, Some(80.0));
+        closed.closed = true;
+        closed.final_grade = Some(83.0);
+        let mut open = row(Uuid::new_v4(), "MAT2", None, 0, None);
+        open.final_grade = Some(70.0);
+
+        let courses = group_by_course(vec![closed, open], &GradingScale::default());
+
+        assert_eq!(courses[0].final_grade.as_ref().unwrap().label, "Distinguido");
+        assert_eq!(courses[1].final_grade, None);
+        // A course without assessments still appears, with no periods
+        assert!(courses[1].periods.is_empty());
+    }
+
+    fn cell(student: Uuid, name: &str, ordinal: Option<i64>, title: &str, score: Option<f64>) -> GradeBookCell {
+        GradeBookCell {
+            student_id: student,
+            full_name: name.to_string(),
+            enrollment_number: format!("M-{}", name),
+            ordinal,
+            assessment_type: ordinal.map(|_| "exam".to_string()),
+            title: ordinal.map(|_| title.to_string()),
+            period: ordinal.map(|_| 1),
+            assessment_date: ordinal.map(|_| Utc::now()),
+            max_score: ordinal.map(|_| 20.0),
+            weight: ordinal.map(|o| o as f64),
+            score,
+        }
+    }
+
+    fn course_ref() -> CourseRef {
+        CourseRef { id: Uuid::new_v4(), code: "MAT1".to_string(), name: "Matemática I".to_string(), teacher_id: None }
+    }
+
+    #[test]
+    fn test_grade_book_matrix() {
+        let (ana, beto) = (Uuid::new_v4(), Uuid::new_v4());
+        let cells = vec![
+            cell(ana, "Ana", Some(1), "Parcial", Some(20.0)),
+            cell(ana, "Ana", Some(2), "Final", Some(14.0)),
+            cell(beto, "Beto", Some(1), "Parcial", Some(10.0)),
+            cell(beto, "Beto", Some(2), "Final", None),
+        ];
+
+        let book = GradeBook::from_cells(course_ref(), cells, &GradingScale::default());
+
+        let titles: Vec<_> = book.assessments.iter().map(|a| a.title.as_str()).collect();
+        assert_eq!(titles, vec!["Parcial", "Final"]);
+        assert_eq!(book.students[0].scores, vec![Some(20.0), Some(14.0)]);
+        // (100 * 1 + 70 * 2) / 3
+        assert_eq!(book.students[0].weighted_average, 80.0);
+        assert_eq!(book.students[0].letter_grade, "Distinguido");
+        // Ungraded assessments are left out of the average
+        assert_eq!(book.students[1].scores, vec![Some(10.0), None]);
+        assert_eq!(book.students[1].weighted_average, 50.0);
+        assert_eq!(book.students[1].letter_grade, "Insuficiente");
+    }
+
+    #[test]
+    fn test_grade_book_without_assessments() {
+        let cells = vec![cell(Uuid::new_v4(), "Ana", None, "", None)];
+
+        let book = GradeBook::from_cells(course_ref(), cells, &GradingScale::default());
+
+        assert!(book.assessments.is_empty());
+        assert_eq!(book.students.len(), 1);
+        assert!(book.students[0].scores.is_empty());
+        assert_eq!(book.students[0].weighted_average, 0.0);
+    }
+
+    #[test]
+    fn test_grade_book_csv() {
+        let ana = Uuid::new_v4();
+        let cells = vec![
+            cell(ana, "Benítez, Ana", Some(1), "Parcial", Some(18.5)),
+            cell(ana, "Benítez, Ana", Some(2), "Final", None),
+        ];
+        let book = GradeBook::from_cells(course_ref(), cells, &GradingScale::default());
+
+        let csv = String::from_utf8(book.to_csv().unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "Estudiante,Matrícula,Parcial (20),Final (20),Promedio,Calificación");
+        assert_eq!(lines[1], "\"Benítez, Ana\",\"M-Benítez, Ana\",18.5,,92.50,Sobresaliente");
+    }
+}
@@ -0,0 +1,350 @@
+//! Institución educativa y su configuración académica
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::{DbError, FieldError};
+use crate::utils::validation::validate_ruc;
+
+/// Variable de entorno con el promedio mínimo de promoción, en porcentaje
+pub const PROMOTION_MIN_AVERAGE_ENV: &str = "PROMOTION_MIN_AVERAGE";
+/// Variable de entorno con la asistencia mínima de promoción, en porcentaje
+pub const PROMOTION_MIN_ATTENDANCE_ENV: &str = "PROMOTION_MIN_ATTENDANCE";
+/// Variable de entorno con la cantidad máxima de materias que se pueden recuperar
+pub const PROMOTION_MAX_FAILED_SUBJECTS_ENV: &str = "PROMOTION_MAX_FAILED_SUBJECTS";
+
+/// Institución educativa
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Institution {
+    /// Identificador único
+    pub id: Uuid,
+    /// Nombre de la institución
+    pub name: String,
+    /// RUC o identificador fiscal
+    pub tax_id: String,
+    /// Dirección física
+    pub address: String,
+    /// Teléfono de contacto
+    pub phone: String,
+    /// Correo electrónico
+    pub email: String,
+    /// Sitio web
+    pub website: Option<String>,
+    /// Director o responsable
+    pub director_name: String,
+    /// Logo de la institución (ruta al archivo)
+    pub logo_path: Option<String>,
+    /// Año de fundación
+    pub foundation_year: i32,
+    /// Niveles educativos ofrecidos
+    pub education_levels: Vec<String>,
+}
+
+/// DTO para el registro de una institución
+#[derive(Debug, Deserialize)]
+pub struct CreateInstitutionDto {
+    pub name: String,
+    pub tax_id: String,
+    pub address: String,
+    pub phone: String,
+    pub email: String,
+    pub website: Option<String>,
+    pub director_name: String,
+    pub logo_path: Option<String>,
+    pub foundation_year: i32,
+    #[serde(default)]
+    pub education_levels: Vec<String>,
+}
+
+/// DTO para la actualización de una institución
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateInstitutionDto {
+    pub name: Option<String>,
+    pub tax_id: Option<String>,
+    pub address: Option<String>,
+    pub phone: Option<String>,
+    pub email: Option<String>,
+    pub website: Option<String>,
+    pub director_name: Option<String>,
+    pub logo_path: Option<String>,
+    pub foundation_year: Option<i32>,
+    pub education_levels: Option<Vec<String>>,
+}
+
+impl CreateInstitutionDto {
+    /// Verifica los datos y devuelve un error por cada campo inválido
+    ///
+    /// El nombre es obligatorio y el RUC debe tener un dígito verificador válido.
+    pub fn validate(&self) -> Result<(), DbError> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push(FieldError::new("name", "must not be empty"));
+        }
+        check_tax_id(&self.tax_id, &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DbError::Invalid(errors))
+        }
+    }
+}
+
+impl UpdateInstitutionDto {
+    /// Verifica los campos indicados, con las mismas reglas que [`CreateInstitutionDto::validate`]
+    pub fn validate(&self) -> Result<(), DbError> {
+        let mut errors = Vec::new();
+
+        if self.name.as_ref().is_some_and(|name| name.trim().is_empty()) {
+            errors.push(FieldError::new("name", "must not be empty"));
+        }
+        if let Some(tax_id) = &self.tax_id {
+            check_tax_id(tax_id, &mut errors);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DbError::Invalid(errors))
+        }
+    }
+}
+
+fn check_tax_id(tax_id: &str, errors: &mut Vec<FieldError>) {
+    if !validate_ruc(tax_id) {
+        errors.push(FieldError::new("tax_id", "is not a valid RUC or its check digit does not match"));
+    }
+}
+
+impl Institution {
+    /// Registra una institución
+    ///
+    /// Los datos se validan con [`CreateInstitutionDto::validate`] antes de tocar la base de datos.
+    pub async fn create(pool: &PgPool, dto: &CreateInstitutionDto) -> Result<Institution, DbError> {
+        dto.validate()?;
+
+        let institution = sqlx::query_as!(
+            Institution,
+            r#"
+            INSERT INTO institutions (
+                name, tax_id, address, phone, email, website, director_name,
+                logo_path, foundation_year, education_levels
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, name, tax_id, address, phone, email, website, director_name,
+                      logo_path, foundation_year, education_levels
+            "#,
+            dto.name,
+            dto.tax_id,
+            dto.address,
+            dto.phone,
+            dto.email,
+            dto.website,
+            dto.director_name,
+            dto.logo_path,
+            dto.foundation_year,
+            &dto.education_levels
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(institution)
+    }
+
+    /// Modifica los campos indicados de una institución
+    ///
+    /// Los cambios se validan con [`UpdateInstitutionDto::validate`] antes de
+    /// tocar la base de datos; si la institución no existe devuelve NotFound.
+    pub async fn update(pool: &PgPool, id: Uuid, dto: &UpdateInstitutionDto) -> Result<Institution, DbError> {
+        dto.validate()?;
+
+        let institution = sqlx::query_as!(
+            Institution,
+            r#"
+            UPDATE institutions
+            SET name = COALESCE($2, name),
+                tax_id = COALESCE($3, tax_id),
+                address = COALESCE($4, address),
+                phone = COALESCE($5, phone),
+                email = COALESCE($6, email),
+                website = COALESCE($7, website),
+                director_name = COALESCE($8, director_name),
+                logo_path = COALESCE($9, logo_path),
+                foundation_year = COALESCE($10, foundation_year),
+                education_levels = COALESCE($11, education_levels),
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING id, name, tax_id, address, phone, email, website, director_name,
+                      logo_path, foundation_year, education_levels
+            "#,
+            id,
+            dto.name,
+            dto.tax_id,
+            dto.address,
+            dto.phone,
+            dto.email,
+            dto.website,
+            dto.director_name,
+            dto.logo_path,
+            dto.foundation_year,
+            dto.education_levels.as_deref()
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound("Institución no encontrada".to_string()))?;
+
+        Ok(institution)
+    }
+}
+
+/// Criterios de promoción de la institución
+///
+/// Los valores por defecto son los del MEC: promedio general de al menos 60%,
+/// asistencia de al menos 75% y hasta 2 materias reprobadas, que se pueden
+/// recuperar en exámenes de recuperación.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstitutionSettings {
+    /// Porcentaje mínimo para aprobar una materia y promedio general mínimo
+    pub min_promotion_average: f64,
+    /// Porcentaje mínimo de asistencia
+    pub min_attendance_rate: f64,
+    /// Materias reprobadas que todavía se pueden recuperar
+    pub max_failed_subjects: usize,
+}
+
+impl Default for InstitutionSettings {
+    fn default() -> Self {
+        Self {
+            min_promotion_average: 60.0,
+            min_attendance_rate: 75.0,
+            max_failed_subjects: 2,
+        }
+    }
+}
+
+impl InstitutionSettings {
+    /// Carga los criterios desde las variables `PROMOTION_*`
+    ///
+    /// Las variables no definidas o inválidas toman el valor por defecto; las
+    /// inválidas además se registran en el log.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+
+        Self {
+            min_promotion_average: parse_setting(
+                &lookup,
+                PROMOTION_MIN_AVERAGE_ENV,
+                defaults.min_promotion_average,
+                is_percentage,
+            ),
+            min_attendance_rate: parse_setting(
+                &lookup,
+                PROMOTION_MIN_ATTENDANCE_ENV,
+                defaults.min_attendance_rate,
+                is_percentage,
+            ),
+            max_failed_subjects: parse_setting(
+                &lookup,
+                PROMOTION_MAX_FAILED_SUBJECTS_ENV,
+                defaults.max_failed_subjects,
+                |_| true,
+            ),
+        }
+    }
+}
+
+fn is_percentage(value: &f64) -> bool {
+    (0.0..=100.0).contains(value)
+}
+
+fn parse_setting<T: std::str::FromStr + Copy>(
+    lookup: &impl Fn(&str) -> Option<String>,
+    name: &str,
+    default: T,
+    valid: impl Fn(&T) -> bool,
+) -> T {
+    let raw = match lookup(name) {
+        Some(raw) if !raw.trim().is_empty() => raw,
+        _ => return default,
+    };
+
+    match raw.trim().parse::<T>() {
+        Ok(value) if valid(&value) => value,
+        _ => {
+            log::warn!("{} inválido ({}), se usa el valor por defecto", name, raw);
+            default
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn institution(tax_id: &str) -> CreateInstitutionDto {
+        CreateInstitutionDto {
+            name: "Colegio Nacional de Asunción".to_string(),
+            tax_id: tax_id.to_string(),
+            address: "Eligio Ayala 1025, Asunción".to_string(),
+            phone: "021 492 512".to_string(),
+            email: "secretaria@cna.edu.py".to_string(),
+            website: None,
+            director_name: "Ana Benítez".to_string(),
+            logo_path: None,
+            foundation_year: 1877,
+            education_levels: vec!["Educación Media".to_string()],
+        }
+    }
+
+    fn invalid_fields(result: Result<(), DbError>) -> Vec<String> {
+        match result {
+            Err(DbError::Invalid(errors)) => errors.into_iter().map(|e| e.field.into_owned()).collect(),
+            other => panic!("expected invalid fields, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_create_rejects_wrong_check_digit() {
+        assert!(institution("80012345-0").validate().is_ok());
+        assert_eq!(invalid_fields(institution("80012345-1").validate()), vec!["tax_id"]);
+    }
+
+    #[test]
+    fn test_update_checks_tax_id_only_when_given() {
+        assert!(UpdateInstitutionDto::default().validate().is_ok());
+
+        let dto = UpdateInstitutionDto { tax_id: Some("1234567-0".to_string()), ..Default::default() };
+        assert_eq!(invalid_fields(dto.validate()), vec!["tax_id"]);
+    }
+
+    #[test]
+    fn test_settings_from_env() {
+        let settings = InstitutionSettings::from_lookup(|name| match name {
+            PROMOTION_MIN_AVERAGE_ENV => Some("70".to_string()),
+            PROMOTION_MAX_FAILED_SUBJECTS_ENV => Some("1".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(settings.min_promotion_average, 70.0);
+        assert_eq!(settings.min_attendance_rate, 75.0);
+        assert_eq!(settings.max_failed_subjects, 1);
+    }
+
+    #[test]
+    fn test_invalid_settings_use_defaults() {
+        let settings = InstitutionSettings::from_lookup(|name| match name {
+            PROMOTION_MIN_AVERAGE_ENV => Some("150".to_string()),
+            PROMOTION_MIN_ATTENDANCE_ENV => Some("abc".to_string()),
+            PROMOTION_MAX_FAILED_SUBJECTS_ENV => Some("-1".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(settings, InstitutionSettings::default());
+    }
+}
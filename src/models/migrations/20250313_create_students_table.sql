@@ -14,12 +14,12 @@ CREATE TYPE student_status AS ENUM (
 
 -- Create students table
 CREATE TABLE students (
-    id SERIAL PRIMARY KEY,
-    user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    enrollment_number VARCHAR(20) NOT NULL UNIQUE, -- Institution-specific student ID
     enrollment_date DATE NOT NULL DEFAULT CURRENT_DATE,
-    student_id VARCHAR(20) NOT NULL UNIQUE, -- Institution-specific student ID
-    grade INTEGER NOT NULL,
+    current_grade VARCHAR(20) NOT NULL,
     section VARCHAR(10) NOT NULL,
+    academic_year INTEGER NOT NULL,
     status student_status NOT NULL DEFAULT 'active',
     guardian_info JSONB NOT NULL DEFAULT '{}', -- Stores parent/guardian contact information
     medical_info JSONB DEFAULT '{}', -- Stores medical conditions, allergies, etc.
@@ -32,18 +32,16 @@ CREATE TABLE students (
     
     -- Metadata
     created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
-    
-    CONSTRAINT grade_check CHECK (grade >= 0 AND grade <= 12)
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
 
 -- Add comments to the table and columns
 COMMENT ON TABLE students IS 'Students enrolled in the institution';
-COMMENT ON COLUMN students.id IS 'Unique identifier for the student record';
-COMMENT ON COLUMN students.user_id IS 'Foreign key to the users table for basic user information';
+COMMENT ON COLUMN students.user_id IS 'User of the student, which also identifies the student record';
 COMMENT ON COLUMN students.enrollment_date IS 'Date when the student was enrolled in the institution';
-COMMENT ON COLUMN students.student_id IS 'Institution-specific identifier for the student';
-COMMENT ON COLUMN students.grade IS 'Current grade level of the student (0-12)';
+COMMENT ON COLUMN students.enrollment_number IS 'Institution-specific identifier for the student';
+COMMENT ON COLUMN students.current_grade IS 'Current grade or course of the student';
+COMMENT ON COLUMN students.academic_year IS 'Academic year of the current grade and section';
 COMMENT ON COLUMN students.section IS 'Class section or division (e.g., A, B, C)';
 COMMENT ON COLUMN students.status IS 'Current enrollment status of the student';
 COMMENT ON COLUMN students.guardian_info IS 'JSON data containing parent/guardian contact information';
@@ -54,8 +52,7 @@ COMMENT ON COLUMN students.scholarship_percentage IS 'Percentage of tuition cove
 COMMENT ON COLUMN students.transportation_route IS 'Transportation route code if using school transport';
 
 -- Create indexes for common query patterns
-CREATE INDEX idx_students_user_id ON students(user_id);
-CREATE INDEX idx_students_grade_section ON students(grade, section);
+CREATE INDEX idx_students_grade_section ON students(current_grade, section);
 CREATE INDEX idx_students_status ON students(status);
 CREATE INDEX idx_students_enrollment_date ON students(enrollment_date);
 
@@ -13,11 +13,10 @@ CREATE TYPE teacher_status AS ENUM (
 
 -- Create the teachers table
 CREATE TABLE teachers (
-  id SERIAL PRIMARY KEY,
-  user_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
-  professional_id VARCHAR(50) UNIQUE,     -- Teacher's professional identification number
+  user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+  professional_id VARCHAR(50) NOT NULL UNIQUE, -- Teacher's professional identification number
   specialization VARCHAR(100) NOT NULL,   -- Main area of specialization
-  academic_degree VARCHAR(100),           -- Highest academic degree achieved
+  education_level VARCHAR(100) NOT NULL,  -- Highest academic degree achieved
   years_experience INTEGER DEFAULT 0,     -- Years of professional teaching experience
   hire_date DATE NOT NULL,                -- Date when teacher started working
   contract_type VARCHAR(50),              -- Type of employment contract
@@ -27,12 +26,11 @@ CREATE TABLE teachers (
   certifications JSONB DEFAULT '[]',      -- Professional certifications
   evaluations JSONB DEFAULT '[]',         -- Performance evaluations
   additional_info TEXT,                   -- Any additional relevant information
-  created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP,
-  updated_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
 );
 
 -- Create indexes for frequent query patterns
-CREATE INDEX teachers_user_id_idx ON teachers(user_id);
 CREATE INDEX teachers_specialization_idx ON teachers(specialization);
 CREATE INDEX teachers_status_idx ON teachers(status);
 CREATE INDEX teachers_hire_date_idx ON teachers(hire_date);
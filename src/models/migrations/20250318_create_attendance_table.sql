@@ -1,16 +1,19 @@
+-- Create the attendance status enum type
+CREATE TYPE attendance_status AS ENUM ('present', 'absent', 'late', 'excused');
+
 -- Create attendance table to track student attendance for courses
 CREATE TABLE IF NOT EXISTS attendance (
     id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
-    student_id UUID NOT NULL REFERENCES students(id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES students(user_id) ON DELETE CASCADE,
     course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
-    attendance_date DATE NOT NULL,
-    status VARCHAR(10) NOT NULL CHECK (status IN ('present', 'absent', 'late', 'excused')),
-    minutes_late INTEGER DEFAULT 0 CHECK (minutes_late >= 0),
-    comments TEXT,
-    recorded_by UUID REFERENCES users(id),
+    date DATE NOT NULL,
+    status attendance_status NOT NULL,
+    minutes_late INTEGER CHECK (minutes_late >= 0),
+    notes TEXT,
+    recorded_by UUID NOT NULL REFERENCES users(id),
     created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
     updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
-    CONSTRAINT unique_student_course_date UNIQUE (student_id, course_id, attendance_date)
+    CONSTRAINT unique_student_course_date UNIQUE (student_id, course_id, date)
 );
 
 -- Create index for faster lookups by student
@@ -20,7 +23,7 @@ CREATE INDEX idx_attendance_student ON attendance(student_id);
 CREATE INDEX idx_attendance_course ON attendance(course_id);
 
 -- Create index for date-based queries
-CREATE INDEX idx_attendance_date ON attendance(attendance_date);
+CREATE INDEX idx_attendance_date ON attendance(date);
 
 -- Add a function to update the updated_at timestamp automatically
 CREATE OR REPLACE FUNCTION update_attendance_updated_at()
@@ -0,0 +1,14 @@
+-- Migration: Enable pg_trgm Extension
+-- Description: Enables trigram similarity search used to detect redundant courses
+-- Timestamp: 2025-03-25
+
+-- Enable trigram extension (provides similarity() and gin_trgm_ops)
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- Create trigram indices for similarity queries on course content
+CREATE INDEX courses_name_trgm_idx ON courses USING GIN (name gin_trgm_ops);
+CREATE INDEX courses_description_trgm_idx ON courses USING GIN (description gin_trgm_ops);
+
+-- Add comments
+COMMENT ON INDEX courses_name_trgm_idx IS 'Trigram index used to find courses with similar names';
+COMMENT ON INDEX courses_description_trgm_idx IS 'Trigram index used to find courses with similar descriptions';
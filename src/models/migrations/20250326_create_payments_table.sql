@@ -0,0 +1,51 @@
+-- Migration: Create Payments Table
+-- Description: Stores tuition payments, fees and late-fee surcharges
+-- Timestamp: 2025-03-26
+
+-- Create payments table
+CREATE TABLE IF NOT EXISTS payments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    student_id UUID NOT NULL REFERENCES students(user_id) ON DELETE RESTRICT,
+    concept VARCHAR(255) NOT NULL,
+    amount DOUBLE PRECISION NOT NULL CHECK (amount >= 0),
+    currency VARCHAR(3) NOT NULL DEFAULT 'PYG',
+    payment_date TIMESTAMP WITH TIME ZONE NOT NULL,
+    payment_method VARCHAR(50) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'completed', 'cancelled', 'refunded', 'overdue')),
+    receipt_number VARCHAR(50),
+    notes TEXT,
+
+    -- Late fee handling: a surcharge payment points to the payment it penalizes
+    base_payment_id UUID REFERENCES payments(id) ON DELETE CASCADE,
+    late_fee_applied_on DATE,
+
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    -- At most one late-fee payment per base payment
+    UNIQUE(base_payment_id)
+);
+
+-- Add indexes for performance
+CREATE INDEX payments_student_id_idx ON payments(student_id);
+CREATE INDEX payments_status_date_idx ON payments(status, payment_date);
+
+-- Add comments
+COMMENT ON TABLE payments IS 'Stores payments and financial transactions of students';
+COMMENT ON COLUMN payments.base_payment_id IS 'For late-fee surcharges, the overdue payment that originated the fee';
+COMMENT ON COLUMN payments.late_fee_applied_on IS 'Cutoff date of the batch that applied a late fee to this payment';
+
+-- Create function to automatically update the updated_at timestamp
+CREATE OR REPLACE FUNCTION update_payment_timestamp()
+RETURNS TRIGGER AS $$
+BEGIN
+    NEW.updated_at = CURRENT_TIMESTAMP;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+-- Create trigger to automatically update the updated_at timestamp
+CREATE TRIGGER update_payment_timestamp
+BEFORE UPDATE ON payments
+FOR EACH ROW
+EXECUTE FUNCTION update_payment_timestamp();
@@ -0,0 +1,5 @@
+-- Migration: Add withdrawn student status
+-- Description: Allows soft-deleting students by marking them as withdrawn
+-- Date: 2025-03-27
+
+ALTER TYPE student_status ADD VALUE IF NOT EXISTS 'withdrawn';
@@ -0,0 +1,10 @@
+-- Migration: Add course prerequisites
+-- Description: Stores the list of courses that must be completed before taking a course
+-- Timestamp: 2025-03-28
+
+ALTER TABLE courses ADD COLUMN IF NOT EXISTS prerequisites UUID[] NOT NULL DEFAULT '{}';
+
+-- GIN index to resolve "which courses require X" and recursive prerequisite trees
+CREATE INDEX IF NOT EXISTS courses_prerequisites_idx ON courses USING GIN (prerequisites);
+
+COMMENT ON COLUMN courses.prerequisites IS 'IDs of the courses that must be completed before enrolling in this course';
@@ -0,0 +1,18 @@
+-- Migration: Create News Items Table
+-- Description: School news published in the monthly newsletter for guardians
+-- Timestamp: 2025-03-29
+
+CREATE TABLE IF NOT EXISTS news_items (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title VARCHAR(255) NOT NULL,
+    content TEXT NOT NULL,
+    image_path VARCHAR(500),
+    published_at DATE NOT NULL DEFAULT CURRENT_DATE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Newsletters select news by publication month
+CREATE INDEX news_items_published_at_idx ON news_items(published_at);
+
+COMMENT ON TABLE news_items IS 'School news items included in the monthly newsletter';
@@ -0,0 +1,23 @@
+-- Migration: Create Audit Logs Table
+-- Description: Append-only record of who changed what data and when
+-- Timestamp: 2025-03-30
+
+CREATE TABLE IF NOT EXISTS audit_logs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    actor_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    action VARCHAR(50) NOT NULL,
+    entity_type VARCHAR(50) NOT NULL,
+    entity_id UUID,
+    old_value JSONB,
+    new_value JSONB,
+    ip_address VARCHAR(45),
+    user_agent TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Keyset pagination walks (created_at, id) in descending order
+CREATE INDEX audit_logs_created_at_id_idx ON audit_logs(created_at DESC, id DESC);
+CREATE INDEX audit_logs_actor_id_idx ON audit_logs(actor_id);
+CREATE INDEX audit_logs_entity_type_action_idx ON audit_logs(entity_type, action);
+
+COMMENT ON TABLE audit_logs IS 'Audit trail of data changes; the actor name is joined from users, never stored';
@@ -0,0 +1,26 @@
+-- Migration: Create Classrooms and Teacher Availability Tables
+-- Description: Rooms with their capacity and the weekly hours each teacher can teach,
+--              used as constraints by the automatic schedule generator
+-- Timestamp: 2025-03-31
+
+CREATE TABLE IF NOT EXISTS classrooms (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(50) NOT NULL UNIQUE,
+    capacity INTEGER NOT NULL CHECK (capacity > 0),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS teacher_availability (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    teacher_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    day_of_week SMALLINT NOT NULL CHECK (day_of_week BETWEEN 1 AND 7),
+    start_time TIME NOT NULL,
+    end_time TIME NOT NULL,
+    CONSTRAINT teacher_availability_valid_range CHECK (start_time < end_time)
+);
+
+CREATE INDEX teacher_availability_teacher_idx ON teacher_availability(teacher_id);
+
+COMMENT ON TABLE classrooms IS 'Physical rooms that can be booked in course schedules';
+COMMENT ON TABLE teacher_availability IS 'Weekly time windows in which a teacher can be scheduled; no rows means fully available';
@@ -0,0 +1,33 @@
+-- Migration: Create Calendar Events Table
+-- Description: Academic calendar with holidays, recesses and other school events
+-- Timestamp: 2025-04-01
+
+DO $$ BEGIN
+    CREATE TYPE calendar_event_type AS ENUM (
+        'institutional_holiday',
+        'national_holiday',
+        'recess',
+        'exam_period',
+        'school_event'
+    );
+EXCEPTION
+    WHEN duplicate_object THEN null;
+END $$;
+
+CREATE TABLE IF NOT EXISTS calendar_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    academic_year INTEGER NOT NULL,
+    name VARCHAR(150) NOT NULL,
+    event_type calendar_event_type NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT calendar_events_valid_range CHECK (start_date <= end_date)
+);
+
+CREATE INDEX calendar_events_year_idx ON calendar_events(academic_year);
+CREATE INDEX calendar_events_dates_idx ON calendar_events(start_date, end_date);
+
+COMMENT ON TABLE calendar_events IS 'Academic calendar events; holidays and recesses are days without classes';
+COMMENT ON COLUMN calendar_events.end_date IS 'Last day of the event (inclusive); equal to start_date for single-day events';
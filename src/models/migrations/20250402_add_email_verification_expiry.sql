@@ -0,0 +1,8 @@
+-- Migration: Add Email Verification Expiry
+-- Description: Verification links expire; the token and flag columns already exist
+-- Timestamp: 2025-04-02
+
+ALTER TABLE authentications
+    ADD COLUMN IF NOT EXISTS email_verification_expires TIMESTAMP WITH TIME ZONE;
+
+COMMENT ON COLUMN authentications.email_verification_expires IS 'Expiration time for the email verification token';
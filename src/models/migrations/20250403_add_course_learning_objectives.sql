@@ -0,0 +1,8 @@
+-- Migration: Add Course Learning Objectives
+-- Description: Learning objectives listed for each course in the published catalog
+-- Timestamp: 2025-04-03
+
+ALTER TABLE courses
+    ADD COLUMN IF NOT EXISTS learning_objectives TEXT[] NOT NULL DEFAULT '{}';
+
+COMMENT ON COLUMN courses.learning_objectives IS 'Learning objectives shown in the course catalog, in display order';
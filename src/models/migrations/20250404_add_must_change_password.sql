@@ -0,0 +1,8 @@
+-- Migration: Add Must Change Password Flag
+-- Description: Accounts created with a temporary password must set their own on first login
+-- Timestamp: 2025-04-04
+
+ALTER TABLE authentications
+    ADD COLUMN IF NOT EXISTS must_change_password BOOLEAN NOT NULL DEFAULT false;
+
+COMMENT ON COLUMN authentications.must_change_password IS 'True while the account still uses a temporary password issued by the institution';
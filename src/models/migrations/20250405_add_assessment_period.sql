@@ -0,0 +1,18 @@
+-- Migration: Add Assessment Period
+-- Description: Grading period (trimester) of each assessment, used to filter and summarize grades
+-- Timestamp: 2025-04-05
+
+ALTER TABLE assessments
+    ADD COLUMN IF NOT EXISTS period SMALLINT NOT NULL DEFAULT 1 CHECK (period BETWEEN 1 AND 4);
+
+-- Existing assessments are assigned by date: February-May, June-August, September onwards
+UPDATE assessments
+SET period = CASE
+    WHEN EXTRACT(MONTH FROM assessment_date) <= 5 THEN 1
+    WHEN EXTRACT(MONTH FROM assessment_date) <= 8 THEN 2
+    ELSE 3
+END;
+
+CREATE INDEX IF NOT EXISTS idx_assessments_course_period ON assessments(course_id, period);
+
+COMMENT ON COLUMN assessments.period IS 'Grading period (trimester) of the academic year the assessment belongs to';
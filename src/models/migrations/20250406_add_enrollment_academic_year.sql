@@ -0,0 +1,26 @@
+-- Migration: Add Enrollment Academic Year
+-- Description: Students in multi-year programs get one enrollment per course and academic year
+-- Timestamp: 2025-04-06
+
+ALTER TABLE enrollments ADD COLUMN IF NOT EXISTS academic_year INTEGER;
+
+-- Existing enrollments belong to the academic year of their course
+UPDATE enrollments e
+SET academic_year = c.academic_year
+FROM courses c
+WHERE c.id = e.course_id AND e.academic_year IS NULL;
+
+UPDATE enrollments
+SET academic_year = EXTRACT(YEAR FROM enrollment_date)::INTEGER
+WHERE academic_year IS NULL;
+
+ALTER TABLE enrollments ALTER COLUMN academic_year SET NOT NULL;
+
+-- A student can be enrolled again in the same course in a later year
+ALTER TABLE enrollments DROP CONSTRAINT IF EXISTS enrollments_student_id_course_id_key;
+ALTER TABLE enrollments
+    ADD CONSTRAINT enrollments_student_course_year_key UNIQUE (student_id, course_id, academic_year);
+
+CREATE INDEX IF NOT EXISTS enrollments_student_year_idx ON enrollments(student_id, academic_year);
+
+COMMENT ON COLUMN enrollments.academic_year IS 'Academic year the enrollment belongs to';
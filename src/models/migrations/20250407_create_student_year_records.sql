@@ -0,0 +1,35 @@
+-- Migration: Create Student Year Records
+-- Description: One record per student and academic year with the grade, section and outcome of that year
+-- Timestamp: 2025-04-07
+
+CREATE TABLE IF NOT EXISTS student_year_records (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- User ID of the student, as in enrollments.student_id
+    student_id UUID NOT NULL,
+    academic_year INTEGER NOT NULL,
+    grade VARCHAR(20) NOT NULL,
+    section VARCHAR(10) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'withdrawn', 'promoted', 'repeated', 'graduated')),
+    enrolled_on DATE NOT NULL DEFAULT CURRENT_DATE,
+    withdrawn_on DATE,
+    withdrawal_reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (student_id, academic_year),
+    CHECK (status <> 'withdrawn' OR withdrawn_on IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_student_year_records_year ON student_year_records(academic_year, grade, section);
+
+-- Current year of existing students; the enrollment day is unknown, so the year start is used
+INSERT INTO student_year_records (student_id, academic_year, grade, section, status, enrolled_on, withdrawn_on)
+SELECT user_id, academic_year, current_grade, section,
+       CASE WHEN status::text = 'withdrawn' THEN 'withdrawn' ELSE 'active' END,
+       make_date(academic_year, 1, 1),
+       CASE WHEN status::text = 'withdrawn' THEN CURRENT_DATE END
+FROM students
+ON CONFLICT (student_id, academic_year) DO NOTHING;
+
+COMMENT ON TABLE student_year_records IS 'Grade, section and outcome of each student per academic year (matrícula)';
+COMMENT ON COLUMN student_year_records.enrolled_on IS 'Day the student was enrolled for the year, used for partial-year comparisons';
@@ -0,0 +1,16 @@
+-- Migration: Create Device Tokens Table
+-- Description: Push notification tokens of the mobile and web apps, one row per device
+-- Timestamp: 2025-04-08
+
+CREATE TABLE IF NOT EXISTS device_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- A token identifies one app installation; re-registering it moves it to the new user
+    token TEXT NOT NULL UNIQUE,
+    platform VARCHAR(10) NOT NULL CHECK (platform IN ('ios', 'android', 'web')),
+    registered_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_device_tokens_user ON device_tokens(user_id);
+
+COMMENT ON TABLE device_tokens IS 'Firebase Cloud Messaging registration tokens per user device';
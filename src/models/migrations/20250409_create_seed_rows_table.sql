@@ -0,0 +1,13 @@
+-- Migration: Create Seed Rows Table
+-- Description: Registry of rows created by db::seed, so seeding is idempotent and unseed removes exactly those rows
+-- Timestamp: 2025-04-09
+
+CREATE TABLE IF NOT EXISTS seed_rows (
+    id BIGSERIAL PRIMARY KEY,
+    seed VARCHAR(50) NOT NULL,
+    -- Natural key of the seeded entity, e.g. 'user:admin@demo.sai.edu.py'
+    seed_key VARCHAR(200) NOT NULL UNIQUE,
+    table_name VARCHAR(63) NOT NULL,
+    row_id UUID NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Migration: Create Feature Flags Table
+-- Description: Runtime overrides of the [features] configuration, edited at PUT /api/admin/features
+-- Timestamp: 2025-04-10
+
+CREATE TABLE IF NOT EXISTS feature_flags (
+    -- Flag name as in the configuration, e.g. 'payments_gateway'
+    name VARCHAR(64) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    updated_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE feature_flags IS 'Feature flag values that override the configuration file until removed';
@@ -0,0 +1,10 @@
+-- Migration: Enrollments Unique While Not Withdrawn
+-- Description: One live enrollment per student, course and academic year; a withdrawn student can enroll again
+-- Timestamp: 2025-04-11
+
+-- The plain unique key also counted withdrawn enrollments
+ALTER TABLE enrollments DROP CONSTRAINT IF EXISTS enrollments_student_course_year_key;
+
+CREATE UNIQUE INDEX IF NOT EXISTS enrollments_active_student_course_year_key
+    ON enrollments (student_id, course_id, academic_year)
+    WHERE status <> 'withdrawn';
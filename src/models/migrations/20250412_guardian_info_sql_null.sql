@@ -0,0 +1,11 @@
+-- Migration: Guardian Info SQL Null
+-- Description: Students without a guardian store SQL NULL instead of a JSON null or an empty object
+-- Timestamp: 2025-04-12
+
+ALTER TABLE students ALTER COLUMN guardian_info DROP DEFAULT;
+ALTER TABLE students ALTER COLUMN guardian_info DROP NOT NULL;
+
+-- Rows written before the change held 'null' or the old '{}' default
+UPDATE students
+SET guardian_info = NULL
+WHERE guardian_info IN ('null'::jsonb, '{}'::jsonb);
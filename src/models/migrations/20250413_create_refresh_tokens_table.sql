@@ -0,0 +1,18 @@
+-- Migration: Create Refresh Tokens Table
+-- Description: Refresh tokens issued at login, stored as SHA-256 hashes so a database leak does not expose live sessions
+-- Timestamp: 2025-04-13
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    -- Hex SHA-256 of the token handed to the client; the token itself is never stored
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    issued_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    -- Set when the token is rotated, on logout or when every session of the user is closed
+    revoked BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_user ON refresh_tokens(user_id) WHERE NOT revoked;
+
+COMMENT ON TABLE refresh_tokens IS 'One row per session; rotated on every refresh';
@@ -0,0 +1,22 @@
+-- Migration: Create Institutions Table
+-- Description: Legal and contact data of the institution printed on receipts and reports
+-- Timestamp: 2025-04-14
+
+CREATE TABLE IF NOT EXISTS institutions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    -- RUC with its check digit, validated by the application before it is stored
+    tax_id VARCHAR(20) NOT NULL UNIQUE,
+    address TEXT NOT NULL,
+    phone VARCHAR(30) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    website VARCHAR(255),
+    director_name VARCHAR(255) NOT NULL,
+    logo_path VARCHAR(500),
+    foundation_year INTEGER NOT NULL,
+    education_levels TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+COMMENT ON TABLE institutions IS 'Educational institution, identified by its RUC';
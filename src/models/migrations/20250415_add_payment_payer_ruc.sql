@@ -0,0 +1,8 @@
+-- Migration: Add Payment Payer RUC
+-- Description: RUC of whoever pays, printed on the receipt when the payer asks for an invoice
+-- Timestamp: 2025-04-15
+
+ALTER TABLE payments
+    ADD COLUMN IF NOT EXISTS payer_ruc VARCHAR(20);
+
+COMMENT ON COLUMN payments.payer_ruc IS 'RUC with its check digit, validated by the application before it is stored';
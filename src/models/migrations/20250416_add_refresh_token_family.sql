@@ -0,0 +1,16 @@
+-- Migration: Add Refresh Token Family
+-- Description: Tokens rotated from the same login share a family, so reusing a rotated token can close the whole session
+-- Timestamp: 2025-04-16
+
+ALTER TABLE refresh_tokens
+    ADD COLUMN IF NOT EXISTS family_id UUID;
+
+-- Tokens issued before this migration each start their own family
+UPDATE refresh_tokens SET family_id = id WHERE family_id IS NULL;
+
+ALTER TABLE refresh_tokens
+    ALTER COLUMN family_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family ON refresh_tokens(family_id) WHERE NOT revoked;
+
+COMMENT ON COLUMN refresh_tokens.family_id IS 'Login the token descends from; every rotation keeps the family of the token it replaces';
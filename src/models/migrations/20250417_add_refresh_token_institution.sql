@@ -0,0 +1,8 @@
+-- Migration: Add Refresh Token Institution
+-- Description: Refresh tokens remember the institution they were issued for, so a refresh cannot switch to another tenant
+-- Timestamp: 2025-04-17
+
+ALTER TABLE refresh_tokens
+    ADD COLUMN IF NOT EXISTS institution VARCHAR(50);
+
+COMMENT ON COLUMN refresh_tokens.institution IS 'Institution slug of the session, NULL for single-institution deployments; rotation keeps it';
@@ -0,0 +1,20 @@
+-- Migration: Create Courses Table (SQLite)
+-- Description: Courses with their weekly schedule stored as a JSON array
+-- Timestamp: 2025-03-13
+
+CREATE TABLE IF NOT EXISTS courses (
+    id BLOB PRIMARY KEY,
+    code TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    description TEXT,
+    grade_level TEXT NOT NULL,
+    credits REAL NOT NULL DEFAULT 0,
+    teacher_id BLOB REFERENCES users(id) ON DELETE SET NULL,
+    academic_year INTEGER NOT NULL,
+    schedule TEXT NOT NULL DEFAULT '[]',
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_courses_academic_year ON courses (academic_year);
+CREATE INDEX IF NOT EXISTS idx_courses_teacher ON courses (teacher_id);
@@ -0,0 +1,18 @@
+-- Migration: Create Students Table (SQLite)
+-- Description: Student profile of a user, keyed by the user id
+-- Timestamp: 2025-03-13
+
+CREATE TABLE IF NOT EXISTS students (
+    user_id BLOB PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    enrollment_number TEXT NOT NULL UNIQUE,
+    current_grade TEXT NOT NULL,
+    section TEXT NOT NULL,
+    academic_year INTEGER NOT NULL,
+    -- JSON object with the guardian's contact details
+    guardian_info TEXT,
+    status TEXT NOT NULL DEFAULT 'Active' CHECK (status IN ('Active', 'Suspended', 'Withdrawn', 'Graduated', 'Transferred')),
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_students_grade_section ON students (current_grade, section);
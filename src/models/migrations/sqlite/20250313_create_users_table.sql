@@ -0,0 +1,19 @@
+-- Migration: Create Users Table (SQLite)
+-- Description: Users of every role; UUIDs are stored as 16-byte blobs, timestamps as RFC 3339 text
+-- Timestamp: 2025-03-13
+
+CREATE TABLE IF NOT EXISTS users (
+    id BLOB PRIMARY KEY,
+    document_id TEXT NOT NULL UNIQUE,
+    full_name TEXT NOT NULL,
+    email TEXT NOT NULL UNIQUE,
+    phone TEXT,
+    address TEXT,
+    birth_date TEXT NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('Admin', 'Director', 'Teacher', 'Student', 'Parent', 'Secretary', 'Accountant')),
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_users_full_name ON users (full_name);
+CREATE INDEX IF NOT EXISTS idx_users_role ON users (role);
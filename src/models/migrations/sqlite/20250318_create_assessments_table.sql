@@ -0,0 +1,24 @@
+-- Migration: Create Assessments Table (SQLite)
+-- Description: Graded assessments of an enrollment
+-- Timestamp: 2025-03-18
+
+CREATE TABLE IF NOT EXISTS assessments (
+    id BLOB PRIMARY KEY,
+    enrollment_id BLOB NOT NULL REFERENCES enrollments(id) ON DELETE CASCADE,
+    course_id BLOB NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    assessment_type TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT,
+    score REAL NOT NULL CHECK (score >= 0),
+    max_score REAL NOT NULL CHECK (max_score > 0),
+    weight REAL NOT NULL CHECK (weight >= 0 AND weight <= 1),
+    assessment_date TEXT NOT NULL,
+    is_final INTEGER NOT NULL DEFAULT 0,
+    period INTEGER NOT NULL DEFAULT 1 CHECK (period >= 1),
+    comments TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    CHECK (score <= max_score)
+);
+
+CREATE INDEX IF NOT EXISTS idx_assessments_enrollment ON assessments (enrollment_id);
@@ -0,0 +1,19 @@
+-- Migration: Create Attendance Table (SQLite)
+-- Description: One attendance record per student, course and day
+-- Timestamp: 2025-03-18
+
+CREATE TABLE IF NOT EXISTS attendance (
+    id BLOB PRIMARY KEY,
+    student_id BLOB NOT NULL REFERENCES students(user_id) ON DELETE CASCADE,
+    course_id BLOB NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    date TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('present', 'absent', 'late', 'excused')),
+    notes TEXT,
+    minutes_late INTEGER CHECK (minutes_late >= 0),
+    recorded_by BLOB NOT NULL REFERENCES users(id),
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (student_id, course_id, date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_attendance_course_date ON attendance (course_id, date);
@@ -0,0 +1,21 @@
+-- Migration: Create Enrollments Table (SQLite)
+-- Description: Student enrollments in courses, one per academic year
+-- Timestamp: 2025-03-18
+
+CREATE TABLE IF NOT EXISTS enrollments (
+    id BLOB PRIMARY KEY,
+    student_id BLOB NOT NULL REFERENCES students(user_id) ON DELETE CASCADE,
+    course_id BLOB NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    academic_year INTEGER NOT NULL,
+    enrollment_date TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('active', 'withdrawn', 'completed', 'on_hold', 'pending')),
+    completion_date TEXT,
+    final_grade REAL,
+    notes TEXT,
+    payment_info TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    UNIQUE (student_id, course_id, academic_year)
+);
+
+CREATE INDEX IF NOT EXISTS idx_enrollments_course ON enrollments (course_id);
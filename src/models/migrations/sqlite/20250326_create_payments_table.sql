@@ -0,0 +1,22 @@
+-- Migration: Create Payments Table (SQLite)
+-- Description: Student payments; a late fee points to the payment it penalizes
+-- Timestamp: 2025-03-26
+
+CREATE TABLE IF NOT EXISTS payments (
+    id BLOB PRIMARY KEY,
+    student_id BLOB NOT NULL REFERENCES students(user_id) ON DELETE RESTRICT,
+    concept TEXT NOT NULL,
+    amount REAL NOT NULL CHECK (amount >= 0),
+    currency TEXT NOT NULL DEFAULT 'PYG',
+    payment_date TEXT NOT NULL,
+    payment_method TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'completed', 'cancelled', 'refunded', 'overdue')),
+    receipt_number TEXT,
+    notes TEXT,
+    base_payment_id BLOB REFERENCES payments(id) ON DELETE CASCADE,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_payments_student ON payments (student_id);
+CREATE INDEX IF NOT EXISTS idx_payments_status ON payments (status);
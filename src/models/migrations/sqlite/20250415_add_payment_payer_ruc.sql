@@ -0,0 +1,5 @@
+-- Migration: Add Payment Payer RUC (SQLite)
+-- Description: RUC of whoever pays, printed on the receipt when the payer asks for an invoice
+-- Timestamp: 2025-04-15
+
+ALTER TABLE payments ADD COLUMN payer_ruc TEXT;
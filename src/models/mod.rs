@@ -3,10 +3,15 @@
 //! Este módulo contiene todas las estructuras de datos que representan
 //! las entidades principales del sistema administrativo escolar.
 
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::postgres::{PgTypeInfo, PgValueRef};
+use sqlx::types::Json;
+use sqlx::Postgres;
 use uuid::Uuid;
 
+use crate::db::{DbError, FieldError};
+
 // Submódulos
 pub mod user;
 pub mod student;
@@ -19,22 +24,36 @@ pub mod assessment;
 pub mod payment;
 pub mod institution;
 pub mod authentication;
+pub mod patch;
+pub mod news;
+pub mod audit_log;
+pub mod schedule;
+pub mod calendar;
+pub mod student_year_record;
+pub mod device_token;
+pub mod feature_flag;
 
 // Re-exportaciones para facilitar el acceso
 pub use user::User;
 pub use student::Student;
 pub use teacher::Teacher;
-pub use course::Course;
 pub use enrollment::Enrollment;
-pub use attendance::Attendance;
+pub use attendance::{Attendance, AttendanceStatus};
 pub use grade::Grade;
 pub use assessment::Assessment;
-pub use payment::Payment;
-pub use institution::Institution;
-pub use authentication::Authentication;
+pub use institution::{Institution, InstitutionSettings};
+pub use authentication::{Authentication, RefreshToken};
+pub use patch::Patch;
+pub use news::NewsItem;
+pub use audit_log::AuditLogEntry;
+pub use calendar::{AcademicCalendar, CalendarEvent};
+pub use device_token::{DeviceToken, Platform};
 
 /// Enumeración que representa los diferentes roles de usuario en el sistema
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+///
+/// Se guarda en el tipo `user_role`, con los mismos nombres que las variantes.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "user_role")]
 pub enum Role {
     Admin,
     Director,
@@ -45,48 +64,24 @@ pub enum Role {
     Accountant,
 }
 
-/// Estructura básica para el Usuario que sirve como base para estudiantes y profesores
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct User {
-    /// Identificador único del usuario
-    pub id: Uuid,
-    /// Número de documento de identidad (cédula)
-    pub document_id: String,
-    /// Nombre completo del usuario
-    pub full_name: String,
-    /// Correo electrónico de contacto
-    pub email: String,
-    /// Número de teléfono de contacto
-    pub phone: Option<String>,
-    /// Dirección física del usuario
-    pub address: Option<String>,
-    /// Fecha de nacimiento
-    pub birth_date: chrono::NaiveDate,
-    /// Rol del usuario en el sistema
-    pub role: Role,
-    /// Fecha de creación del registro
-    pub created_at: DateTime<Utc>,
-    /// Última actualización del registro
-    pub updated_at: DateTime<Utc>,
-}
+impl Role {
+    /// Nombre del rol en `user_role` y en JSON (`"Admin"`, `"Teacher"`, ...)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "Admin",
+            Role::Director => "Director",
+            Role::Teacher => "Teacher",
+            Role::Student => "Student",
+            Role::Parent => "Parent",
+            Role::Secretary => "Secretary",
+            Role::Accountant => "Accountant",
+        }
+    }
 
-/// Estructura que representa a un Estudiante en el sistema
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Student {
-    /// Referencia al usuario base
-    pub user_id: Uuid,
-    /// Número de matrícula del estudiante
-    pub enrollment_number: String,
-    /// Grado o curso actual
-    pub current_grade: String,
-    /// Sección o división del grado
-    pub section: String,
-    /// Año académico actual
-    pub academic_year: i32,
-    /// Información del padre/madre/tutor
-    pub guardian_info: Option<GuardianInfo>,
-    /// Estado académico (activo, suspendido, etc.)
-    pub status: StudentStatus,
+    /// Nombre del rol en los tokens y en `GET /api/auth/me`: [`Role::as_str`] en minúsculas
+    pub fn claim_name(&self) -> String {
+        self.as_str().to_ascii_lowercase()
+    }
 }
 
 /// Información del tutor o encargado del estudiante
@@ -102,10 +97,61 @@ pub struct GuardianInfo {
     pub email: Option<String>,
     /// Número de teléfono de contacto
     pub phone: String,
+    /// Desea recibir los resúmenes periódicos y el boletín mensual por correo
+    #[serde(default)]
+    pub weekly_summary: bool,
+}
+
+// Se guarda como JSONB; un estudiante sin tutor tiene SQL NULL
+impl sqlx::Type<Postgres> for GuardianInfo {
+    fn type_info() -> PgTypeInfo {
+        <Json<GuardianInfo> as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Json<GuardianInfo> as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for GuardianInfo {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(<Json<GuardianInfo> as sqlx::Decode<Postgres>>::decode(value)?.0)
+    }
+}
+
+/// Columna JSONB con un arreglo, leída como `Vec<T>` en `query_as!`
+///
+/// Las macros convierten cada columna con `.into()`, y `Json<Vec<T>>` no se
+/// puede convertir en `Vec<T>`; se anota la columna con este tipo en su lugar
+/// (`schedule as "schedule!: JsonColumn<Vec<ScheduleSlot>>"`).
+#[derive(Debug)]
+pub struct JsonColumn<T>(pub T);
+
+impl<T> sqlx::Type<Postgres> for JsonColumn<T> {
+    fn type_info() -> PgTypeInfo {
+        <Json<T> as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Json<T> as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r, T: DeserializeOwned> sqlx::Decode<'r, Postgres> for JsonColumn<T> {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        Ok(JsonColumn(<Json<T> as sqlx::Decode<Postgres>>::decode(value)?.0))
+    }
+}
+
+impl<T> From<JsonColumn<Vec<T>>> for Vec<T> {
+    fn from(column: JsonColumn<Vec<T>>) -> Self {
+        column.0
+    }
 }
 
 /// Estado posible de un estudiante
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "student_status", rename_all = "lowercase")]
 pub enum StudentStatus {
     Active,
     Suspended,
@@ -114,27 +160,9 @@ pub enum StudentStatus {
     Transferred,
 }
 
-/// Estructura que representa a un Profesor en el sistema
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Teacher {
-    /// Referencia al usuario base
-    pub user_id: Uuid,
-    /// Número de registro profesional
-    pub professional_id: String,
-    /// Especialidad del profesor
-    pub specialization: String,
-    /// Fecha de contratación
-    pub hire_date: chrono::NaiveDate,
-    /// Nivel de educación (licenciatura, maestría, etc.)
-    pub education_level: String,
-    /// Materias que puede enseñar
-    pub subjects: Vec<String>,
-    /// Estado laboral (activo, licencia, etc.)
-    pub status: TeacherStatus,
-}
-
 /// Estado posible de un profesor
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
+#[sqlx(type_name = "teacher_status", rename_all = "snake_case")]
 pub enum TeacherStatus {
     Active,
     OnLeave,
@@ -167,7 +195,7 @@ pub struct Course {
 }
 
 /// Estructura que representa un espacio en el horario
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScheduleSlot {
     /// Día de la semana (1-7, donde 1 es lunes)
     pub day_of_week: u8,
@@ -179,57 +207,72 @@ pub struct ScheduleSlot {
     pub classroom: String,
 }
 
-/// Estructura que representa la inscripción de un estudiante a un curso
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Enrollment {
-    /// Identificador único
-    pub id: Uuid,
-    /// Estudiante inscrito
-    pub student_id: Uuid,
-    /// Curso al que se inscribe
-    pub course_id: Uuid,
-    /// Fecha de inscripción
-    pub enrollment_date: DateTime<Utc>,
-    /// Estado de la inscripción
-    pub status: EnrollmentStatus,
-    /// Notas o comentarios
-    pub notes: Option<String>,
-}
+impl ScheduleSlot {
+    /// Verifica el bloque y devuelve un error por cada campo inválido
+    ///
+    /// - `day_of_week` está entre 1 (lunes) y 7 (domingo)
+    /// - `start_time` y `end_time` tienen el formato `HH:MM`
+    /// - `start_time` es anterior a `end_time`; un bloque puede terminar
+    ///   justo cuando empieza el siguiente
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = Vec::new();
 
-/// Estado de una inscripción
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum EnrollmentStatus {
-    Active,
-    Withdrawn,
-    Completed,
-    Failed,
-}
+        if !(1..=7).contains(&self.day_of_week) {
+            errors.push(FieldError::new("day_of_week", "must be between 1 (Monday) and 7 (Sunday)"));
+        }
+        let start = NaiveTime::parse_from_str(&self.start_time, "%H:%M").ok();
+        if start.is_none() {
+            errors.push(FieldError::new("start_time", "must be a time in HH:MM format"));
+        }
+        let end = NaiveTime::parse_from_str(&self.end_time, "%H:%M").ok();
+        if end.is_none() {
+            errors.push(FieldError::new("end_time", "must be a time in HH:MM format"));
+        }
+        if let (Some(start), Some(end)) = (start, end) {
+            if start >= end {
+                errors.push(FieldError::new("end_time", "must be after start_time"));
+            }
+        }
 
-/// Institución educativa
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Institution {
-    /// Identificador único
-    pub id: Uuid,
-    /// Nombre de la institución
-    pub name: String,
-    /// RUC o identificador fiscal
-    pub tax_id: String,
-    /// Dirección física
-    pub address: String,
-    /// Teléfono de contacto
-    pub phone: String,
-    /// Correo electrónico
-    pub email: String,
-    /// Sitio web
-    pub website: Option<String>,
-    /// Director o responsable
-    pub director_name: String,
-    /// Logo de la institución (ruta al archivo)
-    pub logo_path: Option<String>,
-    /// Año de fundación
-    pub foundation_year: i32,
-    /// Niveles educativos ofrecidos
-    pub education_levels: Vec<String>,
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Verifica el horario completo de un curso
+    ///
+    /// El horario debe tener al menos un bloque, cada bloque debe pasar
+    /// [`ScheduleSlot::validate`] y ningún bloque puede repetirse. Los errores
+    /// de todos los bloques se informan juntos, con la posición del bloque en
+    /// el campo (`schedule[2].end_time`).
+    pub fn validate_schedule(slots: &[ScheduleSlot]) -> Result<(), DbError> {
+        let mut errors = Vec::new();
+
+        if slots.is_empty() {
+            errors.push(FieldError::new("schedule", "must have at least one slot"));
+        }
+        for (index, slot) in slots.iter().enumerate() {
+            if let Err(slot_errors) = slot.validate() {
+                errors.extend(slot_errors.into_iter().map(|error| {
+                    FieldError::new(format!("schedule[{}].{}", index, error.field), error.message)
+                }));
+            }
+            if let Some(first) = slots[..index].iter().position(|other| other == slot) {
+                errors.push(FieldError::new(
+                    format!("schedule[{}]", index),
+                    format!("duplicates schedule[{}]", first),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DbError::Invalid(errors))
+        }
+    }
 }
 
 /// Estructura para almacenar pagos y transacciones financieras
@@ -253,8 +296,12 @@ pub struct Payment {
     pub status: PaymentStatus,
     /// Número de comprobante o factura
     pub receipt_number: Option<String>,
+    /// RUC del pagador, si pidió factura
+    pub payer_ruc: Option<String>,
     /// Notas adicionales
     pub notes: Option<String>,
+    /// Pago original al que corresponde un recargo por mora
+    pub base_payment_id: Option<Uuid>,
 }
 
 /// Estado de un pago
@@ -267,54 +314,19 @@ pub enum PaymentStatus {
     Overdue,
 }
 
-/// Estructura para almacenar calificaciones
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Grade {
-    /// Identificador único
-    pub id: Uuid,
-    /// Estudiante evaluado
-    pub student_id: Uuid,
-    /// Curso evaluado
-    pub course_id: Uuid,
-    /// Tipo de evaluación (examen, trabajo práctico, etc.)
-    pub evaluation_type: String,
-    /// Valor numérico de la calificación
-    pub value: f32,
-    /// Escala (1-5, 1-10, etc.)
-    pub scale: u8,
-    /// Fecha de la evaluación
-    pub evaluation_date: chrono::NaiveDate,
-    /// Profesor que asignó la calificación
-    pub teacher_id: Uuid,
-    /// Comentarios adicionales
-    pub comments: Option<String>,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-/// Estructura para registro de asistencia
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Attendance {
-    /// Identificador único
-    pub id: Uuid,
-    /// Estudiante
-    pub student_id: Uuid,
-    /// Curso al que asistió
-    pub course_id: Uuid,
-    /// Fecha de asistencia
-    pub date: chrono::NaiveDate,
-    /// Estado de asistencia
-    pub status: AttendanceStatus,
-    /// Justificación en caso de ausencia
-    pub justification: Option<String>,
-    /// Registrado por (profesor o administrativo)
-    pub recorded_by: Uuid,
+    #[test]
+    fn test_role_names_match_serde() {
+        let roles = [
+            Role::Admin, Role::Director, Role::Teacher, Role::Student,
+            Role::Parent, Role::Secretary, Role::Accountant,
+        ];
+        for role in roles {
+            assert_eq!(serde_json::to_value(&role).unwrap(), role.as_str());
+        }
+        assert_eq!(Role::Secretary.claim_name(), "secretary");
+    }
 }
-
-/// Estado de asistencia
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum AttendanceStatus {
-    Present,
-    Absent,
-    Late,
-    JustifiedAbsence,
-}
-
@@ -0,0 +1,169 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Noticia institucional publicada en el boletín mensual
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct NewsItem {
+    /// Identificador único de la noticia
+    pub id: Uuid,
+    /// Título de la noticia
+    pub title: String,
+    /// Contenido de la noticia
+    pub content: String,
+    /// Ruta de la imagen asociada (opcional)
+    pub image_path: Option<String>,
+    /// Fecha de publicación
+    pub published_at: NaiveDate,
+    /// Fecha de creación del registro
+    pub created_at: DateTime<Utc>,
+    /// Última actualización del registro
+    pub updated_at: DateTime<Utc>,
+}
+
+/// DTO para la creación de una noticia
+#[derive(Debug, Deserialize)]
+pub struct CreateNewsItemDto {
+    pub title: String,
+    pub content: String,
+    pub image_path: Option<String>,
+    pub published_at: NaiveDate,
+}
+
+/// DTO para la actualización de una noticia
+#[derive(Debug, Deserialize)]
+pub struct UpdateNewsItemDto {
+    pub title: Option<String>,
+    pub content: Option<String>,
+    pub image_path: Option<String>,
+    pub published_at: Option<NaiveDate>,
+}
+
+impl NewsItem {
+    /// Crea una nueva noticia en la base de datos
+    pub async fn create(pool: &PgPool, dto: CreateNewsItemDto) -> Result<NewsItem, DbError> {
+        let news_item = sqlx::query_as!(
+            NewsItem,
+            r#"
+            INSERT INTO news_items (title, content, image_path, published_at)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, title, content, image_path, published_at, created_at, updated_at
+            "#,
+            dto.title,
+            dto.content,
+            dto.image_path,
+            dto.published_at
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(news_item)
+    }
+
+    /// Encuentra una noticia por su ID
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<NewsItem>, DbError> {
+        let news_item = sqlx::query_as!(
+            NewsItem,
+            r#"
+            SELECT id, title, content, image_path, published_at, created_at, updated_at
+            FROM news_items
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(news_item)
+    }
+
+    /// Lista las noticias, de la más reciente a la más antigua
+    pub async fn find_all(pool: &PgPool, limit: i64, offset: i64) -> Result<Vec<NewsItem>, DbError> {
+        let news_items = sqlx::query_as!(
+            NewsItem,
+            r#"
+            SELECT id, title, content, image_path, published_at, created_at, updated_at
+            FROM news_items
+            ORDER BY published_at DESC, created_at DESC
+            LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(news_items)
+    }
+
+    /// Lista las noticias publicadas entre dos fechas (ambas inclusive)
+    pub async fn find_published_between(
+        pool: &PgPool,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<NewsItem>, DbError> {
+        let news_items = sqlx::query_as!(
+            NewsItem,
+            r#"
+            SELECT id, title, content, image_path, published_at, created_at, updated_at
+            FROM news_items
+            WHERE published_at BETWEEN $1 AND $2
+            ORDER BY published_at
+            "#,
+            from,
+            to
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(news_items)
+    }
+
+    /// Actualiza una noticia existente
+    pub async fn update(pool: &PgPool, id: Uuid, dto: UpdateNewsItemDto) -> Result<NewsItem, DbError> {
+        let existing = Self::find_by_id(pool, id)
+            .await?
+            .ok_or_else(|| DbError::NotFound("Noticia no encontrada".to_string()))?;
+
+        // Usamos los valores actuales si no se especifican nuevos
+        let title = dto.title.unwrap_or(existing.title);
+        let content = dto.content.unwrap_or(existing.content);
+        let image_path = dto.image_path.or(existing.image_path);
+        let published_at = dto.published_at.unwrap_or(existing.published_at);
+
+        let news_item = sqlx::query_as!(
+            NewsItem,
+            r#"
+            UPDATE news_items
+            SET title = $1, content = $2, image_path = $3, published_at = $4, updated_at = NOW()
+            WHERE id = $5
+            RETURNING id, title, content, image_path, published_at, created_at, updated_at
+            "#,
+            title,
+            content,
+            image_path,
+            published_at,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(news_item)
+    }
+
+    /// Elimina una noticia por su ID
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<(), DbError> {
+        let result = sqlx::query!("DELETE FROM news_items WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound("Noticia no encontrada".to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+//! Soporte para actualizaciones parciales con JSON Merge Patch (RFC 7396)
+//!
+//! Con `Option<T>` no es posible distinguir un campo ausente de un campo
+//! enviado explícitamente como `null`. `Patch<T>` conserva esa diferencia:
+//! un campo ausente no modifica la columna y un `null` la deja vacía.
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+/// Valor de un campo dentro de un documento merge-patch
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Patch<T> {
+    /// El campo no fue enviado: se conserva el valor actual
+    #[default]
+    Undefined,
+    /// El campo fue enviado como `null`: se elimina el valor actual
+    Null,
+    /// El campo fue enviado con un nuevo valor
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    /// Indica si el campo fue omitido en el documento
+    pub fn is_undefined(&self) -> bool {
+        matches!(self, Patch::Undefined)
+    }
+
+    /// Aplica el cambio sobre el valor actual de una columna opcional
+    pub fn apply(self, current: Option<T>) -> Option<T> {
+        match self {
+            Patch::Undefined => current,
+            Patch::Null => None,
+            Patch::Value(value) => Some(value),
+        }
+    }
+
+    /// Nuevo valor de la columna cuando el campo fue enviado (`None` para `null`)
+    ///
+    /// Junto con [`Patch::is_undefined`] permite aplicar el cambio en SQL con
+    /// `CASE WHEN $enviado THEN $valor ELSE columna END`, sin leer la fila antes.
+    pub fn into_option(self) -> Option<T> {
+        self.apply(None)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Patch<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // Solo se llama cuando el campo está presente; los campos ausentes
+        // toman `Patch::Undefined` mediante `#[serde(default)]`
+        Option::<T>::deserialize(deserializer).map(|value| match value {
+            Some(value) => Patch::Value(value),
+            None => Patch::Null,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Patch::Value(value) => value.serialize(serializer),
+            Patch::Undefined | Patch::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+/// Deserializa un campo obligatorio de un documento merge-patch
+///
+/// Se usa con `#[serde(default, deserialize_with = "required")]`: un campo
+/// ausente queda en `None`, pero un `null` es un error de tipo, ya que la
+/// columna no admite valores vacíos.
+pub fn required<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Deserializa un documento merge-patch desde el cuerpo de la petición
+///
+/// Los errores de tipo se devuelven tal cual para que la capa de rutas
+/// responda con 422 Unprocessable Entity.
+pub fn from_merge_patch<T>(body: &[u8]) -> Result<T, serde_json::Error>
+where
+    T: for<'de> Deserialize<'de>,
+{
+    serde_json::from_slice(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct ContactPatch {
+        #[serde(default)]
+        phone: Patch<String>,
+        #[serde(default, deserialize_with = "required")]
+        full_name: Option<String>,
+    }
+
+    #[test]
+    fn test_null_clears_value() {
+        let patch: ContactPatch = from_merge_patch(br#"{"phone": null}"#).unwrap();
+
+        assert_eq!(patch.phone, Patch::Null);
+        assert_eq!(patch.phone.apply(Some("0981123456".to_string())), None);
+    }
+
+    #[test]
+    fn test_absent_field_is_untouched() {
+        let patch: ContactPatch = from_merge_patch(br#"{"full_name": "Ana"}"#).unwrap();
+
+        assert_eq!(patch.full_name.as_deref(), Some("Ana"));
+        assert!(patch.phone.is_undefined());
+        assert_eq!(
+            patch.phone.apply(Some("0981123456".to_string())),
+            Some("0981123456".to_string())
+        );
+    }
+
+    #[test]
+    fn test_value_replaces_current() {
+        let patch: ContactPatch = from_merge_patch(br#"{"phone": "0991000000"}"#).unwrap();
+
+        assert_eq!(patch.phone.apply(None), Some("0991000000".to_string()));
+    }
+
+    #[test]
+    fn test_null_on_required_field_is_rejected() {
+        let result: Result<ContactPatch, _> = from_merge_patch(br#"{"full_name": null}"#);
+
+        assert!(result.unwrap_err().is_data());
+    }
+
+    #[test]
+    fn test_type_error_is_reported() {
+        let result: Result<ContactPatch, _> = from_merge_patch(br#"{"phone": 123}"#);
+
+        assert!(result.unwrap_err().is_data());
+    }
+}
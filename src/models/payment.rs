@@ -0,0 +1,452 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{postgres::PgRow, Error as SqlxError, PgPool, Postgres, Row, Transaction};
+use uuid::Uuid;
+
+use crate::db::{DbError, FieldError};
+use crate::models::{Payment, PaymentStatus};
+use crate::utils::validation::validate_ruc;
+
+/// Recargo mensual por mora aplicado sobre el monto adeudado (2%)
+pub const LATE_FEE_MONTHLY_RATE: f64 = 0.02;
+
+/// Concepto con el que se registran los recargos por mora
+pub const LATE_FEE_CONCEPT: &str = "Recargo por mora";
+
+impl PaymentStatus {
+    /// Representación del estado tal como se almacena en la base de datos
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PaymentStatus::Pending => "pending",
+            PaymentStatus::Completed => "completed",
+            PaymentStatus::Cancelled => "cancelled",
+            PaymentStatus::Refunded => "refunded",
+            PaymentStatus::Overdue => "overdue",
+        }
+    }
+
+    /// Convierte el valor almacenado en la base de datos en un estado
+    pub fn from_db(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(PaymentStatus::Pending),
+            "completed" => Some(PaymentStatus::Completed),
+            "cancelled" => Some(PaymentStatus::Cancelled),
+            "refunded" => Some(PaymentStatus::Refunded),
+            "overdue" => Some(PaymentStatus::Overdue),
+            _ => None,
+        }
+    }
+}
+
+/// Datos para registrar un pago nuevo
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct NewPayment {
+    /// Estudiante al que corresponde el pago
+    pub student_id: Uuid,
+    /// Concepto del pago (matrícula, mensualidad, etc.)
+    pub concept: String,
+    /// Monto del pago
+    pub amount: f64,
+    /// Moneda; guaraníes si no se indica
+    pub currency: Option<String>,
+    /// Fecha del pago o de vencimiento
+    pub payment_date: DateTime<Utc>,
+    /// Método de pago (efectivo, transferencia, etc.)
+    pub payment_method: String,
+    /// Estado inicial; pendiente si no se indica
+    pub status: Option<PaymentStatus>,
+    /// Número de comprobante o factura
+    pub receipt_number: Option<String>,
+    /// RUC del pagador, si pidió factura
+    #[serde(default)]
+    pub payer_ruc: Option<String>,
+    /// Notas adicionales
+    pub notes: Option<String>,
+}
+
+impl NewPayment {
+    /// Verifica el pago y devuelve un error por cada campo inválido
+    ///
+    /// El RUC del pagador, si se indica, debe tener un dígito verificador válido.
+    pub fn validate(&self) -> Result<(), DbError> {
+        let mut errors = Vec::new();
+
+        if self.concept.trim().is_empty() {
+            errors.push(FieldError::new("concept", "must not be empty"));
+        }
+        check_amount(self.amount, &mut errors);
+        check_payer_ruc(self.payer_ruc.as_deref(), &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DbError::Invalid(errors))
+        }
+    }
+}
+
+/// Campos a modificar de un pago; los que no se indican se conservan
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct UpdatePaymentDto {
+    pub concept: Option<String>,
+    pub amount: Option<f64>,
+    pub payment_date: Option<DateTime<Utc>>,
+    pub payment_method: Option<String>,
+    pub status: Option<PaymentStatus>,
+    pub receipt_number: Option<String>,
+    pub payer_ruc: Option<String>,
+    pub notes: Option<String>,
+}
+
+impl UpdatePaymentDto {
+    /// Verifica los campos indicados, con las mismas reglas que [`NewPayment::validate`]
+    pub fn validate(&self) -> Result<(), DbError> {
+        let mut errors = Vec::new();
+
+        if self.concept.as_ref().is_some_and(|concept| concept.trim().is_empty()) {
+            errors.push(FieldError::new("concept", "must not be empty"));
+        }
+        if let Some(amount) = self.amount {
+            check_amount(amount, &mut errors);
+        }
+        check_payer_ruc(self.payer_ruc.as_deref(), &mut errors);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(DbError::Invalid(errors))
+        }
+    }
+}
+
+fn check_amount(amount: f64, errors: &mut Vec<FieldError>) {
+    if !amount.is_finite() || amount < 0.0 {
+        errors.push(FieldError::new("amount", "must be zero or more"));
+    }
+}
+
+fn check_payer_ruc(ruc: Option<&str>, errors: &mut Vec<FieldError>) {
+    if ruc.is_some_and(|ruc| !validate_ruc(ruc)) {
+        errors.push(FieldError::new("payer_ruc", "is not a valid RUC or its check digit does not match"));
+    }
+}
+
+/// Recargo calculado para un pago vencido, pendiente de registrar
+#[derive(Debug, Clone)]
+pub struct LateFee {
+    /// Pago vencido que origina el recargo
+    pub base_payment_id: Uuid,
+    /// Estudiante al que se cobra el recargo
+    pub student_id: Uuid,
+    /// Monto del recargo
+    pub amount: f64,
+    /// Moneda del pago original
+    pub currency: String,
+}
+
+impl Payment {
+    /// Calcula el recargo por mora de un pago a una fecha de corte
+    ///
+    /// Se cobra `LATE_FEE_MONTHLY_RATE` por cada mes (o fracción) de atraso y
+    /// el resultado se redondea a guaraníes enteros. Un pago que no está vencido
+    /// a la fecha de corte no genera recargo.
+    pub fn calculate_late_fee(&self, cutoff_date: NaiveDate) -> f64 {
+        let days_late = (cutoff_date - self.payment_date.date_naive()).num_days();
+        if days_late <= 0 || self.amount <= 0.0 {
+            return 0.0;
+        }
+
+        let months_late = (days_late + 29) / 30;
+        (self.amount * LATE_FEE_MONTHLY_RATE * months_late as f64).round()
+    }
+
+    /// Registra un pago nuevo
+    ///
+    /// El pago se valida con [`NewPayment::validate`] antes de tocar la base de datos.
+    pub async fn create(pool: &PgPool, new_payment: &NewPayment) -> Result<Payment, DbError> {
+        new_payment.validate()?;
+
+        let row = sqlx::query(
+            r#"
+            INSERT INTO payments (
+                student_id, concept, amount, currency, payment_date, payment_method,
+                status, receipt_number, payer_ruc, notes
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, student_id, concept, amount, currency, payment_date, payment_method,
+                      status, receipt_number, payer_ruc, notes, base_payment_id
+            "#,
+        )
+        .bind(new_payment.student_id)
+        .bind(&new_payment.concept)
+        .bind(new_payment.amount)
+        .bind(new_payment.currency.as_deref().unwrap_or("PYG"))
+        .bind(new_payment.payment_date)
+        .bind(&new_payment.payment_method)
+        .bind(new_payment.status.as_ref().unwrap_or(&PaymentStatus::Pending).as_str())
+        .bind(&new_payment.receipt_number)
+        .bind(&new_payment.payer_ruc)
+        .bind(&new_payment.notes)
+        .fetch_one(pool)
+        .await?;
+
+        Self::from_row(&row)
+    }
+
+    /// Modifica los campos indicados de un pago en una única sentencia
+    ///
+    /// Los cambios se validan con [`UpdatePaymentDto::validate`] antes de tocar
+    /// la base de datos; si el pago no existe devuelve NotFound.
+    pub async fn update(pool: &PgPool, id: Uuid, changes: &UpdatePaymentDto) -> Result<Payment, DbError> {
+        changes.validate()?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE payments
+            SET concept = COALESCE($2, concept),
+                amount = COALESCE($3, amount),
+                payment_date = COALESCE($4, payment_date),
+                payment_method = COALESCE($5, payment_method),
+                status = COALESCE($6, status),
+                receipt_number = COALESCE($7, receipt_number),
+                payer_ruc = COALESCE($8, payer_ruc),
+                notes = COALESCE($9, notes)
+            WHERE id = $1
+            RETURNING id, student_id, concept, amount, currency, payment_date, payment_method,
+                      status, receipt_number, payer_ruc, notes, base_payment_id
+            "#,
+        )
+        .bind(id)
+        .bind(&changes.concept)
+        .bind(changes.amount)
+        .bind(changes.payment_date)
+        .bind(&changes.payment_method)
+        .bind(changes.status.as_ref().map(PaymentStatus::as_str))
+        .bind(&changes.receipt_number)
+        .bind(&changes.payer_ruc)
+        .bind(&changes.notes)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound(format!("Pago con ID {} no encontrado", id)))?;
+
+        Self::from_row(&row)
+    }
+
+    /// Busca un pago por su ID
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Payment>, DbError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, student_id, concept, amount, currency, payment_date, payment_method,
+                   status, receipt_number, payer_ruc, notes, base_payment_id
+            FROM payments
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+        row.map(|row| Self::from_row(&row)).transpose()
+    }
+
+    /// Lista los pagos vencidos antes de la fecha de corte que todavía no
+    /// tienen un recargo por mora registrado
+    pub async fn find_overdue_without_fee(
+        tx: &mut Transaction<'_, Postgres>,
+        cutoff_date: NaiveDate,
+    ) -> Result<Vec<Payment>, DbError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT p.id, p.student_id, p.concept, p.amount, p.currency, p.payment_date,
+                   p.payment_method, p.status, p.receipt_number, p.payer_ruc, p.notes, p.base_payment_id
+            FROM payments p
+            WHERE p.status = 'overdue'
+              AND p.payment_date < $1
+              AND p.base_payment_id IS NULL
+              AND NOT EXISTS (
+                  SELECT 1 FROM payments fee WHERE fee.base_payment_id = p.id
+              )
+            ORDER BY p.payment_date
+            FOR UPDATE OF p
+            "#,
+        )
+        .bind(cutoff_date)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        rows.iter().map(Self::from_row).collect()
+    }
+
+    /// Registra los recargos por mora y marca los pagos originales en lote
+    ///
+    /// Devuelve los recargos efectivamente insertados (pago original y monto);
+    /// si algún pago ya tenía recargo (carrera con otro proceso) se omite sin error.
+    pub async fn insert_late_fees(
+        tx: &mut Transaction<'_, Postgres>,
+        fees: &[LateFee],
+        cutoff_date: NaiveDate,
+    ) -> Result<Vec<(Uuid, f64)>, DbError> {
+        if fees.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let base_ids: Vec<Uuid> = fees.iter().map(|fee| fee.base_payment_id).collect();
+        let student_ids: Vec<Uuid> = fees.iter().map(|fee| fee.student_id).collect();
+        let amounts: Vec<f64> = fees.iter().map(|fee| fee.amount).collect();
+        let currencies: Vec<String> = fees.iter().map(|fee| fee.currency.clone()).collect();
+        let due_date: DateTime<Utc> = cutoff_date
+            .and_hms_opt(0, 0, 0)
+            .map(|dt| DateTime::from_naive_utc_and_offset(dt, Utc))
+            .unwrap_or_else(Utc::now);
+
+        let inserted: Vec<(Uuid, f64)> = sqlx::query_as(
+            r#"
+            INSERT INTO payments (
+                student_id, concept, amount, currency, payment_date,
+                payment_method, status, base_payment_id
+            )
+            SELECT f.student_id, $5, f.amount, f.currency, $6, 'pendiente', 'pending', f.base_payment_id
+            FROM UNNEST($1::uuid[], $2::uuid[], $3::float8[], $4::varchar[])
+                AS f(base_payment_id, student_id, amount, currency)
+            ON CONFLICT (base_payment_id) DO NOTHING
+            RETURNING base_payment_id, amount
+            "#,
+        )
+        .bind(&base_ids)
+        .bind(&student_ids)
+        .bind(&amounts)
+        .bind(&currencies)
+        .bind(LATE_FEE_CONCEPT)
+        .bind(due_date)
+        .fetch_all(&mut **tx)
+        .await?;
+
+        let applied_ids: Vec<Uuid> = inserted.iter().map(|(id, _)| *id).collect();
+
+        sqlx::query(
+            r#"
+            UPDATE payments
+            SET late_fee_applied_on = $2
+            FROM UNNEST($1::uuid[]) AS u(id)
+            WHERE payments.id = u.id
+            "#,
+        )
+        .bind(&applied_ids)
+        .bind(cutoff_date)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(inserted)
+    }
+
+    fn from_row(row: &PgRow) -> Result<Payment, DbError> {
+        let status: String = row.try_get("status")?;
+
+        Ok(Payment {
+            id: row.try_get("id")?,
+            student_id: row.try_get("student_id")?,
+            concept: row.try_get("concept")?,
+            amount: row.try_get("amount")?,
+            currency: row.try_get("currency")?,
+            payment_date: row.try_get("payment_date")?,
+            payment_method: row.try_get("payment_method")?,
+            status: PaymentStatus::from_db(&status).ok_or_else(|| SqlxError::ColumnDecode {
+                index: "status".to_string(),
+                source: format!("estado de pago desconocido: {}", status).into(),
+            })?,
+            receipt_number: row.try_get("receipt_number")?,
+            payer_ruc: row.try_get("payer_ruc")?,
+            notes: row.try_get("notes")?,
+            base_payment_id: row.try_get("base_payment_id")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn overdue_payment(amount: f64, due: NaiveDate) -> Payment {
+        Payment {
+            id: Uuid::new_v4(),
+            student_id: Uuid::new_v4(),
+            concept: "Cuota mensual".to_string(),
+            amount,
+            currency: "PYG".to_string(),
+            payment_date: Utc.from_utc_datetime(&due.and_hms_opt(0, 0, 0).unwrap()),
+            payment_method: "efectivo".to_string(),
+            status: PaymentStatus::Overdue,
+            receipt_number: None,
+            payer_ruc: None,
+            notes: None,
+            base_payment_id: None,
+        }
+    }
+
+    #[test]
+    fn test_late_fee_one_month() {
+        let payment = overdue_payment(500_000.0, NaiveDate::from_ymd_opt(2024, 5, 10).unwrap());
+
+        let fee = payment.calculate_late_fee(NaiveDate::from_ymd_opt(2024, 5, 31).unwrap());
+
+        assert_eq!(fee, 10_000.0);
+    }
+
+    #[test]
+    fn test_late_fee_counts_partial_months() {
+        let payment = overdue_payment(500_000.0, NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+
+        let fee = payment.calculate_late_fee(NaiveDate::from_ymd_opt(2024, 5, 31).unwrap());
+
+        // 61 días de atraso: 3 meses o fracción
+        assert_eq!(fee, 30_000.0);
+    }
+
+    #[test]
+    fn test_no_late_fee_before_due_date() {
+        let payment = overdue_payment(500_000.0, NaiveDate::from_ymd_opt(2024, 6, 10).unwrap());
+
+        assert_eq!(payment.calculate_late_fee(NaiveDate::from_ymd_opt(2024, 5, 31).unwrap()), 0.0);
+    }
+
+    fn new_payment(payer_ruc: Option<&str>) -> NewPayment {
+        NewPayment {
+            student_id: Uuid::new_v4(),
+            concept: "Cuota mensual".to_string(),
+            amount: 350_000.0,
+            currency: None,
+            payment_date: Utc::now(),
+            payment_method: "efectivo".to_string(),
+            status: None,
+            receipt_number: None,
+            payer_ruc: payer_ruc.map(str::to_string),
+            notes: None,
+        }
+    }
+
+    #[test]
+    fn test_payer_ruc_check_digit() {
+        assert!(new_payment(None).validate().is_ok());
+        assert!(new_payment(Some("80.012.345-0")).validate().is_ok());
+
+        match new_payment(Some("80012345-1")).validate() {
+            Err(DbError::Invalid(errors)) => assert_eq!(errors[0].field, "payer_ruc"),
+            other => panic!("expected an invalid payer_ruc, got {:?}", other),
+        }
+        let update = UpdatePaymentDto { payer_ruc: Some("1234567-0".to_string()), ..Default::default() };
+        assert!(matches!(update.validate(), Err(DbError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_status_round_trip() {
+        for status in [
+            PaymentStatus::Pending,
+            PaymentStatus::Completed,
+            PaymentStatus::Cancelled,
+            PaymentStatus::Refunded,
+            PaymentStatus::Overdue,
+        ] {
+            assert_eq!(PaymentStatus::from_db(status.as_str()), Some(status));
+        }
+    }
+}
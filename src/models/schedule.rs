@@ -0,0 +1,72 @@
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Aula disponible para los horarios de los cursos
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Classroom {
+    /// Identificador único del aula
+    pub id: Uuid,
+    /// Nombre del aula, tal como aparece en `ScheduleSlot::classroom`
+    pub name: String,
+    /// Cantidad máxima de estudiantes
+    pub capacity: i32,
+}
+
+/// Franja semanal en la que un profesor puede dictar clases
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TeacherAvailability {
+    /// Profesor (ID de usuario)
+    pub teacher_id: Uuid,
+    /// Día de la semana (1-7, donde 1 es lunes)
+    pub day_of_week: i16,
+    /// Hora de inicio
+    pub start_time: NaiveTime,
+    /// Hora de finalización
+    pub end_time: NaiveTime,
+}
+
+impl Classroom {
+    /// Lista todas las aulas, de menor a mayor capacidad
+    pub async fn find_all(pool: &PgPool) -> Result<Vec<Classroom>, DbError> {
+        sqlx::query_as::<_, Classroom>(
+            "SELECT id, name, capacity FROM classrooms ORDER BY capacity, name",
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Busca un aula por su ID
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Classroom>, DbError> {
+        sqlx::query_as::<_, Classroom>("SELECT id, name, capacity FROM classrooms WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .map_err(DbError::from)
+    }
+}
+
+impl TeacherAvailability {
+    /// Lista las franjas de disponibilidad de todos los profesores
+    pub async fn find_all(pool: &PgPool) -> Result<Vec<TeacherAvailability>, DbError> {
+        sqlx::query_as::<_, TeacherAvailability>(
+            r#"
+            SELECT teacher_id, day_of_week, start_time, end_time
+            FROM teacher_availability
+            ORDER BY teacher_id, day_of_week, start_time
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Indica si la franja cubre por completo un bloque horario
+    pub fn covers(&self, day_of_week: u8, start: NaiveTime, end: NaiveTime) -> bool {
+        self.day_of_week == day_of_week as i16 && self.start_time <= start && end <= self.end_time
+    }
+}
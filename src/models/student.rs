@@ -1,9 +1,10 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool, Error as SqlxError, postgres::PgQueryResult};
+use sqlx::{FromRow, PgPool, Postgres, QueryBuilder, Row, Transaction, postgres::PgQueryResult, types::Json};
 use uuid::Uuid;
 
-use crate::models::{GuardianInfo, StudentStatus, Role, User};
+use crate::db::{metrics, DbError};
+use crate::models::{patch::required, GuardianInfo, Patch, StudentStatus, Role, User};
 
 /// Re-exportamos Student para facilitar su uso en el módulo models
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -47,6 +48,23 @@ pub struct UpdateStudentDto {
     pub status: Option<StudentStatus>,
 }
 
+/// DTO para la actualización parcial (merge-patch) de un estudiante
+#[derive(Debug, Deserialize)]
+pub struct PatchStudentDto {
+    #[serde(default, deserialize_with = "required")]
+    pub enrollment_number: Option<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub current_grade: Option<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub section: Option<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub academic_year: Option<i32>,
+    #[serde(default)]
+    pub guardian_info: Patch<GuardianInfo>,
+    #[serde(default, deserialize_with = "required")]
+    pub status: Option<StudentStatus>,
+}
+
 /// DTO para crear un estudiante junto con sus datos de usuario
 #[derive(Debug, Deserialize)]
 pub struct CreateStudentWithUserDto {
@@ -68,7 +86,7 @@ pub struct CreateStudentWithUserDto {
 }
 
 /// Filtros para la búsqueda de estudiantes
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default)]
 pub struct StudentFilter {
     pub user_id: Option<Uuid>,
     pub enrollment_number: Option<String>,
@@ -79,13 +97,46 @@ pub struct StudentFilter {
     pub guardian_name: Option<String>,
 }
 
+/// Valor de la columna `guardian_info`; un estudiante sin tutor se guarda como SQL NULL
+fn guardian_json(guardian_info: &Option<GuardianInfo>) -> Result<Option<serde_json::Value>, DbError> {
+    guardian_info.as_ref().map(serde_json::to_value).transpose().map_err(DbError::from)
+}
+
+const STUDENT_COLUMNS: &str =
+    "user_id, enrollment_number, current_grade, section, academic_year, guardian_info, status";
+
+/// Agrega las condiciones de un filtro de estudiantes a una consulta que ya tiene `WHERE`
+fn push_student_filters(query: &mut QueryBuilder<'_, Postgres>, filter: &StudentFilter) {
+    if let Some(user_id) = filter.user_id {
+        query.push(" AND user_id = ").push_bind(user_id);
+    }
+    if let Some(enrollment_number) = &filter.enrollment_number {
+        query.push(" AND enrollment_number = ").push_bind(enrollment_number.clone());
+    }
+    if let Some(current_grade) = &filter.current_grade {
+        query.push(" AND current_grade = ").push_bind(current_grade.clone());
+    }
+    if let Some(section) = &filter.section {
+        query.push(" AND section = ").push_bind(section.clone());
+    }
+    if let Some(academic_year) = filter.academic_year {
+        query.push(" AND academic_year = ").push_bind(academic_year);
+    }
+    if let Some(status) = &filter.status {
+        query.push(" AND status = ").push_bind(status.clone());
+    }
+    if let Some(guardian_name) = &filter.guardian_name {
+        query.push(" AND guardian_info->>'name' ILIKE ").push_bind(format!("%{}%", guardian_name));
+    }
+}
+
 impl Student {
     /// Crea un nuevo estudiante en la base de datos
-    pub async fn create(pool: &PgPool, dto: CreateStudentDto) -> Result<Student, SqlxError> {
+    pub async fn create(pool: &PgPool, dto: CreateStudentDto) -> Result<Student, DbError> {
         // Verificar que el usuario exista antes de crear el estudiante
         let user_exists = User::find_by_id(pool, dto.user_id).await?;
         if user_exists.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Usuario no encontrado".to_string()));
         }
 
         let student = sqlx::query_as!(
@@ -98,7 +149,7 @@ impl Student {
             VALUES ($1, $2, $3, $4, $5, $6, $7)
             RETURNING 
                 user_id, enrollment_number, current_grade, section, 
-                academic_year, guardian_info as "guardian_info: Option<GuardianInfo>", 
+                academic_year, guardian_info as "guardian_info: GuardianInfo", 
                 status as "status: StudentStatus"
             "#,
             dto.user_id,
@@ -106,7 +157,7 @@ impl Student {
             dto.current_grade,
             dto.section,
             dto.academic_year,
-            serde_json::to_value(&dto.guardian_info)?,
+            guardian_json(&dto.guardian_info)?,
             dto.status as StudentStatus
         )
         .fetch_one(pool)
@@ -119,72 +170,70 @@ impl Student {
     pub async fn create_with_user(
         pool: &PgPool, 
         dto: CreateStudentWithUserDto
-    ) -> Result<(User, Student), SqlxError> {
-        // Iniciar transacción para garantizar atomicidad
-        let mut tx = pool.begin().await?;
-
-        // Crear el usuario primero
-        let user_dto = crate::models::user::CreateUserDto {
-            document_id: dto.document_id,
-            full_name: dto.full_name,
-            email: dto.email,
-            phone: dto.phone,
-            address: dto.address,
-            birth_date: dto.birth_date,
-            role: Role::Student, // Asignamos automáticamente el rol de estudiante
-        };
-
-        let user = User::create(&mut tx, user_dto).await?;
-
-        // Crear el estudiante usando el ID del usuario recién creado
-        let student_dto = CreateStudentDto {
-            user_id: user.id,
-            enrollment_number: dto.enrollment_number,
-            current_grade: dto.current_grade,
-            section: dto.section,
-            academic_year: dto.academic_year,
-            guardian_info: dto.guardian_info,
-            status: dto.status,
-        };
-
-        let student = sqlx::query_as!(
-            Student,
-            r#"
-            INSERT INTO students (
-                user_id, enrollment_number, current_grade, section, 
-                academic_year, guardian_info, status
+    ) -> Result<(User, Student), DbError> {
+        // Usuario y estudiante se crean en una misma transacción
+        crate::tx!(pool, |tx| {
+            // Crear el usuario primero
+            let user_dto = crate::models::user::CreateUserDto {
+                document_id: dto.document_id,
+                full_name: dto.full_name,
+                email: dto.email,
+                phone: dto.phone,
+                address: dto.address,
+                birth_date: dto.birth_date,
+                role: Role::Student, // Asignamos automáticamente el rol de estudiante
+            };
+
+            let user = User::create(&mut **tx, user_dto).await?;
+
+            // Crear el estudiante usando el ID del usuario recién creado
+            let student_dto = CreateStudentDto {
+                user_id: user.id,
+                enrollment_number: dto.enrollment_number,
+                current_grade: dto.current_grade,
+                section: dto.section,
+                academic_year: dto.academic_year,
+                guardian_info: dto.guardian_info,
+                status: dto.status,
+            };
+
+            let student = sqlx::query_as!(
+                Student,
+                r#"
+                INSERT INTO students (
+                    user_id, enrollment_number, current_grade, section, 
+                    academic_year, guardian_info, status
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING 
+                    user_id, enrollment_number, current_grade, section, 
+                    academic_year, guardian_info as "guardian_info: GuardianInfo", 
+                    status as "status: StudentStatus"
+                "#,
+                student_dto.user_id,
+                student_dto.enrollment_number,
+                student_dto.current_grade,
+                student_dto.section,
+                student_dto.academic_year,
+                guardian_json(&student_dto.guardian_info)?,
+                student_dto.status as StudentStatus
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            RETURNING 
-                user_id, enrollment_number, current_grade, section, 
-                academic_year, guardian_info as "guardian_info: Option<GuardianInfo>", 
-                status as "status: StudentStatus"
-            "#,
-            student_dto.user_id,
-            student_dto.enrollment_number,
-            student_dto.current_grade,
-            student_dto.section,
-            student_dto.academic_year,
-            serde_json::to_value(&student_dto.guardian_info)?,
-            student_dto.status as StudentStatus
-        )
-        .fetch_one(&mut tx)
-        .await?;
-
-        // Confirmar la transacción
-        tx.commit().await?;
+            .fetch_one(&mut **tx)
+            .await?;
 
-        Ok((user, student))
+            Ok::<_, DbError>((user, student))
+        })
+        .await
     }
 
     /// Encuentra un estudiante por el ID de usuario
-    pub async fn find_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Option<Student>, SqlxError> {
+    pub async fn find_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Option<Student>, DbError> {
         let student = sqlx::query_as!(
             Student,
             r#"
             SELECT 
                 user_id, enrollment_number, current_grade, section, 
-                academic_year, guardian_info as "guardian_info: Option<GuardianInfo>", 
+                academic_year, guardian_info as "guardian_info: GuardianInfo", 
                 status as "status: StudentStatus"
             FROM students
             WHERE user_id = $1
@@ -198,13 +247,13 @@ impl Student {
     }
 
     /// Encuentra un estudiante por su número de matrícula
-    pub async fn find_by_enrollment_number(pool: &PgPool, enrollment_number: &str) -> Result<Option<Student>, SqlxError> {
+    pub async fn find_by_enrollment_number(pool: &PgPool, enrollment_number: &str) -> Result<Option<Student>, DbError> {
         let student = sqlx::query_as!(
             Student,
             r#"
             SELECT 
                 user_id, enrollment_number, current_grade, section, 
-                academic_year, guardian_info as "guardian_info: Option<GuardianInfo>", 
+                academic_year, guardian_info as "guardian_info: GuardianInfo", 
                 status as "status: StudentStatus"
             FROM students
             WHERE enrollment_number = $1
@@ -218,111 +267,65 @@ impl Student {
     }
 
     /// Lista todos los estudiantes con opción de filtrado y paginación
+    ///
+    /// Un `guardian_info` que no se puede decodificar devuelve
+    /// `DbError::Serialization` en lugar de tratarse como estudiante sin tutor.
     pub async fn find_all(
         pool: &PgPool, 
         filter: StudentFilter,
         limit: Option<i64>,
         offset: Option<i64>
-    ) -> Result<Vec<Student>, SqlxError> {
-        // Construimos la consulta base
-        let mut query = String::from(
-            "SELECT user_id, enrollment_number, current_grade, section, 
-                    academic_year, guardian_info, status 
-             FROM students WHERE 1=1"
-        );
-
-        // Aplicamos los filtros si existen
-        let mut params = Vec::<String>::new();
-        let mut param_count = 1;
-
-        if let Some(user_id) = filter.user_id {
-            query.push_str(&format!(" AND user_id = ${}", param_count));
-            params.push(user_id.to_string());
-            param_count += 1;
-        }
+    ) -> Result<Vec<Student>, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM students WHERE 1=1", STUDENT_COLUMNS));
+        push_student_filters(&mut query, &filter);
 
-        if let Some(enrollment_number) = &filter.enrollment_number {
-            query.push_str(&format!(" AND enrollment_number = ${}", param_count));
-            params.push(enrollment_number.to_string());
-            param_count += 1;
+        query.push(" ORDER BY current_grade, section, enrollment_number");
+        if let Some(limit) = limit {
+            query.push(" LIMIT ").push_bind(limit);
         }
-
-        if let Some(current_grade) = &filter.current_grade {
-            query.push_str(&format!(" AND current_grade = ${}", param_count));
-            params.push(current_grade.to_string());
-            param_count += 1;
-        }
-
-        if let Some(section) = &filter.section {
-            query.push_str(&format!(" AND section = ${}", param_count));
-            params.push(section.to_string());
-            param_count += 1;
-        }
-
-        if let Some(academic_year) = filter.academic_year {
-            query.push_str(&format!(" AND academic_year = ${}", param_count));
-            params.push(academic_year.to_string());
-            param_count += 1;
-        }
-
-        if let Some(status) = &filter.status {
-            query.push_str(&format!(" AND status = ${}", param_count));
-            params.push(format!("{:?}", status));
-            param_count += 1;
-        }
-
-        if let Some(guardian_name) = &filter.guardian_name {
-            query.push_str(&format!(" AND guardian_info->>'name' ILIKE ${}", param_count));
-            params.push(format!("%{}%", guardian_name));
-            param_count += 1;
-        }
-
-        // Agregamos ordenamiento y paginación
-        query.push_str(" ORDER BY current_grade, section, enrollment_number");
-
-        if let Some(limit_val) = limit {
-            query.push_str(&format!(" LIMIT ${}", param_count));
-            params.push(limit_val.to_string());
-            param_count += 1;
-        }
-
-        if let Some(offset_val) = offset {
-            query.push_str(&format!(" OFFSET ${}", param_count));
-            params.push(offset_val.to_string());
-        }
-
-        // Ejecutamos la consulta dinámica
-        let mut q = sqlx::query(&query);
-        for param in params {
-            q = q.bind(param);
+        if let Some(offset) = offset {
+            query.push(" OFFSET ").push_bind(offset);
         }
 
         // Convertimos el resultado a instancias de Student
-        let rows = q.fetch_all(pool).await?;
-        let students = rows
-            .iter()
+        let sql = query.sql().to_owned();
+        let rows = metrics::timed_query("students.find_all", &sql, query.build().fetch_all(pool)).await?;
+        rows.iter()
             .map(|row| {
-                Student {
-                    user_id: row.get("user_id"),
-                    enrollment_number: row.get("enrollment_number"),
-                    current_grade: row.get("current_grade"),
-                    section: row.get("section"),
-                    academic_year: row.get("academic_year"),
-                    guardian_info: serde_json::from_value(row.get("guardian_info")).unwrap_or(None),
-                    status: serde_json::from_value(row.get("status")).unwrap_or(StudentStatus::Active),
-                }
+                Ok(Student {
+                    user_id: row.try_get("user_id")?,
+                    enrollment_number: row.try_get("enrollment_number")?,
+                    current_grade: row.try_get("current_grade")?,
+                    section: row.try_get("section")?,
+                    academic_year: row.try_get("academic_year")?,
+                    guardian_info: row
+                        .try_get::<Option<Json<GuardianInfo>>, _>("guardian_info")?
+                        .map(|json| json.0),
+                    status: row.try_get("status")?,
+                })
             })
-            .collect();
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(DbError::from)
+    }
 
-        Ok(students)
+    /// Cuenta los estudiantes que coinciden con el filtro
+    ///
+    /// Usa las mismas condiciones que `find_all`, así el total coincide con
+    /// las filas listadas.
+    pub async fn count(pool: &PgPool, filter: &StudentFilter) -> Result<i64, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM students WHERE 1=1");
+        push_student_filters(&mut query, filter);
+
+        query.build_query_scalar::<i64>().fetch_one(pool).await
+        .map_err(DbError::from)
     }
 
     /// Actualiza un estudiante existente
-    pub async fn update(pool: &PgPool, user_id: Uuid, dto: UpdateStudentDto) -> Result<Student, SqlxError> {
+    pub async fn update(pool: &PgPool, user_id: Uuid, dto: UpdateStudentDto) -> Result<Student, DbError> {
         // Primero verificamos si el estudiante existe
         let existing_student = Self::find_by_user_id(pool, user_id).await?;
         if existing_student.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Estudiante no encontrado".to_string()));
         }
 
         let existing_student = existing_student.unwrap();
@@ -344,14 +347,14 @@ impl Student {
             WHERE user_id = $7
             RETURNING 
                 user_id, enrollment_number, current_grade, section, 
-                academic_year, guardian_info as "guardian_info: Option<GuardianInfo>", 
+                academic_year, guardian_info as "guardian_info: GuardianInfo", 
                 status as "status: StudentStatus"
             "#,
             enrollment_number,
             current_grade,
             section,
             academic_year,
-            serde_json::to_value(&guardian_info)?,
+            guardian_json(&guardian_info)?,
             status as StudentStatus,
             user_id
         )
@@ -361,17 +364,103 @@ impl Student {
         Ok(updated_student)
     }
 
+    /// Aplica una actualización parcial (merge-patch) a un estudiante existente
+    ///
+    /// El cambio se resuelve en un único `UPDATE`, de modo que dos parches
+    /// concurrentes sobre campos distintos no se pisan entre sí.
+    pub async fn patch(pool: &PgPool, user_id: Uuid, dto: PatchStudentDto) -> Result<Student, DbError> {
+        // Los campos ausentes conservan su valor; `null` limpia guardian_info
+        let guardian_info_sent = !dto.guardian_info.is_undefined();
+        let guardian_info = guardian_json(&dto.guardian_info.into_option())?;
+
+        let patched_student = sqlx::query_as!(
+            Student,
+            r#"
+            UPDATE students 
+            SET enrollment_number = COALESCE($1, enrollment_number),
+                current_grade = COALESCE($2, current_grade),
+                section = COALESCE($3, section),
+                academic_year = COALESCE($4, academic_year),
+                guardian_info = CASE WHEN $5 THEN $6 ELSE guardian_info END,
+                status = COALESCE($7, status)
+            WHERE user_id = $8
+            RETURNING 
+                user_id, enrollment_number, current_grade, section, 
+                academic_year, guardian_info as "guardian_info: GuardianInfo", 
+                status as "status: StudentStatus"
+            "#,
+            dto.enrollment_number,
+            dto.current_grade,
+            dto.section,
+            dto.academic_year,
+            guardian_info_sent,
+            guardian_info,
+            dto.status as Option<StudentStatus>,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound("Estudiante no encontrado".to_string()))?;
+
+        Ok(patched_student)
+    }
+
+    /// Bloquea y devuelve los IDs de usuario que existen como estudiantes dentro de una transacción
+    pub async fn lock_existing(
+        tx: &mut Transaction<'_, Postgres>,
+        user_ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, DbError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT user_id FROM students
+            WHERE user_id = ANY($1)
+            FOR UPDATE
+            "#,
+            user_ids
+        )
+        .fetch_all(&mut **tx)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| row.user_id).collect())
+    }
+
+    /// Da de baja (borrado lógico) a varios estudiantes dentro de una transacción
+    pub async fn withdraw_many(
+        tx: &mut Transaction<'_, Postgres>,
+        user_ids: &[Uuid],
+    ) -> Result<u64, DbError> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE students
+            SET status = 'withdrawn'
+            WHERE user_id = ANY($1)
+            "#,
+            user_ids
+        )
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
     /// Elimina un estudiante por su ID de usuario
-    pub async fn delete(pool: &PgPool, user_id: Uuid) -> Result<PgQueryResult, SqlxError> {
+    pub async fn delete(pool: &PgPool, user_id: Uuid) -> Result<PgQueryResult, DbError> {
         // Verificamos si el estudiante existe
         let existing_student = Self::find_by_user_id(pool, user_id).await?;
         if existing_student.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Estudiante no encontrado".to_string()));
         }
 
         let result = sqlx::query!(
             r#"
             DELETE FROM students
             WHERE user_id = $1
-            "#
+            "#,
+            user_id
+        )
+        .execute(pool)
+        .await?;
 
+        Ok(result)
+    }
+}
@@ -0,0 +1,178 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, PgPool};
+use uuid::Uuid;
+
+use crate::db::DbError;
+
+/// Matrícula de un estudiante en un año académico
+///
+/// Guarda el grado y la sección de cada año, de modo que las estadísticas de
+/// años anteriores no dependan de los datos actuales del estudiante.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StudentYearRecord {
+    /// Identificador único
+    pub id: Uuid,
+    /// Estudiante (ID de usuario)
+    pub student_id: Uuid,
+    /// Año académico
+    pub academic_year: i32,
+    /// Grado cursado en el año
+    pub grade: String,
+    /// Sección o división
+    pub section: String,
+    /// Situación en el año: active, withdrawn, promoted, repeated o graduated
+    pub status: String,
+    /// Fecha de matriculación
+    pub enrolled_on: NaiveDate,
+    /// Fecha de retiro, si se retiró durante el año
+    pub withdrawn_on: Option<NaiveDate>,
+    /// Motivo del retiro
+    pub withdrawal_reason: Option<String>,
+    /// Fecha de creación del registro
+    pub created_at: DateTime<Utc>,
+    /// Última actualización del registro
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Matrícula de un grado y sección, agregada para las estadísticas
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct SectionCountRow {
+    pub grade: String,
+    pub section: String,
+    /// Estudiantes matriculados en el año (incluye retirados)
+    pub total: i64,
+    pub withdrawn: i64,
+    /// Estudiantes sin matrícula el año anterior
+    pub new_students: i64,
+    /// Estudiantes con matrícula el año anterior
+    pub returning_students: i64,
+    /// Matriculados el año anterior en el mismo grado y sección, a la misma fecha
+    pub previous_total: i64,
+}
+
+/// Cantidad de retiros por motivo
+#[derive(Debug, Clone, PartialEq, Serialize, FromRow)]
+pub struct WithdrawalReasonCount {
+    pub reason: String,
+    pub count: i64,
+}
+
+/// Inscripciones a cursos de un año, por estado
+#[derive(Debug, Clone, Default, PartialEq, Serialize, FromRow)]
+pub struct CourseEnrollmentCounts {
+    /// Inscripciones vigentes o finalizadas
+    pub active: i64,
+    /// Inscripciones retiradas
+    pub withdrawn: i64,
+}
+
+impl StudentYearRecord {
+    /// Matrícula por grado y sección de un año, hasta una fecha
+    ///
+    /// Incluye los grados y secciones que tuvieron estudiantes este año o el
+    /// anterior, de modo que una sección que quedó vacía figura con 0. El año
+    /// anterior se cuenta hasta la misma fecha un año antes, para comparar un
+    /// año en curso con el mismo momento del año pasado.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - Pool de conexiones a la base de datos
+    /// * `academic_year` - Año académico
+    /// * `as_of` - Fecha de corte (inclusiva)
+    pub async fn section_counts(
+        pool: &PgPool,
+        academic_year: i32,
+        as_of: NaiveDate,
+    ) -> Result<Vec<SectionCountRow>, DbError> {
+        sqlx::query_as::<_, SectionCountRow>(
+            r#"
+            WITH sections AS (
+                SELECT DISTINCT grade, section
+                FROM student_year_records
+                WHERE academic_year IN ($1, $1 - 1)
+            ),
+            current_year AS (
+                SELECT r.grade, r.section, r.status, r.withdrawn_on,
+                       EXISTS (
+                           SELECT 1 FROM student_year_records p
+                           WHERE p.student_id = r.student_id AND p.academic_year = $1 - 1
+                       ) AS is_returning
+                FROM student_year_records r
+                WHERE r.academic_year = $1 AND r.enrolled_on <= $2
+            ),
+            previous_year AS (
+                SELECT grade, section, COUNT(*) AS total
+                FROM student_year_records
+                WHERE academic_year = $1 - 1
+                  AND enrolled_on <= ($2 - INTERVAL '1 year')::date
+                GROUP BY grade, section
+            )
+            SELECT s.grade, s.section,
+                   COUNT(c.status) AS total,
+                   COUNT(c.status) FILTER (WHERE c.status = 'withdrawn' AND c.withdrawn_on <= $2) AS withdrawn,
+                   COUNT(c.status) FILTER (WHERE NOT c.is_returning) AS new_students,
+                   COUNT(c.status) FILTER (WHERE c.is_returning) AS returning_students,
+                   COALESCE(MAX(p.total), 0) AS previous_total
+            FROM sections s
+            LEFT JOIN current_year c ON c.grade = s.grade AND c.section = s.section
+            LEFT JOIN previous_year p ON p.grade = s.grade AND p.section = s.section
+            GROUP BY s.grade, s.section
+            ORDER BY s.grade, s.section
+            "#,
+        )
+        .bind(academic_year)
+        .bind(as_of)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Retiros de un año agrupados por motivo, de mayor a menor
+    ///
+    /// Los retiros sin motivo se agrupan como "Sin especificar".
+    pub async fn withdrawal_reasons(
+        pool: &PgPool,
+        academic_year: i32,
+        as_of: NaiveDate,
+    ) -> Result<Vec<WithdrawalReasonCount>, DbError> {
+        sqlx::query_as::<_, WithdrawalReasonCount>(
+            r#"
+            SELECT COALESCE(NULLIF(TRIM(withdrawal_reason), ''), 'Sin especificar') AS reason,
+                   COUNT(*) AS count
+            FROM student_year_records
+            WHERE academic_year = $1
+              AND status = 'withdrawn'
+              AND withdrawn_on <= $2
+            GROUP BY 1
+            ORDER BY count DESC, reason
+            "#,
+        )
+        .bind(academic_year)
+        .bind(as_of)
+        .fetch_all(pool)
+        .await
+        .map_err(DbError::from)
+    }
+
+    /// Inscripciones a cursos de un año, hasta una fecha
+    pub async fn course_enrollment_counts(
+        pool: &PgPool,
+        academic_year: i32,
+        as_of: NaiveDate,
+    ) -> Result<CourseEnrollmentCounts, DbError> {
+        sqlx::query_as::<_, CourseEnrollmentCounts>(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE status <> 'withdrawn') AS active,
+                   COUNT(*) FILTER (WHERE status = 'withdrawn') AS withdrawn
+            FROM enrollments
+            WHERE academic_year = $1 AND enrollment_date::date <= $2
+            "#,
+        )
+        .bind(academic_year)
+        .bind(as_of)
+        .fetch_one(pool)
+        .await
+        .map_err(DbError::from)
+    }
+}
@@ -1,9 +1,10 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool, Error as SqlxError, postgres::PgQueryResult};
+use sqlx::{FromRow, PgExecutor, PgPool, Postgres, QueryBuilder, Row, postgres::PgQueryResult, types::Json};
 use uuid::Uuid;
 
-use crate::models::{TeacherStatus, User};
+use crate::db::{metrics, DbError};
+use crate::models::{JsonColumn, Role, TeacherStatus, User};
 
 /// Re-exportamos Teacher para facilitar su uso en el módulo models
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -51,8 +52,28 @@ pub struct UpdateTeacherDto {
     pub status: Option<TeacherStatus>,
 }
 
+/// DTO para crear un profesor junto con sus datos de usuario
+#[derive(Debug, Deserialize)]
+pub struct CreateTeacherWithUserDto {
+    // Datos del usuario
+    pub document_id: String,
+    pub full_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+    pub birth_date: NaiveDate,
+
+    // Datos específicos del profesor
+    pub professional_id: String,
+    pub specialization: String,
+    pub hire_date: NaiveDate,
+    pub education_level: String,
+    pub subjects: Vec<String>,
+    pub status: TeacherStatus,
+}
+
 /// Filtros para la búsqueda de profesores
-#[derive(Debug, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize, Default)]
 pub struct TeacherFilter {
     pub user_id: Option<Uuid>,
     pub professional_id: Option<String>,
@@ -81,17 +102,47 @@ pub struct TeacherWithUserData {
     pub status: TeacherStatus,
 }
 
+const TEACHER_COLUMNS: &str = "user_id, professional_id, specialization, hire_date, education_level, \
+    subjects, status, created_at, updated_at";
+
+/// Agrega las condiciones de un filtro de profesores a una consulta que ya tiene `WHERE`
+fn push_teacher_filters(query: &mut QueryBuilder<'_, Postgres>, filter: &TeacherFilter) {
+    if let Some(user_id) = filter.user_id {
+        query.push(" AND user_id = ").push_bind(user_id);
+    }
+    if let Some(professional_id) = &filter.professional_id {
+        query.push(" AND professional_id = ").push_bind(professional_id.clone());
+    }
+    if let Some(specialization) = &filter.specialization {
+        query.push(" AND specialization ILIKE ").push_bind(format!("%{}%", specialization));
+    }
+    if let Some(status) = &filter.status {
+        query.push(" AND status = ").push_bind(status.clone());
+    }
+    if let Some(subject) = &filter.subject {
+        // `subjects` es un arreglo JSONB: contiene la materia buscada
+        query.push(" AND subjects @> ").push_bind(serde_json::json!([subject]));
+    }
+}
+
 impl Teacher {
     /// Crea un nuevo profesor en la base de datos
-    pub async fn create(pool: &PgPool, dto: CreateTeacherDto) -> Result<Teacher, SqlxError> {
-        let now = Utc::now();
-
+    pub async fn create(pool: &PgPool, dto: CreateTeacherDto) -> Result<Teacher, DbError> {
         // Verificar que el usuario existe
         let user_exists = User::find_by_id(pool, dto.user_id).await?;
         if user_exists.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Usuario no encontrado".to_string()));
         }
 
+        Self::insert(pool, dto).await
+    }
+
+    /// Inserta el perfil de profesor de un usuario existente
+    ///
+    /// Acepta el pool o una transacción abierta por el llamador.
+    pub async fn insert<'e, E: PgExecutor<'e>>(executor: E, dto: CreateTeacherDto) -> Result<Teacher, DbError> {
+        let now = Utc::now();
+
         // Convertir Vec<String> a formato JSON para almacenar en PostgreSQL
         let subjects_json = serde_json::to_value(&dto.subjects).unwrap();
 
@@ -105,7 +156,7 @@ impl Teacher {
             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING 
                 user_id, professional_id, specialization, hire_date, 
-                education_level, subjects as "subjects: Vec<String>", 
+                education_level, COALESCE(subjects, '[]') as "subjects!: JsonColumn<Vec<String>>", 
                 status as "status: TeacherStatus", created_at, updated_at
             "#,
             dto.user_id,
@@ -118,20 +169,20 @@ impl Teacher {
             now,
             now
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(teacher)
     }
 
     /// Encuentra un profesor por ID de usuario
-    pub async fn find_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Option<Teacher>, SqlxError> {
+    pub async fn find_by_user_id(pool: &PgPool, user_id: Uuid) -> Result<Option<Teacher>, DbError> {
         let teacher = sqlx::query_as!(
             Teacher,
             r#"
             SELECT 
                 user_id, professional_id, specialization, hire_date, 
-                education_level, subjects as "subjects: Vec<String>", 
+                education_level, COALESCE(subjects, '[]') as "subjects!: JsonColumn<Vec<String>>", 
                 status as "status: TeacherStatus", created_at, updated_at
             FROM teachers
             WHERE user_id = $1
@@ -145,13 +196,13 @@ impl Teacher {
     }
 
     /// Encuentra un profesor por su número de registro profesional
-    pub async fn find_by_professional_id(pool: &PgPool, professional_id: &str) -> Result<Option<Teacher>, SqlxError> {
+    pub async fn find_by_professional_id(pool: &PgPool, professional_id: &str) -> Result<Option<Teacher>, DbError> {
         let teacher = sqlx::query_as!(
             Teacher,
             r#"
             SELECT 
                 user_id, professional_id, specialization, hire_date, 
-                education_level, subjects as "subjects: Vec<String>", 
+                education_level, COALESCE(subjects, '[]') as "subjects!: JsonColumn<Vec<String>>", 
                 status as "status: TeacherStatus", created_at, updated_at
             FROM teachers
             WHERE professional_id = $1
@@ -170,97 +221,48 @@ impl Teacher {
         filter: TeacherFilter,
         limit: Option<i64>,
         offset: Option<i64>
-    ) -> Result<Vec<Teacher>, SqlxError> {
-        // Construimos la consulta base
-        let mut query = String::from(
-            "SELECT user_id, professional_id, specialization, hire_date, 
-            education_level, subjects, status, created_at, updated_at 
-            FROM teachers WHERE 1=1"
-        );
-
-        // Aplicamos los filtros si existen
-        let mut params = Vec::<String>::new();
-        let mut param_count = 1;
-
-        if let Some(user_id) = filter.user_id {
-            query.push_str(&format!(" AND user_id = ${}", param_count));
-            params.push(user_id.to_string());
-            param_count += 1;
-        }
-
-        if let Some(professional_id) = &filter.professional_id {
-            query.push_str(&format!(" AND professional_id = ${}", param_count));
-            params.push(professional_id.to_string());
-            param_count += 1;
-        }
-
-        if let Some(specialization) = &filter.specialization {
-            query.push_str(&format!(" AND specialization ILIKE ${}", param_count));
-            params.push(format!("%{}%", specialization));
-            param_count += 1;
-        }
-
-        if let Some(status) = &filter.status {
-            query.push_str(&format!(" AND status = ${}", param_count));
-            params.push(format!("{:?}", status));
-            param_count += 1;
-        }
+    ) -> Result<Vec<Teacher>, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM teachers WHERE 1=1", TEACHER_COLUMNS));
+        push_teacher_filters(&mut query, &filter);
 
-        if let Some(subject) = &filter.subject {
-            // Buscar en el array de subjects
-            query.push_str(&format!(" AND subjects @> ${}::jsonb", param_count));
-            params.push(format!("[\"{}\"]\", subject));
-            param_count += 1;
+        query.push(" ORDER BY created_at DESC");
+        if let Some(limit) = limit {
+            query.push(" LIMIT ").push_bind(limit);
         }
-
-        // Agregamos paginación
-        query.push_str(" ORDER BY created_at DESC");
-
-        if let Some(limit_val) = limit {
-            query.push_str(&format!(" LIMIT ${}", param_count));
-            params.push(limit_val.to_string());
-            param_count += 1;
-        }
-
-        if let Some(offset_val) = offset {
-            query.push_str(&format!(" OFFSET ${}", param_count));
-            params.push(offset_val.to_string());
-        }
-
-        // Ejecutamos la consulta dinámica
-        let mut q = sqlx::query(&query);
-        for param in params {
-            q = q.bind(param);
+        if let Some(offset) = offset {
+            query.push(" OFFSET ").push_bind(offset);
         }
 
         // Convertimos el resultado a instancias de Teacher
-        let rows = q.fetch_all(pool).await?;
-        let teachers = rows
-            .iter()
+        let sql = query.sql().to_owned();
+        let rows = metrics::timed_query("teachers.find_all", &sql, query.build().fetch_all(pool)).await?;
+        rows.iter()
             .map(|row| {
-                Teacher {
-                    user_id: row.get("user_id"),
-                    professional_id: row.get("professional_id"),
-                    specialization: row.get("specialization"),
-                    hire_date: row.get("hire_date"),
-                    education_level: row.get("education_level"),
-                    subjects: serde_json::from_value(row.get("subjects")).unwrap_or_default(),
-                    status: serde_json::from_value(row.get("status")).unwrap_or(TeacherStatus::Active),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                }
+                // `subjects` admite NULL en la tabla: sin materias
+                let subjects: Option<Json<Vec<String>>> = row.try_get("subjects")?;
+
+                Ok(Teacher {
+                    user_id: row.try_get("user_id")?,
+                    professional_id: row.try_get("professional_id")?,
+                    specialization: row.try_get("specialization")?,
+                    hire_date: row.try_get("hire_date")?,
+                    education_level: row.try_get("education_level")?,
+                    subjects: subjects.map(|subjects| subjects.0).unwrap_or_default(),
+                    status: row.try_get("status")?,
+                    created_at: row.try_get("created_at")?,
+                    updated_at: row.try_get("updated_at")?,
+                })
             })
-            .collect();
-
-        Ok(teachers)
+            .collect::<Result<Vec<_>, sqlx::Error>>()
+            .map_err(DbError::from)
     }
 
     /// Actualiza un profesor existente
-    pub async fn update(pool: &PgPool, user_id: Uuid, dto: UpdateTeacherDto) -> Result<Teacher, SqlxError> {
+    pub async fn update(pool: &PgPool, user_id: Uuid, dto: UpdateTeacherDto) -> Result<Teacher, DbError> {
         // Primero verificamos si el profesor existe
         let existing_teacher = Self::find_by_user_id(pool, user_id).await?;
         if existing_teacher.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Profesor no encontrado".to_string()));
         }
 
         let existing_teacher = existing_teacher.unwrap();
@@ -286,7 +288,7 @@ impl Teacher {
             WHERE user_id = $8
             RETURNING 
                 user_id, professional_id, specialization, hire_date, 
-                education_level, subjects as "subjects: Vec<String>", 
+                education_level, COALESCE(subjects, '[]') as "subjects!: JsonColumn<Vec<String>>", 
                 status as "status: TeacherStatus", created_at, updated_at
             "#,
             professional_id,
@@ -305,11 +307,11 @@ impl Teacher {
     }
 
     /// Elimina un profesor por su ID de usuario
-    pub async fn delete(pool: &PgPool, user_id: Uuid) -> Result<PgQueryResult, SqlxError> {
+    pub async fn delete(pool: &PgPool, user_id: Uuid) -> Result<PgQueryResult, DbError> {
         // Verificamos si el profesor existe
         let existing_teacher = Self::find_by_user_id(pool, user_id).await?;
         if existing_teacher.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Profesor no encontrado".to_string()));
         }
 
         let result = sqlx::query!(
@@ -326,13 +328,13 @@ impl Teacher {
     }
 
     /// Obtiene la información completa de un profesor (datos de usuario + datos de profesor)
-    pub async fn get_teacher_with_user_data(pool: &PgPool, user_id: Uuid) -> Result<Option<TeacherWithUserData>, SqlxError> {
+    pub async fn get_teacher_with_user_data(pool: &PgPool, user_id: Uuid) -> Result<Option<TeacherWithUserData>, DbError> {
         let teacher_with_user = sqlx::query!(
             r#"
             SELECT 
                 u.id, u.document_id, u.full_name, u.email, u.phone, u.address, u.birth_date,
                 t.professional_id, t.specialization, t.hire_date, t.education_level, 
-                t.subjects as "subjects: Vec<String>", t.status as "status: TeacherStatus"
+                COALESCE(t.subjects, '[]') as "subjects!: JsonColumn<Vec<String>>", t.status as "status: TeacherStatus"
             FROM teachers t
             JOIN users u ON t.user_id = u.id
             WHERE t.user_id = $1
@@ -355,7 +357,7 @@ impl Teacher {
                 specialization: record.specialization,
                 hire_date: record.hire_date,
                 education_level: record.education_level,
-                subjects: record.subjects,
+                subjects: record.subjects.into(),
                 status: record.status,
             })),
             None => Ok(None),
@@ -363,15 +365,70 @@ impl Teacher {
     }
 
     /// Cuenta el número total de profesores que coinciden con un filtro
-    pub async fn count(pool: &PgPool, filter: TeacherFilter) -> Result<i64, SqlxError> {
-        // Construimos la consulta base
-        let mut query = String::from("SELECT COUNT(*) FROM teachers WHERE 1=1");
+    pub async fn count(pool: &PgPool, filter: TeacherFilter) -> Result<i64, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM teachers WHERE 1=1");
+        push_teacher_filters(&mut query, &filter);
 
-        // Aplicamos los filtros si existen
-        let mut params = Vec::<String>::new();
-        let mut param_count = 1;
+        query.build_query_scalar::<i64>().fetch_one(pool).await
+        .map_err(DbError::from)
+    }
 
-        if let Some(user_id) = filter.user_id {
-            query.push_str(&format!(" AND user_id = ${}", param_count));
-            params.push
+    /// Crea un profesor junto con sus datos de usuario en una sola transacción
+    pub async fn create_with_user(
+        pool: &PgPool,
+        dto: CreateTeacherWithUserDto,
+    ) -> Result<(User, Teacher), DbError> {
+        // Iniciar transacción para garantizar atomicidad
+        let mut tx = metrics::begin(pool).await?;
+
+        // Crear el usuario primero
+        let user_dto = crate::models::user::CreateUserDto {
+            document_id: dto.document_id,
+            full_name: dto.full_name,
+            email: dto.email,
+            phone: dto.phone,
+            address: dto.address,
+            birth_date: dto.birth_date,
+            role: Role::Teacher, // Asignamos automáticamente el rol de profesor
+        };
+
+        let user = User::create(&mut *tx, user_dto).await?;
+
+        // Crear el profesor usando el ID del usuario recién creado
+        let teacher_dto = CreateTeacherDto {
+            user_id: user.id,
+            professional_id: dto.professional_id,
+            specialization: dto.specialization,
+            hire_date: dto.hire_date,
+            education_level: dto.education_level,
+            subjects: dto.subjects,
+            status: dto.status,
+        };
+
+        let teacher = Self::insert(&mut *tx, teacher_dto).await?;
+
+        // Confirmar la transacción
+        tx.commit().await?;
+
+        Ok((user, teacher))
+    }
 
+    /// Da de baja a un profesor sin eliminar su registro
+    ///
+    /// El profesor queda con estado `terminated`; sus cursos e historial se
+    /// conservan. Devuelve `false` si no existe o ya estaba dado de baja.
+    pub async fn deactivate(pool: &PgPool, user_id: Uuid) -> Result<bool, DbError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE teachers
+            SET status = 'terminated', updated_at = NOW()
+            WHERE user_id = $1 AND status <> 'terminated'
+            "#,
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
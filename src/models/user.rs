@@ -1,9 +1,10 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, PgPool, Error as SqlxError, postgres::PgQueryResult};
+use sqlx::{FromRow, PgExecutor, PgPool, Postgres, QueryBuilder, postgres::PgQueryResult};
 use uuid::Uuid;
 
-use crate::models::Role;
+use crate::db::{metrics, DbError};
+use crate::models::{patch::required, Patch, Role};
 
 /// Re-exportamos User para facilitar su uso en el módulo models
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -30,6 +31,36 @@ pub struct User {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Datos del usuario autenticado, tal como los devuelve `GET /auth/me`
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct PrincipalRow {
+    pub id: Uuid,
+    pub full_name: String,
+    /// Rol en minúsculas, como en los tokens (`admin`, `parent`, ...)
+    pub role: String,
+    pub email_verified: bool,
+    /// Estudiantes cuyo encargado es este usuario (vacío si no es encargado)
+    pub children_ids: Vec<Uuid>,
+}
+
+/// Estado de la cuenta de un usuario
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum UserStatus {
+    /// Cuenta habilitada
+    #[default]
+    Active,
+    /// Correo electrónico aún no verificado: puede consultar datos pero no
+    /// registrar calificaciones ni pagos
+    PendingEmailVerification,
+}
+
+impl UserStatus {
+    /// Indica si el usuario puede registrar calificaciones y pagos
+    pub fn can_modify_records(&self) -> bool {
+        matches!(self, UserStatus::Active)
+    }
+}
+
 /// DTO para la creación de un nuevo usuario
 #[derive(Debug, Deserialize)]
 pub struct CreateUserDto {
@@ -54,6 +85,28 @@ pub struct UpdateUserDto {
     pub role: Option<Role>,
 }
 
+/// DTO para la actualización parcial (merge-patch) de un usuario
+///
+/// Los campos opcionales usan `Patch` para que un `null` explícito limpie la columna;
+/// en los obligatorios un `null` es un error de tipo.
+#[derive(Debug, Deserialize)]
+pub struct PatchUserDto {
+    #[serde(default, deserialize_with = "required")]
+    pub document_id: Option<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub full_name: Option<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Patch<String>,
+    #[serde(default)]
+    pub address: Patch<String>,
+    #[serde(default, deserialize_with = "required")]
+    pub birth_date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "required")]
+    pub role: Option<Role>,
+}
+
 /// Filtros para la búsqueda de usuarios
 #[derive(Debug, Deserialize, Default)]
 pub struct UserFilter {
@@ -64,9 +117,34 @@ pub struct UserFilter {
     pub role: Option<Role>,
 }
 
+/// Columnas de `users` que se leen en un `User`
+const USER_COLUMNS: &str =
+    "id, document_id, full_name, email, phone, address, birth_date, role, created_at, updated_at";
+
+/// Agrega las condiciones de un filtro de usuarios a una consulta que ya tiene `WHERE`
+fn push_user_filters(query: &mut QueryBuilder<'_, Postgres>, filter: &UserFilter) {
+    if let Some(id) = filter.id {
+        query.push(" AND id = ").push_bind(id);
+    }
+    if let Some(document_id) = &filter.document_id {
+        query.push(" AND document_id = ").push_bind(document_id.clone());
+    }
+    if let Some(full_name) = &filter.full_name {
+        query.push(" AND full_name ILIKE ").push_bind(format!("%{}%", full_name));
+    }
+    if let Some(email) = &filter.email {
+        query.push(" AND email ILIKE ").push_bind(format!("%{}%", email));
+    }
+    if let Some(role) = &filter.role {
+        query.push(" AND role = ").push_bind(role.clone());
+    }
+}
+
 impl User {
     /// Crea un nuevo usuario en la base de datos
-    pub async fn create(pool: &PgPool, dto: CreateUserDto) -> Result<User, SqlxError> {
+    ///
+    /// Acepta el pool o una transacción abierta por el llamador.
+    pub async fn create<'e, E: PgExecutor<'e>>(executor: E, dto: CreateUserDto) -> Result<User, DbError> {
         let now = Utc::now();
         let id = Uuid::new_v4();
 
@@ -88,14 +166,14 @@ impl User {
             now,
             now
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(user)
     }
 
     /// Encuentra un usuario por su ID
-    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<User>, SqlxError> {
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<User>, DbError> {
         let user = sqlx::query_as!(
             User,
             r#"
@@ -112,7 +190,7 @@ impl User {
     }
 
     /// Encuentra un usuario por su documento de identidad
-    pub async fn find_by_document_id(pool: &PgPool, document_id: &str) -> Result<Option<User>, SqlxError> {
+    pub async fn find_by_document_id(pool: &PgPool, document_id: &str) -> Result<Option<User>, DbError> {
         let user = sqlx::query_as!(
             User,
             r#"
@@ -129,7 +207,7 @@ impl User {
     }
 
     /// Encuentra un usuario por su correo electrónico
-    pub async fn find_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, SqlxError> {
+    pub async fn find_by_email(pool: &PgPool, email: &str) -> Result<Option<User>, DbError> {
         let user = sqlx::query_as!(
             User,
             r#"
@@ -146,101 +224,71 @@ impl User {
     }
 
     /// Lista todos los usuarios con opción de filtrado y paginación
+    ///
+    /// Los usuarios se ordenan del más reciente al más antiguo, con el ID como
+    /// desempate, igual que en `find_all_cursor`. Un rol que la aplicación no
+    /// conoce falla con `DbError::Serialization` en lugar de reemplazarse.
     pub async fn find_all(
-        pool: &PgPool, 
+        pool: &PgPool,
         filter: UserFilter,
         limit: Option<i64>,
         offset: Option<i64>
-    ) -> Result<Vec<User>, SqlxError> {
-        // Construimos la consulta base
-        let mut query = String::from(
-            "SELECT id, document_id, full_name, email, phone, address, birth_date, role, created_at, updated_at 
-             FROM users WHERE 1=1"
-        );
-
-        // Aplicamos los filtros si existen
-        let mut params = Vec::<String>::new();
-        let mut param_count = 1;
-
-        if let Some(id) = filter.id {
-            query.push_str(&format!(" AND id = ${}", param_count));
-            params.push(id.to_string());
-            param_count += 1;
-        }
+    ) -> Result<Vec<User>, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM users WHERE 1=1", USER_COLUMNS));
+        push_user_filters(&mut query, &filter);
 
-        if let Some(document_id) = &filter.document_id {
-            query.push_str(&format!(" AND document_id = ${}", param_count));
-            params.push(document_id.to_string());
-            param_count += 1;
+        query.push(" ORDER BY created_at DESC, id DESC");
+        if let Some(limit) = limit {
+            query.push(" LIMIT ").push_bind(limit);
         }
-
-        if let Some(full_name) = &filter.full_name {
-            query.push_str(&format!(" AND full_name ILIKE ${}", param_count));
-            params.push(format!("%{}%", full_name));
-            param_count += 1;
-        }
-
-        if let Some(email) = &filter.email {
-            query.push_str(&format!(" AND email ILIKE ${}", param_count));
-            params.push(format!("%{}%", email));
-            param_count += 1;
+        if let Some(offset) = offset {
+            query.push(" OFFSET ").push_bind(offset);
         }
 
-        if let Some(role) = &filter.role {
-            query.push_str(&format!(" AND role = ${}", param_count));
-            params.push(format!("{:?}", role));
-            param_count += 1;
-        }
-
-        // Agregamos paginación
-        query.push_str(" ORDER BY created_at DESC");
-
-        if let Some(limit_val) = limit {
-            query.push_str(&format!(" LIMIT ${}", param_count));
-            params.push(limit_val.to_string());
-            param_count += 1;
-        }
-
-        if let Some(offset_val) = offset {
-            query.push_str(&format!(" OFFSET ${}", param_count));
-            params.push(offset_val.to_string());
-        }
+        let sql = query.sql().to_owned();
+        metrics::timed_query("users.find_all", &sql, query.build_query_as::<User>().fetch_all(pool)).await
+        .map_err(DbError::from)
+    }
 
-        // Ejecutamos la consulta dinámica
-        let mut q = sqlx::query(&query);
-        for param in params {
-            q = q.bind(param);
+    /// Lista usuarios con paginación por cursor
+    ///
+    /// Devuelve los usuarios que siguen a `after` en el orden de `find_all`, de
+    /// modo que recorrer todas las páginas da los mismos usuarios que la
+    /// paginación por desplazamiento, aunque se creen usuarios entre páginas.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - Pool de conexiones a la base de datos
+    /// * `filter` - Filtros de búsqueda
+    /// * `after` - ID del último usuario de la página anterior (opcional)
+    /// * `limit` - Cantidad máxima de usuarios
+    pub async fn find_all_cursor(
+        pool: &PgPool,
+        filter: &UserFilter,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<User>, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new(format!("SELECT {} FROM users WHERE 1=1", USER_COLUMNS));
+        push_user_filters(&mut query, filter);
+
+        if let Some(after) = after {
+            query
+                .push(" AND (created_at, id) < (SELECT created_at, id FROM users WHERE id = ")
+                .push_bind(after)
+                .push(")");
         }
+        query.push(" ORDER BY created_at DESC, id DESC LIMIT ").push_bind(limit);
 
-        // Convertimos el resultado a instancias de User
-        let rows = q.fetch_all(pool).await?;
-        let users = rows
-            .iter()
-            .map(|row| {
-                User {
-                    id: row.get("id"),
-                    document_id: row.get("document_id"),
-                    full_name: row.get("full_name"),
-                    email: row.get("email"),
-                    phone: row.get("phone"),
-                    address: row.get("address"),
-                    birth_date: row.get("birth_date"),
-                    role: serde_json::from_value(row.get("role")).unwrap_or(Role::Student),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                }
-            })
-            .collect();
-
-        Ok(users)
+        query.build_query_as::<User>().fetch_all(pool).await
+        .map_err(DbError::from)
     }
 
     /// Actualiza un usuario existente
-    pub async fn update(pool: &PgPool, id: Uuid, dto: UpdateUserDto) -> Result<User, SqlxError> {
+    pub async fn update(pool: &PgPool, id: Uuid, dto: UpdateUserDto) -> Result<User, DbError> {
         // Primero verificamos si el usuario existe
         let existing_user = Self::find_by_id(pool, id).await?;
         if existing_user.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Usuario no encontrado".to_string()));
         }
 
         let existing_user = existing_user.unwrap();
@@ -280,12 +328,57 @@ impl User {
         Ok(updated_user)
     }
 
+    /// Aplica una actualización parcial (merge-patch) a un usuario existente
+    ///
+    /// El cambio se resuelve en un único `UPDATE`, de modo que dos parches
+    /// concurrentes sobre campos distintos no se pisan entre sí.
+    pub async fn patch(pool: &PgPool, id: Uuid, dto: PatchUserDto) -> Result<User, DbError> {
+        let now = Utc::now();
+
+        // Los campos ausentes conservan su valor; `null` limpia los opcionales
+        let phone_sent = !dto.phone.is_undefined();
+        let address_sent = !dto.address.is_undefined();
+
+        let patched_user = sqlx::query_as!(
+            User,
+            r#"
+            UPDATE users 
+            SET document_id = COALESCE($1, document_id),
+                full_name = COALESCE($2, full_name),
+                email = COALESCE($3, email),
+                phone = CASE WHEN $4 THEN $5 ELSE phone END,
+                address = CASE WHEN $6 THEN $7 ELSE address END,
+                birth_date = COALESCE($8, birth_date),
+                role = COALESCE($9, role),
+                updated_at = $10
+            WHERE id = $11
+            RETURNING id, document_id, full_name, email, phone, address, birth_date, role as "role: Role", created_at, updated_at
+            "#,
+            dto.document_id,
+            dto.full_name,
+            dto.email,
+            phone_sent,
+            dto.phone.into_option(),
+            address_sent,
+            dto.address.into_option(),
+            dto.birth_date,
+            dto.role as Option<Role>,
+            now,
+            id
+        )
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| DbError::NotFound("Usuario no encontrado".to_string()))?;
+
+        Ok(patched_user)
+    }
+
     /// Elimina un usuario por su ID
-    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<PgQueryResult, SqlxError> {
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<PgQueryResult, DbError> {
         // Verificamos si el usuario existe
         let existing_user = Self::find_by_id(pool, id).await?;
         if existing_user.is_none() {
-            return Err(SqlxError::RowNotFound);
+            return Err(DbError::NotFound("Usuario no encontrado".to_string()));
         }
 
         let result = sqlx::query!(
@@ -302,57 +395,16 @@ impl User {
     }
 
     /// Cuenta el número total de usuarios que coinciden con un filtro
-    pub async fn count(pool: &PgPool, filter: UserFilter) -> Result<i64, SqlxError> {
-        // Construimos la consulta base
-        let mut query = String::from("SELECT COUNT(*) FROM users WHERE 1=1");
-
-        // Aplicamos los filtros si existen
-        let mut params = Vec::<String>::new();
-        let mut param_count = 1;
-
-        if let Some(id) = filter.id {
-            query.push_str(&format!(" AND id = ${}", param_count));
-            params.push(id.to_string());
-            param_count += 1;
-        }
+    pub async fn count(pool: &PgPool, filter: UserFilter) -> Result<i64, DbError> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM users WHERE 1=1");
+        push_user_filters(&mut query, &filter);
 
-        if let Some(document_id) = &filter.document_id {
-            query.push_str(&format!(" AND document_id = ${}", param_count));
-            params.push(document_id.to_string());
-            param_count += 1;
-        }
-
-        if let Some(full_name) = &filter.full_name {
-            query.push_str(&format!(" AND full_name ILIKE ${}", param_count));
-            params.push(format!("%{}%", full_name));
-            param_count += 1;
-        }
-
-        if let Some(email) = &filter.email {
-            query.push_str(&format!(" AND email ILIKE ${}", param_count));
-            params.push(format!("%{}%", email));
-            param_count += 1;
-        }
-
-        if let Some(role) = &filter.role {
-            query.push_str(&format!(" AND role = ${}", param_count));
-            params.push(format!("{:?}", role));
-        }
-
-        // Ejecutamos la consulta dinámica
-        let mut q = sqlx::query(&query);
-        for param in params {
-            q = q.bind(param);
-        }
-
-        let row = q.fetch_one(pool).await?;
-        let count: i64 = row.get(0);
-
-        Ok(count)
+        query.build_query_scalar::<i64>().fetch_one(pool).await
+        .map_err(DbError::from)
     }
 
     /// Busca usuarios por rol
-    pub async fn find_by_role(pool: &PgPool, role: Role) -> Result<Vec<User>, SqlxError> {
+    pub async fn find_by_role(pool: &PgPool, role: Role) -> Result<Vec<User>, DbError> {
         let users = sqlx::query_as!(
             User,
             r#"
@@ -370,7 +422,7 @@ impl User {
     }
 
     /// Busca usuarios por coincidencia parcial en el nombre
-    pub async fn search_by_name(pool: &PgPool, name_query: &str) -> Result<Vec<User>, SqlxError> {
+    pub async fn search_by_name(pool: &PgPool, name_query: &str) -> Result<Vec<User>, DbError> {
         let search_pattern = format!("%{}%", name_query);
         
         let users = sqlx::query_as!(
@@ -389,10 +441,29 @@ impl User {
 
         Ok(users)
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    
 
+    /// Datos del usuario autenticado, con sus hijos si es encargado, en una sola consulta
+    ///
+    /// Los hijos son los estudiantes cuyo `guardian_info.document_id` coincide con
+    /// el documento del usuario.
+    pub async fn find_principal(pool: &PgPool, id: Uuid) -> Result<Option<PrincipalRow>, DbError> {
+        sqlx::query_as::<_, PrincipalRow>(
+            r#"
+            SELECT u.id, u.full_name, LOWER(u.role::text) AS role,
+                   COALESCE(a.email_verified, false) AS email_verified,
+                   ARRAY(
+                       SELECT s.user_id FROM students s
+                       WHERE s.guardian_info->>'document_id' = u.document_id
+                       ORDER BY s.user_id
+                   ) AS children_ids
+            FROM users u
+            LEFT JOIN authentications a ON a.user_id = u.id
+            WHERE u.id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(DbError::from)
+    }
+}
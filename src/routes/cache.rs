@@ -0,0 +1,217 @@
+//! Caché HTTP para los endpoints GET más pesados.
+//! Calcula ETags débiles, responde `If-None-Match` con 304 Not Modified y
+//! fija el encabezado `Cache-Control` según la política de cada endpoint.
+
+use actix_web::{
+    http::header::{self, HeaderValue},
+    HttpRequest, HttpResponse,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+/// Política de caché de un endpoint
+#[derive(Debug, Clone, Copy)]
+pub struct CachePolicy {
+    /// Segundos que el cliente puede reutilizar la respuesta sin revalidarla
+    pub max_age: u32,
+}
+
+impl CachePolicy {
+    /// Datos que cambian poco (configuración de la institución, catálogos)
+    pub const CATALOG: CachePolicy = CachePolicy { max_age: 300 };
+    /// Datos que cambian durante el día (listados, horarios)
+    pub const LISTING: CachePolicy = CachePolicy { max_age: 60 };
+    /// Datos de la sesión (el usuario autenticado)
+    pub const SESSION: CachePolicy = CachePolicy { max_age: 60 };
+
+    fn header_value(&self) -> String {
+        format!("private, max-age={}", self.max_age)
+    }
+}
+
+/// ETag débil de un cuerpo de respuesta serializado
+///
+/// Son los primeros 16 dígitos hexadecimales de su SHA-256, iguales en
+/// todas las versiones y réplicas del servidor.
+pub fn weak_etag_from_body(body: &[u8]) -> String {
+    let digest = format!("{:x}", Sha256::digest(body));
+    format!("W/\"{}\"", &digest[..16])
+}
+
+/// ETag débil de una entidad a partir de su id y su última modificación
+pub fn weak_etag_from_version<I: std::fmt::Display>(id: I, updated_at: DateTime<Utc>) -> String {
+    format!("W/\"{}-{}\"", id, updated_at.timestamp_millis())
+}
+
+/// Indica si el encabezado `If-None-Match` de la solicitud coincide con el ETag
+///
+/// La comparación es débil: el prefijo `W/` se ignora en ambos lados.
+pub fn if_none_match(req: &HttpRequest, etag: &str) -> bool {
+    let Some(value) = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+
+    let wanted = etag.trim_start_matches("W/");
+    value
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == wanted)
+}
+
+/// Serializa `body` como JSON y lo devuelve con los encabezados ETag y
+/// Cache-Control, o un 304 vacío si el cliente ya tiene la versión actual
+pub fn cached_json<T: Serialize>(req: &HttpRequest, body: &T, policy: CachePolicy) -> HttpResponse {
+    let bytes = match serde_json::to_vec(body) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("Failed to serialize response body: {}", e);
+            return HttpResponse::InternalServerError().json("Failed to serialize response");
+        }
+    };
+
+    let etag = weak_etag_from_body(&bytes);
+    respond_with_etag(req, &etag, policy, bytes)
+}
+
+/// Igual que [`cached_json`] con un ETag ya calculado (p. ej. a partir de `updated_at`)
+pub fn cached_json_with_etag<T: Serialize>(
+    req: &HttpRequest,
+    body: &T,
+    etag: &str,
+    policy: CachePolicy,
+) -> HttpResponse {
+    if if_none_match(req, etag) {
+        return not_modified(etag, policy);
+    }
+
+    match serde_json::to_vec(body) {
+        Ok(bytes) => respond_with_etag(req, etag, policy, bytes),
+        Err(e) => {
+            log::error!("Failed to serialize response body: {}", e);
+            HttpResponse::InternalServerError().json("Failed to serialize response")
+        }
+    }
+}
+
+fn respond_with_etag(req: &HttpRequest, etag: &str, policy: CachePolicy, bytes: Vec<u8>) -> HttpResponse {
+    if if_none_match(req, etag) {
+        return not_modified(etag, policy);
+    }
+
+    HttpResponse::Ok()
+        .insert_header((header::ETAG, etag))
+        .insert_header((header::CACHE_CONTROL, policy.header_value()))
+        .content_type("application/json")
+        .body(bytes)
+}
+
+fn not_modified(etag: &str, policy: CachePolicy) -> HttpResponse {
+    let mut response = HttpResponse::NotModified()
+        .insert_header((header::CACHE_CONTROL, policy.header_value()))
+        .finish();
+    if let Ok(value) = HeaderValue::from_str(etag) {
+        response.headers_mut().insert(header::ETAG, value);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, web, App};
+    use serde::Serialize;
+    use std::sync::Mutex;
+
+    #[derive(Serialize)]
+    struct Page<T> {
+        items: Vec<T>,
+        page: u32,
+        total: u64,
+    }
+
+    struct Catalog(Mutex<Vec<String>>);
+
+    async fn list(req: HttpRequest, catalog: web::Data<Catalog>) -> HttpResponse {
+        let items = catalog.0.lock().unwrap().clone();
+        let total = items.len() as u64;
+        cached_json(&req, &Page { items, page: 1, total }, CachePolicy::LISTING)
+    }
+
+    fn etag_of(resp: &actix_web::dev::ServiceResponse) -> String {
+        resp.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string()
+    }
+
+    #[actix_rt::test]
+    async fn test_repeat_fetch_returns_not_modified() {
+        let catalog = web::Data::new(Catalog(Mutex::new(vec!["MAT-101".to_string()])));
+        let app = actix_web::test::init_service(
+            App::new().app_data(catalog.clone()).route("/courses", web::get().to(list))
+        ).await;
+
+        let resp = actix_web::test::call_service(&app, actix_web::test::TestRequest::get().uri("/courses").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(
+            resp.headers().get(header::CACHE_CONTROL).unwrap(),
+            "private, max-age=60"
+        );
+        let etag = etag_of(&resp);
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/courses")
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(etag_of(&resp), etag);
+    }
+
+    #[actix_rt::test]
+    async fn test_etag_changes_after_update() {
+        let catalog = web::Data::new(Catalog(Mutex::new(vec!["MAT-101".to_string()])));
+        let app = actix_web::test::init_service(
+            App::new().app_data(catalog.clone()).route("/courses", web::get().to(list))
+        ).await;
+
+        let resp = actix_web::test::call_service(&app, actix_web::test::TestRequest::get().uri("/courses").to_request()).await;
+        let etag = etag_of(&resp);
+
+        catalog.0.lock().unwrap().push("HIS-201".to_string());
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/courses")
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_ne!(etag_of(&resp), etag);
+    }
+
+    #[test]
+    fn test_etag_covers_pagination_envelope() {
+        let first = serde_json::to_vec(&Page { items: vec!["a"], page: 1, total: 2 }).unwrap();
+        let second = serde_json::to_vec(&Page { items: vec!["a"], page: 2, total: 2 }).unwrap();
+
+        assert_ne!(weak_etag_from_body(&first), weak_etag_from_body(&second));
+        assert_eq!(weak_etag_from_body(&first), weak_etag_from_body(&first));
+    }
+
+    #[test]
+    fn test_body_etag_is_truncated_sha256() {
+        assert_eq!(weak_etag_from_body(b""), "W/\"e3b0c44298fc1c14\"");
+        assert_eq!(weak_etag_from_body(b"{}"), "W/\"44136fa355b3678a\"");
+    }
+
+    #[test]
+    fn test_version_etag_uses_updated_at() {
+        let earlier = Utc::now();
+        let later = earlier + chrono::Duration::seconds(1);
+
+        assert_ne!(weak_etag_from_version(1, earlier), weak_etag_from_version(1, later));
+        assert!(weak_etag_from_version(1, earlier).starts_with("W/\""));
+    }
+}
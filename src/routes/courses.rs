@@ -1,20 +1,75 @@
 use actix_web::{
-    delete, get, post, put,
+    delete, get, patch, post, put,
     web::{self, Data, Json, Path},
-    HttpResponse, Responder,
+    HttpRequest, HttpResponse, Responder, ResponseError,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use uuid::Uuid;
 
+use super::cache::{cached_json, CachePolicy};
 use crate::{
-    models::course::{Course, NewCourse, UpdateCourse},
-    services::courses::CourseService,
+    db::{DbError, DEFAULT_PAGE_SIZE},
+    models::{
+        course::{CreateCourseDto, PatchCourseDto, UpdateCourseDto},
+    },
+    routes::{
+        extractors::{merge_patch, QueryParamError, QueryParams, MAX_YEAR, MIN_YEAR},
+        response::ApiError,
+    },
+    services::{catalog::ExportFormat, courses::CourseService, ServiceError},
 };
 
+/// Query parameters accepted by `GET /api/courses`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct CourseListQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl TryFrom<QueryParams> for CourseListQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        Ok(CourseListQuery {
+            page: params.page("page")?,
+            page_size: params.page_size("page_size")?,
+        })
+    }
+}
+
+/// Query parameters accepted by `GET /api/courses/catalog`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct CatalogQuery {
+    pub year: i32,
+    pub format: ExportFormat,
+}
+
+impl TryFrom<QueryParams> for CatalogQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        Ok(CatalogQuery {
+            year: params
+                .year("year")?
+                .ok_or_else(|| QueryParamError::new("year", format!("a year between {} and {}", MIN_YEAR, MAX_YEAR)))?,
+            format: params.value("format", "one of json, pdf, html")?.unwrap_or_default(),
+        })
+    }
+}
+
 #[get("")]
-async fn get_all_courses(course_service: Data<CourseService>) -> impl Responder {
-    match course_service.get_all_courses().await {
-        Ok(courses) => HttpResponse::Ok().json(courses),
+async fn get_all_courses(
+    req: HttpRequest,
+    query: web::Query<CourseListQuery>,
+    course_service: Data<CourseService>,
+) -> impl Responder {
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    match course_service.get_all_courses(page, page_size).await {
+        Ok(courses) => cached_json(&req, &courses, CachePolicy::LISTING),
         Err(e) => {
             log::error!("Failed to get courses: {}", e);
             HttpResponse::InternalServerError().json("Failed to get courses")
@@ -22,6 +77,21 @@ async fn get_all_courses(course_service: Data<CourseService>) -> impl Responder
     }
 }
 
+#[get("/catalog")]
+async fn export_catalog(
+    query: web::Query<CatalogQuery>,
+    course_service: Data<CourseService>,
+) -> Result<HttpResponse, ApiError> {
+    let CatalogQuery { year, format } = query.into_inner();
+    let document = course_service.export_catalog(year, format).await?;
+
+    let disposition = format!("inline; filename=\"catalogo-{}.{}\"", year, format.extension());
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header(("Content-Disposition", disposition))
+        .body(document))
+}
+
 #[get("/{id}")]
 async fn get_course_by_id(
     path: Path<(Uuid,)>,
@@ -30,8 +100,8 @@ async fn get_course_by_id(
     let course_id = path.into_inner().0;
     
     match course_service.get_course_by_id(course_id).await {
-        Ok(Some(course)) => HttpResponse::Ok().json(course),
-        Ok(None) => HttpResponse::NotFound().json("Course not found"),
+        Ok(course) => HttpResponse::Ok().json(course),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Course not found"),
         Err(e) => {
             log::error!("Failed to get course: {}", e);
             HttpResponse::InternalServerError().json("Failed to get course")
@@ -41,7 +111,7 @@ async fn get_course_by_id(
 
 #[post("")]
 async fn create_course(
-    course: Json<NewCourse>,
+    course: Json<CreateCourseDto>,
     course_service: Data<CourseService>,
 ) -> impl Responder {
     match course_service.create_course(course.into_inner()).await {
@@ -56,14 +126,14 @@ async fn create_course(
 #[put("/{id}")]
 async fn update_course(
     path: Path<(Uuid,)>,
-    course: Json<UpdateCourse>,
+    course: Json<UpdateCourseDto>,
     course_service: Data<CourseService>,
 ) -> impl Responder {
     let course_id = path.into_inner().0;
     
     match course_service.update_course(course_id, course.into_inner()).await {
-        Ok(Some(updated_course)) => HttpResponse::Ok().json(updated_course),
-        Ok(None) => HttpResponse::NotFound().json("Course not found"),
+        Ok(updated_course) => HttpResponse::Ok().json(updated_course),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Course not found"),
         Err(e) => {
             log::error!("Failed to update course: {}", e);
             HttpResponse::InternalServerError().json("Failed to update course")
@@ -71,6 +141,31 @@ async fn update_course(
     }
 }
 
+#[patch("/{id}")]
+async fn patch_course(
+    req: HttpRequest,
+    path: Path<(Uuid,)>,
+    body: web::Bytes,
+    course_service: Data<CourseService>,
+) -> impl Responder {
+    let course_id = path.into_inner().0;
+    let patch: PatchCourseDto = match merge_patch(&req, &body) {
+        Ok(patch) => patch,
+        Err(e) => return e.error_response(),
+    };
+
+    match course_service.patch_course(course_id, patch).await {
+        Ok(course) => HttpResponse::Ok().json(course),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Course not found"),
+        Err(ServiceError::ValidationError(msg)) => HttpResponse::BadRequest().json(msg),
+        Err(e @ ServiceError::DatabaseError(DbError::Invalid(_))) => ApiError::from(e).error_response(),
+        Err(e) => {
+            log::error!("Failed to patch course: {}", e);
+            HttpResponse::InternalServerError().json("Failed to patch course")
+        }
+    }
+}
+
 #[delete("/{id}")]
 async fn delete_course(
     path: Path<(Uuid,)>,
@@ -79,8 +174,8 @@ async fn delete_course(
     let course_id = path.into_inner().0;
     
     match course_service.delete_course(course_id).await {
-        Ok(true) => HttpResponse::NoContent().finish(),
-        Ok(false) => HttpResponse::NotFound().json("Course not found"),
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Course not found"),
         Err(e) => {
             log::error!("Failed to delete course: {}", e);
             HttpResponse::InternalServerError().json("Failed to delete course")
@@ -88,15 +183,67 @@ async fn delete_course(
     }
 }
 
+/// Parámetros de consulta para la búsqueda de cursos similares
+#[derive(Debug, Deserialize)]
+pub struct SimilarCoursesQuery {
+    pub threshold: Option<f64>,
+}
+
+#[get("/{id}/similar")]
+async fn get_similar_courses(
+    path: Path<(Uuid,)>,
+    query: web::Query<SimilarCoursesQuery>,
+    course_service: Data<CourseService>,
+) -> impl Responder {
+    let course_id = path.into_inner().0;
+    let threshold = query.threshold.unwrap_or(0.7);
+
+    match course_service.find_similar_courses(course_id, threshold).await {
+        Ok(similar) => HttpResponse::Ok().json(similar),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Course not found"),
+        Err(ServiceError::ValidationError(msg)) => HttpResponse::BadRequest().json(msg),
+        Err(e) => {
+            log::error!("Failed to get similar courses: {}", e);
+            HttpResponse::InternalServerError().json("Failed to get similar courses")
+        }
+    }
+}
+
+/// Parámetros de consulta para el árbol de prerrequisitos
+#[derive(Debug, Deserialize)]
+pub struct PrerequisiteTreeQuery {
+    pub student_id: Option<Uuid>,
+}
+
+#[get("/{id}/prerequisite-tree")]
+async fn get_prerequisite_tree(
+    path: Path<(Uuid,)>,
+    query: web::Query<PrerequisiteTreeQuery>,
+    course_service: Data<CourseService>,
+) -> impl Responder {
+    let course_id = path.into_inner().0;
+
+    match course_service.get_prerequisite_tree(course_id, query.student_id).await {
+        Ok(tree) => HttpResponse::Ok().json(tree),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Course not found"),
+        Err(ServiceError::ValidationError(msg)) => HttpResponse::UnprocessableEntity().json(msg),
+        Err(e) => {
+            log::error!("Failed to get prerequisite tree: {}", e);
+            HttpResponse::InternalServerError().json("Failed to get prerequisite tree")
+        }
+    }
+}
+
 #[get("/academic-year/{year}")]
 async fn get_courses_by_academic_year(
-    path: Path<(String,)>,
+    req: HttpRequest,
+    path: Path<(i32,)>,
     course_service: Data<CourseService>,
 ) -> impl Responder {
     let academic_year = path.into_inner().0;
     
-    match course_service.get_courses_by_academic_year(&academic_year).await {
-        Ok(courses) => HttpResponse::Ok().json(courses),
+    match course_service.get_courses_by_academic_year(academic_year).await {
+        Ok(courses) => cached_json(&req, &courses, CachePolicy::LISTING),
         Err(e) => {
             log::error!("Failed to get courses by academic year: {}", e);
             HttpResponse::InternalServerError().json("Failed to get courses by academic year")
@@ -120,10 +267,17 @@ async fn get_stats_by_academic_year(
 pub fn routes() -> actix_web::Scope {
     web::scope("/courses")
         .service(get_all_courses)
+        // Before `/{id}` so "catalog" is not parsed as an ID
+        .service(export_catalog)
         .service(get_course_by_id)
         .service(create_course)
         .service(update_course)
+        .service(patch_course)
         .service(delete_course)
+        .service(get_similar_courses)
+        .service(get_prerequisite_tree)
+        .service(super::grades::get_course_grades)
+        .service(super::grades::get_grade_book)
         .service(get_courses_by_academic_year)
         .service(get_stats_by_academic_year)
 }
@@ -0,0 +1,431 @@
+//! Configuration for the JSON, raw body and query string extractors.
+//!
+//! The JSON and query extractors report failures with the standard
+//! [`ApiError`] body instead of actix's plain text defaults. JSON and raw
+//! bodies share the limit set with [`set_max_payload_size`]. Typed query structs deserialize through
+//! [`QueryParams`] (`#[serde(try_from = "QueryParams")]`), so a malformed or out
+//! of range value fails with a [`QueryParamError`] that names the parameter and
+//! the expected type.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use actix_web::{
+    error::{JsonPayloadError, QueryPayloadError},
+    http::StatusCode,
+    web, HttpMessage, HttpRequest,
+};
+use serde::{
+    de::{value::StrDeserializer, DeserializeOwned, IntoDeserializer},
+    Deserialize, Serialize,
+};
+
+use crate::models::patch::from_merge_patch;
+use crate::routes::response::{request_id, ApiError};
+
+/// Largest page size accepted by paginated endpoints ([`db::MAX_PAGE_SIZE`](crate::db::MAX_PAGE_SIZE))
+pub const MAX_PAGE_SIZE: u8 = crate::db::MAX_PAGE_SIZE as u8;
+
+/// Earliest academic year accepted in filters
+pub const MIN_YEAR: i32 = 2000;
+
+/// Latest academic year accepted in filters
+pub const MAX_YEAR: i32 = 2100;
+
+/// Error code of every query string failure
+const INVALID_QUERY_PARAMETER: &str = "invalid_query_parameter";
+
+/// Largest JSON or raw body accepted, in bytes (`server.max_payload_size`)
+static MAX_PAYLOAD_SIZE: AtomicUsize = AtomicUsize::new(2 * 1024 * 1024);
+
+/// Sets the body limit of the extractors built afterwards; `sai serve` calls
+/// it with `server.max_payload_size` before starting the workers
+pub fn set_max_payload_size(bytes: usize) {
+    MAX_PAYLOAD_SIZE.store(bytes, Ordering::Relaxed);
+}
+
+/// JSON body extractor that answers with the standard error body
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(MAX_PAYLOAD_SIZE.load(Ordering::Relaxed))
+        .error_handler(|err, req| json_error(&err, req).into())
+}
+
+/// Raw body (`web::Bytes`, `String`) extractor with the same limit as JSON bodies
+pub fn payload_config() -> web::PayloadConfig {
+    web::PayloadConfig::new(MAX_PAYLOAD_SIZE.load(Ordering::Relaxed))
+}
+
+/// Media type of JSON Merge Patch documents (RFC 7396)
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+
+/// Reads the body of a `PATCH` request as a JSON Merge Patch document
+///
+/// Any other `Content-Type` is answered with 415, so a plain JSON body is never
+/// given merge-patch semantics. Type errors, including `null` on a required
+/// field, are answered with 422.
+pub fn merge_patch<T: DeserializeOwned>(req: &HttpRequest, body: &[u8]) -> Result<T, ApiError> {
+    if req.content_type() != MERGE_PATCH_CONTENT_TYPE {
+        return Err(ApiError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_media_type",
+            format!("Expected a merge-patch body (Content-Type: {})", MERGE_PATCH_CONTENT_TYPE),
+        ));
+    }
+
+    from_merge_patch(body).map_err(|e| ApiError::unprocessable(format!("Invalid patch document: {}", e)))
+}
+
+/// Query string extractor that answers with the standard error body
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|err, req| query_error(&err, req).into())
+}
+
+fn json_error(err: &JsonPayloadError, req: &HttpRequest) -> ApiError {
+    let error = match err {
+        JsonPayloadError::ContentType => ApiError::new(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported_media_type",
+            "Expected a JSON body (Content-Type: application/json)",
+        ),
+        JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+            ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", err.to_string())
+        }
+        // Well-formed body with a value outside an enum, e.g. an unknown enrollment status;
+        // serde's message lists the accepted values
+        JsonPayloadError::Deserialize(e) if e.is_data() && e.to_string().starts_with("unknown variant") => {
+            ApiError::unprocessable(format!("Invalid value: {}", e)).with_code("invalid_value")
+        }
+        _ => ApiError::bad_request(format!("Invalid JSON body: {}", err)).with_code("invalid_json"),
+    };
+
+    error.with_request_id(request_id(req))
+}
+
+fn query_error(err: &QueryPayloadError, req: &HttpRequest) -> ApiError {
+    let message = match err {
+        QueryPayloadError::Deserialize(err) => err.to_string(),
+        other => other.to_string(),
+    };
+
+    let error = match QueryParamError::parse(&message) {
+        Some(param) => ApiError::bad_request(param.to_string()).with_details(param),
+        None => ApiError::bad_request(format!("Invalid query string: {}", message)),
+    };
+
+    error.with_code(INVALID_QUERY_PARAMETER).with_request_id(request_id(req))
+}
+
+/// A query parameter with a value of the wrong type or out of range
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryParamError {
+    /// Name of the parameter
+    pub parameter: String,
+    /// Description of the accepted values
+    pub expected: String,
+}
+
+impl QueryParamError {
+    pub fn new(parameter: impl Into<String>, expected: impl Into<String>) -> Self {
+        Self {
+            parameter: parameter.into(),
+            expected: expected.into(),
+        }
+    }
+
+    /// Recovers the error from its `Display` output
+    ///
+    /// Serde only hands the message of a `try_from` failure to the extractor,
+    /// so the parameter has to be read back from the text. Serde's own
+    /// "missing field" errors are recognized as well.
+    fn parse(message: &str) -> Option<Self> {
+        if let Some(rest) = message.strip_prefix("Invalid value for `") {
+            let (parameter, expected) = rest.split_once("`: expected ")?;
+            return Some(Self::new(parameter, expected));
+        }
+
+        let parameter = message.strip_prefix("missing field `")?.strip_suffix('`')?;
+        Some(Self::new(parameter, "a value"))
+    }
+}
+
+impl fmt::Display for QueryParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid value for `{}`: expected {}", self.parameter, self.expected)
+    }
+}
+
+/// Raw query string parameters, read into typed fields with validation
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct QueryParams(HashMap<String, String>);
+
+impl QueryParams {
+    /// Raw value of a parameter; empty values count as absent
+    fn raw(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str).filter(|value| !value.is_empty())
+    }
+
+    /// Free text parameter
+    pub fn string(&self, name: &str) -> Option<String> {
+        self.raw(name).map(str::to_string)
+    }
+
+    /// Page number (1 or greater)
+    pub fn page<T>(&self, name: &str) -> Result<Option<T>, QueryParamError>
+    where
+        T: FromStr + PartialOrd + From<u8>,
+    {
+        self.number(name, T::from(1), None, "an integer greater than or equal to 1")
+    }
+
+    /// Page size (between 1 and [`MAX_PAGE_SIZE`])
+    pub fn page_size<T>(&self, name: &str) -> Result<Option<T>, QueryParamError>
+    where
+        T: FromStr + PartialOrd + From<u8>,
+    {
+        self.number(
+            name,
+            T::from(1),
+            Some(T::from(MAX_PAGE_SIZE)),
+            &format!("an integer between 1 and {}", MAX_PAGE_SIZE),
+        )
+    }
+
+    /// Academic year (between [`MIN_YEAR`] and [`MAX_YEAR`])
+    pub fn year(&self, name: &str) -> Result<Option<i32>, QueryParamError> {
+        self.number(
+            name,
+            MIN_YEAR,
+            Some(MAX_YEAR),
+            &format!("a year between {} and {}", MIN_YEAR, MAX_YEAR),
+        )
+    }
+
+    /// Value deserialized from its text form (UUIDs, dates, enums, ...)
+    pub fn value<T: DeserializeOwned>(&self, name: &str, expected: &str) -> Result<Option<T>, QueryParamError> {
+        self.raw(name)
+            .map(|value| {
+                let deserializer: StrDeserializer<'_, serde::de::value::Error> = value.into_deserializer();
+                T::deserialize(deserializer).map_err(|_| QueryParamError::new(name, expected))
+            })
+            .transpose()
+    }
+
+    /// Number of at least `min` and, if given, at most `max`
+    pub fn number<T>(&self, name: &str, min: T, max: Option<T>, expected: &str) -> Result<Option<T>, QueryParamError>
+    where
+        T: FromStr + PartialOrd,
+    {
+        self.raw(name)
+            .map(|value| {
+                value
+                    .parse::<T>()
+                    .ok()
+                    .filter(|n| *n >= min && max.as_ref().is_none_or(|max| n <= max))
+                    .ok_or_else(|| QueryParamError::new(name, expected))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse};
+    use chrono::NaiveDate;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(try_from = "QueryParams")]
+    struct ListQuery {
+        page: Option<u32>,
+        per_page: Option<u32>,
+        year: Option<i32>,
+        since: Option<NaiveDate>,
+    }
+
+    impl TryFrom<QueryParams> for ListQuery {
+        type Error = QueryParamError;
+
+        fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+            Ok(ListQuery {
+                page: params.page("page")?,
+                per_page: params.page_size("per_page")?,
+                year: params.year("year")?,
+                since: params.value("since", "a date (YYYY-MM-DD)")?,
+            })
+        }
+    }
+
+    fn parse(qs: &str) -> Result<ListQuery, String> {
+        web::Query::<ListQuery>::from_query(qs)
+            .map(web::Query::into_inner)
+            .map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_valid_parameters() {
+        let query = parse("page=2&per_page=100&year=2025&since=2025-03-01").unwrap();
+
+        assert_eq!(query.page, Some(2));
+        assert_eq!(query.per_page, Some(100));
+        assert_eq!(query.year, Some(2025));
+        assert_eq!(query.since, NaiveDate::from_ymd_opt(2025, 3, 1));
+    }
+
+    #[test]
+    fn test_empty_values_are_absent() {
+        let query = parse("page=&year=").unwrap();
+
+        assert_eq!(query.page, None);
+        assert_eq!(query.year, None);
+    }
+
+    #[test]
+    fn test_malformed_value_names_parameter() {
+        let err = parse("page=abc").unwrap_err();
+
+        assert!(err.contains("`page`"));
+    }
+
+    #[test]
+    fn test_out_of_range_values() {
+        assert!(parse("page=0").is_err());
+        assert!(parse("per_page=101").is_err());
+        assert!(parse("year=1999").is_err());
+        assert!(parse("year=2101").is_err());
+        assert!(parse("since=01/03/2025").is_err());
+    }
+
+    #[test]
+    fn test_error_message_round_trip() {
+        let error = QueryParamError::new("per_page", "an integer between 1 and 100");
+
+        assert_eq!(QueryParamError::parse(&error.to_string()), Some(error));
+        assert_eq!(
+            QueryParamError::parse("missing field `year`"),
+            Some(QueryParamError::new("year", "a value"))
+        );
+        assert_eq!(QueryParamError::parse("unknown variant `x`"), None);
+    }
+
+    async fn list(_query: web::Query<ListQuery>) -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_query_error_body() {
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(query_config())
+                .route("/list", web::get().to(list)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/list?year=banana")
+            .insert_header(("X-Request-Id", "req-7"))
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["error"], INVALID_QUERY_PARAMETER);
+        assert_eq!(body["details"]["parameter"], "year");
+        assert_eq!(body["details"]["expected"], "a year between 2000 and 2100");
+        assert_eq!(body["request_id"], "req-7");
+    }
+
+    #[actix_rt::test]
+    async fn test_json_error_body() {
+        async fn echo(body: web::Json<serde_json::Value>) -> HttpResponse {
+            HttpResponse::Ok().json(body.into_inner())
+        }
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(json_config())
+                .route("/echo", web::post().to(echo)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::post()
+            .uri("/echo")
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload("{not json")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["error"], "invalid_json");
+    }
+
+    #[actix_rt::test]
+    async fn test_unknown_enum_value_is_unprocessable() {
+        use crate::models::enrollment::NewEnrollment;
+
+        async fn enroll(body: web::Json<NewEnrollment>) -> HttpResponse {
+            HttpResponse::Ok().json(body.into_inner())
+        }
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(json_config())
+                .route("/enroll", web::post().to(enroll)),
+        )
+        .await;
+        let request = |status: &str| {
+            actix_web::test::TestRequest::post()
+                .uri("/enroll")
+                .set_json(serde_json::json!({
+                    "student_id": uuid::Uuid::new_v4(),
+                    "course_id": uuid::Uuid::new_v4(),
+                    "academic_year": 2025,
+                    "status": status,
+                }))
+                .to_request()
+        };
+
+        let resp = actix_web::test::call_service(&app, request("actve")).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["error"], "invalid_value");
+        let message = body["message"].as_str().unwrap();
+        for allowed in ["active", "withdrawn", "completed", "on_hold", "pending"] {
+            assert!(message.contains(allowed), "{}", message);
+        }
+
+        let resp = actix_web::test::call_service(&app, request("on_hold")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["status"], "on_hold");
+    }
+
+    #[test]
+    fn test_merge_patch_requires_its_media_type() {
+        use crate::models::{user::PatchUserDto, Patch};
+        use actix_web::ResponseError;
+
+        let request = |content_type: &str| {
+            actix_web::test::TestRequest::patch()
+                .insert_header(("Content-Type", content_type))
+                .to_http_request()
+        };
+        let patch_request = request(MERGE_PATCH_CONTENT_TYPE);
+
+        let error = merge_patch::<PatchUserDto>(&request("application/json"), br#"{"phone": null}"#).unwrap_err();
+        assert_eq!(error.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let patch: PatchUserDto = merge_patch(&patch_request, br#"{"phone": null}"#).unwrap();
+        assert_eq!(patch.phone, Patch::Null);
+        assert!(patch.full_name.is_none());
+
+        for body in [br#"{"full_name": null}"#.as_slice(), br#"{"birth_date": 5}"#] {
+            let error = merge_patch::<PatchUserDto>(&patch_request, body).unwrap_err();
+            assert_eq!(error.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Admin web UI, served from `frontend.dir` at `/app`.
+//!
+//! Files under `frontend.assets_dir` carry a content hash in their name and
+//! are cached as immutable; everything else, `index.html` included, is
+//! revalidated on each load. A path without a file extension that matches no
+//! file gets `index.html`, so client-side routes survive a reload. Only
+//! `/app` is mounted here, so `/api` and `/system` never reach these files.
+
+use std::path::{Path, PathBuf};
+
+use actix_files::{Files, NamedFile};
+use actix_web::dev::{fn_service, Service, ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderValue, CACHE_CONTROL};
+use actix_web::{web, HttpResponse, Scope};
+
+use crate::config::FrontendConfig;
+
+/// Path the web UI is served under
+pub const MOUNT_PATH: &str = "/app";
+
+/// `Cache-Control` of the hashed assets: a new build changes their names
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+
+/// `Cache-Control` of every other file, so a deploy is picked up on the next load
+const REVALIDATE: &str = "no-cache";
+
+/// Serves the files in `dir` at [`MOUNT_PATH`], falling back to the index page
+pub fn scope(
+    dir: &Path,
+    config: &FrontendConfig,
+) -> Scope<impl ServiceFactory<ServiceRequest, Config = (), Response = ServiceResponse, Error = actix_web::Error, InitError = ()>>
+{
+    let index = dir.join(&config.index_file);
+    let assets = format!("{}/{}/", MOUNT_PATH, config.assets_dir.trim_matches('/'));
+
+    web::scope(MOUNT_PATH)
+        .wrap_fn(move |req, srv| {
+            let immutable = req.path().starts_with(&assets);
+            let response = srv.call(req);
+            async move {
+                let mut response = response.await?;
+                if response.status().is_success() {
+                    let policy = if immutable { IMMUTABLE } else { REVALIDATE };
+                    response.headers_mut().insert(CACHE_CONTROL, HeaderValue::from_static(policy));
+                }
+                Ok(response)
+            }
+        })
+        .service(
+            Files::new("", dir)
+                .index_file(config.index_file.clone())
+                .default_handler(fn_service(move |req: ServiceRequest| spa_fallback(req, index.clone()))),
+        )
+}
+
+/// `index.html` for client-side routes, 404 for a missing file
+async fn spa_fallback(req: ServiceRequest, index: PathBuf) -> Result<ServiceResponse, actix_web::Error> {
+    let (req, _) = req.into_parts();
+    let last_segment = req.path().rsplit('/').next().unwrap_or_default();
+    if last_segment.contains('.') {
+        return Ok(ServiceResponse::new(req, HttpResponse::NotFound().finish()));
+    }
+
+    let response = NamedFile::open_async(&index).await?.into_response(&req);
+    Ok(ServiceResponse::new(req, response))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::{header, StatusCode};
+    use actix_web::{test, App};
+
+    const INDEX: &str = "<!doctype html><title>SAI</title><div id=\"app\"></div>";
+
+    /// Build output with an index page and one hashed asset
+    fn build_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sai-frontend-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("assets")).unwrap();
+        std::fs::write(dir.join("index.html"), INDEX).unwrap();
+        std::fs::write(dir.join("assets/index-4f2a9c1e.js"), "console.log('sai')").unwrap();
+        std::fs::write(dir.join("favicon.ico"), [0u8; 4]).unwrap();
+        dir
+    }
+
+    fn frontend(dir: &Path) -> FrontendConfig {
+        FrontendConfig { dir: Some(dir.to_path_buf()), ..Default::default() }
+    }
+
+    fn cache_control(response: &ServiceResponse) -> &str {
+        response.headers().get(header::CACHE_CONTROL).unwrap().to_str().unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_hashed_asset_cached_as_immutable() {
+        let dir = build_dir();
+        let app = test::init_service(App::new().service(scope(&dir, &frontend(&dir)))).await;
+
+        let req = test::TestRequest::get().uri("/app/assets/index-4f2a9c1e.js").to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get(header::CONTENT_TYPE).unwrap().to_str().unwrap().contains("javascript"));
+        assert_eq!(cache_control(&resp), IMMUTABLE);
+
+        // Files outside the assets directory keep their names across builds
+        let req = test::TestRequest::get().uri("/app/favicon.ico").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(cache_control(&resp), REVALIDATE);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_client_routes_get_the_index_page() {
+        let dir = build_dir();
+        let app = test::init_service(App::new().service(scope(&dir, &frontend(&dir)))).await;
+
+        for uri in ["/app/", "/app/students/42/grades"] {
+            let req = test::TestRequest::get().uri(uri).to_request();
+            let resp = test::call_service(&app, req).await;
+
+            assert_eq!(resp.status(), StatusCode::OK, "{}", uri);
+            assert!(resp.headers().get(header::CONTENT_TYPE).unwrap().to_str().unwrap().starts_with("text/html"));
+            assert_eq!(cache_control(&resp), REVALIDATE);
+            assert_eq!(test::read_body(resp).await, INDEX.as_bytes(), "{}", uri);
+        }
+
+        // A missing file is not a client route
+        let req = test::TestRequest::get().uri("/app/assets/index-00000000.js").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_api_never_falls_through_to_the_index_page() {
+        let dir = build_dir();
+        let config = frontend(&dir);
+        let app = test::init_service(
+            App::new()
+                .service(crate::routes::configure())
+                .service(crate::routes::configure_system_routes())
+                .configure(|cfg| crate::routes::configure_frontend(cfg, &config)),
+        )
+        .await;
+
+        for uri in ["/api/unknown/route", "/api/app/", "/system/unknown", "/students/42"] {
+            let req = test::TestRequest::get().uri(uri).to_request();
+            let resp = test::call_service(&app, req).await;
+
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND, "{}", uri);
+            assert_ne!(test::read_body(resp).await, INDEX.as_bytes(), "{}", uri);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
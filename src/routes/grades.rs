@@ -0,0 +1,231 @@
+use actix_web::{
+    get,
+    web::{self, Data, Path, Query},
+    HttpRequest, HttpResponse, Scope,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    models::grade::MAX_PERIOD,
+    routes::{
+        auth::{bearer_claims, is_staff, Claims},
+        extractors::{QueryParamError, QueryParams},
+        response::{ApiError, ApiResponse},
+    },
+    services::grades::{can_view_course_grades, GradeService, GradeViewer},
+};
+
+/// Query parameters accepted by the grade endpoints
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct GradeQuery {
+    pub year: Option<i32>,
+    /// Grading period (trimester), from 1 to `MAX_PERIOD`
+    pub period: Option<i16>,
+}
+
+impl TryFrom<QueryParams> for GradeQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        let expected = format!("an integer between 1 and {}", MAX_PERIOD);
+        let period = params.number("period", 1, Some(MAX_PERIOD), &expected)?;
+
+        Ok(GradeQuery {
+            year: params.year("year")?,
+            period,
+        })
+    }
+}
+
+/// Output format of the gradebook
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GradeBookFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Query parameters accepted by `GET /api/courses/{id}/gradebook`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct GradeBookQuery {
+    pub trimester: Option<u8>,
+    pub format: GradeBookFormat,
+}
+
+impl TryFrom<QueryParams> for GradeBookQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        let expected = format!("an integer between 1 and {}", MAX_PERIOD);
+        let trimester = params.number("trimester", 1, Some(MAX_PERIOD as u8), &expected)?;
+
+        Ok(GradeBookQuery {
+            trimester,
+            format: params.value("format", "one of json, csv")?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Maps the token role to the grade authorization matrix
+fn viewer(claims: &Claims) -> Result<GradeViewer, ApiError> {
+    let own_id = || Uuid::parse_str(&claims.sub).map_err(|_| ApiError::unauthorized("Invalid subject in token"));
+
+    Ok(match claims.role.as_str() {
+        "teacher" => GradeViewer::Teacher(own_id()?),
+        "parent" => GradeViewer::Parent(own_id()?),
+        "student" => GradeViewer::Student(own_id()?),
+        // Admin, director, secretary and accountant
+        role if is_staff(role) => GradeViewer::Staff,
+        _ => return Err(ApiError::forbidden("This role cannot view grades")),
+    })
+}
+
+fn request_viewer(req: &HttpRequest) -> Result<GradeViewer, ApiError> {
+    let claims = bearer_claims(req).ok_or_else(|| ApiError::unauthorized("Authentication required"))?;
+    viewer(&claims)
+}
+
+/// Grades of a student grouped by course, one summary per period.
+/// Registered in the students scope as `/students/{id}/grades`.
+#[get("/{id}/grades")]
+pub(crate) async fn get_student_grades(
+    req: HttpRequest,
+    path: Path<Uuid>,
+    query: Query<GradeQuery>,
+    service: Data<GradeService>,
+) -> Result<HttpResponse, ApiError> {
+    let viewer = request_viewer(&req)?;
+    let courses = service
+        .student_grades(viewer, path.into_inner(), query.year, query.period)
+        .await?;
+
+    Ok(ApiResponse::new(courses).ok())
+}
+
+/// Grades of every student enrolled in a course (teacher view).
+/// Registered in the courses scope as `/courses/{id}/grades`.
+#[get("/{id}/grades")]
+pub(crate) async fn get_course_grades(
+    req: HttpRequest,
+    path: Path<Uuid>,
+    query: Query<GradeQuery>,
+    service: Data<GradeService>,
+) -> Result<HttpResponse, ApiError> {
+    let viewer = request_viewer(&req)?;
+    let students = service.course_grades(viewer, path.into_inner(), query.period).await?;
+
+    Ok(ApiResponse::new(students).ok())
+}
+
+/// Gradebook of a course: students as rows, assessments as columns.
+/// Registered in the courses scope as `/courses/{id}/gradebook`.
+#[get("/{id}/gradebook")]
+pub(crate) async fn get_grade_book(
+    req: HttpRequest,
+    path: Path<Uuid>,
+    query: Query<GradeBookQuery>,
+    service: Data<GradeService>,
+) -> Result<HttpResponse, ApiError> {
+    let viewer = request_viewer(&req)?;
+    let book = service.get_grade_book(path.into_inner(), query.trimester).await?;
+
+    if !can_view_course_grades(viewer, book.course.teacher_id) {
+        return Err(ApiError::forbidden("Only the course teacher can view its gradebook"));
+    }
+
+    match query.format {
+        GradeBookFormat::Json => Ok(ApiResponse::new(book).ok()),
+        GradeBookFormat::Csv => {
+            let body = book.to_csv().map_err(|e| {
+                log::error!("Failed to export gradebook: {}", e);
+                ApiError::internal("Failed to export gradebook")
+            })?;
+
+            Ok(HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .insert_header((
+                    "Content-Disposition",
+                    format!("attachment; filename=\"planilla-{}.csv\"", book.course.code),
+                ))
+                .body(body))
+        }
+    }
+}
+
+/// Grading scale used for the labels
+#[get("/scale")]
+async fn get_grading_scale(service: Data<GradeService>) -> HttpResponse {
+    ApiResponse::new(service.scale().bands()).ok()
+}
+
+pub fn routes() -> Scope {
+    web::scope("/grades")
+        .service(get_grading_scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::ResponseError;
+
+    fn claims(role: &str, sub: Uuid) -> Claims {
+        Claims {
+            sub: sub.to_string(),
+            role: role.to_string(),
+            status: Default::default(),
+            exp: 0,
+            iat: 0,
+            iss: None,
+            aud: None,
+            impersonator: None,
+            institution: None,
+            must_change_password: false,
+            token_version: None,
+        }
+    }
+
+    #[test]
+    fn test_roles_map_to_viewers() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(viewer(&claims("admin", id)).unwrap(), GradeViewer::Staff);
+        assert_eq!(viewer(&claims("secretary", id)).unwrap(), GradeViewer::Staff);
+        assert_eq!(viewer(&claims("teacher", id)).unwrap(), GradeViewer::Teacher(id));
+        assert_eq!(viewer(&claims("parent", id)).unwrap(), GradeViewer::Parent(id));
+        assert_eq!(viewer(&claims("student", id)).unwrap(), GradeViewer::Student(id));
+    }
+
+    #[test]
+    fn test_roles_outside_staff_are_forbidden() {
+        for role in ["user", "guest", ""] {
+            let error = viewer(&claims(role, Uuid::new_v4())).unwrap_err();
+            assert_eq!(error.status_code(), actix_web::http::StatusCode::FORBIDDEN, "{}", role);
+        }
+    }
+
+    #[test]
+    fn test_query_validates_period() {
+        let q = Query::<GradeQuery>::from_query("year=2025&period=2").unwrap();
+        assert_eq!((q.year, q.period), (Some(2025), Some(2)));
+
+        assert!(Query::<GradeQuery>::from_query("period=0").is_err());
+        assert!(Query::<GradeQuery>::from_query("period=5").is_err());
+        assert!(Query::<GradeQuery>::from_query("year=1990").is_err());
+    }
+
+    #[test]
+    fn test_gradebook_query() {
+        let q = Query::<GradeBookQuery>::from_query("trimester=1&format=csv").unwrap();
+        assert_eq!((q.trimester, q.format), (Some(1), GradeBookFormat::Csv));
+
+        let q = Query::<GradeBookQuery>::from_query("").unwrap();
+        assert_eq!((q.trimester, q.format), (None, GradeBookFormat::Json));
+
+        assert!(Query::<GradeBookQuery>::from_query("trimester=0").is_err());
+        assert!(Query::<GradeBookQuery>::from_query("format=xlsx").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Audit trail of mutating API calls
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::{header, Method},
+    web, Error, HttpMessage, HttpRequest,
+};
+use futures::future::{self, LocalBoxFuture};
+use uuid::Uuid;
+
+use crate::models::audit_log::{AuditAction, NewAuditLogEntry};
+use crate::routes::auth::{bearer_claims, Claims};
+use crate::state::AppState;
+
+/// Id of the audit event recorded for a request, stored in its extensions by [`AuditTrail`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditLogId(pub Uuid);
+
+/// Middleware that records every POST, PUT, PATCH and DELETE in the audit log
+///
+/// The event is recorded once the handler has answered, with the action
+/// derived from the method, the entity from the path and the method, path
+/// and status code as the new value. The actor comes from the [`Claims`]
+/// stored by an outer middleware such as `RequireAdmin`, or from the bearer
+/// token. A failure to record is logged and never fails the request.
+pub struct AuditTrail;
+
+impl<S, B> Transform<S, ServiceRequest> for AuditTrail
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AuditTrailMiddleware<S>;
+    type InitError = ();
+    type Future = future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ok(AuditTrailMiddleware { service })
+    }
+}
+
+pub struct AuditTrailMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for AuditTrailMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let Some(action) = audit_action(req.method()) else {
+            return Box::pin(self.service.call(req));
+        };
+
+        let claims = req.extensions().get::<Claims>().cloned().or_else(|| bearer_claims(req.request()));
+        let mut entry = audit_context(req.request(), claims.as_ref());
+        let (entity_type, entity_id) = audited_entity(req.path());
+        entry.action = action.to_string();
+        entry.entity_type = entity_type;
+        entry.entity_id = entity_id;
+
+        let state = req.app_data::<web::Data<AppState>>().cloned();
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let response = self.service.call(req);
+
+        Box::pin(async move {
+            let res = response.await?;
+
+            let Some(state) = state else {
+                log::warn!("AppState is not registered as app data; {} {} was not audited", method, path);
+                return Ok(res);
+            };
+
+            entry.new_value = Some(serde_json::json!({
+                "method": method,
+                "path": path,
+                "status": res.status().as_u16(),
+            }));
+            match state.services.audit.log_action(entry).await {
+                Ok(id) => {
+                    res.request().extensions_mut().insert(AuditLogId(id));
+                }
+                Err(e) => log::error!("Failed to audit {} {}: {}", method, path, e),
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// Actor, IP address and user agent of an audited request
+pub(crate) fn audit_context(req: &HttpRequest, claims: Option<&Claims>) -> NewAuditLogEntry {
+    NewAuditLogEntry {
+        actor_id: claims.and_then(|claims| Uuid::parse_str(&claims.sub).ok()),
+        ip_address: req.connection_info().realip_remote_addr().map(str::to_string),
+        user_agent: req
+            .headers()
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        ..Default::default()
+    }
+}
+
+/// Audit action of a request method; `None` for methods that do not modify data
+fn audit_action(method: &Method) -> Option<AuditAction> {
+    match *method {
+        Method::POST => Some(AuditAction::Create),
+        Method::PUT | Method::PATCH => Some(AuditAction::Update),
+        Method::DELETE => Some(AuditAction::Delete),
+        _ => None,
+    }
+}
+
+/// Entity type and id addressed by a path
+///
+/// The type is the first segment after the `/api` and `/admin` prefixes and
+/// the id the first segment that is a UUID:
+/// `/api/admin/students/{id}` is `("students", Some(id))`.
+fn audited_entity(path: &str) -> (String, Option<Uuid>) {
+    let mut segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .skip_while(|segment| matches!(*segment, "api" | "admin"))
+        .peekable();
+
+    let entity_type = segments.peek().map_or("unknown", |segment| *segment).to_string();
+    let entity_id = segments.find_map(|segment| Uuid::parse_str(segment).ok());
+
+    (entity_type, entity_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, App, HttpResponse};
+
+    #[test]
+    fn test_audit_action_from_method() {
+        assert_eq!(audit_action(&Method::POST), Some(AuditAction::Create));
+        assert_eq!(audit_action(&Method::PUT), Some(AuditAction::Update));
+        assert_eq!(audit_action(&Method::PATCH), Some(AuditAction::Update));
+        assert_eq!(audit_action(&Method::DELETE), Some(AuditAction::Delete));
+        assert_eq!(audit_action(&Method::GET), None);
+        assert_eq!(audit_action(&Method::HEAD), None);
+        assert_eq!(audit_action(&Method::OPTIONS), None);
+    }
+
+    #[test]
+    fn test_audited_entity() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(audited_entity(&format!("/api/admin/students/{}", id)), ("students".to_string(), Some(id)));
+        assert_eq!(
+            audited_entity(&format!("/api/admin/users/{}/verify-email", id)),
+            ("users".to_string(), Some(id))
+        );
+        assert_eq!(audited_entity("/api/admin/students/batch-withdraw"), ("students".to_string(), None));
+        assert_eq!(audited_entity("/api/courses"), ("courses".to_string(), None));
+        assert_eq!(audited_entity("/api/admin"), ("unknown".to_string(), None));
+    }
+
+    #[actix_rt::test]
+    async fn test_requests_pass_through_without_state() {
+        let app = actix_web::test::init_service(
+            App::new().service(
+                web::scope("/admin")
+                    .wrap(AuditTrail)
+                    .route("/news", web::get().to(HttpResponse::Ok))
+                    .route("/news", web::post().to(HttpResponse::Created)),
+            ),
+        )
+        .await;
+
+        // Reads are never audited and writes still reach the handler when recording is not possible
+        let req = actix_web::test::TestRequest::get().uri("/admin/news").to_request();
+        assert_eq!(actix_web::test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        let req = actix_web::test::TestRequest::post().uri("/admin/news").to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::CREATED);
+        assert!(resp.request().extensions().get::<AuditLogId>().is_none());
+    }
+}
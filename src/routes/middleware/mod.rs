@@ -0,0 +1,3 @@
+//! Middleware shared by several route scopes
+
+pub mod audit;
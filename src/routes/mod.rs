@@ -1,9 +1,20 @@
 //! Routes module for the SAI API.
 //! This module defines all the HTTP routes and handlers for the application.
 
-use actix_web::{web, Scope};
+use std::sync::Arc;
+
+use actix_web::dev::{Extensions, Service, ServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::{web, HttpRequest, HttpResponse, Scope};
+use futures::future::{self, Either};
+
+use crate::config::{AppConfig, FrontendConfig};
+use crate::db::{DbPool, DbPools};
+use crate::services::features::{Feature, FeatureFlags};
+use crate::state::AppState;
 
 // Import submodules
+pub mod extractors;
+pub mod response;
 mod users;
 mod students;
 mod courses;
@@ -11,23 +22,120 @@ mod teachers;
 mod attendance;
 mod grades;
 mod schedules;
-mod reports;
-mod auth;
+pub mod auth;
 mod admin;
+pub mod cache;
+pub mod middleware;
+pub mod features;
+pub mod tenant;
+#[cfg(feature = "frontend")]
+pub mod frontend;
 
 /// Configure all API routes
-pub fn configure() -> Scope {
+///
+/// Tokens of accounts that still use a temporary password are rejected here,
+/// before routing, for everything but the password change itself (see
+/// `auth::require_password_changed`). Every scope but `/auth` runs with the
+/// state of the institution named by the token (see `tenant::TenantScope`);
+/// accounts and sessions live in `public`.
+pub fn configure() -> Scope<
+    impl ServiceFactory<ServiceRequest, Config = (), Response = ServiceResponse, Error = actix_web::Error, InitError = ()>,
+> {
     web::scope("/api")
+        .wrap_fn(|req, srv| match auth::require_password_changed(req.request()) {
+            Ok(()) => Either::Left(srv.call(req)),
+            Err(e) => Either::Right(future::ready(Ok(req.error_response(e)))),
+        })
+        .app_data(extractors::json_config())
+        .app_data(extractors::payload_config())
+        .app_data(extractors::query_config())
         .service(auth::routes())
-        .service(users::routes())
-        .service(students::routes())
-        .service(teachers::routes())
-        .service(courses::routes())
-        .service(attendance::routes())
-        .service(grades::routes())
-        .service(schedules::routes())
-        .service(reports::routes())
-        .service(admin::routes())
+        .service(
+            web::scope("")
+                .wrap(tenant::TenantScope)
+                .service(users::routes())
+                .service(students::routes())
+                .service(teachers::routes())
+                .service(courses::routes())
+                .service(attendance::routes())
+                .service(grades::routes())
+                .service(schedules::routes())
+                .service(admin::routes()),
+        )
+}
+
+/// Register the application state, the token signer, the database pool and
+/// every service as app data
+///
+/// Admin, user, student and teacher handlers take `web::Data<AppState>`; the
+/// other handlers still extract each service either as `web::Data<Arc<T>>` or
+/// as `web::Data<T>`, sharing the instances held by the state.
+pub fn configure_app_data(cfg: &mut web::ServiceConfig, pool: &DbPool) {
+    configure_app_data_with_pools(cfg, &DbPools::primary_only(pool.clone()));
+}
+
+/// Like `configure_app_data`, routing read-only paths to the replica pool
+pub fn configure_app_data_with_pools(cfg: &mut web::ServiceConfig, pools: &DbPools) {
+    configure_app_data_with_state(cfg, pools, &AppState::new(pools, AppConfig::from_env()));
+}
+
+/// Like `configure_app_data_with_pools`, reusing an already built state so
+/// every worker shares the same services
+pub fn configure_app_data_with_state(cfg: &mut web::ServiceConfig, pools: &DbPools, state: &AppState) {
+    register_state(cfg, pools, state);
+}
+
+/// The app data of `configure_app_data_with_state` for the state of a tenant,
+/// as a container that `tenant::TenantScope` adds to the request
+pub(crate) fn tenant_app_data(state: &AppState) -> Extensions {
+    let mut data = Extensions::new();
+    register_state(&mut data, &state.db.pools(), state);
+    data
+}
+
+/// Where app data is registered: the app itself, or a per-request container
+trait AppData {
+    fn add<T: 'static>(&mut self, data: T);
+}
+
+impl AppData for web::ServiceConfig {
+    fn add<T: 'static>(&mut self, data: T) {
+        self.app_data(data);
+    }
+}
+
+impl AppData for Extensions {
+    fn add<T: 'static>(&mut self, data: T) {
+        self.insert(data);
+    }
+}
+
+fn register_state(cfg: &mut impl AppData, pools: &DbPools, state: &AppState) {
+    let services = &state.services;
+
+    cfg.add(web::Data::new(state.clone()));
+    cfg.add(web::Data::from(state.auth.clone()));
+    cfg.add(web::Data::new(state.db_pool.clone()));
+    cfg.add(web::Data::new(pools.clone()));
+    register_service(cfg, &services.users);
+    register_service(cfg, &services.students);
+    register_service(cfg, &services.teachers);
+    register_service(cfg, &services.courses);
+    register_service(cfg, &services.attendance);
+    register_service(cfg, &services.grades);
+    register_service(cfg, &services.schedules);
+    register_service(cfg, &services.reports);
+    register_service(cfg, &services.notifications);
+    register_service(cfg, &services.payments);
+    register_service(cfg, &services.enrollments);
+    register_service(cfg, &services.audit);
+    register_service(cfg, &services.admin);
+    register_service(cfg, &services.features);
+}
+
+fn register_service<T: 'static>(cfg: &mut impl AppData, service: &Arc<T>) {
+    cfg.add(web::Data::new(service.clone()));
+    cfg.add(web::Data::from(service.clone()));
 }
 
 /// Configure health check and system status routes
@@ -35,25 +143,104 @@ pub fn configure_system_routes() -> Scope {
     web::scope("/system")
         .route("/health", web::get().to(health_check))
         .route("/status", web::get().to(system_status))
+        .route("/metrics", web::get().to(metrics))
+}
+
+/// Mount the web UI at `/app` when `frontend.dir` is set
+///
+/// Without the `frontend` feature nothing is mounted; `sai serve` warns when
+/// the directory is configured anyway.
+pub fn configure_frontend(cfg: &mut web::ServiceConfig, config: &FrontendConfig) {
+    #[cfg(feature = "frontend")]
+    if let Some(dir) = &config.dir {
+        cfg.service(frontend::scope(dir, config));
+    }
+    #[cfg(not(feature = "frontend"))]
+    let _ = (cfg, config);
 }
 
-/// Simple health check handler
-async fn health_check() -> &'static str {
-    "OK"
+/// Health check: 200 while the database is reachable and fully migrated, 503 otherwise
+///
+/// Served at `/system/health` and, by `sai serve`, at `/health`.
+pub async fn health_check(state: web::Data<AppState>) -> HttpResponse {
+    let health = state.db.health().await;
+    if health.is_healthy() {
+        HttpResponse::Ok().json(health)
+    } else {
+        HttpResponse::ServiceUnavailable().json(health)
+    }
 }
 
-/// System status handler
-async fn system_status() -> web::Json<serde_json::Value> {
-    web::Json(serde_json::json!({
+/// System status handler, including the applied and pending migrations
+///
+/// Admins also get the findings of the startup self-check, run again on each request.
+async fn system_status(req: HttpRequest, state: web::Data<AppState>) -> web::Json<serde_json::Value> {
+    let migrations = match crate::db::migration_status_for(&state.db_pool, crate::db::MIGRATIONS).await {
+        Ok(status) => serde_json::json!({
+            "applied": status.applied.len(),
+            "pending": status.pending,
+            "up_to_date": status.is_up_to_date()
+        }),
+        Err(e) => {
+            log::error!("Failed to read migration status: {}", e);
+            serde_json::json!({ "error": "unavailable" })
+        }
+    };
+
+    let config = state.config.current();
+    let environment = config.environment;
+    let mut status = serde_json::json!({
         "status": "running",
         "version": env!("CARGO_PKG_VERSION"),
-        "environment": std::env::var("APP_ENVIRONMENT").unwrap_or_else(|_| "development".to_string())
-    }))
+        "environment": environment,
+        "profile": environment.profile(),
+        "migrations": migrations,
+        "replica_fallbacks": crate::db::replica_fallback_count()
+    });
+    if auth::bearer_claims(&req).is_some_and(|claims| claims.role == "admin") {
+        status["self_check"] = serde_json::json!(crate::startup::self_check(&config, &state.db_pool).await);
+    }
+    web::Json(status)
+}
+
+/// Connection pool and query metrics in the Prometheus text format
+async fn metrics(state: web::Data<AppState>) -> HttpResponse {
+    let stats = crate::db::metrics::pool_stats(&state.db_pool);
+    let mut body = crate::db::metrics::render_prometheus(&stats);
+    body.push_str(&crate::db::metrics::render_query_prometheus(&crate::db::metrics::query_stats()));
+
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(body)
+}
+
+/// Handles a notification from the LISTEN/NOTIFY bridge
+///
+/// Cache invalidations and feature flag changes from other instances are
+/// applied locally (this instance already applied its own). Events have no
+/// consumer yet and are only logged.
+pub fn handle_bridge_notification(features: &FeatureFlags, notification: crate::db::pubsub::Notification) {
+    use crate::db::pubsub::{CacheInvalidation, Event, CACHE_INVALIDATION_CHANNEL, EVENTS_CHANNEL};
+
+    match notification.channel.as_str() {
+        CACHE_INVALIDATION_CHANNEL if !notification.from_self => {
+            match serde_json::from_value::<CacheInvalidation>(notification.data) {
+                Ok(CacheInvalidation::FeatureFlag { name, enabled }) => match Feature::from_name(&name) {
+                    Some(feature) => features.apply_override(feature, enabled),
+                    None => log::warn!("Ignoring change of unknown feature flag {}", name),
+                },
+                Ok(invalidation) => auth::apply_cache_invalidation(&invalidation),
+                Err(e) => log::warn!("Ignoring unknown cache invalidation: {}", e),
+            }
+        }
+        EVENTS_CHANNEL => match serde_json::from_value::<Event>(notification.data) {
+            Ok(event) => log::debug!("Event {} received (from_self: {})", event.kind, notification.from_self),
+            Err(e) => log::warn!("Ignoring malformed event: {}", e),
+        },
+        _ => {}
+    }
 }
 
 // Public re-exports for easier module usage
 pub use auth::Auth;
-pub use users::User;
-pub use students::Student;
-pub use courses::Course;
 
@@ -0,0 +1,367 @@
+//! Shared JSON envelopes for API responses.
+//!
+//! Successful responses wrap their payload in [`ApiResponse`]; the HTTP status
+//! code carries the outcome, so there is no `success` flag. Failures are
+//! returned as [`ApiError`], which implements `ResponseError` and can be used
+//! directly as the error type of a handler.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use actix_web::{http::StatusCode, HttpRequest, HttpResponse, ResponseError};
+use serde::Serialize;
+
+use crate::db::DbError;
+use crate::services::ServiceError;
+
+/// Header used to correlate a response with the request logs
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Whether `500` responses carry the internal message; see [`set_verbose_errors`]
+static VERBOSE_ERRORS: AtomicBool = AtomicBool::new(true);
+
+/// Sets `server.verbose_errors` for every response built from now on.
+///
+/// When off, the message and details of internal errors are logged and the
+/// client only gets a generic message with the error code and request id.
+pub fn set_verbose_errors(verbose: bool) {
+    VERBOSE_ERRORS.store(verbose, Ordering::Relaxed);
+}
+
+/// Reads the request id sent by the client or the reverse proxy, if any
+pub fn request_id(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Envelope for successful responses
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl<T: Serialize> ApiResponse<T> {
+    /// Wraps a payload
+    pub fn new(data: T) -> Self {
+        Self {
+            data: Some(data),
+            message: None,
+            request_id: None,
+        }
+    }
+
+    /// Adds a human readable message
+    pub fn with_message(mut self, message: impl Into<String>) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    /// Adds the id of the request this response belongs to
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// `200 OK` with this envelope as body
+    pub fn ok(self) -> HttpResponse {
+        HttpResponse::Ok().json(self)
+    }
+
+    /// `201 Created` with this envelope as body
+    pub fn created(self) -> HttpResponse {
+        HttpResponse::Created().json(self)
+    }
+}
+
+impl ApiResponse<()> {
+    /// Response that only carries a message (e.g. after a delete)
+    pub fn message(message: impl Into<String>) -> Self {
+        Self {
+            data: None,
+            message: Some(message.into()),
+            request_id: None,
+        }
+    }
+}
+
+/// Error body returned by every route
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    status: StatusCode,
+    /// Stable, machine readable error code (e.g. `not_found`)
+    pub error: String,
+    /// Human readable description
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Additional details, such as one entry per invalid field
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ApiError {
+    /// Creates an error with an explicit status and code
+    pub fn new(status: StatusCode, error: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error: error.into(),
+            message: message.into(),
+            request_id: None,
+            details: None,
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "bad_request", message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthorized", message)
+    }
+
+    pub fn forbidden(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "forbidden", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "conflict", message)
+    }
+
+    pub fn unprocessable(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNPROCESSABLE_ENTITY, "unprocessable_entity", message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", message)
+    }
+
+    pub fn gateway_timeout(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::GATEWAY_TIMEOUT, "gateway_timeout", message)
+    }
+
+    /// Replaces the generic error code with a more specific one
+    pub fn with_code(mut self, error: impl Into<String>) -> Self {
+        self.error = error.into();
+        self
+    }
+
+    /// Attaches structured details to the error
+    pub fn with_details(mut self, details: impl Serialize) -> Self {
+        self.details = serde_json::to_value(details).ok();
+        self
+    }
+
+    /// Adds the id of the request this error belongs to
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.error, self.message)
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        self.status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if self.status == StatusCode::INTERNAL_SERVER_ERROR && !VERBOSE_ERRORS.load(Ordering::Relaxed) {
+            log::error!("{}: {}", self.error, self.message);
+            return HttpResponse::build(self.status).json(self.without_internals());
+        }
+        HttpResponse::build(self.status).json(self)
+    }
+}
+
+impl ApiError {
+    /// Same error with a generic message and no details, for clients that
+    /// must not see internal messages
+    fn without_internals(&self) -> ApiError {
+        ApiError {
+            status: self.status,
+            error: self.error.clone(),
+            message: "Internal server error".to_string(),
+            request_id: self.request_id.clone(),
+            details: None,
+        }
+    }
+}
+
+impl From<DbError> for ApiError {
+    fn from(err: DbError) -> Self {
+        ServiceError::from(err).into()
+    }
+}
+
+impl From<ServiceError> for ApiError {
+    fn from(err: ServiceError) -> Self {
+        match err {
+            ServiceError::NotFound(_) => ApiError::not_found(err.to_string()),
+            ServiceError::DatabaseError(DbError::NotFound(msg)) => ApiError::not_found(msg),
+            ServiceError::DatabaseError(DbError::Conflict(msg)) => ApiError::conflict(msg),
+            ServiceError::DatabaseError(DbError::Invalid(errors)) => {
+                ApiError::unprocessable("Invalid fields").with_code("validation_failed").with_details(errors)
+            }
+            ServiceError::DatabaseError(ref e) if e.is_statement_timeout() => {
+                log::warn!("Query timed out: {}", e);
+                ApiError::gateway_timeout("The request took too long to complete")
+            }
+            ServiceError::AlreadyEnrolled { course_name, .. } => {
+                ApiError::conflict(format!("Student is already enrolled in {}", course_name))
+            }
+            ServiceError::ValidationError(msg) => ApiError::bad_request(msg),
+            ServiceError::AuthenticationError(msg) => ApiError::unauthorized(msg),
+            ServiceError::AuthorizationError(msg) => ApiError::forbidden(msg),
+            ServiceError::Timeout(msg) => {
+                log::warn!("Query timed out: {}", msg);
+                ApiError::gateway_timeout("The request took too long to complete")
+            }
+            other => {
+                // Internal details stay in the logs, not in the response
+                log::error!("{}", other);
+                ApiError::internal("Internal server error")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::FieldError;
+    use actix_web::body::to_bytes;
+    use serde_json::json;
+
+    #[test]
+    fn test_success_omits_empty_fields() {
+        let body = serde_json::to_value(ApiResponse::new(vec![1, 2])).unwrap();
+
+        assert_eq!(body, json!({ "data": [1, 2] }));
+    }
+
+    #[test]
+    fn test_success_with_message_and_request_id() {
+        let response = ApiResponse::new("ok")
+            .with_message("Created")
+            .with_request_id(Some("req-1".to_string()));
+
+        let body = serde_json::to_value(response).unwrap();
+
+        assert_eq!(body, json!({ "data": "ok", "message": "Created", "request_id": "req-1" }));
+        assert!(body.get("success").is_none());
+    }
+
+    #[test]
+    fn test_message_only_response() {
+        let body = serde_json::to_value(ApiResponse::message("Deleted")).unwrap();
+
+        assert_eq!(body, json!({ "message": "Deleted" }));
+    }
+
+    #[test]
+    fn test_error_serialization_hides_status() {
+        let body = serde_json::to_value(ApiError::not_found("User not found")).unwrap();
+
+        assert_eq!(body, json!({ "error": "not_found", "message": "User not found" }));
+    }
+
+    #[actix_rt::test]
+    async fn test_error_response_uses_status() {
+        let error = ApiError::conflict("Batch rolled back").with_details(json!({ "committed": false }));
+
+        let response = error.error_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body["details"]["committed"], false);
+    }
+
+    #[test]
+    fn test_internal_message_can_be_hidden() {
+        let error = ApiError::internal("Error al leer /var/lib/sai/backup.sql: permiso denegado")
+            .with_details(json!({ "path": "/var/lib/sai" }));
+
+        let hidden = serde_json::to_value(error.without_internals()).unwrap();
+
+        assert_eq!(hidden, json!({ "error": "internal_error", "message": "Internal server error" }));
+    }
+
+    #[test]
+    fn test_service_error_mapping() {
+        assert_eq!(
+            ApiError::from(ServiceError::NotFound("Curso".to_string())).status_code(),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            ApiError::from(ServiceError::ValidationError("x".to_string())).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        let internal = ApiError::from(ServiceError::GenericError("secreto".to_string()));
+        assert_eq!(internal.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!internal.message.contains("secreto"));
+        assert_eq!(
+            ApiError::from(ServiceError::Timeout("x".to_string())).status_code(),
+            StatusCode::GATEWAY_TIMEOUT
+        );
+    }
+
+    #[test]
+    fn test_already_enrolled_names_the_course() {
+        let error = ApiError::from(ServiceError::AlreadyEnrolled {
+            student_id: uuid::Uuid::new_v4(),
+            course_id: uuid::Uuid::new_v4(),
+            course_name: "Matemática I".to_string(),
+        });
+
+        assert_eq!(error.status_code(), StatusCode::CONFLICT);
+        assert_eq!(error.message, "Student is already enrolled in Matemática I");
+    }
+
+    #[test]
+    fn test_database_error_mapping() {
+        let missing = ApiError::from(ServiceError::from(sqlx::Error::RowNotFound));
+        assert_eq!(missing.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(missing.error, "not_found");
+        assert!(matches!(DbError::from(sqlx::Error::RowNotFound), DbError::NotFound(_)));
+
+        assert_eq!(
+            ApiError::from(DbError::Conflict("duplicate key".to_string())).status_code(),
+            StatusCode::CONFLICT
+        );
+        let invalid = ApiError::from(DbError::Invalid(vec![FieldError::new("weight", "must be greater than 0")]));
+        assert_eq!(invalid.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(invalid.error, "validation_failed");
+        assert_eq!(invalid.details, Some(json!([{ "field": "weight", "message": "must be greater than 0" }])));
+        let outage = ApiError::from(DbError::from(sqlx::Error::PoolTimedOut));
+        assert_eq!(outage.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!outage.message.contains("pool"));
+    }
+
+    #[actix_rt::test]
+    async fn test_row_not_found_is_a_404_response() {
+        let response = ApiError::from(ServiceError::from(sqlx::Error::RowNotFound)).error_response();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body: serde_json::Value =
+            serde_json::from_slice(&to_bytes(response.into_body()).await.unwrap()).unwrap();
+        assert_eq!(body["error"], "not_found");
+    }
+}
@@ -0,0 +1,105 @@
+use actix_web::{
+    get, post,
+    web::{self, Data, Json, Path, Query},
+    HttpRequest, HttpResponse, Scope,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    routes::{
+        auth::require_staff,
+        extractors::{QueryParamError, QueryParams},
+        response::{ApiError, ApiResponse},
+    },
+    services::schedules::{ScheduleCheckRequest, ScheduleService},
+};
+
+/// Query parameters accepted by `GET /api/schedules/classrooms/{id}/occupancy`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct OccupancyQuery {
+    pub year: i32,
+}
+
+impl TryFrom<QueryParams> for OccupancyQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        let year = params
+            .year("year")?
+            .ok_or_else(|| QueryParamError::new("year", "an academic year"))?;
+
+        Ok(OccupancyQuery { year })
+    }
+}
+
+/// Reports the teacher and classroom conflicts of a candidate schedule.
+/// Nothing is persisted.
+#[post("/check")]
+async fn check_schedule(
+    req: HttpRequest,
+    request: Json<ScheduleCheckRequest>,
+    service: Data<ScheduleService>,
+) -> Result<HttpResponse, ApiError> {
+    require_staff(&req)?;
+
+    let conflicts = service.check_conflicts(request.into_inner()).await?;
+    let message = if conflicts.is_empty() {
+        "No conflicts found".to_string()
+    } else {
+        format!("{} conflicts found", conflicts.len())
+    };
+
+    Ok(ApiResponse::new(conflicts).with_message(message).ok())
+}
+
+/// Weekly occupancy grid of a classroom
+#[get("/classrooms/{id}/occupancy")]
+async fn get_classroom_occupancy(
+    req: HttpRequest,
+    path: Path<Uuid>,
+    query: Query<OccupancyQuery>,
+    service: Data<ScheduleService>,
+) -> Result<HttpResponse, ApiError> {
+    require_staff(&req)?;
+
+    let occupancy = service.classroom_occupancy(path.into_inner(), query.year).await?;
+
+    Ok(ApiResponse::new(occupancy).ok())
+}
+
+pub fn routes() -> Scope {
+    web::scope("/schedules")
+        .service(check_schedule)
+        .service(get_classroom_occupancy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_occupancy_requires_year() {
+        assert_eq!(Query::<OccupancyQuery>::from_query("year=2025").unwrap().year, 2025);
+        assert!(Query::<OccupancyQuery>::from_query("").is_err());
+        assert!(Query::<OccupancyQuery>::from_query("year=25").is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_routes_require_authentication() {
+        use actix_web::{test, App};
+
+        // The handler rejects the request before touching the database
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/sai").unwrap();
+        let service = ScheduleService::new(std::sync::Arc::new(pool));
+        let app = test::init_service(App::new().app_data(Data::new(service)).service(routes())).await;
+
+        let req = test::TestRequest::get()
+            .uri(&format!("/schedules/classrooms/{}/occupancy?year=2025", Uuid::new_v4()))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 401);
+    }
+}
@@ -1,38 +1,70 @@
 use actix_web::{
-    delete, get, post, put,
-    web::{self, Data, Json, Path},
-    HttpResponse, Responder,
+    delete, get, patch, post, put,
+    web::{self, Data, Json, Path, Query},
+    HttpRequest, HttpResponse, Responder, ResponseError,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
-    models::student::Student,
-    services::students::StudentService,
+    db::DEFAULT_PAGE_SIZE,
+    models::student::PatchStudentDto,
+    routes::{
+        auth::require_staff,
+        extractors::{merge_patch, QueryParamError, QueryParams},
+        response::{ApiError, ApiResponse},
+    },
+    services::{
+        students::{CreateStudentRequest, UpdateStudentRequest},
+        ServiceError,
+    },
+    state::AppState,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateStudentRequest {
-    pub first_name: String,
-    pub last_name: String,
-    pub email: String,
-    pub user_id: Option<Uuid>,
-    pub phone: Option<String>,
-    pub address: Option<String>,
+/// Query parameters accepted by `GET /api/students`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct StudentListQuery {
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UpdateStudentRequest {
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
-    pub email: Option<String>,
-    pub phone: Option<String>,
-    pub address: Option<String>,
+impl TryFrom<QueryParams> for StudentListQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        Ok(StudentListQuery {
+            page: params.page("page")?,
+            page_size: params.page_size("page_size")?,
+        })
+    }
+}
+
+/// Query parameters accepted by `GET /api/students/{id}/promotion-eligibility`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct PromotionQuery {
+    pub year: i32,
+}
+
+impl TryFrom<QueryParams> for PromotionQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        let year = params
+            .year("year")?
+            .ok_or_else(|| QueryParamError::new("year", "an academic year"))?;
+
+        Ok(PromotionQuery { year })
+    }
 }
 
 #[get("")]
-async fn get_all_students(student_service: Data<StudentService>) -> impl Responder {
-    match student_service.get_all_students().await {
+async fn get_all_students(query: Query<StudentListQuery>, state: Data<AppState>) -> impl Responder {
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    match state.services.students.get_all_students(None, page, page_size).await {
         Ok(students) => HttpResponse::Ok().json(students),
         Err(e) => {
             log::error!("Failed to get all students: {}", e);
@@ -44,12 +76,12 @@ async fn get_all_students(student_service: Data<StudentService>) -> impl Respond
 #[get("/{id}")]
 async fn get_student_by_id(
     path: Path<(Uuid,)>,
-    student_service: Data<StudentService>,
+    state: Data<AppState>,
 ) -> impl Responder {
     let id = path.into_inner().0;
-    match student_service.get_student_by_id(id).await {
-        Ok(Some(student)) => HttpResponse::Ok().json(student),
-        Ok(None) => HttpResponse::NotFound().json("Student not found"),
+    match state.services.students.get_student_by_id(id).await {
+        Ok(student) => HttpResponse::Ok().json(student),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Student not found"),
         Err(e) => {
             log::error!("Failed to get student by id: {}", e);
             HttpResponse::InternalServerError().json(format!("Failed to get student: {}", e))
@@ -60,9 +92,9 @@ async fn get_student_by_id(
 #[post("")]
 async fn create_student(
     req: Json<CreateStudentRequest>,
-    student_service: Data<StudentService>,
+    state: Data<AppState>,
 ) -> impl Responder {
-    match student_service.create_student(req.into_inner()).await {
+    match state.services.students.create_student(req.into_inner()).await {
         Ok(student) => HttpResponse::Created().json(student),
         Err(e) => {
             log::error!("Failed to create student: {}", e);
@@ -75,12 +107,12 @@ async fn create_student(
 async fn update_student(
     path: Path<(Uuid,)>,
     req: Json<UpdateStudentRequest>,
-    student_service: Data<StudentService>,
+    state: Data<AppState>,
 ) -> impl Responder {
     let id = path.into_inner().0;
-    match student_service.update_student(id, req.into_inner()).await {
-        Ok(Some(student)) => HttpResponse::Ok().json(student),
-        Ok(None) => HttpResponse::NotFound().json("Student not found"),
+    match state.services.students.update_student(id, req.into_inner()).await {
+        Ok(student) => HttpResponse::Ok().json(student),
+        Err(ServiceError::NotFound(_)) => HttpResponse::NotFound().json("Student not found"),
         Err(e) => {
             log::error!("Failed to update student: {}", e);
             HttpResponse::InternalServerError().json(format!("Failed to update student: {}", e))
@@ -88,18 +120,63 @@ async fn update_student(
     }
 }
 
+#[patch("/{id}")]
+async fn patch_student(
+    req: HttpRequest,
+    path: Path<(Uuid,)>,
+    body: web::Bytes,
+    state: Data<AppState>,
+) -> impl Responder {
+    let id = path.into_inner().0;
+    let patch: PatchStudentDto = match merge_patch(&req, &body) {
+        Ok(patch) => patch,
+        Err(e) => return e.error_response(),
+    };
+
+    match state.services.students.patch_student(id, patch).await {
+        Ok(student) => HttpResponse::Ok().json(student),
+        Err(e) => {
+            log::error!("Failed to patch student: {}", e);
+            ApiError::from(e).error_response()
+        }
+    }
+}
+
 #[delete("/{id}")]
 async fn delete_student(
     path: Path<(Uuid,)>,
-    student_service: Data<StudentService>,
+    state: Data<AppState>,
 ) -> impl Responder {
     let id = path.into_inner().0;
-    match student_service.delete_student(id).await {
-        Ok(true) => HttpResponse::NoContent().finish(),
-        Ok(false) => HttpResponse::NotFound().json("Student not found"),
+    match state.services.students.delete_student(id).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(ServiceError::NotFound(_)) => ApiError::not_found("Student not found").error_response(),
+        Err(e) => {
+            log::error!("Failed to delete student {}: {}", id, e);
+            ApiError::internal("Failed to delete student").error_response()
+        }
+    }
+}
+
+/// Promotion eligibility of a student for an academic year (MEC criteria)
+#[get("/{id}/promotion-eligibility")]
+async fn get_promotion_eligibility(
+    req: HttpRequest,
+    path: Path<Uuid>,
+    query: Query<PromotionQuery>,
+    state: Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    require_staff(&req)?;
+
+    match state.services.students
+        .calculate_promotion_eligibility(path.into_inner(), query.year)
+        .await
+    {
+        Ok(eligibility) => Ok(ApiResponse::new(eligibility).ok()),
+        Err(ServiceError::NotFound(_)) => Err(ApiError::not_found("Student not found")),
         Err(e) => {
-            log::error!("Failed to delete student: {}", e);
-            HttpResponse::InternalServerError().json(format!("Failed to delete student: {}", e))
+            log::error!("Failed to calculate promotion eligibility: {}", e);
+            Err(ApiError::internal("Failed to calculate promotion eligibility"))
         }
     }
 }
@@ -108,8 +185,11 @@ pub fn routes() -> actix_web::Scope {
     web::scope("/students")
         .service(get_all_students)
         .service(get_student_by_id)
+        .service(super::grades::get_student_grades)
+        .service(get_promotion_eligibility)
         .service(create_student)
         .service(update_student)
+        .service(patch_student)
         .service(delete_student)
 }
 
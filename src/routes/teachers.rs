@@ -1,156 +1,232 @@
 use actix_web::{
-    delete, get, post, put,
-    web::{self, Data, Json, Path},
-    HttpResponse, Responder, Scope,
+    delete, get, patch, post, put,
+    web::{self, Data, Json, Path, Query},
+    HttpRequest, HttpResponse, Scope,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
-    models::teacher::Teacher,
-    services::teachers::{CreateTeacherError, TeacherService, UpdateTeacherError},
+    db::DEFAULT_PAGE_SIZE,
+    models::{
+        teacher::{CreateTeacherWithUserDto, TeacherFilter},
+        TeacherStatus,
+    },
+    routes::{
+        extractors::{merge_patch, QueryParamError, QueryParams},
+        response::{ApiError, ApiResponse},
+    },
+    services::teachers::{CreateTeacherRequest, UpdateTeacherRequest},
+    state::AppState,
 };
 
-#[derive(Debug, Serialize)]
-pub struct TeacherResponse {
-    pub id: Uuid,
-    pub user_id: Uuid,
-    pub specialization: String,
-    pub hire_date: chrono::NaiveDate,
-    pub department: String,
-    pub is_active: bool,
+/// Query parameters accepted by `GET /api/teachers`
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "QueryParams")]
+pub struct TeacherQuery {
+    pub professional_id: Option<String>,
+    pub specialization: Option<String>,
+    pub status: Option<TeacherStatus>,
+    pub subject: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
 }
 
-impl From<Teacher> for TeacherResponse {
-    fn from(teacher: Teacher) -> Self {
-        Self {
-            id: teacher.id,
-            user_id: teacher.user_id,
-            specialization: teacher.specialization,
-            hire_date: teacher.hire_date,
-            department: teacher.department,
-            is_active: teacher.is_active,
-        }
+impl TryFrom<QueryParams> for TeacherQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        Ok(TeacherQuery {
+            professional_id: params.string("professional_id"),
+            specialization: params.string("specialization"),
+            status: params.value("status", "one of Active, OnLeave, Retired, Suspended, Terminated")?,
+            subject: params.string("subject"),
+            page: params.page("page")?,
+            page_size: params.page_size("page_size")?,
+        })
     }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct CreateTeacherRequest {
-    pub user_id: Uuid,
-    pub specialization: String,
-    pub hire_date: chrono::NaiveDate,
-    pub department: String,
-    pub is_active: bool,
+impl TeacherQuery {
+    fn filter(&self) -> TeacherFilter {
+        TeacherFilter {
+            professional_id: self.professional_id.clone(),
+            specialization: self.specialization.clone(),
+            status: self.status.clone(),
+            subject: self.subject.clone(),
+            ..Default::default()
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-pub struct UpdateTeacherRequest {
-    pub specialization: Option<String>,
-    pub hire_date: Option<chrono::NaiveDate>,
-    pub department: Option<String>,
-    pub is_active: Option<bool>,
+/// Body of `POST /api/teachers`
+///
+/// A body with `user_id` creates the profile for an existing user; otherwise
+/// the user account is created from the inline user fields as well.
+#[derive(Debug)]
+enum CreateTeacherBody {
+    ExistingUser(CreateTeacherRequest),
+    WithUser(CreateTeacherWithUserDto),
 }
 
-#[get("")]
-async fn get_all_teachers(service: Data<TeacherService>) -> impl Responder {
-    match service.get_all_teachers().await {
-        Ok(teachers) => {
-            let teacher_responses: Vec<TeacherResponse> = teachers.into_iter().map(TeacherResponse::from).collect();
-            HttpResponse::Ok().json(teacher_responses)
-        }
-        Err(err) => {
-            log::error!("Failed to get all teachers: {:?}", err);
-            HttpResponse::InternalServerError().json("Failed to get teachers")
+impl CreateTeacherBody {
+    fn parse(body: serde_json::Value) -> Result<Self, serde_json::Error> {
+        if body.get("user_id").is_some() {
+            serde_json::from_value(body).map(CreateTeacherBody::ExistingUser)
+        } else {
+            serde_json::from_value(body).map(CreateTeacherBody::WithUser)
         }
     }
 }
 
+#[get("")]
+async fn get_all_teachers(
+    query: Query<TeacherQuery>,
+    state: Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    let teachers = state.services.teachers
+        .get_all_teachers(Some(query.filter()), page, page_size)
+        .await?;
+
+    Ok(ApiResponse::new(teachers).ok())
+}
+
 #[get("/{id}")]
-async fn get_teacher_by_id(path: Path<Uuid>, service: Data<TeacherService>) -> impl Responder {
-    let teacher_id = path.into_inner();
-    
-    match service.get_teacher_by_id(teacher_id).await {
-        Ok(Some(teacher)) => HttpResponse::Ok().json(TeacherResponse::from(teacher)),
-        Ok(None) => HttpResponse::NotFound().json("Teacher not found"),
-        Err(err) => {
-            log::error!("Failed to get teacher {}: {:?}", teacher_id, err);
-            HttpResponse::InternalServerError().json("Failed to get teacher")
-        }
-    }
+async fn get_teacher_by_id(path: Path<Uuid>, state: Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let teacher = state.services.teachers.get_teacher_with_user_data(path.into_inner()).await?;
+
+    Ok(ApiResponse::new(teacher).ok())
+}
+
+#[get("/{id}/courses")]
+async fn get_teacher_courses(path: Path<Uuid>, state: Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let courses = state.services.teachers.get_teacher_courses(path.into_inner()).await?;
+
+    Ok(ApiResponse::new(courses).ok())
 }
 
 #[post("")]
 async fn create_teacher(
-    request: Json<CreateTeacherRequest>,
-    service: Data<TeacherService>,
-) -> impl Responder {
-    match service.create_teacher(
-        request.user_id,
-        request.specialization.clone(),
-        request.hire_date,
-        request.department.clone(),
-        request.is_active,
-    ).await {
-        Ok(teacher) => HttpResponse::Created().json(TeacherResponse::from(teacher)),
-        Err(CreateTeacherError::UserNotFound) => {
-            HttpResponse::BadRequest().json("User not found")
-        }
-        Err(CreateTeacherError::UserAlreadyAssigned) => {
-            HttpResponse::BadRequest().json("User is already assigned to a teacher")
-        }
-        Err(CreateTeacherError::DatabaseError(err)) => {
-            log::error!("Failed to create teacher: {:?}", err);
-            HttpResponse::InternalServerError().json("Failed to create teacher")
-        }
-    }
+    body: Json<serde_json::Value>,
+    state: Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let body = CreateTeacherBody::parse(body.into_inner())
+        .map_err(|e| ApiError::unprocessable(format!("Invalid teacher: {}", e)))?;
+
+    let teacher = match body {
+        CreateTeacherBody::ExistingUser(request) => state.services.teachers.create_teacher(request).await?,
+        CreateTeacherBody::WithUser(request) => state.services.teachers.create_teacher_with_user(request).await?.1,
+    };
+
+    Ok(ApiResponse::new(teacher).with_message("Teacher created successfully").created())
 }
 
 #[put("/{id}")]
 async fn update_teacher(
     path: Path<Uuid>,
     request: Json<UpdateTeacherRequest>,
-    service: Data<TeacherService>,
-) -> impl Responder {
-    let teacher_id = path.into_inner();
-    
-    match service.update_teacher(
-        teacher_id,
-        request.specialization.clone(),
-        request.hire_date,
-        request.department.clone(),
-        request.is_active,
-    ).await {
-        Ok(teacher) => HttpResponse::Ok().json(TeacherResponse::from(teacher)),
-        Err(UpdateTeacherError::TeacherNotFound) => {
-            HttpResponse::NotFound().json("Teacher not found")
-        }
-        Err(UpdateTeacherError::DatabaseError(err)) => {
-            log::error!("Failed to update teacher {}: {:?}", teacher_id, err);
-            HttpResponse::InternalServerError().json("Failed to update teacher")
-        }
-    }
+    state: Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let teacher = state.services.teachers.update_teacher(path.into_inner(), request.into_inner()).await?;
+
+    Ok(ApiResponse::new(teacher).with_message("Teacher updated successfully").ok())
 }
 
+/// Soft delete: the teacher is marked as terminated, courses and history stay
 #[delete("/{id}")]
-async fn delete_teacher(path: Path<Uuid>, service: Data<TeacherService>) -> impl Responder {
-    let teacher_id = path.into_inner();
-    
-    match service.delete_teacher(teacher_id).await {
-        Ok(true) => HttpResponse::NoContent().finish(),
-        Ok(false) => HttpResponse::NotFound().json("Teacher not found"),
-        Err(err) => {
-            log::error!("Failed to delete teacher {}: {:?}", teacher_id, err);
-            HttpResponse::InternalServerError().json("Failed to delete teacher")
-        }
-    }
+async fn delete_teacher(path: Path<Uuid>, state: Data<AppState>) -> Result<HttpResponse, ApiError> {
+    state.services.teachers.delete_teacher(path.into_inner()).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Partial update. Teachers have no nullable columns, so every field is
+/// optional, absent fields keep their current value and `null` is rejected.
+#[patch("/{id}")]
+async fn patch_teacher(
+    req: HttpRequest,
+    path: Path<Uuid>,
+    body: web::Bytes,
+    state: Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let patch: UpdateTeacherRequest = merge_patch(&req, &body)?;
+
+    let teacher = state.services.teachers.update_teacher(path.into_inner(), patch).await?;
+
+    Ok(ApiResponse::new(teacher).ok())
 }
 
 pub fn routes() -> Scope {
     web::scope("/teachers")
         .service(get_all_teachers)
         .service(get_teacher_by_id)
+        .service(get_teacher_courses)
         .service(create_teacher)
         .service(update_teacher)
+        .service(patch_teacher)
         .service(delete_teacher)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn teacher_fields() -> serde_json::Value {
+        json!({
+            "professional_id": "MEC-1234",
+            "specialization": "Matemática",
+            "hire_date": "2024-02-15",
+            "education_level": "Licenciatura",
+            "subjects": ["Matemática", "Física"],
+            "status": "Active"
+        })
+    }
+
+    #[test]
+    fn test_body_with_user_id_uses_existing_user() {
+        let mut body = teacher_fields();
+        body["user_id"] = json!(Uuid::new_v4());
+
+        assert!(matches!(CreateTeacherBody::parse(body), Ok(CreateTeacherBody::ExistingUser(_))));
+    }
+
+    #[test]
+    fn test_body_without_user_id_creates_user() {
+        let mut body = teacher_fields();
+        body["document_id"] = json!("4567890");
+        body["full_name"] = json!("María González");
+        body["email"] = json!("maria@example.com");
+        body["birth_date"] = json!("1985-06-01");
+
+        assert!(matches!(CreateTeacherBody::parse(body), Ok(CreateTeacherBody::WithUser(_))));
+    }
+
+    #[test]
+    fn test_body_without_user_data_is_rejected() {
+        let err = CreateTeacherBody::parse(teacher_fields()).unwrap_err();
+
+        assert!(err.to_string().contains("document_id"));
+    }
+
+    #[test]
+    fn test_query_builds_filter() {
+        let query = Query::<TeacherQuery>::from_query("subject=Física&status=OnLeave&page=2")
+            .unwrap()
+            .into_inner();
+        let filter = query.filter();
+
+        assert_eq!(filter.subject.as_deref(), Some("Física"));
+        assert_eq!(filter.status, Some(TeacherStatus::OnLeave));
+        assert_eq!(query.page, Some(2));
+    }
+
+    #[test]
+    fn test_query_rejects_unknown_status() {
+        assert!(Query::<TeacherQuery>::from_query("status=on_leave").is_err());
+        assert!(Query::<TeacherQuery>::from_query("page_size=0").is_err());
+    }
+}
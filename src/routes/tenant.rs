@@ -0,0 +1,156 @@
+//! Tenant resolution for requests.
+//!
+//! [`TenantScope`] serves the request with the state of the institution named
+//! by the `institution` claim of the bearer token (see
+//! [`AppState::for_tenant`]), so every service and pool the handlers extract
+//! reads the tenant schema. Handlers that take a [`TenantPool`] get the pool
+//! scoped the same way. Requests without the claim (or without a token) use
+//! `public`. The claim is trusted because login and refresh only sign it after
+//! checking membership ([`crate::db::tenant::is_member`]). Errors use the
+//! standard [`ApiError`] body.
+
+use std::future::{ready, Ready};
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{web, Error, FromRequest, HttpRequest};
+use futures::future::{self, LocalBoxFuture};
+
+use crate::db::tenant::{TenantError, TenantId, TenantPool};
+use crate::db::DbPool;
+use crate::routes::auth::bearer_claims;
+use crate::routes::response::{request_id, ApiError};
+use crate::state::AppState;
+
+/// Middleware that swaps the app data for the state of the request's tenant
+///
+/// The tenant state is added as a data container of the request, which
+/// actix searches before the app's own data: `web::Data<AppState>`, the
+/// pools and every service then point at the tenant. An invalid slug is
+/// answered with 400 and an unprovisioned one with 404.
+pub struct TenantScope;
+
+impl<S, B> Transform<S, ServiceRequest> for TenantScope
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = TenantScopeMiddleware<S>;
+    type InitError = ();
+    type Future = future::Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        future::ok(TenantScopeMiddleware { service: Rc::new(service) })
+    }
+}
+
+pub struct TenantScopeMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for TenantScopeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let tenant = match request_tenant(req.request()) {
+            Ok(Some(tenant)) => tenant,
+            Ok(None) => {
+                let response = self.service.call(req);
+                return Box::pin(async move { response.await.map(ServiceResponse::map_into_left_body) });
+            }
+            Err(e) => {
+                let e = e.with_request_id(request_id(req.request()));
+                return Box::pin(future::ok(req.error_response(e).map_into_right_body()));
+            }
+        };
+        let Some(state) = req.app_data::<web::Data<AppState>>().cloned() else {
+            log::error!("AppState is not registered as app data; serving tenant {} from public", tenant);
+            let response = self.service.call(req);
+            return Box::pin(async move { response.await.map(ServiceResponse::map_into_left_body) });
+        };
+
+        let service = self.service.clone();
+        Box::pin(async move {
+            match state.for_tenant(&tenant).await {
+                Ok(tenant_state) => {
+                    req.add_data_container(Rc::new(super::tenant_app_data(&tenant_state)));
+                    service.call(req).await.map(ServiceResponse::map_into_left_body)
+                }
+                Err(e) => {
+                    let e = ApiError::from(e).with_request_id(request_id(req.request()));
+                    Ok(req.error_response(e).map_into_right_body())
+                }
+            }
+        })
+    }
+}
+
+/// Institution named by the bearer token of `req`, if any
+fn request_tenant(req: &HttpRequest) -> Result<Option<TenantId>, ApiError> {
+    let tenant = bearer_claims(req)
+        .and_then(|claims| claims.institution)
+        .map(|slug| TenantId::parse(&slug))
+        .transpose()?;
+    Ok(tenant)
+}
+
+impl FromRequest for TenantPool {
+    type Error = ApiError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(tenant_pool(req).map_err(|e| e.with_request_id(request_id(req))))
+    }
+}
+
+fn tenant_pool(req: &HttpRequest) -> Result<TenantPool, ApiError> {
+    let pool = req
+        .app_data::<web::Data<DbPool>>()
+        .ok_or_else(|| ApiError::internal("Database pool not configured"))?;
+    Ok(TenantPool::new(pool.get_ref().clone(), request_tenant(req)?))
+}
+
+impl From<TenantError> for ApiError {
+    fn from(err: TenantError) -> Self {
+        match err {
+            TenantError::InvalidName(_) => ApiError::bad_request(err.to_string()).with_code("invalid_institution"),
+            TenantError::MissingSchema(_) => ApiError::not_found(err.to_string()).with_code("unknown_institution"),
+            TenantError::Database(e) => {
+                log::error!("Tenant database error: {}", e);
+                ApiError::internal("Internal server error")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::ResponseError;
+
+    #[test]
+    fn test_tenant_error_mapping() {
+        let invalid = ApiError::from(TenantError::InvalidName("San José".to_string()));
+        assert_eq!(invalid.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(invalid.error, "invalid_institution");
+
+        let missing = ApiError::from(TenantError::MissingSchema("cnc".to_string()));
+        assert_eq!(missing.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(missing.error, "unknown_institution");
+        assert!(missing.message.contains("\"cnc\" has no schema"));
+    }
+}
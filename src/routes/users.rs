@@ -1,189 +1,183 @@
-use actix_web::{delete, get, post, put, web, HttpResponse, Responder};
-use serde::{Deserialize, Serialize};
+use actix_web::{delete, get, patch, post, put, web, HttpRequest, HttpResponse, ResponseError};
+use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::models::user::User;
-use crate::services::users::{CreateUserError, UpdateUserError, UserService};
+use crate::db::DEFAULT_PAGE_SIZE;
+use crate::models::{
+    device_token::Platform,
+    user::{CreateUserDto, PatchUserDto, UpdateUserDto},
+};
+use crate::routes::auth::{bearer_claims, invalidate_principal};
+use crate::routes::extractors::{merge_patch, QueryParamError, QueryParams};
+use crate::routes::response::{ApiError, ApiResponse};
+use crate::services::users::{UserError, UserPagination};
+use crate::state::AppState;
+use crate::utils::pagination::PaginatedResponse;
 
-#[derive(Debug, Serialize)]
-struct ErrorResponse {
-    error: String,
+impl From<UserError> for ApiError {
+    fn from(err: UserError) -> Self {
+        match err {
+            UserError::NotFound => ApiError::not_found("User not found"),
+            UserError::EmailAlreadyExists => ApiError::conflict("Email already exists"),
+            UserError::DocumentIdAlreadyExists => ApiError::conflict("Document ID already exists"),
+            UserError::ValidationError(msg) => ApiError::bad_request(msg),
+            UserError::DatabaseError(e) => e.into(),
+        }
+    }
 }
 
+/// Query parameters accepted by `GET /api/users`
 #[derive(Debug, Deserialize)]
-pub struct CreateUserRequest {
-    pub username: String,
-    pub email: String,
-    pub password: String,
-    pub first_name: String,
-    pub last_name: String,
-    pub role: String,
+#[serde(try_from = "QueryParams")]
+pub struct UserQuery {
+    /// Partial match on the full name
+    pub search: Option<String>,
+    pub page: Option<u32>,
+    pub page_size: Option<u32>,
+}
+
+impl TryFrom<QueryParams> for UserQuery {
+    type Error = QueryParamError;
+
+    fn try_from(params: QueryParams) -> Result<Self, Self::Error> {
+        Ok(UserQuery {
+            search: params.string("search"),
+            page: params.page("page")?,
+            page_size: params.page_size("page_size")?,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
-pub struct UpdateUserRequest {
-    pub username: Option<String>,
-    pub email: Option<String>,
-    pub password: Option<String>,
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
-    pub role: Option<String>,
-    pub is_active: Option<bool>,
+pub struct DeviceTokenRequest {
+    pub token: String,
+    pub platform: Platform,
 }
 
-#[get("")]
-async fn get_all_users(user_service: web::Data<UserService>) -> impl Responder {
-    match user_service.get_all_users().await {
-        Ok(users) => HttpResponse::Ok().json(users),
-        Err(err) => {
-            log::error!("Failed to get all users: {}", err);
-            HttpResponse::InternalServerError().json(ErrorResponse {
-                error: "Failed to get users".to_string(),
-            })
-        }
+/// ID of the authenticated user, or a 401 response
+fn current_user_id(req: &HttpRequest) -> Result<Uuid, ApiError> {
+    bearer_claims(req)
+        .and_then(|claims| Uuid::parse_str(&claims.sub).ok())
+        .ok_or_else(|| ApiError::unauthorized("Authentication required"))
+}
+
+/// Registers the push notification token of the caller's device
+///
+/// Registering a token that already exists moves it to the caller.
+#[post("/me/device-token")]
+async fn register_device_token(
+    req: HttpRequest,
+    request: web::Json<DeviceTokenRequest>,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    let user_id = match current_user_id(&req) {
+        Ok(user_id) => user_id,
+        Err(e) => return e.error_response(),
+    };
+
+    match state.services.notifications.register_device(user_id, &request.token, request.platform).await {
+        Ok(device) => ApiResponse::new(device).created(),
+        Err(e) => ApiError::from(e).error_response(),
     }
 }
 
-#[get("/{id}")]
-async fn get_user_by_id(
-    path: web::Path<(Uuid,)>,
-    user_service: web::Data<UserService>,
-) -> impl Responder {
-    let user_id = path.into_inner().0;
-
-    match user_service.get_user_by_id(user_id).await {
-        Ok(Some(user)) => HttpResponse::Ok().json(user),
-        Ok(None) => HttpResponse::NotFound().json(ErrorResponse {
-            error: format!("User with id {} not found", user_id),
-        }),
-        Err(err) => {
-            log::error!("Failed to get user: {}", err);
-            HttpResponse::InternalServerError().json(ErrorResponse {
-                error: "Failed to get user".to_string(),
-            })
-        }
+/// Removes one of the caller's push notification tokens
+#[delete("/me/device-token/{token}")]
+async fn unregister_device_token(
+    req: HttpRequest,
+    path: web::Path<String>,
+    state: web::Data<AppState>,
+) -> HttpResponse {
+    let user_id = match current_user_id(&req) {
+        Ok(user_id) => user_id,
+        Err(e) => return e.error_response(),
+    };
+
+    match state.services.notifications.unregister_device(user_id, &path.into_inner()).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => ApiError::from(e).error_response(),
     }
 }
 
+#[get("")]
+async fn get_all_users(query: web::Query<UserQuery>, state: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+
+    let result = state.services.users
+        .get_all_users(
+            query.search.as_deref(),
+            UserPagination::Offset { page: page as usize, per_page: page_size as usize },
+        )
+        .await?;
+
+    Ok(ApiResponse::new(PaginatedResponse::new(result.users, result.total, page, page_size)).ok())
+}
+
+#[get("/{id}")]
+async fn get_user_by_id(path: web::Path<Uuid>, state: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let user = state.services.users.get_user_by_id(path.into_inner()).await?;
+
+    Ok(ApiResponse::new(user).ok())
+}
+
 #[post("")]
 async fn create_user(
-    request: web::Json<CreateUserRequest>,
-    user_service: web::Data<UserService>,
-) -> impl Responder {
-    match user_service
-        .create_user(
-            &request.username,
-            &request.email,
-            &request.password,
-            &request.first_name,
-            &request.last_name,
-            &request.role,
-        )
-        .await
-    {
-        Ok(user) => HttpResponse::Created().json(user),
-        Err(CreateUserError::UsernameAlreadyExists) => {
-            HttpResponse::BadRequest().json(ErrorResponse {
-                error: "Username already exists".to_string(),
-            })
-        }
-        Err(CreateUserError::EmailAlreadyExists) => {
-            HttpResponse::BadRequest().json(ErrorResponse {
-                error: "Email already exists".to_string(),
-            })
-        }
-        Err(CreateUserError::InvalidRole) => {
-            HttpResponse::BadRequest().json(ErrorResponse {
-                error: "Invalid role".to_string(),
-            })
-        }
-        Err(CreateUserError::DatabaseError(err)) => {
-            log::error!("Failed to create user: {}", err);
-            HttpResponse::InternalServerError().json(ErrorResponse {
-                error: "Failed to create user".to_string(),
-            })
-        }
-    }
+    request: web::Json<CreateUserDto>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let user = state.services.users.create_user(request.into_inner()).await?;
+
+    Ok(ApiResponse::new(user).created())
 }
 
 #[put("/{id}")]
 async fn update_user(
-    path: web::Path<(Uuid,)>,
-    request: web::Json<UpdateUserRequest>,
-    user_service: web::Data<UserService>,
-) -> impl Responder {
-    let user_id = path.into_inner().0;
-
-    match user_service
-        .update_user(
-            user_id,
-            request.username.as_deref(),
-            request.email.as_deref(),
-            request.password.as_deref(),
-            request.first_name.as_deref(),
-            request.last_name.as_deref(),
-            request.role.as_deref(),
-            request.is_active,
-        )
-        .await
-    {
-        Ok(user) => HttpResponse::Ok().json(user),
-        Err(UpdateUserError::UserNotFound) => {
-            HttpResponse::NotFound().json(ErrorResponse {
-                error: format!("User with id {} not found", user_id),
-            })
-        }
-        Err(UpdateUserError::UsernameAlreadyExists) => {
-            HttpResponse::BadRequest().json(ErrorResponse {
-                error: "Username already exists".to_string(),
-            })
-        }
-        Err(UpdateUserError::EmailAlreadyExists) => {
-            HttpResponse::BadRequest().json(ErrorResponse {
-                error: "Email already exists".to_string(),
-            })
-        }
-        Err(UpdateUserError::InvalidRole) => {
-            HttpResponse::BadRequest().json(ErrorResponse {
-                error: "Invalid role".to_string(),
-            })
-        }
-        Err(UpdateUserError::DatabaseError(err)) => {
-            log::error!("Failed to update user: {}", err);
-            HttpResponse::InternalServerError().json(ErrorResponse {
-                error: "Failed to update user".to_string(),
-            })
-        }
-    }
+    path: web::Path<Uuid>,
+    request: web::Json<UpdateUserDto>,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = path.into_inner();
+    let user = state.services.users.update_user(user_id, request.into_inner()).await?;
+
+    invalidate_principal(user_id);
+    Ok(ApiResponse::new(user).ok())
+}
+
+#[patch("/{id}")]
+async fn patch_user(
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Bytes,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, ApiError> {
+    let user_id = path.into_inner();
+    let patch: PatchUserDto = merge_patch(&req, &body)?;
+
+    let user = state.services.users.patch_user(user_id, patch).await?;
+
+    invalidate_principal(user_id);
+    Ok(ApiResponse::new(user).ok())
 }
 
 #[delete("/{id}")]
-async fn delete_user(
-    path: web::Path<(Uuid,)>,
-    user_service: web::Data<UserService>,
-) -> impl Responder {
-    let user_id = path.into_inner().0;
-
-    match user_service.delete_user(user_id).await {
-        Ok(true) => HttpResponse::NoContent().finish(),
-        Ok(false) => HttpResponse::NotFound().json(ErrorResponse {
-            error: format!("User with id {} not found", user_id),
-        }),
-        Err(err) => {
-            log::error!("Failed to delete user: {}", err);
-            HttpResponse::InternalServerError().json(ErrorResponse {
-                error: "Failed to delete user".to_string(),
-            })
-        }
-    }
+async fn delete_user(path: web::Path<Uuid>, state: web::Data<AppState>) -> Result<HttpResponse, ApiError> {
+    let user_id = path.into_inner();
+    state.services.users.delete_user(user_id).await?;
+
+    invalidate_principal(user_id);
+    Ok(HttpResponse::NoContent().finish())
 }
 
-pub fn routes() -> web::Scope {
-    web::Scope::new("/users")
+pub fn routes() -> actix_web::Scope {
+    web::scope("/users")
+        .service(register_device_token)
+        .service(unregister_device_token)
         .service(get_all_users)
         .service(get_user_by_id)
         .service(create_user)
         .service(update_user)
+        .service(patch_user)
         .service(delete_user)
 }
 
@@ -0,0 +1,825 @@
+//! Tareas de administración: respaldo lógico y restauración de los datos, datos
+//! de la institución y alta de administradores desde la línea de comandos
+//!
+//! Un respaldo es un `.tar.gz` con un archivo JSON-lines por tabla
+//! (`data/<tabla>.jsonl`, una fila por línea tal como la devuelve `to_jsonb`) y,
+//! al final, `manifest.json` con la versión de esquema y la cantidad de filas de
+//! cada tabla. Se exporta en una única transacción de solo lectura, de modo que
+//! todas las tablas corresponden al mismo instante, y dentro del esquema de la
+//! institución (ver `db::tenant`).
+
+pub mod archive;
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::sync::Arc;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use futures::stream::{self, Stream, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{Error as SqlxError, PgConnection, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::db::tenant::{TenantError, TenantId, TenantPool};
+use crate::db::{DbError, DbPool};
+use crate::models::audit_log::{AuditLogEntry, NewAuditLogEntry};
+use crate::models::authentication::{Authentication, NewAuthentication};
+use crate::models::institution::{CreateInstitutionDto, Institution, UpdateInstitutionDto};
+use crate::models::user::{CreateUserDto, User};
+use crate::models::Role;
+use crate::services::{ServiceError, ServiceResult};
+use crate::utils::validation::{normalize_email, validate_email};
+use archive::ArchiveWriter;
+
+/// Versión del formato de los respaldos
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Ruta del manifiesto dentro del archivo
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// Tablas de credenciales: hashes de contraseñas y tokens de verificación, de
+/// restablecimiento, de sesión y de notificaciones. Se excluyen salvo que se
+/// pidan explícitamente; tras restaurar sin ellas, los usuarios restablecen su contraseña
+pub const SECRET_TABLES: [&str; 2] = ["authentications", "device_tokens"];
+
+/// Tablas que nunca se respaldan: la base de destino ya tiene sus migraciones
+const SKIPPED_TABLES: [&str; 1] = ["schema_migrations"];
+
+/// Filas insertadas por sentencia al restaurar
+const RESTORE_BATCH_SIZE: usize = 500;
+
+/// Error al exportar o restaurar un respaldo
+#[derive(Debug, thiserror::Error)]
+pub enum BackupError {
+    #[error("Archivo de respaldo inválido: {0}")]
+    InvalidArchive(String),
+    #[error("La base de datos de destino no está vacía; tablas con datos: {}", .0.join(", "))]
+    NotEmpty(Vec<String>),
+    #[error("El respaldo contiene la tabla {0}, que no existe en la base de datos de destino")]
+    UnknownTable(String),
+    #[error("El respaldo es de la versión de esquema {backup:?} y la base de datos está en {database:?}")]
+    SchemaMismatch {
+        backup: Option<String>,
+        database: Option<String>,
+    },
+    #[error(transparent)]
+    Tenant(#[from] TenantError),
+    #[error("Error de base de datos: {0}")]
+    Database(#[from] DbError),
+    #[error("Error al escribir el respaldo: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<SqlxError> for BackupError {
+    fn from(err: SqlxError) -> Self {
+        BackupError::Database(err.into())
+    }
+}
+
+/// Opciones de exportación
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct BackupOptions {
+    /// Incluir las tablas de [`SECRET_TABLES`]
+    #[serde(default)]
+    pub include_secrets: bool,
+}
+
+/// Filas de una tabla del respaldo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupTable {
+    pub name: String,
+    pub rows: u64,
+}
+
+/// Contenido de `manifest.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Versión del formato ([`BACKUP_FORMAT_VERSION`])
+    pub format: u32,
+    pub created_at: DateTime<Utc>,
+    /// Institución respaldada; `None` para el esquema `public`
+    pub institution: Option<String>,
+    /// Última migración aplicada en el origen
+    pub schema_version: Option<String>,
+    /// Tablas respaldadas, en orden referencial
+    pub tables: Vec<BackupTable>,
+    /// Tablas de credenciales que no se incluyeron
+    pub excluded_tables: Vec<String>,
+}
+
+/// Resultado de una restauración
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RestoreSummary {
+    /// Tablas cargadas, en el orden en que se cargaron
+    pub tables: Vec<BackupTable>,
+}
+
+impl RestoreSummary {
+    /// Total de filas cargadas
+    pub fn rows(&self) -> u64 {
+        self.tables.iter().map(|table| table.rows).sum()
+    }
+}
+
+/// Longitud mínima de la contraseña de un administrador
+pub const MIN_ADMIN_PASSWORD_LENGTH: usize = 8;
+
+/// Longitud de las contraseñas generadas
+const GENERATED_PASSWORD_LENGTH: usize = 20;
+
+/// Error al crear un administrador
+#[derive(Debug, thiserror::Error)]
+pub enum CreateAdminError {
+    #[error("Correo electrónico inválido: {0}")]
+    InvalidEmail(String),
+    #[error("El nombre no puede estar vacío")]
+    EmptyName,
+    #[error("La contraseña debe tener al menos {} caracteres", MIN_ADMIN_PASSWORD_LENGTH)]
+    WeakPassword,
+    #[error("Ya existe un usuario con el correo {0}")]
+    EmailTaken(String),
+    #[error("Error de base de datos: {0}")]
+    Database(#[from] DbError),
+}
+
+impl From<SqlxError> for CreateAdminError {
+    fn from(err: SqlxError) -> Self {
+        CreateAdminError::Database(err.into())
+    }
+}
+
+/// Administrador creado
+#[derive(Debug, Clone)]
+pub struct CreatedAdmin {
+    pub user: User,
+    /// Contraseña generada, si no se indicó una; se pide cambiarla en el primer ingreso
+    pub generated_password: Option<String>,
+}
+
+/// Servicio de tareas de administración
+pub struct AdminService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+    /// `mail.lowercase_local_part`: si el correo del administrador se guarda con la parte local en minúsculas
+    lowercase_email_local_part: bool,
+}
+
+impl AdminService {
+    /// Crea una nueva instancia del servicio de administración
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de AdminService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self { db_pool, lowercase_email_local_part: true }
+    }
+
+    /// Normaliza los correos según `mail.lowercase_local_part` (activo por defecto)
+    pub fn with_lowercase_email_local_part(mut self, enabled: bool) -> Self {
+        self.lowercase_email_local_part = enabled;
+        self
+    }
+
+    /// Exporta un respaldo de los datos de una institución
+    ///
+    /// Registra el evento de auditoría y abre la transacción antes de devolver
+    /// el stream, de modo que una institución inexistente se informa como error
+    /// y no como una descarga vacía. Cada fragmento contiene una tabla completa
+    /// comprimida; el último cierra el archivo con el manifiesto.
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant` - Institución a respaldar, o `None` para el esquema `public`
+    /// * `options` - Opciones de exportación
+    /// * `audit` - Actor y origen de la solicitud; la acción la completa el servicio
+    ///
+    /// # Returns
+    ///
+    /// Un stream con los bytes del `.tar.gz`
+    pub async fn export_backup(
+        &self,
+        tenant: Option<TenantId>,
+        options: BackupOptions,
+        audit: NewAuditLogEntry,
+    ) -> Result<impl Stream<Item = Result<Vec<u8>, BackupError>>, BackupError> {
+        let pool = TenantPool::new(self.db_pool.as_ref().clone(), tenant);
+
+        let event = NewAuditLogEntry {
+            action: "backup.export".to_string(),
+            entity_type: "backup".to_string(),
+            new_value: Some(json!({
+                "institution": pool.tenant().map(TenantId::slug),
+                "include_secrets": options.include_secrets,
+            })),
+            ..audit
+        };
+        let mut tx = pool.begin().await?;
+        AuditLogEntry::record(&mut *tx, &event).await?;
+        tx.commit().await?;
+
+        let export = Export::open(&pool, options).await?;
+        Ok(stream::try_unfold(export, |mut export| async move {
+            Ok(export.next_chunk().await?.map(|chunk| (chunk, export)))
+        }))
+    }
+
+    /// Restaura un respaldo en una base de datos migrada y vacía
+    ///
+    /// Antes de cargar nada verifica que la versión de esquema coincida, que
+    /// existan todas las tablas y que ninguna tenga datos. Las tablas se cargan
+    /// en orden referencial dentro de una única transacción: ante cualquier
+    /// error no queda nada a medias.
+    ///
+    /// # Arguments
+    ///
+    /// * `tenant` - Institución de destino, o `None` para el esquema `public`
+    /// * `archive` - Contenido del `.tar.gz`
+    ///
+    /// # Returns
+    ///
+    /// Las tablas y filas cargadas
+    pub async fn restore_backup(&self, tenant: Option<TenantId>, archive: &[u8]) -> Result<RestoreSummary, BackupError> {
+        let mut files = archive::read_archive(archive).map_err(|e| BackupError::InvalidArchive(e.to_string()))?;
+        let manifest: BackupManifest = files
+            .get(MANIFEST_PATH)
+            .ok_or_else(|| BackupError::InvalidArchive(format!("falta {}", MANIFEST_PATH)))
+            .and_then(|bytes| {
+                serde_json::from_slice(bytes).map_err(|e| BackupError::InvalidArchive(format!("{}: {}", MANIFEST_PATH, e)))
+            })?;
+        if manifest.format != BACKUP_FORMAT_VERSION {
+            return Err(BackupError::InvalidArchive(format!(
+                "formato {} no soportado (se esperaba {})",
+                manifest.format, BACKUP_FORMAT_VERSION
+            )));
+        }
+
+        let pool = TenantPool::new(self.db_pool.as_ref().clone(), tenant);
+        let mut tx = pool.begin().await?;
+        let schema = SchemaInfo::load(&mut tx).await?;
+
+        let database_version = schema_version(&mut tx).await?;
+        if database_version != manifest.schema_version {
+            return Err(BackupError::SchemaMismatch {
+                backup: manifest.schema_version,
+                database: database_version,
+            });
+        }
+
+        let names: Vec<String> = manifest.tables.iter().map(|table| table.name.clone()).collect();
+        if let Some(unknown) = names.iter().find(|name| !schema.tables.contains(*name)) {
+            return Err(BackupError::UnknownTable(unknown.clone()));
+        }
+        let mut not_empty = Vec::new();
+        for name in &names {
+            let has_rows: bool = sqlx::query_scalar(&format!("SELECT EXISTS (SELECT 1 FROM {})", quote_ident(name)))
+                .fetch_one(&mut *tx)
+                .await?;
+            if has_rows {
+                not_empty.push(name.clone());
+            }
+        }
+        if !not_empty.is_empty() {
+            return Err(BackupError::NotEmpty(not_empty));
+        }
+
+        let expected: BTreeMap<&str, u64> = manifest
+            .tables
+            .iter()
+            .map(|table| (table.name.as_str(), table.rows))
+            .collect();
+        let mut tables = Vec::with_capacity(names.len());
+        for name in referential_order(&names, &schema.references) {
+            let path = data_path(&name);
+            let data = files
+                .remove(&path)
+                .ok_or_else(|| BackupError::InvalidArchive(format!("falta {}", path)))?;
+            let rows = load_table(&mut tx, &name, &data).await?;
+            if rows != expected[name.as_str()] {
+                return Err(BackupError::InvalidArchive(format!(
+                    "{} tiene {} filas y el manifiesto indica {}",
+                    path, rows, expected[name.as_str()]
+                )));
+            }
+            tables.push(BackupTable { name, rows });
+        }
+
+        reset_sequences(&mut tx, &names).await?;
+        tx.commit().await?;
+
+        Ok(RestoreSummary { tables })
+    }
+
+    /// Crea un usuario administrador con su registro de autenticación
+    ///
+    /// Sin contraseña se genera una aleatoria y la cuenta queda marcada para
+    /// cambiarla en el primer ingreso. El usuario, la autenticación y el evento
+    /// de auditoría (`admin.create`) se guardan en una única transacción.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - Correo del administrador
+    /// * `full_name` - Nombre completo
+    /// * `password` - Contraseña elegida, o `None` para generarla
+    ///
+    /// # Returns
+    ///
+    /// El usuario creado y, si corresponde, la contraseña generada
+    pub async fn create_admin(
+        &self,
+        email: &str,
+        full_name: &str,
+        password: Option<&str>,
+    ) -> Result<CreatedAdmin, CreateAdminError> {
+        let (email, full_name) = validate_admin(email, full_name, password, self.lowercase_email_local_part)?;
+        if User::find_by_email(self.db_pool.as_ref(), &email).await?.is_some() {
+            return Err(CreateAdminError::EmailTaken(email));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        let created = insert_admin(&mut tx, &email, full_name, password, "admin.create").await?;
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// Crea el primer administrador de una instalación nueva
+    ///
+    /// Solo actúa si no hay ningún usuario con rol `Admin`: la contraseña se
+    /// genera y la cuenta queda marcada para cambiarla en el primer ingreso. Un
+    /// bloqueo consultivo serializa a las instancias que arrancan a la vez, de
+    /// modo que solo una lo crea. Se audita como `admin.bootstrap`.
+    ///
+    /// # Arguments
+    ///
+    /// * `email` - Correo del administrador (`SAI_BOOTSTRAP_ADMIN_EMAIL`)
+    /// * `full_name` - Nombre completo
+    ///
+    /// # Returns
+    ///
+    /// El administrador creado con su contraseña temporal, o `None` si ya había uno
+    pub async fn bootstrap_admin(&self, email: &str, full_name: &str) -> Result<Option<CreatedAdmin>, CreateAdminError> {
+        let (email, full_name) = validate_admin(email, full_name, None, self.lowercase_email_local_part)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(BOOTSTRAP_LOCK_KEY)
+            .execute(&mut *tx)
+            .await?;
+        let has_admin: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE role = 'Admin')")
+            .fetch_one(&mut *tx)
+            .await?;
+        if has_admin {
+            return Ok(None);
+        }
+
+        let created = insert_admin(&mut tx, &email, full_name, None, "admin.bootstrap").await?;
+        tx.commit().await?;
+
+        Ok(Some(created))
+    }
+
+    /// Registra los datos de la institución
+    ///
+    /// # Arguments
+    ///
+    /// * `dto` - Datos de la institución
+    ///
+    /// # Returns
+    ///
+    /// La institución registrada, o `DbError::Invalid` si el RUC u otro campo es inválido
+    pub async fn create_institution(&self, dto: CreateInstitutionDto) -> ServiceResult<Institution> {
+        Institution::create(self.db_pool.as_ref(), &dto)
+            .await
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Modifica los datos de la institución
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID de la institución
+    /// * `dto` - Campos a modificar
+    ///
+    /// # Returns
+    ///
+    /// La institución actualizada, NotFound si no existe o `DbError::Invalid` si algún campo es inválido
+    pub async fn update_institution(&self, id: Uuid, dto: UpdateInstitutionDto) -> ServiceResult<Institution> {
+        Institution::update(self.db_pool.as_ref(), id, &dto)
+            .await
+            .map_err(|e| match e {
+                DbError::NotFound(_) => ServiceError::NotFound(format!("Institución con ID {}", id)),
+                e => ServiceError::DatabaseError(e),
+            })
+    }
+}
+
+/// Clave del bloqueo consultivo que serializa la creación del primer administrador
+const BOOTSTRAP_LOCK_KEY: i64 = 0x5341_4941_444d_494e;
+
+/// Valida los datos de un administrador nuevo y devuelve el correo normalizado y el nombre sin espacios
+fn validate_admin<'a>(
+    email: &str,
+    full_name: &'a str,
+    password: Option<&str>,
+    lowercase_email_local_part: bool,
+) -> Result<(String, &'a str), CreateAdminError> {
+    let email = normalize_email(email, lowercase_email_local_part);
+    let full_name = full_name.trim();
+    if !validate_email(&email) {
+        return Err(CreateAdminError::InvalidEmail(email));
+    }
+    if full_name.is_empty() {
+        return Err(CreateAdminError::EmptyName);
+    }
+    if password.is_some_and(|password| password.chars().count() < MIN_ADMIN_PASSWORD_LENGTH) {
+        return Err(CreateAdminError::WeakPassword);
+    }
+    Ok((email, full_name))
+}
+
+/// Guarda el usuario, su autenticación y el evento de auditoría `action` en `tx`
+async fn insert_admin(
+    tx: &mut Transaction<'_, Postgres>,
+    email: &str,
+    full_name: &str,
+    password: Option<&str>,
+    action: &str,
+) -> Result<CreatedAdmin, CreateAdminError> {
+    let user = User::create(
+        &mut **tx,
+        CreateUserDto {
+            // La cédula y la fecha de nacimiento se completan luego desde la API
+            document_id: format!("ADMIN-{}", &uuid::Uuid::new_v4().simple().to_string()[..8]),
+            full_name: full_name.to_string(),
+            email: email.to_string(),
+            phone: None,
+            address: None,
+            birth_date: NaiveDate::from_ymd_opt(1970, 1, 1).expect("fecha válida"),
+            role: Role::Admin,
+        },
+    )
+    .await
+    .map_err(|e| match e {
+        DbError::Conflict(_) => CreateAdminError::EmailTaken(email.to_string()),
+        e => CreateAdminError::Database(e),
+    })?;
+
+    let generated_password = match password {
+        Some(password) => {
+            Authentication::create(&mut **tx, NewAuthentication { user_id: user.id, password: password.to_string() }).await?;
+            None
+        }
+        None => {
+            let password = generate_password();
+            Authentication::create_with_temporary_password(&mut **tx, user.id, &password).await?;
+            Some(password)
+        }
+    };
+
+    let event = NewAuditLogEntry {
+        action: action.to_string(),
+        entity_type: "user".to_string(),
+        entity_id: Some(user.id),
+        new_value: Some(json!({ "email": user.email, "generated_password": generated_password.is_some() })),
+        ..Default::default()
+    };
+    AuditLogEntry::record(&mut **tx, &event).await?;
+
+    Ok(CreatedAdmin { user, generated_password })
+}
+
+/// Contraseña aleatoria alfanumérica de [`GENERATED_PASSWORD_LENGTH`] caracteres
+pub fn generate_password() -> String {
+    use rand::{distributions::Alphanumeric, Rng};
+
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(GENERATED_PASSWORD_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+/// Exportación en curso: la transacción de solo lectura y las tablas pendientes
+struct Export {
+    tx: Transaction<'static, Postgres>,
+    /// `None` una vez cerrado el archivo
+    archive: Option<ArchiveWriter>,
+    pending: VecDeque<String>,
+    self_references: BTreeMap<String, Vec<String>>,
+    manifest: BackupManifest,
+}
+
+impl Export {
+    async fn open(pool: &TenantPool, options: BackupOptions) -> Result<Self, BackupError> {
+        let mut tx = pool.begin_read_only().await?;
+        let schema = SchemaInfo::load(&mut tx).await?;
+        let schema_version = schema_version(&mut tx).await?;
+
+        let (excluded, included): (Vec<String>, Vec<String>) = schema
+            .tables
+            .iter()
+            .filter(|name| !SKIPPED_TABLES.contains(&name.as_str()))
+            .cloned()
+            .partition(|name| !options.include_secrets && SECRET_TABLES.contains(&name.as_str()));
+        let created_at = Utc::now();
+
+        Ok(Self {
+            tx,
+            archive: Some(ArchiveWriter::new(created_at.timestamp().max(0) as u64)),
+            pending: referential_order(&included, &schema.references).into(),
+            self_references: schema.self_references,
+            manifest: BackupManifest {
+                format: BACKUP_FORMAT_VERSION,
+                created_at,
+                institution: pool.tenant().map(|tenant| tenant.slug().to_string()),
+                schema_version,
+                tables: Vec::new(),
+                excluded_tables: excluded,
+            },
+        })
+    }
+
+    /// Siguiente fragmento comprimido: una tabla, o el manifiesto y el cierre del archivo
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>, BackupError> {
+        if self.archive.is_none() {
+            return Ok(None);
+        }
+
+        if let Some(name) = self.pending.pop_front() {
+            let (data, rows) = dump_table(&mut self.tx, &name, self.self_references.get(&name)).await?;
+            let archive = self.archive.as_mut().expect("archive is open while tables are pending");
+            archive.append(&data_path(&name), &data)?;
+            self.manifest.tables.push(BackupTable { name, rows });
+            return Ok(Some(archive.take_output()));
+        }
+
+        let mut archive = self.archive.take().expect("archive is open");
+        archive.append(MANIFEST_PATH, &serde_json::to_vec_pretty(&self.manifest).map_err(std::io::Error::from)?)?;
+        let mut chunk = archive.take_output();
+        chunk.extend(archive.finish()?);
+        Ok(Some(chunk))
+    }
+}
+
+/// Tablas y claves foráneas del esquema activo (el de la institución, o `public`)
+#[derive(Debug, Default)]
+struct SchemaInfo {
+    tables: BTreeSet<String>,
+    /// `(tabla, tabla referenciada)` por cada clave foránea entre tablas distintas
+    references: Vec<(String, String)>,
+    /// Columnas de las claves foráneas que apuntan a la misma tabla
+    self_references: BTreeMap<String, Vec<String>>,
+}
+
+impl SchemaInfo {
+    async fn load(conn: &mut PgConnection) -> Result<Self, SqlxError> {
+        let tables: Vec<String> =
+            sqlx::query_scalar("SELECT tablename::TEXT FROM pg_tables WHERE schemaname = current_schema()")
+                .fetch_all(&mut *conn)
+                .await?;
+        let keys: Vec<(String, String, Vec<String>)> = sqlx::query_as(
+            r#"
+            SELECT child.relname::TEXT, parent.relname::TEXT,
+                   ARRAY(SELECT a.attname::TEXT FROM pg_attribute a
+                         WHERE a.attrelid = c.conrelid AND a.attnum = ANY(c.conkey)
+                         ORDER BY a.attnum)
+            FROM pg_constraint c
+            JOIN pg_class child ON child.oid = c.conrelid
+            JOIN pg_class parent ON parent.oid = c.confrelid
+            WHERE c.contype = 'f' AND c.connamespace = to_regnamespace(current_schema())
+            "#,
+        )
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut schema = SchemaInfo {
+            tables: tables.into_iter().collect(),
+            ..Default::default()
+        };
+        for (child, parent, columns) in keys {
+            if child == parent {
+                schema.self_references.entry(child).or_default().extend(columns);
+            } else {
+                schema.references.push((child, parent));
+            }
+        }
+        Ok(schema)
+    }
+}
+
+/// Ordena las tablas de modo que cada una quede después de las que referencia
+///
+/// Las claves foráneas hacia tablas fuera de la lista se ignoran. Las tablas
+/// listas al mismo tiempo se ordenan alfabéticamente; un ciclo se rompe por la
+/// primera tabla en orden alfabético y la carga informará la clave violada.
+fn referential_order(tables: &[String], references: &[(String, String)]) -> Vec<String> {
+    let mut parents: BTreeMap<&str, BTreeSet<&str>> =
+        tables.iter().map(|name| (name.as_str(), BTreeSet::new())).collect();
+    for (child, parent) in references {
+        if parents.contains_key(parent.as_str()) {
+            if let Some(set) = parents.get_mut(child.as_str()) {
+                set.insert(parent.as_str());
+            }
+        }
+    }
+
+    let mut order = Vec::with_capacity(parents.len());
+    while let Some(first) = parents.keys().next().copied() {
+        let mut ready: Vec<&str> = parents
+            .iter()
+            .filter(|(_, pending)| pending.is_empty())
+            .map(|(name, _)| *name)
+            .collect();
+        if ready.is_empty() {
+            ready.push(first);
+        }
+        for name in ready {
+            parents.remove(name);
+            parents.values_mut().for_each(|pending| {
+                pending.remove(name);
+            });
+            order.push(name.to_string());
+        }
+    }
+    order
+}
+
+/// Última migración aplicada en el esquema activo
+async fn schema_version(conn: &mut PgConnection) -> Result<Option<String>, SqlxError> {
+    sqlx::query_scalar("SELECT MAX(version)::TEXT FROM schema_migrations")
+        .fetch_one(conn)
+        .await
+}
+
+/// Filas de una tabla como JSON-lines
+///
+/// Las filas que apuntan a otra de la misma tabla (un recargo y su pago) van
+/// después de las que no, para que al restaurar por lotes la referida ya exista.
+async fn dump_table(
+    conn: &mut PgConnection,
+    table: &str,
+    self_references: Option<&Vec<String>>,
+) -> Result<(Vec<u8>, u64), SqlxError> {
+    let order = self_references
+        .map(|columns| {
+            let keys: Vec<String> = columns
+                .iter()
+                .map(|column| format!("{} IS NOT NULL", quote_ident(column)))
+                .collect();
+            format!(" ORDER BY {}", keys.join(", "))
+        })
+        .unwrap_or_default();
+    let sql = format!("SELECT to_jsonb(t)::TEXT FROM {} t{}", quote_ident(table), order);
+
+    let mut rows = sqlx::query_scalar::<_, String>(&sql).fetch(&mut *conn);
+    let (mut data, mut count) = (Vec::new(), 0);
+    while let Some(row) = rows.try_next().await? {
+        data.extend_from_slice(row.as_bytes());
+        data.push(b'\n');
+        count += 1;
+    }
+    Ok((data, count))
+}
+
+/// Inserta las filas JSON-lines de una tabla
+async fn load_table(conn: &mut PgConnection, table: &str, data: &[u8]) -> Result<u64, BackupError> {
+    let invalid = |message: String| BackupError::InvalidArchive(format!("{}: {}", data_path(table), message));
+    let text = std::str::from_utf8(data).map_err(|e| invalid(e.to_string()))?;
+    let rows = text
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(serde_json::from_str)
+        .collect::<Result<Vec<Value>, _>>()
+        .map_err(|e| invalid(e.to_string()))?;
+
+    let sql = format!(
+        "INSERT INTO {0} SELECT * FROM jsonb_populate_recordset(NULL::{0}, $1)",
+        quote_ident(table)
+    );
+    let mut inserted = 0;
+    for batch in rows.chunks(RESTORE_BATCH_SIZE) {
+        inserted += sqlx::query(&sql)
+            .bind(Value::Array(batch.to_vec()))
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+    }
+    Ok(inserted)
+}
+
+/// Avanza las secuencias de las columnas seriales restauradas más allá del máximo cargado
+async fn reset_sequences(conn: &mut PgConnection, tables: &[String]) -> Result<(), SqlxError> {
+    let serials: Vec<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT table_name::TEXT, column_name::TEXT
+        FROM information_schema.columns
+        WHERE table_schema = current_schema() AND column_default LIKE 'nextval(%'
+        "#,
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (table, column) in serials.iter().filter(|(table, _)| tables.contains(table)) {
+        let sql = format!(
+            "SELECT setval(pg_get_serial_sequence($1, $2), COALESCE(MAX({}), 0) + 1, false) FROM {}",
+            quote_ident(column),
+            quote_ident(table)
+        );
+        sqlx::query(&sql)
+            .bind(quote_ident(table))
+            .bind(column)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Ruta del archivo de datos de una tabla
+fn data_path(table: &str) -> String {
+    format!("data/{}.jsonl", table)
+}
+
+/// Identificador SQL entre comillas; los nombres vienen del catálogo o del manifiesto
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_passwords_are_long_and_distinct() {
+        let first = generate_password();
+
+        assert_eq!(first.len(), GENERATED_PASSWORD_LENGTH);
+        assert!(first.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(first, generate_password());
+    }
+
+    fn names(tables: &[&str]) -> Vec<String> {
+        tables.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn references(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(child, parent)| (child.to_string(), parent.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_referential_order_puts_parents_first() {
+        let order = referential_order(
+            &names(&["payments", "enrollments", "students", "courses", "users", "news_items"]),
+            &references(&[
+                ("payments", "students"),
+                ("enrollments", "students"),
+                ("enrollments", "courses"),
+                ("students", "users"),
+                ("audit_logs", "users"),
+            ]),
+        );
+
+        assert_eq!(
+            order,
+            names(&["courses", "news_items", "users", "students", "enrollments", "payments"])
+        );
+    }
+
+    #[test]
+    fn test_referential_order_tolerates_cycles() {
+        let order = referential_order(&names(&["b", "a", "c"]), &references(&[("a", "b"), ("b", "a"), ("c", "a")]));
+
+        assert_eq!(order, names(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn test_quote_ident() {
+        assert_eq!(quote_ident("users"), "\"users\"");
+        assert_eq!(quote_ident("we\"ird"), "\"we\"\"ird\"");
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let manifest = BackupManifest {
+            format: BACKUP_FORMAT_VERSION,
+            created_at: Utc::now(),
+            institution: Some("cnc".to_string()),
+            schema_version: Some("20250409_create_seed_rows_table".to_string()),
+            tables: vec![BackupTable {
+                name: "users".to_string(),
+                rows: 3,
+            }],
+            excluded_tables: names(&SECRET_TABLES),
+        };
+
+        let json = serde_json::to_vec(&manifest).unwrap();
+
+        assert_eq!(serde_json::from_slice::<BackupManifest>(&json).unwrap(), manifest);
+    }
+}
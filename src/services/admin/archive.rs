@@ -0,0 +1,210 @@
+//! Archivos `.tar.gz` de los respaldos
+//!
+//! Solo lo necesario para los respaldos: archivos regulares con nombres de
+//! menos de 100 bytes, en formato ustar, de modo que `tar -xzf` los abre sin
+//! herramientas propias.
+
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+/// Tamaño de bloque del formato tar
+const BLOCK_SIZE: usize = 512;
+
+/// Longitud máxima del nombre de un archivo sin usar el prefijo ustar
+const MAX_NAME_LEN: usize = 100;
+
+/// Escribe un `.tar.gz` por partes, entregando los bytes comprimidos a medida que se generan
+pub struct ArchiveWriter {
+    gz: GzEncoder<Vec<u8>>,
+    /// Fecha de modificación de los archivos, en segundos desde 1970
+    mtime: u64,
+}
+
+impl ArchiveWriter {
+    /// Crea un archivo vacío
+    ///
+    /// # Arguments
+    ///
+    /// * `mtime` - Fecha de modificación de todos los archivos, en segundos desde 1970
+    pub fn new(mtime: u64) -> Self {
+        Self {
+            gz: GzEncoder::new(Vec::new(), Compression::default()),
+            mtime,
+        }
+    }
+
+    /// Agrega un archivo regular
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Nombre del archivo dentro del tar (menos de 100 bytes)
+    /// * `data` - Contenido del archivo
+    pub fn append(&mut self, path: &str, data: &[u8]) -> io::Result<()> {
+        self.gz.write_all(&header(path, data.len() as u64, self.mtime)?)?;
+        self.gz.write_all(data)?;
+        self.gz.write_all(&vec![0; padding(data.len())])?;
+        // Vacía el compresor para que cada archivo pueda enviarse en cuanto está listo
+        self.gz.flush()
+    }
+
+    /// Bytes comprimidos generados desde la llamada anterior
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(self.gz.get_mut())
+    }
+
+    /// Cierra el tar y la compresión
+    ///
+    /// # Returns
+    ///
+    /// Los últimos bytes comprimidos, con el final del tar y del gzip
+    pub fn finish(mut self) -> io::Result<Vec<u8>> {
+        self.gz.write_all(&[0; 2 * BLOCK_SIZE])?;
+        self.gz.finish()
+    }
+}
+
+/// Lee todos los archivos regulares de un `.tar.gz`
+///
+/// # Arguments
+///
+/// * `bytes` - Contenido del `.tar.gz`
+///
+/// # Returns
+///
+/// El contenido de cada archivo, por nombre
+pub fn read_archive(bytes: &[u8]) -> io::Result<BTreeMap<String, Vec<u8>>> {
+    let mut tar = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut tar)?;
+
+    let mut files = BTreeMap::new();
+    let mut offset = 0;
+    while offset + BLOCK_SIZE <= tar.len() {
+        let block = &tar[offset..offset + BLOCK_SIZE];
+        if block.iter().all(|&b| b == 0) {
+            return Ok(files);
+        }
+        if checksum(block) != octal(&block[148..156])? {
+            return Err(invalid(format!("checksum incorrecto en el byte {}", offset)));
+        }
+
+        let size = octal(&block[124..136])? as usize;
+        let start = offset + BLOCK_SIZE;
+        let end = start
+            .checked_add(size)
+            .filter(|&end| end <= tar.len())
+            .ok_or_else(|| invalid("archivo truncado".to_string()))?;
+        // Solo archivos regulares; directorios y enlaces no forman parte de un respaldo
+        if matches!(block[156], b'0' | 0) {
+            files.insert(text(&block[..MAX_NAME_LEN]), tar[start..end].to_vec());
+        }
+        offset = end + padding(size);
+    }
+
+    Err(invalid("falta el final del archivo tar".to_string()))
+}
+
+/// Encabezado ustar de un archivo regular
+fn header(path: &str, size: u64, mtime: u64) -> io::Result<[u8; BLOCK_SIZE]> {
+    if path.len() >= MAX_NAME_LEN {
+        return Err(invalid(format!("nombre de archivo demasiado largo: {}", path)));
+    }
+
+    let mut block = [0u8; BLOCK_SIZE];
+    block[..path.len()].copy_from_slice(path.as_bytes());
+    block[100..108].copy_from_slice(b"0000644\0");
+    block[108..116].copy_from_slice(b"0000000\0");
+    block[116..124].copy_from_slice(b"0000000\0");
+    block[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    block[136..148].copy_from_slice(format!("{:011o}\0", mtime).as_bytes());
+    block[156] = b'0';
+    block[257..263].copy_from_slice(b"ustar\0");
+    block[263..265].copy_from_slice(b"00");
+    let sum = checksum(&block);
+    block[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+
+    Ok(block)
+}
+
+/// Suma de los bytes del encabezado, contando el campo de checksum como espacios
+fn checksum(block: &[u8]) -> u64 {
+    block
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| if (148..156).contains(&i) { b' ' as u64 } else { b as u64 })
+        .sum()
+}
+
+fn octal(field: &[u8]) -> io::Result<u64> {
+    let digits = text(field);
+    u64::from_str_radix(digits.trim(), 8).map_err(|_| invalid(format!("número octal inválido: {:?}", digits)))
+}
+
+/// Texto de un campo terminado en NUL
+fn text(field: &[u8]) -> String {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    String::from_utf8_lossy(&field[..end]).into_owned()
+}
+
+/// Bytes de relleno hasta completar el último bloque
+fn padding(size: usize) -> usize {
+    (BLOCK_SIZE - size % BLOCK_SIZE) % BLOCK_SIZE
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_across_chunks() {
+        let mut writer = ArchiveWriter::new(1_735_689_600);
+        writer.append("data/users.jsonl", b"{\"id\":1}\n{\"id\":2}\n").unwrap();
+        let mut bytes = writer.take_output();
+        writer.append("data/empty.jsonl", b"").unwrap();
+        writer.append("manifest.json", &vec![b'x'; 1300]).unwrap();
+        bytes.extend(writer.take_output());
+        bytes.extend(writer.finish().unwrap());
+
+        let files = read_archive(&bytes).unwrap();
+
+        assert_eq!(files.len(), 3);
+        assert_eq!(files["data/users.jsonl"], b"{\"id\":1}\n{\"id\":2}\n");
+        assert!(files["data/empty.jsonl"].is_empty());
+        assert_eq!(files["manifest.json"].len(), 1300);
+    }
+
+    #[test]
+    fn test_header_checksum() {
+        let block = header("manifest.json", 10, 0).unwrap();
+
+        assert_eq!(octal(&block[148..156]).unwrap(), checksum(&block));
+        assert_eq!(octal(&block[124..136]).unwrap(), 10);
+        assert!(header(&"x".repeat(MAX_NAME_LEN), 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_corrupted_archives_are_rejected() {
+        let mut writer = ArchiveWriter::new(0);
+        writer.append("manifest.json", b"{}").unwrap();
+        let complete = {
+            let mut bytes = writer.take_output();
+            bytes.extend(writer.finish().unwrap());
+            bytes
+        };
+
+        assert!(read_archive(b"not gzip").is_err());
+        assert!(read_archive(&complete[..complete.len() / 2]).is_err());
+
+        // A tar without its end-of-archive blocks is truncated too
+        let mut unterminated = GzEncoder::new(Vec::new(), Compression::default());
+        unterminated.write_all(&header("manifest.json", 2, 0).unwrap()).unwrap();
+        unterminated.write_all(b"{}").unwrap();
+        unterminated.write_all(&[0; BLOCK_SIZE - 2]).unwrap();
+        assert!(read_archive(&unterminated.finish().unwrap()).is_err());
+    }
+}
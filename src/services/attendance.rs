@@ -0,0 +1,417 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::{DbError, DbPool, FieldError},
+    models::attendance::{Attendance, AttendanceFilter, AttendanceStatus, AttendanceStatusCounts, NewAttendance},
+    models::calendar::{AcademicCalendar, CalendarEvent, CalendarEventType, CreateCalendarEventDto},
+    models::Enrollment,
+    services::batch::MAX_BATCH_SIZE,
+    services::{ServiceError, ServiceResult},
+};
+
+/// Justificación registrada en las asistencias de días sin clases
+pub const HOLIDAY_JUSTIFICATION: &str = "Feriado institucional";
+
+/// Resultado de conciliar la asistencia de una fecha con el calendario académico
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconciliationResult {
+    /// Cantidad de registros que pasaron a `Excused`
+    pub updated: u32,
+    /// Fecha conciliada
+    pub date: NaiveDate,
+    /// Nombre del feriado o receso que cae en la fecha
+    pub calendar_event_name: String,
+}
+
+/// Asistencia de un estudiante dentro de una sesión de clase
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkAttendanceRecord {
+    pub student_id: Uuid,
+    pub status: AttendanceStatus,
+    /// Minutos de retraso; solo para `Late`
+    pub minutes_late: Option<i32>,
+    pub notes: Option<String>,
+}
+
+/// Asistencia de toda una sesión de clase: un curso, una fecha y un registro por estudiante
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkAttendanceRequest {
+    pub course_id: Uuid,
+    pub date: NaiveDate,
+    /// Usuario que toma la asistencia
+    pub recorded_by: Uuid,
+    pub records: Vec<BulkAttendanceRecord>,
+}
+
+impl BulkAttendanceRequest {
+    /// Registro del modelo correspondiente a un elemento de la sesión
+    fn new_attendance(&self, record: &BulkAttendanceRecord) -> NewAttendance {
+        NewAttendance {
+            student_id: record.student_id,
+            course_id: self.course_id,
+            date: self.date,
+            status: record.status.clone(),
+            notes: record.notes.clone(),
+            minutes_late: record.minutes_late,
+            recorded_by: self.recorded_by,
+        }
+    }
+
+    /// Valida la sesión sin consultar la base de datos
+    ///
+    /// Los errores de cada registro se informan con su posición en el campo
+    /// (`records[2].minutes_late`). Un estudiante repetido se informa en su
+    /// segunda aparición.
+    fn validate(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if self.records.is_empty() {
+            errors.push(FieldError::new("records", "must have at least one record"));
+        }
+        if self.records.len() > MAX_BATCH_SIZE {
+            errors.push(FieldError::new(
+                "records",
+                format!("must have at most {} records", MAX_BATCH_SIZE),
+            ));
+        }
+
+        let mut first_index = HashMap::new();
+        for (index, record) in self.records.iter().enumerate() {
+            let validation = Attendance::validate_new_attendance(&self.new_attendance(record));
+            if let Err(DbError::Invalid(record_errors)) = validation {
+                for error in record_errors {
+                    // La fecha es común a toda la sesión y se informa una sola vez
+                    if error.field == "date" {
+                        if index == 0 {
+                            errors.push(error);
+                        }
+                    } else {
+                        errors.push(FieldError::new(format!("records[{}].{}", index, error.field), error.message));
+                    }
+                }
+            }
+            match first_index.get(&record.student_id) {
+                Some(first) => errors.push(FieldError::new(
+                    format!("records[{}].student_id", index),
+                    format!("duplicates records[{}]", first),
+                )),
+                None => {
+                    first_index.insert(record.student_id, index);
+                }
+            }
+        }
+
+        errors
+    }
+}
+
+/// Servicio para la gestión de asistencia
+pub struct AttendanceService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+}
+
+impl AttendanceService {
+    /// Crea una nueva instancia del servicio de asistencia
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de AttendanceService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Busca registros de asistencia y calcula los totales por estado
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Filtros de estudiante, curso, rango de fechas (inclusivo), estado y página
+    ///
+    /// # Returns
+    ///
+    /// La página de registros solicitada y los totales por estado de todo el conjunto filtrado
+    pub async fn query_attendance(
+        &self,
+        filter: AttendanceFilter,
+    ) -> ServiceResult<(Vec<Attendance>, AttendanceStatusCounts)> {
+        if let (Some(from), Some(to)) = (filter.date_from, filter.date_to) {
+            if from > to {
+                return Err(ServiceError::ValidationError(
+                    "La fecha inicial no puede ser posterior a la fecha final".to_string()
+                ));
+            }
+        }
+
+        let pool = self.db_pool.as_ref();
+        let (records, counts) = futures::try_join!(
+            Attendance::filter(pool, &filter),
+            Attendance::count_by_status(pool, &filter),
+        )?;
+
+        Ok((records, counts))
+    }
+
+    /// Registra la asistencia de toda una sesión de clase
+    ///
+    /// Antes de abrir la transacción se validan los registros y se comprueba
+    /// que todos los estudiantes tengan una inscripción activa en el curso.
+    /// Los registros se insertan en una única transacción: si alguno falla
+    /// (por ejemplo, porque el estudiante ya tiene asistencia en esa fecha)
+    /// se revierte la sesión completa.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Curso, fecha, usuario que registra y un registro por estudiante
+    ///
+    /// # Returns
+    ///
+    /// Los registros creados, en el orden de la petición, o
+    /// `DbError::Invalid` con un error por cada registro rechazado
+    pub async fn bulk_create_session(&self, request: BulkAttendanceRequest) -> ServiceResult<Vec<Attendance>> {
+        let mut errors = request.validate();
+
+        let student_ids: Vec<Uuid> = request.records.iter().map(|record| record.student_id).collect();
+        if !student_ids.is_empty() {
+            let enrolled: HashSet<Uuid> =
+                Enrollment::active_students(self.db_pool.as_ref(), request.course_id, &student_ids)
+                    .await?
+                    .into_iter()
+                    .collect();
+            for (index, student_id) in student_ids.iter().enumerate() {
+                if !enrolled.contains(student_id) {
+                    errors.push(FieldError::new(
+                        format!("records[{}].student_id", index),
+                        "is not actively enrolled in the course",
+                    ));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(DbError::Invalid(errors).into());
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        let mut created = Vec::with_capacity(request.records.len());
+        for (index, record) in request.records.iter().enumerate() {
+            match Attendance::create_in_transaction(&mut tx, request.new_attendance(record)).await {
+                Ok(attendance) => created.push(attendance),
+                Err(e) if Attendance::is_duplicate(&e) => {
+                    // La transacción quedó abortada; los registros restantes no se intentan
+                    tx.rollback().await?;
+                    return Err(DbError::Invalid(vec![FieldError::new(
+                        format!("records[{}].student_id", index),
+                        format!("already has attendance recorded on {}", request.date),
+                    )])
+                    .into());
+                }
+                Err(e) => {
+                    tx.rollback().await?;
+                    return Err(e.into());
+                }
+            }
+        }
+        tx.commit().await?;
+
+        Ok(created)
+    }
+
+    /// Crea un evento del calendario académico
+    ///
+    /// Si el evento es un feriado institucional, las asistencias ya cargadas
+    /// para sus fechas se concilian automáticamente.
+    ///
+    /// # Arguments
+    ///
+    /// * `dto` - Datos del evento
+    ///
+    /// # Returns
+    ///
+    /// El evento creado
+    pub async fn create_calendar_event(&self, dto: CreateCalendarEventDto) -> ServiceResult<CalendarEvent> {
+        if let Some(end_date) = dto.end_date {
+            if end_date < dto.start_date {
+                return Err(ServiceError::ValidationError(
+                    "La fecha de fin no puede ser anterior a la fecha de inicio".to_string()
+                ));
+            }
+        }
+
+        let event = CalendarEvent::create(self.db_pool.as_ref(), dto).await?;
+
+        if event.event_type == CalendarEventType::InstitutionalHoliday {
+            self.reconcile_event(&event).await?;
+        }
+
+        Ok(event)
+    }
+
+    /// Obtiene el calendario académico de un año
+    ///
+    /// # Arguments
+    ///
+    /// * `academic_year` - Año académico
+    ///
+    /// # Returns
+    ///
+    /// Los eventos del año en orden cronológico
+    pub async fn get_academic_calendar(&self, academic_year: i32) -> ServiceResult<AcademicCalendar> {
+        AcademicCalendar::load(self.db_pool.as_ref(), academic_year)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    /// Justifica las asistencias de un curso en una fecha sin clases
+    ///
+    /// Los registros `Present`, `Absent` o `Late` de la fecha pasan a `Excused`
+    /// con la justificación "Feriado institucional".
+    ///
+    /// # Arguments
+    ///
+    /// * `course_id` - ID del curso
+    /// * `date` - Fecha a conciliar
+    ///
+    /// # Returns
+    ///
+    /// La cantidad de registros actualizados y el evento del calendario, o un
+    /// error de validación si la fecha no es feriado ni receso
+    pub async fn reconcile_with_calendar(
+        &self,
+        course_id: Uuid,
+        date: NaiveDate,
+    ) -> ServiceResult<ReconciliationResult> {
+        let calendar = self.get_academic_calendar(date.year()).await?;
+
+        let event = calendar.non_teaching_event(date).ok_or_else(|| {
+            ServiceError::ValidationError(format!(
+                "La fecha {} no es feriado ni receso en el calendario académico",
+                date
+            ))
+        })?;
+
+        self.excuse_date(Some(course_id), date, &event.name).await
+    }
+
+    /// Concilia la asistencia de todos los cursos con los feriados y recesos de un año
+    ///
+    /// # Arguments
+    ///
+    /// * `academic_year` - Año académico
+    ///
+    /// # Returns
+    ///
+    /// Un resultado por cada día sin clases del calendario
+    pub async fn reconcile_academic_year(
+        &self,
+        academic_year: i32,
+    ) -> ServiceResult<Vec<ReconciliationResult>> {
+        let calendar = self.get_academic_calendar(academic_year).await?;
+
+        let mut results = Vec::new();
+        for event in calendar.non_teaching_events() {
+            results.extend(self.reconcile_event(event).await?);
+        }
+
+        Ok(results)
+    }
+
+    /// Justifica las asistencias de todos los cursos en cada día del evento
+    async fn reconcile_event(&self, event: &CalendarEvent) -> ServiceResult<Vec<ReconciliationResult>> {
+        let mut results = Vec::new();
+        for date in event.dates() {
+            results.push(self.excuse_date(None, date, &event.name).await?);
+        }
+
+        Ok(results)
+    }
+
+    async fn excuse_date(
+        &self,
+        course_id: Option<Uuid>,
+        date: NaiveDate,
+        event_name: &str,
+    ) -> ServiceResult<ReconciliationResult> {
+        let updated = Attendance::excuse_for_date(self.db_pool.as_ref(), course_id, date, HOLIDAY_JUSTIFICATION)
+            .await?;
+
+        Ok(ReconciliationResult {
+            updated: updated as u32,
+            date,
+            calendar_event_name: event_name.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(student_id: Uuid, status: AttendanceStatus, minutes_late: Option<i32>) -> BulkAttendanceRecord {
+        BulkAttendanceRecord { student_id, status, minutes_late, notes: None }
+    }
+
+    fn session(records: Vec<BulkAttendanceRecord>) -> BulkAttendanceRequest {
+        BulkAttendanceRequest {
+            course_id: Uuid::new_v4(),
+            date: NaiveDate::from_ymd_opt(2025, 3, 3).unwrap(),
+            recorded_by: Uuid::new_v4(),
+            records,
+        }
+    }
+
+    fn invalid_fields(request: &BulkAttendanceRequest) -> Vec<String> {
+        request.validate().into_iter().map(|error| error.field.into_owned()).collect()
+    }
+
+    #[test]
+    fn test_valid_session() {
+        let request = session(vec![
+            record(Uuid::new_v4(), AttendanceStatus::Present, None),
+            record(Uuid::new_v4(), AttendanceStatus::Late, Some(10)),
+            record(Uuid::new_v4(), AttendanceStatus::Absent, None),
+        ]);
+
+        assert!(invalid_fields(&request).is_empty());
+    }
+
+    #[test]
+    fn test_errors_name_the_record() {
+        let repeated = Uuid::new_v4();
+        let request = session(vec![
+            record(repeated, AttendanceStatus::Present, None),
+            record(Uuid::new_v4(), AttendanceStatus::Absent, Some(5)),
+            record(repeated, AttendanceStatus::Late, Some(5)),
+        ]);
+
+        let errors = request.validate();
+
+        assert_eq!(invalid_fields(&request), ["records[1].minutes_late", "records[2].student_id"]);
+        assert_eq!(errors[1].message, "duplicates records[0]");
+    }
+
+    #[test]
+    fn test_session_level_errors_are_reported_once() {
+        assert_eq!(invalid_fields(&session(Vec::new())), ["records"]);
+
+        let mut request = session(vec![
+            record(Uuid::new_v4(), AttendanceStatus::Present, None),
+            record(Uuid::new_v4(), AttendanceStatus::Present, None),
+        ]);
+        request.date = chrono::Utc::now().date_naive() + chrono::Duration::days(7);
+        assert_eq!(invalid_fields(&request), ["date"]);
+
+        let records = (0..=MAX_BATCH_SIZE)
+            .map(|_| record(Uuid::new_v4(), AttendanceStatus::Present, None))
+            .collect();
+        assert_eq!(invalid_fields(&session(records)), ["records"]);
+    }
+}
@@ -0,0 +1,220 @@
+use std::sync::Arc;
+use futures::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    db::DbPool,
+    models::audit_log::{AuditCursor, AuditLogEntry, AuditLogFilter, NewAuditLogEntry},
+    services::{ServiceError, ServiceResult},
+};
+
+/// Cantidad de eventos por página si no se indica otra
+pub const DEFAULT_AUDIT_PAGE_SIZE: i64 = 50;
+
+/// Cantidad máxima de eventos por página
+pub const MAX_AUDIT_PAGE_SIZE: i64 = 500;
+
+/// Eventos leídos de la base de datos por cada bloque de la exportación CSV
+const EXPORT_CHUNK_SIZE: i64 = 1000;
+
+/// Encabezado de la exportación CSV
+const CSV_HEADER: &str =
+    "id,created_at,actor_id,actor_name,action,entity_type,entity_id,ip_address,user_agent,old_value,new_value\n";
+
+/// Página de eventos de auditoría
+#[derive(Debug, Serialize)]
+pub struct AuditLogPage {
+    /// Eventos de la página, del más reciente al más antiguo
+    pub data: Vec<AuditLogEntry>,
+    /// Cursor para pedir la página siguiente, o `None` si no hay más eventos
+    pub next_page: Option<String>,
+}
+
+/// Servicio de registro y consulta de la auditoría
+pub struct AuditLogService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+}
+
+impl AuditLogService {
+    /// Crea una nueva instancia del servicio de auditoría
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de AuditLogService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Registra un evento de auditoría
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Actor, acción, entidad y origen del evento
+    ///
+    /// # Returns
+    ///
+    /// El identificador del evento registrado
+    pub async fn log_action(&self, entry: NewAuditLogEntry) -> ServiceResult<Uuid> {
+        AuditLogEntry::record(self.db_pool.as_ref(), &entry)
+            .await
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Consulta eventos de auditoría con paginación por conjunto de claves
+    ///
+    /// A diferencia de LIMIT/OFFSET, el cursor no se desplaza cuando se
+    /// registran eventos nuevos mientras el administrador recorre el listado.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Filtros de actor, entidad, acción, fechas y cursor
+    /// * `page_size` - Cantidad de eventos por página
+    ///
+    /// # Returns
+    ///
+    /// La página de eventos y el cursor de la página siguiente
+    pub async fn query_logs(&self, filter: AuditLogFilter, page_size: Option<i64>) -> ServiceResult<AuditLogPage> {
+        if let (Some(from), Some(to)) = (filter.from, filter.to) {
+            if from > to {
+                return Err(ServiceError::ValidationError(
+                    "La fecha inicial no puede ser posterior a la final".to_string(),
+                ));
+            }
+        }
+
+        let page_size = page_size
+            .unwrap_or(DEFAULT_AUDIT_PAGE_SIZE)
+            .clamp(1, MAX_AUDIT_PAGE_SIZE);
+
+        // Se pide un evento de más para saber si existe una página siguiente
+        let mut data = AuditLogEntry::query(self.db_pool.as_ref(), &filter, page_size + 1)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        let next_page = if data.len() as i64 > page_size {
+            data.truncate(page_size as usize);
+            data.last().map(|entry| AuditCursor::after(entry).to_string())
+        } else {
+            None
+        };
+
+        Ok(AuditLogPage { data, next_page })
+    }
+
+    /// Exporta en CSV todos los eventos que cumplen el filtro
+    ///
+    /// Los eventos se leen por bloques de `EXPORT_CHUNK_SIZE`, de modo que la
+    /// exportación no carga toda la tabla en memoria.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Filtros de actor, entidad, acción y fechas
+    ///
+    /// # Returns
+    ///
+    /// Un stream de fragmentos CSV, comenzando por el encabezado
+    pub fn export_csv(&self, filter: AuditLogFilter) -> impl Stream<Item = ServiceResult<String>> {
+        let pool = self.db_pool.clone();
+        let header = stream::once(async { Ok(CSV_HEADER.to_string()) });
+
+        let rows = stream::unfold(Some(filter), move |state| {
+            let pool = pool.clone();
+            async move {
+                let mut filter = state?;
+                let entries = match AuditLogEntry::query(pool.as_ref(), &filter, EXPORT_CHUNK_SIZE).await {
+                    Ok(entries) => entries,
+                    Err(e) => return Some((Err(ServiceError::DatabaseError(e)), None)),
+                };
+                if entries.is_empty() {
+                    return None;
+                }
+
+                let chunk: String = entries.iter().map(csv_row).collect();
+                let next = if (entries.len() as i64) < EXPORT_CHUNK_SIZE {
+                    None
+                } else {
+                    filter.after = entries.last().map(AuditCursor::after);
+                    Some(filter)
+                };
+                Some((Ok(chunk), next))
+            }
+        });
+
+        header.chain(rows)
+    }
+}
+
+/// Convierte un evento en una línea CSV
+fn csv_row(entry: &AuditLogEntry) -> String {
+    let json = |value: &Option<serde_json::Value>| value.as_ref().map(|v| v.to_string()).unwrap_or_default();
+    let fields = [
+        entry.id.to_string(),
+        entry.created_at.to_rfc3339(),
+        entry.actor_id.map(|id| id.to_string()).unwrap_or_default(),
+        entry.actor_name.clone().unwrap_or_default(),
+        entry.action.clone(),
+        entry.entity_type.clone(),
+        entry.entity_id.map(|id| id.to_string()).unwrap_or_default(),
+        entry.ip_address.clone().unwrap_or_default(),
+        entry.user_agent.clone().unwrap_or_default(),
+        json(&entry.old_value),
+        json(&entry.new_value),
+    ];
+
+    let mut line = fields.iter().map(|field| csv_escape(field)).collect::<Vec<_>>().join(",");
+    line.push('\n');
+    line
+}
+
+/// Escapa un campo CSV según RFC 4180
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use serde_json::json;
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("simple"), "simple");
+        assert_eq!(csv_escape("Benítez, Ana"), "\"Benítez, Ana\"");
+        assert_eq!(csv_escape("dijo \"hola\""), "\"dijo \"\"hola\"\"\"");
+    }
+
+    #[test]
+    fn test_csv_row_has_one_field_per_header_column() {
+        let entry = AuditLogEntry {
+            id: Uuid::new_v4(),
+            actor_id: None,
+            actor_name: None,
+            action: "update".to_string(),
+            entity_type: "student".to_string(),
+            entity_id: Some(Uuid::new_v4()),
+            old_value: None,
+            new_value: Some(json!({ "section": "B" })),
+            ip_address: None,
+            user_agent: None,
+            created_at: Utc::now(),
+        };
+
+        let row = csv_row(&entry);
+
+        assert!(row.ends_with('\n'));
+        assert!(row.contains("\"{\"\"section\"\":\"\"B\"\"}\""));
+        assert_eq!(CSV_HEADER.matches(',').count(), 10);
+        assert_eq!(row.trim_end().split(',').count(), 11);
+    }
+}
@@ -0,0 +1,292 @@
+//! Utilidades compartidas para operaciones administrativas en lote
+//!
+//! Una operación en lote valida primero todos los IDs, luego se ejecuta en una
+//! única transacción y devuelve el resultado individual de cada ID. En modo
+//! estricto cualquier fallo grave (ID inexistente, restricción de clave
+//! foránea) revierte el lote completo; en modo no estricto cada elemento se
+//! aplica en su propio savepoint y un fallo solo revierte ese elemento.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::db::DbError;
+use crate::models::audit_log::{AuditLogEntry, NewAuditLogEntry};
+
+/// Cantidad máxima de IDs aceptados en una sola petición
+pub const MAX_BATCH_SIZE: usize = 200;
+
+/// Petición de operación en lote
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRequest {
+    /// IDs sobre los que se aplica la operación
+    pub ids: Vec<Uuid>,
+    /// Si es `true`, un ID inexistente o un elemento fallido revierte todo el lote
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Resultado de la operación para un ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    /// La operación se aplicó y fue confirmada
+    Applied,
+    /// El ID no existe y se omitió (modo no estricto)
+    NotFound,
+    /// El ID aparece repetido en la petición y se procesó una sola vez
+    Duplicate,
+    /// La operación no se aplicó porque el lote fue revertido
+    RolledBack,
+    /// La operación falló y solo este elemento se revirtió (modo no estricto)
+    Failed,
+}
+
+/// Resultado individual de un ID dentro del lote
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchItemOutcome {
+    pub id: Uuid,
+    pub status: BatchItemStatus,
+    pub message: Option<String>,
+}
+
+/// Resultado completo de una operación en lote
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+    /// Indica si la transacción fue confirmada
+    pub committed: bool,
+    /// Resultado de cada ID, en el orden de la petición
+    pub outcomes: Vec<BatchItemOutcome>,
+}
+
+/// Evento de auditoría registrado por cada elemento procesado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchAuditEvent {
+    /// Acción aplicada (por ejemplo `student.withdraw`)
+    pub action: &'static str,
+    /// Entidad afectada
+    pub entity_id: Uuid,
+}
+
+/// Plan de ejecución de un lote, calculado antes de modificar datos
+#[derive(Debug, Clone)]
+pub struct BatchPlan {
+    /// IDs existentes y únicos sobre los que se aplicará la operación
+    pub to_apply: Vec<Uuid>,
+    /// Resultado previsto para cada ID de la petición
+    pub outcomes: Vec<BatchItemOutcome>,
+}
+
+/// Valida el tamaño de la petición
+pub fn validate_batch_size(ids: &[Uuid]) -> Result<(), String> {
+    if ids.is_empty() {
+        return Err("La lista de IDs no puede estar vacía".to_string());
+    }
+    if ids.len() > MAX_BATCH_SIZE {
+        return Err(format!(
+            "Se permiten como máximo {} IDs por petición (recibidos: {})",
+            MAX_BATCH_SIZE,
+            ids.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Calcula qué IDs se procesarán a partir de los que existen en la base de datos
+///
+/// En modo estricto, si falta algún ID se devuelve `Err` con el resultado de
+/// cada elemento marcado como revertido (o no encontrado).
+pub fn plan_batch(
+    requested: &[Uuid],
+    existing: &HashSet<Uuid>,
+    strict: bool,
+) -> Result<BatchPlan, Vec<BatchItemOutcome>> {
+    let mut seen = HashSet::new();
+    let mut to_apply = Vec::new();
+    let mut outcomes = Vec::with_capacity(requested.len());
+    let mut missing = false;
+
+    for &id in requested {
+        let (status, message) = if !seen.insert(id) {
+            (BatchItemStatus::Duplicate, Some("ID repetido en la petición".to_string()))
+        } else if existing.contains(&id) {
+            to_apply.push(id);
+            (BatchItemStatus::Applied, None)
+        } else {
+            missing = true;
+            (BatchItemStatus::NotFound, Some("No existe".to_string()))
+        };
+        outcomes.push(BatchItemOutcome { id, status, message });
+    }
+
+    if strict && missing {
+        return Err(rolled_back(outcomes, "Lote revertido: hay IDs inexistentes en modo estricto"));
+    }
+
+    Ok(BatchPlan { to_apply, outcomes })
+}
+
+/// Marca como revertidos todos los elementos que iban a aplicarse
+pub fn rolled_back(outcomes: Vec<BatchItemOutcome>, reason: &str) -> Vec<BatchItemOutcome> {
+    outcomes
+        .into_iter()
+        .map(|outcome| match outcome.status {
+            BatchItemStatus::Applied => BatchItemOutcome {
+                id: outcome.id,
+                status: BatchItemStatus::RolledBack,
+                message: Some(reason.to_string()),
+            },
+            _ => outcome,
+        })
+        .collect()
+}
+
+/// Marca como fallido el elemento `id` que iba a aplicarse
+pub fn mark_failed(outcomes: &mut [BatchItemOutcome], id: Uuid, reason: &str) {
+    if let Some(outcome) = outcomes
+        .iter_mut()
+        .find(|outcome| outcome.id == id && outcome.status == BatchItemStatus::Applied)
+    {
+        outcome.status = BatchItemStatus::Failed;
+        outcome.message = Some(reason.to_string());
+    }
+}
+
+/// Genera un evento de auditoría por cada elemento aplicado
+pub fn audit_events(action: &'static str, outcomes: &[BatchItemOutcome]) -> Vec<BatchAuditEvent> {
+    outcomes
+        .iter()
+        .filter(|outcome| outcome.status == BatchItemStatus::Applied)
+        .map(|outcome| BatchAuditEvent {
+            action,
+            entity_id: outcome.id,
+        })
+        .collect()
+}
+
+/// Registra en `audit_logs` un evento por cada elemento aplicado
+///
+/// Se llama dentro de la transacción del lote, antes del commit, para que los
+/// eventos se confirmen o se reviertan junto con los cambios.
+///
+/// # Arguments
+///
+/// * `tx` - Transacción del lote
+/// * `audit` - Actor y origen de la petición
+/// * `action` - Acción aplicada (por ejemplo `student.withdraw`)
+/// * `entity_type` - Tipo de entidad afectada
+/// * `outcomes` - Resultado de cada ID del lote
+///
+/// # Returns
+///
+/// Los eventos registrados
+pub async fn record_audit_events(
+    tx: &mut Transaction<'_, Postgres>,
+    audit: &NewAuditLogEntry,
+    action: &'static str,
+    entity_type: &str,
+    outcomes: &[BatchItemOutcome],
+) -> Result<Vec<BatchAuditEvent>, DbError> {
+    let events = audit_events(action, outcomes);
+    for event in &events {
+        let entry = NewAuditLogEntry {
+            action: action.to_string(),
+            entity_type: entity_type.to_string(),
+            entity_id: Some(event.entity_id),
+            ..audit.clone()
+        };
+        AuditLogEntry::record(&mut **tx, &entry).await?;
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(n: usize) -> Vec<Uuid> {
+        (0..n).map(|_| Uuid::new_v4()).collect()
+    }
+
+    #[test]
+    fn test_lenient_mode_skips_missing_ids() {
+        let requested = ids(3);
+        let existing: HashSet<Uuid> = requested[..2].iter().copied().collect();
+
+        let plan = plan_batch(&requested, &existing, false).unwrap();
+
+        assert_eq!(plan.to_apply, requested[..2].to_vec());
+        assert_eq!(plan.outcomes[2].status, BatchItemStatus::NotFound);
+    }
+
+    #[test]
+    fn test_strict_mode_rolls_back_whole_batch() {
+        let requested = ids(3);
+        let existing: HashSet<Uuid> = requested[..2].iter().copied().collect();
+
+        let outcomes = plan_batch(&requested, &existing, true).unwrap_err();
+
+        assert_eq!(outcomes[0].status, BatchItemStatus::RolledBack);
+        assert_eq!(outcomes[1].status, BatchItemStatus::RolledBack);
+        assert_eq!(outcomes[2].status, BatchItemStatus::NotFound);
+    }
+
+    #[test]
+    fn test_duplicates_processed_once() {
+        let id = Uuid::new_v4();
+        let existing: HashSet<Uuid> = [id].into_iter().collect();
+
+        let plan = plan_batch(&[id, id], &existing, true).unwrap();
+
+        assert_eq!(plan.to_apply, vec![id]);
+        assert_eq!(plan.outcomes[1].status, BatchItemStatus::Duplicate);
+    }
+
+    #[test]
+    fn test_rollback_after_execution_failure() {
+        let requested = ids(2);
+        let existing: HashSet<Uuid> = requested.iter().copied().collect();
+        let plan = plan_batch(&requested, &existing, false).unwrap();
+
+        let outcomes = rolled_back(plan.outcomes, "violación de clave foránea");
+
+        assert!(outcomes.iter().all(|o| o.status == BatchItemStatus::RolledBack));
+        assert!(audit_events("enrollment.delete", &outcomes).is_empty());
+    }
+
+    #[test]
+    fn test_failed_item_is_not_audited() {
+        let id = Uuid::new_v4();
+        let existing: HashSet<Uuid> = [id].into_iter().collect();
+        let mut plan = plan_batch(&[id, id], &existing, false).unwrap();
+
+        mark_failed(&mut plan.outcomes, id, "violación de clave foránea");
+
+        assert_eq!(plan.outcomes[0].status, BatchItemStatus::Failed);
+        assert_eq!(plan.outcomes[1].status, BatchItemStatus::Duplicate);
+        assert!(audit_events("enrollment.delete", &plan.outcomes).is_empty());
+    }
+
+    #[test]
+    fn test_audit_event_per_applied_item() {
+        let requested = ids(3);
+        let existing: HashSet<Uuid> = requested[..2].iter().copied().collect();
+        let plan = plan_batch(&requested, &existing, false).unwrap();
+
+        let events = audit_events("student.withdraw", &plan.outcomes);
+
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].entity_id, requested[0]);
+        assert_eq!(events[1].action, "student.withdraw");
+    }
+
+    #[test]
+    fn test_batch_size_limits() {
+        assert!(validate_batch_size(&[]).is_err());
+        assert!(validate_batch_size(&ids(MAX_BATCH_SIZE)).is_ok());
+        assert!(validate_batch_size(&ids(MAX_BATCH_SIZE + 1)).is_err());
+    }
+}
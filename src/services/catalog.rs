@@ -0,0 +1,511 @@
+//! Catálogo de cursos de un año académico
+//!
+//! Agrupa los cursos por grado y los exporta como JSON, HTML o PDF. El
+//! contenido se arma una sola vez en [`CourseCatalog`] y cada formato es una
+//! representación distinta del mismo documento.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfLayerReference};
+use serde::{Deserialize, Serialize};
+use tera::{Context, Tera};
+
+use crate::{
+    models::ScheduleSlot,
+    services::{ServiceError, ServiceResult},
+};
+
+/// Plantilla HTML del catálogo de cursos
+const CATALOG_TEMPLATE: &str = include_str!("../../templates/course_catalog.html");
+
+/// Nombres de los días de la semana, indexados desde el lunes
+const DAY_NAMES: [&str; 7] = ["Lunes", "Martes", "Miércoles", "Jueves", "Viernes", "Sábado", "Domingo"];
+
+/// Dimensiones de página A4 en milímetros
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+
+/// Margen de la página en milímetros
+const MARGIN: f32 = 20.0;
+
+/// Cantidad aproximada de caracteres por línea de texto normal
+const WRAP_WIDTH: usize = 90;
+
+/// Nombre de la capa única de cada página
+const LAYER_NAME: &str = "Contenido";
+
+/// Formato de exportación del catálogo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Pdf,
+    Html,
+}
+
+impl ExportFormat {
+    /// Tipo MIME del documento generado
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Pdf => "application/pdf",
+            ExportFormat::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    /// Extensión del archivo descargado
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// Fila del catálogo tal como se lee de la base de datos
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CatalogRow {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub grade_level: String,
+    pub credits: f32,
+    /// Nombre completo del profesor asignado
+    pub teacher_name: Option<String>,
+    pub schedule: sqlx::types::Json<Vec<ScheduleSlot>>,
+    pub learning_objectives: Vec<String>,
+}
+
+/// Bloque del horario de un curso, con el nombre del día
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogSlot {
+    /// Día de la semana (1-7, donde 1 es lunes)
+    pub day_of_week: u8,
+    /// Nombre del día en español
+    pub day_name: String,
+    pub start_time: String,
+    pub end_time: String,
+    pub classroom: String,
+}
+
+impl From<ScheduleSlot> for CatalogSlot {
+    fn from(slot: ScheduleSlot) -> Self {
+        let day_name = DAY_NAMES
+            .get((slot.day_of_week as usize).wrapping_sub(1))
+            .map_or_else(|| format!("Día {}", slot.day_of_week), |name| name.to_string());
+
+        Self {
+            day_of_week: slot.day_of_week,
+            day_name,
+            start_time: slot.start_time,
+            end_time: slot.end_time,
+            classroom: slot.classroom,
+        }
+    }
+}
+
+/// Curso publicado en el catálogo
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogCourse {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub credits: f32,
+    /// Nombre del profesor, o `null` si el curso no tiene profesor asignado
+    pub teacher: Option<String>,
+    pub schedule: Vec<CatalogSlot>,
+    pub learning_objectives: Vec<String>,
+}
+
+/// Cursos de un mismo grado
+#[derive(Debug, Clone, Serialize)]
+pub struct CatalogGrade {
+    pub grade_level: String,
+    /// Cursos del grado ordenados por código
+    pub courses: Vec<CatalogCourse>,
+}
+
+/// Catálogo completo de un año académico
+#[derive(Debug, Clone, Serialize)]
+pub struct CourseCatalog {
+    pub academic_year: i32,
+    pub generated_at: DateTime<Utc>,
+    /// Grados ordenados por nivel
+    pub grades: Vec<CatalogGrade>,
+}
+
+impl CourseCatalog {
+    /// Arma el catálogo agrupando las filas por grado
+    ///
+    /// # Arguments
+    ///
+    /// * `academic_year` - Año académico del catálogo
+    /// * `rows` - Cursos del año académico
+    ///
+    /// # Returns
+    ///
+    /// El catálogo con los grados y sus cursos ordenados
+    pub fn from_rows(academic_year: i32, rows: Vec<CatalogRow>) -> Self {
+        let mut grades: BTreeMap<String, Vec<CatalogCourse>> = BTreeMap::new();
+
+        for row in rows {
+            let mut schedule: Vec<CatalogSlot> = row.schedule.0.into_iter().map(CatalogSlot::from).collect();
+            schedule.sort_by(|a, b| (a.day_of_week, &a.start_time).cmp(&(b.day_of_week, &b.start_time)));
+
+            grades.entry(row.grade_level).or_default().push(CatalogCourse {
+                code: row.code,
+                name: row.name,
+                description: row.description.filter(|d| !d.trim().is_empty()),
+                credits: row.credits,
+                teacher: row.teacher_name,
+                schedule,
+                learning_objectives: row.learning_objectives,
+            });
+        }
+
+        let grades = grades
+            .into_iter()
+            .map(|(grade_level, mut courses)| {
+                courses.sort_by(|a, b| a.code.cmp(&b.code));
+                CatalogGrade { grade_level, courses }
+            })
+            .collect();
+
+        Self {
+            academic_year,
+            generated_at: Utc::now(),
+            grades,
+        }
+    }
+
+    /// Genera el documento en el formato solicitado
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Formato de exportación
+    ///
+    /// # Returns
+    ///
+    /// El contenido del documento
+    pub fn render(&self, format: ExportFormat) -> ServiceResult<Vec<u8>> {
+        match format {
+            ExportFormat::Json => self.render_json(),
+            ExportFormat::Html => self.render_html().map(String::into_bytes),
+            ExportFormat::Pdf => self.render_pdf(),
+        }
+    }
+
+    fn render_json(&self) -> ServiceResult<Vec<u8>> {
+        serde_json::to_vec_pretty(self)
+            .map_err(|e| ServiceError::GenericError(format!("Error al serializar el catálogo: {}", e)))
+    }
+
+    fn render_html(&self) -> ServiceResult<String> {
+        let mut context = Context::new();
+        context.insert("catalog", self);
+
+        Tera::one_off(CATALOG_TEMPLATE, &context, true)
+            .map_err(|e| ServiceError::GenericError(format!("Error al generar el catálogo: {}", e)))
+    }
+
+    fn render_pdf(&self) -> ServiceResult<Vec<u8>> {
+        let pdf_error = |e: printpdf::Error| ServiceError::GenericError(format!("Error al generar el PDF: {}", e));
+
+        let title = format!("Catálogo de cursos {}", self.academic_year);
+        let (doc, toc_page, toc_layer) = PdfDocument::new(&title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), LAYER_NAME);
+        let fonts = Fonts {
+            regular: doc.add_builtin_font(BuiltinFont::Helvetica).map_err(pdf_error)?,
+            bold: doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(pdf_error)?,
+        };
+
+        // Cada grado empieza en una página nueva; el índice ocupa la primera
+        let sections: Vec<Vec<Vec<Line>>> = self.grades.iter().map(|grade| paginate(grade_lines(grade))).collect();
+
+        let mut toc = vec![
+            Line::heading(title.clone()),
+            Line::blank(),
+            Line::subheading("Índice"),
+        ];
+        let mut page_number = 2;
+        for (grade, pages) in self.grades.iter().zip(&sections) {
+            toc.push(Line::text(format!("Grado {} ........ página {}", grade.grade_level, page_number)));
+            page_number += pages.len();
+        }
+        if self.grades.is_empty() {
+            toc.push(Line::text("No hay cursos registrados para este año académico."));
+        }
+        draw_page(&doc.get_page(toc_page).get_layer(toc_layer), &toc, &fonts);
+        doc.add_bookmark("Índice", toc_page);
+
+        for (grade, pages) in self.grades.iter().zip(sections) {
+            for (i, lines) in pages.iter().enumerate() {
+                let (page, layer) = doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), LAYER_NAME);
+                if i == 0 {
+                    doc.add_bookmark(format!("Grado {}", grade.grade_level), page);
+                }
+                draw_page(&doc.get_page(page).get_layer(layer), lines, &fonts);
+            }
+        }
+
+        doc.save_to_bytes().map_err(pdf_error)
+    }
+}
+
+/// Fuentes usadas en el PDF
+struct Fonts {
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+}
+
+/// Línea de texto del PDF, ya cortada al ancho de la página
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    text: String,
+    size: f32,
+    bold: bool,
+    indent: f32,
+}
+
+impl Line {
+    fn heading(text: impl Into<String>) -> Self {
+        Self { text: text.into(), size: 18.0, bold: true, indent: 0.0 }
+    }
+
+    fn subheading(text: impl Into<String>) -> Self {
+        Self { text: text.into(), size: 13.0, bold: true, indent: 0.0 }
+    }
+
+    fn text(text: impl Into<String>) -> Self {
+        Self { text: text.into(), size: 10.0, bold: false, indent: 0.0 }
+    }
+
+    fn blank() -> Self {
+        Self::text("")
+    }
+
+    fn indented(mut self, indent: f32) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Alto de la línea en milímetros
+    fn height(&self) -> f32 {
+        self.size * 0.5
+    }
+}
+
+/// Líneas de la sección de un grado
+fn grade_lines(grade: &CatalogGrade) -> Vec<Line> {
+    let mut lines = vec![Line::heading(format!("Grado {}", grade.grade_level)), Line::blank()];
+
+    for course in &grade.courses {
+        lines.push(Line::subheading(format!("{} - {}", course.code, course.name)));
+        lines.push(Line::text(format!(
+            "Créditos: {}    Profesor: {}",
+            course.credits,
+            course.teacher.as_deref().unwrap_or("Sin asignar")
+        )));
+
+        if let Some(description) = &course.description {
+            lines.extend(wrap(description, WRAP_WIDTH).into_iter().map(Line::text));
+        }
+
+        if !course.schedule.is_empty() {
+            lines.push(Line::text("Horario:"));
+            for slot in &course.schedule {
+                lines.push(
+                    Line::text(format!("{} {}-{}, aula {}", slot.day_name, slot.start_time, slot.end_time, slot.classroom))
+                        .indented(5.0),
+                );
+            }
+        }
+
+        if !course.learning_objectives.is_empty() {
+            lines.push(Line::text("Objetivos de aprendizaje:"));
+            for objective in &course.learning_objectives {
+                for (i, part) in wrap(objective, WRAP_WIDTH - 6).into_iter().enumerate() {
+                    let text = if i == 0 { format!("- {}", part) } else { format!("  {}", part) };
+                    lines.push(Line::text(text).indented(5.0));
+                }
+            }
+        }
+
+        lines.push(Line::blank());
+    }
+
+    lines
+}
+
+/// Reparte las líneas en páginas según el alto disponible
+fn paginate(lines: Vec<Line>) -> Vec<Vec<Line>> {
+    let available = PAGE_HEIGHT - 2.0 * MARGIN;
+    let mut pages = vec![Vec::new()];
+    let mut used = 0.0;
+
+    for line in lines {
+        if used + line.height() > available && !pages.last().is_none_or(Vec::is_empty) {
+            pages.push(Vec::new());
+            used = 0.0;
+        }
+        used += line.height();
+        if let Some(page) = pages.last_mut() {
+            page.push(line);
+        }
+    }
+
+    pages
+}
+
+/// Dibuja las líneas de una página de arriba hacia abajo
+fn draw_page(layer: &PdfLayerReference, lines: &[Line], fonts: &Fonts) {
+    let mut y = PAGE_HEIGHT - MARGIN;
+
+    for line in lines {
+        y -= line.height();
+        if line.text.is_empty() {
+            continue;
+        }
+        let font = if line.bold { &fonts.bold } else { &fonts.regular };
+        layer.use_text(line.text.as_str(), line.size, Mm(MARGIN + line.indent), Mm(y), font);
+    }
+}
+
+/// Corta un texto en líneas de a lo sumo `width` caracteres, respetando palabras
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(code: &str, grade_level: &str, teacher_name: Option<&str>) -> CatalogRow {
+        CatalogRow {
+            code: code.to_string(),
+            name: format!("Curso {}", code),
+            description: Some("Introducción a los contenidos del programa oficial.".to_string()),
+            grade_level: grade_level.to_string(),
+            credits: 4.0,
+            teacher_name: teacher_name.map(str::to_string),
+            schedule: sqlx::types::Json(vec![
+                ScheduleSlot {
+                    day_of_week: 3,
+                    start_time: "08:00".to_string(),
+                    end_time: "09:30".to_string(),
+                    classroom: "A1".to_string(),
+                },
+                ScheduleSlot {
+                    day_of_week: 1,
+                    start_time: "10:00".to_string(),
+                    end_time: "11:30".to_string(),
+                    classroom: "A1".to_string(),
+                },
+            ]),
+            learning_objectives: vec!["Resolver problemas".to_string(), "Comunicar resultados".to_string()],
+        }
+    }
+
+    fn catalog() -> CourseCatalog {
+        CourseCatalog::from_rows(
+            2024,
+            vec![
+                row("MAT2", "2", Some("Ana Benítez")),
+                row("CAS1", "1", None),
+                row("MAT1", "1", Some("Carlos Giménez")),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_courses_are_grouped_by_grade() {
+        let catalog = catalog();
+
+        let grades: Vec<&str> = catalog.grades.iter().map(|g| g.grade_level.as_str()).collect();
+        assert_eq!(grades, vec!["1", "2"]);
+
+        let codes: Vec<&str> = catalog.grades[0].courses.iter().map(|c| c.code.as_str()).collect();
+        assert_eq!(codes, vec!["CAS1", "MAT1"]);
+    }
+
+    #[test]
+    fn test_schedule_is_sorted_with_day_names() {
+        let catalog = catalog();
+        let schedule = &catalog.grades[0].courses[0].schedule;
+
+        assert_eq!(schedule[0].day_name, "Lunes");
+        assert_eq!(schedule[1].day_name, "Miércoles");
+    }
+
+    #[test]
+    fn test_json_export() {
+        let bytes = catalog().render(ExportFormat::Json).unwrap();
+        assert!(!bytes.is_empty());
+
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(json["academic_year"], 2024);
+        assert_eq!(json["grades"][0]["courses"][0]["teacher"], serde_json::Value::Null);
+        assert_eq!(json["grades"][1]["courses"][0]["learning_objectives"][0], "Resolver problemas");
+    }
+
+    #[test]
+    fn test_html_export() {
+        let bytes = catalog().render(ExportFormat::Html).unwrap();
+        let html = String::from_utf8(bytes).unwrap();
+
+        assert!(html.contains("Grado 2"));
+        assert!(html.contains("Ana Benítez"));
+        assert!(html.contains("Sin asignar"));
+    }
+
+    #[test]
+    fn test_pdf_export() {
+        let bytes = catalog().render(ExportFormat::Pdf).unwrap();
+
+        assert!(bytes.starts_with(b"%PDF"));
+    }
+
+    #[test]
+    fn test_empty_catalog_renders_in_every_format() {
+        let catalog = CourseCatalog::from_rows(2024, Vec::new());
+
+        for format in [ExportFormat::Json, ExportFormat::Html, ExportFormat::Pdf] {
+            assert!(!catalog.render(format).unwrap().is_empty(), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_long_sections_span_several_pages() {
+        let lines = (0..200).map(|i| Line::text(format!("línea {}", i))).collect();
+
+        let pages = paginate(lines);
+
+        assert!(pages.len() > 1);
+        assert_eq!(pages.iter().map(Vec::len).sum::<usize>(), 200);
+    }
+
+    #[test]
+    fn test_wrap_respects_width() {
+        let lines = wrap("uno dos tres cuatro cinco", 9);
+
+        assert_eq!(lines, vec!["uno dos", "tres", "cuatro", "cinco"]);
+    }
+}
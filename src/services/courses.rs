@@ -1,17 +1,34 @@
 use std::sync::Arc;
+use serde::Serialize;
 use uuid::Uuid;
-use diesel::result::Error as DieselError;
 
 use crate::{
-    db::DbPool,
-    models::{Course, CreateCourseDto, UpdateCourseDto},
-    services::{ServiceError, ServiceResult},
+    db::{DbError, DbPool, ReadPool},
+    models::{course::{CourseNode, CreateCourseDto, PatchCourseDto, UpdateCourseDto}, Course, ScheduleSlot},
+    services::{
+        catalog::{CatalogRow, CourseCatalog, ExportFormat},
+        ServiceError, ServiceResult,
+    },
+    utils::pagination::PaginatedResponse,
 };
 
+/// Curso candidato a ser redundante con otro curso del catálogo
+#[derive(Debug, Clone, Serialize)]
+pub struct CourseSimilarity {
+    /// Curso similar encontrado
+    pub course: Course,
+    /// Puntaje de similitud del nombre (0.0 a 1.0)
+    pub similarity_score: f64,
+    /// Campos cuya similitud supera el umbral ("name", "description")
+    pub matching_fields: Vec<String>,
+}
+
 /// Servicio para la gestión de cursos
 pub struct CourseService {
     /// Pool de conexiones a la base de datos
     db_pool: Arc<DbPool>,
+    /// Pool para listados, búsquedas y exportaciones (réplica, si está configurada)
+    replica: ReadPool,
 }
 
 impl CourseService {
@@ -25,24 +42,35 @@ impl CourseService {
     ///
     /// Una nueva instancia de CourseService
     pub fn new(db_pool: Arc<DbPool>) -> Self {
-        Self { db_pool }
+        let replica = ReadPool::primary(db_pool.as_ref().clone());
+        Self { db_pool, replica }
+    }
+
+    /// Envía los listados, búsquedas y exportaciones a la réplica de lectura
+    pub fn with_replica(mut self, replica: ReadPool) -> Self {
+        self.replica = replica;
+        self
     }
 
     /// Obtiene todos los cursos con paginación
     ///
     /// # Arguments
     ///
-    /// * `page` - Número de página
+    /// * `page` - Número de página, desde 1
     /// * `page_size` - Tamaño de página
     ///
     /// # Returns
     ///
-    /// Un vector con los cursos encontrados
-    pub async fn get_all_courses(&self, page: u32, page_size: u32) -> ServiceResult<Vec<Course>> {
-        let pool = self.db_pool.as_ref();
-        Course::find_all(pool, page, page_size)
-            .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+    /// La página de cursos y el total de cursos, consultados en paralelo
+    pub async fn get_all_courses(&self, page: u32, page_size: u32) -> ServiceResult<PaginatedResponse<Course>> {
+        let pool = &self.replica.reader().await;
+        let (courses, total) = futures::try_join!(
+            Course::find_all(pool, page.max(1), page_size),
+            Course::count(pool),
+        )
+        .map_err(ServiceError::DatabaseError)?;
+
+        Ok(PaginatedResponse::new(courses, total, page.max(1), page_size))
     }
 
     /// Obtiene un curso por su ID
@@ -58,7 +86,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::find_by_id(pool, id)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))?
+            .map_err(ServiceError::DatabaseError)?
             .ok_or_else(|| ServiceError::NotFound(format!("Curso con ID {}", id)))
     }
 
@@ -75,7 +103,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::find_by_code(pool, code)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))?
+            .map_err(ServiceError::DatabaseError)?
             .ok_or_else(|| ServiceError::NotFound(format!("Curso con código {}", code)))
     }
 
@@ -92,7 +120,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::find_by_grade_level(pool, grade_level)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Obtiene cursos por profesor asignado
@@ -108,7 +136,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::find_by_teacher(pool, teacher_id)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Obtiene cursos por año académico
@@ -124,7 +152,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::find_by_academic_year(pool, academic_year)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Obtiene cursos sin profesor asignado
@@ -136,7 +164,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::find_unassigned_courses(pool)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Busca cursos que coincidan con un término de búsqueda
@@ -149,10 +177,10 @@ impl CourseService {
     ///
     /// Un vector con los cursos que coinciden con el término de búsqueda
     pub async fn search_courses(&self, term: &str) -> ServiceResult<Vec<Course>> {
-        let pool = self.db_pool.as_ref();
+        let pool = &self.replica.reader().await;
         Course::search(pool, term)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Crea un nuevo curso
@@ -172,7 +200,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         let existing = Course::find_by_code(pool, &dto.code)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+            .map_err(ServiceError::DatabaseError)?;
             
         if existing.is_some() {
             return Err(ServiceError::ValidationError(
@@ -183,7 +211,7 @@ impl CourseService {
         // Crear el curso
         Course::create(pool, dto)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Actualiza un curso existente
@@ -197,6 +225,11 @@ impl CourseService {
     ///
     /// El curso actualizado
     pub async fn update_course(&self, id: Uuid, dto: UpdateCourseDto) -> ServiceResult<Course> {
+        // Validar el horario si se está actualizando
+        if let Some(ref schedule) = dto.schedule {
+            ScheduleSlot::validate_schedule(schedule)?;
+        }
+        
         // Obtener el curso existente
         let pool = self.db_pool.as_ref();
         let course = self.get_course_by_id(id).await?;
@@ -206,7 +239,7 @@ impl CourseService {
             if code != &course.code {
                 let existing = Course::find_by_code(pool, code)
                     .await
-                    .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+                    .map_err(ServiceError::DatabaseError)?;
                     
                 if existing.is_some() {
                     return Err(ServiceError::ValidationError(
@@ -219,7 +252,45 @@ impl CourseService {
         // Actualizar el curso
         course.update(pool, dto)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Aplica una actualización parcial (merge-patch) a un curso
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - UUID del curso a actualizar
+    /// * `dto` - Campos a modificar; `null` limpia la descripción o el profesor
+    ///
+    /// # Returns
+    ///
+    /// El curso actualizado
+    pub async fn patch_course(&self, id: Uuid, dto: PatchCourseDto) -> ServiceResult<Course> {
+        if let Some(ref schedule) = dto.schedule {
+            ScheduleSlot::validate_schedule(schedule)?;
+        }
+        
+        let pool = self.db_pool.as_ref();
+        let course = self.get_course_by_id(id).await?;
+        
+        // Validar el código si se está actualizando
+        if let Some(ref code) = dto.code {
+            if code != &course.code {
+                let existing = Course::find_by_code(pool, code)
+                    .await
+                    .map_err(ServiceError::DatabaseError)?;
+                    
+                if existing.is_some() {
+                    return Err(ServiceError::ValidationError(
+                        format!("Ya existe un curso con el código {}", code)
+                    ));
+                }
+            }
+        }
+        
+        course.patch(pool, dto)
+            .await
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Elimina un curso
@@ -239,7 +310,7 @@ impl CourseService {
         // Eliminar el curso
         course.delete(pool)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Asigna un profesor a un curso
@@ -260,7 +331,7 @@ impl CourseService {
         // Asignar el profesor
         course.assign_teacher(pool, teacher_id)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Elimina la asignación de profesor de un curso
@@ -280,7 +351,7 @@ impl CourseService {
         // Quitar la asignación del profesor
         course.unassign_teacher(pool)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Obtiene estadísticas sobre los cursos por grado
@@ -292,7 +363,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::stats_by_grade(pool)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Obtiene estadísticas sobre los cursos por año académico
@@ -304,7 +375,7 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::stats_by_academic_year(pool)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
     }
 
     /// Obtiene el número total de cursos
@@ -316,7 +387,96 @@ impl CourseService {
         let pool = self.db_pool.as_ref();
         Course::count(pool)
             .await
-            .map_err(|e| ServiceError::DatabaseError(e.into()))
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Busca cursos con contenido casi idéntico a un curso dado
+    ///
+    /// # Arguments
+    ///
+    /// * `course_id` - UUID del curso de referencia
+    /// * `similarity_threshold` - Similitud mínima (exclusiva) entre 0.0 y 1.0
+    ///
+    /// # Returns
+    ///
+    /// Un vector con los cursos similares, ordenados de mayor a menor similitud
+    pub async fn find_similar_courses(
+        &self,
+        course_id: Uuid,
+        similarity_threshold: f64,
+    ) -> ServiceResult<Vec<CourseSimilarity>> {
+        if !(0.0..=1.0).contains(&similarity_threshold) {
+            return Err(ServiceError::ValidationError(
+                "El umbral de similitud debe estar entre 0.0 y 1.0".to_string()
+            ));
+        }
+
+        // Verificar que el curso de referencia exista
+        self.get_course_by_id(course_id).await?;
+
+        let pool = self.db_pool.as_ref();
+        let candidates = Course::find_similar(pool, course_id, similarity_threshold)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        Ok(filter_similar_courses(candidates, similarity_threshold))
+    }
+
+    /// Obtiene el árbol completo de prerrequisitos de un curso
+    ///
+    /// # Arguments
+    ///
+    /// * `course_id` - UUID del curso
+    /// * `student_id` - Estudiante opcional para marcar los cursos ya completados
+    ///
+    /// # Returns
+    ///
+    /// Los nodos del árbol ordenados por profundidad
+    pub async fn get_prerequisite_tree(
+        &self,
+        course_id: Uuid,
+        student_id: Option<Uuid>,
+    ) -> ServiceResult<Vec<CourseNode>> {
+        let pool = self.db_pool.as_ref();
+        Course::get_full_prerequisite_tree(pool, course_id, student_id)
+            .await
+            .map_err(|e| match e {
+                DbError::NotFound(_) => ServiceError::NotFound(format!("Curso con ID {}", course_id)),
+                DbError::Conflict(msg) => ServiceError::ValidationError(msg),
+                e => ServiceError::DatabaseError(e),
+            })
+    }
+
+    /// Exporta el catálogo de cursos de un año académico
+    ///
+    /// Los cursos se agrupan por grado e incluyen profesor, horario y
+    /// objetivos de aprendizaje.
+    ///
+    /// # Arguments
+    ///
+    /// * `academic_year` - Año académico del catálogo
+    /// * `format` - Formato del documento (JSON, PDF o HTML)
+    ///
+    /// # Returns
+    ///
+    /// El contenido del documento generado
+    pub async fn export_catalog(&self, academic_year: i32, format: ExportFormat) -> ServiceResult<Vec<u8>> {
+        let rows = sqlx::query_as::<_, CatalogRow>(
+            r#"
+            SELECT
+                c.code, c.name, c.description, c.grade_level, c.credits,
+                u.full_name AS teacher_name, c.schedule, c.learning_objectives
+            FROM courses c
+            LEFT JOIN users u ON u.id = c.teacher_id
+            WHERE c.academic_year = $1
+            "#,
+        )
+        .bind(academic_year)
+        .fetch_all(&self.replica.reader().await)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+
+        CourseCatalog::from_rows(academic_year, rows).render(format)
     }
 
     // Métodos privados auxiliares
@@ -366,7 +526,100 @@ impl CourseService {
             ));
         }
         
+        // Validar horario
+        ScheduleSlot::validate_schedule(&dto.schedule)?;
+        
         Ok(())
     }
 }
 
+/// Convierte los candidatos devueltos por la base de datos en `CourseSimilarity`,
+/// descartando los que no superan el umbral de similitud del nombre
+fn filter_similar_courses(
+    candidates: Vec<(Course, f64, f64)>,
+    threshold: f64,
+) -> Vec<CourseSimilarity> {
+    let mut similar: Vec<CourseSimilarity> = candidates
+        .into_iter()
+        .filter(|(_, name_score, _)| *name_score > threshold)
+        .map(|(course, name_score, description_score)| {
+            let mut matching_fields = vec!["name".to_string()];
+            if description_score > threshold {
+                matching_fields.push("description".to_string());
+            }
+
+            CourseSimilarity {
+                course,
+                similarity_score: name_score,
+                matching_fields,
+            }
+        })
+        .collect();
+
+    similar.sort_by(|a, b| {
+        b.similarity_score
+            .partial_cmp(&a.similarity_score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    similar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn course(name: &str) -> Course {
+        Course {
+            id: Uuid::new_v4(),
+            code: name.to_uppercase(),
+            name: name.to_string(),
+            description: None,
+            grade_level: "1".to_string(),
+            credits: 1.0,
+            teacher_id: None,
+            academic_year: 2025,
+            schedule: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_filter_discards_courses_below_threshold() {
+        let candidates = vec![
+            (course("Matemática I"), 0.9, 0.0),
+            (course("Matemáticas"), 0.7, 0.0),
+            (course("Historia"), 0.2, 0.0),
+        ];
+
+        let similar = filter_similar_courses(candidates, 0.7);
+
+        assert_eq!(similar.len(), 1);
+        assert_eq!(similar[0].course.name, "Matemática I");
+    }
+
+    #[test]
+    fn test_filter_reports_matching_fields() {
+        let candidates = vec![
+            (course("Física"), 0.8, 0.9),
+            (course("Física II"), 0.75, 0.3),
+        ];
+
+        let similar = filter_similar_courses(candidates, 0.5);
+
+        assert_eq!(similar[0].matching_fields, vec!["name", "description"]);
+        assert_eq!(similar[1].matching_fields, vec!["name"]);
+    }
+
+    #[test]
+    fn test_filter_orders_by_score() {
+        let candidates = vec![
+            (course("Química"), 0.6, 0.0),
+            (course("Química I"), 0.95, 0.0),
+        ];
+
+        let similar = filter_similar_courses(candidates, 0.5);
+
+        assert_eq!(similar[0].course.name, "Química I");
+        assert_eq!(similar[1].course.name, "Química");
+    }
+}
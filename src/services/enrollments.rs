@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use crate::{
+    db::{metrics, DbPool},
+    models::{audit_log::NewAuditLogEntry, enrollment::NewEnrollment, Course, Enrollment},
+    services::{
+        batch::{self, BatchRequest, BatchResult},
+        ServiceError, ServiceResult,
+    },
+};
+
+/// Servicio para la gestión de inscripciones
+pub struct EnrollmentService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+}
+
+impl EnrollmentService {
+    /// Crea una nueva instancia del servicio de inscripciones
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de EnrollmentService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Inscribe a un estudiante en un curso
+    ///
+    /// # Arguments
+    ///
+    /// * `new_enrollment` - Estudiante, curso, año académico y estado inicial
+    ///
+    /// # Returns
+    ///
+    /// La inscripción creada, o `ServiceError::AlreadyEnrolled` si el
+    /// estudiante ya tiene una inscripción no retirada en el curso y año
+    pub async fn enroll(&self, new_enrollment: NewEnrollment) -> ServiceResult<Enrollment> {
+        match Enrollment::create(&self.db_pool, &new_enrollment).await {
+            Err(e) if Enrollment::is_duplicate(&e) => {
+                let course_name = Course::find_by_id(&self.db_pool, new_enrollment.course_id)
+                    .await?
+                    .map(|course| course.name)
+                    .unwrap_or_else(|| new_enrollment.course_id.to_string());
+
+                Err(ServiceError::AlreadyEnrolled {
+                    student_id: new_enrollment.student_id,
+                    course_id: new_enrollment.course_id,
+                    course_name,
+                })
+            }
+            result => result.map_err(ServiceError::from),
+        }
+    }
+
+    /// Elimina varias inscripciones en una única transacción
+    ///
+    /// Las inscripciones no tienen borrado lógico, por lo que se eliminan
+    /// físicamente. En modo estricto, si alguna eliminación viola una
+    /// restricción de clave foránea se revierte el lote completo; en modo no
+    /// estricto cada eliminación va en su propio savepoint y solo se revierte
+    /// la que falla.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - IDs de las inscripciones y modo estricto
+    /// * `audit` - Actor y origen de la petición, para la auditoría de cada eliminación
+    ///
+    /// # Returns
+    ///
+    /// El resultado individual de cada ID y si el lote fue confirmado
+    pub async fn batch_delete(&self, request: BatchRequest, audit: NewAuditLogEntry) -> ServiceResult<BatchResult> {
+        batch::validate_batch_size(&request.ids).map_err(ServiceError::ValidationError)?;
+
+        let pool = self.db_pool.as_ref();
+        let mut tx = metrics::begin(pool)
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+
+        let existing = Enrollment::lock_existing(&mut tx, &request.ids)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .into_iter()
+            .collect();
+
+        let plan = match batch::plan_batch(&request.ids, &existing, request.strict) {
+            Ok(plan) => plan,
+            Err(outcomes) => {
+                tx.rollback()
+                    .await
+                    .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+                return Ok(BatchResult { committed: false, outcomes });
+            }
+        };
+
+        let mut outcomes = plan.outcomes;
+        if !request.strict {
+            for &id in &plan.to_apply {
+                let deleted = crate::savepoint!(&mut tx, "batch_item", |sp| { Enrollment::delete_many(sp, &[id]).await })
+                    .await;
+                if let Err(e) = deleted {
+                    log::warn!("Eliminación de la inscripción {} revertida: {}", id, e);
+                    batch::mark_failed(&mut outcomes, id, &e.to_string());
+                }
+            }
+        } else if let Err(e) = Enrollment::delete_many(&mut tx, &plan.to_apply).await {
+            log::warn!("Eliminación en lote de inscripciones revertida: {}", e);
+            tx.rollback()
+                .await
+                .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+            return Ok(BatchResult {
+                committed: false,
+                outcomes: batch::rolled_back(outcomes, &e.to_string()),
+            });
+        }
+
+        batch::record_audit_events(&mut tx, &audit, "enrollment.delete", "enrollments", &outcomes)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+        tx.commit()
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+
+        Ok(BatchResult { committed: true, outcomes })
+    }
+}
@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use crate::{
+    db::{DbPool, ReadPool},
+    models::{
+        grade::{self, CourseGrades, GradeBook, GradingScale, StudentGrades, MAX_PERIOD},
+        Course,
+    },
+    services::{ServiceError, ServiceResult},
+};
+
+/// Usuario que consulta las calificaciones, según su rol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradeViewer {
+    /// Administración, dirección y secretaría: acceso completo
+    Staff,
+    /// Profesor: sólo los cursos que dicta
+    Teacher(Uuid),
+    /// Padre, madre o tutor: sólo los estudiantes a su cargo
+    Parent(Uuid),
+    /// Estudiante: sólo sus propias calificaciones
+    Student(Uuid),
+}
+
+/// Alcance de la consulta de calificaciones de un estudiante
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StudentGradesScope {
+    /// Todos los cursos del estudiante
+    AllCourses,
+    /// Sólo los cursos dictados por este profesor
+    TeacherCourses(Uuid),
+}
+
+/// Decide si un usuario puede ver las calificaciones de un estudiante
+///
+/// # Arguments
+///
+/// * `viewer` - Usuario que consulta
+/// * `student_id` - Usuario del estudiante consultado
+/// * `is_guardian` - Si `viewer` figura como encargado del estudiante
+///
+/// # Returns
+///
+/// Los cursos visibles, o `AuthorizationError` si no tiene acceso
+pub fn student_grades_scope(
+    viewer: GradeViewer,
+    student_id: Uuid,
+    is_guardian: bool,
+) -> ServiceResult<StudentGradesScope> {
+    match viewer {
+        GradeViewer::Staff => Ok(StudentGradesScope::AllCourses),
+        GradeViewer::Teacher(teacher_id) => Ok(StudentGradesScope::TeacherCourses(teacher_id)),
+        GradeViewer::Student(id) if id == student_id => Ok(StudentGradesScope::AllCourses),
+        GradeViewer::Parent(_) if is_guardian => Ok(StudentGradesScope::AllCourses),
+        GradeViewer::Student(_) | GradeViewer::Parent(_) => Err(ServiceError::AuthorizationError(
+            "No tiene acceso a las calificaciones de este estudiante".to_string(),
+        )),
+    }
+}
+
+/// Decide si un usuario puede ver las calificaciones de todo un curso
+///
+/// # Arguments
+///
+/// * `viewer` - Usuario que consulta
+/// * `course_teacher_id` - Profesor asignado al curso
+pub fn can_view_course_grades(viewer: GradeViewer, course_teacher_id: Option<Uuid>) -> bool {
+    match viewer {
+        GradeViewer::Staff => true,
+        GradeViewer::Teacher(id) => course_teacher_id == Some(id),
+        GradeViewer::Parent(_) | GradeViewer::Student(_) => false,
+    }
+}
+
+/// Servicio para la consulta de calificaciones
+pub struct GradeService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+    /// Pool para la planilla de calificaciones (réplica, si está configurada)
+    replica: ReadPool,
+    /// Escala usada para las notas y sus denominaciones
+    scale: GradingScale,
+}
+
+impl GradeService {
+    /// Crea una nueva instancia del servicio de calificaciones
+    ///
+    /// La escala de calificaciones se lee de la variable `GRADING_SCALE`.
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de GradeService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self::with_scale(db_pool, GradingScale::from_env())
+    }
+
+    /// Crea el servicio con una escala de calificaciones explícita
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    /// * `scale` - Escala de calificaciones
+    pub fn with_scale(db_pool: Arc<DbPool>, scale: GradingScale) -> Self {
+        let replica = ReadPool::primary(db_pool.as_ref().clone());
+        Self { db_pool, replica, scale }
+    }
+
+    /// Envía la planilla de calificaciones y su exportación a la réplica de lectura
+    pub fn with_replica(mut self, replica: ReadPool) -> Self {
+        self.replica = replica;
+        self
+    }
+
+    /// Escala de calificaciones en uso
+    pub fn scale(&self) -> &GradingScale {
+        &self.scale
+    }
+
+    /// Calificaciones de un estudiante agrupadas por curso
+    ///
+    /// # Arguments
+    ///
+    /// * `viewer` - Usuario que consulta
+    /// * `student_id` - Usuario del estudiante
+    /// * `academic_year` - Año lectivo (opcional)
+    /// * `period` - Etapa (opcional)
+    ///
+    /// # Returns
+    ///
+    /// Los cursos con el resumen de cada etapa y la calificación final si está cerrada
+    pub async fn student_grades(
+        &self,
+        viewer: GradeViewer,
+        student_id: Uuid,
+        academic_year: Option<i32>,
+        period: Option<i16>,
+    ) -> ServiceResult<Vec<CourseGrades>> {
+        let pool = self.db_pool.as_ref();
+
+        let is_guardian = match viewer {
+            GradeViewer::Parent(parent_id) => grade::is_guardian_of(pool, parent_id, student_id)
+                .await?,
+            _ => false,
+        };
+        let teacher_id = match student_grades_scope(viewer, student_id, is_guardian)? {
+            StudentGradesScope::AllCourses => None,
+            StudentGradesScope::TeacherCourses(teacher_id) => Some(teacher_id),
+        };
+
+        let rows = grade::find_student_period_rows(pool, student_id, academic_year, period, teacher_id)
+            .await?;
+
+        Ok(grade::group_by_course(rows, &self.scale))
+    }
+
+    /// Calificaciones de los estudiantes de un curso (vista del profesor)
+    ///
+    /// # Arguments
+    ///
+    /// * `viewer` - Usuario que consulta
+    /// * `course_id` - Curso
+    /// * `period` - Etapa (opcional)
+    ///
+    /// # Returns
+    ///
+    /// Los estudiantes inscritos con el resumen de cada etapa
+    pub async fn course_grades(
+        &self,
+        viewer: GradeViewer,
+        course_id: Uuid,
+        period: Option<i16>,
+    ) -> ServiceResult<Vec<StudentGrades>> {
+        let pool = self.db_pool.as_ref();
+
+        let course = Course::find_by_id(pool, course_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Curso".to_string()))?;
+
+        if !can_view_course_grades(viewer, course.teacher_id) {
+            return Err(ServiceError::AuthorizationError(
+                "Sólo el profesor del curso puede ver sus calificaciones".to_string(),
+            ));
+        }
+
+        let rows = grade::find_course_period_rows(pool, course_id, period).await?;
+
+        Ok(grade::group_by_student(rows, &self.scale))
+    }
+
+    /// Planilla de calificaciones de un curso: estudiantes × evaluaciones
+    ///
+    /// La autorización queda a cargo del llamador, con `can_view_course_grades`
+    /// y el profesor de `GradeBook::course`.
+    ///
+    /// # Arguments
+    ///
+    /// * `course_id` - Curso
+    /// * `trimester` - Etapa de las evaluaciones (opcional)
+    ///
+    /// # Returns
+    ///
+    /// Las evaluaciones en orden de fecha y una fila por estudiante con sus puntajes
+    pub async fn get_grade_book(&self, course_id: Uuid, trimester: Option<u8>) -> ServiceResult<GradeBook> {
+        if trimester.is_some_and(|t| t == 0 || i16::from(t) > MAX_PERIOD) {
+            return Err(ServiceError::ValidationError(format!(
+                "El trimestre debe estar entre 1 y {}",
+                MAX_PERIOD
+            )));
+        }
+
+        let pool = &self.replica.reader().await;
+
+        let course = grade::find_course_ref(pool, course_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Curso".to_string()))?;
+
+        let cells = grade::find_grade_book_cells(pool, course_id, trimester.map(i16::from)).await?;
+
+        Ok(GradeBook::from_cells(course, cells, &self.scale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staff_and_teachers_can_view_any_student() {
+        let student = Uuid::new_v4();
+        let teacher = Uuid::new_v4();
+
+        assert_eq!(
+            student_grades_scope(GradeViewer::Staff, student, false).unwrap(),
+            StudentGradesScope::AllCourses
+        );
+        assert_eq!(
+            student_grades_scope(GradeViewer::Teacher(teacher), student, false).unwrap(),
+            StudentGradesScope::TeacherCourses(teacher)
+        );
+    }
+
+    #[test]
+    fn test_student_only_sees_own_grades() {
+        let student = Uuid::new_v4();
+
+        assert!(student_grades_scope(GradeViewer::Student(student), student, false).is_ok());
+        assert!(matches!(
+            student_grades_scope(GradeViewer::Student(Uuid::new_v4()), student, false),
+            Err(ServiceError::AuthorizationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_sibling_parent_is_rejected() {
+        let parent = Uuid::new_v4();
+        let student = Uuid::new_v4();
+
+        assert!(student_grades_scope(GradeViewer::Parent(parent), student, true).is_ok());
+        assert!(matches!(
+            student_grades_scope(GradeViewer::Parent(parent), student, false),
+            Err(ServiceError::AuthorizationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_course_view_is_limited_to_its_teacher() {
+        let teacher = Uuid::new_v4();
+
+        assert!(can_view_course_grades(GradeViewer::Staff, None));
+        assert!(can_view_course_grades(GradeViewer::Teacher(teacher), Some(teacher)));
+        assert!(!can_view_course_grades(GradeViewer::Teacher(Uuid::new_v4()), Some(teacher)));
+        assert!(!can_view_course_grades(GradeViewer::Parent(Uuid::new_v4()), Some(teacher)));
+        assert!(!can_view_course_grades(GradeViewer::Student(Uuid::new_v4()), Some(teacher)));
+    }
+}
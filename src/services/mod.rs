@@ -4,8 +4,10 @@
 //! la lógica de negocio de la aplicación. Cada servicio se encarga de una
 //! entidad o funcionalidad específica del sistema.
 
-use crate::models;
+use crate::db::DbError;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 // Módulos para cada tipo de servicio
 pub mod users;
@@ -18,6 +20,12 @@ pub mod schedules;
 pub mod reports;
 pub mod notifications;
 pub mod payments;
+pub mod enrollments;
+pub mod batch;
+pub mod audit;
+pub mod catalog;
+pub mod admin;
+pub mod features;
 
 // Re-exportación de servicios para uso fácil
 pub use users::UserService;
@@ -30,6 +38,10 @@ pub use schedules::ScheduleService;
 pub use reports::ReportService;
 pub use notifications::NotificationService;
 pub use payments::PaymentService;
+pub use enrollments::EnrollmentService;
+pub use audit::AuditLogService;
+pub use admin::AdminService;
+pub use features::FeatureFlags;
 
 /// Estructura que contiene todos los servicios de la aplicación
 pub struct Services {
@@ -53,6 +65,14 @@ pub struct Services {
     pub notifications: Arc<NotificationService>,
     /// Servicio para gestión de pagos
     pub payments: Arc<PaymentService>,
+    /// Servicio para gestión de inscripciones
+    pub enrollments: Arc<EnrollmentService>,
+    /// Servicio de consulta del registro de auditoría
+    pub audit: Arc<AuditLogService>,
+    /// Servicio de tareas de administración (respaldos)
+    pub admin: Arc<AdminService>,
+    /// Flags de funcionalidad consultados por servicios y rutas
+    pub features: Arc<FeatureFlags>,
 }
 
 impl Services {
@@ -61,32 +81,61 @@ impl Services {
     /// # Arguments
     ///
     /// * `db_pool` - Pool de conexiones a la base de datos
+    /// * `config` - Configuración cargada con `AppConfig::load`
     ///
     /// # Returns
     ///
     /// Una nueva instancia de Services
-    pub fn new(db_pool: Arc<crate::db::DbPool>) -> Self {
+    pub fn new(db_pool: Arc<crate::db::DbPool>, config: &crate::config::AppConfig) -> Self {
+        Self::with_pools(&crate::db::DbPools::primary_only(db_pool.as_ref().clone()), config)
+    }
+
+    /// Crea los servicios enviando reportes, listados y exportaciones a la
+    /// réplica de lectura; el resto usa el pool primario
+    ///
+    /// # Arguments
+    ///
+    /// * `pools` - Pools primario y de réplica
+    /// * `config` - Configuración cargada con `AppConfig::load`
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de Services
+    pub fn with_pools(pools: &crate::db::DbPools, config: &crate::config::AppConfig) -> Self {
+        let db_pool = Arc::new(pools.primary().clone());
+        let lowercase_email_local_part = config.mail.lowercase_local_part;
         Self {
-            users: Arc::new(UserService::new(db_pool.clone())),
-            students: Arc::new(StudentService::new(db_pool.clone())),
-            teachers: Arc::new(TeacherService::new(db_pool.clone())),
-            courses: Arc::new(CourseService::new(db_pool.clone())),
+            users: Arc::new(
+                UserService::new(db_pool.clone())
+                    .with_replica(pools.replica())
+                    .with_lowercase_email_local_part(lowercase_email_local_part),
+            ),
+            students: Arc::new(StudentService::new(actix_web::web::Data::from(db_pool.clone()))),
+            teachers: Arc::new(TeacherService::new(db_pool.clone()).with_lowercase_email_local_part(lowercase_email_local_part)),
+            courses: Arc::new(CourseService::new(db_pool.clone()).with_replica(pools.replica())),
             attendance: Arc::new(AttendanceService::new(db_pool.clone())),
-            grades: Arc::new(GradeService::new(db_pool.clone())),
+            grades: Arc::new(GradeService::new(db_pool.clone()).with_replica(pools.replica())),
             schedules: Arc::new(ScheduleService::new(db_pool.clone())),
-            reports: Arc::new(ReportService::new(db_pool.clone())),
-            notifications: Arc::new(NotificationService::new(db_pool.clone())),
+            reports: Arc::new(ReportService::new(db_pool.clone()).with_replica(pools.replica())),
+            notifications: Arc::new(NotificationService::new(db_pool.clone(), config)),
             payments: Arc::new(PaymentService::new(db_pool.clone())),
+            enrollments: Arc::new(EnrollmentService::new(db_pool.clone())),
+            audit: Arc::new(AuditLogService::new(db_pool.clone())),
+            admin: Arc::new(AdminService::new(db_pool.clone()).with_lowercase_email_local_part(lowercase_email_local_part)),
+            features: Arc::new(FeatureFlags::new(db_pool.clone(), &config.features)),
         }
     }
 }
 
-/// Error genérico para los servicios de negocio
+/// Error común de los servicios de negocio
+///
+/// Los errores de sqlx llegan como `DatabaseError` a través de [`DbError`], que
+/// distingue filas inexistentes, conflictos de restricciones y fallas de conexión.
 #[derive(Debug, thiserror::Error)]
 pub enum ServiceError {
     /// Error de base de datos
     #[error("Error de base de datos: {0}")]
-    DatabaseError(#[from] diesel::result::Error),
+    DatabaseError(#[from] DbError),
     
     /// Entidad no encontrada
     #[error("{0} no encontrado/a")]
@@ -104,11 +153,156 @@ pub enum ServiceError {
     #[error("Error de autorización: {0}")]
     AuthorizationError(String),
     
+    /// La consulta superó el tiempo máximo permitido
+    #[error("Tiempo de espera agotado: {0}")]
+    Timeout(String),
+    
+    /// El estudiante ya tiene una inscripción no retirada en el curso y año
+    #[error("El estudiante {student_id} ya está inscripto en {course_name} ({course_id})")]
+    AlreadyEnrolled {
+        student_id: uuid::Uuid,
+        course_id: uuid::Uuid,
+        course_name: String,
+    },
+    
     /// Error genérico
     #[error("{0}")]
     GenericError(String),
 }
 
+impl From<sqlx::Error> for ServiceError {
+    fn from(err: sqlx::Error) -> Self {
+        ServiceError::DatabaseError(err.into())
+    }
+}
+
 /// Resultado de operaciones de servicio
 pub type ServiceResult<T> = Result<T, ServiceError>;
 
+/// Ejecuta una consulta con un tiempo máximo de espera
+///
+/// Pensado para consultas pesadas conocidas (reportes, estadísticas). Tanto el
+/// vencimiento del plazo como la cancelación por `statement_timeout` del
+/// servidor se informan como `ServiceError::Timeout`.
+///
+/// # Arguments
+///
+/// * `duration` - Tiempo máximo de espera
+/// * `query` - Consulta a ejecutar
+///
+/// # Returns
+///
+/// El resultado de la consulta o `ServiceError::Timeout` si no terminó a tiempo
+pub async fn with_timeout<T, E, F>(duration: Duration, query: F) -> ServiceResult<T>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<DbError>,
+{
+    match actix_rt::time::timeout(duration, query).await {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => match e.into() {
+            e if e.is_statement_timeout() => {
+                Err(ServiceError::Timeout("la base de datos canceló la consulta".to_string()))
+            }
+            e => Err(ServiceError::DatabaseError(e)),
+        },
+        Err(_) => Err(ServiceError::Timeout(format!("la consulta no terminó en {} ms", duration.as_millis()))),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Error de PostgreSQL con solo un código SQLSTATE
+    #[derive(Debug)]
+    struct PgCode(&'static str);
+
+    impl std::fmt::Display for PgCode {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for PgCode {}
+
+    impl sqlx::error::DatabaseError for PgCode {
+        fn message(&self) -> &str {
+            "duplicate key value violates unique constraint \"users_email_key\""
+        }
+
+        fn code(&self) -> Option<std::borrow::Cow<'_, str>> {
+            Some(self.0.into())
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::Other
+        }
+    }
+
+    #[test]
+    fn test_row_not_found_is_not_found() {
+        let error = ServiceError::from(sqlx::Error::RowNotFound);
+
+        assert!(matches!(error, ServiceError::DatabaseError(DbError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_unique_violation_is_conflict() {
+        let error = ServiceError::from(sqlx::Error::Database(Box::new(PgCode("23505"))));
+
+        match error {
+            ServiceError::DatabaseError(DbError::Conflict(message)) => assert!(message.contains("users_email_key")),
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        // Otros errores del servidor no son conflictos
+        let error = ServiceError::from(sqlx::Error::Database(Box::new(PgCode("42P01"))));
+        assert!(matches!(error, ServiceError::DatabaseError(DbError::Query(_))));
+    }
+
+    #[test]
+    fn test_pool_timeout_is_connection_error() {
+        for error in [sqlx::Error::PoolTimedOut, sqlx::Error::PoolClosed] {
+            let error = ServiceError::from(error);
+
+            assert!(matches!(error, ServiceError::DatabaseError(DbError::Connection(_))));
+            // No es un `statement_timeout`: el servidor nunca recibió la consulta
+            assert!(!matches!(error, ServiceError::DatabaseError(ref e) if e.is_statement_timeout()));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_with_timeout_reports_elapsed_query() {
+        let query = async {
+            actix_rt::time::sleep(Duration::from_millis(200)).await;
+            Ok::<_, sqlx::Error>(1)
+        };
+
+        let result = with_timeout(Duration::from_millis(20), query).await;
+
+        assert!(matches!(result, Err(ServiceError::Timeout(_))));
+    }
+
+    #[actix_rt::test]
+    async fn test_with_timeout_passes_results_through() {
+        let result = with_timeout(Duration::from_secs(1), async { Ok::<_, sqlx::Error>(7) }).await;
+        assert_eq!(result.unwrap(), 7);
+
+        let result = with_timeout(Duration::from_secs(1), async { Err::<(), _>(sqlx::Error::RowNotFound) }).await;
+        assert!(matches!(result, Err(ServiceError::DatabaseError(_))));
+    }
+}
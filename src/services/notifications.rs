@@ -0,0 +1,515 @@
+use std::sync::{Arc, RwLock};
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    config::{AppConfig, MailConfig},
+    db::DbPool,
+    models::{
+        authentication::{Authentication, EMAIL_VERIFICATION_TTL_HOURS},
+        device_token::{DeviceToken, Platform},
+        user::User,
+    },
+    services::{reports::ReportService, ServiceError, ServiceResult},
+};
+
+pub mod push;
+
+use push::{FirebaseFcmProvider, PushError, PushNotificationProvider};
+
+/// Longitud máxima aceptada para un token de dispositivo
+pub const MAX_DEVICE_TOKEN_LENGTH: usize = 4096;
+
+/// Cantidad de correos enviados en paralelo por lote
+const NEWSLETTER_BATCH_SIZE: usize = 20;
+
+/// Resultado del envío del boletín mensual
+#[derive(Debug, Default, Serialize)]
+pub struct NewsletterDispatchResult {
+    /// Correos enviados correctamente
+    pub sent: u32,
+    /// Correos que no pudieron enviarse y el motivo
+    pub failed: Vec<String>,
+}
+
+/// Resultado del envío de una notificación push a los dispositivos de un usuario
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct PushDispatchResult {
+    /// Notificaciones entregadas al proveedor
+    pub sent: u32,
+    /// Tokens eliminados por ser inválidos o no estar registrados
+    pub removed_tokens: Vec<String>,
+    /// Envíos fallidos y el motivo
+    pub failed: Vec<String>,
+}
+
+/// Servicio para envío de notificaciones
+pub struct NotificationService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+    /// Proveedor de notificaciones push; `None` si no está configurado
+    push: Option<Arc<dyn PushNotificationProvider>>,
+    /// Servidor SMTP y remitente (`[mail]`); se reemplaza al recargar la configuración
+    mail: RwLock<MailConfig>,
+    /// URL pública de la API para los enlaces de los correos (`server.base_url`)
+    base_url: String,
+}
+
+impl NotificationService {
+    /// Crea una nueva instancia del servicio de notificaciones
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    /// * `config` - Configuración de la aplicación (correo y URL pública)
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de NotificationService
+    pub fn new(db_pool: Arc<DbPool>, config: &AppConfig) -> Self {
+        let push: Option<Arc<dyn PushNotificationProvider>> = match FirebaseFcmProvider::from_env() {
+            Some(Ok(provider)) => Some(Arc::new(provider)),
+            Some(Err(e)) => {
+                log::error!("Notificaciones push desactivadas: {}", e);
+                None
+            }
+            None => None,
+        };
+        Self {
+            db_pool,
+            push,
+            mail: RwLock::new(config.mail.clone()),
+            base_url: config.server.base_url.clone(),
+        }
+    }
+
+    /// Reemplaza el servidor SMTP y el remitente; los envíos ya iniciados
+    /// terminan con los anteriores
+    pub fn set_mail_config(&self, mail: MailConfig) {
+        *self.mail.write().unwrap() = mail;
+    }
+
+    /// Servidor SMTP y remitente vigentes
+    fn mail(&self) -> MailConfig {
+        self.mail.read().unwrap().clone()
+    }
+
+    /// Reemplaza el proveedor de notificaciones push
+    pub fn with_push_provider(mut self, provider: Arc<dyn PushNotificationProvider>) -> Self {
+        self.push = Some(provider);
+        self
+    }
+
+    /// Envía el boletín mensual a los encargados que lo solicitaron
+    ///
+    /// Cada encargado recibe un ejemplar personalizado con el nombre, el curso
+    /// y los pagos pendientes del estudiante. Los correos se envían en lotes de
+    /// `NEWSLETTER_BATCH_SIZE`; un fallo individual no interrumpe el envío.
+    ///
+    /// # Arguments
+    ///
+    /// * `month` - Mes del boletín (1-12)
+    /// * `year` - Año del boletín
+    ///
+    /// # Returns
+    ///
+    /// La cantidad de correos enviados y los errores por destinatario
+    pub async fn send_monthly_newsletter(&self, month: u32, year: i32) -> ServiceResult<NewsletterDispatchResult> {
+        let reports = ReportService::new(self.db_pool.clone());
+        let data = reports.newsletter_data(month, year).await?;
+        let recipients = reports.newsletter_recipients().await?;
+
+        let mail = self.mail();
+        let mailer = smtp_transport(&mail)?;
+        let from = sender_mailbox(&mail)?;
+        let subject = format!("Boletín mensual {:02}/{}", month, year);
+
+        let mut result = NewsletterDispatchResult::default();
+        let mut delivered = Vec::new();
+
+        for chunk in recipients.chunks(NEWSLETTER_BATCH_SIZE) {
+            let mut messages = Vec::with_capacity(chunk.len());
+            for (recipient, email) in chunk {
+                let html = reports.generate_newsletter_for(&data, recipient).await?;
+                let message = email
+                    .parse::<Mailbox>()
+                    .map_err(|e| e.to_string())
+                    .and_then(|to| {
+                        Message::builder()
+                            .from(from.clone())
+                            .to(to)
+                            .subject(subject.clone())
+                            .header(ContentType::TEXT_HTML)
+                            .body(html)
+                            .map_err(|e| e.to_string())
+                    });
+                messages.push((email, message));
+            }
+
+            let sends = messages.into_iter().map(|(email, message)| {
+                let mailer = &mailer;
+                async move {
+                    let outcome = match message {
+                        Ok(message) => mailer.send(message).await.map(|_| ()).map_err(|e| e.to_string()),
+                        Err(e) => Err(e),
+                    };
+                    (email, outcome)
+                }
+            });
+
+            for (email, outcome) in futures::future::join_all(sends).await {
+                match outcome {
+                    Ok(()) => {
+                        result.sent += 1;
+                        delivered.push(email.clone());
+                    }
+                    Err(e) => {
+                        log::warn!("No se pudo enviar el boletín a {}: {}", email, e);
+                        result.failed.push(format!("{}: {}", email, e));
+                    }
+                }
+            }
+        }
+
+        // Los encargados con la aplicación instalada reciben además un aviso push
+        let devices = DeviceToken::find_by_emails(self.db_pool.as_ref(), &delivered)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+        let data = serde_json::json!({ "kind": "newsletter", "month": month, "year": year });
+        self.push_to_devices(&devices, &subject, "Ya está disponible en tu correo.", &data).await?;
+
+        Ok(result)
+    }
+
+    /// Envía una notificación push a todos los dispositivos de un usuario
+    ///
+    /// Los tokens que el proveedor informa como inválidos se eliminan. Sin
+    /// proveedor configurado no se envía nada.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - ID del usuario
+    /// * `title` - Título de la notificación
+    /// * `body` - Texto de la notificación
+    /// * `data` - Datos adicionales para la aplicación
+    ///
+    /// # Returns
+    ///
+    /// La cantidad de envíos, los tokens eliminados y los errores
+    pub async fn send_push_notification(
+        &self,
+        user_id: Uuid,
+        title: &str,
+        body: &str,
+        data: serde_json::Value,
+    ) -> ServiceResult<PushDispatchResult> {
+        let devices = DeviceToken::find_by_user(self.db_pool.as_ref(), user_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        self.push_to_devices(&devices, title, body, &data).await
+    }
+
+    /// Notifica a un usuario por correo y, si tiene dispositivos registrados, por push
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - ID del usuario
+    /// * `subject` - Asunto del correo y título de la notificación
+    /// * `body` - Texto del mensaje
+    /// * `data` - Datos adicionales para la aplicación
+    pub async fn notify_user(
+        &self,
+        user_id: Uuid,
+        subject: &str,
+        body: &str,
+        data: serde_json::Value,
+    ) -> ServiceResult<PushDispatchResult> {
+        let user = User::find_by_id(self.db_pool.as_ref(), user_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Usuario".to_string()))?;
+
+        let to = user
+            .email
+            .parse::<Mailbox>()
+            .map_err(|e| ServiceError::ValidationError(format!("Correo electrónico inválido: {}", e)))?;
+        let mail = self.mail();
+        let message = Message::builder()
+            .from(sender_mailbox(&mail)?)
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body.to_string())
+            .map_err(|e| ServiceError::GenericError(e.to_string()))?;
+        smtp_transport(&mail)?
+            .send(message)
+            .await
+            .map_err(|e| ServiceError::GenericError(format!("No se pudo enviar el correo: {}", e)))?;
+
+        self.send_push_notification(user_id, subject, body, data).await
+    }
+
+    /// Registra el token de notificaciones de un dispositivo del usuario
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - ID del usuario autenticado
+    /// * `token` - Token de registro de FCM
+    /// * `platform` - Plataforma de la aplicación
+    ///
+    /// # Returns
+    ///
+    /// El token registrado
+    pub async fn register_device(&self, user_id: Uuid, token: &str, platform: Platform) -> ServiceResult<DeviceToken> {
+        let token = token.trim();
+        if token.is_empty() || token.len() > MAX_DEVICE_TOKEN_LENGTH {
+            return Err(ServiceError::ValidationError(format!(
+                "El token del dispositivo debe tener entre 1 y {} caracteres",
+                MAX_DEVICE_TOKEN_LENGTH
+            )));
+        }
+
+        DeviceToken::register(self.db_pool.as_ref(), user_id, token, platform)
+            .await
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Elimina el token de un dispositivo del usuario
+    ///
+    /// # Returns
+    ///
+    /// NotFound si el usuario no tenía registrado ese token
+    pub async fn unregister_device(&self, user_id: Uuid, token: &str) -> ServiceResult<()> {
+        let removed = DeviceToken::delete_for_user(self.db_pool.as_ref(), user_id, token)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        if removed {
+            Ok(())
+        } else {
+            Err(ServiceError::NotFound("Token de dispositivo".to_string()))
+        }
+    }
+
+    /// Envía la notificación a los dispositivos y elimina los tokens inválidos
+    async fn push_to_devices(
+        &self,
+        devices: &[DeviceToken],
+        title: &str,
+        body: &str,
+        data: &serde_json::Value,
+    ) -> ServiceResult<PushDispatchResult> {
+        let Some(provider) = &self.push else {
+            return Ok(PushDispatchResult::default());
+        };
+
+        let result = dispatch_push(provider.as_ref(), devices, title, body, data).await;
+        if !result.removed_tokens.is_empty() {
+            DeviceToken::delete_tokens(self.db_pool.as_ref(), &result.removed_tokens)
+                .await
+                .map_err(ServiceError::DatabaseError)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Envía a un usuario el enlace para verificar su correo electrónico
+    ///
+    /// Genera un token nuevo en cada envío, por lo que los enlaces anteriores
+    /// dejan de funcionar.
+    ///
+    /// # Arguments
+    ///
+    /// * `user_id` - ID del usuario
+    ///
+    /// # Returns
+    ///
+    /// Ok si el correo fue enviado, o un error de validación si el correo ya
+    /// estaba verificado
+    pub async fn send_email_verification(&self, user_id: Uuid) -> ServiceResult<()> {
+        let pool = self.db_pool.as_ref();
+
+        let user = User::find_by_id(pool, user_id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound("Usuario".to_string()))?;
+        let auth = Authentication::find_by_user_id(pool, user_id).await?;
+
+        if auth.email_verified {
+            return Err(ServiceError::ValidationError(
+                "El correo electrónico ya fue verificado".to_string()
+            ));
+        }
+
+        let token = auth
+            .generate_email_verification_token(pool)
+            .await?;
+
+        let to = user
+            .email
+            .parse::<Mailbox>()
+            .map_err(|e| ServiceError::ValidationError(format!("Correo electrónico inválido: {}", e)))?;
+        let mail = self.mail();
+        let message = Message::builder()
+            .from(sender_mailbox(&mail)?)
+            .to(to)
+            .subject("Verificá tu correo electrónico")
+            .header(ContentType::TEXT_PLAIN)
+            .body(format!(
+                "Hola {},\n\nPara activar tu cuenta ingresá al siguiente enlace:\n{}\n\nEl enlace vence en {} horas.",
+                user.full_name,
+                verification_link(&self.base_url, &token),
+                EMAIL_VERIFICATION_TTL_HOURS
+            ))
+            .map_err(|e| ServiceError::GenericError(e.to_string()))?;
+
+        smtp_transport(&mail)?
+            .send(message)
+            .await
+            .map_err(|e| ServiceError::GenericError(format!("No se pudo enviar el correo: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+/// Envía la notificación a cada dispositivo, separando los tokens inválidos
+async fn dispatch_push(
+    provider: &dyn PushNotificationProvider,
+    devices: &[DeviceToken],
+    title: &str,
+    body: &str,
+    data: &serde_json::Value,
+) -> PushDispatchResult {
+    let sends = devices
+        .iter()
+        .map(|device| async move { (device, provider.send(&device.token, title, body, data.clone()).await) });
+
+    let mut result = PushDispatchResult::default();
+    for (device, outcome) in futures::future::join_all(sends).await {
+        match outcome {
+            Ok(()) => result.sent += 1,
+            Err(PushError::InvalidToken) => {
+                log::info!("Se elimina el token inválido del dispositivo {}", device.id);
+                result.removed_tokens.push(device.token.clone());
+            }
+            Err(e) => {
+                log::warn!("No se pudo enviar la notificación al dispositivo {}: {}", device.id, e);
+                result.failed.push(format!("{}: {}", device.id, e));
+            }
+        }
+    }
+
+    result
+}
+
+/// Enlace de verificación de correo, a partir de la URL pública de la API (`server.base_url`)
+fn verification_link(base_url: &str, token: &str) -> String {
+    format!("{}/api/auth/verify-email?token={}", base_url.trim_end_matches('/'), token)
+}
+
+/// Construye el transporte SMTP a partir de la sección `[mail]`
+fn smtp_transport(mail: &MailConfig) -> ServiceResult<AsyncSmtpTransport<Tokio1Executor>> {
+    if !mail.enabled {
+        return Err(ServiceError::GenericError("El envío de correos está desactivado (mail.enabled)".to_string()));
+    }
+    let host = mail
+        .host
+        .as_deref()
+        .ok_or_else(|| ServiceError::GenericError("SMTP_HOST no está configurado".to_string()))?;
+    let port = mail.port.unwrap_or(587);
+
+    let mut builder = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+        .map_err(|e| ServiceError::GenericError(format!("Servidor SMTP inválido: {}", e)))?
+        .port(port);
+
+    if let (Some(user), Some(password)) = (&mail.user, &mail.password) {
+        builder = builder.credentials(Credentials::new(user.clone(), password.clone()));
+    }
+
+    Ok(builder.build())
+}
+
+/// Remitente de los correos institucionales
+fn sender_mailbox(mail: &MailConfig) -> ServiceResult<Mailbox> {
+    mail.from
+        .as_deref()
+        .ok_or_else(|| ServiceError::GenericError("SMTP_FROM no está configurado".to_string()))?
+        .parse()
+        .map_err(|e| ServiceError::GenericError(format!("Remitente SMTP inválido: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use push::PushFuture;
+    use std::sync::Mutex;
+
+    /// Proveedor de prueba: registra los envíos y rechaza los tokens que empiezan con "stale"
+    #[derive(Default)]
+    struct MockProvider {
+        sent: Mutex<Vec<(String, String)>>,
+    }
+
+    impl PushNotificationProvider for MockProvider {
+        fn send<'a>(&'a self, device_token: &'a str, title: &'a str, _body: &'a str, _data: serde_json::Value) -> PushFuture<'a> {
+            Box::pin(async move {
+                if device_token.starts_with("stale") {
+                    return Err(PushError::InvalidToken);
+                }
+                if device_token.starts_with("quota") {
+                    return Err(PushError::Provider { status: 429, message: "Quota exceeded".to_string() });
+                }
+                self.sent.lock().unwrap().push((device_token.to_string(), title.to_string()));
+                Ok(())
+            })
+        }
+    }
+
+    fn device(token: &str) -> DeviceToken {
+        DeviceToken {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            token: token.to_string(),
+            platform: Platform::Android,
+            registered_at: Utc::now(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_dispatch_sends_to_every_device() {
+        let provider = MockProvider::default();
+        let devices = vec![device("phone"), device("tablet")];
+
+        let result = dispatch_push(&provider, &devices, "Boletín", "Disponible", &serde_json::json!({})).await;
+
+        assert_eq!(result.sent, 2);
+        assert!(result.removed_tokens.is_empty());
+        assert_eq!(provider.sent.lock().unwrap().len(), 2);
+    }
+
+    #[actix_rt::test]
+    async fn test_dispatch_collects_invalid_tokens() {
+        let provider = MockProvider::default();
+        let devices = vec![device("phone"), device("stale-token"), device("quota")];
+
+        let result = dispatch_push(&provider, &devices, "Boletín", "Disponible", &serde_json::json!({})).await;
+
+        assert_eq!(result.sent, 1);
+        assert_eq!(result.removed_tokens, vec!["stale-token".to_string()]);
+        assert_eq!(result.failed.len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_dispatch_without_devices() {
+        let provider = MockProvider::default();
+
+        let result = dispatch_push(&provider, &[], "Boletín", "Disponible", &serde_json::json!({})).await;
+
+        assert_eq!(result, PushDispatchResult::default());
+    }
+}
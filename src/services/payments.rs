@@ -0,0 +1,305 @@
+use std::sync::Arc;
+use chrono::NaiveDate;
+use printpdf::{BuiltinFont, Mm, PdfDocument};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    db::{DbError, DbPool},
+    models::payment::{LateFee, NewPayment, UpdatePaymentDto},
+    models::Payment,
+    services::{ServiceError, ServiceResult},
+    utils::currency::{format_guaranies, generate_iva_receipt, guaranies_to_words, InvoiceItem, IvaBreakdown, IvaRate, CURRENCY_CODE},
+};
+
+/// Dimensiones del recibo en milímetros (A5 vertical)
+const RECEIPT_WIDTH: f32 = 148.0;
+const RECEIPT_HEIGHT: f32 = 210.0;
+const RECEIPT_MARGIN: f32 = 12.0;
+
+/// Resultado del procesamiento de recargos por mora de fin de mes
+#[derive(Debug, Default, Serialize)]
+pub struct BatchLateFeeResult {
+    /// Cantidad de recargos registrados
+    pub processed: u32,
+    /// Suma de los montos de recargo registrados
+    pub total_fees_added: f64,
+    /// Pagos que no pudieron procesarse y el motivo
+    pub errors: Vec<String>,
+}
+
+/// Servicio para la gestión de pagos
+pub struct PaymentService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+}
+
+impl PaymentService {
+    /// Crea una nueva instancia del servicio de pagos
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de PaymentService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Registra un pago nuevo
+    ///
+    /// # Arguments
+    ///
+    /// * `new_payment` - Datos del pago
+    ///
+    /// # Returns
+    ///
+    /// El pago registrado, o `DbError::Invalid` si algún campo (como el RUC del pagador) es inválido
+    pub async fn create_payment(&self, new_payment: NewPayment) -> ServiceResult<Payment> {
+        Payment::create(self.db_pool.as_ref(), &new_payment)
+            .await
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Modifica un pago existente
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID del pago
+    /// * `changes` - Campos a modificar
+    ///
+    /// # Returns
+    ///
+    /// El pago actualizado, NotFound si no existe o `DbError::Invalid` si algún campo es inválido
+    pub async fn update_payment(&self, id: Uuid, changes: UpdatePaymentDto) -> ServiceResult<Payment> {
+        Payment::update(self.db_pool.as_ref(), id, &changes)
+            .await
+            .map_err(|e| match e {
+                DbError::NotFound(_) => ServiceError::NotFound(format!("Pago con ID {}", id)),
+                e => ServiceError::DatabaseError(e),
+            })
+    }
+
+    /// Aplica recargos por mora a todos los pagos vencidos antes de una fecha de corte
+    ///
+    /// Todo el proceso se ejecuta en una única transacción: si falla la
+    /// inserción de los recargos no se registra ninguno. Los pagos que ya
+    /// tienen un recargo asociado se ignoran, por lo que el proceso puede
+    /// repetirse sin duplicar cobros.
+    ///
+    /// # Arguments
+    ///
+    /// * `cutoff_date` - Fecha de corte (normalmente el último día del mes)
+    ///
+    /// # Returns
+    ///
+    /// Un resumen con la cantidad de recargos, el monto total y los errores por pago
+    pub async fn apply_late_fees_batch(&self, cutoff_date: NaiveDate) -> ServiceResult<BatchLateFeeResult> {
+        let (fees, inserted, errors) = crate::tx!(self.db_pool.as_ref(), |tx| {
+            let overdue = Payment::find_overdue_without_fee(tx, cutoff_date).await?;
+            let (fees, errors) = compute_late_fees(&overdue, cutoff_date);
+            let inserted = Payment::insert_late_fees(tx, &fees, cutoff_date).await?;
+
+            Ok::<_, DbError>((fees, inserted, errors))
+        })
+        .await
+        .map_err(ServiceError::DatabaseError)?;
+
+        if inserted.len() != fees.len() {
+            log::warn!(
+                "Se omitieron {} recargos por mora ya registrados por otro proceso",
+                fees.len() - inserted.len()
+            );
+        }
+
+        Ok(BatchLateFeeResult {
+            processed: inserted.len() as u32,
+            total_fees_added: inserted.iter().map(|(_, amount)| amount).sum(),
+            errors,
+        })
+    }
+
+    /// Genera el recibo en PDF de un pago con la liquidación del IVA
+    ///
+    /// El monto del pago se toma con el IVA incluido, como se cobra en Paraguay.
+    ///
+    /// # Arguments
+    ///
+    /// * `payment_id` - ID del pago
+    /// * `rate` - Tasa de IVA del concepto cobrado
+    ///
+    /// # Returns
+    ///
+    /// El contenido del PDF, o NotFound si el pago no existe
+    pub async fn generate_receipt_pdf(&self, payment_id: Uuid, rate: IvaRate) -> ServiceResult<Vec<u8>> {
+        let payment = Payment::find_by_id(self.db_pool.as_ref(), payment_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .ok_or_else(|| ServiceError::NotFound(format!("Pago con ID {} no encontrado", payment_id)))?;
+
+        let breakdown = generate_iva_receipt(receipt_items(&payment, rate)?);
+        render_receipt_pdf(&receipt_lines(&payment, &breakdown))
+    }
+}
+
+/// Ítems facturables de un pago; solo se emiten recibos en guaraníes
+fn receipt_items(payment: &Payment, rate: IvaRate) -> ServiceResult<Vec<InvoiceItem>> {
+    if payment.currency != CURRENCY_CODE {
+        return Err(ServiceError::ValidationError(format!(
+            "Solo se emiten recibos en {}; el pago está en {}",
+            CURRENCY_CODE, payment.currency
+        )));
+    }
+    if payment.amount < 0.0 {
+        return Err(ServiceError::ValidationError("El monto del pago no puede ser negativo".to_string()));
+    }
+
+    Ok(vec![InvoiceItem {
+        description: payment.concept.clone(),
+        amount: payment.amount.round() as u64,
+        rate,
+    }])
+}
+
+/// Monto del IVA para los formateadores, que trabajan con `i64`
+///
+/// Ningún recibo se acerca a `i64::MAX`; se satura por las dudas.
+fn receipt_amount(value: u64) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Texto del recibo, línea por línea
+fn receipt_lines(payment: &Payment, breakdown: &IvaBreakdown) -> Vec<String> {
+    let mut lines = vec![
+        format!("Recibo N° {}", payment.receipt_number.as_deref().unwrap_or("S/N")),
+        format!("Fecha: {}", payment.payment_date.format("%d/%m/%Y")),
+        format!("Forma de pago: {}", payment.payment_method),
+    ];
+    if let Some(ruc) = &payment.payer_ruc {
+        lines.push(format!("RUC: {}", ruc));
+    }
+    lines.push(String::new());
+
+    for line in &breakdown.lines {
+        lines.push(format!(
+            "{} ({}): {}",
+            line.description,
+            line.rate.label(),
+            format_guaranies(receipt_amount(line.calculation.total_amount))
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(format!("Subtotal exentas: {}", format_guaranies(receipt_amount(breakdown.exempt_subtotal))));
+    lines.push(format!("Subtotal 5%: {}", format_guaranies(receipt_amount(breakdown.taxed_5_subtotal))));
+    lines.push(format!("Subtotal 10%: {}", format_guaranies(receipt_amount(breakdown.taxed_10_subtotal))));
+    lines.push(format!(
+        "Liquidación del IVA: (5%) {}  (10%) {}  Total IVA: {}",
+        format_guaranies(receipt_amount(breakdown.iva_5)),
+        format_guaranies(receipt_amount(breakdown.iva_10)),
+        format_guaranies(receipt_amount(breakdown.total_iva))
+    ));
+    lines.push(format!("Total a pagar: {}", format_guaranies(receipt_amount(breakdown.total_amount))));
+    lines.push(format!("Son: {}", guaranies_to_words(receipt_amount(breakdown.total_amount))));
+
+    lines
+}
+
+/// Dibuja el recibo en una única página
+fn render_receipt_pdf(lines: &[String]) -> ServiceResult<Vec<u8>> {
+    let pdf_error = |e: printpdf::Error| ServiceError::GenericError(format!("Error al generar el PDF: {}", e));
+
+    let (doc, page, layer) = PdfDocument::new("Recibo de pago", Mm(RECEIPT_WIDTH), Mm(RECEIPT_HEIGHT), "Recibo");
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(pdf_error)?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(pdf_error)?;
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let mut y = RECEIPT_HEIGHT - RECEIPT_MARGIN;
+    for (i, line) in lines.iter().enumerate() {
+        let (font, size) = if i == 0 { (&bold, 14.0) } else { (&regular, 9.0) };
+        y -= size * 0.5;
+        if !line.is_empty() {
+            layer.use_text(line.as_str(), size, Mm(RECEIPT_MARGIN), Mm(y), font);
+        }
+    }
+
+    doc.save_to_bytes().map_err(pdf_error)
+}
+
+/// Calcula los recargos de los pagos vencidos, separando los que no generan recargo
+fn compute_late_fees(overdue: &[Payment], cutoff_date: NaiveDate) -> (Vec<LateFee>, Vec<String>) {
+    let mut fees = Vec::with_capacity(overdue.len());
+    let mut errors = Vec::new();
+
+    for payment in overdue {
+        let amount = payment.calculate_late_fee(cutoff_date);
+        if amount <= 0.0 {
+            errors.push(format!(
+                "Pago {}: el monto {} no genera recargo por mora",
+                payment.id, payment.amount
+            ));
+            continue;
+        }
+
+        fees.push(LateFee {
+            base_payment_id: payment.id,
+            student_id: payment.student_id,
+            amount,
+            currency: payment.currency.clone(),
+        });
+    }
+
+    (fees, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PaymentStatus;
+    use chrono::{TimeZone, Utc};
+
+    fn payment(amount: f64, currency: &str) -> Payment {
+        Payment {
+            id: Uuid::new_v4(),
+            student_id: Uuid::new_v4(),
+            concept: "Cuota mensual".to_string(),
+            amount,
+            currency: currency.to_string(),
+            payment_date: Utc.with_ymd_and_hms(2024, 3, 5, 10, 0, 0).unwrap(),
+            payment_method: "efectivo".to_string(),
+            status: PaymentStatus::Completed,
+            receipt_number: Some("001-001-0000123".to_string()),
+            payer_ruc: None,
+            notes: None,
+            base_payment_id: None,
+        }
+    }
+
+    #[test]
+    fn test_receipt_items_rejects_other_currencies() {
+        let result = receipt_items(&payment(100.0, "USD"), IvaRate::Standard10);
+        assert!(matches!(result, Err(ServiceError::ValidationError(_))));
+    }
+
+    #[test]
+    fn test_receipt_lines_include_iva_liquidation() {
+        let payment = payment(550_000.0, "PYG");
+        let breakdown = generate_iva_receipt(receipt_items(&payment, IvaRate::Standard10).unwrap());
+        let lines = receipt_lines(&payment, &breakdown);
+
+        assert_eq!(lines[0], "Recibo N° 001-001-0000123");
+        assert!(lines.contains(&"Cuota mensual (IVA 10%): Gs. 550.000".to_string()));
+        assert!(lines.contains(&"Liquidación del IVA: (5%) Gs. 0  (10%) Gs. 50.000  Total IVA: Gs. 50.000".to_string()));
+        assert_eq!(lines.last().unwrap(), "Son: quinientos cincuenta mil guaraníes");
+    }
+
+    #[test]
+    fn test_render_receipt_pdf() {
+        let payment = payment(210_000.0, "PYG");
+        let breakdown = generate_iva_receipt(receipt_items(&payment, IvaRate::Reduced5).unwrap());
+        let pdf = render_receipt_pdf(&receipt_lines(&payment, &breakdown)).unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+}
@@ -0,0 +1,765 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::Serialize;
+use tera::{Context, Tera};
+use uuid::Uuid;
+
+use crate::{
+    db::{DbError, DbPool, ReadPool},
+    models::{
+        news::{CreateNewsItemDto, UpdateNewsItemDto},
+        student_year_record::{CourseEnrollmentCounts, SectionCountRow, StudentYearRecord, WithdrawalReasonCount},
+        NewsItem,
+    },
+    services::{with_timeout, ServiceError, ServiceResult},
+};
+
+/// Tiempo máximo de cada consulta de las estadísticas de matrícula
+const STATS_QUERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Plantilla HTML del boletín mensual para encargados
+const NEWSLETTER_TEMPLATE: &str = include_str!("../../templates/newsletter.html");
+
+/// Nombres de los meses en español, indexados desde enero
+const MONTH_NAMES: [&str; 12] = [
+    "Enero", "Febrero", "Marzo", "Abril", "Mayo", "Junio",
+    "Julio", "Agosto", "Septiembre", "Octubre", "Noviembre", "Diciembre",
+];
+
+/// Frases motivacionales, una por mes del año
+const QUOTES: [(&str, &str); 12] = [
+    ("La educación es el arma más poderosa que puedes usar para cambiar el mundo.", "Nelson Mandela"),
+    ("El que lee mucho y anda mucho, ve mucho y sabe mucho.", "Miguel de Cervantes"),
+    ("Aprender sin pensar es esfuerzo perdido.", "Confucio"),
+    ("La paciencia es amarga, pero su fruto es dulce.", "Jean-Jacques Rousseau"),
+    ("Educad a los niños y no será necesario castigar a los hombres.", "Pitágoras"),
+    ("El éxito es la suma de pequeños esfuerzos repetidos día tras día.", "Robert Collier"),
+    ("La raíz de la educación es amarga, pero la fruta es dulce.", "Aristóteles"),
+    ("Dime y lo olvido, enséñame y lo recuerdo, involúcrame y lo aprendo.", "Benjamin Franklin"),
+    ("Nunca consideres el estudio como una obligación, sino como una oportunidad.", "Albert Einstein"),
+    ("Lo que sabemos es una gota de agua; lo que ignoramos es el océano.", "Isaac Newton"),
+    ("La lectura es a la mente lo que el ejercicio al cuerpo.", "Joseph Addison"),
+    ("Enseñar es aprender dos veces.", "Joseph Joubert"),
+];
+
+/// Asistencia de un grado y sección durante el mes
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ClassAttendance {
+    /// Grado o curso
+    pub grade: String,
+    /// Sección del grado
+    pub section: String,
+    /// Porcentaje de registros presentes o con llegada tardía
+    pub attendance_rate: f64,
+}
+
+/// Evaluación programada para el mes siguiente
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UpcomingExam {
+    /// Nombre de la materia
+    pub course_name: String,
+    /// Título de la evaluación
+    pub title: String,
+    /// Fecha de la evaluación
+    pub date: NaiveDate,
+}
+
+/// Pago pendiente de un estudiante, incluido como recordatorio
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct PaymentReminder {
+    pub concept: String,
+    pub amount: f64,
+    pub currency: String,
+    pub due_date: NaiveDate,
+}
+
+/// Estudiante al que se dirige un ejemplar personalizado del boletín
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct NewsletterRecipient {
+    pub student_id: Uuid,
+    pub student_name: String,
+    pub grade: String,
+    pub section: String,
+}
+
+/// Frase motivacional del boletín
+#[derive(Debug, Clone, Serialize)]
+pub struct Quote {
+    pub text: &'static str,
+    pub author: &'static str,
+}
+
+/// Contenido común a todos los ejemplares del boletín de un mes
+#[derive(Debug, Clone, Serialize)]
+pub struct NewsletterData {
+    pub month: u32,
+    pub year: i32,
+    pub news: Vec<NewsItem>,
+    pub attendance: Vec<ClassAttendance>,
+    pub exams: Vec<UpcomingExam>,
+}
+
+/// Matrícula de un grado y sección en las estadísticas de un año
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectionStats {
+    pub grade: String,
+    pub section: String,
+    /// Estudiantes matriculados en el año, incluidos los retirados
+    pub total: i64,
+    /// Estudiantes que siguen matriculados
+    pub active: i64,
+    pub withdrawn: i64,
+    /// Estudiantes sin matrícula el año anterior
+    pub new_students: i64,
+    /// Estudiantes que ya estaban matriculados el año anterior
+    pub returning_students: i64,
+    /// Matrícula del año anterior a la misma fecha
+    pub previous_year_total: i64,
+    /// Diferencia con el año anterior
+    pub change: i64,
+}
+
+/// Totales de la institución en las estadísticas de un año
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct EnrollmentTotals {
+    pub total: i64,
+    pub active: i64,
+    pub withdrawn: i64,
+    pub new_students: i64,
+    pub returning_students: i64,
+    pub previous_year_total: i64,
+    pub change: i64,
+    /// Variación porcentual respecto del año anterior; `None` si no hubo matrícula
+    pub change_percentage: Option<f64>,
+    /// Inscripciones a cursos vigentes o finalizadas
+    pub course_enrollments: i64,
+    /// Inscripciones a cursos retiradas
+    pub course_withdrawals: i64,
+}
+
+/// Estadísticas de matrícula de un año académico
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnrollmentStats {
+    pub academic_year: i32,
+    /// Fecha de corte de los datos
+    pub as_of: NaiveDate,
+    /// `true` si el año está en curso y los datos son parciales
+    pub partial: bool,
+    pub totals: EnrollmentTotals,
+    pub sections: Vec<SectionStats>,
+    pub withdrawal_reasons: Vec<WithdrawalReasonCount>,
+}
+
+impl EnrollmentStats {
+    /// Arma las estadísticas a partir de los conteos de la base de datos
+    pub fn from_parts(
+        academic_year: i32,
+        as_of: NaiveDate,
+        partial: bool,
+        rows: Vec<SectionCountRow>,
+        courses: CourseEnrollmentCounts,
+        withdrawal_reasons: Vec<WithdrawalReasonCount>,
+    ) -> Self {
+        let mut totals = EnrollmentTotals {
+            course_enrollments: courses.active,
+            course_withdrawals: courses.withdrawn,
+            ..Default::default()
+        };
+
+        let sections: Vec<SectionStats> = rows
+            .into_iter()
+            .map(|row| SectionStats {
+                active: row.total - row.withdrawn,
+                change: row.total - row.previous_total,
+                grade: row.grade,
+                section: row.section,
+                total: row.total,
+                withdrawn: row.withdrawn,
+                new_students: row.new_students,
+                returning_students: row.returning_students,
+                previous_year_total: row.previous_total,
+            })
+            .collect();
+
+        for section in &sections {
+            totals.total += section.total;
+            totals.active += section.active;
+            totals.withdrawn += section.withdrawn;
+            totals.new_students += section.new_students;
+            totals.returning_students += section.returning_students;
+            totals.previous_year_total += section.previous_year_total;
+        }
+        totals.change = totals.total - totals.previous_year_total;
+        totals.change_percentage = (totals.previous_year_total > 0).then(|| {
+            (totals.change as f64 * 10000.0 / totals.previous_year_total as f64).round() / 100.0
+        });
+
+        Self {
+            academic_year,
+            as_of,
+            partial,
+            totals,
+            sections,
+            withdrawal_reasons,
+        }
+    }
+
+    /// Exporta la matrícula por grado y sección a CSV, con una fila final de totales
+    pub fn to_csv(&self) -> Result<Vec<u8>, csv::Error> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        writer.write_record([
+            "Grado",
+            "Sección",
+            "Matriculados",
+            "Activos",
+            "Retirados",
+            "Nuevos",
+            "Reincorporados",
+            "Año anterior",
+            "Variación",
+        ])?;
+
+        for s in &self.sections {
+            writer.write_record([
+                s.grade.clone(),
+                s.section.clone(),
+                s.total.to_string(),
+                s.active.to_string(),
+                s.withdrawn.to_string(),
+                s.new_students.to_string(),
+                s.returning_students.to_string(),
+                s.previous_year_total.to_string(),
+                s.change.to_string(),
+            ])?;
+        }
+
+        let t = &self.totals;
+        writer.write_record([
+            "TOTAL".to_string(),
+            String::new(),
+            t.total.to_string(),
+            t.active.to_string(),
+            t.withdrawn.to_string(),
+            t.new_students.to_string(),
+            t.returning_students.to_string(),
+            t.previous_year_total.to_string(),
+            t.change.to_string(),
+        ])?;
+
+        writer
+            .into_inner()
+            .map_err(|e| csv::Error::from(e.into_error()))
+    }
+}
+
+/// Servicio para la generación de reportes
+pub struct ReportService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+    /// Pool para las consultas de solo lectura (réplica, si está configurada)
+    replica: ReadPool,
+}
+
+impl ReportService {
+    /// Crea una nueva instancia del servicio de reportes
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de ReportService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        let replica = ReadPool::primary(db_pool.as_ref().clone());
+        Self { db_pool, replica }
+    }
+
+    /// Envía los reportes y listados a la réplica de lectura
+    pub fn with_replica(mut self, replica: ReadPool) -> Self {
+        self.replica = replica;
+        self
+    }
+
+    /// Genera el boletín mensual genérico (sin datos de un estudiante en particular)
+    ///
+    /// # Arguments
+    ///
+    /// * `month` - Mes del boletín (1-12)
+    /// * `year` - Año del boletín
+    ///
+    /// # Returns
+    ///
+    /// El boletín en formato HTML
+    pub async fn generate_monthly_newsletter(&self, month: u32, year: i32) -> ServiceResult<String> {
+        let data = self.newsletter_data(month, year).await?;
+        render_newsletter(&data, None, &[])
+    }
+
+    /// Genera el ejemplar del boletín para un estudiante, con su curso y sus pagos pendientes
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Contenido común del boletín, obtenido con `newsletter_data`
+    /// * `recipient` - Estudiante destinatario
+    ///
+    /// # Returns
+    ///
+    /// El boletín personalizado en formato HTML
+    pub async fn generate_newsletter_for(
+        &self,
+        data: &NewsletterData,
+        recipient: &NewsletterRecipient,
+    ) -> ServiceResult<String> {
+        let payments = self.pending_payments(recipient.student_id).await?;
+        render_newsletter(data, Some(recipient), &payments)
+    }
+
+    /// Reúne las noticias, la asistencia por curso y las evaluaciones del mes siguiente
+    ///
+    /// # Arguments
+    ///
+    /// * `month` - Mes del boletín (1-12)
+    /// * `year` - Año del boletín
+    ///
+    /// # Returns
+    ///
+    /// El contenido común del boletín
+    pub async fn newsletter_data(&self, month: u32, year: i32) -> ServiceResult<NewsletterData> {
+        let (first_day, last_day) = month_bounds(month, year)
+            .ok_or_else(|| ServiceError::ValidationError(format!("Mes inválido: {}/{}", month, year)))?;
+        let (next_first, next_last) = last_day
+            .succ_opt()
+            .and_then(|day| month_bounds(day.month(), day.year()))
+            .ok_or_else(|| ServiceError::ValidationError(format!("Año inválido: {}", year)))?;
+
+        let pool = &self.replica.reader().await;
+
+        let news = NewsItem::find_published_between(pool, first_day, last_day)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        let attendance = sqlx::query_as::<_, ClassAttendance>(
+            r#"
+            SELECT s.current_grade AS grade, s.section,
+                   (100.0 * COUNT(*) FILTER (WHERE a.status IN ('present', 'late')) / COUNT(*))::float8
+                       AS attendance_rate
+            FROM attendance a
+            JOIN students s ON s.user_id = a.student_id
+            WHERE a.date BETWEEN $1 AND $2
+            GROUP BY s.current_grade, s.section
+            ORDER BY s.current_grade, s.section
+            "#,
+        )
+        .bind(first_day)
+        .bind(last_day)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+
+        let exams = sqlx::query_as::<_, UpcomingExam>(
+            r#"
+            SELECT DISTINCT c.name AS course_name, a.title, a.assessment_date::date AS date
+            FROM assessments a
+            JOIN courses c ON c.id = a.course_id
+            WHERE a.assessment_type IN ('exam', 'test')
+              AND a.assessment_date::date BETWEEN $1 AND $2
+            ORDER BY date, course_name
+            "#,
+        )
+        .bind(next_first)
+        .bind(next_last)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+
+        Ok(NewsletterData { month, year, news, attendance, exams })
+    }
+
+    /// Lista los estudiantes cuyos encargados aceptaron recibir el boletín por correo
+    ///
+    /// # Returns
+    ///
+    /// Los destinatarios junto con el correo del encargado
+    pub async fn newsletter_recipients(&self) -> ServiceResult<Vec<(NewsletterRecipient, String)>> {
+        let rows: Vec<(Uuid, String, String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT s.user_id, u.full_name, s.current_grade, s.section, s.guardian_info->>'email'
+            FROM students s
+            JOIN users u ON u.id = s.user_id
+            WHERE s.status = 'active'
+              AND (s.guardian_info->>'weekly_summary')::boolean IS TRUE
+              AND COALESCE(s.guardian_info->>'email', '') <> ''
+            ORDER BY s.current_grade, s.section, u.full_name
+            "#,
+        )
+        .fetch_all(&self.replica.reader().await)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(student_id, student_name, grade, section, email)| {
+                (NewsletterRecipient { student_id, student_name, grade, section }, email)
+            })
+            .collect())
+    }
+
+    /// Lista las noticias del boletín, de la más reciente a la más antigua
+    ///
+    /// # Arguments
+    ///
+    /// * `page` - Número de página (comienza en 1)
+    /// * `page_size` - Cantidad de noticias por página
+    ///
+    /// # Returns
+    ///
+    /// Las noticias de la página solicitada
+    pub async fn list_news(&self, page: i64, page_size: i64) -> ServiceResult<Vec<NewsItem>> {
+        let offset = (page.max(1) - 1) * page_size;
+        NewsItem::find_all(&self.replica.reader().await, page_size, offset)
+            .await
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Obtiene una noticia por su ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID de la noticia
+    ///
+    /// # Returns
+    ///
+    /// La noticia encontrada o un error NotFound
+    pub async fn get_news(&self, id: Uuid) -> ServiceResult<NewsItem> {
+        NewsItem::find_by_id(self.db_pool.as_ref(), id)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .ok_or_else(|| ServiceError::NotFound(format!("Noticia con ID {}", id)))
+    }
+
+    /// Crea una noticia para el boletín
+    ///
+    /// # Arguments
+    ///
+    /// * `dto` - Datos de la noticia
+    ///
+    /// # Returns
+    ///
+    /// La noticia creada
+    pub async fn create_news(&self, dto: CreateNewsItemDto) -> ServiceResult<NewsItem> {
+        if dto.title.trim().is_empty() || dto.content.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "El título y el contenido de la noticia son obligatorios".to_string(),
+            ));
+        }
+
+        NewsItem::create(self.db_pool.as_ref(), dto)
+            .await
+            .map_err(ServiceError::DatabaseError)
+    }
+
+    /// Actualiza una noticia existente
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID de la noticia
+    /// * `dto` - Campos a modificar
+    ///
+    /// # Returns
+    ///
+    /// La noticia actualizada
+    pub async fn update_news(&self, id: Uuid, dto: UpdateNewsItemDto) -> ServiceResult<NewsItem> {
+        NewsItem::update(self.db_pool.as_ref(), id, dto)
+            .await
+            .map_err(|e| match e {
+                DbError::NotFound(_) => ServiceError::NotFound(format!("Noticia con ID {}", id)),
+                e => ServiceError::DatabaseError(e),
+            })
+    }
+
+    /// Elimina una noticia
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - ID de la noticia
+    ///
+    /// # Returns
+    ///
+    /// `()` si la noticia fue eliminada
+    pub async fn delete_news(&self, id: Uuid) -> ServiceResult<()> {
+        NewsItem::delete(self.db_pool.as_ref(), id)
+            .await
+            .map_err(|e| match e {
+                DbError::NotFound(_) => ServiceError::NotFound(format!("Noticia con ID {}", id)),
+                e => ServiceError::DatabaseError(e),
+            })
+    }
+
+    /// Estadísticas de matrícula de un año académico
+    ///
+    /// Para el año en curso los datos se cortan en la fecha de hoy y se
+    /// comparan con la misma fecha del año anterior.
+    ///
+    /// # Arguments
+    ///
+    /// * `academic_year` - Año académico
+    ///
+    /// # Returns
+    ///
+    /// La matrícula por grado y sección, los totales y los motivos de retiro
+    pub async fn enrollment_stats(&self, academic_year: i32) -> ServiceResult<EnrollmentStats> {
+        let (as_of, partial) = stats_as_of(academic_year, Utc::now().date_naive())
+            .ok_or_else(|| ServiceError::ValidationError(format!("Año inválido: {}", academic_year)))?;
+        let pool = &self.replica.reader().await;
+
+        let rows = with_timeout(STATS_QUERY_TIMEOUT, StudentYearRecord::section_counts(pool, academic_year, as_of)).await?;
+        let courses = with_timeout(
+            STATS_QUERY_TIMEOUT,
+            StudentYearRecord::course_enrollment_counts(pool, academic_year, as_of),
+        )
+        .await?;
+        let reasons = with_timeout(STATS_QUERY_TIMEOUT, StudentYearRecord::withdrawal_reasons(pool, academic_year, as_of)).await?;
+
+        Ok(EnrollmentStats::from_parts(academic_year, as_of, partial, rows, courses, reasons))
+    }
+
+    /// Pagos pendientes o vencidos de un estudiante
+    async fn pending_payments(&self, student_id: Uuid) -> ServiceResult<Vec<PaymentReminder>> {
+        sqlx::query_as::<_, PaymentReminder>(
+            r#"
+            SELECT concept, amount, currency, payment_date::date AS due_date
+            FROM payments
+            WHERE student_id = $1 AND status IN ('pending', 'overdue')
+            ORDER BY payment_date
+            "#,
+        )
+        .bind(student_id)
+        .fetch_all(self.db_pool.as_ref())
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.into()))
+    }
+}
+
+/// Fecha de corte de las estadísticas de un año
+///
+/// Un año cerrado se cuenta hasta el 31 de diciembre; el año en curso (o uno
+/// futuro) hasta `today`, marcado como parcial.
+fn stats_as_of(academic_year: i32, today: NaiveDate) -> Option<(NaiveDate, bool)> {
+    let year_end = NaiveDate::from_ymd_opt(academic_year, 12, 31)?;
+    if academic_year < today.year() {
+        Some((year_end, false))
+    } else {
+        Some((today, true))
+    }
+}
+
+/// Primer y último día de un mes, o `None` si el mes no es válido
+fn month_bounds(month: u32, year: i32) -> Option<(NaiveDate, NaiveDate)> {
+    let first_day = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    Some((first_day, next_month.pred_opt()?))
+}
+
+/// Frase motivacional correspondiente a un mes (1-12)
+fn quote_for_month(month: u32) -> Quote {
+    let (text, author) = QUOTES[(month.saturating_sub(1) % 12) as usize];
+    Quote { text, author }
+}
+
+/// Renderiza el boletín a partir de su contenido
+///
+/// Si se indica un destinatario, el saludo se personaliza y se resalta su
+/// curso en la tabla de asistencia.
+pub fn render_newsletter(
+    data: &NewsletterData,
+    recipient: Option<&NewsletterRecipient>,
+    payments: &[PaymentReminder],
+) -> ServiceResult<String> {
+    let month_name = MONTH_NAMES
+        .get(data.month.wrapping_sub(1) as usize)
+        .ok_or_else(|| ServiceError::ValidationError(format!("Mes inválido: {}", data.month)))?;
+
+    let mut context = Context::new();
+    context.insert("month_name", month_name);
+    context.insert("year", &data.year);
+    context.insert("recipient", &recipient);
+    context.insert("news", &data.news);
+    context.insert("attendance", &data.attendance);
+    context.insert("exams", &data.exams);
+    context.insert("payments", payments);
+    context.insert("quote", &quote_for_month(data.month));
+
+    Tera::one_off(NEWSLETTER_TEMPLATE, &context, true)
+        .map_err(|e| ServiceError::GenericError(format!("Error al generar el boletín: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_data() -> NewsletterData {
+        NewsletterData {
+            month: 5,
+            year: 2024,
+            news: vec![NewsItem {
+                id: Uuid::new_v4(),
+                title: "Festival de la Independencia".to_string(),
+                content: "Acto central el 14 de mayo".to_string(),
+                image_path: None,
+                published_at: NaiveDate::from_ymd_opt(2024, 5, 2).unwrap(),
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            }],
+            attendance: vec![ClassAttendance {
+                grade: "3er Curso".to_string(),
+                section: "A".to_string(),
+                attendance_rate: 94.25,
+            }],
+            exams: vec![UpcomingExam {
+                course_name: "Matemática".to_string(),
+                title: "Examen parcial".to_string(),
+                date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        assert_eq!(
+            month_bounds(2, 2024),
+            Some((NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(), NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()))
+        );
+        assert_eq!(month_bounds(12, 2024).unwrap().1, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+        assert!(month_bounds(13, 2024).is_none());
+    }
+
+    #[test]
+    fn test_render_generic_newsletter() {
+        let html = render_newsletter(&sample_data(), None, &[]).unwrap();
+
+        assert!(html.contains("Mayo 2024"));
+        assert!(html.contains("Festival de la Independencia"));
+        assert!(html.contains("Examen parcial"));
+        assert!(html.contains("Estimadas familias"));
+        assert!(!html.contains("Recordatorio de pagos"));
+    }
+
+    #[test]
+    fn test_render_personalized_newsletter() {
+        let recipient = NewsletterRecipient {
+            student_id: Uuid::new_v4(),
+            student_name: "María Benítez".to_string(),
+            grade: "3er Curso".to_string(),
+            section: "A".to_string(),
+        };
+        let payments = vec![PaymentReminder {
+            concept: "Cuota de junio".to_string(),
+            amount: 450000.0,
+            currency: "PYG".to_string(),
+            due_date: NaiveDate::from_ymd_opt(2024, 6, 10).unwrap(),
+        }];
+
+        let html = render_newsletter(&sample_data(), Some(&recipient), &payments).unwrap();
+
+        assert!(html.contains("María Benítez"));
+        assert!(html.contains("sección A"));
+        assert!(html.contains("Cuota de junio"));
+    }
+
+    #[test]
+    fn test_render_escapes_news_content() {
+        let mut data = sample_data();
+        data.news[0].content = "<script>alert(1)</script>".to_string();
+
+        let html = render_newsletter(&data, None, &[]).unwrap();
+
+        assert!(!html.contains("<script>"));
+    }
+
+    fn section(grade: &str, section: &str, total: i64, withdrawn: i64, returning: i64, previous: i64) -> SectionCountRow {
+        SectionCountRow {
+            grade: grade.to_string(),
+            section: section.to_string(),
+            total,
+            withdrawn,
+            new_students: total - returning,
+            returning_students: returning,
+            previous_total: previous,
+        }
+    }
+
+    #[test]
+    fn test_stats_as_of() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 15).unwrap();
+
+        assert_eq!(stats_as_of(2024, today), Some((NaiveDate::from_ymd_opt(2024, 12, 31).unwrap(), false)));
+        assert_eq!(stats_as_of(2025, today), Some((today, true)));
+        assert_eq!(stats_as_of(2026, today), Some((today, true)));
+    }
+
+    #[test]
+    fn test_enrollment_stats_totals() {
+        let stats = EnrollmentStats::from_parts(
+            2025,
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            false,
+            vec![section("1", "A", 30, 2, 25, 28), section("1", "B", 0, 0, 0, 12)],
+            CourseEnrollmentCounts { active: 240, withdrawn: 8 },
+            vec![WithdrawalReasonCount { reason: "Mudanza".to_string(), count: 2 }],
+        );
+
+        assert_eq!(stats.sections[0].active, 28);
+        assert_eq!(stats.sections[0].change, 2);
+        assert_eq!((stats.sections[1].total, stats.sections[1].change), (0, -12));
+
+        let t = &stats.totals;
+        assert_eq!((t.total, t.active, t.withdrawn), (30, 28, 2));
+        assert_eq!((t.new_students, t.returning_students), (5, 25));
+        assert_eq!((t.previous_year_total, t.change), (40, -10));
+        assert_eq!(t.change_percentage, Some(-25.0));
+        assert_eq!((t.course_enrollments, t.course_withdrawals), (240, 8));
+    }
+
+    #[test]
+    fn test_enrollment_stats_without_previous_year() {
+        let stats = EnrollmentStats::from_parts(
+            2025,
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            false,
+            vec![section("1", "A", 10, 0, 0, 0)],
+            CourseEnrollmentCounts::default(),
+            vec![],
+        );
+
+        assert_eq!(stats.totals.change, 10);
+        assert!(stats.totals.change_percentage.is_none());
+    }
+
+    #[test]
+    fn test_enrollment_stats_csv() {
+        let stats = EnrollmentStats::from_parts(
+            2025,
+            NaiveDate::from_ymd_opt(2025, 12, 31).unwrap(),
+            false,
+            vec![section("1", "A", 30, 2, 25, 28), section("1", "B", 0, 0, 0, 12)],
+            CourseEnrollmentCounts::default(),
+            vec![],
+        );
+
+        let csv = String::from_utf8(stats.to_csv().unwrap()).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("Grado,Sección,Matriculados"));
+        assert_eq!(lines[1], "1,A,30,28,2,5,25,28,2");
+        assert_eq!(lines[2], "1,B,0,0,0,0,0,12,-12");
+        assert_eq!(lines[3], "TOTAL,,30,28,2,5,25,40,-10");
+    }
+}
@@ -0,0 +1,969 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::DbPool,
+    models::{
+        schedule::{Classroom, TeacherAvailability},
+        teacher::{Teacher, TeacherFilter},
+        Course, ScheduleSlot, TeacherStatus,
+    },
+    services::{ServiceError, ServiceResult},
+};
+
+/// Días hábiles considerados por el generador (lunes a viernes)
+const SCHOOL_DAYS: [u8; 5] = [1, 2, 3, 4, 5];
+
+/// Horas de inicio de los bloques del turno mañana
+const MORNING_PERIODS: [u32; 5] = [7, 8, 9, 10, 11];
+
+/// Horas de inicio de los bloques del turno tarde
+const AFTERNOON_PERIODS: [u32; 4] = [13, 14, 15, 16];
+
+/// Duración de cada bloque en horas
+const PERIOD_HOURS: f64 = 1.0;
+
+/// Criterio para decidir qué cursos se asignan primero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrioritizationMode {
+    /// Más prerrequisitos primero, luego más estudiantes inscritos
+    #[default]
+    Prerequisites,
+    /// Más estudiantes inscritos primero, luego más prerrequisitos
+    Enrollment,
+    /// Más horas semanales primero
+    Credits,
+}
+
+/// Opciones del generador automático de horarios
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleGenerationOptions {
+    /// Completar los bloques de la mañana de toda la semana antes que los de la tarde
+    pub prefer_morning: bool,
+    /// Horas máximas de clase por día para un mismo profesor
+    pub max_daily_hours_per_teacher: f64,
+    /// Criterio de prioridad entre cursos
+    pub prioritize_by: PrioritizationMode,
+}
+
+impl Default for ScheduleGenerationOptions {
+    fn default() -> Self {
+        Self {
+            prefer_morning: true,
+            max_daily_hours_per_teacher: 6.0,
+            prioritize_by: PrioritizationMode::default(),
+        }
+    }
+}
+
+/// Horario propuesto para un curso
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleAssignment {
+    pub course_id: Uuid,
+    pub course_code: String,
+    /// Profesor asignado (ID de usuario)
+    pub teacher_id: Uuid,
+    /// Bloques semanales con su aula
+    pub slots: Vec<ScheduleSlot>,
+}
+
+/// Resultado de la generación automática de horarios
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScheduleGenerationResult {
+    /// Cursos con horario asignado
+    pub assignments: Vec<ScheduleAssignment>,
+    /// Cursos para los que no se encontró una combinación válida
+    pub unscheduled: Vec<Course>,
+}
+
+/// Curso a programar junto con los datos que determinan su dificultad
+#[derive(Debug, Clone)]
+pub struct CourseDemand {
+    pub course: Course,
+    pub prerequisite_count: usize,
+    pub enrolled_students: i64,
+}
+
+/// Profesor que puede recibir cursos
+#[derive(Debug, Clone)]
+pub struct TeacherCandidate {
+    pub teacher_id: Uuid,
+    pub specialization: String,
+    pub subjects: Vec<String>,
+    /// Franjas disponibles; vacío significa disponible en todo el horario escolar
+    pub availability: Vec<TeacherAvailability>,
+}
+
+impl TeacherCandidate {
+    fn can_teach(&self, course: &Course) -> bool {
+        let name = course.name.to_lowercase();
+        self.specialization.to_lowercase() == name
+            || self.subjects.iter().any(|subject| subject.to_lowercase() == name)
+    }
+
+    fn is_available(&self, slot: &TimeSlot) -> bool {
+        self.availability.is_empty()
+            || self
+                .availability
+                .iter()
+                .any(|window| window.covers(slot.day, slot.start, slot.end))
+    }
+}
+
+/// Bloque horario semanal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TimeSlot {
+    day: u8,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl TimeSlot {
+    fn new(day: u8, hour: u32) -> Self {
+        Self {
+            day,
+            start: NaiveTime::from_hms_opt(hour, 0, 0).unwrap_or_default(),
+            end: NaiveTime::from_hms_opt(hour + 1, 0, 0).unwrap_or_default(),
+        }
+    }
+}
+
+/// Bloques de la semana en el orden en que se intentan asignar
+fn time_slots(prefer_morning: bool) -> Vec<TimeSlot> {
+    let by_day = |periods: &[u32]| -> Vec<TimeSlot> {
+        SCHOOL_DAYS
+            .iter()
+            .flat_map(|&day| periods.iter().map(move |&hour| TimeSlot::new(day, hour)))
+            .collect()
+    };
+
+    if prefer_morning {
+        let mut slots = by_day(&MORNING_PERIODS);
+        slots.extend(by_day(&AFTERNOON_PERIODS));
+        slots
+    } else {
+        let all: Vec<u32> = MORNING_PERIODS.iter().chain(AFTERNOON_PERIODS.iter()).copied().collect();
+        by_day(&all)
+    }
+}
+
+/// Cantidad de bloques semanales que necesita un curso (un bloque por crédito)
+fn weekly_periods(course: &Course) -> usize {
+    (course.credits.ceil().max(1.0)) as usize
+}
+
+/// Reservas ya confirmadas durante la generación
+#[derive(Default)]
+struct Bookings {
+    teachers: HashSet<(Uuid, TimeSlot)>,
+    classrooms: HashSet<(Uuid, TimeSlot)>,
+    grades: HashSet<(String, TimeSlot)>,
+    teacher_daily_hours: HashMap<(Uuid, u8), f64>,
+}
+
+/// Genera una propuesta de horario con un algoritmo voraz
+///
+/// Los cursos se ordenan por dificultad según `options.prioritize_by` y, para
+/// cada uno, se buscan los primeros bloques libres en los que haya un profesor
+/// habilitado y disponible (sin exceder sus horas diarias), un aula con
+/// capacidad suficiente y ningún otro curso del mismo grado. Un curso se asigna
+/// completo o no se asigna. Es una heurística: no garantiza el horario óptimo.
+pub fn plan_schedule(
+    mut demands: Vec<CourseDemand>,
+    teachers: &[TeacherCandidate],
+    classrooms: &[Classroom],
+    options: &ScheduleGenerationOptions,
+) -> ScheduleGenerationResult {
+    match options.prioritize_by {
+        PrioritizationMode::Prerequisites => demands.sort_by_key(|d| {
+            (Reverse(d.prerequisite_count), Reverse(d.enrolled_students), d.course.code.clone())
+        }),
+        PrioritizationMode::Enrollment => demands.sort_by_key(|d| {
+            (Reverse(d.enrolled_students), Reverse(d.prerequisite_count), d.course.code.clone())
+        }),
+        PrioritizationMode::Credits => demands.sort_by_key(|d| {
+            (Reverse(weekly_periods(&d.course)), Reverse(d.prerequisite_count), d.course.code.clone())
+        }),
+    }
+
+    let mut rooms: Vec<&Classroom> = classrooms.iter().collect();
+    rooms.sort_by_key(|room| (room.capacity, room.name.clone()));
+
+    let slots = time_slots(options.prefer_morning);
+    let mut bookings = Bookings::default();
+    let mut result = ScheduleGenerationResult::default();
+
+    for demand in demands {
+        match assign_course(&demand, teachers, &rooms, &slots, options, &bookings) {
+            Some((teacher_id, placed)) => {
+                for (slot, room) in &placed {
+                    bookings.teachers.insert((teacher_id, *slot));
+                    bookings.classrooms.insert((room.id, *slot));
+                    bookings.grades.insert((demand.course.grade_level.clone(), *slot));
+                    *bookings.teacher_daily_hours.entry((teacher_id, slot.day)).or_default() += PERIOD_HOURS;
+                }
+                result.assignments.push(ScheduleAssignment {
+                    course_id: demand.course.id,
+                    course_code: demand.course.code.clone(),
+                    teacher_id,
+                    slots: placed
+                        .iter()
+                        .map(|(slot, room)| ScheduleSlot {
+                            day_of_week: slot.day,
+                            start_time: slot.start.format("%H:%M").to_string(),
+                            end_time: slot.end.format("%H:%M").to_string(),
+                            classroom: room.name.clone(),
+                        })
+                        .collect(),
+                });
+            }
+            None => result.unscheduled.push(demand.course),
+        }
+    }
+
+    result
+}
+
+/// Busca el primer profesor con el que se pueden ubicar todos los bloques de un curso
+fn assign_course<'a>(
+    demand: &CourseDemand,
+    teachers: &[TeacherCandidate],
+    rooms: &[&'a Classroom],
+    slots: &[TimeSlot],
+    options: &ScheduleGenerationOptions,
+    bookings: &Bookings,
+) -> Option<(Uuid, Vec<(TimeSlot, &'a Classroom)>)> {
+    let course = &demand.course;
+    let needed = weekly_periods(course);
+    // Repartir los bloques en la semana en lugar de concentrarlos en un día
+    let max_per_day = needed.div_ceil(SCHOOL_DAYS.len());
+
+    let candidates = teachers.iter().filter(|teacher| match course.teacher_id {
+        Some(assigned) => teacher.teacher_id == assigned,
+        None => teacher.can_teach(course),
+    });
+
+    for teacher in candidates {
+        let mut placed: Vec<(TimeSlot, &Classroom)> = Vec::with_capacity(needed);
+        let mut per_day: HashMap<u8, usize> = HashMap::new();
+
+        for slot in slots {
+            if placed.len() == needed {
+                break;
+            }
+
+            let day_count = per_day.get(&slot.day).copied().unwrap_or(0);
+            let booked_hours = bookings
+                .teacher_daily_hours
+                .get(&(teacher.teacher_id, slot.day))
+                .copied()
+                .unwrap_or(0.0);
+            let daily_hours = booked_hours + day_count as f64 * PERIOD_HOURS;
+
+            if day_count >= max_per_day
+                || daily_hours + PERIOD_HOURS > options.max_daily_hours_per_teacher
+                || bookings.teachers.contains(&(teacher.teacher_id, *slot))
+                || bookings.grades.contains(&(course.grade_level.clone(), *slot))
+                || !teacher.is_available(slot)
+            {
+                continue;
+            }
+
+            let room = rooms.iter().find(|room| {
+                room.capacity as i64 >= demand.enrolled_students
+                    && !bookings.classrooms.contains(&(room.id, *slot))
+            });
+
+            if let Some(room) = room {
+                placed.push((*slot, *room));
+                *per_day.entry(slot.day).or_default() += 1;
+            }
+        }
+
+        if placed.len() == needed {
+            return Some((teacher.teacher_id, placed));
+        }
+    }
+
+    None
+}
+
+/// Horario candidato a verificar antes de guardarlo en un curso
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleCheckRequest {
+    /// Año académico contra el que se verifica
+    pub academic_year: i32,
+    /// Curso que se está editando; sus propios bloques no cuentan como conflicto
+    #[serde(default)]
+    pub course_id: Option<Uuid>,
+    /// Profesor que dictaría los bloques
+    #[serde(default)]
+    pub teacher_id: Option<Uuid>,
+    /// Aula de todos los bloques; si falta se usa el aula de cada bloque
+    #[serde(default)]
+    pub classroom_id: Option<Uuid>,
+    /// Bloques propuestos
+    pub slots: Vec<ScheduleSlot>,
+}
+
+/// Recurso que dos bloques se disputan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// El profesor ya dicta otro curso en ese horario
+    Teacher,
+    /// El aula ya está ocupada en ese horario
+    Classroom,
+}
+
+/// Superposición entre un bloque propuesto y el horario de un curso existente
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleConflict {
+    pub kind: ConflictKind,
+    /// Bloque propuesto que genera el conflicto
+    pub slot: ScheduleSlot,
+    pub course_id: Uuid,
+    pub course_code: String,
+    /// Bloque del curso existente
+    pub day_of_week: u8,
+    pub start_time: String,
+    pub end_time: String,
+    pub classroom: String,
+}
+
+/// Bloque del horario con las horas ya interpretadas
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SlotTimes {
+    day: u8,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl SlotTimes {
+    fn parse(slot: &ScheduleSlot) -> Option<Self> {
+        let start = NaiveTime::parse_from_str(&slot.start_time, "%H:%M").ok()?;
+        let end = NaiveTime::parse_from_str(&slot.end_time, "%H:%M").ok()?;
+        Some(Self { day: slot.day_of_week, start, end })
+    }
+
+    fn overlaps(&self, other: &SlotTimes) -> bool {
+        self.day == other.day && self.start < other.end && other.start < self.end
+    }
+}
+
+/// Verifica que los bloques tengan día (1-7) y horas `HH:MM` válidas
+fn validate_slots(slots: &[ScheduleSlot]) -> ServiceResult<Vec<SlotTimes>> {
+    slots
+        .iter()
+        .map(|slot| {
+            let times = SlotTimes::parse(slot).ok_or_else(|| {
+                ServiceError::ValidationError(format!(
+                    "Horario inválido {}-{}: se espera el formato HH:MM",
+                    slot.start_time, slot.end_time
+                ))
+            })?;
+            if !(1..=7).contains(&times.day) {
+                return Err(ServiceError::ValidationError(format!(
+                    "Día de la semana inválido: {}",
+                    slot.day_of_week
+                )));
+            }
+            if times.start >= times.end {
+                return Err(ServiceError::ValidationError(format!(
+                    "La hora de inicio {} debe ser anterior a la de fin {}",
+                    slot.start_time, slot.end_time
+                )));
+            }
+            Ok(times)
+        })
+        .collect()
+}
+
+/// Busca los conflictos de profesor y aula de un horario candidato
+///
+/// # Arguments
+///
+/// * `slots` - Bloques propuestos
+/// * `teacher_id` - Profesor que los dictaría (opcional)
+/// * `classroom` - Aula de todos los bloques; si es `None` se usa la de cada bloque
+/// * `courses` - Cursos existentes del año, sin el curso que se está editando
+///
+/// # Returns
+///
+/// Los conflictos en el orden de los bloques propuestos
+pub fn find_conflicts(
+    slots: &[ScheduleSlot],
+    teacher_id: Option<Uuid>,
+    classroom: Option<&str>,
+    courses: &[Course],
+) -> ServiceResult<Vec<ScheduleConflict>> {
+    let candidates = validate_slots(slots)?;
+    let mut conflicts = Vec::new();
+
+    for (slot, times) in slots.iter().zip(&candidates) {
+        let room = classroom.unwrap_or(&slot.classroom);
+
+        for course in courses {
+            for existing in &course.schedule {
+                // Los horarios guardados con formato inválido no se pueden comparar
+                let Some(existing_times) = SlotTimes::parse(existing) else { continue };
+                if !times.overlaps(&existing_times) {
+                    continue;
+                }
+
+                let mut kinds = Vec::new();
+                if teacher_id.is_some() && course.teacher_id == teacher_id {
+                    kinds.push(ConflictKind::Teacher);
+                }
+                if !room.is_empty() && existing.classroom.eq_ignore_ascii_case(room) {
+                    kinds.push(ConflictKind::Classroom);
+                }
+
+                conflicts.extend(kinds.into_iter().map(|kind| ScheduleConflict {
+                    kind,
+                    slot: ScheduleSlot { classroom: room.to_string(), ..slot.clone() },
+                    course_id: course.id,
+                    course_code: course.code.clone(),
+                    day_of_week: existing.day_of_week,
+                    start_time: existing.start_time.clone(),
+                    end_time: existing.end_time.clone(),
+                    classroom: existing.classroom.clone(),
+                }));
+            }
+        }
+    }
+
+    Ok(conflicts)
+}
+
+/// Bloque de la grilla de ocupación de un aula
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OccupancyBlock {
+    pub start_time: String,
+    pub end_time: String,
+    /// Curso que ocupa el bloque; `None` si el aula está libre
+    pub course_id: Option<Uuid>,
+    pub course_code: Option<String>,
+}
+
+/// Ocupación de un aula en un día de la semana
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OccupancyDay {
+    /// Día de la semana (1-7, donde 1 es lunes)
+    pub day_of_week: u8,
+    pub blocks: Vec<OccupancyBlock>,
+}
+
+/// Grilla semanal de ocupación de un aula
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassroomOccupancy {
+    pub classroom: Classroom,
+    pub academic_year: i32,
+    pub days: Vec<OccupancyDay>,
+    /// Bloques ocupados de la grilla
+    pub occupied_blocks: usize,
+    /// Bloques libres de la grilla
+    pub free_blocks: usize,
+}
+
+/// Arma la grilla semanal de ocupación de un aula
+///
+/// La grilla tiene los bloques de una hora de los turnos mañana y tarde de
+/// lunes a viernes, más los días fuera de ese rango en los que el aula tenga
+/// clases. Un bloque está ocupado si algún curso dicta clases en el aula en
+/// parte de esa hora.
+pub fn occupancy_grid(classroom: &str, courses: &[Course]) -> Vec<OccupancyDay> {
+    let bookings: Vec<(&Course, SlotTimes)> = courses
+        .iter()
+        .flat_map(|course| {
+            course
+                .schedule
+                .iter()
+                .filter(|slot| slot.classroom.eq_ignore_ascii_case(classroom))
+                .filter_map(move |slot| SlotTimes::parse(slot).map(|times| (course, times)))
+        })
+        .collect();
+
+    let mut days: Vec<u8> = SCHOOL_DAYS.to_vec();
+    for (_, times) in &bookings {
+        if !days.contains(&times.day) {
+            days.push(times.day);
+        }
+    }
+    days.sort_unstable();
+
+    let hours: Vec<u32> = MORNING_PERIODS.iter().chain(AFTERNOON_PERIODS.iter()).copied().collect();
+
+    days.into_iter()
+        .map(|day| OccupancyDay {
+            day_of_week: day,
+            blocks: hours
+                .iter()
+                .map(|&hour| {
+                    let block = TimeSlot::new(day, hour);
+                    let block_times = SlotTimes { day, start: block.start, end: block.end };
+                    let course = bookings
+                        .iter()
+                        .find(|(_, times)| times.overlaps(&block_times))
+                        .map(|(course, _)| *course);
+
+                    OccupancyBlock {
+                        start_time: block.start.format("%H:%M").to_string(),
+                        end_time: block.end.format("%H:%M").to_string(),
+                        course_id: course.map(|c| c.id),
+                        course_code: course.map(|c| c.code.clone()),
+                    }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Servicio para gestión de horarios
+pub struct ScheduleService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+}
+
+impl ScheduleService {
+    /// Crea una nueva instancia del servicio de horarios
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de ScheduleService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Genera automáticamente una propuesta de horario para un año académico
+    ///
+    /// La propuesta no se guarda: sirve como punto de partida para que la
+    /// coordinación la revise y la aplique a cada curso.
+    ///
+    /// # Arguments
+    ///
+    /// * `academic_year` - Año académico a programar
+    /// * `options` - Preferencias del generador
+    ///
+    /// # Returns
+    ///
+    /// Los horarios asignados y los cursos que no pudieron programarse
+    pub async fn auto_generate_schedule(
+        &self,
+        academic_year: i32,
+        options: ScheduleGenerationOptions,
+    ) -> ServiceResult<ScheduleGenerationResult> {
+        if options.max_daily_hours_per_teacher < PERIOD_HOURS {
+            return Err(ServiceError::ValidationError(format!(
+                "max_daily_hours_per_teacher debe ser al menos {}",
+                PERIOD_HOURS
+            )));
+        }
+
+        let pool = self.db_pool.as_ref();
+
+        let courses = Course::find_by_academic_year(pool, academic_year)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        let course_ids: Vec<Uuid> = courses.iter().map(|course| course.id).collect();
+        let counts: Vec<(Uuid, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT c.id,
+                   COALESCE(cardinality(c.prerequisites), 0)::int8,
+                   (SELECT COUNT(*) FROM enrollments e
+                    WHERE e.course_id = c.id AND e.status = 'active')
+            FROM courses c
+            WHERE c.id = ANY($1)
+            "#,
+        )
+        .bind(&course_ids)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.into()))?;
+        let counts: HashMap<Uuid, (i64, i64)> = counts
+            .into_iter()
+            .map(|(id, prerequisites, enrolled)| (id, (prerequisites, enrolled)))
+            .collect();
+
+        let demands = courses
+            .into_iter()
+            .map(|course| {
+                let (prerequisites, enrolled) = counts.get(&course.id).copied().unwrap_or((0, 0));
+                CourseDemand {
+                    course,
+                    prerequisite_count: prerequisites as usize,
+                    enrolled_students: enrolled,
+                }
+            })
+            .collect();
+
+        let filter = TeacherFilter {
+            status: Some(TeacherStatus::Active),
+            ..Default::default()
+        };
+        let teachers = Teacher::find_all(pool, filter, None, None)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        let mut availability: HashMap<Uuid, Vec<TeacherAvailability>> = HashMap::new();
+        for window in TeacherAvailability::find_all(pool)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+        {
+            availability.entry(window.teacher_id).or_default().push(window);
+        }
+
+        let candidates: Vec<TeacherCandidate> = teachers
+            .into_iter()
+            .map(|teacher| TeacherCandidate {
+                availability: availability.remove(&teacher.user_id).unwrap_or_default(),
+                teacher_id: teacher.user_id,
+                specialization: teacher.specialization,
+                subjects: teacher.subjects,
+            })
+            .collect();
+
+        let classrooms = Classroom::find_all(pool)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        Ok(plan_schedule(demands, &candidates, &classrooms, &options))
+    }
+
+    /// Verifica un horario candidato sin guardarlo
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Bloques propuestos, profesor, aula y año académico
+    ///
+    /// # Returns
+    ///
+    /// Los conflictos con los cursos existentes; vacío si el horario es viable
+    pub async fn check_conflicts(&self, request: ScheduleCheckRequest) -> ServiceResult<Vec<ScheduleConflict>> {
+        let pool = self.db_pool.as_ref();
+
+        let classroom = match request.classroom_id {
+            Some(id) => Some(
+                Classroom::find_by_id(pool, id)
+                    .await
+                    .map_err(ServiceError::DatabaseError)?
+                    .ok_or_else(|| ServiceError::NotFound("Aula".to_string()))?,
+            ),
+            None => None,
+        };
+
+        let courses: Vec<Course> = Course::find_by_academic_year(pool, request.academic_year)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .into_iter()
+            .filter(|course| Some(course.id) != request.course_id)
+            .collect();
+
+        find_conflicts(
+            &request.slots,
+            request.teacher_id,
+            classroom.as_ref().map(|room| room.name.as_str()),
+            &courses,
+        )
+    }
+
+    /// Obtiene la grilla semanal de ocupación de un aula
+    ///
+    /// # Arguments
+    ///
+    /// * `classroom_id` - ID del aula
+    /// * `academic_year` - Año académico
+    ///
+    /// # Returns
+    ///
+    /// Los bloques de cada día con el curso que los ocupa, o libres
+    pub async fn classroom_occupancy(
+        &self,
+        classroom_id: Uuid,
+        academic_year: i32,
+    ) -> ServiceResult<ClassroomOccupancy> {
+        let pool = self.db_pool.as_ref();
+
+        let classroom = Classroom::find_by_id(pool, classroom_id)
+            .await
+            .map_err(ServiceError::DatabaseError)?
+            .ok_or_else(|| ServiceError::NotFound("Aula".to_string()))?;
+
+        let courses = Course::find_by_academic_year(pool, academic_year)
+            .await
+            .map_err(ServiceError::DatabaseError)?;
+
+        let days = occupancy_grid(&classroom.name, &courses);
+        let occupied_blocks = days
+            .iter()
+            .flat_map(|day| &day.blocks)
+            .filter(|block| block.course_id.is_some())
+            .count();
+        let total_blocks: usize = days.iter().map(|day| day.blocks.len()).sum();
+
+        Ok(ClassroomOccupancy {
+            classroom,
+            academic_year,
+            days,
+            occupied_blocks,
+            free_blocks: total_blocks - occupied_blocks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn course(code: &str, name: &str, grade: &str, credits: f32) -> Course {
+        Course {
+            id: Uuid::new_v4(),
+            code: code.to_string(),
+            name: name.to_string(),
+            description: None,
+            grade_level: grade.to_string(),
+            credits,
+            teacher_id: None,
+            academic_year: 2024,
+            schedule: Vec::new(),
+        }
+    }
+
+    fn demand(course: Course, prerequisites: usize, enrolled: i64) -> CourseDemand {
+        CourseDemand { course, prerequisite_count: prerequisites, enrolled_students: enrolled }
+    }
+
+    fn teacher(subjects: &[&str]) -> TeacherCandidate {
+        TeacherCandidate {
+            teacher_id: Uuid::new_v4(),
+            specialization: String::new(),
+            subjects: subjects.iter().map(|s| s.to_string()).collect(),
+            availability: Vec::new(),
+        }
+    }
+
+    fn room(name: &str, capacity: i32) -> Classroom {
+        Classroom { id: Uuid::new_v4(), name: name.to_string(), capacity }
+    }
+
+    #[test]
+    fn test_assigns_course_with_matching_teacher_and_room() {
+        let teachers = vec![teacher(&["Matemática"])];
+        let rooms = vec![room("A1", 30)];
+        let demands = vec![demand(course("MAT-1", "Matemática", "1", 3.0), 0, 25)];
+
+        let result = plan_schedule(demands, &teachers, &rooms, &ScheduleGenerationOptions::default());
+
+        assert_eq!(result.assignments.len(), 1);
+        let slots = &result.assignments[0].slots;
+        assert_eq!(slots.len(), 3);
+        // Un bloque por día, repartidos en la semana
+        let days: HashSet<u8> = slots.iter().map(|s| s.day_of_week).collect();
+        assert_eq!(days.len(), 3);
+    }
+
+    #[test]
+    fn test_room_capacity_is_respected() {
+        let teachers = vec![teacher(&["Historia"])];
+        let rooms = vec![room("Chica", 10), room("Grande", 40)];
+        let demands = vec![demand(course("HIS-1", "Historia", "1", 1.0), 0, 35)];
+
+        let result = plan_schedule(demands, &teachers, &rooms, &ScheduleGenerationOptions::default());
+
+        assert_eq!(result.assignments[0].slots[0].classroom, "Grande");
+    }
+
+    #[test]
+    fn test_course_without_room_large_enough_is_unscheduled() {
+        let teachers = vec![teacher(&["Historia"])];
+        let rooms = vec![room("Chica", 10)];
+        let demands = vec![demand(course("HIS-1", "Historia", "1", 1.0), 0, 35)];
+
+        let result = plan_schedule(demands, &teachers, &rooms, &ScheduleGenerationOptions::default());
+
+        assert!(result.assignments.is_empty());
+        assert_eq!(result.unscheduled[0].code, "HIS-1");
+    }
+
+    #[test]
+    fn test_no_teacher_or_grade_conflicts() {
+        let teachers = vec![teacher(&["Matemática", "Física"])];
+        let rooms = vec![room("A1", 30), room("A2", 30)];
+        let demands = vec![
+            demand(course("MAT-1", "Matemática", "1", 5.0), 0, 20),
+            demand(course("FIS-1", "Física", "1", 5.0), 0, 20),
+        ];
+
+        let result = plan_schedule(demands, &teachers, &rooms, &ScheduleGenerationOptions::default());
+
+        let mut seen = HashSet::new();
+        for assignment in &result.assignments {
+            for slot in &assignment.slots {
+                assert!(seen.insert((slot.day_of_week, slot.start_time.clone())), "bloque repetido");
+            }
+        }
+        assert_eq!(result.assignments.len(), 2);
+    }
+
+    #[test]
+    fn test_teacher_daily_limit() {
+        let teachers = vec![teacher(&["Guaraní"])];
+        let rooms = vec![room("A1", 30)];
+        let demands = vec![
+            demand(course("GUA-1", "Guaraní", "1", 5.0), 0, 20),
+            demand(course("GUA-2", "Guaraní", "2", 5.0), 0, 20),
+        ];
+        let options = ScheduleGenerationOptions { max_daily_hours_per_teacher: 1.0, ..Default::default() };
+
+        let result = plan_schedule(demands, &teachers, &rooms, &options);
+
+        // Con una hora diaria el profesor solo puede cubrir un curso de cinco bloques
+        assert_eq!(result.assignments.len(), 1);
+        assert_eq!(result.unscheduled.len(), 1);
+    }
+
+    #[test]
+    fn test_teacher_availability_is_respected() {
+        let mut only_monday = teacher(&["Química"]);
+        only_monday.availability = vec![TeacherAvailability {
+            teacher_id: only_monday.teacher_id,
+            day_of_week: 1,
+            start_time: NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            end_time: NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        }];
+        let rooms = vec![room("Lab", 30)];
+        let demands = vec![demand(course("QUI-1", "Química", "1", 1.0), 0, 20)];
+
+        let result = plan_schedule(demands, &[only_monday], &rooms, &ScheduleGenerationOptions::default());
+
+        assert_eq!(result.assignments[0].slots[0].day_of_week, 1);
+    }
+
+    #[test]
+    fn test_harder_courses_are_scheduled_first() {
+        let teachers = vec![teacher(&["Inglés"])];
+        let rooms = vec![room("A1", 30)];
+        // Un solo profesor: el curso con más prerrequisitos toma el primer bloque de la semana
+        let easy = course("ING-1", "Inglés", "1", 1.0);
+        let hard = course("ING-2", "Inglés", "2", 1.0);
+        let demands = vec![demand(easy, 0, 10), demand(hard, 2, 10)];
+
+        let result = plan_schedule(demands, &teachers, &rooms, &ScheduleGenerationOptions::default());
+
+        assert_eq!(result.assignments[0].course_code, "ING-2");
+        assert_eq!(result.assignments[0].slots[0].start_time, "07:00");
+    }
+
+    #[test]
+    fn test_prioritize_by_enrollment() {
+        let demands = vec![
+            demand(course("A", "Arte", "1", 1.0), 3, 10),
+            demand(course("B", "Arte", "2", 1.0), 0, 30),
+        ];
+        let options = ScheduleGenerationOptions { prioritize_by: PrioritizationMode::Enrollment, ..Default::default() };
+
+        let result = plan_schedule(demands, &[teacher(&["Arte"])], &[room("A1", 30)], &options);
+
+        assert_eq!(result.assignments[0].course_code, "B");
+    }
+
+    #[test]
+    fn test_morning_slots_are_filled_first() {
+        let slots = time_slots(true);
+        assert!(slots[..25].iter().all(|slot| slot.start < NaiveTime::from_hms_opt(12, 0, 0).unwrap()));
+
+        let mixed = time_slots(false);
+        assert_eq!(mixed[5].start, NaiveTime::from_hms_opt(13, 0, 0).unwrap());
+    }
+
+    fn slot(day: u8, start: &str, end: &str, classroom: &str) -> ScheduleSlot {
+        ScheduleSlot {
+            day_of_week: day,
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            classroom: classroom.to_string(),
+        }
+    }
+
+    fn scheduled(code: &str, teacher_id: Option<Uuid>, schedule: Vec<ScheduleSlot>) -> Course {
+        Course { teacher_id, schedule, ..course(code, code, "1", 1.0) }
+    }
+
+    #[test]
+    fn test_overlapping_candidate_reports_course_and_time() {
+        let teacher_id = Uuid::new_v4();
+        let existing = vec![
+            scheduled("MAT-1", Some(teacher_id), vec![slot(1, "08:00", "09:30", "A1")]),
+            scheduled("HIS-1", None, vec![slot(1, "09:30", "10:30", "A1")]),
+        ];
+
+        let conflicts = find_conflicts(&[slot(1, "09:00", "10:00", "B2")], Some(teacher_id), None, &existing).unwrap();
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::Teacher);
+        assert_eq!(conflicts[0].course_code, "MAT-1");
+        assert_eq!((conflicts[0].start_time.as_str(), conflicts[0].end_time.as_str()), ("08:00", "09:30"));
+    }
+
+    #[test]
+    fn test_classroom_conflict_uses_requested_classroom() {
+        let existing = vec![scheduled("CAS-1", None, vec![slot(3, "10:00", "11:00", "Lab")])];
+        let candidate = [slot(3, "10:30", "11:30", "")];
+
+        let conflicts = find_conflicts(&candidate, None, Some("lab"), &existing).unwrap();
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].kind, ConflictKind::Classroom);
+        assert_eq!(conflicts[0].slot.classroom, "lab");
+        // Adjacent blocks and other days do not overlap
+        assert!(find_conflicts(&[slot(3, "11:00", "12:00", "Lab")], None, None, &existing).unwrap().is_empty());
+        assert!(find_conflicts(&[slot(4, "10:00", "11:00", "Lab")], None, None, &existing).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_candidate_slots_are_rejected() {
+        for bad in [slot(1, "9am", "10:00", "A1"), slot(8, "09:00", "10:00", "A1"), slot(1, "10:00", "09:00", "A1")] {
+            assert!(matches!(find_conflicts(&[bad], None, None, &[]), Err(ServiceError::ValidationError(_))));
+        }
+    }
+
+    #[test]
+    fn test_occupancy_grid_shows_bookings_and_gaps() {
+        let courses = vec![
+            scheduled("MAT-1", None, vec![slot(1, "07:00", "08:00", "A1"), slot(3, "13:00", "14:30", "A1")]),
+            scheduled("HIS-1", None, vec![slot(1, "08:00", "09:00", "B2")]),
+        ];
+
+        let grid = occupancy_grid("A1", &courses);
+
+        assert_eq!(grid.iter().map(|d| d.day_of_week).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        let monday = &grid[0].blocks;
+        assert_eq!(monday[0].course_code.as_deref(), Some("MAT-1"));
+        assert_eq!(monday[1].course_code, None);
+        let wednesday: Vec<_> = grid[2].blocks.iter().filter_map(|b| b.course_code.as_deref()).collect();
+        assert_eq!(wednesday, vec!["MAT-1", "MAT-1"]);
+        let occupied = grid.iter().flat_map(|d| &d.blocks).filter(|b| b.course_id.is_some()).count();
+        assert_eq!(occupied, 3);
+    }
+
+    #[test]
+    fn test_occupancy_grid_includes_weekend_bookings() {
+        let courses = vec![scheduled("ART-1", None, vec![slot(6, "09:00", "10:00", "A1")])];
+
+        let grid = occupancy_grid("A1", &courses);
+
+        assert_eq!(grid.last().unwrap().day_of_week, 6);
+    }
+}
@@ -1,13 +1,20 @@
-use actix_web::{http::StatusCode, web, HttpResponse};
-use chrono::{NaiveDate, Utc};
+use actix_web::web;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::db::metrics;
+use crate::services::batch::{self, BatchRequest, BatchResult};
+use crate::services::{ServiceError, ServiceResult};
+use crate::utils::pagination::{self, PaginatedResponse};
+use crate::utils::validation::DocumentId;
 use crate::models::{
-    student::{CreateStudentDto, CreateStudentWithUserDto, Student, StudentFilter, UpdateStudentDto},
-    GuardianInfo, StudentStatus,
+    audit_log::NewAuditLogEntry,
+    grade::{self, CoursePeriodRow},
+    student::{CreateStudentDto, CreateStudentWithUserDto, PatchStudentDto, Student, StudentFilter, UpdateStudentDto},
+    GuardianInfo, InstitutionSettings, StudentStatus,
 };
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateStudentRequest {
     pub user_id: Uuid,
@@ -17,7 +24,8 @@ pub struct CreateStudentRequest {
     pub academic_year: i32,
     pub guardian_info: Option<GuardianInfo>,
     pub status: StudentStatus,
-};
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateStudentRequest {
     pub enrollment_number: Option<String>,
@@ -26,73 +34,78 @@ pub struct UpdateStudentRequest {
     pub academic_year: Option<i32>,
     pub guardian_info: Option<GuardianInfo>,
     pub status: Option<StudentStatus>,
-};
-
-#[derive(Debug, thiserror::Error)]
-pub enum ServiceError {
-    #[error("Student not found")]
-    NotFound,
-    #[error("Bad request: {0}")]
-    BadRequest(String),
-    #[error("Internal server error: {0}")]
-    InternalServerError(String),
-    #[error("Validation error: {0}")]
-    ValidationError(String),
 }
 
-impl From<ServiceError> for HttpResponse {
-    fn from(error: ServiceError) -> Self {
-        match error {
-            ServiceError::NotFound => HttpResponse::NotFound().json(error.to_string()),
-            ServiceError::BadRequest(msg) => HttpResponse::BadRequest().json(msg),
-            ServiceError::ValidationError(msg) => {
-                HttpResponse::UnprocessableEntity().json(msg)
-            }
-            ServiceError::InternalServerError(msg) => {
-                HttpResponse::InternalServerError().json(msg)
-            }
-        }
-    }
+/// Promotion outcome of a student for an academic year, per MEC rules
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PromotionEligibility {
+    /// Promoted without recovery exams
+    pub is_eligible: bool,
+    /// Subjects whose average is below the passing percentage
+    pub failed_subjects: Vec<String>,
+    /// Percentage of attended classes (present, late or excused)
+    pub attendance_rate: f64,
+    pub meets_attendance_requirement: bool,
+    /// Subjects to recover when the failed ones are within the allowed number
+    pub recovery_exams_needed: Vec<String>,
+    /// Average percentage of all graded subjects
+    pub overall_gpa: f64,
 }
 
-
 pub struct StudentService {
     pool: web::Data<PgPool>,
+    settings: InstitutionSettings,
 }
 
 impl StudentService {
     pub fn new(pool: web::Data<PgPool>) -> Self {
-        Self { pool }
+        Self::with_settings(pool, InstitutionSettings::from_env())
     }
 
-    pub async fn get_all_students(&self, filter: Option<StudentFilter>, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Student>, ServiceError> {
+    pub fn with_settings(pool: web::Data<PgPool>, settings: InstitutionSettings) -> Self {
+        Self { pool, settings }
+    }
+
+    /// Lista una página de estudiantes junto con el total que coincide con el filtro
+    ///
+    /// La página y el conteo se consultan en paralelo.
+    pub async fn get_all_students(
+        &self,
+        filter: Option<StudentFilter>,
+        page: u32,
+        page_size: u32,
+    ) -> ServiceResult<PaginatedResponse<Student>> {
         let filter = filter.unwrap_or_default();
-        
-        Student::find_all(&self.pool, filter, limit, offset)
-            .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+
+        let (students, total) = futures::try_join!(
+            Student::find_all(&self.pool, filter.clone(), Some(i64::from(page_size)), Some(pagination::offset(page, page_size))),
+            Student::count(&self.pool, &filter),
+        )?;
+
+        Ok(PaginatedResponse::new(students, total, page, page_size))
     }
-    pub async fn get_student_by_id(&self, user_id: Uuid) -> Result<Student, ServiceError> {
+
+    pub async fn get_student_by_id(&self, user_id: Uuid) -> ServiceResult<Student> {
         Student::find_by_user_id(&self.pool, user_id)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
             .and_then(|maybe_student| {
-                maybe_student.ok_or(ServiceError::NotFound)
+                maybe_student.ok_or_else(|| ServiceError::NotFound("Estudiante".to_string()))
             })
     }
     
-    pub async fn get_student_by_enrollment_number(&self, enrollment_number: &str) -> Result<Student, ServiceError> {
+    pub async fn get_student_by_enrollment_number(&self, enrollment_number: &str) -> ServiceResult<Student> {
         Student::find_by_enrollment_number(&self.pool, enrollment_number)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
             .and_then(|maybe_student| {
-                maybe_student.ok_or(ServiceError::NotFound)
+                maybe_student.ok_or_else(|| ServiceError::NotFound("Estudiante".to_string()))
             })
     }
     pub async fn create_student(
         &self,
         request: CreateStudentRequest,
-    ) -> Result<Student, ServiceError> {
+    ) -> ServiceResult<Student> {
         // Validate the request
         Self::validate_create_student(&request)?;
 
@@ -108,22 +121,29 @@ impl StudentService {
 
         Student::create(&self.pool, dto)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
     }
     
+    /// Crea el usuario y el estudiante en una misma transacción
+    ///
+    /// El documento se valida con `DocumentId` y se guarda solo con sus dígitos.
     pub async fn create_student_with_user(
         &self,
-        request: CreateStudentWithUserDto,
-    ) -> Result<(crate::models::User, Student), ServiceError> {
+        mut request: CreateStudentWithUserDto,
+    ) -> ServiceResult<(crate::models::User, Student)> {
+        request.document_id = DocumentId::parse(&request.document_id)
+            .map_err(|e| ServiceError::ValidationError(format!("document_id: {}", e)))?
+            .into_string();
+
         Student::create_with_user(&self.pool, request)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
     }
     pub async fn update_student(
         &self,
         user_id: Uuid,
         request: UpdateStudentRequest,
-    ) -> Result<Student, ServiceError> {
+    ) -> ServiceResult<Student> {
         // First, check if the student exists
         self.get_student_by_id(user_id).await?;
 
@@ -141,20 +161,110 @@ impl StudentService {
 
         Student::update(&self.pool, user_id, dto)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
     }
-    pub async fn delete_student(&self, user_id: Uuid) -> Result<(), ServiceError> {
+    pub async fn patch_student(
+        &self,
+        user_id: Uuid,
+        patch: PatchStudentDto,
+    ) -> ServiceResult<Student> {
+        // First, check if the student exists
+        self.get_student_by_id(user_id).await?;
+
+        if matches!(&patch.enrollment_number, Some(number) if number.is_empty()) {
+            return Err(ServiceError::ValidationError(
+                "Enrollment number cannot be empty".to_string(),
+            ));
+        }
+
+        Student::patch(&self.pool, user_id, patch)
+            .await
+            .map_err(ServiceError::from)
+    }
+    pub async fn delete_student(&self, user_id: Uuid) -> ServiceResult<()> {
         // First, check if the student exists
         self.get_student_by_id(user_id).await?;
 
         Student::delete(&self.pool, user_id)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
             .map(|_| ())
     }
 
+    /// Withdraws (soft-deletes) several students in a single transaction.
+    ///
+    /// In strict mode an unknown id rolls back the whole batch; otherwise it is
+    /// reported as `not_found` and the rest are withdrawn. Each withdrawal is
+    /// recorded in the audit log, attributed to `audit`, in the same transaction.
+    pub async fn batch_withdraw(&self, request: BatchRequest, audit: NewAuditLogEntry) -> ServiceResult<BatchResult> {
+        batch::validate_batch_size(&request.ids).map_err(ServiceError::ValidationError)?;
+
+        let mut tx = metrics::begin(&self.pool)
+            .await?;
+
+        let existing = Student::lock_existing(&mut tx, &request.ids)
+            .await?
+            .into_iter()
+            .collect();
+
+        let plan = match batch::plan_batch(&request.ids, &existing, request.strict) {
+            Ok(plan) => plan,
+            Err(outcomes) => {
+                tx.rollback().await?;
+                return Ok(BatchResult { committed: false, outcomes });
+            }
+        };
+
+        if let Err(e) = Student::withdraw_many(&mut tx, &plan.to_apply).await {
+            log::warn!("Batch withdraw rolled back: {}", e);
+            tx.rollback().await?;
+            return Ok(BatchResult {
+                committed: false,
+                outcomes: batch::rolled_back(plan.outcomes, &e.to_string()),
+            });
+        }
+
+        batch::record_audit_events(&mut tx, &audit, "student.withdraw", "students", &plan.outcomes).await?;
+        tx.commit().await?;
+
+        Ok(BatchResult { committed: true, outcomes: plan.outcomes })
+    }
+
+    /// Evaluates whether a student is promoted at the end of an academic year.
+    ///
+    /// Subjects are graded with their final grade once closed, otherwise with
+    /// the mean of the period averages; subjects without assessments are left
+    /// out. A year without attendance records counts as full attendance.
+    pub async fn calculate_promotion_eligibility(
+        &self,
+        student_id: Uuid,
+        academic_year: i32,
+    ) -> ServiceResult<PromotionEligibility> {
+        self.get_student_by_id(student_id).await?;
+
+        let rows = grade::find_student_period_rows(&self.pool, student_id, Some(academic_year), None, None)
+            .await?;
+
+        let (attended, total): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FILTER (WHERE a.status IN ('present', 'late', 'excused')), COUNT(*)
+            FROM attendance a
+            JOIN courses c ON c.id = a.course_id
+            WHERE a.student_id = $1 AND c.academic_year = $2
+            "#,
+        )
+        .bind(student_id)
+        .bind(academic_year)
+        .fetch_one(self.pool.get_ref())
+        .await?;
+
+        let attendance_rate = if total > 0 { attended as f64 * 100.0 / total as f64 } else { 100.0 };
+
+        Ok(evaluate_promotion(&subject_averages(&rows), attendance_rate, &self.settings))
+    }
+
     // Helper methods for validation
-    fn validate_create_student(request: &CreateStudentRequest) -> Result<(), ServiceError> {
+    fn validate_create_student(request: &CreateStudentRequest) -> ServiceResult<()> {
         if request.enrollment_number.is_empty() {
             return Err(ServiceError::ValidationError(
                 "Enrollment number cannot be empty".to_string(),
@@ -177,7 +287,7 @@ impl StudentService {
         Ok(())
     }
 
-    fn validate_update_student(request: &UpdateStudentRequest) -> Result<(), ServiceError> {
+    fn validate_update_student(request: &UpdateStudentRequest) -> ServiceResult<()> {
         if let Some(ref enrollment_number) = request.enrollment_number {
             if enrollment_number.is_empty() {
                 return Err(ServiceError::ValidationError(
@@ -205,4 +315,178 @@ impl StudentService {
         // Add more validations as needed
         Ok(())
     }
+}
+
+/// Average percentage of each subject, from the per-period rows of a student
+fn subject_averages(rows: &[CoursePeriodRow]) -> Vec<(String, f64)> {
+    let mut subjects: Vec<(Uuid, String, Option<f64>, Vec<f64>)> = Vec::new();
+
+    for row in rows {
+        let closed_grade = if row.closed { row.final_grade } else { None };
+        match subjects.last_mut() {
+            Some(subject) if subject.0 == row.course_id => subject.3.extend(row.weighted_average),
+            _ => subjects.push((
+                row.course_id,
+                row.course_name.clone(),
+                closed_grade,
+                row.weighted_average.into_iter().collect(),
+            )),
+        }
+    }
+
+    subjects
+        .into_iter()
+        .filter_map(|(_, name, final_grade, periods)| {
+            let average = final_grade.or_else(|| {
+                (!periods.is_empty()).then(|| periods.iter().sum::<f64>() / periods.len() as f64)
+            })?;
+            Some((name, average))
+        })
+        .collect()
+}
+
+/// Applies the promotion criteria to the subject averages and attendance of a year.
+///
+/// A student with no failed subjects, the minimum overall average and the
+/// minimum attendance is promoted. With up to `max_failed_subjects` failed
+/// subjects (and the other criteria met) those subjects go to recovery exams;
+/// with more, the year must be repeated.
+pub fn evaluate_promotion(
+    subjects: &[(String, f64)],
+    attendance_rate: f64,
+    settings: &InstitutionSettings,
+) -> PromotionEligibility {
+    let failed_subjects: Vec<String> = subjects
+        .iter()
+        .filter(|(_, average)| *average < settings.min_promotion_average)
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    let overall_gpa = if subjects.is_empty() {
+        0.0
+    } else {
+        subjects.iter().map(|(_, average)| average).sum::<f64>() / subjects.len() as f64
+    };
+    let overall_gpa = (overall_gpa * 100.0).round() / 100.0;
+    let attendance_rate = (attendance_rate * 100.0).round() / 100.0;
+
+    let meets_attendance_requirement = attendance_rate >= settings.min_attendance_rate;
+    let meets_average = overall_gpa >= settings.min_promotion_average;
+
+    let recovery_exams_needed = if meets_attendance_requirement
+        && meets_average
+        && failed_subjects.len() <= settings.max_failed_subjects
+    {
+        failed_subjects.clone()
+    } else {
+        Vec::new()
+    };
+
+    PromotionEligibility {
+        is_eligible: meets_attendance_requirement && meets_average && failed_subjects.is_empty(),
+        failed_subjects,
+        attendance_rate,
+        meets_attendance_requirement,
+        recovery_exams_needed,
+        overall_gpa,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn subjects(averages: &[(&str, f64)]) -> Vec<(String, f64)> {
+        averages.iter().map(|(name, avg)| (name.to_string(), *avg)).collect()
+    }
 
+    fn row(course: Uuid, name: &str, period: i16, average: Option<f64>, final_grade: Option<f64>) -> CoursePeriodRow {
+        CoursePeriodRow {
+            course_id: course,
+            course_code: name.to_uppercase(),
+            course_name: name.to_string(),
+            period: Some(period),
+            assessment_count: 1,
+            weighted_average: average,
+            final_grade,
+            closed: final_grade.is_some(),
+        }
+    }
+
+    #[test]
+    fn test_promoted_student() {
+        let result = evaluate_promotion(
+            &subjects(&[("Matemática", 72.0), ("Castellano", 88.0)]),
+            92.5,
+            &InstitutionSettings::default(),
+        );
+
+        assert!(result.is_eligible);
+        assert_eq!(result.overall_gpa, 80.0);
+        assert!(result.failed_subjects.is_empty());
+        assert!(result.recovery_exams_needed.is_empty());
+    }
+
+    #[test]
+    fn test_two_failed_subjects_go_to_recovery() {
+        let result = evaluate_promotion(
+            &subjects(&[("Matemática", 55.0), ("Física", 50.0), ("Castellano", 90.0), ("Historia", 85.0)]),
+            80.0,
+            &InstitutionSettings::default(),
+        );
+
+        assert!(!result.is_eligible);
+        assert_eq!(result.failed_subjects, vec!["Matemática", "Física"]);
+        assert_eq!(result.recovery_exams_needed, result.failed_subjects);
+    }
+
+    #[test]
+    fn test_three_failed_subjects_repeat_the_year() {
+        let result = evaluate_promotion(
+            &subjects(&[("Matemática", 55.0), ("Física", 50.0), ("Química", 40.0), ("Castellano", 100.0)]),
+            80.0,
+            &InstitutionSettings::default(),
+        );
+
+        assert!(!result.is_eligible);
+        assert_eq!(result.failed_subjects.len(), 3);
+        assert!(result.recovery_exams_needed.is_empty());
+    }
+
+    #[test]
+    fn test_low_attendance_is_not_eligible() {
+        let result = evaluate_promotion(&subjects(&[("Matemática", 95.0)]), 74.99, &InstitutionSettings::default());
+
+        assert!(!result.meets_attendance_requirement);
+        assert!(!result.is_eligible);
+    }
+
+    #[test]
+    fn test_thresholds_come_from_settings() {
+        let settings = InstitutionSettings {
+            min_promotion_average: 70.0,
+            min_attendance_rate: 80.0,
+            max_failed_subjects: 1,
+        };
+        let result = evaluate_promotion(&subjects(&[("Matemática", 65.0), ("Castellano", 95.0)]), 85.0, &settings);
+
+        assert_eq!(result.failed_subjects, vec!["Matemática"]);
+        assert_eq!(result.recovery_exams_needed, vec!["Matemática"]);
+    }
+
+    #[test]
+    fn test_subject_averages_prefer_final_grade() {
+        let (mat, cas, his) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let rows = vec![
+            row(mat, "Matemática", 1, Some(50.0), None),
+            row(mat, "Matemática", 2, Some(70.0), None),
+            row(cas, "Castellano", 1, Some(40.0), Some(65.0)),
+            row(his, "Historia", 1, None, None),
+        ];
+
+        assert_eq!(
+            subject_averages(&rows),
+            vec![("Matemática".to_string(), 60.0), ("Castellano".to_string(), 65.0)]
+        );
+    }
+}
@@ -1,12 +1,22 @@
-use actix_web::{http::StatusCode, web, HttpResponse};
+use std::collections::HashSet;
+use std::sync::Arc;
+
 use chrono::{NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::db::{metrics, DbError, DbPool};
 use crate::models::{
-    teacher::{CreateTeacherDto, Teacher, TeacherFilter, UpdateTeacherDto, TeacherWithUserData},
-    TeacherStatus,
+    patch::required,
+    teacher::{CreateTeacherDto, CreateTeacherWithUserDto, Teacher, TeacherFilter, UpdateTeacherDto, TeacherWithUserData},
+    user::CreateUserDto,
+    Authentication, Course, Role, TeacherStatus, User,
+};
+use crate::services::{ServiceError, ServiceResult};
+use crate::utils::pagination::{self, PaginatedResponse};
+use crate::utils::{
+    format_ci,
+    validation::{normalize_email, validate_email, validate_phone_number, DocumentId},
 };
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,26 +32,20 @@ pub struct CreateTeacherRequest {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateTeacherRequest {
+    #[serde(default, deserialize_with = "required")]
     pub professional_id: Option<String>,
+    #[serde(default, deserialize_with = "required")]
     pub specialization: Option<String>,
+    #[serde(default, deserialize_with = "required")]
     pub hire_date: Option<NaiveDate>,
+    #[serde(default, deserialize_with = "required")]
     pub education_level: Option<String>,
+    #[serde(default, deserialize_with = "required")]
     pub subjects: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "required")]
     pub status: Option<TeacherStatus>,
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum ServiceError {
-    #[error("Teacher not found")]
-    NotFound,
-    #[error("Bad request: {0}")]
-    BadRequest(String),
-    #[error("Internal server error: {0}")]
-    InternalServerError(String),
-    #[error("Validation error: {0}")]
-    ValidationError(String),
-}
-
 // Custom error types for teacher operations
 #[derive(Debug, thiserror::Error)]
 pub enum CreateTeacherError {
@@ -73,69 +77,79 @@ pub enum UpdateTeacherError {
     DatabaseError(String),
 }
 
-impl From<ServiceError> for HttpResponse {
-    fn from(error: ServiceError) -> Self {
-        match error {
-            ServiceError::NotFound => HttpResponse::NotFound().json(error.to_string()),
-            ServiceError::BadRequest(msg) => HttpResponse::BadRequest().json(msg),
-            ServiceError::ValidationError(msg) => {
-                HttpResponse::UnprocessableEntity().json(msg)
-            }
-            ServiceError::InternalServerError(msg) => {
-                HttpResponse::InternalServerError().json(msg)
-            }
-        }
-    }
-}
-
 pub struct TeacherService {
-    pool: web::Data<PgPool>,
+    pool: Arc<DbPool>,
+    /// `mail.lowercase_local_part`: si los correos importados van con la parte local en minúsculas
+    lowercase_email_local_part: bool,
 }
 
 impl TeacherService {
-    pub fn new(pool: web::Data<PgPool>) -> Self {
-        Self { pool }
+    pub fn new(pool: Arc<DbPool>) -> Self {
+        Self { pool, lowercase_email_local_part: true }
+    }
+
+    /// Normaliza los correos según `mail.lowercase_local_part` (activo por defecto)
+    pub fn with_lowercase_email_local_part(mut self, enabled: bool) -> Self {
+        self.lowercase_email_local_part = enabled;
+        self
     }
 
-    pub async fn get_all_teachers(&self, filter: Option<TeacherFilter>, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<Teacher>, ServiceError> {
+    /// Lista una página de profesores junto con el total que coincide con el filtro
+    ///
+    /// La página y el conteo se consultan en paralelo.
+    pub async fn get_all_teachers(
+        &self,
+        filter: Option<TeacherFilter>,
+        page: u32,
+        page_size: u32,
+    ) -> ServiceResult<PaginatedResponse<Teacher>> {
         let filter = filter.unwrap_or_default();
-        
-        Teacher::find_all(&self.pool, filter, limit, offset)
+
+        let (teachers, total) = futures::try_join!(
+            Teacher::find_all(&self.pool, filter.clone(), Some(i64::from(page_size)), Some(pagination::offset(page, page_size))),
+            Teacher::count(&self.pool, filter),
+        )?;
+
+        Ok(PaginatedResponse::new(teachers, total, page, page_size))
+    }
+
+    pub async fn count_teachers(&self, filter: Option<TeacherFilter>) -> ServiceResult<i64> {
+        Teacher::count(&self.pool, filter.unwrap_or_default())
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
     }
 
-    pub async fn get_teacher_by_id(&self, user_id: Uuid) -> Result<Teacher, ServiceError> {
+    pub async fn get_teacher_by_id(&self, user_id: Uuid) -> ServiceResult<Teacher> {
         Teacher::find_by_user_id(&self.pool, user_id)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
             .and_then(|maybe_teacher| {
-                maybe_teacher.ok_or(ServiceError::NotFound)
+                maybe_teacher.ok_or_else(|| ServiceError::NotFound("Profesor".to_string()))
             })
     }
     
-    pub async fn get_teacher_with_user_data(&self, user_id: Uuid) -> Result<TeacherWithUserData, ServiceError> {
+    pub async fn get_teacher_with_user_data(&self, user_id: Uuid) -> ServiceResult<TeacherWithUserData> {
         Teacher::get_teacher_with_user_data(&self.pool, user_id)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
             .and_then(|maybe_teacher| {
-                maybe_teacher.ok_or(ServiceError::NotFound)
+                maybe_teacher.ok_or_else(|| ServiceError::NotFound("Profesor".to_string()))
             })
     }
     
-    pub async fn get_teacher_by_professional_id(&self, professional_id: &str) -> Result<Teacher, ServiceError> {
+    pub async fn get_teacher_by_professional_id(&self, professional_id: &str) -> ServiceResult<Teacher> {
         Teacher::find_by_professional_id(&self.pool, professional_id)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
             .and_then(|maybe_teacher| {
-                maybe_teacher.ok_or(ServiceError::NotFound)
+                maybe_teacher.ok_or_else(|| ServiceError::NotFound("Profesor".to_string()))
             })
     }
 
     pub async fn create_teacher(
         &self,
         request: CreateTeacherRequest,
-    ) -> Result<Teacher, ServiceError> {
+    ) -> ServiceResult<Teacher> {
         // Validate the request
         Self::validate_create_teacher(&request)?;
 
@@ -149,16 +163,35 @@ impl TeacherService {
             status: request.status,
         };
 
-        Teacher::create(&self.pool, dto)
+        Teacher::create(&self.pool, dto).await.map_err(|e| match e {
+            DbError::NotFound(_) => ServiceError::ValidationError("User not found".to_string()),
+            e => ServiceError::DatabaseError(e),
+        })
+    }
+
+    /// Creates the user account and the teacher profile in one transaction
+    pub async fn create_teacher_with_user(
+        &self,
+        request: CreateTeacherWithUserDto,
+    ) -> ServiceResult<(User, Teacher)> {
+        Self::validate_user_fields(&request)?;
+        Self::validate_teacher_fields(
+            &request.professional_id,
+            &request.specialization,
+            &request.education_level,
+            &request.subjects,
+        )?;
+
+        Teacher::create_with_user(&self.pool, request)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
     }
     
     pub async fn update_teacher(
         &self,
         user_id: Uuid,
         request: UpdateTeacherRequest,
-    ) -> Result<Teacher, ServiceError> {
+    ) -> ServiceResult<Teacher> {
         // First, check if the teacher exists
         self.get_teacher_by_id(user_id).await?;
 
@@ -176,40 +209,131 @@ impl TeacherService {
 
         Teacher::update(&self.pool, user_id, dto)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
     }
 
-    pub async fn delete_teacher(&self, user_id: Uuid) -> Result<(), ServiceError> {
+    /// Soft delete: the teacher is marked as terminated and keeps their history
+    pub async fn delete_teacher(&self, user_id: Uuid) -> ServiceResult<()> {
         // First, check if the teacher exists
         self.get_teacher_by_id(user_id).await?;
 
-        Teacher::delete(&self.pool, user_id)
+        Teacher::deactivate(&self.pool, user_id)
             .await
-            .map_err(|e| ServiceError::InternalServerError(e.to_string()))
+            .map_err(ServiceError::from)
             .map(|_| ())
     }
 
+    pub async fn get_teacher_courses(&self, user_id: Uuid) -> ServiceResult<Vec<Course>> {
+        // First, check if the teacher exists
+        self.get_teacher_by_id(user_id).await?;
+
+        Course::find_by_teacher(&self.pool, user_id)
+            .await
+            .map_err(ServiceError::from)
+    }
+
+    /// Onboards teachers in bulk from a CSV file
+    ///
+    /// Each valid row creates the user, the teacher profile and an account
+    /// whose temporary password is the formatted CI followed by `@SAI`; the
+    /// teacher must replace it on first login. Rows whose professional ID is
+    /// already registered are skipped, invalid rows are reported in `errors`.
+    /// All inserts run in one transaction, so a database failure leaves no
+    /// partial import behind.
+    pub async fn import_from_csv(&self, csv_bytes: &[u8]) -> ServiceResult<ImportResult> {
+        let (rows, errors) = parse_teacher_csv(csv_bytes, self.lowercase_email_local_part)?;
+        let mut result = ImportResult {
+            errors,
+            ..Default::default()
+        };
+
+        let mut tx = metrics::begin(&self.pool).await?;
+        let mut professional_ids = HashSet::new();
+        let mut emails = HashSet::new();
+        let mut document_ids = HashSet::new();
+
+        for (line, row) in rows {
+            let registered = Teacher::find_by_professional_id(&self.pool, &row.teacher.professional_id)
+                .await?
+                .is_some();
+            if registered || !professional_ids.insert(row.teacher.professional_id.clone()) {
+                log::warn!(
+                    "CSV line {}: professional ID {} is already registered, row skipped",
+                    line,
+                    row.teacher.professional_id
+                );
+                result.skipped += 1;
+                continue;
+            }
+
+            let email_taken = User::find_by_email(&self.pool, &row.user.email)
+                .await?
+                .is_some();
+            if email_taken || !emails.insert(row.user.email.clone()) {
+                result.errors.push(CsvImportError::new(line, format!("Email {} is already in use", row.user.email)));
+                continue;
+            }
+
+            let document_taken = User::find_by_document_id(&self.pool, &row.user.document_id)
+                .await?
+                .is_some();
+            if document_taken || !document_ids.insert(row.user.document_id.clone()) {
+                result.errors.push(CsvImportError::new(line, format!("CI {} is already registered", row.user.document_id)));
+                continue;
+            }
+
+            let user = User::create(&mut *tx, row.user).await?;
+            let teacher = CreateTeacherDto {
+                user_id: user.id,
+                ..row.teacher
+            };
+            Teacher::insert(&mut *tx, teacher).await?;
+            Authentication::create_with_temporary_password(&mut *tx, user.id, &row.temporary_password)
+                .await?;
+
+            result.created += 1;
+        }
+
+        tx.commit().await?;
+
+        Ok(result)
+    }
+
     // Helper methods for validation
-    fn validate_create_teacher(request: &CreateTeacherRequest) -> Result<(), ServiceError> {
-        if request.professional_id.is_empty() {
+    fn validate_create_teacher(request: &CreateTeacherRequest) -> ServiceResult<()> {
+        Self::validate_teacher_fields(
+            &request.professional_id,
+            &request.specialization,
+            &request.education_level,
+            &request.subjects,
+        )
+    }
+
+    fn validate_teacher_fields(
+        professional_id: &str,
+        specialization: &str,
+        education_level: &str,
+        subjects: &[String],
+    ) -> ServiceResult<()> {
+        if professional_id.trim().is_empty() {
             return Err(ServiceError::ValidationError(
                 "Professional ID cannot be empty".to_string(),
             ));
         }
         
-        if request.specialization.is_empty() {
+        if specialization.trim().is_empty() {
             return Err(ServiceError::ValidationError(
                 "Specialization cannot be empty".to_string(),
             ));
         }
         
-        if request.education_level.is_empty() {
+        if education_level.trim().is_empty() {
             return Err(ServiceError::ValidationError(
                 "Education level cannot be empty".to_string(),
             ));
         }
         
-        if request.subjects.is_empty() {
+        if subjects.is_empty() {
             return Err(ServiceError::ValidationError(
                 "Subjects list cannot be empty".to_string(),
             ));
@@ -219,7 +343,29 @@ impl TeacherService {
         Ok(())
     }
 
-    fn validate_update_teacher(request: &UpdateTeacherRequest) -> Result<(), ServiceError> {
+    fn validate_user_fields(request: &CreateTeacherWithUserDto) -> ServiceResult<()> {
+        if request.document_id.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Document ID cannot be empty".to_string(),
+            ));
+        }
+
+        if request.full_name.trim().is_empty() {
+            return Err(ServiceError::ValidationError(
+                "Full name cannot be empty".to_string(),
+            ));
+        }
+
+        if !request.email.contains('@') {
+            return Err(ServiceError::ValidationError(
+                "Email address is not valid".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn validate_update_teacher(request: &UpdateTeacherRequest) -> ServiceResult<()> {
         if let Some(ref professional_id) = request.professional_id {
             if professional_id.is_empty() {
                 return Err(ServiceError::ValidationError(
@@ -257,3 +403,267 @@ impl TeacherService {
     }
 }
 
+
+/// Columns of the teacher onboarding CSV
+const CSV_COLUMNS: [&str; 10] = [
+    "cedula",
+    "apellidos",
+    "nombres",
+    "fecha_nacimiento",
+    "email",
+    "telefono",
+    "especialidad",
+    "nivel_educacion",
+    "registro_profesional",
+    "materias",
+];
+
+/// Suffix appended to the formatted CI to build the temporary password
+const TEMPORARY_PASSWORD_SUFFIX: &str = "@SAI";
+
+/// Outcome of a CSV teacher import
+#[derive(Debug, Default, Serialize)]
+pub struct ImportResult {
+    pub created: u32,
+    /// Rows whose professional ID was already registered
+    pub skipped: u32,
+    pub errors: Vec<CsvImportError>,
+}
+
+/// A CSV row that could not be imported
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CsvImportError {
+    /// Line of the file, counting the header as line 1
+    pub line: u64,
+    pub message: String,
+}
+
+impl CsvImportError {
+    fn new(line: u64, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TeacherCsvRow {
+    cedula: String,
+    apellidos: String,
+    nombres: String,
+    fecha_nacimiento: String,
+    email: String,
+    telefono: Option<String>,
+    especialidad: String,
+    nivel_educacion: String,
+    registro_profesional: String,
+    materias: String,
+}
+
+/// A validated row, ready to insert
+#[derive(Debug)]
+struct TeacherImportRow {
+    user: CreateUserDto,
+    /// Teacher profile; `user_id` is filled in once the user exists
+    teacher: CreateTeacherDto,
+    temporary_password: String,
+}
+
+/// Valid rows with their line numbers, and the errors of the invalid ones
+type ParsedTeacherCsv = (Vec<(u64, TeacherImportRow)>, Vec<CsvImportError>);
+
+/// Reads and validates every row of the CSV
+///
+/// Fails only when the file itself is unusable (missing columns); invalid
+/// rows are returned as errors next to the valid ones.
+fn parse_teacher_csv(csv_bytes: &[u8], lowercase_email_local_part: bool) -> ServiceResult<ParsedTeacherCsv> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(csv_bytes);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| ServiceError::ValidationError(format!("Invalid CSV file: {}", e)))?
+        .clone();
+    let missing: Vec<&str> = CSV_COLUMNS
+        .iter()
+        .copied()
+        .filter(|column| !headers.iter().any(|header| header == *column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(ServiceError::ValidationError(format!("Missing CSV columns: {}", missing.join(", "))));
+    }
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                let line = e.position().map_or(0, |position| position.line());
+                errors.push(CsvImportError::new(line, e.to_string()));
+                continue;
+            }
+        };
+        let line = record.position().map_or(0, |position| position.line());
+
+        let parsed = record
+            .deserialize::<TeacherCsvRow>(Some(&headers))
+            .map_err(|e| e.to_string())
+            .and_then(|row| validate_csv_row(row, lowercase_email_local_part));
+        match parsed {
+            Ok(row) => rows.push((line, row)),
+            Err(message) => errors.push(CsvImportError::new(line, message)),
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+fn validate_csv_row(row: TeacherCsvRow, lowercase_email_local_part: bool) -> Result<TeacherImportRow, String> {
+    let document_id = DocumentId::parse(&row.cedula)
+        .map_err(|_| format!("Invalid CI: {}", row.cedula))?
+        .into_string();
+
+    if row.nombres.is_empty() || row.apellidos.is_empty() {
+        return Err("First and last names are required".to_string());
+    }
+
+    let birth_date = NaiveDate::parse_from_str(&row.fecha_nacimiento, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(&row.fecha_nacimiento, "%d/%m/%Y"))
+        .map_err(|_| format!("Invalid birth date (expected YYYY-MM-DD or DD/MM/YYYY): {}", row.fecha_nacimiento))?;
+
+    let email = normalize_email(&row.email, lowercase_email_local_part);
+    if !validate_email(&email) {
+        return Err(format!("Invalid email: {}", row.email));
+    }
+
+    let phone = row.telefono.filter(|phone| !phone.is_empty());
+    if let Some(phone) = &phone {
+        if !validate_phone_number(phone) {
+            return Err(format!("Invalid phone number: {}", phone));
+        }
+    }
+
+    let subjects: Vec<String> = row
+        .materias
+        .split(';')
+        .map(str::trim)
+        .filter(|subject| !subject.is_empty())
+        .map(str::to_string)
+        .collect();
+    TeacherService::validate_teacher_fields(
+        &row.registro_profesional,
+        &row.especialidad,
+        &row.nivel_educacion,
+        &subjects,
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(TeacherImportRow {
+        temporary_password: format!("{}{}", format_ci(&document_id), TEMPORARY_PASSWORD_SUFFIX),
+        user: CreateUserDto {
+            document_id,
+            full_name: format!("{} {}", row.nombres, row.apellidos),
+            email,
+            phone,
+            address: None,
+            birth_date,
+            role: Role::Teacher,
+        },
+        teacher: CreateTeacherDto {
+            user_id: Uuid::nil(),
+            professional_id: row.registro_profesional,
+            specialization: row.especialidad,
+            hire_date: Utc::now().date_naive(),
+            education_level: row.nivel_educacion,
+            subjects,
+            status: TeacherStatus::Active,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request() -> CreateTeacherRequest {
+        CreateTeacherRequest {
+            user_id: Uuid::new_v4(),
+            professional_id: "MEC-1234".to_string(),
+            specialization: "Matemática".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2024, 2, 15).unwrap(),
+            education_level: "Licenciatura".to_string(),
+            subjects: vec!["Matemática".to_string()],
+            status: TeacherStatus::Active,
+        }
+    }
+
+    #[test]
+    fn test_valid_request_passes() {
+        assert!(TeacherService::validate_create_teacher(&request()).is_ok());
+    }
+
+    #[test]
+    fn test_blank_fields_are_rejected() {
+        let mut blank_id = request();
+        blank_id.professional_id = "  ".to_string();
+        assert!(TeacherService::validate_create_teacher(&blank_id).is_err());
+
+        let mut no_subjects = request();
+        no_subjects.subjects.clear();
+        assert!(TeacherService::validate_create_teacher(&no_subjects).is_err());
+    }
+
+    const HEADER: &str = "cedula,apellidos,nombres,fecha_nacimiento,email,telefono,especialidad,nivel_educacion,registro_profesional,materias";
+
+    fn csv(rows: &[&str]) -> Vec<u8> {
+        std::iter::once(HEADER).chain(rows.iter().copied()).collect::<Vec<_>>().join("\n").into_bytes()
+    }
+
+    #[test]
+    fn test_csv_row_is_parsed() {
+        let bytes = csv(&["1.234.567,Benítez,Ana María,15/03/1985,Ana@Colegio.edu.py,0981123456,Matemática,Licenciatura,MEC-001,Álgebra; Geometría"]);
+
+        let (rows, errors) = parse_teacher_csv(&bytes, true).unwrap();
+
+        assert!(errors.is_empty());
+        let (line, row) = &rows[0];
+        assert_eq!(*line, 2);
+        assert_eq!(row.user.document_id, "1234567");
+        assert_eq!(row.user.full_name, "Ana María Benítez");
+        assert_eq!(row.user.email, "ana@colegio.edu.py");
+        assert_eq!(row.user.birth_date, NaiveDate::from_ymd_opt(1985, 3, 15).unwrap());
+        assert_eq!(row.teacher.subjects, vec!["Álgebra", "Geometría"]);
+        assert_eq!(row.temporary_password, "1.234.567@SAI");
+    }
+
+    #[test]
+    fn test_invalid_rows_are_reported_by_line() {
+        let bytes = csv(&[
+            "12345,Benítez,Ana,1985-03-15,ana@colegio.edu.py,,Matemática,Licenciatura,MEC-001,Álgebra",
+            "2345678,Giménez,Carlos,1980-01-02,no-es-email,,Historia,Licenciatura,MEC-002,Historia",
+            "3456789,Ortiz,Luis,1979-07-20,luis@colegio.edu.py,,Física,Maestría,MEC-003,",
+            "4567890,Vera,Rosa,1990-11-05,rosa@colegio.edu.py,,Química,Licenciatura,MEC-004,Química",
+        ]);
+
+        let (rows, errors) = parse_teacher_csv(&bytes, true).unwrap();
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0, 5);
+        let lines: Vec<u64> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3, 4]);
+        assert!(errors[0].message.contains("CI"));
+    }
+
+    #[test]
+    fn test_missing_columns_reject_the_file() {
+        let bytes = "cedula,apellidos,nombres\n1234567,Benítez,Ana".as_bytes().to_vec();
+
+        let err = parse_teacher_csv(&bytes, true).unwrap_err();
+
+        assert!(err.to_string().contains("fecha_nacimiento"));
+    }
+}
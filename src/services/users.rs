@@ -1,384 +1,372 @@
-use actix_web::{web, HttpResponse};
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
-use sqlx::{self, PgPool};
+use std::sync::Arc;
+
 use thiserror::Error;
 use uuid::Uuid;
 
-use crate::models::user::{Role, User};
-use crate::utils::pagination::{PaginationOptions, PaginationResponse};
+use crate::db::{DbError, DbPool, ReadPool};
+use crate::models::user::{CreateUserDto, PatchUserDto, UpdateUserDto, User, UserFilter};
+use crate::utils::validation::{normalize_email, validate_email, validate_phone_number, DocumentId};
 
+/// Errores de las operaciones sobre usuarios
 #[derive(Debug, Error)]
-pub enum ServiceError {
-    #[error("Error de la base de datos: {0}")]
-    DatabaseError(#[from] sqlx::Error),
+pub enum UserError {
+    /// No existe un usuario con el ID indicado
     #[error("Usuario no encontrado")]
     NotFound,
-    #[error("Error en la petición: {0}")]
-    BadRequest(String),
-    #[error("Error de validación: {0}")]
-    ValidationError(String),
-    #[error("Error interno del servidor: {0}")]
-    InternalServerError(String),
-}
-
-#[derive(Debug, Error)]
-pub enum CreateUserError {
+    /// Otro usuario ya tiene el correo electrónico
     #[error("Usuario con este email ya existe")]
     EmailAlreadyExists,
-    #[error("Usuario con este nombre de usuario ya existe")]
-    UsernameAlreadyExists,
+    /// Otro usuario ya tiene el número de documento
+    #[error("Usuario con este número de documento ya existe")]
+    DocumentIdAlreadyExists,
+    /// Datos inválidos; el mensaje reúne todos los campos con errores
     #[error("Error de validación: {0}")]
     ValidationError(String),
-    #[error("Error interno: {0}")]
-    InternalError(String),
+    /// Error de base de datos
+    #[error("Error de base de datos: {0}")]
+    DatabaseError(#[from] DbError),
 }
 
-#[derive(Debug, Error)]
-pub enum UpdateUserError {
-    #[error("Usuario no encontrado")]
-    NotFound,
-    #[error("Usuario con este email ya existe")]
-    EmailAlreadyExists,
-    #[error("Usuario con este nombre de usuario ya existe")]
-    UsernameAlreadyExists,
-    #[error("Error de validación: {0}")]
-    ValidationError(String),
-    #[error("Error interno: {0}")]
-    InternalError(String),
+impl From<sqlx::Error> for UserError {
+    fn from(err: sqlx::Error) -> Self {
+        UserError::DatabaseError(err.into())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct UserResponse {
-    pub id: Uuid,
-    pub username: String,
-    pub email: String,
-    pub first_name: String,
-    pub last_name: String,
-    pub role: Role,
-    pub is_active: bool,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+/// Resultado de las operaciones sobre usuarios
+pub type UserResult<T> = Result<T, UserError>;
+
+/// Forma de recorrer el listado de usuarios
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserPagination {
+    /// Página `page` (desde 1) de `per_page` usuarios
+    Offset { page: usize, per_page: usize },
+    /// Los `per_page` usuarios que siguen a `after`
+    Cursor { after: Uuid, per_page: usize },
 }
 
-#[derive(Debug, Deserialize)]
-pub struct CreateUserRequest {
-    pub username: String,
-    pub email: String,
-    pub password: String,
-    pub first_name: String,
-    pub last_name: String,
-    pub role: Role,
+/// Una página del listado de usuarios
+#[derive(Debug)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    /// Usuarios que coinciden con la búsqueda en todas las páginas
+    pub total: i64,
+    /// Si quedan usuarios después de esta página
+    pub has_more: bool,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct UpdateUserRequest {
-    pub username: Option<String>,
-    pub email: Option<String>,
-    pub password: Option<String>,
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
-    pub role: Option<Role>,
-    pub is_active: Option<bool>,
+/// Servicio para la gestión de usuarios
+pub struct UserService {
+    /// Pool de conexiones a la base de datos
+    db_pool: Arc<DbPool>,
+    /// Pool para los listados (réplica, si está configurada)
+    replica: ReadPool,
+    /// `mail.lowercase_local_part`: si los correos se guardan con la parte local en minúsculas
+    lowercase_email_local_part: bool,
 }
 
-impl From<User> for UserResponse {
-    fn from(user: User) -> Self {
-        Self {
-            id: user.id,
-            username: user.username,
-            email: user.email,
-            first_name: user.first_name,
-            last_name: user.last_name,
-            role: user.role,
-            is_active: user.is_active,
-            created_at: user.created_at,
-            updated_at: user.updated_at,
-        }
+impl UserService {
+    /// Crea una nueva instancia del servicio de usuarios
+    ///
+    /// # Arguments
+    ///
+    /// * `db_pool` - Pool de conexiones a la base de datos
+    ///
+    /// # Returns
+    ///
+    /// Una nueva instancia de UserService
+    pub fn new(db_pool: Arc<DbPool>) -> Self {
+        let replica = ReadPool::primary(db_pool.as_ref().clone());
+        Self { db_pool, replica, lowercase_email_local_part: true }
     }
-}
 
-pub struct UserService;
+    /// Envía los listados a la réplica de lectura
+    pub fn with_replica(mut self, replica: ReadPool) -> Self {
+        self.replica = replica;
+        self
+    }
 
-impl UserService {
-    pub async fn get_all_users(
-        pool: &PgPool,
-        pagination: PaginationOptions,
-    ) -> Result<PaginationResponse<UserResponse>, ServiceError> {
-        let offset = (pagination.page - 1) * pagination.per_page;
-        
-        let users = sqlx::query_as!(
-            User,
-            r#"
-            SELECT * FROM users
-            ORDER BY created_at DESC
-            LIMIT $1 OFFSET $2
-            "#,
-            pagination.per_page as i64,
-            offset as i64
-        )
-        .fetch_all(pool)
-        .await?;
-
-        let total_count = sqlx::query!(
-            r#"
-            SELECT COUNT(*) as count FROM users
-            "#
-        )
-        .fetch_one(pool)
-        .await?
-        .count
-        .unwrap_or(0) as usize;
-
-        let total_pages = (total_count as f64 / pagination.per_page as f64).ceil() as usize;
-        
-        Ok(PaginationResponse {
-            data: users.into_iter().map(UserResponse::from).collect(),
-            pagination: pagination.to_response(total_count, total_pages),
-        })
+    /// Normaliza los correos según `mail.lowercase_local_part` (activo por defecto)
+    pub fn with_lowercase_email_local_part(mut self, enabled: bool) -> Self {
+        self.lowercase_email_local_part = enabled;
+        self
     }
 
-    pub async fn get_user_by_id(
-        pool: &PgPool,
-        user_id: Uuid,
-    ) -> Result<UserResponse, ServiceError> {
-        let user = sqlx::query_as!(
-            User,
-            r#"
-            SELECT * FROM users WHERE id = $1
-            "#,
-            user_id
-        )
-        .fetch_optional(pool)
-        .await?
-        .ok_or(ServiceError::NotFound)?;
-
-        Ok(UserResponse::from(user))
+    /// Lista usuarios, del más reciente al más antiguo
+    ///
+    /// # Arguments
+    ///
+    /// * `search` - Coincidencia parcial en el nombre completo (opcional)
+    /// * `pagination` - Página por desplazamiento o por cursor
+    ///
+    /// # Returns
+    ///
+    /// La página de usuarios, el total de coincidencias y si hay más páginas
+    pub async fn get_all_users(&self, search: Option<&str>, pagination: UserPagination) -> UserResult<UserPage> {
+        let pool = &self.replica.reader().await;
+        let filter = || UserFilter {
+            full_name: search.map(str::to_string),
+            ..Default::default()
+        };
+
+        // Una fila de más indica si existe la página siguiente
+        let page = async {
+            match pagination {
+                UserPagination::Offset { page, per_page } => {
+                    let offset = (page.max(1) - 1) * per_page;
+                    let users = User::find_all(pool, filter(), Some(per_page as i64 + 1), Some(offset as i64)).await?;
+                    Ok::<_, DbError>((users, per_page))
+                }
+                UserPagination::Cursor { after, per_page } => {
+                    let users = User::find_all_cursor(pool, &filter(), Some(after), per_page as i64 + 1).await?;
+                    Ok((users, per_page))
+                }
+            }
+        };
+        // El total se cuenta en paralelo con la página
+        let ((mut users, per_page), total) = futures::try_join!(page, User::count(pool, filter()))?;
+
+        let has_more = users.len() > per_page;
+        users.truncate(per_page);
+
+        Ok(UserPage { users, total, has_more })
     }
 
-    pub async fn get_user_by_username(
-        pool: &PgPool,
-        username: &str,
-    ) -> Result<UserResponse, ServiceError> {
-        let user = sqlx::query_as!(
-            User,
-            r#"
-            SELECT * FROM users WHERE username = $1
-            "#,
-            username
-        )
-        .fetch_optional(pool)
-        .await?
-        .ok_or(ServiceError::NotFound)?;
-
-        Ok(UserResponse::from(user))
+    /// Obtiene un usuario por su ID
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - UUID del usuario
+    ///
+    /// # Returns
+    ///
+    /// El usuario o `UserError::NotFound` si no existe
+    pub async fn get_user_by_id(&self, id: Uuid) -> UserResult<User> {
+        User::find_by_id(&self.db_pool, id).await?.ok_or(UserError::NotFound)
     }
 
-    pub async fn get_user_by_email(
-        pool: &PgPool,
-        email: &str,
-    ) -> Result<UserResponse, ServiceError> {
-        let user = sqlx::query_as!(
-            User,
-            r#"
-            SELECT * FROM users WHERE email = $1
-            "#,
-            email
-        )
-        .fetch_optional(pool)
-        .await?
-        .ok_or(ServiceError::NotFound)?;
-
-        Ok(UserResponse::from(user))
+    /// Crea un usuario
+    ///
+    /// # Arguments
+    ///
+    /// * `dto` - Datos del nuevo usuario
+    ///
+    /// # Returns
+    ///
+    /// El usuario creado, o un error si los datos son inválidos o el correo o
+    /// el documento ya están registrados. El correo se guarda normalizado
+    /// (`normalize_email`), así que no se distingue por mayúsculas, y el
+    /// documento solo con sus dígitos (`DocumentId`).
+    pub async fn create_user(&self, dto: CreateUserDto) -> UserResult<User> {
+        let dto = CreateUserDto {
+            email: normalize_email(&dto.email, self.lowercase_email_local_part),
+            document_id: normalize_document_id(&dto.document_id),
+            ..dto
+        };
+        validate_user_fields(Some(&dto.document_id), Some(&dto.full_name), Some(&dto.email), dto.phone.as_deref())?;
+        self.check_unique(None, Some(&dto.email), Some(&dto.document_id)).await?;
+
+        User::create(self.db_pool.as_ref(), dto).await.map_err(user_error)
     }
 
-    pub async fn create_user(
-        pool: &PgPool,
-        user_data: CreateUserRequest,
-    ) -> Result<UserResponse, CreateUserError> {
-        // Validate username and email uniqueness
-        let existing_username = sqlx::query!(
-            r#"
-            SELECT id FROM users WHERE username = $1
-            "#,
-            user_data.username
-        )
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| CreateUserError::InternalError(e.to_string()))?;
-
-        if existing_username.is_some() {
-            return Err(CreateUserError::UsernameAlreadyExists);
-        }
+    /// Actualiza un usuario; los campos ausentes conservan su valor
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - UUID del usuario
+    /// * `dto` - Campos a modificar
+    ///
+    /// # Returns
+    ///
+    /// El usuario actualizado
+    pub async fn update_user(&self, id: Uuid, mut dto: UpdateUserDto) -> UserResult<User> {
+        dto.email = dto.email.as_deref().map(|email| normalize_email(email, self.lowercase_email_local_part));
+        dto.document_id = dto.document_id.as_deref().map(normalize_document_id);
+        validate_user_fields(
+            dto.document_id.as_deref(),
+            dto.full_name.as_deref(),
+            dto.email.as_deref(),
+            dto.phone.as_deref(),
+        )?;
+        self.check_unique(Some(id), dto.email.as_deref(), dto.document_id.as_deref()).await?;
+
+        User::update(&self.db_pool, id, dto).await.map_err(user_error)
+    }
 
-        let existing_email = sqlx::query!(
-            r#"
-            SELECT id FROM users WHERE email = $1
-            "#,
-            user_data.email
-        )
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| CreateUserError::InternalError(e.to_string()))?;
-
-        if existing_email.is_some() {
-            return Err(CreateUserError::EmailAlreadyExists);
-        }
+    /// Aplica una actualización parcial (merge-patch) a un usuario
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - UUID del usuario
+    /// * `patch` - Campos a modificar; `null` limpia los opcionales
+    ///
+    /// # Returns
+    ///
+    /// El usuario actualizado
+    pub async fn patch_user(&self, id: Uuid, mut patch: PatchUserDto) -> UserResult<User> {
+        patch.email = patch.email.as_deref().map(|email| normalize_email(email, self.lowercase_email_local_part));
+        patch.document_id = patch.document_id.as_deref().map(normalize_document_id);
+        validate_user_fields(
+            patch.document_id.as_deref(),
+            patch.full_name.as_deref(),
+            patch.email.as_deref(),
+            None,
+        )?;
+        self.check_unique(Some(id), patch.email.as_deref(), patch.document_id.as_deref()).await?;
+
+        User::patch(&self.db_pool, id, patch).await.map_err(user_error)
+    }
 
-        // Hash the password - in a real implementation, you would use argon2 or bcrypt
-        // For this example, I'll use a simple placeholder
-        let password_hash = format!("hashed_{}", user_data.password);
-
-        let now = Utc::now();
-        let user_id = Uuid::new_v4();
-
-        let user = sqlx::query_as!(
-            User,
-            r#"
-            INSERT INTO users (
-                id, username, email, password_hash, 
-                first_name, last_name, role, is_active, 
-                created_at, updated_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
-            RETURNING *
-            "#,
-            user_id,
-            user_data.username,
-            user_data.email,
-            password_hash,
-            user_data.first_name,
-            user_data.last_name,
-            user_data.role as Role,
-            true,
-            now,
-            now
-        )
-        .fetch_one(pool)
-        .await
-        .map_err(|e| CreateUserError::InternalError(e.to_string()))?;
-
-        Ok(UserResponse::from(user))
+    /// Elimina un usuario
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - UUID del usuario
+    ///
+    /// # Returns
+    ///
+    /// `()` si se eliminó, `UserError::NotFound` si no existía
+    pub async fn delete_user(&self, id: Uuid) -> UserResult<()> {
+        User::delete(&self.db_pool, id).await.map_err(user_error)?;
+        Ok(())
     }
 
-    pub async fn update_user(
-        pool: &PgPool,
-        user_id: Uuid,
-        update_data: UpdateUserRequest,
-    ) -> Result<UserResponse, UpdateUserError> {
-        // Check if the user exists
-        let existing_user = sqlx::query_as!(
-            User,
-            r#"
-            SELECT * FROM users WHERE id = $1
-            "#,
-            user_id
-        )
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| UpdateUserError::InternalError(e.to_string()))?
-        .ok_or(UpdateUserError::NotFound)?;
-
-        // Check username uniqueness if it's being updated
-        if let Some(username) = &update_data.username {
-            if username != &existing_user.username {
-                let existing_username = sqlx::query!(
-                    r#"
-                    SELECT id FROM users WHERE username = $1 AND id != $2
-                    "#,
-                    username,
-                    user_id
-                )
-                .fetch_optional(pool)
-                .await
-                .map_err(|e| UpdateUserError::InternalError(e.to_string()))?;
-
-                if existing_username.is_some() {
-                    return Err(UpdateUserError::UsernameAlreadyExists);
-                }
+    /// Verifica que el correo y el documento no pertenezcan a otro usuario
+    async fn check_unique(&self, id: Option<Uuid>, email: Option<&str>, document_id: Option<&str>) -> UserResult<()> {
+        let pool = self.db_pool.as_ref();
+        let taken_by_other = |user: Option<User>| user.is_some_and(|user| Some(user.id) != id);
+
+        if let Some(email) = email {
+            if taken_by_other(User::find_by_email(pool, email).await?) {
+                return Err(UserError::EmailAlreadyExists);
             }
         }
-
-        // Check email uniqueness if it's being updated
-        if let Some(email) = &update_data.email {
-            if email != &existing_user.email {
-                let existing_email = sqlx::query!(
-                    r#"
-                    SELECT id FROM users WHERE email = $1 AND id != $2
-                    "#,
-                    email,
-                    user_id
-                )
-                .fetch_optional(pool)
-                .await
-                .map_err(|e| UpdateUserError::InternalError(e.to_string()))?;
-
-                if existing_email.is_some() {
-                    return Err(UpdateUserError::EmailAlreadyExists);
-                }
+        if let Some(document_id) = document_id {
+            if taken_by_other(User::find_by_document_id(pool, document_id).await?) {
+                return Err(UserError::DocumentIdAlreadyExists);
             }
         }
-
-        // Process password if it's being updated
-        let password_hash = if let Some(password) = &update_data.password {
-            // In a real app, hash the password with a proper algorithm
-            Some(format!("hashed_{}", password))
-        } else {
-            None
-        };
-
-        // Update the user record
-        let now = Utc::now();
-        let updated_user = sqlx::query_as!(
-            User,
-            r#"
-            UPDATE users SET
-                username = COALESCE($1, username),
-                email = COALESCE($2, email),
-                password_hash = COALESCE($3, password_hash),
-                first_name = COALESCE($4, first_name),
-                last_name = COALESCE($5, last_name),
-                role = COALESCE($6, role),
-                is_active = COALESCE($7, is_active),
-                updated_at = $8
-            WHERE id = $9
-            RETURNING *
-            "#,
-            update_data.username,
-            update_data.email,
-            password_hash,
-            update_data.first_name,
-            update_data.last_name,
-            update_data.role as Option<Role>,
-            update_data.is_active,
-            now,
-            user_id
-        )
-        .fetch_one(pool)
-        .await
-        .map_err(|e| UpdateUserError::InternalError(e.to_string()))?;
-
-        Ok(UserResponse::from(updated_user))
+        Ok(())
     }
+}
 
-    pub async fn delete_user(
-        pool: &PgPool,
-        user_id: Uuid,
-    ) -> Result<(), ServiceError> {
-        let result = sqlx::query!(
-            r#"
-            DELETE FROM users WHERE id = $1
-            "#,
-            user_id
-        )
-        .execute(pool)
-        .await?;
-
-        if result.rows_affected() == 0 {
-            return Err(ServiceError::NotFound);
+/// Traduce los errores de la base de datos que las rutas distinguen
+///
+/// Cubre la carrera entre `check_unique` y la escritura: la restricción de
+/// unicidad decide cuál de los dos pedidos gana.
+fn user_error(error: DbError) -> UserError {
+    match error {
+        DbError::NotFound(_) => UserError::NotFound,
+        DbError::Conflict(message) if message.contains("users_email_unique") => UserError::EmailAlreadyExists,
+        DbError::Conflict(message) if message.contains("users_document_id_unique") => {
+            UserError::DocumentIdAlreadyExists
         }
+        error => UserError::DatabaseError(error),
+    }
+}
+
+/// Deja solo los dígitos de un documento válido; uno inválido queda igual para que
+/// `validate_user_fields` lo rechace
+fn normalize_document_id(document_id: &str) -> String {
+    DocumentId::parse(document_id).map_or_else(|_| document_id.to_string(), DocumentId::into_string)
+}
+
+/// Valida los campos presentes de un usuario y reúne todos los errores encontrados
+fn validate_user_fields(
+    document_id: Option<&str>,
+    full_name: Option<&str>,
+    email: Option<&str>,
+    phone: Option<&str>,
+) -> UserResult<()> {
+    let mut errors = Vec::new();
+
+    if let Some(Err(e)) = document_id.map(DocumentId::parse) {
+        errors.push(format!("document_id: {}", e));
+    }
+    if full_name.is_some_and(|full_name| full_name.trim().is_empty()) {
+        errors.push("full_name: es obligatorio".to_string());
+    }
+    if email.is_some_and(|email| !validate_email(email)) {
+        errors.push("email: no es una dirección de correo válida".to_string());
+    }
+    if phone.is_some_and(|phone| !validate_phone_number(phone)) {
+        errors.push("phone: no es un número de teléfono válido".to_string());
+    }
 
+    if errors.is_empty() {
         Ok(())
+    } else {
+        Err(UserError::ValidationError(errors.join("; ")))
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_fields() {
+        assert!(validate_user_fields(Some("1234567"), Some("Ana Benítez"), Some("ana@example.com"), None).is_ok());
+        // Los campos ausentes no se validan
+        assert!(validate_user_fields(None, None, None, None).is_ok());
+    }
+
+    #[test]
+    fn test_every_invalid_field_reported() {
+        let error = validate_user_fields(Some(""), Some("  "), Some("ana"), Some("123")).unwrap_err();
+
+        match error {
+            UserError::ValidationError(message) => {
+                assert!(message.contains("document_id"));
+                assert!(message.contains("full_name"));
+                assert!(message.contains("email"));
+                assert!(message.contains("phone"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_document_ids_are_rejected() {
+        for document_id in ["12345", "123456789", "ADMIN-1a2b3c4d", "1234567-3"] {
+            let error = validate_user_fields(Some(document_id), None, None, None).unwrap_err();
+            assert!(
+                matches!(&error, UserError::ValidationError(message) if message.starts_with("document_id: ")),
+                "{}: {:?}",
+                document_id,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn test_document_ids_are_stored_as_digits() {
+        assert_eq!(normalize_document_id(" 1.234.567 "), "1234567");
+        assert_eq!(normalize_document_id("12.345.678-9"), "12345678");
+        assert_eq!(normalize_document_id("123.456"), "123456");
+        // Los inválidos llegan intactos a la validación
+        assert_eq!(normalize_document_id("1.234"), "1.234");
+    }
+
+    #[test]
+    fn test_user_errors_by_constraint() {
+        let conflict = |constraint: &str| {
+            DbError::Conflict(format!("duplicate key value violates unique constraint \"{}\"", constraint))
+        };
+
+        assert!(matches!(user_error(conflict("users_email_unique")), UserError::EmailAlreadyExists));
+        assert!(matches!(
+            user_error(conflict("users_document_id_unique")),
+            UserError::DocumentIdAlreadyExists
+        ));
+        // Otras restricciones, como una clave foránea al eliminar, quedan como conflicto
+        assert!(matches!(
+            user_error(conflict("students_user_id_fkey")),
+            UserError::DatabaseError(DbError::Conflict(_))
+        ));
+        assert!(matches!(
+            user_error(DbError::NotFound("Usuario no encontrado".to_string())),
+            UserError::NotFound
+        ));
+    }
+}
@@ -0,0 +1,244 @@
+//! Startup self-check for insecure defaults.
+//!
+//! [`self_check`] runs in `sai serve` once the configuration is loaded and the
+//! database is connected. Every finding is logged; in production an
+//! [`Severity::Error`] stops the server before it binds. Admins see the
+//! current findings in `GET /system/status`.
+
+use log::{error, warn};
+use serde::Serialize;
+
+use crate::config::{AppConfig, Environment};
+use crate::db::{self, seed, DbPool};
+use crate::utils::password;
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Logged and reported
+    Warn,
+    /// Also refuses to serve in production
+    Error,
+}
+
+/// Something in the configuration or the database that leaves the install open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Stable identifier, e.g. `default_jwt_secret`
+    pub code: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Every finding of [`self_check`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SelfCheck {
+    pub findings: Vec<Finding>,
+}
+
+impl SelfCheck {
+    /// Whether some finding has `code`
+    pub fn has(&self, code: &str) -> bool {
+        self.findings.iter().any(|finding| finding.code == code)
+    }
+
+    /// Findings with [`Severity::Error`]
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|finding| finding.severity == Severity::Error)
+    }
+
+    /// Whether the server must not start: an error in the production environment
+    pub fn blocks_serving(&self, environment: Environment) -> bool {
+        environment.is_production() && self.errors().next().is_some()
+    }
+
+    /// Logs every finding with its severity
+    pub fn log(&self) {
+        for finding in &self.findings {
+            match finding.severity {
+                Severity::Warn => warn!("Verificación de seguridad ({}): {}", finding.code, finding.message),
+                Severity::Error => error!("Verificación de seguridad ({}): {}", finding.code, finding.message),
+            }
+        }
+    }
+}
+
+/// Checks the configuration and the database for insecure defaults
+///
+/// Findings: example or development JWT secret, demo login enabled, wildcard
+/// CORS origin (the session travels in the `auth_token` cookie), pending
+/// migrations, and the seeded admin still using the demo password. The JWT
+/// secret, demo login and admin password are errors in production and
+/// warnings elsewhere; the other findings are always warnings.
+pub async fn self_check(config: &AppConfig, pool: &DbPool) -> SelfCheck {
+    let mut findings = check_config(config);
+    findings.extend(check_database(config, pool).await);
+    SelfCheck { findings }
+}
+
+/// Findings that only depend on the configuration
+pub fn check_config(config: &AppConfig) -> Vec<Finding> {
+    let severity = production_error(config.environment);
+    let mut findings = Vec::new();
+
+    if config.jwt.has_default_secret() {
+        findings.push(Finding {
+            code: "default_jwt_secret",
+            severity,
+            message: "jwt.secret es la clave de desarrollo o la de un ejemplo; cualquiera puede firmar tokens válidos"
+                .to_string(),
+        });
+    }
+    if config.jwt.demo_login == Some(true) {
+        findings.push(Finding {
+            code: "demo_login_enabled",
+            severity,
+            message: "jwt.demo_login acepta admin / password como administrador".to_string(),
+        });
+    }
+    if config.server.cors_allowed_origins.iter().any(|origin| origin == "*") {
+        findings.push(Finding {
+            code: "wildcard_cors",
+            severity: Severity::Warn,
+            message: "server.cors_allowed_origins admite cualquier origen (*) y la sesión viaja en la cookie auth_token"
+                .to_string(),
+        });
+    }
+    findings
+}
+
+async fn check_database(config: &AppConfig, pool: &DbPool) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    match db::migration_status_for(pool, db::MIGRATIONS).await {
+        Ok(status) if !status.is_up_to_date() => findings.push(Finding {
+            code: "pending_migrations",
+            severity: Severity::Warn,
+            message: format!("{} migración(es) pendiente(s): {}", status.pending.len(), status.pending.join(", ")),
+        }),
+        Ok(_) => {}
+        Err(e) => findings.push(Finding {
+            code: "migration_status_unavailable",
+            severity: Severity::Warn,
+            message: format!("no se pudo leer el estado de las migraciones: {}", e),
+        }),
+    }
+
+    let hash: Result<Option<String>, _> = sqlx::query_scalar(
+        "SELECT a.password_hash FROM authentications a JOIN users u ON u.id = a.user_id WHERE u.email = $1",
+    )
+    .bind(seed::ADMIN_EMAIL)
+    .fetch_optional(pool)
+    .await;
+    match hash {
+        Ok(Some(hash)) if password::verify_password(seed::DEMO_PASSWORD, &hash) => findings.push(Finding {
+            code: "default_admin_password",
+            severity: production_error(config.environment),
+            message: format!("{} conserva la contraseña de los datos de demostración", seed::ADMIN_EMAIL),
+        }),
+        Ok(_) => {}
+        Err(e) => warn!("No se pudo revisar la contraseña del administrador de demostración: {}", e),
+    }
+    findings
+}
+
+/// `Error` in production, `Warn` elsewhere
+fn production_error(environment: Environment) -> Severity {
+    if environment.is_production() {
+        Severity::Error
+    } else {
+        Severity::Warn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secure(environment: Environment) -> AppConfig {
+        let mut config = AppConfig { environment, ..Default::default() };
+        config.jwt.secret = Some("0123456789abcdef0123456789abcdef".to_string());
+        config.jwt.demo_login = Some(false);
+        config
+    }
+
+    fn codes(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|finding| finding.code).collect()
+    }
+
+    #[test]
+    fn test_secure_config_has_no_findings() {
+        for environment in Environment::ALL {
+            assert!(check_config(&secure(environment)).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_default_jwt_secret() {
+        for secret in [None, Some("your-secret-key")] {
+            let mut config = secure(Environment::Production);
+            config.jwt.secret = secret.map(str::to_string);
+
+            let findings = check_config(&config);
+
+            assert_eq!(codes(&findings), vec!["default_jwt_secret"]);
+            assert_eq!(findings[0].severity, Severity::Error);
+        }
+
+        let mut config = secure(Environment::Development);
+        config.jwt.secret = None;
+        assert_eq!(check_config(&config)[0].severity, Severity::Warn);
+    }
+
+    #[test]
+    fn test_demo_login_enabled() {
+        let mut config = secure(Environment::Staging);
+        config.jwt.demo_login = Some(true);
+
+        let findings = check_config(&config);
+
+        assert_eq!(codes(&findings), vec!["demo_login_enabled"]);
+        assert_eq!(findings[0].severity, Severity::Warn);
+
+        config.environment = Environment::Production;
+        assert_eq!(check_config(&config)[0].severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_wildcard_cors() {
+        let mut config = secure(Environment::Production);
+        config.server.cors_allowed_origins = vec!["https://sai.edu.py".to_string()];
+        assert!(check_config(&config).is_empty());
+
+        config.server.cors_allowed_origins.push("*".to_string());
+        let findings = check_config(&config);
+
+        assert_eq!(codes(&findings), vec!["wildcard_cors"]);
+        assert_eq!(findings[0].severity, Severity::Warn);
+    }
+
+    #[test]
+    fn test_errors_block_serving_only_in_production() {
+        let warning = Finding { code: "wildcard_cors", severity: Severity::Warn, message: String::new() };
+        let error = Finding { code: "default_jwt_secret", severity: Severity::Error, message: String::new() };
+
+        let check = SelfCheck { findings: vec![warning.clone()] };
+        assert!(!check.blocks_serving(Environment::Production));
+
+        let check = SelfCheck { findings: vec![warning, error] };
+        assert!(check.blocks_serving(Environment::Production));
+        assert!(!check.blocks_serving(Environment::Staging));
+        assert!(check.has("default_jwt_secret"));
+    }
+
+    #[test]
+    fn test_findings_serialized_for_status() {
+        let finding = Finding { code: "pending_migrations", severity: Severity::Warn, message: "1".to_string() };
+
+        assert_eq!(
+            serde_json::to_value(SelfCheck { findings: vec![finding] }).unwrap(),
+            serde_json::json!({ "findings": [{ "code": "pending_migrations", "severity": "warn", "message": "1" }] })
+        );
+    }
+}
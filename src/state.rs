@@ -0,0 +1,206 @@
+//! Shared application state.
+//!
+//! `AppState` is registered once as `web::Data<AppState>` and gives handlers
+//! the primary pool and its manager, every service, the token signer and the
+//! runtime configuration. Requests of an institution get the state of its
+//! tenant instead (see [`AppState::for_tenant`]).
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use log::{error, info, warn};
+use serde_json::json;
+
+use crate::config::{AppConfig, LiveConfig, ReloadError, ReloadReport};
+use crate::db::tenant::{self, TenantError, TenantId};
+use crate::db::{DbManager, DbPool, DbPools};
+use crate::logging::{self, InvalidSpec, LogLevelStatus};
+use crate::models::audit_log::{AuditLogEntry, NewAuditLogEntry};
+use crate::routes::Auth;
+use crate::services::Services;
+
+/// State shared by all workers; cloning only clones the handles
+#[derive(Clone)]
+pub struct AppState {
+    /// Primary database pool
+    pub db_pool: DbPool,
+    /// Manager of the primary and replica pools, behind the health endpoints
+    pub db: Arc<DbManager>,
+    /// Every service, already wired to the primary and replica pools
+    pub services: Arc<Services>,
+    /// Issues and validates tokens with the `[jwt]` keys
+    pub auth: Arc<Auth>,
+    /// Configuration in effect; `[mail]`, `[features]` and `[logging]` change on reload
+    pub config: Arc<LiveConfig>,
+    /// State of each tenant served so far, shared by every clone
+    tenants: Arc<Mutex<HashMap<TenantId, AppState>>>,
+}
+
+impl AppState {
+    /// Builds the services on `pools`, sending read-only paths to the replica
+    ///
+    /// # Panics
+    ///
+    /// If the JWT keys cannot be loaded; `AppConfig::validate` reports that first.
+    pub fn new(pools: &DbPools, config: AppConfig) -> Self {
+        Self::with_manager(Arc::new(DbManager::from_pools(pools)), config)
+    }
+
+    /// Like [`AppState::new`], keeping the manager that connected the pools at startup
+    ///
+    /// # Panics
+    ///
+    /// If the JWT keys cannot be loaded, like [`AppState::new`].
+    pub fn with_manager(db: Arc<DbManager>, config: AppConfig) -> Self {
+        let pools = db.pools();
+        Self {
+            db_pool: pools.primary().clone(),
+            db,
+            services: Arc::new(Services::with_pools(&pools, &config)),
+            auth: auth(&config),
+            config: Arc::new(LiveConfig::new(config)),
+            tenants: Arc::default(),
+        }
+    }
+
+    /// State with a single pool and no replica
+    ///
+    /// # Panics
+    ///
+    /// If the JWT keys cannot be loaded, like [`AppState::new`].
+    pub fn with_pool(pool: DbPool, config: AppConfig) -> Self {
+        Self {
+            services: Arc::new(Services::new(Arc::new(pool.clone()), &config)),
+            auth: auth(&config),
+            db: Arc::new(DbManager::from_pools(&DbPools::primary_only(pool.clone()))),
+            db_pool: pool,
+            config: Arc::new(LiveConfig::new(config)),
+            tenants: Arc::default(),
+        }
+    }
+
+    /// State of `tenant`: its own pool, whose connections only see the tenant
+    /// schema, and every service built on it
+    ///
+    /// The token signer, the feature flags and the configuration stay shared
+    /// with this state, so revocations and flag changes apply to every tenant.
+    /// Tenants have no replica: their reads go to the primary. Built on first
+    /// use and kept for the life of the process.
+    pub async fn for_tenant(&self, tenant: &TenantId) -> Result<AppState, TenantError> {
+        if let Some(state) = self.tenants().get(tenant) {
+            return Ok(state.clone());
+        }
+
+        let pools = DbPools::primary_only(tenant::connect(&self.db_pool, tenant).await?);
+        let mut services = Services::with_pools(&pools, &self.config.current());
+        services.features = self.services.features.clone();
+        let state = AppState {
+            db_pool: pools.primary().clone(),
+            db: Arc::new(DbManager::from_pools(&pools)),
+            services: Arc::new(services),
+            auth: self.auth.clone(),
+            config: self.config.clone(),
+            tenants: self.tenants.clone(),
+        };
+
+        // A concurrent request may have built it first; keep that one
+        Ok(self.tenants().entry(tenant.clone()).or_insert(state).clone())
+    }
+
+    fn tenants(&self) -> MutexGuard<'_, HashMap<TenantId, AppState>> {
+        self.tenants.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Reloads the configuration from `file` (the `--config` option) instead
+    /// of `SAI_CONFIG` or `sai.toml`
+    pub fn with_config_file(mut self, file: Option<PathBuf>) -> Self {
+        self.config = Arc::new(LiveConfig::new(AppConfig::clone(&self.config.current())).with_file(file));
+        self
+    }
+
+    /// Re-reads the configuration, hands the reloadable sections to the
+    /// services and records the outcome as `config.reload` in the audit log
+    ///
+    /// Used by `SIGHUP` and `POST /api/admin/config/reload`; `audit` carries
+    /// the actor of the request, if any.
+    pub async fn reload_config(&self, audit: NewAuditLogEntry) -> Result<ReloadReport, ReloadError> {
+        let result = self.config.reload();
+        let outcome = match &result {
+            Ok(report) => {
+                self.refresh_services();
+                for key in &report.ignored {
+                    warn!("{} cambió, pero solo se aplica al reiniciar el servidor", key);
+                }
+                info!("Configuración recargada; cambios aplicados: {:?}", report.applied);
+                json!(report)
+            }
+            Err(e) => {
+                error!("No se recargó la configuración; se mantiene la anterior: {}", e);
+                json!({ "error": e.to_string() })
+            }
+        };
+
+        let entry = NewAuditLogEntry {
+            action: "config.reload".to_string(),
+            entity_type: "config".to_string(),
+            new_value: Some(outcome),
+            ..audit
+        };
+        if let Err(e) = AuditLogEntry::record(&self.db_pool, &entry).await {
+            error!("No se pudo registrar la recarga de la configuración en la auditoría: {}", e);
+        }
+        result
+    }
+
+    /// Logs with `level` for `duration` (`logging.override_ttl_secs` if `None`),
+    /// or goes back to `logging.level` when `level` is `None`, and records the
+    /// change as `logging.override` in the audit log
+    pub async fn set_log_level(
+        &self,
+        level: Option<&str>,
+        duration: Option<Duration>,
+        audit: NewAuditLogEntry,
+    ) -> Result<LogLevelStatus, InvalidSpec> {
+        let outcome = match level {
+            Some(spec) => {
+                let ttl = duration.unwrap_or(Duration::from_secs(self.config.current().logging.override_ttl_secs));
+                json!(logging::set_override(spec, ttl)?)
+            }
+            None => {
+                logging::clear_override();
+                json!(null)
+            }
+        };
+
+        let entry = NewAuditLogEntry {
+            action: "logging.override".to_string(),
+            entity_type: "logging".to_string(),
+            new_value: Some(outcome),
+            ..audit
+        };
+        if let Err(e) = AuditLogEntry::record(&self.db_pool, &entry).await {
+            error!("No se pudo registrar el cambio de nivel de log en la auditoría: {}", e);
+        }
+        Ok(logging::status())
+    }
+
+    /// Passes the reloadable sections of the current configuration to the services
+    pub fn refresh_services(&self) {
+        let config = self.config.current();
+        self.services.notifications.set_mail_config(config.mail.clone());
+        for state in self.tenants().values() {
+            state.services.notifications.set_mail_config(config.mail.clone());
+        }
+        self.services.features.set_defaults(&config.features);
+        crate::logging::apply(&config.logging);
+    }
+}
+
+fn auth(config: &AppConfig) -> Arc<Auth> {
+    match Auth::new(&config.jwt) {
+        Ok(auth) => Arc::new(auth),
+        Err(e) => panic!("No se pudieron cargar las claves JWT: {}", e),
+    }
+}
@@ -0,0 +1,173 @@
+//! Harness for database-backed tests.
+//!
+//! Each [`TestDb`] gets its own migrated schema in the database behind
+//! `TEST_DATABASE_URL` and a pool whose `search_path` points at it, so tests
+//! can run in parallel without seeing each other's rows. Available to unit
+//! tests and, with the `testing` feature, to the integration tests in `tests/`.
+//!
+//! ```ignore
+//! let db = TestDb::new().await;
+//! let user = fixtures::user().create(&db.pool).await;
+//! // ...
+//! db.teardown().await;
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{info, warn};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+use sqlx::Error as SqlxError;
+use uuid::Uuid;
+
+use crate::db::{self, DbPool, Migration};
+
+pub mod fixtures;
+
+/// Prefix of every schema created by the harness
+pub const SCHEMA_PREFIX: &str = "sai_test_";
+
+/// Schemas older than this are assumed to belong to a run that died before teardown
+pub const ORPHAN_MAX_AGE: Duration = Duration::from_secs(60 * 60);
+
+static ORPHANS_SWEPT: AtomicBool = AtomicBool::new(false);
+
+/// Migrated schema private to one test
+pub struct TestDb {
+    /// Pool whose connections use the test schema first in `search_path`
+    pub pool: DbPool,
+    admin: DbPool,
+    schema: String,
+}
+
+impl TestDb {
+    /// Create and migrate a fresh schema.
+    ///
+    /// The first call in a process also drops schemas left behind by earlier runs
+    /// (see [`cleanup_orphaned_schemas`]). Panics when the database is unreachable,
+    /// like any other failed test setup.
+    pub async fn new() -> Self {
+        Self::with_migrations(db::MIGRATIONS).await
+    }
+
+    /// Like [`TestDb::new`], applying only `migrations` (in order)
+    pub async fn with_migrations(migrations: &[Migration]) -> Self {
+        Self::try_new(migrations).await.expect("Failed to set up the test database")
+    }
+
+    /// Same as [`TestDb::with_migrations`], returning the error instead of panicking
+    pub async fn try_new(migrations: &[Migration]) -> Result<Self, SqlxError> {
+        dotenv::dotenv().ok();
+        let url = std::env::var("TEST_DATABASE_URL")
+            .map_err(|_| SqlxError::Configuration("TEST_DATABASE_URL must be set for database tests".into()))?;
+        let options: PgConnectOptions = url.parse()?;
+
+        let admin = PgPoolOptions::new().max_connections(2).connect_with(options.clone()).await?;
+        if !ORPHANS_SWEPT.swap(true, Ordering::SeqCst) {
+            cleanup_orphaned_schemas(&admin, ORPHAN_MAX_AGE).await?;
+        }
+        db::create_extensions(&admin).await?;
+
+        let schema = schema_name(SystemTime::now());
+        sqlx::query(&format!("CREATE SCHEMA {}", schema)).execute(&admin).await?;
+
+        let pool = PgPoolOptions::new()
+            .max_connections(5)
+            .connect_with(options.options([("search_path", format!("{},public", schema))]))
+            .await?;
+        let test_db = Self { pool, admin, schema };
+
+        if let Err(e) = db::apply_migrations(&test_db.pool, migrations, false).await {
+            test_db.teardown().await;
+            return Err(e);
+        }
+        Ok(test_db)
+    }
+
+    /// Name of the schema backing this database
+    pub fn schema(&self) -> &str {
+        &self.schema
+    }
+
+    /// Close the pool and drop the schema with everything in it.
+    ///
+    /// A test that panics before calling this leaves its schema behind; the
+    /// next run drops it once it is older than [`ORPHAN_MAX_AGE`].
+    pub async fn teardown(self) {
+        self.pool.close().await;
+        if let Err(e) = drop_schema(&self.admin, &self.schema).await {
+            warn!("Failed to drop test schema {}: {}", self.schema, e);
+        }
+        self.admin.close().await;
+    }
+}
+
+/// `sai_test_<unix seconds>_<random>`; the timestamp lets later runs spot orphans
+fn schema_name(now: SystemTime) -> String {
+    let created = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let suffix = Uuid::new_v4().simple().to_string();
+    format!("{}{}_{}", SCHEMA_PREFIX, created, &suffix[..12])
+}
+
+/// Creation time encoded in a harness schema name
+fn schema_created_at(schema: &str) -> Option<SystemTime> {
+    let (created, _) = schema.strip_prefix(SCHEMA_PREFIX)?.split_once('_')?;
+    Some(UNIX_EPOCH + Duration::from_secs(created.parse().ok()?))
+}
+
+async fn drop_schema(admin: &DbPool, schema: &str) -> Result<(), SqlxError> {
+    sqlx::query(&format!("DROP SCHEMA IF EXISTS {} CASCADE", schema))
+        .execute(admin)
+        .await?;
+    Ok(())
+}
+
+/// Drop harness schemas created more than `max_age` ago.
+///
+/// Meant for CI, where a killed job never reaches teardown; schemas of runs
+/// still in progress are younger and left alone.
+///
+/// # Returns
+///
+/// The names of the dropped schemas
+pub async fn cleanup_orphaned_schemas(admin: &DbPool, max_age: Duration) -> Result<Vec<String>, SqlxError> {
+    let schemas: Vec<String> = sqlx::query_scalar("SELECT nspname::TEXT FROM pg_namespace WHERE starts_with(nspname, $1)")
+        .bind(SCHEMA_PREFIX)
+        .fetch_all(admin)
+        .await?;
+
+    let cutoff = SystemTime::now() - max_age;
+    let mut dropped = Vec::new();
+    for schema in schemas {
+        if schema_created_at(&schema).is_some_and(|created| created < cutoff) {
+            drop_schema(admin, &schema).await?;
+            dropped.push(schema);
+        }
+    }
+
+    if !dropped.is_empty() {
+        info!("Dropped {} orphaned test schema(s)", dropped.len());
+    }
+    Ok(dropped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_name_round_trip() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_744_000_000);
+        let name = schema_name(now);
+
+        assert!(name.starts_with("sai_test_1744000000_"));
+        assert_eq!(schema_created_at(&name), Some(now));
+        assert_ne!(name, schema_name(now));
+    }
+
+    #[test]
+    fn test_foreign_schemas_are_not_orphans() {
+        assert_eq!(schema_created_at("public"), None);
+        assert_eq!(schema_created_at("sai_test_notanumber_abc"), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! Builders for test rows with unique, valid defaults.
+//!
+//! Each builder fills every required field; tests override only what they
+//! assert on. `create` panics on failure, like any other failed test setup.
+
+use chrono::{Datelike, NaiveDate, Utc};
+use uuid::Uuid;
+
+use crate::db::DbPool;
+use crate::models::{
+    course::CreateCourseDto,
+    student::CreateStudentWithUserDto,
+    teacher::CreateTeacherWithUserDto,
+    user::CreateUserDto,
+    Course, GuardianInfo, Role, Student, StudentStatus, Teacher, TeacherStatus, User,
+};
+
+/// Short random tag that keeps unique columns unique across fixtures
+fn unique_tag() -> String {
+    Uuid::new_v4().simple().to_string()[..10].to_string()
+}
+
+/// Random 8-digit cédula, unlikely to repeat across fixtures
+fn unique_document_id() -> String {
+    (10_000_000 + Uuid::new_v4().as_u128() % 90_000_000).to_string()
+}
+
+/// A user with a unique document number and email
+pub fn user() -> UserFixture {
+    let tag = unique_tag();
+    UserFixture {
+        dto: CreateUserDto {
+            document_id: unique_document_id(),
+            full_name: "Usuario de Prueba".to_string(),
+            email: format!("usuario-{}@example.com", tag),
+            phone: None,
+            address: None,
+            birth_date: NaiveDate::from_ymd_opt(1990, 1, 15).unwrap(),
+            role: Role::Teacher,
+        },
+    }
+}
+
+/// A student in 1st grade, section A, of the current academic year
+pub fn student_with_user() -> StudentFixture {
+    let tag = unique_tag();
+    StudentFixture {
+        dto: CreateStudentWithUserDto {
+            document_id: unique_document_id(),
+            full_name: "Estudiante de Prueba".to_string(),
+            email: format!("estudiante-{}@example.com", tag),
+            phone: None,
+            address: None,
+            birth_date: NaiveDate::from_ymd_opt(2012, 3, 20).unwrap(),
+            enrollment_number: format!("MAT-{}", tag),
+            current_grade: "1".to_string(),
+            section: "A".to_string(),
+            academic_year: Utc::now().year(),
+            guardian_info: None,
+            status: StudentStatus::Active,
+        },
+    }
+}
+
+/// An active mathematics teacher hired at the start of 2024
+pub fn teacher_with_user() -> TeacherFixture {
+    let tag = unique_tag();
+    TeacherFixture {
+        dto: CreateTeacherWithUserDto {
+            document_id: unique_document_id(),
+            full_name: "Docente de Prueba".to_string(),
+            email: format!("docente-{}@example.com", tag),
+            phone: None,
+            address: None,
+            birth_date: NaiveDate::from_ymd_opt(1985, 6, 1).unwrap(),
+            professional_id: format!("MEC-{}", tag),
+            specialization: "Matemática".to_string(),
+            hire_date: NaiveDate::from_ymd_opt(2024, 2, 15).unwrap(),
+            education_level: "Licenciatura".to_string(),
+            subjects: vec!["Matemática".to_string()],
+            status: TeacherStatus::Active,
+        },
+    }
+}
+
+/// A 1st grade course of the current academic year, without teacher or schedule
+pub fn course() -> CourseFixture {
+    let tag = unique_tag();
+    CourseFixture {
+        dto: CreateCourseDto {
+            code: format!("CUR-{}", tag),
+            name: format!("Curso {}", tag),
+            description: None,
+            grade_level: "1".to_string(),
+            credits: 4.0,
+            teacher_id: None,
+            academic_year: Utc::now().year(),
+            schedule: Vec::new(),
+        },
+    }
+}
+
+pub struct UserFixture {
+    dto: CreateUserDto,
+}
+
+impl UserFixture {
+    pub fn full_name(mut self, full_name: &str) -> Self {
+        self.dto.full_name = full_name.to_string();
+        self
+    }
+
+    pub fn email(mut self, email: &str) -> Self {
+        self.dto.email = email.to_string();
+        self
+    }
+
+    pub fn role(mut self, role: Role) -> Self {
+        self.dto.role = role;
+        self
+    }
+
+    /// The DTO without inserting it
+    pub fn dto(self) -> CreateUserDto {
+        self.dto
+    }
+
+    pub async fn create(self, pool: &DbPool) -> User {
+        User::create(pool, self.dto).await.expect("Failed to create user fixture")
+    }
+}
+
+pub struct StudentFixture {
+    dto: CreateStudentWithUserDto,
+}
+
+impl StudentFixture {
+    pub fn full_name(mut self, full_name: &str) -> Self {
+        self.dto.full_name = full_name.to_string();
+        self
+    }
+
+    pub fn grade(mut self, grade: &str, section: &str) -> Self {
+        self.dto.current_grade = grade.to_string();
+        self.dto.section = section.to_string();
+        self
+    }
+
+    pub fn academic_year(mut self, academic_year: i32) -> Self {
+        self.dto.academic_year = academic_year;
+        self
+    }
+
+    pub fn status(mut self, status: StudentStatus) -> Self {
+        self.dto.status = status;
+        self
+    }
+
+    /// A mother named `name` as guardian
+    pub fn guardian(mut self, name: &str) -> Self {
+        self.dto.guardian_info = Some(GuardianInfo {
+            name: name.to_string(),
+            relationship: "Madre".to_string(),
+            document_id: unique_document_id(),
+            email: None,
+            phone: "0981000000".to_string(),
+            weekly_summary: false,
+        });
+        self
+    }
+
+    /// The DTO without inserting it
+    pub fn dto(self) -> CreateStudentWithUserDto {
+        self.dto
+    }
+
+    pub async fn create(self, pool: &DbPool) -> (User, Student) {
+        Student::create_with_user(pool, self.dto)
+            .await
+            .expect("Failed to create student fixture")
+    }
+}
+
+pub struct TeacherFixture {
+    dto: CreateTeacherWithUserDto,
+}
+
+impl TeacherFixture {
+    pub fn specialization(mut self, specialization: &str) -> Self {
+        self.dto.specialization = specialization.to_string();
+        self
+    }
+
+    pub fn subjects(mut self, subjects: &[&str]) -> Self {
+        self.dto.subjects = subjects.iter().map(|subject| subject.to_string()).collect();
+        self
+    }
+
+    pub fn status(mut self, status: TeacherStatus) -> Self {
+        self.dto.status = status;
+        self
+    }
+
+    /// The DTO without inserting it
+    pub fn dto(self) -> CreateTeacherWithUserDto {
+        self.dto
+    }
+
+    pub async fn create(self, pool: &DbPool) -> (User, Teacher) {
+        Teacher::create_with_user(pool, self.dto)
+            .await
+            .expect("Failed to create teacher fixture")
+    }
+}
+
+pub struct CourseFixture {
+    dto: CreateCourseDto,
+}
+
+impl CourseFixture {
+    pub fn name(mut self, name: &str) -> Self {
+        self.dto.name = name.to_string();
+        self
+    }
+
+    /// The DTO without inserting it
+    pub fn dto(self) -> CreateCourseDto {
+        self.dto
+    }
+
+    pub async fn create(self, pool: &DbPool) -> Course {
+        Course::create(pool, self.dto).await.expect("Failed to create course fixture")
+    }
+}
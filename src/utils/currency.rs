@@ -0,0 +1,287 @@
+//! Utilidades para el manejo de montos en guaraníes
+//!
+//! El guaraní no tiene subdivisiones en uso, por lo que los montos se manejan
+//! como enteros sin decimales.
+
+pub mod tax_calculation;
+
+pub use tax_calculation::{calculate_iva, generate_iva_receipt, InvoiceItem, IvaBreakdown, IvaCalculation, IvaRate};
+
+/// Código ISO 4217 del guaraní
+pub const CURRENCY_CODE: &str = "PYG";
+
+/// Formatea un monto en guaraníes con separador de miles
+///
+/// Los montos negativos (notas de crédito, devoluciones) llevan el signo
+/// delante de las cifras.
+///
+/// # Ejemplos
+/// ```
+/// use sai::utils::currency::format_guaranies;
+///
+/// assert_eq!(format_guaranies(1_250_000), "Gs. 1.250.000");
+/// assert_eq!(format_guaranies(0), "Gs. 0");
+/// assert_eq!(format_guaranies(-15_000), "Gs. -15.000");
+/// ```
+pub fn format_guaranies(amount: i64) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    let digits = amount.unsigned_abs().to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push('.');
+        }
+        formatted.push(c);
+    }
+
+    format!("Gs. {}{}", sign, formatted)
+}
+
+const UNITS: [&str; 30] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+    "diez", "once", "doce", "trece", "catorce", "quince", "dieciséis", "diecisiete", "dieciocho", "diecinueve",
+    "veinte", "veintiuno", "veintidós", "veintitrés", "veinticuatro", "veinticinco", "veintiséis", "veintisiete",
+    "veintiocho", "veintinueve",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "", "treinta", "cuarenta", "cincuenta", "sesenta", "setenta", "ochenta", "noventa",
+];
+
+const HUNDREDS: [&str; 10] = [
+    "", "ciento", "doscientos", "trescientos", "cuatrocientos", "quinientos", "seiscientos", "setecientos",
+    "ochocientos", "novecientos",
+];
+
+/// Escribe en letras un número menor a mil
+fn hundreds_to_words(n: u64) -> String {
+    let (hundreds, rest) = (n / 100, n % 100);
+    let rest_words = match rest {
+        0 => String::new(),
+        1..=29 => UNITS[rest as usize].to_string(),
+        _ if rest % 10 == 0 => TENS[(rest / 10) as usize].to_string(),
+        _ => format!("{} y {}", TENS[(rest / 10) as usize], UNITS[(rest % 10) as usize]),
+    };
+
+    match (hundreds, rest) {
+        (0, _) => rest_words,
+        (1, 0) => "cien".to_string(),
+        (_, 0) => HUNDREDS[hundreds as usize].to_string(),
+        _ => format!("{} {}", HUNDREDS[hundreds as usize], rest_words),
+    }
+}
+
+/// Forma apocopada usada delante de "mil" y "millones" ("veintiún mil", "un millón")
+fn apocopate(words: String) -> String {
+    if words == "uno" {
+        "un".to_string()
+    } else if let Some(prefix) = words.strip_suffix("iuno") {
+        format!("{}iún", prefix)
+    } else if let Some(prefix) = words.strip_suffix("uno") {
+        format!("{}un", prefix)
+    } else {
+        words
+    }
+}
+
+/// Escribe en letras un número menor a un millón
+fn thousands_to_words(n: u64) -> String {
+    let (thousands, rest) = (n / 1000, n % 1000);
+    let thousands_words = match thousands {
+        0 => String::new(),
+        1 => "mil".to_string(),
+        _ => format!("{} mil", apocopate(hundreds_to_words(thousands))),
+    };
+
+    match (thousands, rest) {
+        (0, _) => hundreds_to_words(rest),
+        (_, 0) => thousands_words,
+        _ => format!("{} {}", thousands_words, hundreds_to_words(rest)),
+    }
+}
+
+/// Escribe en letras cualquier número, con millones, billones y trillones
+///
+/// Usa la escala larga: mil millones (10⁹) se dice así y no "un billón",
+/// que es 10¹².
+fn number_to_words(n: u64) -> String {
+    const MILLION: u64 = 1_000_000;
+    const BILLION: u64 = 1_000_000_000_000;
+    const TRILLION: u64 = 1_000_000_000_000_000_000;
+
+    let (large, rest, singular, plural) = if n >= TRILLION {
+        (n / TRILLION, n % TRILLION, "un trillón", "trillones")
+    } else if n >= BILLION {
+        (n / BILLION, n % BILLION, "un billón", "billones")
+    } else if n >= MILLION {
+        (n / MILLION, n % MILLION, "un millón", "millones")
+    } else {
+        return thousands_to_words(n);
+    };
+
+    let large_words = if large == 1 {
+        singular.to_string()
+    } else {
+        format!("{} {}", apocopate(number_to_words(large)), plural)
+    };
+
+    if rest == 0 {
+        large_words
+    } else {
+        format!("{} {}", large_words, number_to_words(rest))
+    }
+}
+
+/// Escribe un monto en guaraníes en letras, como se exige en facturas y recibos
+///
+/// Los montos negativos se escriben precedidos de "menos".
+///
+/// # Ejemplos
+/// ```
+/// use sai::utils::currency::guaranies_to_words;
+///
+/// assert_eq!(guaranies_to_words(1_250_000), "un millón doscientos cincuenta mil guaraníes");
+/// assert_eq!(guaranies_to_words(21_000), "veintiún mil guaraníes");
+/// assert_eq!(guaranies_to_words(-1), "menos un guaraní");
+/// ```
+pub fn guaranies_to_words(amount: i64) -> String {
+    if amount < 0 {
+        return format!("menos {}", unsigned_guaranies_to_words(amount.unsigned_abs()));
+    }
+    unsigned_guaranies_to_words(amount.unsigned_abs())
+}
+
+fn unsigned_guaranies_to_words(amount: u64) -> String {
+    match amount {
+        0 => "cero guaraníes".to_string(),
+        1 => "un guaraní".to_string(),
+        // "un millón de guaraníes", pero "un millón cien guaraníes"
+        _ if amount >= 1_000_000 && amount.is_multiple_of(1_000_000) => format!("{} de guaraníes", number_to_words(amount)),
+        _ => format!("{} guaraníes", apocopate(number_to_words(amount))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_guaranies() {
+        assert_eq!(format_guaranies(0), "Gs. 0");
+        assert_eq!(format_guaranies(7), "Gs. 7");
+        assert_eq!(format_guaranies(999), "Gs. 999");
+        assert_eq!(format_guaranies(1000), "Gs. 1.000");
+        assert_eq!(format_guaranies(99_999), "Gs. 99.999");
+        assert_eq!(format_guaranies(100_000), "Gs. 100.000");
+        assert_eq!(format_guaranies(1_250_000), "Gs. 1.250.000");
+        assert_eq!(format_guaranies(10_000_000), "Gs. 10.000.000");
+        assert_eq!(format_guaranies(1_000_000_000), "Gs. 1.000.000.000");
+    }
+
+    #[test]
+    fn test_format_negative_guaranies() {
+        assert_eq!(format_guaranies(-1), "Gs. -1");
+        assert_eq!(format_guaranies(-999), "Gs. -999");
+        assert_eq!(format_guaranies(-1000), "Gs. -1.000");
+        assert_eq!(format_guaranies(-1_250_000), "Gs. -1.250.000");
+    }
+
+    #[test]
+    fn test_format_extremes() {
+        assert_eq!(format_guaranies(i64::MAX), "Gs. 9.223.372.036.854.775.807");
+        assert_eq!(format_guaranies(i64::MIN), "Gs. -9.223.372.036.854.775.808");
+    }
+
+    #[test]
+    fn test_words_small_numbers() {
+        assert_eq!(guaranies_to_words(0), "cero guaraníes");
+        assert_eq!(guaranies_to_words(1), "un guaraní");
+        assert_eq!(guaranies_to_words(2), "dos guaraníes");
+        assert_eq!(guaranies_to_words(15), "quince guaraníes");
+        assert_eq!(guaranies_to_words(16), "dieciséis guaraníes");
+        assert_eq!(guaranies_to_words(20), "veinte guaraníes");
+        assert_eq!(guaranies_to_words(21), "veintiún guaraníes");
+        assert_eq!(guaranies_to_words(22), "veintidós guaraníes");
+        assert_eq!(guaranies_to_words(30), "treinta guaraníes");
+        assert_eq!(guaranies_to_words(31), "treinta y un guaraníes");
+        assert_eq!(guaranies_to_words(45), "cuarenta y cinco guaraníes");
+        assert_eq!(guaranies_to_words(99), "noventa y nueve guaraníes");
+    }
+
+    #[test]
+    fn test_words_hundreds() {
+        assert_eq!(guaranies_to_words(100), "cien guaraníes");
+        assert_eq!(guaranies_to_words(101), "ciento un guaraníes");
+        assert_eq!(guaranies_to_words(115), "ciento quince guaraníes");
+        assert_eq!(guaranies_to_words(200), "doscientos guaraníes");
+        assert_eq!(guaranies_to_words(500), "quinientos guaraníes");
+        assert_eq!(guaranies_to_words(700), "setecientos guaraníes");
+        assert_eq!(guaranies_to_words(900), "novecientos guaraníes");
+        assert_eq!(guaranies_to_words(999), "novecientos noventa y nueve guaraníes");
+    }
+
+    #[test]
+    fn test_words_thousands() {
+        assert_eq!(guaranies_to_words(1000), "mil guaraníes");
+        assert_eq!(guaranies_to_words(1001), "mil un guaraníes");
+        assert_eq!(guaranies_to_words(2000), "dos mil guaraníes");
+        assert_eq!(guaranies_to_words(21_000), "veintiún mil guaraníes");
+        assert_eq!(guaranies_to_words(31_500), "treinta y un mil quinientos guaraníes");
+        assert_eq!(guaranies_to_words(100_000), "cien mil guaraníes");
+        assert_eq!(guaranies_to_words(101_000), "ciento un mil guaraníes");
+        assert_eq!(guaranies_to_words(150_000), "ciento cincuenta mil guaraníes");
+        assert_eq!(guaranies_to_words(999_999), "novecientos noventa y nueve mil novecientos noventa y nueve guaraníes");
+    }
+
+    #[test]
+    fn test_words_millions() {
+        assert_eq!(guaranies_to_words(1_000_000), "un millón de guaraníes");
+        assert_eq!(guaranies_to_words(1_000_001), "un millón un guaraníes");
+        assert_eq!(guaranies_to_words(1_000_100), "un millón cien guaraníes");
+        assert_eq!(guaranies_to_words(1_250_000), "un millón doscientos cincuenta mil guaraníes");
+        assert_eq!(guaranies_to_words(2_000_000), "dos millones de guaraníes");
+        assert_eq!(guaranies_to_words(2_500_000), "dos millones quinientos mil guaraníes");
+        assert_eq!(guaranies_to_words(21_000_000), "veintiún millones de guaraníes");
+        assert_eq!(guaranies_to_words(100_000_000), "cien millones de guaraníes");
+        assert_eq!(guaranies_to_words(101_000_000), "ciento un millones de guaraníes");
+    }
+
+    #[test]
+    fn test_words_above_one_billion() {
+        // Escala larga: 10⁹ son mil millones
+        assert_eq!(guaranies_to_words(1_000_000_000), "mil millones de guaraníes");
+        assert_eq!(guaranies_to_words(1_001_000_000), "mil un millones de guaraníes");
+        assert_eq!(guaranies_to_words(1_500_000_000), "mil quinientos millones de guaraníes");
+        assert_eq!(guaranies_to_words(2_000_000_001), "dos mil millones un guaraníes");
+        assert_eq!(guaranies_to_words(1_000_000_000_000), "un billón de guaraníes");
+        assert_eq!(guaranies_to_words(3_000_000_000_000), "tres billones de guaraníes");
+        assert_eq!(guaranies_to_words(1_000_000_000_000_000_000), "un trillón de guaraníes");
+        assert_eq!(
+            guaranies_to_words(i64::MAX),
+            "nueve trillones doscientos veintitrés mil trescientos setenta y dos billones \
+             treinta y seis mil ochocientos cincuenta y cuatro millones \
+             setecientos setenta y cinco mil ochocientos siete guaraníes"
+        );
+    }
+
+    #[test]
+    fn test_words_negative_amounts() {
+        assert_eq!(guaranies_to_words(-1), "menos un guaraní");
+        assert_eq!(guaranies_to_words(-21), "menos veintiún guaraníes");
+        assert_eq!(guaranies_to_words(-1_000_000), "menos un millón de guaraníes");
+        assert_eq!(guaranies_to_words(-1_250_000), "menos un millón doscientos cincuenta mil guaraníes");
+        assert!(guaranies_to_words(i64::MIN).starts_with("menos nueve trillones"));
+    }
+
+    #[test]
+    fn test_words_are_well_formed() {
+        // Estos textos van en documentos legales: sin espacios dobles ni "uno" delante del sustantivo
+        for amount in (0..200_000).chain((0..2_000).map(|n| n * 999_983)) {
+            let words = guaranies_to_words(amount);
+            assert!(!words.contains("  "), "{}: {}", amount, words);
+            assert!(!words.starts_with(' ') && !words.ends_with(' '), "{}: {}", amount, words);
+            assert!(!words.contains("uno "), "{}: {}", amount, words);
+        }
+    }
+}
@@ -0,0 +1,354 @@
+//! Cálculo del IVA paraguayo para facturas y recibos
+//!
+//! En Paraguay los precios se expresan con el IVA incluido, por lo que el
+//! impuesto se extrae del total: `iva = total * tasa / (1 + tasa)`, redondeado
+//! al guaraní más cercano. La base imponible es la diferencia entre ambos.
+
+use serde::{Deserialize, Serialize};
+
+/// Tasa de IVA aplicable a un ítem
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum IvaRate {
+    /// Tasa general del 10%
+    Standard10,
+    /// Tasa reducida del 5%
+    Reduced5,
+    /// Operaciones exentas
+    Exempt,
+}
+
+impl IvaRate {
+    /// Porcentaje de la tasa (10, 5 o 0)
+    pub fn percent(&self) -> u64 {
+        match self {
+            IvaRate::Standard10 => 10,
+            IvaRate::Reduced5 => 5,
+            IvaRate::Exempt => 0,
+        }
+    }
+
+    /// Etiqueta usada en la columna de la factura
+    pub fn label(&self) -> &'static str {
+        match self {
+            IvaRate::Standard10 => "IVA 10%",
+            IvaRate::Reduced5 => "IVA 5%",
+            IvaRate::Exempt => "Exenta",
+        }
+    }
+}
+
+/// Desglose del IVA contenido en un monto
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct IvaCalculation {
+    /// Monto sin IVA (base imponible)
+    pub base_amount: u64,
+    /// IVA contenido en el monto
+    pub iva_amount: u64,
+    /// Monto con IVA incluido, igual al monto original
+    pub total_amount: u64,
+    /// Tasa aplicada en porcentaje
+    pub rate_percent: f64,
+}
+
+/// Extrae el IVA incluido en un monto
+///
+/// # Arguments
+///
+/// * `amount` - Monto en guaraníes con el IVA incluido
+/// * `rate` - Tasa de IVA aplicable
+///
+/// # Returns
+///
+/// La base imponible, el IVA y el total; `base_amount + iva_amount == total_amount`
+///
+/// # Ejemplos
+/// ```
+/// use sai::utils::currency::{calculate_iva, IvaRate};
+///
+/// let iva = calculate_iva(110_000, IvaRate::Standard10);
+/// assert_eq!((iva.base_amount, iva.iva_amount), (100_000, 10_000));
+/// ```
+pub fn calculate_iva(amount: u64, rate: IvaRate) -> IvaCalculation {
+    let percent = rate.percent();
+    let divisor = 100 + percent as u128;
+    // Redondeo al entero más cercano sin pasar por punto flotante
+    let iva_amount = ((2 * amount as u128 * percent as u128 + divisor) / (2 * divisor)) as u64;
+
+    IvaCalculation {
+        base_amount: amount - iva_amount,
+        iva_amount,
+        total_amount: amount,
+        rate_percent: percent as f64,
+    }
+}
+
+/// Ítem de una factura, con su precio con IVA incluido
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvoiceItem {
+    /// Descripción del concepto facturado
+    pub description: String,
+    /// Monto en guaraníes con el IVA incluido
+    pub amount: u64,
+    /// Tasa de IVA del concepto
+    pub rate: IvaRate,
+}
+
+/// Ítem de la factura con su IVA calculado
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IvaLine {
+    pub description: String,
+    pub rate: IvaRate,
+    pub calculation: IvaCalculation,
+}
+
+/// Liquidación del IVA de una factura con tasas mixtas
+///
+/// Como en la factura impresa, el IVA de cada tasa se calcula sobre el
+/// subtotal de esa tasa y no como suma del IVA de cada ítem, por lo que puede
+/// diferir en un guaraní de la suma de `lines`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IvaBreakdown {
+    /// Ítems en el orden recibido
+    pub lines: Vec<IvaLine>,
+    /// Subtotal de ítems exentos
+    pub exempt_subtotal: u64,
+    /// Subtotal de ítems gravados al 5% (IVA incluido)
+    pub taxed_5_subtotal: u64,
+    /// Subtotal de ítems gravados al 10% (IVA incluido)
+    pub taxed_10_subtotal: u64,
+    /// IVA contenido en el subtotal al 5%
+    pub iva_5: u64,
+    /// IVA contenido en el subtotal al 10%
+    pub iva_10: u64,
+    /// Total de IVA de la factura
+    pub total_iva: u64,
+    /// Total a pagar
+    pub total_amount: u64,
+}
+
+/// Calcula la liquidación del IVA de una factura
+///
+/// # Arguments
+///
+/// * `items` - Ítems de la factura con precios con IVA incluido
+///
+/// # Returns
+///
+/// El IVA de cada ítem y los subtotales e impuestos por tasa
+pub fn generate_iva_receipt(items: Vec<InvoiceItem>) -> IvaBreakdown {
+    let subtotal = |rate: IvaRate| -> u64 {
+        items.iter().filter(|item| item.rate == rate).map(|item| item.amount).sum()
+    };
+    let exempt_subtotal = subtotal(IvaRate::Exempt);
+    let taxed_5_subtotal = subtotal(IvaRate::Reduced5);
+    let taxed_10_subtotal = subtotal(IvaRate::Standard10);
+
+    let iva_5 = calculate_iva(taxed_5_subtotal, IvaRate::Reduced5).iva_amount;
+    let iva_10 = calculate_iva(taxed_10_subtotal, IvaRate::Standard10).iva_amount;
+
+    let lines = items
+        .into_iter()
+        .map(|item| IvaLine {
+            calculation: calculate_iva(item.amount, item.rate),
+            description: item.description,
+            rate: item.rate,
+        })
+        .collect();
+
+    IvaBreakdown {
+        lines,
+        exempt_subtotal,
+        taxed_5_subtotal,
+        taxed_10_subtotal,
+        iva_5,
+        iva_10,
+        total_iva: iva_5 + iva_10,
+        total_amount: exempt_subtotal + taxed_5_subtotal + taxed_10_subtotal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(amount: u64, rate: IvaRate) -> InvoiceItem {
+        InvoiceItem {
+            description: format!("Concepto {}", amount),
+            amount,
+            rate,
+        }
+    }
+
+    #[test]
+    fn test_standard_rate_round_amount() {
+        let iva = calculate_iva(1_100_000, IvaRate::Standard10);
+        assert_eq!(iva.iva_amount, 100_000);
+        assert_eq!(iva.base_amount, 1_000_000);
+        assert_eq!(iva.total_amount, 1_100_000);
+        assert_eq!(iva.rate_percent, 10.0);
+    }
+
+    #[test]
+    fn test_reduced_rate_round_amount() {
+        let iva = calculate_iva(210_000, IvaRate::Reduced5);
+        assert_eq!(iva.iva_amount, 10_000);
+        assert_eq!(iva.base_amount, 200_000);
+        assert_eq!(iva.rate_percent, 5.0);
+    }
+
+    #[test]
+    fn test_exempt_has_no_iva() {
+        let iva = calculate_iva(350_000, IvaRate::Exempt);
+        assert_eq!(iva.iva_amount, 0);
+        assert_eq!(iva.base_amount, 350_000);
+        assert_eq!(iva.rate_percent, 0.0);
+    }
+
+    #[test]
+    fn test_zero_amount() {
+        for rate in [IvaRate::Standard10, IvaRate::Reduced5, IvaRate::Exempt] {
+            let iva = calculate_iva(0, rate);
+            assert_eq!((iva.base_amount, iva.iva_amount, iva.total_amount), (0, 0, 0));
+        }
+    }
+
+    #[test]
+    fn test_one_guarani() {
+        // 1 / 11 = 0,09 y 1 / 21 = 0,05: ambos redondean a cero
+        assert_eq!(calculate_iva(1, IvaRate::Standard10).iva_amount, 0);
+        assert_eq!(calculate_iva(1, IvaRate::Reduced5).iva_amount, 0);
+    }
+
+    #[test]
+    fn test_standard_rate_rounds_down() {
+        // 100 / 11 = 9,09
+        let iva = calculate_iva(100, IvaRate::Standard10);
+        assert_eq!((iva.base_amount, iva.iva_amount), (91, 9));
+    }
+
+    #[test]
+    fn test_standard_rate_rounds_up() {
+        // 10 / 11 = 0,909
+        assert_eq!(calculate_iva(10, IvaRate::Standard10).iva_amount, 1);
+        // 150.001 / 11 = 13.636,45
+        assert_eq!(calculate_iva(150_001, IvaRate::Standard10).iva_amount, 13_636);
+        // 150.006 / 11 = 13.636,91
+        assert_eq!(calculate_iva(150_006, IvaRate::Standard10).iva_amount, 13_637);
+    }
+
+    #[test]
+    fn test_standard_rate_just_below_half() {
+        // 5 / 11 = 0,4545 redondea a cero; 6 / 11 = 0,545 redondea a uno
+        assert_eq!(calculate_iva(5, IvaRate::Standard10).iva_amount, 0);
+        assert_eq!(calculate_iva(6, IvaRate::Standard10).iva_amount, 1);
+    }
+
+    #[test]
+    fn test_reduced_rate_rounding_boundary() {
+        // 10 / 21 = 0,476 redondea a cero; 11 / 21 = 0,524 redondea a uno
+        assert_eq!(calculate_iva(10, IvaRate::Reduced5).iva_amount, 0);
+        assert_eq!(calculate_iva(11, IvaRate::Reduced5).iva_amount, 1);
+    }
+
+    #[test]
+    fn test_reduced_rate_odd_amount() {
+        // 99.999 / 21 = 4.761,86
+        let iva = calculate_iva(99_999, IvaRate::Reduced5);
+        assert_eq!((iva.base_amount, iva.iva_amount), (95_237, 4_762));
+    }
+
+    #[test]
+    fn test_base_plus_iva_equals_total_for_odd_amounts() {
+        for amount in [3, 7, 13, 999, 12_345, 777_777, 1_234_567] {
+            for rate in [IvaRate::Standard10, IvaRate::Reduced5, IvaRate::Exempt] {
+                let iva = calculate_iva(amount, rate);
+                assert_eq!(iva.base_amount + iva.iva_amount, amount);
+            }
+        }
+    }
+
+    #[test]
+    fn test_large_amount_does_not_overflow() {
+        // u64::MAX / 11 = 1.676.976.733.973.595.601,36
+        let iva = calculate_iva(u64::MAX, IvaRate::Standard10);
+        assert_eq!(iva.iva_amount, 1_676_976_733_973_595_601);
+        assert_eq!(iva.base_amount + iva.iva_amount, u64::MAX);
+    }
+
+    #[test]
+    fn test_matches_float_formula() {
+        for amount in [1_001, 45_500, 333_333, 2_500_001] {
+            let expected = (amount as f64 * 0.10 / 1.10).round() as u64;
+            assert_eq!(calculate_iva(amount, IvaRate::Standard10).iva_amount, expected);
+            let expected = (amount as f64 * 0.05 / 1.05).round() as u64;
+            assert_eq!(calculate_iva(amount, IvaRate::Reduced5).iva_amount, expected);
+        }
+    }
+
+    #[test]
+    fn test_rate_labels() {
+        assert_eq!(IvaRate::Standard10.label(), "IVA 10%");
+        assert_eq!(IvaRate::Reduced5.label(), "IVA 5%");
+        assert_eq!(IvaRate::Exempt.label(), "Exenta");
+    }
+
+    #[test]
+    fn test_receipt_without_items() {
+        let breakdown = generate_iva_receipt(Vec::new());
+        assert!(breakdown.lines.is_empty());
+        assert_eq!(breakdown.total_amount, 0);
+        assert_eq!(breakdown.total_iva, 0);
+    }
+
+    #[test]
+    fn test_receipt_single_rate() {
+        let breakdown = generate_iva_receipt(vec![item(550_000, IvaRate::Standard10), item(220_000, IvaRate::Standard10)]);
+        assert_eq!(breakdown.taxed_10_subtotal, 770_000);
+        assert_eq!(breakdown.iva_10, 70_000);
+        assert_eq!(breakdown.iva_5, 0);
+        assert_eq!(breakdown.total_iva, 70_000);
+        assert_eq!(breakdown.total_amount, 770_000);
+    }
+
+    #[test]
+    fn test_receipt_mixed_rates() {
+        let breakdown = generate_iva_receipt(vec![
+            item(1_100_000, IvaRate::Standard10),
+            item(105_000, IvaRate::Reduced5),
+            item(300_000, IvaRate::Exempt),
+        ]);
+        assert_eq!(breakdown.exempt_subtotal, 300_000);
+        assert_eq!(breakdown.taxed_5_subtotal, 105_000);
+        assert_eq!(breakdown.taxed_10_subtotal, 1_100_000);
+        assert_eq!(breakdown.iva_5, 5_000);
+        assert_eq!(breakdown.iva_10, 100_000);
+        assert_eq!(breakdown.total_iva, 105_000);
+        assert_eq!(breakdown.total_amount, 1_505_000);
+    }
+
+    #[test]
+    fn test_receipt_keeps_item_order_and_descriptions() {
+        let breakdown = generate_iva_receipt(vec![item(300, IvaRate::Exempt), item(110, IvaRate::Standard10)]);
+        let descriptions: Vec<_> = breakdown.lines.iter().map(|l| l.description.as_str()).collect();
+        assert_eq!(descriptions, vec!["Concepto 300", "Concepto 110"]);
+        assert_eq!(breakdown.lines[1].calculation.iva_amount, 10);
+        assert_eq!(breakdown.lines[0].rate, IvaRate::Exempt);
+    }
+
+    #[test]
+    fn test_receipt_taxes_subtotal_not_sum_of_lines() {
+        // Cada ítem de 6 tiene 0,545 de IVA (redondea a 1); el subtotal de 12 tiene 1,09
+        let breakdown = generate_iva_receipt(vec![item(6, IvaRate::Standard10), item(6, IvaRate::Standard10)]);
+        let sum_of_lines: u64 = breakdown.lines.iter().map(|l| l.calculation.iva_amount).sum();
+        assert_eq!(sum_of_lines, 2);
+        assert_eq!(breakdown.iva_10, 1);
+    }
+
+    #[test]
+    fn test_receipt_only_exempt_items() {
+        let breakdown = generate_iva_receipt(vec![item(450_000, IvaRate::Exempt), item(1, IvaRate::Exempt)]);
+        assert_eq!(breakdown.exempt_subtotal, 450_001);
+        assert_eq!(breakdown.total_iva, 0);
+        assert_eq!(breakdown.total_amount, 450_001);
+    }
+}
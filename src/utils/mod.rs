@@ -9,20 +9,21 @@
 //! * Manejo de fechas y cálculos temporales
 //! * Generación de identificadores únicos
 //! * Utilidades para manejo de moneda (guaraníes)
+//! * Hash y verificación de contraseñas (Argon2id)
+//! * Paginación de los listados
 //! * Otras funciones de utilidad general
 
-pub mod validation;
-pub mod formatting;
-pub mod date_utils;
-pub mod id_generator;
 pub mod currency;
-pub mod string_utils;
+pub mod password;
+pub mod pagination;
+pub mod phone;
 
 // Re-exportamos las funciones más utilizadas para facilitar su uso
-pub use validation::{validate_ci, validate_ruc, validate_phone_number};
+pub use validation::{validate_ci, validate_ruc, DocumentId, compute_ruc_check_digit, validate_phone_number, validate_email, normalize_email, ValidatorBuilder};
 pub use formatting::{format_ci, format_ruc, format_phone_number};
-pub use date_utils::{format_date_py, is_paraguay_holiday};
+pub use date_utils::{format_date_py, is_paraguay_holiday, add_business_days, next_business_day};
 pub use currency::{format_guaranies, guaranies_to_words};
+pub use phone::{PhoneError, PhoneKind, PhoneNumber};
 
 /// Constantes de utilidad general para el contexto paraguayo
 pub mod constants {
@@ -38,6 +39,12 @@ pub mod constants {
     /// Longitud estándar de un CI paraguayo (sin puntos)
     pub const CI_LENGTH: usize = 7;
     
+    /// Longitud mínima de un CI paraguayo (sin puntos); las cédulas antiguas tienen 6 dígitos
+    pub const CI_MIN_LENGTH: usize = 6;
+    
+    /// Longitud máxima de un CI paraguayo (sin puntos)
+    pub const CI_MAX_LENGTH: usize = 8;
+    
     /// Longitud estándar de un RUC paraguayo (sin guión)
     pub const RUC_BASE_LENGTH: usize = 8;
 }
@@ -49,6 +56,8 @@ pub mod validation {
     
     /// Valida un número de Cédula de Identidad paraguaya
     /// 
+    /// Equivale a que [`DocumentId::parse`] acepte el número.
+    /// 
     /// # Argumentos
     /// * `ci` - Número de cédula a validar (puede contener puntos)
     /// 
@@ -58,24 +67,118 @@ pub mod validation {
     /// 
     /// assert!(validate_ci("1234567"));
     /// assert!(validate_ci("1.234.567"));
+    /// assert!(validate_ci("12.345.678"));
     /// assert!(!validate_ci("12345")); // Muy corto
     /// ```
     pub fn validate_ci(ci: &str) -> bool {
-        let digits_only = ci.replace(".", "");
+        DocumentId::parse(ci).is_ok()
+    }
+    
+    /// Errores al interpretar un número de Cédula de Identidad
+    #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+    pub enum DocumentError {
+        #[error("es obligatorio")]
+        Empty,
+        #[error("solo puede contener dígitos, puntos y un dígito verificador")]
+        InvalidCharacters,
+        #[error("debe tener entre 6 y 8 dígitos, tiene {0}")]
+        InvalidLength(usize),
+        #[error("el dígito verificador no corresponde, se esperaba {expected}")]
+        InvalidCheckDigit { expected: u8 },
+    }
+    
+    /// Número de Cédula de Identidad normalizado: solo los dígitos, sin puntos
+    /// ni dígito verificador
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::validation::DocumentId;
+    /// 
+    /// let ci = DocumentId::parse(" 1.234.567-9 ").unwrap();
+    /// assert_eq!(ci.as_str(), "1234567");
+    /// assert_eq!(ci.formatted(), "1.234.567");
+    /// assert!(DocumentId::parse("1.234.567-3").is_err());
+    /// ```
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct DocumentId(String);
+    
+    impl DocumentId {
+        /// Interpreta un número de cédula
+        /// 
+        /// Ignora espacios y puntos y acepta de `CI_MIN_LENGTH` a
+        /// `CI_MAX_LENGTH` dígitos. Si el número termina en un dígito
+        /// verificador separado por guión, se comprueba con el mismo módulo 11
+        /// del RUC; el dígito no forma parte del valor normalizado.
+        pub fn parse(value: &str) -> Result<Self, DocumentError> {
+            let compact: String = value.chars().filter(|&c| c != '.' && !c.is_whitespace()).collect();
+            if compact.is_empty() {
+                return Err(DocumentError::Empty);
+            }
+            
+            let (digits, verifier) = match compact.split_once('-') {
+                Some((digits, verifier)) => (digits, Some(verifier)),
+                None => (compact.as_str(), None),
+            };
+            let only_digits = |text: &str| text.bytes().all(|b| b.is_ascii_digit());
+            if !only_digits(digits) || verifier.is_some_and(|verifier| verifier.len() != 1 || !only_digits(verifier)) {
+                return Err(DocumentError::InvalidCharacters);
+            }
+            if !(CI_MIN_LENGTH..=CI_MAX_LENGTH).contains(&digits.len()) {
+                return Err(DocumentError::InvalidLength(digits.len()));
+            }
+            
+            if let Some(verifier) = verifier {
+                let expected = modulo_11_check_digit(digits);
+                if verifier.as_bytes()[0] - b'0' != expected {
+                    return Err(DocumentError::InvalidCheckDigit { expected });
+                }
+            }
+            
+            Ok(DocumentId(digits.to_string()))
+        }
         
-        // Verificar longitud básica
-        if digits_only.len() != CI_LENGTH {
-            return false;
+        /// Dígitos del documento
+        pub fn as_str(&self) -> &str {
+            &self.0
+        }
+        
+        /// Dígitos del documento, tal como se guardan en `users.document_id`
+        pub fn into_string(self) -> String {
+            self.0
         }
         
-        // Verificar que solo contiene dígitos
-        digits_only.chars().all(|c| c.is_digit(10))
+        /// Dígito verificador del documento (módulo 11 de la SET)
+        pub fn check_digit(&self) -> u8 {
+            modulo_11_check_digit(&self.0)
+        }
+        
+        /// Documento con los puntos del formato paraguayo (ver [`format_ci`](super::formatting::format_ci))
+        pub fn formatted(&self) -> String {
+            super::formatting::format_ci(&self.0)
+        }
+    }
+    
+    impl std::fmt::Display for DocumentId {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.0)
+        }
+    }
+    
+    impl std::str::FromStr for DocumentId {
+        type Err = DocumentError;
+        
+        fn from_str(value: &str) -> Result<Self, Self::Err> {
+            DocumentId::parse(value)
+        }
     }
     
     /// Valida un número de RUC paraguayo
     /// 
+    /// El último dígito debe ser el dígito verificador de la base, calculado
+    /// con [`compute_ruc_check_digit`].
+    /// 
     /// # Argumentos
-    /// * `ruc` - Número de RUC a validar (puede contener guión y dígito verificador)
+    /// * `ruc` - Número de RUC a validar (puede contener puntos, guión y dígito verificador)
     /// 
     /// # Ejemplos
     /// ```
@@ -83,25 +186,73 @@ pub mod validation {
     /// 
     /// assert!(validate_ruc("12345678-9"));
     /// assert!(validate_ruc("123456789"));
+    /// assert!(validate_ruc("80.012.345-0"));
+    /// assert!(!validate_ruc("12345678-5")); // Dígito verificador incorrecto
     /// assert!(!validate_ruc("1234-5")); // Formato incorrecto
     /// ```
     pub fn validate_ruc(ruc: &str) -> bool {
-        // RUC puede tener formato XXXXXXXX-Y o XXXXXXXXY
-        let ruc_regex = Regex::new(r"^(\d{7,8})[-]?(\d)$").unwrap();
+        // RUC puede tener formato XXXXXXXX-Y o XXXXXXXXY, con o sin puntos
+        let ruc_regex = Regex::new(r"^(\d{7,8})-?(\d)$").unwrap();
+        let ruc = ruc.replace('.', "");
         
-        if !ruc_regex.is_match(ruc) {
+        let Some(captures) = ruc_regex.captures(&ruc) else {
             return false;
+        };
+        
+        compute_ruc_check_digit(&captures[1]) == captures[2].parse().ok()
+    }
+    
+    /// Calcula el dígito verificador de la base de un RUC (módulo 11 de la SET)
+    /// 
+    /// Cada dígito de la base, desde la derecha, se multiplica por los pesos
+    /// 2, 3, ..., 11 (que vuelven a empezar en 2 si la base es más larga). Si
+    /// el resto de la suma por 11 es mayor que 1, el dígito es `11 - resto`;
+    /// si no, es 0.
+    /// 
+    /// # Argumentos
+    /// * `base` - Los 7 u 8 dígitos del RUC, sin dígito verificador; se ignoran los puntos
+    /// 
+    /// # Retorna
+    /// `None` si la base no tiene 7 u 8 dígitos
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::validation::compute_ruc_check_digit;
+    /// 
+    /// assert_eq!(compute_ruc_check_digit("12345678"), Some(9));
+    /// assert_eq!(compute_ruc_check_digit("80.012.345"), Some(0));
+    /// assert_eq!(compute_ruc_check_digit("12-345"), None);
+    /// ```
+    pub fn compute_ruc_check_digit(base: &str) -> Option<u8> {
+        let base = base.replace('.', "");
+        let valid_length = (RUC_BASE_LENGTH - 1..=RUC_BASE_LENGTH).contains(&base.len());
+        if !valid_length || !base.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
         }
         
-        // TODO: Implementar algoritmo de verificación del dígito verificador
-        // Para una implementación completa, se debe verificar que el último dígito
-        // sea correcto según el algoritmo de verificación de RUC paraguayo.
+        Some(modulo_11_check_digit(&base))
+    }
+    
+    /// Módulo 11 de la SET sobre una cadena de dígitos ASCII
+    fn modulo_11_check_digit(digits: &str) -> u8 {
+        let sum: u32 = digits
+            .bytes()
+            .rev()
+            .zip((2..=11).cycle())
+            .map(|(digit, weight)| u32::from(digit - b'0') * weight)
+            .sum();
         
-        true
+        match sum % 11 {
+            remainder if remainder > 1 => (11 - remainder) as u8,
+            _ => 0,
+        }
     }
     
     /// Valida un número de teléfono paraguayo
     /// 
+    /// Equivale a que [`PhoneNumber::parse`](super::phone::PhoneNumber::parse)
+    /// acepte el número: un celular o una línea fija con código de área conocido.
+    /// 
     /// # Argumentos
     /// * `phone` - Número de teléfono a validar
     /// 
@@ -111,38 +262,753 @@ pub mod validation {
     /// 
     /// assert!(validate_phone_number("0981123456"));
     /// assert!(validate_phone_number("+595981123456"));
+    /// assert!(validate_phone_number("(021) 123-456"));
     /// assert!(!validate_phone_number("123456")); // Muy corto
     /// ```
     pub fn validate_phone_number(phone: &str) -> bool {
-        let phone_clean = phone
-            .replace(" ", "")
-            .replace("-", "")
-            .replace("(", "")
-            .replace(")", "");
-            
-        // Formato local o internacional
-        if phone_clean.starts_with(PHONE_COUNTRY_CODE) {
-            // Formato internacional +595XXXXXXXXX
-            phone_clean.len() >= 12 && phone_clean[4..].chars().all(|c| c.is_digit(10))
-        } else if phone_clean.starts_with("0") {
-            // Formato local 0XXXXXXXXX
-            phone_clean.len() >= 10 && phone_clean.chars().all(|c| c.is_digit(10))
-        } else {
-            false
-        }
+        super::phone::PhoneNumber::parse(phone).is_ok()
     }
     
+    /// Largo máximo de una dirección de correo (RFC 5321)
+    const EMAIL_MAX_LENGTH: usize = 254;
+    
+    /// Largo máximo de la parte local de un correo (RFC 5321)
+    const EMAIL_LOCAL_MAX_LENGTH: usize = 64;
+    
     /// Valida una dirección de correo electrónico
+    /// 
+    /// Sigue la sintaxis de RFC 5322 sin comentarios ni literales de IP: la
+    /// parte local es un dot-atom o una cadena entre comillas, y el dominio
+    /// tiene al menos dos etiquetas alfanuméricas (con guiones internos) y un
+    /// dominio de primer nivel alfabético o en punycode. Solo se aceptan
+    /// caracteres ASCII.
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::validation::validate_email;
+    /// 
+    /// assert!(validate_email("ana+notas@colegio.edu.py"));
+    /// assert!(validate_email("\"ana benitez\"@example.museum"));
+    /// assert!(!validate_email("ana..benitez@colegio.edu.py"));
+    /// assert!(!validate_email("ana@colegio-.edu.py"));
+    /// ```
     pub fn validate_email(email: &str) -> bool {
-        let email_regex = Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").unwrap();
-        email_regex.is_match(email)
+        if !email.is_ascii() || email.len() > EMAIL_MAX_LENGTH {
+            return false;
+        }
+        let Some((local, domain)) = email.rsplit_once('@') else {
+            return false;
+        };
+        
+        (1..=EMAIL_LOCAL_MAX_LENGTH).contains(&local.len())
+            && (is_dot_atom(local) || is_quoted_local_part(local))
+            && is_email_domain(domain)
+    }
+    
+    /// Caracteres de un átomo de RFC 5322 (`atext`)
+    fn is_atext(c: char) -> bool {
+        c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c)
+    }
+    
+    /// Átomos no vacíos separados por puntos simples
+    fn is_dot_atom(local: &str) -> bool {
+        local.split('.').all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+    }
+    
+    /// Cadena entre comillas con caracteres imprimibles; `\` escapa el siguiente
+    fn is_quoted_local_part(local: &str) -> bool {
+        let Some(inner) = local.strip_prefix('"').and_then(|rest| rest.strip_suffix('"')) else {
+            return false;
+        };
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\'
+                    if !chars.next().is_some_and(|escaped| escaped == ' ' || escaped.is_ascii_graphic()) => {
+                        return false;
+                    }
+                '"' => return false,
+                c if c != ' ' && !c.is_ascii_graphic() => return false,
+                _ => {}
+            }
+        }
+        true
+    }
+    
+    /// Nombre de dominio con al menos dos etiquetas y un dominio de primer nivel válido
+    fn is_email_domain(domain: &str) -> bool {
+        let labels: Vec<&str> = domain.split('.').collect();
+        let Some(tld) = labels.last().filter(|_| labels.len() >= 2) else {
+            return false;
+        };
+        
+        let valid_label = |label: &&str| {
+            (1..=63).contains(&label.len())
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        };
+        let valid_tld = (tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic()))
+            || tld.to_ascii_lowercase().starts_with("xn--");
+        
+        domain.len() <= 253 && labels.iter().all(valid_label) && valid_tld
+    }
+    
+    /// Normaliza un correo para guardarlo y buscarlo
+    /// 
+    /// Quita los espacios de los extremos y pasa el dominio a minúsculas. La
+    /// parte local también se pasa a minúsculas cuando `lowercase_local_part`
+    /// (`mail.lowercase_local_part` de la configuración) está activo y no va
+    /// entre comillas, de modo que "Juan@X.com" y "juan@x.com" sean la misma cuenta.
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::validation::normalize_email;
+    /// 
+    /// assert_eq!(normalize_email("  Juan.Perez@Colegio.EDU.py ", true), "juan.perez@colegio.edu.py");
+    /// assert_eq!(normalize_email("Juan.Perez@Colegio.EDU.py", false), "Juan.Perez@colegio.edu.py");
+    /// ```
+    pub fn normalize_email(email: &str, lowercase_local_part: bool) -> String {
+        let email = email.trim();
+        let Some((local, domain)) = email.rsplit_once('@') else {
+            return email.to_string();
+        };
+        
+        let lowercase_local = lowercase_local_part && !local.starts_with('"');
+        let local = if lowercase_local { local.to_lowercase() } else { local.to_string() };
+        format!("{}@{}", local, domain.to_lowercase())
+    }
+    
+    /// Regla de validación que originó un error
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ValidationRule {
+        /// El valor es obligatorio
+        Required,
+        /// Longitud mínima en caracteres
+        MinLength(usize),
+        /// Longitud máxima en caracteres
+        MaxLength(usize),
+        /// El valor debe coincidir con una expresión regular
+        Pattern(String),
+        /// Regla definida por el llamador
+        Custom,
+    }
+    
+    /// Error producido por una regla de `ValidatorBuilder`
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ValidationError {
+        /// Campo validado, si se indicó con `field`
+        pub field: Option<&'static str>,
+        /// Regla que no se cumplió
+        pub rule: ValidationRule,
+        /// Mensaje legible para el usuario
+        pub message: String,
+    }
+    
+    impl std::fmt::Display for ValidationError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self.field {
+                Some(field) => write!(f, "{}: {}", field, self.message),
+                None => write!(f, "{}", self.message),
+            }
+        }
+    }
+    
+    /// Constructor de cadenas de validación componibles
+    ///
+    /// Cada regla se evalúa al agregarse y, si falla, acumula un
+    /// `ValidationError`; `validate` devuelve todos los errores juntos en lugar
+    /// de detenerse en el primero.
+    ///
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::validation::ValidatorBuilder;
+    ///
+    /// let username = ValidatorBuilder::new("ana_py".to_string())
+    ///     .field("username")
+    ///     .min_length(3)
+    ///     .max_length(30)
+    ///     .matches_regex(r"^[A-Za-z0-9_]+$")
+    ///     .validate();
+    /// assert!(username.is_ok());
+    ///
+    /// let errors = ValidatorBuilder::new("a!".to_string())
+    ///     .min_length(3)
+    ///     .matches_regex(r"^[A-Za-z0-9_]+$")
+    ///     .validate()
+    ///     .unwrap_err();
+    /// assert_eq!(errors.len(), 2);
+    /// ```
+    #[derive(Debug)]
+    pub struct ValidatorBuilder<T> {
+        /// Valor a validar; `None` cuando faltaba un valor obligatorio
+        value: Option<T>,
+        field: Option<&'static str>,
+        errors: Vec<ValidationError>,
+    }
+    
+    impl<T> ValidatorBuilder<T> {
+        /// Crea un validador para el valor indicado
+        pub fn new(value: T) -> Self {
+            Self {
+                value: Some(value),
+                field: None,
+                errors: Vec::new(),
+            }
+        }
+    
+        /// Asigna el nombre del campo que se incluirá en los errores
+        pub fn field(mut self, name: &'static str) -> Self {
+            self.field = Some(name);
+            for error in &mut self.errors {
+                error.field = Some(name);
+            }
+            self
+        }
+    
+        /// Devuelve el valor si todas las reglas se cumplieron, o todos los errores
+        pub fn validate(self) -> Result<T, Vec<ValidationError>> {
+            match self.value {
+                Some(value) if self.errors.is_empty() => Ok(value),
+                _ => Err(self.errors),
+            }
+        }
+    
+        fn push_error(&mut self, rule: ValidationRule, message: String) {
+            self.errors.push(ValidationError {
+                field: self.field,
+                rule,
+                message,
+            });
+        }
+    }
+    
+    impl ValidatorBuilder<Option<String>> {
+        /// Exige que el valor esté presente y no sea vacío
+        ///
+        /// Si falta, se registra un error `Required` y las reglas siguientes se omiten.
+        pub fn required(self) -> ValidatorBuilder<String> {
+            let mut builder = ValidatorBuilder {
+                value: self.value.flatten().filter(|value| !value.trim().is_empty()),
+                field: self.field,
+                errors: self.errors,
+            };
+            if builder.value.is_none() {
+                builder.push_error(ValidationRule::Required, "es obligatorio".to_string());
+            }
+            builder
+        }
+    }
+    
+    impl ValidatorBuilder<String> {
+        /// Exige una longitud mínima (en caracteres)
+        pub fn min_length(mut self, min: usize) -> Self {
+            if let Some(length) = self.value.as_ref().map(|value| value.chars().count()) {
+                if length < min {
+                    self.push_error(
+                        ValidationRule::MinLength(min),
+                        format!("debe tener al menos {} caracteres", min),
+                    );
+                }
+            }
+            self
+        }
+    
+        /// Exige una longitud máxima (en caracteres)
+        pub fn max_length(mut self, max: usize) -> Self {
+            if let Some(length) = self.value.as_ref().map(|value| value.chars().count()) {
+                if length > max {
+                    self.push_error(
+                        ValidationRule::MaxLength(max),
+                        format!("debe tener como máximo {} caracteres", max),
+                    );
+                }
+            }
+            self
+        }
+    
+        /// Exige que el valor coincida con una expresión regular
+        ///
+        /// Un patrón inválido se informa como error de la misma regla.
+        pub fn matches_regex(mut self, pattern: &str) -> Self {
+            let Some(value) = self.value.as_ref() else {
+                return self;
+            };
+            let message = match Regex::new(pattern) {
+                Ok(regex) if regex.is_match(value) => return self,
+                Ok(_) => "no tiene un formato válido".to_string(),
+                Err(e) => format!("patrón de validación inválido: {}", e),
+            };
+            self.push_error(ValidationRule::Pattern(pattern.to_string()), message);
+            self
+        }
+    
+        /// Aplica una regla propia que devuelve el mensaje de error si falla
+        pub fn custom<F>(mut self, rule: F) -> Self
+        where
+            F: Fn(&str) -> Result<(), String>,
+        {
+            if let Some(Err(message)) = self.value.as_deref().map(rule) {
+                self.push_error(ValidationRule::Custom, message);
+            }
+            self
+        }
+    }
+    
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+    
+        const USERNAME_PATTERN: &str = r"^[A-Za-z0-9_]+$";
+    
+        fn text(value: &str) -> ValidatorBuilder<String> {
+            ValidatorBuilder::new(value.to_string())
+        }
+    
+        fn has_digit(value: &str) -> Result<(), String> {
+            if value.chars().any(|c| c.is_ascii_digit()) {
+                Ok(())
+            } else {
+                Err("debe contener al menos un número".to_string())
+            }
+        }
+    
+        fn has_uppercase(value: &str) -> Result<(), String> {
+            if value.chars().any(|c| c.is_uppercase()) {
+                Ok(())
+            } else {
+                Err("debe contener al menos una mayúscula".to_string())
+            }
+        }
+    
+        /// Bases de 7 y 8 dígitos con su dígito verificador
+        const RUC_CHECK_DIGITS: &[(&str, u8)] = &[
+            ("1234567", 9),
+            ("4567890", 1),
+            ("7654321", 8),
+            ("3456789", 5),
+            ("1000000", 3),
+            ("2222222", 7),
+            ("12345678", 9),
+            ("80012345", 0),
+            ("80000519", 8),
+            ("80009735", 1),
+            ("80023325", 5),
+            ("80016096", 7),
+            // Resto 0 y resto 1: el dígito es 0
+            ("1000007", 0),
+            ("1000002", 0),
+            ("80000003", 0),
+        ];
+    
+        #[test]
+        fn test_ruc_check_digit_table() {
+            for (base, digit) in RUC_CHECK_DIGITS {
+                assert_eq!(compute_ruc_check_digit(base), Some(*digit), "base {}", base);
+            }
+        }
+    
+        #[test]
+        fn test_ruc_check_digit_rejects_invalid_base() {
+            for base in ["", "123456", "123456789", "1234a67", "1234-567", " 1234567"] {
+                assert_eq!(compute_ruc_check_digit(base), None, "base {:?}", base);
+            }
+        }
+    
+        #[test]
+        fn test_valid_rucs_in_every_notation() {
+            for (base, digit) in RUC_CHECK_DIGITS {
+                let dotted = if base.len() == 8 {
+                    format!("{}.{}.{}", &base[..2], &base[2..5], &base[5..])
+                } else {
+                    format!("{}.{}.{}", &base[..1], &base[1..4], &base[4..])
+                };
+                for ruc in [format!("{}-{}", base, digit), format!("{}{}", base, digit), format!("{}-{}", dotted, digit)] {
+                    assert!(validate_ruc(&ruc), "{}", ruc);
+                }
+            }
+        }
+    
+        #[test]
+        fn test_corrupted_rucs_are_rejected() {
+            let corrupted = [
+                // Dígito verificador cambiado
+                "12345678-0",
+                "80012345-1",
+                "1234567-8",
+                "80.000.519-9",
+                // Un dígito de la base cambiado
+                "12345679-9",
+                "80012346-0",
+                "2222232-7",
+                // Dígitos de la base transpuestos
+                "21345678-9",
+                "80021345-0",
+                // La `k` de otros países no existe en el RUC paraguayo
+                "1000005-k",
+            ];
+    
+            for ruc in corrupted {
+                assert!(!validate_ruc(ruc), "{}", ruc);
+            }
+        }
+    
+        #[test]
+        fn test_malformed_rucs_are_rejected() {
+            for ruc in ["1234-5", "123456789-5", "12345678--9", "12345678-", " 12345678-9", "12345678-99"] {
+                assert!(!validate_ruc(ruc), "{}", ruc);
+            }
+        }
+    
+        #[test]
+        fn test_document_id_lengths() {
+            for (input, digits, formatted) in [
+                ("123456", "123456", "1.23.456"),
+                ("1234567", "1234567", "1.234.567"),
+                ("12345678", "12345678", "12.345.678"),
+                ("1.234.567", "1234567", "1.234.567"),
+                ("12.345.678", "12345678", "12.345.678"),
+            ] {
+                let document = DocumentId::parse(input).unwrap();
+                assert_eq!(document.as_str(), digits, "{}", input);
+                assert_eq!(document.formatted(), formatted, "{}", input);
+            }
+            
+            assert_eq!(DocumentId::parse("12345"), Err(DocumentError::InvalidLength(5)));
+            assert_eq!(DocumentId::parse("123.456.789"), Err(DocumentError::InvalidLength(9)));
+        }
+        
+        #[test]
+        fn test_document_id_ignores_spaces() {
+            for input in [" 1234567", "1234567 ", "1 234 567", " 1.234.567 ", "\t12.345.678\n"] {
+                assert!(DocumentId::parse(input).is_ok(), "{:?}", input);
+            }
+            assert_eq!(DocumentId::parse("  ").unwrap_err(), DocumentError::Empty);
+            assert_eq!(DocumentId::parse(" . ").unwrap_err(), DocumentError::Empty);
+        }
+        
+        #[test]
+        fn test_document_id_check_digit() {
+            // Las cédulas usan el mismo dígito verificador que el RUC de la persona
+            for (base, digit) in RUC_CHECK_DIGITS {
+                let document = DocumentId::parse(&format!("{}-{}", base, digit)).unwrap();
+                assert_eq!(document.as_str(), *base);
+                assert_eq!(document.check_digit(), *digit);
+            }
+            
+            assert_eq!(DocumentId::parse("1.234.567 - 9").unwrap().as_str(), "1234567");
+            assert_eq!(
+                DocumentId::parse("1234567-3"),
+                Err(DocumentError::InvalidCheckDigit { expected: 9 })
+            );
+        }
+        
+        #[test]
+        fn test_document_id_rejects_garbage() {
+            for input in ["DEMO-ADMIN", "ADMIN-1a2b3c4d", "1234567-", "1234567-99", "1234567-k", "12-34-567", "１２３４５６７"] {
+                assert_eq!(DocumentId::parse(input), Err(DocumentError::InvalidCharacters), "{:?}", input);
+            }
+        }
+        
+        #[test]
+        fn test_no_rules_passes() {
+            assert_eq!(text("hola").validate(), Ok("hola".to_string()));
+        }
+    
+        #[test]
+        fn test_min_length_passes_at_boundary() {
+            assert!(text("abc").min_length(3).validate().is_ok());
+        }
+    
+        #[test]
+        fn test_min_length_fails_below_boundary() {
+            let errors = text("ab").min_length(3).validate().unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].rule, ValidationRule::MinLength(3));
+        }
+    
+        #[test]
+        fn test_min_length_counts_characters_not_bytes() {
+            assert!(text("ñandú").min_length(5).validate().is_ok());
+            assert!(text("ñandú").max_length(5).validate().is_ok());
+        }
+    
+        #[test]
+        fn test_min_length_fails_on_empty_string() {
+            assert!(text("").min_length(1).validate().is_err());
+        }
+    
+        #[test]
+        fn test_max_length_passes_at_boundary() {
+            assert!(text("abcde").max_length(5).validate().is_ok());
+        }
+    
+        #[test]
+        fn test_max_length_fails_above_boundary() {
+            let errors = text("abcdef").max_length(5).validate().unwrap_err();
+            assert_eq!(errors[0].rule, ValidationRule::MaxLength(5));
+            assert_eq!(errors[0].message, "debe tener como máximo 5 caracteres");
+        }
+    
+        #[test]
+        fn test_regex_passes() {
+            assert!(text("ana_py_2024").matches_regex(USERNAME_PATTERN).validate().is_ok());
+        }
+    
+        #[test]
+        fn test_regex_fails() {
+            let errors = text("ana-py").matches_regex(USERNAME_PATTERN).validate().unwrap_err();
+            assert_eq!(errors[0].rule, ValidationRule::Pattern(USERNAME_PATTERN.to_string()));
+        }
+    
+        #[test]
+        fn test_regex_rejects_spaces() {
+            assert!(text("ana py").matches_regex(USERNAME_PATTERN).validate().is_err());
+        }
+    
+        #[test]
+        fn test_invalid_regex_is_reported() {
+            let errors = text("abc").matches_regex("[").validate().unwrap_err();
+            assert!(errors[0].message.starts_with("patrón de validación inválido"));
+        }
+    
+        #[test]
+        fn test_custom_rule_passes() {
+            assert!(text("clave1").custom(has_digit).validate().is_ok());
+        }
+    
+        #[test]
+        fn test_custom_rule_fails_with_message() {
+            let errors = text("clave").custom(has_digit).validate().unwrap_err();
+            assert_eq!(errors[0].rule, ValidationRule::Custom);
+            assert_eq!(errors[0].message, "debe contener al menos un número");
+        }
+    
+        #[test]
+        fn test_custom_rule_accepts_closure() {
+            let forbidden = "admin";
+            let result = text("admin")
+                .custom(|value| if value == forbidden { Err("reservado".to_string()) } else { Ok(()) })
+                .validate();
+            assert_eq!(result.unwrap_err()[0].message, "reservado");
+        }
+    
+        #[test]
+        fn test_multiple_failures_are_accumulated() {
+            let errors = text("a-")
+                .min_length(3)
+                .matches_regex(USERNAME_PATTERN)
+                .validate()
+                .unwrap_err();
+            assert_eq!(errors.len(), 2);
+        }
+    
+        #[test]
+        fn test_errors_keep_rule_order() {
+            let errors = text("abcdef!")
+                .max_length(3)
+                .matches_regex(USERNAME_PATTERN)
+                .custom(has_digit)
+                .validate()
+                .unwrap_err();
+            let rules: Vec<_> = errors.into_iter().map(|e| e.rule).collect();
+            assert_eq!(
+                rules,
+                vec![
+                    ValidationRule::MaxLength(3),
+                    ValidationRule::Pattern(USERNAME_PATTERN.to_string()),
+                    ValidationRule::Custom,
+                ]
+            );
+        }
+    
+        #[test]
+        fn test_min_and_max_can_both_fail_when_misconfigured() {
+            let errors = text("abcd").min_length(5).max_length(3).validate().unwrap_err();
+            assert_eq!(errors.len(), 2);
+        }
+    
+        #[test]
+        fn test_all_password_rules_fail_together() {
+            let errors = text("abc")
+                .min_length(8)
+                .custom(has_digit)
+                .custom(has_uppercase)
+                .validate()
+                .unwrap_err();
+            assert_eq!(errors.len(), 3);
+        }
+    
+        #[test]
+        fn test_password_rules_pass() {
+            assert!(text("Segura2024")
+                .min_length(8)
+                .custom(has_digit)
+                .custom(has_uppercase)
+                .validate()
+                .is_ok());
+        }
+    
+        #[test]
+        fn test_password_missing_uppercase_only() {
+            let errors = text("segura2024")
+                .min_length(8)
+                .custom(has_digit)
+                .custom(has_uppercase)
+                .validate()
+                .unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].message, "debe contener al menos una mayúscula");
+        }
+    
+        #[test]
+        fn test_email_rule_with_existing_validator() {
+            let check = |value: &str| {
+                if validate_email(value) { Ok(()) } else { Err("email inválido".to_string()) }
+            };
+            assert!(text("ana@colegio.edu.py").custom(check).validate().is_ok());
+            assert!(text("ana@colegio").custom(check).validate().is_err());
+        }
+    
+        #[test]
+        fn test_valid_email_corpus() {
+            for email in [
+                "ana@colegio.edu.py",
+                "ana+notas@colegio.edu.py",
+                "ana.maria+2025@example.technology",
+                "o'higgins@example.cl",
+                "x@example.museum",
+                "\"ana benitez\"@example.com",
+                "\"ana\\\"b\"@example.com",
+                "user_name-1@sub.domain-2.example.org",
+                "ana@xn--caa-8ma.com.py",
+                "ANA@COLEGIO.EDU.PY",
+            ] {
+                assert!(validate_email(email), "{}", email);
+            }
+        }
+    
+        #[test]
+        fn test_invalid_email_corpus() {
+            for email in [
+                "",
+                "ana",
+                "ana@",
+                "@colegio.edu.py",
+                "ana@colegio",
+                "ana..maria@colegio.edu.py",
+                ".ana@colegio.edu.py",
+                "ana.@colegio.edu.py",
+                "ana@colegio..edu.py",
+                "ana@.colegio.edu.py",
+                "ana@colegio-.edu.py",
+                "ana@-colegio.edu.py",
+                "ana@colegio.edu.p",
+                "ana@colegio.edu.p1",
+                "ana maria@colegio.edu.py",
+                "\"ana\"maria@colegio.edu.py",
+                "ana@colegio.edu.py ",
+                "añá@colegio.edu.py",
+                "ana@@colegio.edu.py",
+            ] {
+                assert!(!validate_email(email), "{}", email);
+            }
+            let long_local = format!("{}@colegio.edu.py", "a".repeat(65));
+            assert!(!validate_email(&long_local));
+        }
+    
+        #[test]
+        fn test_normalize_email() {
+            assert_eq!(normalize_email("Juan@X.com", true), normalize_email("juan@x.com", true));
+            assert_eq!(normalize_email(" Ana@Colegio.EDU.py\n", true), "ana@colegio.edu.py");
+            // Las partes locales entre comillas se conservan tal cual
+            assert_eq!(normalize_email("\"Ana B\"@Example.com", true), "\"Ana B\"@example.com");
+            assert_eq!(normalize_email("sin-arroba", true), "sin-arroba");
+            // Con `mail.lowercase_local_part` desactivado solo cambia el dominio
+            assert_eq!(normalize_email(" Ana@Colegio.EDU.py", false), "Ana@colegio.edu.py");
+        }
+    
+        #[test]
+        fn test_field_name_is_attached_to_errors() {
+            let errors = text("ab").field("username").min_length(3).validate().unwrap_err();
+            assert_eq!(errors[0].field, Some("username"));
+        }
+    
+        #[test]
+        fn test_field_set_after_rules_updates_errors() {
+            let errors = text("ab").min_length(3).field("username").validate().unwrap_err();
+            assert_eq!(errors[0].field, Some("username"));
+        }
+    
+        #[test]
+        fn test_display_includes_field_name() {
+            let errors = text("ab").field("username").min_length(3).validate().unwrap_err();
+            assert_eq!(errors[0].to_string(), "username: debe tener al menos 3 caracteres");
+        }
+    
+        #[test]
+        fn test_display_without_field_name() {
+            let errors = text("ab").min_length(3).validate().unwrap_err();
+            assert_eq!(errors[0].to_string(), "debe tener al menos 3 caracteres");
+        }
+    
+        #[test]
+        fn test_required_passes_with_value() {
+            let result = ValidatorBuilder::new(Some("Asunción".to_string())).required().validate();
+            assert_eq!(result, Ok("Asunción".to_string()));
+        }
+    
+        #[test]
+        fn test_required_fails_with_none() {
+            let errors = ValidatorBuilder::new(None::<String>).required().validate().unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].rule, ValidationRule::Required);
+        }
+    
+        #[test]
+        fn test_required_fails_with_blank_string() {
+            let errors = ValidatorBuilder::new(Some("   ".to_string())).required().validate().unwrap_err();
+            assert_eq!(errors[0].rule, ValidationRule::Required);
+        }
+    
+        #[test]
+        fn test_required_skips_following_rules_when_missing() {
+            let errors = ValidatorBuilder::new(None::<String>)
+                .field("email")
+                .required()
+                .min_length(5)
+                .matches_regex(USERNAME_PATTERN)
+                .custom(has_digit)
+                .validate()
+                .unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].field, Some("email"));
+        }
+    
+        #[test]
+        fn test_required_then_rules_accumulate() {
+            let errors = ValidatorBuilder::new(Some("a!".to_string()))
+                .required()
+                .min_length(3)
+                .matches_regex(USERNAME_PATTERN)
+                .validate()
+                .unwrap_err();
+            assert_eq!(errors.len(), 2);
+        }
     }
 }
 
 /// Módulo para formateo de datos según estándares locales paraguayos
+///
+/// Las funciones nunca entran en pánico: una entrada que no tiene la forma
+/// esperada (vacía, con letras o emojis, o con una cantidad de dígitos que no
+/// corresponde) se devuelve sin cambios.
 pub mod formatting {
     /// Formatea un número de Cédula de Identidad con el formato paraguayo
     /// 
+    /// Acepta de 6 a 8 dígitos, con o sin puntos. Cualquier otra entrada se
+    /// devuelve sin cambios.
+    /// 
     /// # Argumentos
     /// * `ci` - Número de cédula sin formato
     /// 
@@ -151,29 +1017,31 @@ pub mod formatting {
     /// use sai::utils::formatting::format_ci;
     /// 
     /// assert_eq!(format_ci("1234567"), "1.234.567");
+    /// assert_eq!(format_ci("123.456"), "1.23.456");
+    /// assert_eq!(format_ci("12345678"), "12.345.678");
+    /// assert_eq!(format_ci("12345"), "12345");
     /// ```
     pub fn format_ci(ci: &str) -> String {
-        let digits_only = ci.replace(".", "");
-        
-        match digits_only.len() {
-            7 => format!(
-                "{}.{}.{}",
-                &digits_only[0..1],
-                &digits_only[1..4],
-                &digits_only[4..7]
-            ),
-            6 => format!(
-                "{}.{}.{}",
-                &digits_only[0..1],
-                &digits_only[1..3],
-                &digits_only[3..6]
-            ),
-            _ => digits_only, // Devolver sin cambios si no coincide con el formato esperado
+        let digits: Vec<char> = ci.chars().filter(|&c| c != '.').collect();
+        if !digits.iter().all(char::is_ascii_digit) {
+            return ci.to_string();
+        }
+
+        let group = |range: std::ops::Range<usize>| digits[range].iter().collect::<String>();
+        match digits.len() {
+            7 => format!("{}.{}.{}", group(0..1), group(1..4), group(4..7)),
+            8 => format!("{}.{}.{}", group(0..2), group(2..5), group(5..8)),
+            6 => format!("{}.{}.{}", group(0..1), group(1..3), group(3..6)),
+            _ => ci.to_string(),
         }
     }
     
     /// Formatea un número de RUC con el formato paraguayo
     /// 
+    /// Toma los dígitos de la entrada (ignorando guiones y espacios) y separa
+    /// el último como dígito verificador. Con menos de 2 o más de 9 dígitos, o
+    /// con otros caracteres, la entrada se devuelve sin cambios.
+    /// 
     /// # Argumentos
     /// * `ruc` - Número de RUC sin formato o parcialmente formateado
     /// 
@@ -182,22 +1050,25 @@ pub mod formatting {
     /// use sai::utils::formatting::format_ruc;
     /// 
     /// assert_eq!(format_ruc("123456789"), "12345678-9");
+    /// assert_eq!(format_ruc("80012345 - 6"), "80012345-6");
+    /// assert_eq!(format_ruc("7"), "7");
     /// ```
     pub fn format_ruc(ruc: &str) -> String {
-        let digits_only: String = ruc.chars().filter(|c| c.is_digit(10)).collect();
-        
-        if digits_only.len() < 2 {
-            return digits_only;
+        let digits: Vec<char> = ruc.chars().filter(|&c| c != '-' && !c.is_whitespace()).collect();
+        if !(2..=9).contains(&digits.len()) || !digits.iter().all(char::is_ascii_digit) {
+            return ruc.to_string();
         }
-        
-        let base = &digits_only[0..digits_only.len()-1];
-        let check_digit = &digits_only[digits_only.len()-1..];
-        
-        format!("{}-{}", base, check_digit)
+
+        let (base, check_digit) = digits.split_at(digits.len() - 1);
+        format!("{}-{}", base.iter().collect::<String>(), check_digit[0])
     }
     
     /// Formatea un número de teléfono con el formato paraguayo
     /// 
+    /// Delega en [`PhoneNumber`](super::phone::PhoneNumber): acepta celulares
+    /// y líneas fijas con o sin 0 inicial o código de país, e ignora espacios,
+    /// guiones y paréntesis. Cualquier otra entrada se devuelve sin cambios.
+    /// 
     /// # Argumentos
     /// * `phone` - Número de teléfono sin formato
     /// * `international` - Si es true, incluye el código de país (+595)
@@ -208,45 +1079,101 @@ pub mod formatting {
     /// 
     /// assert_eq!(format_phone_number("0981123456", false), "0981 123 456");
     /// assert_eq!(format_phone_number("981123456", true), "+595 981 123 456");
+    /// assert_eq!(format_phone_number("+595 981 123456", false), "0981 123 456");
+    /// assert_eq!(format_phone_number("021123456", true), "+595 21 123 456");
+    /// assert_eq!(format_phone_number("12345", true), "12345");
     /// ```
     pub fn format_phone_number(phone: &str, international: bool) -> String {
-        let digits_only: String = phone
-            .chars()
-            .filter(|c| c.is_digit(10))
-            .collect();
-            
-        // Eliminar el 0 inicial si existe y está en formato internacional
-        let digits = if international && digits_only.starts_with('0') {
-            digits_only[1..].to_string()
-        } else {
-            digits_only
-        };
-        
-        // Formatear según longitud
-        if digits.len() >= 9 {
-            let area_code = &digits[..3];
-            let middle = &digits[3..6];
-            let end = &digits[6..9];
-            
-            if international {
-                format!("+595 {} {} {}", area_code, middle, end)
-            } else {
-                format!("0{} {} {}", area_code, middle, end)
+        match super::phone::PhoneNumber::parse(phone) {
+            Ok(number) if international => number.international_format(),
+            Ok(number) => number.local_format(),
+            Err(_) => phone.to_string(),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Entradas que no tienen la forma de ningún documento ni teléfono
+        const ODD_INPUTS: &[&str] = &[
+            "",
+            " ",
+            "1",
+            "-",
+            ".",
+            "+",
+            "😀",
+            "1😀2",
+            "١٢٣٤٥٦٧",
+            "12.3😀4.567",
+            "ñandú",
+            "123456789012345678901234567890",
+            "+595 123456789012345678901234567890",
+        ];
+
+        #[test]
+        fn test_odd_inputs_never_panic_and_come_back_unchanged() {
+            for input in ODD_INPUTS {
+                assert_eq!(format_ci(input), *input, "format_ci({:?})", input);
+                assert_eq!(format_ruc(input), *input, "format_ruc({:?})", input);
+                assert_eq!(format_phone_number(input, false), *input, "format_phone_number({:?})", input);
+                assert_eq!(format_phone_number(input, true), *input, "format_phone_number({:?})", input);
             }
-        } else {
-            // Si no cumple con la longitud esperada, devolver sin cambios
-            if international && !phone.contains("+595") {
-                format!("+595 {}", phone)
-            } else {
-                phone.to_string()
+        }
+
+        #[test]
+        fn test_every_length_never_panics() {
+            for len in 0..=30 {
+                let digits: String = "1234567890".chars().cycle().take(len).collect();
+                for input in [digits.clone(), format!("0{}", digits), format!("{}😀", digits), format!("😀{}", digits)] {
+                    format_ci(&input);
+                    format_ruc(&input);
+                    format_phone_number(&input, false);
+                    format_phone_number(&input, true);
+                }
             }
         }
+
+        #[test]
+        fn test_format_ci() {
+            assert_eq!(format_ci("1234567"), "1.234.567");
+            assert_eq!(format_ci("1.234.567"), "1.234.567");
+            assert_eq!(format_ci("123456"), "1.23.456");
+            assert_eq!(format_ci("12345678"), "12.345.678");
+            assert_eq!(format_ci("123456789"), "123456789");
+            assert_eq!(format_ci("DEMO-ADMIN"), "DEMO-ADMIN");
+        }
+
+        #[test]
+        fn test_format_ruc() {
+            assert_eq!(format_ruc("123456789"), "12345678-9");
+            assert_eq!(format_ruc("12345678-9"), "12345678-9");
+            assert_eq!(format_ruc("12"), "1-2");
+            assert_eq!(format_ruc("1234567890"), "1234567890");
+            assert_eq!(format_ruc("1234567-A"), "1234567-A");
+        }
+
+        #[test]
+        fn test_format_phone_number() {
+            assert_eq!(format_phone_number("0981123456", true), "+595 981 123 456");
+            assert_eq!(format_phone_number("(0981) 123-456", false), "0981 123 456");
+            assert_eq!(format_phone_number("+595981123456", true), "+595 981 123 456");
+            assert_eq!(format_phone_number("981123456", false), "0981 123 456");
+            // Once dígitos no son un número paraguayo
+            assert_eq!(format_phone_number("09811234567", true), "09811234567");
+            // Las líneas fijas separan el código de área
+            assert_eq!(format_phone_number("021123456", false), "021 123 456");
+            assert_eq!(format_phone_number("(021) 123-4567", true), "+595 21 123 4567");
+            assert_eq!(format_phone_number("0521 201234", false), "0521 201 234");
+        }
     }
 }
 
 /// Módulo para manejo de fechas según contexto paraguayo
 pub mod date_utils {
-    use chrono::{NaiveDate, Datelike};
+    use chrono::{NaiveDate, Datelike, Duration};
+    use std::sync::LazyLock;
     
     /// Formatea una fecha según el formato paraguayo (DD/MM/YYYY)
     /// 
@@ -265,49 +1192,587 @@ pub mod date_utils {
         format!("{:02}/{:02}/{:04}", date.day(), date.month(), date.year())
     }
     
+    /// Variable de entorno con los feriados trasladados por decreto, p. ej.
+    /// `2024:boqueron,2026:heroes`
+    pub const MOVED_HOLIDAYS_ENV: &str = "PARAGUAY_MOVED_HOLIDAYS";
+    
+    /// Feriados fijos: mes, día y nombre
+    const FIXED_HOLIDAYS: [(u32, u32, &str); 10] = [
+        (1, 1, "Año Nuevo"),
+        (3, 1, "Día de los Héroes"),
+        (5, 1, "Día del Trabajador"),
+        (5, 14, "Independencia Nacional"),
+        (5, 15, "Independencia Nacional"),
+        (6, 12, "Paz del Chaco"),
+        (8, 15, "Fundación de Asunción"),
+        (9, 29, "Victoria de Boquerón"),
+        (12, 8, "Virgen de Caacupé"),
+        (12, 25, "Navidad"),
+    ];
+    
+    /// Feriado que un decreto puede trasladar al lunes más cercano
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum MovableHoliday {
+        /// Día de los Héroes, 1 de marzo
+        Heroes,
+        /// Victoria de Boquerón, 29 de septiembre
+        Boqueron,
+    }
+    
+    impl MovableHoliday {
+        /// Fecha original del feriado en `year`
+        fn date(self, year: i32) -> Option<NaiveDate> {
+            match self {
+                MovableHoliday::Heroes => NaiveDate::from_ymd_opt(year, 3, 1),
+                MovableHoliday::Boqueron => NaiveDate::from_ymd_opt(year, 9, 29),
+            }
+        }
+    
+        fn parse(name: &str) -> Option<Self> {
+            match name.trim().to_lowercase().as_str() {
+                "heroes" | "héroes" => Some(MovableHoliday::Heroes),
+                "boqueron" | "boquerón" => Some(MovableHoliday::Boqueron),
+                _ => None,
+            }
+        }
+    }
+    
+    /// Interpreta una lista `año:feriado` separada por comas
+    ///
+    /// Las entradas inválidas se registran en el log y se ignoran.
+    fn parse_moved_holidays(raw: &str) -> Vec<(i32, MovableHoliday)> {
+        raw.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let parsed = entry.split_once(':').and_then(|(year, holiday)| {
+                    Some((year.trim().parse().ok()?, MovableHoliday::parse(holiday)?))
+                });
+                if parsed.is_none() {
+                    log::warn!("{}: entrada inválida {:?}, se espera año:heroes o año:boqueron", MOVED_HOLIDAYS_ENV, entry);
+                }
+                parsed
+            })
+            .collect()
+    }
+    
+    /// Feriados trasladados, leídos de `PARAGUAY_MOVED_HOLIDAYS` la primera vez que se consultan
+    static MOVED_HOLIDAYS: LazyLock<Vec<(i32, MovableHoliday)>> =
+        LazyLock::new(|| parse_moved_holidays(&std::env::var(MOVED_HOLIDAYS_ENV).unwrap_or_default()));
+    
+    /// Domingo de Pascua del calendario gregoriano (algoritmo de Meeus/Butcher)
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sai::utils::date_utils::easter_sunday;
+    /// 
+    /// assert_eq!(easter_sunday(2025), NaiveDate::from_ymd_opt(2025, 4, 20).unwrap());
+    /// ```
+    pub fn easter_sunday(year: i32) -> NaiveDate {
+        let a = year.rem_euclid(19);
+        let b = year.div_euclid(100);
+        let c = year.rem_euclid(100);
+        let d = b.div_euclid(4);
+        let e = b.rem_euclid(4);
+        let f = (b + 8).div_euclid(25);
+        let g = (b - f + 1).div_euclid(3);
+        let h = (19 * a + b - d - g + 15).rem_euclid(30);
+        let i = c / 4;
+        let k = c % 4;
+        let l = (32 + 2 * e + 2 * i - h - k).rem_euclid(7);
+        let m = (a + 11 * h + 22 * l) / 451;
+        let month = (h + l - 7 * m + 114) / 31;
+        let day = (h + l - 7 * m + 114) % 31 + 1;
+        
+        NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("la Pascua cae en marzo o abril")
+    }
+    
+    /// Lunes más cercano a una fecha: de martes a jueves el anterior, de
+    /// viernes a domingo el siguiente
+    pub fn nearest_monday(date: NaiveDate) -> NaiveDate {
+        let offset = i64::from(date.weekday().num_days_from_monday());
+        if offset <= 3 {
+            date - Duration::days(offset)
+        } else {
+            date + Duration::days(7 - offset)
+        }
+    }
+    
+    /// Calendario de feriados nacionales de un año, ordenado por fecha
+    /// 
+    /// Incluye los feriados fijos de la Ley 1723, el Jueves y el Viernes Santo,
+    /// y los feriados trasladados al lunes más cercano según
+    /// `PARAGUAY_MOVED_HOLIDAYS`. Las celebraciones que no son días no
+    /// laborables están en [`paraguay_observances`].
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sai::utils::date_utils::paraguay_holidays;
+    /// 
+    /// let holidays = paraguay_holidays(2025);
+    /// assert!(holidays.contains(&(NaiveDate::from_ymd_opt(2025, 4, 18).unwrap(), "Viernes Santo")));
+    /// ```
+    pub fn paraguay_holidays(year: i32) -> Vec<(NaiveDate, &'static str)> {
+        holidays_with(year, &MOVED_HOLIDAYS)
+    }
+    
+    fn holidays_with(year: i32, moved: &[(i32, MovableHoliday)]) -> Vec<(NaiveDate, &'static str)> {
+        let mut holidays: Vec<_> = FIXED_HOLIDAYS
+            .iter()
+            .filter_map(|&(month, day, name)| NaiveDate::from_ymd_opt(year, month, day).map(|date| (date, name)))
+            .collect();
+        
+        for original in moved.iter().filter(|(moved_year, _)| *moved_year == year).filter_map(|(_, holiday)| holiday.date(year)) {
+            if let Some(holiday) = holidays.iter_mut().find(|(date, _)| *date == original) {
+                holiday.0 = nearest_monday(original);
+            }
+        }
+        
+        let easter = easter_sunday(year);
+        holidays.push((easter - Duration::days(3), "Jueves Santo"));
+        holidays.push((easter - Duration::days(2), "Viernes Santo"));
+        
+        holidays.sort_by_key(|(date, _)| *date);
+        holidays
+    }
+    
+    /// Celebraciones de un año que no son feriados nacionales, ordenadas por fecha
+    /// 
+    /// San Blas, el martes de Carnaval y Corpus Christi se muestran en el
+    /// calendario escolar pero no cuentan para los días hábiles.
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sai::utils::date_utils::{is_paraguay_holiday, paraguay_observances};
+    /// 
+    /// let san_blas = NaiveDate::from_ymd_opt(2025, 2, 3).unwrap();
+    /// assert!(paraguay_observances(2025).contains(&(san_blas, "San Blas")));
+    /// assert!(!is_paraguay_holiday(&san_blas));
+    /// ```
+    pub fn paraguay_observances(year: i32) -> Vec<(NaiveDate, &'static str)> {
+        let easter = easter_sunday(year);
+        let mut observances: Vec<_> = NaiveDate::from_ymd_opt(year, 2, 3)
+            .map(|date| (date, "San Blas"))
+            .into_iter()
+            .collect();
+        observances.push((easter - Duration::days(47), "Carnaval"));
+        observances.push((easter + Duration::days(60), "Corpus Christi"));
+        
+        observances.sort_by_key(|(date, _)| *date);
+        observances
+    }
+    
     /// Verifica si una fecha es un feriado en Paraguay
     /// 
     /// # Argumentos
     /// * `date` - Fecha a verificar
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sai::utils::date_utils::is_paraguay_holiday;
+    /// 
+    /// assert!(is_paraguay_holiday(&NaiveDate::from_ymd_opt(2024, 3, 29).unwrap())); // Viernes Santo
+    /// assert!(!is_paraguay_holiday(&NaiveDate::from_ymd_opt(2024, 4, 1).unwrap()));
+    /// ```
     pub fn is_paraguay_holiday(date: &NaiveDate) -> bool {
-        let (day, month, year) = (date.day(), date.month(), date.year());
-        
-        // Feriados fijos
-        if (day == 1 && month == 1) ||    // Año Nuevo
-           (day == 1 && month == 5) ||    // Día del Trabajador
-           (day == 15 && month == 5) ||   // Independencia Nacional
-           (day == 12 && month == 6) ||   // Paz del Chaco
-           (day == 15 && month == 8) ||   // Fundación de Asunción
-           (day == 29 && month == 9) ||   // Victoria de Boquerón
-           (day == 8 && month == 12) ||   // Virgen de Caacupé
-           (day == 25 && month == 12) {   // Navidad
-            return true;
+        paraguay_holidays(date.year()).iter().any(|(holiday, _)| holiday == date)
+    }
+    
+    /// Fechas consecutivas desde `start_date`, hasta `NaiveDate::MAX` inclusive
+    ///
+    /// `NaiveDate::iter_days` nunca devuelve `NaiveDate::MAX`.
+    fn days_from(start_date: &NaiveDate) -> impl Iterator<Item = NaiveDate> {
+        std::iter::successors(Some(*start_date), NaiveDate::succ_opt)
+    }
+    
+    /// Feriados del último año consultado, para no recalcular el calendario día por día
+    #[derive(Default)]
+    struct HolidayCache {
+        year: Option<i32>,
+        dates: Vec<NaiveDate>,
+    }
+    
+    impl HolidayCache {
+        /// Indica si una fecha es día hábil: de lunes a viernes y no feriado
+        fn is_business_day(&mut self, date: &NaiveDate) -> bool {
+            if date.weekday().number_from_monday() > 5 {
+                return false;
+            }
+            if self.year != Some(date.year()) {
+                self.year = Some(date.year());
+                self.dates = paraguay_holidays(date.year()).into_iter().map(|(date, _)| date).collect();
+            }
+            !self.dates.contains(date)
         }
-        
-        // TODO: Implementar cálculo de feriados móviles (Semana Santa, etc.)
-        // Requiere algoritmos específicos para calcular fechas como Semana Santa
-        
-        false
     }
     
-    /// Calcula la cantidad de días hábiles entre dos fechas
+    /// Calcula la cantidad de días hábiles entre dos fechas, ambas incluidas
+    /// 
+    /// Si `start_date` es posterior a `end_date` el rango está vacío y el
+    /// resultado es 0.
     /// 
     /// # Argumentos
-    /// * `start_date` - Fecha de inicio
-    /// * `end_date` - Fecha de fin
+    /// * `start_date` - Fecha de inicio (incluida)
+    /// * `end_date` - Fecha de fin (incluida)
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sai::utils::date_utils::business_days_between;
+    /// 
+    /// let monday = NaiveDate::from_ymd_opt(2023, 6, 5).unwrap();
+    /// let friday = NaiveDate::from_ymd_opt(2023, 6, 9).unwrap();
+    /// assert_eq!(business_days_between(&monday, &friday), 5);
+    /// assert_eq!(business_days_between(&friday, &monday), 0);
+    /// ```
     pub fn business_days_between(start_date: &NaiveDate, end_date: &NaiveDate) -> u32 {
-        let mut count = 0;
-        let mut current_date = *start_date;
+        let mut holidays = HolidayCache::default();
+        days_from(start_date)
+            .take_while(|date| date <= end_date)
+            .filter(|date| holidays.is_business_day(date))
+            .count() as u32
+    }
+    
+    /// Calcula la cantidad de días hábiles desde `start_date` (incluida) hasta
+    /// `end_date` (excluida)
+    /// 
+    /// Es la forma de contar plazos: el día del vencimiento no cuenta como
+    /// transcurrido. Un rango vacío o invertido da 0.
+    /// 
+    /// # Argumentos
+    /// * `start_date` - Fecha de inicio (incluida)
+    /// * `end_date` - Fecha de fin (excluida)
+    pub fn business_days_between_exclusive(start_date: &NaiveDate, end_date: &NaiveDate) -> u32 {
+        let mut holidays = HolidayCache::default();
+        days_from(start_date)
+            .take_while(|date| date < end_date)
+            .filter(|date| holidays.is_business_day(date))
+            .count() as u32
+    }
+    
+    /// Primer día hábil a partir de `date`: la misma fecha si es hábil, si no
+    /// el siguiente lunes a viernes que no sea feriado
+    /// 
+    /// Sirve para correr un vencimiento que cae en fin de semana o feriado.
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sai::utils::date_utils::next_business_day;
+    /// 
+    /// // Viernes 15 de agosto de 2025, Fundación de Asunción
+    /// let holiday = NaiveDate::from_ymd_opt(2025, 8, 15).unwrap();
+    /// assert_eq!(next_business_day(&holiday), NaiveDate::from_ymd_opt(2025, 8, 18).unwrap());
+    /// ```
+    pub fn next_business_day(date: &NaiveDate) -> NaiveDate {
+        let mut holidays = HolidayCache::default();
+        days_from(date)
+            .find(|date| holidays.is_business_day(date))
+            .unwrap_or(NaiveDate::MAX)
+    }
+    
+    /// Fecha que cae `days` días hábiles después de `start`
+    /// 
+    /// `start` no cuenta: el resultado es el día hábil número `days` posterior
+    /// a `start`, así que [`business_days_between`] desde el día siguiente a
+    /// `start` hasta el resultado da `days`. Con `days` igual a 0 devuelve
+    /// [`next_business_day`] de `start`. Las fechas posteriores a
+    /// `NaiveDate::MAX` se recortan a `NaiveDate::MAX`.
+    /// 
+    /// # Ejemplos
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use sai::utils::date_utils::add_business_days;
+    /// 
+    /// // Del miércoles 13 de agosto de 2025: jueves 14, (feriado 15), lunes 18
+    /// let wednesday = NaiveDate::from_ymd_opt(2025, 8, 13).unwrap();
+    /// assert_eq!(add_business_days(&wednesday, 2), NaiveDate::from_ymd_opt(2025, 8, 18).unwrap());
+    /// ```
+    pub fn add_business_days(start: &NaiveDate, days: u32) -> NaiveDate {
+        if days == 0 {
+            return next_business_day(start);
+        }
         
-        while current_date <= *end_date {
-            // Si no es fin de semana ni feriado
-            if current_date.weekday().number_from_monday() <= 5 && !is_paraguay_holiday(&current_date) {
-                count += 1;
+        let mut holidays = HolidayCache::default();
+        days_from(start)
+            .skip(1)
+            .filter(|date| holidays.is_business_day(date))
+            .nth(days as usize - 1)
+            .unwrap_or(NaiveDate::MAX)
+    }
+    
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+    
+        fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+            NaiveDate::from_ymd_opt(year, month, day).unwrap()
+        }
+    
+        fn is_business_day(date: &NaiveDate) -> bool {
+            HolidayCache::default().is_business_day(date)
+        }
+    
+        #[test]
+        fn test_easter_sunday() {
+            assert_eq!(easter_sunday(2000), date(2000, 4, 23));
+            assert_eq!(easter_sunday(2019), date(2019, 4, 21));
+            assert_eq!(easter_sunday(2024), date(2024, 3, 31));
+            assert_eq!(easter_sunday(2025), date(2025, 4, 20));
+            assert_eq!(easter_sunday(2026), date(2026, 4, 5));
+            assert_eq!(easter_sunday(2038), date(2038, 4, 25));
+        }
+    
+        #[test]
+        fn test_holy_week_2024_to_2026() {
+            let holy_weeks = [
+                (date(2024, 3, 28), date(2024, 3, 29)),
+                (date(2025, 4, 17), date(2025, 4, 18)),
+                (date(2026, 4, 2), date(2026, 4, 3)),
+            ];
+    
+            for (thursday, friday) in holy_weeks {
+                let holidays = paraguay_holidays(thursday.year());
+                assert!(holidays.contains(&(thursday, "Jueves Santo")), "{}", thursday);
+                assert!(holidays.contains(&(friday, "Viernes Santo")), "{}", friday);
+                assert!(is_paraguay_holiday(&thursday));
+                assert!(is_paraguay_holiday(&friday));
+                // El miércoles y el lunes de Pascua son días hábiles
+                assert!(!is_paraguay_holiday(&(thursday - Duration::days(1))));
+                assert!(!is_paraguay_holiday(&(friday + Duration::days(3))));
             }
-            current_date = current_date.succ_opt().unwrap_or(*end_date);
         }
-        
-        count
+    
+        /// Mes y día
+        type MonthDay = (u32, u32);
+
+        /// Pascua, martes de Carnaval, Jueves Santo y Corpus Christi de 2023 a 2030
+        const MOVEABLE_FEASTS: [(i32, MonthDay, MonthDay, MonthDay, MonthDay); 8] = [
+            (2023, (4, 9), (2, 21), (4, 6), (6, 8)),
+            (2024, (3, 31), (2, 13), (3, 28), (5, 30)),
+            (2025, (4, 20), (3, 4), (4, 17), (6, 19)),
+            (2026, (4, 5), (2, 17), (4, 2), (6, 4)),
+            (2027, (3, 28), (2, 9), (3, 25), (5, 27)),
+            (2028, (4, 16), (2, 29), (4, 13), (6, 15)),
+            (2029, (4, 1), (2, 13), (3, 29), (5, 31)),
+            (2030, (4, 21), (3, 5), (4, 18), (6, 20)),
+        ];
+    
+        #[test]
+        fn test_moveable_feasts_2023_to_2030() {
+            for (year, easter, carnival, thursday, corpus) in MOVEABLE_FEASTS {
+                let day = |(month, day): (u32, u32)| date(year, month, day);
+                assert_eq!(easter_sunday(year), day(easter), "{}", year);
+    
+                let holidays = paraguay_holidays(year);
+                assert!(holidays.contains(&(day(thursday), "Jueves Santo")), "{}", year);
+                assert!(holidays.contains(&(day(thursday) + Duration::days(1), "Viernes Santo")), "{}", year);
+    
+                let observances = paraguay_observances(year);
+                assert!(observances.contains(&(day(carnival), "Carnaval")), "{}", year);
+                assert!(observances.contains(&(day(corpus), "Corpus Christi")), "{}", year);
+                assert!(!is_paraguay_holiday(&day(carnival)), "{}", year);
+                assert!(!is_paraguay_holiday(&day(corpus)), "{}", year);
+    
+                // Carnaval cae en martes y Corpus Christi en jueves
+                assert_eq!(day(carnival).weekday(), chrono::Weekday::Tue, "{}", year);
+                assert_eq!(day(corpus).weekday(), chrono::Weekday::Thu, "{}", year);
+            }
+        }
+    
+        #[test]
+        fn test_heroes_every_year() {
+            for year in 2023..=2030 {
+                assert!(is_paraguay_holiday(&date(year, 3, 1)), "{}", year);
+            }
+        }
+    
+        #[test]
+        fn test_san_blas_is_a_business_day() {
+            for year in 2023..=2030 {
+                assert!(!is_paraguay_holiday(&date(year, 2, 3)), "{}", year);
+                assert!(paraguay_observances(year).contains(&(date(year, 2, 3), "San Blas")), "{}", year);
+            }
+            // Lunes 3 de febrero de 2025
+            assert_eq!(next_business_day(&date(2025, 2, 3)), date(2025, 2, 3));
+            assert_eq!(business_days_between(&date(2025, 2, 3), &date(2025, 2, 7)), 5);
+        }
+    
+        #[test]
+        fn test_easter_week_deadlines() {
+            // Miércoles 27 de marzo de 2024: el Jueves y el Viernes Santo se saltan
+            assert_eq!(add_business_days(&date(2024, 3, 27), 1), date(2024, 4, 1));
+            assert_eq!(next_business_day(&date(2024, 3, 28)), date(2024, 4, 1));
+            // Del lunes 25 al domingo 31 de marzo de 2024
+            assert_eq!(business_days_between(&date(2024, 3, 25), &date(2024, 3, 31)), 3);
+            // Corpus Christi 2026 (jueves 4 de junio) y el martes de Carnaval 2027 son hábiles
+            assert_eq!(next_business_day(&date(2026, 6, 4)), date(2026, 6, 4));
+            assert_eq!(add_business_days(&date(2027, 2, 8), 1), date(2027, 2, 9));
+        }
+    
+        #[test]
+        fn test_calendar_is_sorted_and_named() {
+            let holidays = paraguay_holidays(2025);
+    
+            assert_eq!(holidays.len(), 12);
+            assert!(holidays.windows(2).all(|pair| pair[0].0 < pair[1].0));
+            assert_eq!(holidays[0], (date(2025, 1, 1), "Año Nuevo"));
+            assert_eq!(holidays[1], (date(2025, 3, 1), "Día de los Héroes"));
+            assert_eq!(holidays[2], (date(2025, 4, 17), "Jueves Santo"));
+    
+            let observances = paraguay_observances(2025);
+            assert_eq!(observances, [
+                (date(2025, 2, 3), "San Blas"),
+                (date(2025, 3, 4), "Carnaval"),
+                (date(2025, 6, 19), "Corpus Christi"),
+            ]);
+        }
+    
+        #[test]
+        fn test_nearest_monday() {
+            // Del lunes 22 al domingo 28 de septiembre de 2025
+            let expected = [22, 22, 22, 22, 29, 29, 29];
+            for (offset, monday) in expected.into_iter().enumerate() {
+                let day = date(2025, 9, 22) + Duration::days(offset as i64);
+                assert_eq!(nearest_monday(day), date(2025, 9, monday), "{}", day);
+            }
+        }
+    
+        #[test]
+        fn test_moved_holidays() {
+            let moved = [(2026, MovableHoliday::Heroes), (2026, MovableHoliday::Boqueron)];
+            let holidays = holidays_with(2026, &moved);
+    
+            // El domingo 1 de marzo pasa al lunes 2 y el martes 29 de septiembre al lunes 28
+            assert!(holidays.contains(&(date(2026, 3, 2), "Día de los Héroes")));
+            assert!(holidays.contains(&(date(2026, 9, 28), "Victoria de Boquerón")));
+            assert!(!holidays.iter().any(|(day, _)| *day == date(2026, 3, 1) || *day == date(2026, 9, 29)));
+    
+            // Los traslados solo valen para el año del decreto
+            assert!(holidays_with(2025, &moved).contains(&(date(2025, 9, 29), "Victoria de Boquerón")));
+        }
+    
+        #[test]
+        fn test_parse_moved_holidays() {
+            assert_eq!(
+                parse_moved_holidays("2024:boqueron, 2026:Héroes"),
+                vec![(2024, MovableHoliday::Boqueron), (2026, MovableHoliday::Heroes)]
+            );
+            assert_eq!(parse_moved_holidays("2024:navidad,boqueron,,x:heroes"), vec![]);
+            assert_eq!(parse_moved_holidays(""), vec![]);
+        }
+    
+        #[test]
+        fn test_holy_week_is_not_business_days() {
+            // Del lunes 14 al viernes 18 de abril de 2025
+            assert_eq!(business_days_between(&date(2025, 4, 14), &date(2025, 4, 18)), 3);
+        }
+    
+        #[test]
+        fn test_week_with_holiday() {
+            // Del lunes 12 al domingo 18 de junio de 2023; el 12 es la Paz del Chaco
+            assert_eq!(business_days_between(&date(2023, 6, 12), &date(2023, 6, 18)), 4);
+            assert_eq!(business_days_between_exclusive(&date(2023, 6, 12), &date(2023, 6, 16)), 3);
+        }
+    
+        #[test]
+        fn test_week_with_asuncion_foundation_day() {
+            // Del lunes 11 al domingo 17 de agosto de 2025; el viernes 15 es feriado
+            let (monday, friday, sunday) = (date(2025, 8, 11), date(2025, 8, 15), date(2025, 8, 17));
+    
+            assert_eq!(business_days_between(&monday, &sunday), 4);
+            assert_eq!(business_days_between_exclusive(&monday, &friday), 4);
+            assert_eq!(business_days_between(&sunday, &monday), 0);
+    
+            assert_eq!(next_business_day(&date(2025, 8, 14)), date(2025, 8, 14));
+            assert_eq!(next_business_day(&friday), date(2025, 8, 18));
+            assert_eq!(next_business_day(&date(2025, 8, 16)), date(2025, 8, 18));
+    
+            assert_eq!(add_business_days(&date(2025, 8, 14), 1), date(2025, 8, 18));
+            assert_eq!(add_business_days(&monday, 4), date(2025, 8, 18));
+            assert_eq!(add_business_days(&monday, 5), date(2025, 8, 19));
+            assert_eq!(add_business_days(&friday, 0), date(2025, 8, 18));
+        }
+    
+        #[test]
+        fn test_add_business_days_matches_count() {
+            let start = date(2025, 3, 25);
+            for days in 1..60 {
+                let due = add_business_days(&start, days);
+                assert!(is_business_day(&due), "{}", due);
+                assert_eq!(business_days_between(&start.succ_opt().unwrap(), &due), days, "{} + {}", start, days);
+            }
+        }
+    
+        #[test]
+        fn test_add_business_days_saturates_at_max_date() {
+            assert_eq!(add_business_days(&(NaiveDate::MAX - Duration::days(3)), 30), NaiveDate::MAX);
+        }
+    
+        #[test]
+        fn test_single_day() {
+            let saturday = date(2023, 6, 10);
+            let monday = date(2023, 6, 5);
+    
+            assert_eq!(business_days_between(&saturday, &saturday), 0);
+            assert_eq!(business_days_between(&monday, &monday), 1);
+            // Con el fin excluido, un único día es un rango vacío
+            assert_eq!(business_days_between_exclusive(&monday, &monday), 0);
+        }
+    
+        #[test]
+        fn test_inverted_range_is_empty() {
+            let start = date(2023, 6, 5);
+            for days in 1..40 {
+                let end = start - Duration::days(days);
+                assert_eq!(business_days_between(&start, &end), 0);
+                assert_eq!(business_days_between_exclusive(&start, &end), 0);
+            }
+        }
+    
+        #[test]
+        fn test_ranges_split_at_any_day_add_up() {
+            let start = date(2023, 1, 1);
+            let end = date(2023, 12, 31);
+            let total = business_days_between(&start, &end);
+    
+            for middle in days_from(&start).take_while(|day| *day <= end).step_by(7) {
+                assert_eq!(
+                    business_days_between_exclusive(&start, &middle) + business_days_between(&middle, &end),
+                    total
+                );
+            }
+        }
+    
+        #[test]
+        fn test_exclusive_is_inclusive_minus_the_last_day() {
+            let start = date(2024, 2, 20);
+            for days in 0..30 {
+                let end = start + Duration::days(days);
+                let last_day = u32::from(is_business_day(&end));
+                assert_eq!(
+                    business_days_between_exclusive(&start, &end),
+                    business_days_between(&start, &end) - last_day
+                );
+            }
+        }
+    
+        #[test]
+        fn test_range_ending_at_max_date_terminates() {
+            let start = NaiveDate::MAX - Duration::days(10);
+    
+            let inclusive = business_days_between(&start, &NaiveDate::MAX);
+            let exclusive = business_days_between_exclusive(&start, &NaiveDate::MAX);
+    
+            // Once días, con un fin de semana y Navidad entre ellos
+            assert!((5..=8).contains(&inclusive));
+            assert_eq!(exclusive, inclusive - u32::from(is_business_day(&NaiveDate::MAX)));
+            assert_eq!(business_days_between(&NaiveDate::MAX, &NaiveDate::MAX), u32::from(is_business_day(&NaiveDate::MAX)));
+        }
     }
 }
 
@@ -341,8 +1806,168 @@ pub mod id_generator {
         format!("{}-{:04}", department_code.to_uppercase(), sequence)
     }
     
-    /// Genera un código de factura según estándares paraguayos
-    /// 
+    /// Mayor código de establecimiento o de punto de expedición (3 dígitos)
+    pub const MAX_INVOICE_PREFIX: u16 = 999;
+    /// Mayor número secuencial de factura (7 dígitos)
+    pub const MAX_INVOICE_SEQUENCE: u32 = 9_999_999;
+
+    /// Errores al generar o interpretar identificadores
+    #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+    pub enum IdError {
+        #[error("El código de establecimiento debe estar entre 1 y 999, se recibió {0}")]
+        InvalidBranch(u16),
+        #[error("El punto de expedición debe estar entre 1 y 999, se recibió {0}")]
+        InvalidPointOfSale(u16),
+        #[error("El número de factura debe estar entre 1 y 9999999, se recibió {0}")]
+        InvalidSequence(u32),
+        #[error("Número de factura con formato inválido, se esperaba EEE-PPP-NNNNNNN: {0}")]
+        MalformedInvoiceNumber(String),
+        #[error("Se agotó la numeración del punto de expedición {0}")]
+        SequenceExhausted(String),
+    }
+
+    /// Genera un número de factura con el formato de la SET: `EEE-PPP-NNNNNNN`
+    ///
     /// # Argumentos
-    /// * `branch` - Código de sucursal (
+    /// * `branch` - Código del establecimiento (sucursal), de 1 a 999
+    /// * `point_of_sale` - Punto de expedición (caja), de 1 a 999
+    /// * `sequence` - Número secuencial dentro del punto de expedición, de 1 a 9999999
+    ///
+    /// La numeración de cada timbrado empieza en `001-001-0000001`, por eso
+    /// ningún componente puede ser cero.
+    ///
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::id_generator::generate_invoice_number;
+    ///
+    /// assert_eq!(generate_invoice_number(1, 2, 1234).unwrap(), "001-002-0001234");
+    /// assert!(generate_invoice_number(1000, 1, 1).is_err());
+    /// ```
+    pub fn generate_invoice_number(branch: u16, point_of_sale: u16, sequence: u32) -> Result<String, IdError> {
+        if branch == 0 || branch > MAX_INVOICE_PREFIX {
+            return Err(IdError::InvalidBranch(branch));
+        }
+        if point_of_sale == 0 || point_of_sale > MAX_INVOICE_PREFIX {
+            return Err(IdError::InvalidPointOfSale(point_of_sale));
+        }
+        if sequence == 0 || sequence > MAX_INVOICE_SEQUENCE {
+            return Err(IdError::InvalidSequence(sequence));
+        }
 
+        Ok(format!("{:03}-{:03}-{:07}", branch, point_of_sale, sequence))
+    }
+
+    /// Separa un número de factura `EEE-PPP-NNNNNNN` en establecimiento,
+    /// punto de expedición y número secuencial
+    ///
+    /// Es la inversa de [`generate_invoice_number`]: exige los guiones y la
+    /// cantidad exacta de dígitos de cada componente, y rechaza los ceros.
+    pub fn parse_invoice_number(invoice_number: &str) -> Result<(u16, u16, u32), IdError> {
+        let malformed = || IdError::MalformedInvoiceNumber(invoice_number.to_string());
+
+        let parts: Vec<&str> = invoice_number.trim().split('-').collect();
+        let [branch, point_of_sale, sequence] = parts.as_slice() else {
+            return Err(malformed());
+        };
+        let digits = |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+        if !digits(branch, 3) || !digits(point_of_sale, 3) || !digits(sequence, 7) {
+            return Err(malformed());
+        }
+
+        // Los dígitos ya se verificaron, así que los valores caben en cada tipo
+        let branch: u16 = branch.parse().map_err(|_| malformed())?;
+        let point_of_sale: u16 = point_of_sale.parse().map_err(|_| malformed())?;
+        let sequence: u32 = sequence.parse().map_err(|_| malformed())?;
+
+        generate_invoice_number(branch, point_of_sale, sequence)?;
+        Ok((branch, point_of_sale, sequence))
+    }
+
+    /// Número de factura siguiente del mismo punto de expedición
+    ///
+    /// Devuelve `IdError::SequenceExhausted` después de `NNN-NNN-9999999`:
+    /// hay que habilitar un nuevo punto de expedición.
+    ///
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::id_generator::next_invoice_number;
+    ///
+    /// assert_eq!(next_invoice_number("001-001-0000123").unwrap(), "001-001-0000124");
+    /// ```
+    pub fn next_invoice_number(invoice_number: &str) -> Result<String, IdError> {
+        let (branch, point_of_sale, sequence) = parse_invoice_number(invoice_number)?;
+        if sequence == MAX_INVOICE_SEQUENCE {
+            return Err(IdError::SequenceExhausted(format!("{:03}-{:03}", branch, point_of_sale)));
+        }
+
+        generate_invoice_number(branch, point_of_sale, sequence + 1)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_generate_invoice_number() {
+            assert_eq!(generate_invoice_number(1, 2, 1234).unwrap(), "001-002-0001234");
+            assert_eq!(generate_invoice_number(1, 1, 1).unwrap(), "001-001-0000001");
+            assert_eq!(generate_invoice_number(999, 999, 9_999_999).unwrap(), "999-999-9999999");
+        }
+
+        #[test]
+        fn test_components_out_of_range() {
+            assert_eq!(generate_invoice_number(0, 1, 1), Err(IdError::InvalidBranch(0)));
+            assert_eq!(generate_invoice_number(1000, 1, 1), Err(IdError::InvalidBranch(1000)));
+            assert_eq!(generate_invoice_number(1, 0, 1), Err(IdError::InvalidPointOfSale(0)));
+            assert_eq!(generate_invoice_number(1, 1000, 1), Err(IdError::InvalidPointOfSale(1000)));
+            assert_eq!(generate_invoice_number(1, 1, 0), Err(IdError::InvalidSequence(0)));
+            assert_eq!(generate_invoice_number(1, 1, 10_000_000), Err(IdError::InvalidSequence(10_000_000)));
+        }
+
+        #[test]
+        fn test_round_trip() {
+            for (branch, point_of_sale, sequence) in [(1, 1, 1), (1, 2, 1234), (12, 340, 5_600_789), (999, 999, 9_999_999)] {
+                let number = generate_invoice_number(branch, point_of_sale, sequence).unwrap();
+                assert_eq!(parse_invoice_number(&number).unwrap(), (branch, point_of_sale, sequence));
+            }
+            assert_eq!(parse_invoice_number(" 001-002-0001234 ").unwrap(), (1, 2, 1234));
+        }
+
+        #[test]
+        fn test_malformed_numbers_rejected() {
+            for input in [
+                "",
+                "001-002",
+                "001-002-0001234-5",
+                "1-2-1234",
+                "001-002-001234",
+                "0001-002-0001234",
+                "001 002 0001234",
+                "001-00a-0001234",
+                "001-+02-0001234",
+                "001--002-0001234",
+                "００1-002-0001234",
+            ] {
+                assert!(
+                    matches!(parse_invoice_number(input), Err(IdError::MalformedInvoiceNumber(_))),
+                    "{:?}",
+                    input
+                );
+            }
+
+            assert_eq!(parse_invoice_number("000-001-0000001"), Err(IdError::InvalidBranch(0)));
+            assert_eq!(parse_invoice_number("001-001-0000000"), Err(IdError::InvalidSequence(0)));
+        }
+
+        #[test]
+        fn test_next_invoice_number() {
+            assert_eq!(next_invoice_number("001-001-0000123").unwrap(), "001-001-0000124");
+            assert_eq!(next_invoice_number("001-001-0999999").unwrap(), "001-001-1000000");
+            assert_eq!(
+                next_invoice_number("002-003-9999999"),
+                Err(IdError::SequenceExhausted("002-003".to_string()))
+            );
+            assert!(matches!(next_invoice_number("basura"), Err(IdError::MalformedInvoiceNumber(_))));
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! Paginación por desplazamiento de los listados
+//!
+//! Los servicios devuelven un [`PaginatedResponse`] con la página pedida y el
+//! total de filas que coinciden con el filtro, así los clientes saben cuántas
+//! páginas hay sin una segunda consulta.
+
+use serde::{Deserialize, Serialize};
+
+/// Una página de un listado junto con el total de resultados
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    /// Elementos de la página
+    pub data: Vec<T>,
+    /// Elementos que coinciden con el filtro en todas las páginas
+    pub total: i64,
+    /// Página actual, desde 1
+    pub page: u32,
+    /// Tamaño de página pedido
+    pub page_size: u32,
+    /// Cantidad de páginas; 0 cuando no hay resultados
+    pub total_pages: u32,
+}
+
+impl<T> PaginatedResponse<T> {
+    /// Arma la página y calcula `total_pages` a partir del total
+    ///
+    /// # Ejemplos
+    /// ```
+    /// use sai::utils::pagination::PaginatedResponse;
+    ///
+    /// let page = PaginatedResponse::new(vec!["b"], 3, 2, 1);
+    /// assert_eq!(page.total_pages, 3);
+    /// ```
+    pub fn new(data: Vec<T>, total: i64, page: u32, page_size: u32) -> Self {
+        Self {
+            data,
+            total,
+            page,
+            page_size,
+            total_pages: total_pages(total, page_size),
+        }
+    }
+
+    /// Convierte los elementos de la página conservando los totales
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> PaginatedResponse<U> {
+        PaginatedResponse {
+            data: self.data.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            page_size: self.page_size,
+            total_pages: self.total_pages,
+        }
+    }
+}
+
+/// Cantidad de páginas de `page_size` elementos necesarias para `total`
+pub fn total_pages(total: i64, page_size: u32) -> u32 {
+    if total <= 0 || page_size == 0 {
+        return 0;
+    }
+    let pages = (total + i64::from(page_size) - 1) / i64::from(page_size);
+    u32::try_from(pages).unwrap_or(u32::MAX)
+}
+
+/// Desplazamiento SQL (`OFFSET`) de la página `page`; la página 0 se trata como la 1
+pub fn offset(page: u32, page_size: u32) -> i64 {
+    i64::from(page.max(1) - 1) * i64::from(page_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_total_pages() {
+        assert_eq!(total_pages(0, 20), 0);
+        assert_eq!(total_pages(1, 20), 1);
+        assert_eq!(total_pages(20, 20), 1);
+        assert_eq!(total_pages(21, 20), 2);
+        assert_eq!(total_pages(3, 1), 3);
+        assert_eq!(total_pages(5, 0), 0);
+        assert_eq!(total_pages(-1, 20), 0);
+    }
+
+    #[test]
+    fn test_offset() {
+        assert_eq!(offset(1, 20), 0);
+        assert_eq!(offset(2, 1), 1);
+        assert_eq!(offset(3, 20), 40);
+        assert_eq!(offset(0, 20), 0);
+        assert_eq!(offset(u32::MAX, 100), i64::from(u32::MAX - 1) * 100);
+    }
+
+    #[test]
+    fn test_serialized_shape() {
+        let page = PaginatedResponse::new(vec![1, 2], 5, 1, 2).map(|n| n * 10);
+
+        assert_eq!(
+            serde_json::to_value(&page).unwrap(),
+            serde_json::json!({ "data": [10, 20], "total": 5, "page": 1, "page_size": 2, "total_pages": 3 })
+        );
+    }
+}
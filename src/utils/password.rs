@@ -0,0 +1,261 @@
+//! Hash y verificación de contraseñas con Argon2id
+//!
+//! Los parámetros se leen una sola vez de las variables `ARGON2_*` (ver
+//! [`Argon2Config::from_env`]). Cada hash guarda los parámetros con los que
+//! se calculó en formato PHC, así que cambiarlos no invalida las contraseñas
+//! ya almacenadas.
+//!
+//! Los hashes bcrypt (`$2a$`, `$2b$`, `$2y$`) guardados antes de Argon2id se
+//! siguen verificando; [`needs_rehash`] indica cuándo conviene reemplazarlos
+//! por uno Argon2id en el próximo ingreso.
+
+use std::sync::LazyLock;
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Algorithm, Argon2, Params, Version,
+};
+
+/// Variable de entorno con la memoria usada por cada hash, en KiB
+pub const ARGON2_MEMORY_ENV: &str = "ARGON2_MEMORY_KIB";
+/// Variable de entorno con la cantidad de pasadas sobre la memoria
+pub const ARGON2_ITERATIONS_ENV: &str = "ARGON2_ITERATIONS";
+/// Variable de entorno con la cantidad de hilos (carriles) de cada hash
+pub const ARGON2_PARALLELISM_ENV: &str = "ARGON2_PARALLELISM";
+
+/// Parámetros de costo de Argon2id
+///
+/// Los valores por defecto son los mínimos recomendados por OWASP: 19 MiB de
+/// memoria, 2 pasadas y un hilo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Config {
+    /// Memoria por hash, en KiB
+    pub memory_kib: u32,
+    /// Pasadas sobre la memoria
+    pub iterations: u32,
+    /// Hilos (carriles)
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Config {
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl Argon2Config {
+    /// Carga los parámetros desde las variables `ARGON2_*`
+    ///
+    /// Las variables no definidas o inválidas toman el valor por defecto; las
+    /// inválidas además se registran en el log. Si la combinación resultante
+    /// no es aceptada por Argon2 se usan todos los valores por defecto.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let config = Self {
+            memory_kib: parse_setting(&lookup, ARGON2_MEMORY_ENV, defaults.memory_kib),
+            iterations: parse_setting(&lookup, ARGON2_ITERATIONS_ENV, defaults.iterations),
+            parallelism: parse_setting(&lookup, ARGON2_PARALLELISM_ENV, defaults.parallelism),
+        };
+
+        match config.params() {
+            Ok(_) => config,
+            Err(e) => {
+                log::warn!("Parámetros de Argon2 inválidos ({}), se usan los valores por defecto", e);
+                defaults
+            }
+        }
+    }
+
+    fn params(&self) -> Result<Params, argon2::Error> {
+        Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+    }
+
+    fn hasher(&self) -> Result<Argon2<'static>, PasswordError> {
+        let params = self.params().map_err(PasswordError::Params)?;
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+}
+
+fn parse_setting(lookup: &impl Fn(&str) -> Option<String>, name: &str, default: u32) -> u32 {
+    let raw = match lookup(name) {
+        Some(raw) if !raw.trim().is_empty() => raw,
+        _ => return default,
+    };
+
+    match raw.trim().parse::<u32>() {
+        Ok(value) if value > 0 => value,
+        _ => {
+            log::warn!("{} inválido ({}), se usa el valor por defecto", name, raw);
+            default
+        }
+    }
+}
+
+/// Parámetros leídos del entorno la primera vez que se calcula un hash
+static CONFIG: LazyLock<Argon2Config> = LazyLock::new(Argon2Config::from_env);
+
+/// Errores al calcular el hash de una contraseña
+#[derive(Debug, thiserror::Error)]
+pub enum PasswordError {
+    #[error("Parámetros de Argon2 inválidos: {0}")]
+    Params(argon2::Error),
+    #[error("No se pudo calcular el hash de la contraseña: {0}")]
+    Hash(argon2::password_hash::Error),
+}
+
+/// Calcula el hash Argon2id de una contraseña con los parámetros del entorno
+///
+/// # Ejemplos
+/// ```
+/// use sai::utils::password::{hash_password, verify_password};
+///
+/// let hash = hash_password("clave-segura").unwrap();
+/// assert!(hash.starts_with("$argon2id$"));
+/// assert!(verify_password("clave-segura", &hash));
+/// ```
+pub fn hash_password(plain: &str) -> Result<String, PasswordError> {
+    hash_password_with(plain, &CONFIG)
+}
+
+/// Calcula el hash Argon2id de una contraseña con parámetros explícitos
+pub fn hash_password_with(plain: &str, config: &Argon2Config) -> Result<String, PasswordError> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = config
+        .hasher()?
+        .hash_password(plain.as_bytes(), &salt)
+        .map_err(PasswordError::Hash)?;
+
+    Ok(hash.to_string())
+}
+
+/// Verifica una contraseña contra un hash en formato PHC o bcrypt
+///
+/// Usa los parámetros guardados en el hash. Un hash que no es Argon2 ni
+/// bcrypt, o que no se puede interpretar, nunca coincide.
+pub fn verify_password(plain: &str, hash: &str) -> bool {
+    if is_bcrypt(hash) {
+        return bcrypt::verify(plain, hash).unwrap_or(false);
+    }
+
+    let Ok(parsed) = PasswordHash::new(hash) else {
+        return false;
+    };
+
+    Argon2::default().verify_password(plain.as_bytes(), &parsed).is_ok()
+}
+
+/// Indica si un hash debe reemplazarse por uno Argon2id
+///
+/// Es el caso de los hashes bcrypt y de cualquier otro que no sea Argon2id;
+/// los Argon2id calculados con otros parámetros se conservan.
+///
+/// # Ejemplos
+/// ```
+/// use sai::utils::password::{hash_password, needs_rehash};
+///
+/// assert!(needs_rehash("$2b$12$C6UzMDM.H6dfI/f/IKcEeO5uXvnC1eGZ3EQ1l7ekUm7X4UjhFVr7G"));
+/// assert!(!needs_rehash(&hash_password("clave-segura").unwrap()));
+/// ```
+pub fn needs_rehash(hash: &str) -> bool {
+    !hash.starts_with("$argon2id$")
+}
+
+fn is_bcrypt(hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$"].iter().any(|prefix| hash.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parámetros mínimos para que los tests sean rápidos
+    const CHEAP: Argon2Config = Argon2Config { memory_kib: 8, iterations: 1, parallelism: 1 };
+
+    #[test]
+    fn test_round_trip() {
+        let hash = hash_password_with("clave-segura", &CHEAP).unwrap();
+
+        assert!(hash.starts_with("$argon2id$v=19$m=8,t=1,p=1$"));
+        assert!(verify_password("clave-segura", &hash));
+    }
+
+    #[test]
+    fn test_wrong_password_fails() {
+        let hash = hash_password_with("clave-segura", &CHEAP).unwrap();
+
+        assert!(!verify_password("clave-Segura", &hash));
+        assert!(!verify_password("", &hash));
+    }
+
+    #[test]
+    fn test_each_hash_has_its_own_salt() {
+        let first = hash_password_with("clave-segura", &CHEAP).unwrap();
+        let second = hash_password_with("clave-segura", &CHEAP).unwrap();
+
+        assert_ne!(first, second);
+        assert!(verify_password("clave-segura", &second));
+    }
+
+    #[test]
+    fn test_malformed_hash_never_matches() {
+        assert!(!verify_password("password", ""));
+        assert!(!verify_password("password", "hashed_password"));
+        assert!(!verify_password("password", "$2b$12$truncado"));
+    }
+
+    #[test]
+    fn test_bcrypt_hash_still_verifies() {
+        let hash = bcrypt::hash("clave-segura", 4).unwrap();
+
+        assert!(verify_password("clave-segura", &hash));
+        assert!(!verify_password("clave-Segura", &hash));
+        assert!(verify_password("clave-segura", &hash.replacen("$2b$", "$2y$", 1)));
+        assert!(needs_rehash(&hash));
+    }
+
+    #[test]
+    fn test_argon2id_hash_is_kept() {
+        let hash = hash_password_with("clave-segura", &CHEAP).unwrap();
+
+        assert!(!needs_rehash(&hash));
+        assert!(needs_rehash("hashed_password"));
+    }
+
+    #[test]
+    fn test_config_from_env() {
+        let config = Argon2Config::from_lookup(|name| match name {
+            ARGON2_MEMORY_ENV => Some("65536".to_string()),
+            ARGON2_ITERATIONS_ENV => Some("3".to_string()),
+            ARGON2_PARALLELISM_ENV => Some("4".to_string()),
+            _ => None,
+        });
+
+        assert_eq!(config, Argon2Config { memory_kib: 65536, iterations: 3, parallelism: 4 });
+    }
+
+    #[test]
+    fn test_invalid_config_uses_defaults() {
+        let config = Argon2Config::from_lookup(|name| match name {
+            ARGON2_ITERATIONS_ENV => Some("muchas".to_string()),
+            ARGON2_PARALLELISM_ENV => Some("0".to_string()),
+            _ => None,
+        });
+        assert_eq!(config, Argon2Config::default());
+
+        // 8 KiB no alcanzan para 4 hilos
+        let config = Argon2Config::from_lookup(|name| match name {
+            ARGON2_MEMORY_ENV => Some("8".to_string()),
+            ARGON2_PARALLELISM_ENV => Some("4".to_string()),
+            _ => None,
+        });
+        assert_eq!(config, Argon2Config::default());
+    }
+}
@@ -0,0 +1,292 @@
+//! Números de teléfono paraguayos
+//!
+//! [`PhoneNumber::parse`] acepta las formas habituales de escribir un número
+//! (con 0 inicial, con +595, con espacios, guiones o paréntesis) y lo guarda
+//! como número nacional, del que salen la forma E.164 y los formatos local e
+//! internacional. `validate_phone_number` y `format_phone_number` delegan en él.
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::constants::{MOBILE_PREFIXES, PHONE_COUNTRY_CODE};
+
+/// Código de Asunción y Gran Asunción, cuyos abonados tienen 6 o 7 dígitos
+pub const ASUNCION_AREA_CODE: &str = "21";
+
+/// Códigos de área del interior (sin el 0 inicial); sus abonados tienen 6 dígitos
+pub const INTERIOR_AREA_CODES: [&str; 12] = [
+    "61",  // Ciudad del Este
+    "71",  // Encarnación
+    "81",  // San Juan Bautista
+    "86",  // Pilar
+    "331", // Concepción
+    "336", // Pedro Juan Caballero
+    "343", // Salto del Guairá
+    "511", // Itá
+    "521", // Coronel Oviedo
+    "522", // Caaguazú
+    "531", // Paraguarí
+    "541", // Villarrica
+];
+
+/// Cantidad de dígitos del número nacional de un celular
+const MOBILE_LENGTH: usize = 9;
+
+/// Cantidad de dígitos de un abonado de línea fija del interior
+const LANDLINE_SUBSCRIBER_LENGTH: usize = 6;
+
+/// Tipo de línea de un número
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhoneKind {
+    /// Celular (09xx)
+    Mobile,
+    /// Línea fija (021 en Asunción, otros códigos en el interior)
+    Landline,
+}
+
+/// Errores al interpretar un número de teléfono
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum PhoneError {
+    #[error("es obligatorio")]
+    Empty,
+    #[error("solo puede contener dígitos, espacios, guiones, puntos, paréntesis y el + inicial")]
+    InvalidCharacters,
+    #[error("no es un número paraguayo")]
+    InvalidCountryCode,
+    #[error("no corresponde a un celular ni a un código de área conocido")]
+    UnknownPrefix,
+    #[error("no tiene la cantidad de dígitos de un número {0}")]
+    InvalidLength(&'static str),
+}
+
+/// Número de teléfono paraguayo normalizado
+///
+/// # Ejemplos
+/// ```
+/// use sai::utils::phone::{PhoneKind, PhoneNumber};
+///
+/// let mobile = PhoneNumber::parse("(0981) 123-456").unwrap();
+/// assert_eq!(mobile.e164(), "+595981123456");
+/// assert_eq!(mobile.kind(), PhoneKind::Mobile);
+///
+/// let landline = PhoneNumber::parse("021 123 456").unwrap();
+/// assert_eq!(landline.international_format(), "+595 21 123 456");
+/// assert!(!landline.is_mobile());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PhoneNumber {
+    /// Número nacional, sin 0 inicial ni código de país
+    national: String,
+    kind: PhoneKind,
+    /// Largo del código de área dentro de `national`; 0 para celulares
+    area_code_length: usize,
+}
+
+impl PhoneNumber {
+    /// Interpreta un número de teléfono
+    ///
+    /// Ignora espacios, guiones, puntos y paréntesis. El número puede venir
+    /// con código de país (`+595` o `595`), con el 0 de discado nacional o
+    /// sin ninguno de los dos.
+    pub fn parse(value: &str) -> Result<Self, PhoneError> {
+        let compact: String = value
+            .chars()
+            .filter(|&c| !matches!(c, '-' | '(' | ')' | '.') && !c.is_whitespace())
+            .collect();
+        if compact.is_empty() {
+            return Err(PhoneError::Empty);
+        }
+
+        let (international, digits) = match compact.strip_prefix('+') {
+            Some(digits) => (true, digits),
+            None => (false, compact.as_str()),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(PhoneError::InvalidCharacters);
+        }
+
+        let country_code = &PHONE_COUNTRY_CODE[1..];
+        let national = if international {
+            digits.strip_prefix(country_code).ok_or(PhoneError::InvalidCountryCode)?
+        } else if let Some(national) = digits.strip_prefix('0') {
+            national
+        } else {
+            // 595 seguido de un número nacional completo; si no, el número ya es nacional
+            digits
+                .strip_prefix(country_code)
+                .filter(|national| national.len() > LANDLINE_SUBSCRIBER_LENGTH + 1)
+                .unwrap_or(digits)
+        };
+
+        Self::from_national(national)
+    }
+
+    /// Clasifica un número nacional como celular o línea fija
+    fn from_national(national: &str) -> Result<Self, PhoneError> {
+        if MOBILE_PREFIXES.iter().any(|prefix| national.starts_with(prefix)) {
+            if national.len() != MOBILE_LENGTH {
+                return Err(PhoneError::InvalidLength("de celular"));
+            }
+            return Ok(Self {
+                national: national.to_string(),
+                kind: PhoneKind::Mobile,
+                area_code_length: 0,
+            });
+        }
+
+        let area_code = std::iter::once(ASUNCION_AREA_CODE)
+            .chain(INTERIOR_AREA_CODES)
+            .find(|code| national.starts_with(*code))
+            .ok_or(PhoneError::UnknownPrefix)?;
+        let subscriber = national.len() - area_code.len();
+        let valid_subscriber = subscriber == LANDLINE_SUBSCRIBER_LENGTH
+            || (area_code == ASUNCION_AREA_CODE && subscriber == LANDLINE_SUBSCRIBER_LENGTH + 1);
+        if !valid_subscriber {
+            return Err(PhoneError::InvalidLength("de línea fija"));
+        }
+
+        Ok(Self {
+            national: national.to_string(),
+            kind: PhoneKind::Landline,
+            area_code_length: area_code.len(),
+        })
+    }
+
+    /// Número en formato E.164, p. ej. `+595981123456`
+    pub fn e164(&self) -> String {
+        format!("{}{}", PHONE_COUNTRY_CODE, self.national)
+    }
+
+    /// Número nacional, sin 0 inicial ni código de país
+    pub fn national_number(&self) -> &str {
+        &self.national
+    }
+
+    /// Tipo de línea
+    pub fn kind(&self) -> PhoneKind {
+        self.kind
+    }
+
+    /// Indica si el número es de celular y puede recibir SMS
+    pub fn is_mobile(&self) -> bool {
+        self.kind == PhoneKind::Mobile
+    }
+
+    /// Formato local, p. ej. `0981 123 456` o `021 123 456`
+    pub fn local_format(&self) -> String {
+        format!("0{}", self.grouped())
+    }
+
+    /// Formato internacional, p. ej. `+595 981 123 456` o `+595 21 123 456`
+    pub fn international_format(&self) -> String {
+        format!("{} {}", PHONE_COUNTRY_CODE, self.grouped())
+    }
+
+    /// Prefijo (operadora o código de área) y abonado en grupos de 3
+    ///
+    /// Los celulares se agrupan 3-3-3; las líneas fijas separan el código de
+    /// área y dejan el dígito extra de los abonados de 7 dígitos al final.
+    fn grouped(&self) -> String {
+        let prefix_length = match self.kind {
+            PhoneKind::Mobile => 3,
+            PhoneKind::Landline => self.area_code_length,
+        };
+        let (prefix, subscriber) = self.national.split_at(prefix_length);
+        let (first, rest) = subscriber.split_at(3);
+        format!("{} {} {}", prefix, first, rest)
+    }
+}
+
+impl fmt::Display for PhoneNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.e164())
+    }
+}
+
+impl FromStr for PhoneNumber {
+    type Err = PhoneError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        PhoneNumber::parse(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mobile_notations() {
+        for input in [
+            "0981123456",
+            "0981 123 456",
+            "0981-123-456",
+            "(0981) 123-456",
+            "981123456",
+            "+595981123456",
+            "+595 981 123456",
+            "+595 (981) 123-456",
+            "595981123456",
+        ] {
+            let phone = PhoneNumber::parse(input).unwrap_or_else(|e| panic!("{:?}: {}", input, e));
+            assert_eq!(phone.e164(), "+595981123456", "{:?}", input);
+            assert_eq!(phone.kind(), PhoneKind::Mobile, "{:?}", input);
+            assert_eq!(phone.local_format(), "0981 123 456", "{:?}", input);
+            assert_eq!(phone.international_format(), "+595 981 123 456", "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_09xx_mobile_vs_021_landline() {
+        let mobile = PhoneNumber::parse("0991 234 567").unwrap();
+        assert!(mobile.is_mobile());
+        assert_eq!(mobile.national_number(), "991234567");
+
+        let landline = PhoneNumber::parse("(021) 123-456").unwrap();
+        assert_eq!(landline.kind(), PhoneKind::Landline);
+        assert_eq!(landline.e164(), "+59521123456");
+        assert_eq!(landline.local_format(), "021 123 456");
+        assert_eq!(landline.international_format(), "+595 21 123 456");
+
+        // Asunción también tiene abonados de 7 dígitos
+        let landline = PhoneNumber::parse("021 123 4567").unwrap();
+        assert_eq!(landline.e164(), "+595211234567");
+        assert_eq!(landline.local_format(), "021 123 4567");
+        assert_eq!(PhoneNumber::parse("+595 21 1234567").unwrap(), landline);
+    }
+
+    #[test]
+    fn test_interior_landlines() {
+        let encarnacion = PhoneNumber::parse("071-204-123").unwrap();
+        assert_eq!(encarnacion.kind(), PhoneKind::Landline);
+        assert_eq!(encarnacion.local_format(), "071 204 123");
+
+        let oviedo = PhoneNumber::parse("+595 521 201 234").unwrap();
+        assert_eq!(oviedo.e164(), "+595521201234");
+        assert_eq!(oviedo.local_format(), "0521 201 234");
+        assert_eq!(oviedo.international_format(), "+595 521 201 234");
+
+        // Fuera de Asunción los abonados tienen 6 dígitos
+        assert_eq!(PhoneNumber::parse("071 204 1234"), Err(PhoneError::InvalidLength("de línea fija")));
+    }
+
+    #[test]
+    fn test_invalid_numbers() {
+        assert_eq!(PhoneNumber::parse(""), Err(PhoneError::Empty));
+        assert_eq!(PhoneNumber::parse(" - "), Err(PhoneError::Empty));
+        assert_eq!(PhoneNumber::parse("0981 12a 456"), Err(PhoneError::InvalidCharacters));
+        assert_eq!(PhoneNumber::parse("+"), Err(PhoneError::InvalidCharacters));
+        assert_eq!(PhoneNumber::parse("098+1123456"), Err(PhoneError::InvalidCharacters));
+        assert_eq!(PhoneNumber::parse("+54 11 1234 5678"), Err(PhoneError::InvalidCountryCode));
+        assert_eq!(PhoneNumber::parse("09811234567"), Err(PhoneError::InvalidLength("de celular")));
+        assert_eq!(PhoneNumber::parse("098112345"), Err(PhoneError::InvalidLength("de celular")));
+        assert_eq!(PhoneNumber::parse("0123456789"), Err(PhoneError::UnknownPrefix));
+        assert_eq!(PhoneNumber::parse("123456"), Err(PhoneError::UnknownPrefix));
+    }
+
+    #[test]
+    fn test_display_is_e164() {
+        assert_eq!(PhoneNumber::parse("0981 123 456").unwrap().to_string(), "+595981123456");
+        assert_eq!("021 123 456".parse::<PhoneNumber>().unwrap().to_string(), "+59521123456");
+    }
+}
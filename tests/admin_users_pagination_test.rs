@@ -0,0 +1,72 @@
+//! Database-backed tests for user listing pagination.
+//!
+//! Requires a migrated database in `DATABASE_URL`; run manually with
+//! `cargo test --test admin_users_pagination_test -- --ignored`.
+
+use sai::models::user::{User, UserFilter};
+use uuid::Uuid;
+
+async fn pool() -> sqlx::PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    sqlx::PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+/// Users sharing a name prefix, some of them created in the same instant
+async fn seed_users(pool: &sqlx::PgPool, prefix: &str, count: usize) {
+    for i in 0..count {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, document_id, full_name, email, birth_date, role, created_at, updated_at)
+             VALUES ($1, $2, $3, $4, '1990-01-01', 'Teacher', date_trunc('second', NOW()) - ($5 / 3) * INTERVAL '1 minute', NOW())",
+        )
+        .bind(id)
+        .bind(&id.simple().to_string()[..12])
+        .bind(format!("{} {:02}", prefix, i))
+        .bind(format!("{}@example.com", id.simple()))
+        .bind(i as i32)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+}
+
+fn filter(prefix: &str) -> UserFilter {
+    UserFilter {
+        full_name: Some(prefix.to_string()),
+        ..Default::default()
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_cursor_and_offset_pagination_return_the_same_users() {
+    let pool = pool().await;
+    let prefix = format!("Paginado {}", &Uuid::new_v4().simple().to_string()[..8]);
+    seed_users(&pool, &prefix, 23).await;
+    let per_page = 5;
+
+    let mut by_offset = Vec::new();
+    for page in 0.. {
+        let users = User::find_all(&pool, filter(&prefix), Some(per_page), Some(page * per_page)).await.unwrap();
+        if users.is_empty() {
+            break;
+        }
+        by_offset.extend(users.into_iter().map(|u| u.id));
+    }
+
+    let mut by_cursor = Vec::new();
+    let mut after = None;
+    loop {
+        let users = User::find_all_cursor(&pool, &filter(&prefix), after, per_page).await.unwrap();
+        if users.is_empty() {
+            break;
+        }
+        after = users.last().map(|u| u.id);
+        by_cursor.extend(users.into_iter().map(|u| u.id));
+    }
+
+    assert_eq!(by_offset.len(), 23);
+    assert_eq!(by_cursor, by_offset);
+    assert_eq!(User::count(&pool, filter(&prefix)).await.unwrap(), 23);
+}
@@ -0,0 +1,120 @@
+//! Weighted averages, grades and course statistics without usable data.
+//!
+//! Every test gets its own schema, so they run in parallel. Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test assessment_stats_test -- --ignored`.
+
+use chrono::Utc;
+use sai::db::DbPool;
+use sai::models::assessment::{AssessmentType, GradeDistribution, NewAssessment};
+use sai::models::enrollment::{EnrollmentStatus, NewEnrollment};
+use sai::models::{Assessment, Enrollment};
+use sai::testing::{fixtures, TestDb};
+use uuid::Uuid;
+
+/// A new student enrolled in `course_id`
+async fn enroll(pool: &DbPool, course_id: Uuid) -> Enrollment {
+    let (user, _) = fixtures::student_with_user().create(pool).await;
+    Enrollment::create(
+        pool,
+        &NewEnrollment {
+            student_id: user.id,
+            course_id,
+            academic_year: 2025,
+            status: Some(EnrollmentStatus::Active),
+            notes: None,
+            payment_info: None,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+fn exam(enrollment: &Enrollment, score: f64, weight: f64) -> NewAssessment {
+    NewAssessment {
+        enrollment_id: enrollment.id,
+        course_id: enrollment.course_id,
+        assessment_type: AssessmentType::Exam,
+        title: "Examen parcial".to_string(),
+        description: None,
+        score,
+        max_score: 100.0,
+        weight,
+        assessment_date: Utc::now(),
+        is_final: false,
+        period: 1,
+        comments: None,
+    }
+}
+
+/// Rows loaded before weights were validated can still weigh zero
+async fn insert_zero_weight(pool: &DbPool, enrollment: &Enrollment, score: f64) {
+    sqlx::query(
+        "INSERT INTO assessments (enrollment_id, course_id, assessment_type, title, score, max_score, weight, assessment_date, is_final, period)
+         VALUES ($1, $2, 'exam', 'Sin peso', $3, 100, 0, NOW(), false, 1)",
+    )
+    .bind(enrollment.id)
+    .bind(enrollment.course_id)
+    .bind(score)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_no_assessments_is_ungraded() {
+    let db = TestDb::new().await;
+    let course = fixtures::course().create(&db.pool).await;
+    let enrollment = enroll(&db.pool, course.id).await;
+
+    let average = Assessment::calculate_weighted_average(&db.pool, enrollment.id, course.id).await.unwrap();
+    let grade = Assessment::calculate_grade(&db.pool, enrollment.id, course.id).await.unwrap();
+    let statistics = Assessment::calculate_course_statistics(&db.pool, course.id).await.unwrap();
+
+    assert_eq!(average, None);
+    assert_eq!(grade, None);
+    assert_eq!(statistics.assessment_count, 0);
+    assert_eq!(statistics.average_score, None);
+    assert_eq!(statistics.median_score, None);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_zero_weights_are_ungraded_and_rejected() {
+    let db = TestDb::new().await;
+    let course = fixtures::course().create(&db.pool).await;
+    let enrollment = enroll(&db.pool, course.id).await;
+    insert_zero_weight(&db.pool, &enrollment, 35.0).await;
+
+    let grade = Assessment::calculate_grade(&db.pool, enrollment.id, course.id).await.unwrap();
+    assert_eq!(grade, None);
+
+    let created = Assessment::create(&db.pool, exam(&enrollment, 35.0, 0.0)).await;
+    assert!(matches!(created, Err(sai::db::DbError::Invalid(_))));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_distribution_skips_ungraded_enrollments() {
+    let db = TestDb::new().await;
+    let course = fixtures::course().create(&db.pool).await;
+
+    let excellent = enroll(&db.pool, course.id).await;
+    Assessment::create(&db.pool, exam(&excellent, 95.0, 40.0)).await.unwrap();
+    let failing = enroll(&db.pool, course.id).await;
+    Assessment::create(&db.pool, exam(&failing, 30.0, 40.0)).await.unwrap();
+    let zero_weight = enroll(&db.pool, course.id).await;
+    insert_zero_weight(&db.pool, &zero_weight, 80.0).await;
+    enroll(&db.pool, course.id).await;
+
+    let distribution = Assessment::calculate_grade_distribution(&db.pool, course.id).await.unwrap();
+
+    assert_eq!(
+        distribution,
+        GradeDistribution { a_count: 1, f_count: 1, ungraded_count: 2, ..Default::default() }
+    );
+    db.teardown().await;
+}
@@ -0,0 +1,209 @@
+//! Attendance of a whole class session recorded in one transaction.
+//!
+//! Every test gets its own schema, so they run in parallel. Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test attendance_bulk_test -- --ignored`.
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, ResponseError};
+use chrono::NaiveDate;
+use sai::db::DbError;
+use sai::models::attendance::{AttendanceFilter, AttendanceStatus, NewAttendance};
+use sai::models::enrollment::{EnrollmentStatus, NewEnrollment};
+use sai::models::{Attendance, Enrollment};
+use sai::routes::response::ApiError;
+use sai::services::attendance::{AttendanceService, BulkAttendanceRecord, BulkAttendanceRequest};
+use sai::services::ServiceError;
+use sai::testing::{fixtures, TestDb};
+use uuid::Uuid;
+
+fn day() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 3, 3).unwrap()
+}
+
+fn record(student_id: Uuid, status: AttendanceStatus) -> BulkAttendanceRecord {
+    BulkAttendanceRecord {
+        student_id,
+        status,
+        minutes_late: None,
+        notes: None,
+    }
+}
+
+/// Creates a course with `count` students enrolled with `status`
+async fn course_with_students(db: &TestDb, count: usize, status: EnrollmentStatus) -> (Uuid, Vec<Uuid>) {
+    let course = fixtures::course().create(&db.pool).await;
+    let mut students = Vec::with_capacity(count);
+    for _ in 0..count {
+        let (user, _) = fixtures::student_with_user().create(&db.pool).await;
+        Enrollment::create(
+            &db.pool,
+            &NewEnrollment {
+                student_id: user.id,
+                course_id: course.id,
+                academic_year: 2025,
+                status: Some(status),
+                notes: None,
+                payment_info: None,
+            },
+        )
+        .await
+        .unwrap();
+        students.push(user.id);
+    }
+
+    (course.id, students)
+}
+
+async fn recorded(db: &TestDb, course_id: Uuid) -> Vec<Attendance> {
+    let filter = AttendanceFilter { course_id: Some(course_id), ..Default::default() };
+    Attendance::filter(&db.pool, &filter).await.unwrap()
+}
+
+/// Fields of the per-record errors returned for a rejected session
+fn invalid_fields(error: ServiceError) -> Vec<String> {
+    match error {
+        ServiceError::DatabaseError(DbError::Invalid(errors)) => {
+            errors.into_iter().map(|error| error.field.into_owned()).collect()
+        }
+        other => panic!("expected per-record errors, got {}", other),
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_session_is_recorded() {
+    let db = TestDb::new().await;
+    let service = AttendanceService::new(Arc::new(db.pool.clone()));
+    let (teacher, _) = fixtures::teacher_with_user().create(&db.pool).await;
+    let (course_id, students) = course_with_students(&db, 3, EnrollmentStatus::Active).await;
+
+    let created = service
+        .bulk_create_session(BulkAttendanceRequest {
+            course_id,
+            date: day(),
+            recorded_by: teacher.id,
+            records: vec![
+                record(students[0], AttendanceStatus::Present),
+                BulkAttendanceRecord {
+                    minutes_late: Some(12),
+                    notes: Some("Llegó con el transporte escolar".to_string()),
+                    ..record(students[1], AttendanceStatus::Late)
+                },
+                record(students[2], AttendanceStatus::Absent),
+            ],
+        })
+        .await
+        .unwrap();
+
+    // Returned in the order of the request
+    assert_eq!(created.iter().map(|a| a.student_id).collect::<Vec<_>>(), students);
+    assert_eq!(created[1].minutes_late, Some(12));
+    assert!(created.iter().all(|a| a.date == day() && a.recorded_by == teacher.id));
+    assert_eq!(recorded(&db, course_id).await.len(), 3);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_students_not_actively_enrolled_are_rejected() {
+    let db = TestDb::new().await;
+    let service = AttendanceService::new(Arc::new(db.pool.clone()));
+    let (teacher, _) = fixtures::teacher_with_user().create(&db.pool).await;
+    let (course_id, enrolled) = course_with_students(&db, 1, EnrollmentStatus::Active).await;
+    let (_, withdrawn) = course_with_students(&db, 1, EnrollmentStatus::Withdrawn).await;
+    let (stranger, _) = fixtures::student_with_user().create(&db.pool).await;
+
+    let error = service
+        .bulk_create_session(BulkAttendanceRequest {
+            course_id,
+            date: day(),
+            recorded_by: teacher.id,
+            records: vec![
+                record(enrolled[0], AttendanceStatus::Present),
+                record(withdrawn[0], AttendanceStatus::Present),
+                record(stranger.id, AttendanceStatus::Absent),
+            ],
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(invalid_fields(error), ["records[1].student_id", "records[2].student_id"]);
+    assert!(recorded(&db, course_id).await.is_empty());
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_existing_record_rolls_back_whole_session() {
+    let db = TestDb::new().await;
+    let service = AttendanceService::new(Arc::new(db.pool.clone()));
+    let (teacher, _) = fixtures::teacher_with_user().create(&db.pool).await;
+    let (course_id, students) = course_with_students(&db, 3, EnrollmentStatus::Active).await;
+
+    // The last student already has attendance for the day
+    Attendance::create(
+        &db.pool,
+        NewAttendance {
+            student_id: students[2],
+            course_id,
+            date: day(),
+            status: AttendanceStatus::Absent,
+            notes: None,
+            minutes_late: None,
+            recorded_by: teacher.id,
+        },
+    )
+    .await
+    .unwrap();
+
+    let error = service
+        .bulk_create_session(BulkAttendanceRequest {
+            course_id,
+            date: day(),
+            recorded_by: teacher.id,
+            records: students.iter().map(|&id| record(id, AttendanceStatus::Present)).collect(),
+        })
+        .await
+        .unwrap_err();
+
+    let response = ApiError::from(error);
+    assert_eq!(response.status_code(), StatusCode::UNPROCESSABLE_ENTITY);
+    let details = response.details.unwrap();
+    assert_eq!(details[0]["field"], "records[2].student_id");
+
+    // The first two inserts were rolled back with the failing one
+    let records = recorded(&db, course_id).await;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].student_id, students[2]);
+    assert_eq!(records[0].status, AttendanceStatus::Absent);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_every_invalid_record_is_listed() {
+    let db = TestDb::new().await;
+    let service = AttendanceService::new(Arc::new(db.pool.clone()));
+    let (teacher, _) = fixtures::teacher_with_user().create(&db.pool).await;
+    let (course_id, students) = course_with_students(&db, 2, EnrollmentStatus::Active).await;
+
+    let error = service
+        .bulk_create_session(BulkAttendanceRequest {
+            course_id,
+            date: day(),
+            recorded_by: teacher.id,
+            records: vec![
+                BulkAttendanceRecord { minutes_late: Some(5), ..record(students[0], AttendanceStatus::Present) },
+                record(students[1], AttendanceStatus::Present),
+                record(students[1], AttendanceStatus::Absent),
+            ],
+        })
+        .await
+        .unwrap_err();
+
+    assert_eq!(invalid_fields(error), ["records[0].minutes_late", "records[2].student_id"]);
+    assert!(recorded(&db, course_id).await.is_empty());
+    db.teardown().await;
+}
@@ -0,0 +1,71 @@
+//! Database-backed tests for `GET /api/attendance`.
+//!
+//! Requires a migrated database in `DATABASE_URL`; run manually with
+//! `cargo test --test attendance_query_test -- --ignored`.
+
+use actix_web::{test, web, App};
+use chrono::NaiveDate;
+use sai::models::attendance::{AttendanceFilter, AttendanceStatus};
+use sai::models::Attendance;
+use std::sync::Arc;
+
+async fn pool() -> sqlx::PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    sqlx::PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_date_range_boundaries_are_inclusive() {
+    let pool = pool().await;
+    let from = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
+    let to = NaiveDate::from_ymd_opt(2025, 3, 7).unwrap();
+
+    let filter = AttendanceFilter {
+        date_from: Some(from),
+        date_to: Some(to),
+        page_size: Some(1000),
+        ..Default::default()
+    };
+    let records = Attendance::filter(&pool, &filter).await.unwrap();
+
+    assert!(records.iter().all(|r| r.date >= from && r.date <= to));
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_aggregates_match_rows() {
+    let pool = pool().await;
+    let filter = AttendanceFilter {
+        page_size: Some(10_000),
+        ..Default::default()
+    };
+
+    let records = Attendance::filter(&pool, &filter).await.unwrap();
+    let counts = Attendance::count_by_status(&pool, &filter).await.unwrap();
+
+    let count = |status: AttendanceStatus| records.iter().filter(|r| r.status == status).count() as i64;
+    assert_eq!(counts.total, records.len() as i64);
+    assert_eq!(counts.present, count(AttendanceStatus::Present));
+    assert_eq!(counts.absent, count(AttendanceStatus::Absent));
+    assert_eq!(counts.late, count(AttendanceStatus::Late));
+    assert_eq!(counts.excused, count(AttendanceStatus::Excused));
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_route_requires_authentication() {
+    let pool = Arc::new(pool().await);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(sai::services::AttendanceService::new(pool)))
+            .service(sai::routes::configure()),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/attendance").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), actix_web::http::StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,58 @@
+//! Database-backed tests for reconciling attendance with the academic calendar.
+//!
+//! Requires a migrated database in `DATABASE_URL`; run manually with
+//! `cargo test --test attendance_reconcile_test -- --ignored`.
+
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use sai::models::calendar::{CalendarEventType, CreateCalendarEventDto};
+use sai::services::{AttendanceService, ServiceError};
+use uuid::Uuid;
+
+async fn pool() -> sqlx::PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    sqlx::PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_reconcile_rejects_regular_school_day() {
+    let service = AttendanceService::new(Arc::new(pool().await));
+    let date = NaiveDate::from_ymd_opt(1990, 3, 14).unwrap();
+
+    let result = service.reconcile_with_calendar(Uuid::new_v4(), date).await;
+
+    assert!(matches!(result, Err(ServiceError::ValidationError(_))));
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_reconcile_academic_year_covers_every_holiday_date() {
+    let pool = pool().await;
+    let service = AttendanceService::new(Arc::new(pool.clone()));
+    let year = 2099;
+
+    let event = service
+        .create_calendar_event(CreateCalendarEventDto {
+            academic_year: year,
+            name: "Aniversario del colegio".to_string(),
+            event_type: CalendarEventType::InstitutionalHoliday,
+            start_date: NaiveDate::from_ymd_opt(year, 9, 1).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(year, 9, 2),
+        })
+        .await
+        .unwrap();
+
+    let results = service.reconcile_academic_year(year).await.unwrap();
+
+    sqlx::query("DELETE FROM calendar_events WHERE id = $1")
+        .bind(event.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|r| r.calendar_event_name == "Aniversario del colegio"));
+}
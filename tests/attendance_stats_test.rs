@@ -0,0 +1,66 @@
+//! Attendance statistics after a day is justified.
+//!
+//! Every test gets its own schema, so they run in parallel. Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test attendance_stats_test -- --ignored`.
+
+use chrono::NaiveDate;
+use sai::models::attendance::{AttendanceFilter, AttendanceStatus, NewAttendance};
+use sai::models::Attendance;
+use sai::testing::{fixtures, TestDb};
+
+#[actix_rt::test]
+#[ignore]
+async fn test_statistics_count_excused_days() {
+    let db = TestDb::new().await;
+    let (student, _) = fixtures::student_with_user().create(&db.pool).await;
+    let (teacher, _) = fixtures::teacher_with_user().create(&db.pool).await;
+    let course = fixtures::course().create(&db.pool).await;
+    let day = |d: u32| NaiveDate::from_ymd_opt(2025, 3, d).unwrap();
+
+    for (date, status) in [
+        (day(3), AttendanceStatus::Present),
+        (day(4), AttendanceStatus::Absent),
+        (day(5), AttendanceStatus::Late),
+        (day(6), AttendanceStatus::Excused),
+    ] {
+        Attendance::create(
+            &db.pool,
+            NewAttendance {
+                student_id: student.id,
+                course_id: course.id,
+                date,
+                status,
+                notes: None,
+                minutes_late: None,
+                recorded_by: teacher.id,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    // Institutional holiday on the 4th: the absence becomes excused
+    let updated = Attendance::excuse_for_date(&db.pool, Some(course.id), day(4), "Feriado institucional")
+        .await
+        .unwrap();
+    assert_eq!(updated, 1);
+
+    let stats = Attendance::get_student_statistics(&db.pool, student.id, course.id).await.unwrap();
+    assert_eq!(stats.total_days, 4);
+    assert_eq!(stats.present_days, 1);
+    assert_eq!(stats.absent_days, 0);
+    assert_eq!(stats.late_days, 1);
+    assert_eq!(stats.excused_days, 2);
+    assert_eq!(stats.attendance_rate, 0.75);
+
+    let filter = AttendanceFilter { course_id: Some(course.id), ..Default::default() };
+    let counts = Attendance::count_by_status(&db.pool, &filter).await.unwrap();
+    assert_eq!(counts.excused, 2);
+
+    let excused = AttendanceFilter { status: Some(AttendanceStatus::Excused), ..filter };
+    let records = Attendance::filter(&db.pool, &excused).await.unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|record| record.status == AttendanceStatus::Excused));
+    db.teardown().await;
+}
@@ -0,0 +1,174 @@
+//! Sign-in through `/api/auth` against a `sai::testing` schema.
+//!
+//! Needs `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test auth_test -- --ignored`.
+
+use actix_web::{http::StatusCode, test, App};
+use sai::config::AppConfig;
+use sai::db::tenant::{self, TenantId};
+use sai::db::{DbPools, MIGRATIONS};
+use sai::models::authentication::{Authentication, NewAuthentication};
+use sai::models::User;
+use sai::testing::{fixtures, TestDb};
+use sai::AppState;
+use serde_json::json;
+use uuid::Uuid;
+
+const EMAIL: &str = "docente@colegio.edu.py";
+const PASSWORD: &str = "clave-segura";
+
+async fn account(db: &TestDb) -> (User, Authentication) {
+    let user = fixtures::user().email(EMAIL).create(&db.pool).await;
+    let account = Authentication::create(
+        &db.pool,
+        NewAuthentication { user_id: user.id, password: PASSWORD.to_string() },
+    )
+    .await
+    .unwrap();
+    (user, account)
+}
+
+macro_rules! app {
+    ($db:expr) => {{
+        let state = AppState::with_pool($db.pool.clone(), AppConfig::default());
+        let pools = DbPools::primary_only($db.pool.clone());
+        test::init_service(
+            App::new()
+                .configure(|cfg| sai::routes::configure_app_data_with_state(cfg, &pools, &state))
+                .service(sai::routes::configure()),
+        )
+        .await
+    }};
+}
+
+fn login(password: &str) -> test::TestRequest {
+    test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "username": EMAIL, "password": password }))
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_bcrypt_hash_signs_in_and_is_upgraded() {
+    let db = TestDb::new().await;
+    let (user, _) = account(&db).await;
+    let legacy = bcrypt::hash(PASSWORD, 4).unwrap();
+    sqlx::query("UPDATE authentications SET password_hash = $1 WHERE user_id = $2")
+        .bind(&legacy)
+        .bind(user.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let app = app!(db);
+
+    assert_eq!(test::call_service(&app, login("otra-clave").to_request()).await.status(), StatusCode::UNAUTHORIZED);
+    let stored = Authentication::find_by_user_id(&db.pool, user.id).await.unwrap();
+    assert_eq!(stored.password_hash, legacy);
+
+    assert_eq!(test::call_service(&app, login(PASSWORD).to_request()).await.status(), StatusCode::OK);
+    let stored = Authentication::find_by_user_id(&db.pool, user.id).await.unwrap();
+    assert!(stored.password_hash.starts_with("$argon2id$"), "{}", stored.password_hash);
+
+    // The Argon2id hash is the one checked from now on
+    assert_eq!(test::call_service(&app, login(PASSWORD).to_request()).await.status(), StatusCode::OK);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_refresh_rejects_older_tokens_and_reuse_closes_the_session() {
+    let db = TestDb::new().await;
+    account(&db).await;
+    let app = app!(db);
+    let me = |token: &str| {
+        test::TestRequest::get()
+            .uri("/api/auth/me")
+            .insert_header(("Authorization", format!("Bearer {}", token)))
+            .to_request()
+    };
+    let refresh = |refresh_token: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/refresh")
+            .set_json(json!({ "refresh_token": refresh_token }))
+            .to_request()
+    };
+
+    let body: serde_json::Value = test::call_and_read_body_json(&app, login(PASSWORD).to_request()).await;
+    let (first, first_refresh) = (body["data"]["token"].as_str().unwrap(), body["data"]["refresh_token"].as_str().unwrap());
+    assert_eq!(test::call_service(&app, me(first)).await.status(), StatusCode::OK);
+
+    let resp = test::call_service(&app, refresh(first_refresh)).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let (second, second_refresh) = (body["data"]["token"].as_str().unwrap(), body["data"]["refresh_token"].as_str().unwrap());
+    assert_eq!(test::call_service(&app, me(first)).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(test::call_service(&app, me(second)).await.status(), StatusCode::OK);
+
+    // Replaying the first refresh token revokes the rotated one and the access token
+    assert_eq!(test::call_service(&app, refresh(first_refresh)).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(test::call_service(&app, refresh(second_refresh)).await.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(test::call_service(&app, me(second)).await.status(), StatusCode::UNAUTHORIZED);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_institution_requires_membership_and_refresh_keeps_it() {
+    let db = TestDb::new().await;
+    let (user, _) = account(&db).await;
+    let tenant = TenantId::parse(&format!("t{}", &Uuid::new_v4().simple().to_string()[..12])).unwrap();
+    let users_table = MIGRATIONS.iter().position(|m| m.version == "20250313_create_users_table").unwrap();
+    tenant::provision_with(&db.pool, &tenant, &MIGRATIONS[..=users_table]).await.unwrap();
+    let app = app!(db);
+    let login_to = |institution: &str| {
+        test::TestRequest::post()
+            .uri("/api/auth/login")
+            .set_json(json!({ "username": EMAIL, "password": PASSWORD, "institution": institution }))
+            .to_request()
+    };
+    let refresh = |refresh_token: &str, institution: Option<&str>| {
+        test::TestRequest::post()
+            .uri("/api/auth/refresh")
+            .set_json(json!({ "refresh_token": refresh_token, "institution": institution }))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, login_to(tenant.slug())).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "not_a_member");
+
+    sqlx::query(&format!(
+        "INSERT INTO {0}.users (id, document_id, full_name, email, birth_date, role, created_at, updated_at) \
+         SELECT id, document_id, full_name, email, birth_date, role::TEXT::{0}.user_role, created_at, updated_at FROM users WHERE id = $1",
+        tenant.schema()
+    ))
+    .bind(user.id)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, login_to(tenant.slug())).await;
+    let refresh_token = body["data"]["refresh_token"].as_str().unwrap().to_string();
+
+    // The session cannot move to another institution or back to `public`
+    for institution in [None, Some("otro_colegio")] {
+        let resp = test::call_service(&app, refresh(&refresh_token, institution)).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+    let resp = test::call_service(&app, refresh(&refresh_token, Some(tenant.slug()))).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let refresh_token = body["data"]["refresh_token"].as_str().unwrap().to_string();
+
+    // Leaving the institution ends the session at the next refresh
+    sqlx::query(&format!("DELETE FROM {}.users WHERE id = $1", tenant.schema()))
+        .bind(user.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    let resp = test::call_service(&app, refresh(&refresh_token, Some(tenant.slug()))).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+    sqlx::query(&format!("DROP SCHEMA {} CASCADE", tenant.schema())).execute(&db.pool).await.unwrap();
+    db.teardown().await;
+}
@@ -0,0 +1,133 @@
+//! Logical backups: export/restore round trip on the demo school and exclusion of auth secrets.
+//!
+//! Needs the full schema (see the note in docs/database.md) and `TEST_DATABASE_URL`;
+//! run with `cargo test --features testing --test backup_test -- --ignored`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use futures::TryStreamExt;
+use sai::db::seed;
+use sai::models::audit_log::NewAuditLogEntry;
+use sai::services::admin::{archive, BackupError, BackupManifest, BackupOptions, MANIFEST_PATH};
+use sai::services::AdminService;
+use sai::testing::TestDb;
+use uuid::Uuid;
+
+async fn export(db: &TestDb, options: BackupOptions) -> Vec<u8> {
+    AdminService::new(Arc::new(db.pool.clone()))
+        .export_backup(None, options, NewAuditLogEntry::default())
+        .await
+        .unwrap()
+        .try_concat()
+        .await
+        .unwrap()
+}
+
+async fn seeded() -> TestDb {
+    let db = TestDb::new().await;
+    seed::seed_demo_school(&db.pool).await.unwrap();
+    db
+}
+
+async fn row_counts(db: &TestDb, tables: &[&str]) -> BTreeMap<String, i64> {
+    let mut counts = BTreeMap::new();
+    for table in tables {
+        let count = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+        counts.insert(table.to_string(), count);
+    }
+    counts
+}
+
+async fn ids(db: &TestDb, table: &str) -> Vec<Uuid> {
+    sqlx::query_scalar(&format!("SELECT id FROM {} ORDER BY id", table))
+        .fetch_all(&db.pool)
+        .await
+        .unwrap()
+}
+
+const TABLES: [&str; 6] = ["users", "courses", "enrollments", "assessments", "payments", "seed_rows"];
+
+#[actix_rt::test]
+#[ignore]
+async fn test_round_trip_preserves_rows_and_ids() {
+    let source = seeded().await;
+    let target = TestDb::new().await;
+
+    let backup = export(&source, BackupOptions::default()).await;
+    let summary = AdminService::new(Arc::new(target.pool.clone()))
+        .restore_backup(None, &backup)
+        .await
+        .unwrap();
+
+    assert_eq!(row_counts(&target, &TABLES).await, row_counts(&source, &TABLES).await);
+    assert_eq!(ids(&target, "users").await, ids(&source, "users").await);
+    assert_eq!(ids(&target, "payments").await, ids(&source, "payments").await);
+    assert_eq!(summary.rows() as i64, {
+        let files = archive::read_archive(&backup).unwrap();
+        let manifest: BackupManifest = serde_json::from_slice(&files[MANIFEST_PATH]).unwrap();
+        manifest.tables.iter().map(|table| table.rows as i64).sum::<i64>()
+    });
+    // Users come before the tables that reference them
+    let position = |name: &str| summary.tables.iter().position(|table| table.name == name).unwrap();
+    assert!(position("users") < position("enrollments"));
+
+    // Restored sequences continue past the loaded ids
+    sqlx::query("INSERT INTO seed_rows (seed, seed_key, table_name, row_id) VALUES ('test', 'test', 'users', $1)")
+        .bind(Uuid::new_v4())
+        .execute(&target.pool)
+        .await
+        .unwrap();
+    source.teardown().await;
+    target.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_auth_secrets_are_excluded_by_default() {
+    let db = seeded().await;
+
+    let backup = export(&db, BackupOptions::default()).await;
+    let files = archive::read_archive(&backup).unwrap();
+    let manifest: BackupManifest = serde_json::from_slice(&files[MANIFEST_PATH]).unwrap();
+
+    assert!(!files.contains_key("data/authentications.jsonl"));
+    assert!(!files.contains_key("data/schema_migrations.jsonl"));
+    assert!(manifest.excluded_tables.contains(&"authentications".to_string()));
+    for (path, data) in &files {
+        let text = String::from_utf8_lossy(data);
+        assert!(!text.contains("password_hash") && !text.contains("$2b$"), "{}", path);
+    }
+    // The export itself is audited
+    assert!(files["data/audit_logs.jsonl"]
+        .split(|&b| b == b'\n')
+        .any(|line| String::from_utf8_lossy(line).contains("backup.export")));
+
+    let with_secrets = export(&db, BackupOptions { include_secrets: true }).await;
+    assert!(archive::read_archive(&with_secrets)
+        .unwrap()
+        .contains_key("data/authentications.jsonl"));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_restore_refuses_a_database_with_data() {
+    let db = seeded().await;
+    let backup = export(&db, BackupOptions::default()).await;
+
+    let error = AdminService::new(Arc::new(db.pool.clone()))
+        .restore_backup(None, &backup)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(&error, BackupError::NotEmpty(tables) if tables.contains(&"users".to_string())));
+    assert!(matches!(
+        AdminService::new(Arc::new(db.pool.clone())).restore_backup(None, b"not a backup").await,
+        Err(BackupError::InvalidArchive(_))
+    ));
+    db.teardown().await;
+}
@@ -0,0 +1,139 @@
+//! First-run bootstrap of the admin account, and the forced password change
+//! that follows it, against a `sai::testing` schema.
+//!
+//! Needs `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test bootstrap_test -- --ignored`.
+
+use actix_web::{http::StatusCode, test, App};
+use sai::config::AppConfig;
+use sai::db::DbPools;
+use sai::services::AdminService;
+use sai::testing::TestDb;
+use sai::AppState;
+use serde_json::json;
+use std::sync::Arc;
+
+async fn admins(db: &TestDb) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = 'Admin'")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap()
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_bootstrap_runs_once() {
+    let db = TestDb::new().await;
+    let service = AdminService::new(Arc::new(db.pool.clone()));
+
+    let created = service
+        .bootstrap_admin("admin@colegio.edu.py", "Administrador")
+        .await
+        .unwrap()
+        .expect("no admin yet");
+    assert!(created.generated_password.is_some());
+
+    // A restart, or another instance starting at the same time, finds the admin
+    let again = service.bootstrap_admin("otro@colegio.edu.py", "Administrador").await.unwrap();
+    assert!(again.is_none());
+    assert_eq!(admins(&db).await, 1);
+
+    let (must_change, audited): (bool, i64) = sqlx::query_as(
+        "SELECT a.must_change_password,
+                (SELECT COUNT(*) FROM audit_logs WHERE action = 'admin.bootstrap')
+         FROM authentications a WHERE a.user_id = $1",
+    )
+    .bind(created.user.id)
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert!(must_change);
+    assert_eq!(audited, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_bootstrap_skipped_when_an_admin_exists() {
+    let db = TestDb::new().await;
+    let service = AdminService::new(Arc::new(db.pool.clone()));
+    service
+        .create_admin("directora@colegio.edu.py", "Ana Pérez", Some("una-clave-larga"))
+        .await
+        .unwrap();
+
+    let bootstrapped = service.bootstrap_admin("admin@colegio.edu.py", "Administrador").await.unwrap();
+
+    assert!(bootstrapped.is_none());
+    assert_eq!(admins(&db).await, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_password_change_required_before_other_endpoints() {
+    let db = TestDb::new().await;
+    let created = AdminService::new(Arc::new(db.pool.clone()))
+        .bootstrap_admin("admin@colegio.edu.py", "Administrador")
+        .await
+        .unwrap()
+        .unwrap();
+    let password = created.generated_password.unwrap();
+
+    let state = AppState::with_pool(db.pool.clone(), AppConfig::default());
+    let pools = DbPools::primary_only(db.pool.clone());
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| sai::routes::configure_app_data_with_state(cfg, &pools, &state))
+            .service(sai::routes::configure()),
+    )
+    .await;
+    let bearer = |token: &str| ("Authorization", format!("Bearer {}", token));
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "username": "admin@colegio.edu.py", "password": password }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["data"]["must_change_password"], true);
+    assert_eq!(body["data"]["role"], "admin");
+    let temporary = body["data"]["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get().uri("/api/admin/users").insert_header(bearer(&temporary)).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "password_change_required");
+
+    let req = test::TestRequest::post()
+        .uri("/api/auth/change-password")
+        .insert_header(bearer(&temporary))
+        .set_json(json!({
+            "current_password": password,
+            "new_password": "mi-clave-definitiva",
+            "confirm_password": "mi-clave-definitiva"
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["data"].get("must_change_password").is_none());
+    let token = body["data"]["token"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get().uri("/api/admin/users").insert_header(bearer(&token)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    // The temporary password no longer works, and new logins are not flagged
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "username": "admin@colegio.edu.py", "password": password }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+    let req = test::TestRequest::post()
+        .uri("/api/auth/login")
+        .set_json(json!({ "username": "admin@colegio.edu.py", "password": "mi-clave-definitiva" }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(body["data"].get("must_change_password").is_none());
+    db.teardown().await;
+}
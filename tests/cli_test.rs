@@ -0,0 +1,103 @@
+//! `sai create-admin` run as a subprocess against a `sai::testing` schema.
+//!
+//! Needs `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test cli_test -- --ignored`.
+
+use std::process::{Command, Output};
+
+use sai::testing::TestDb;
+
+/// `TEST_DATABASE_URL` with the test schema first in `search_path`
+fn database_url(db: &TestDb) -> String {
+    let url = std::env::var("TEST_DATABASE_URL").unwrap();
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}options=-c%20search_path%3D{}%2Cpublic", url, separator, db.schema())
+}
+
+fn sai(db: &TestDb, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sai"))
+        .args(args)
+        .env("DATABASE_URL", database_url(db))
+        .env_remove("SAI_CONFIG")
+        .env_remove("SAI__DATABASE__URL")
+        .output()
+        .expect("failed to run the sai binary")
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_create_admin_with_generated_password() {
+    let db = TestDb::new().await;
+
+    let output = sai(
+        &db,
+        &["create-admin", "--email", "directora@colegio.edu.py", "--name", "Ana Pérez", "--generate-password"],
+    );
+
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let password = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Contraseña temporal: "))
+        .expect("the generated password is printed");
+
+    let (role, must_change, hash): (String, bool, String) = sqlx::query_as(
+        "SELECT u.role::text, a.must_change_password, a.password_hash
+         FROM users u JOIN authentications a ON a.user_id = u.id
+         WHERE u.email = 'directora@colegio.edu.py'",
+    )
+    .fetch_one(&db.pool)
+    .await
+    .unwrap();
+    assert_eq!(role, "Admin");
+    assert!(must_change);
+    assert!(sai::utils::password::verify_password(password, &hash));
+
+    let audited: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs WHERE action = 'admin.create'")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(audited, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_create_admin_exit_codes() {
+    let db = TestDb::new().await;
+    let args = ["create-admin", "--email", "admin@colegio.edu.py", "--name", "Admin", "--generate-password"];
+
+    assert_eq!(sai(&db, &args).status.code(), Some(0));
+    // Same email again: nothing can be created
+    assert_eq!(sai(&db, &args).status.code(), Some(73));
+    // Invalid input data
+    assert_eq!(
+        sai(&db, &["create-admin", "--email", "not-an-email", "--name", "Admin", "--generate-password"]).status.code(),
+        Some(65)
+    );
+    // Missing argument
+    assert_eq!(sai(&db, &["create-admin", "--email", "otro@colegio.edu.py"]).status.code(), Some(64));
+
+    let admins: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE role = 'Admin'")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(admins, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_missing_database_url_is_a_configuration_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_sai"))
+        .args(["migrate", "--dry-run"])
+        .env_remove("DATABASE_URL")
+        .env_remove("SAI_CONFIG")
+        .env_remove("SAI__DATABASE__URL")
+        .current_dir(std::env::temp_dir())
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(78));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("database.url"));
+}
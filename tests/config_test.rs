@@ -0,0 +1,27 @@
+//! Integration tests for the configuration sections.
+
+use sai::db::DbConfig;
+use sai::{AppConfig, DatabaseConfig, ServerConfig};
+use std::time::Duration;
+
+#[test]
+fn test_section_types_reach_the_server_and_pool() {
+    let config = AppConfig {
+        server: ServerConfig { host: "0.0.0.0".to_string(), port: 9090, ..Default::default() },
+        database: DatabaseConfig {
+            url: Some("postgres://sai@db.internal/sai".to_string()),
+            max_connections: 4,
+            acquire_timeout_secs: 5,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let pool = DbConfig::from_settings(&config.database);
+
+    assert_eq!(config.server_addr(), "0.0.0.0:9090");
+    assert_eq!(sai::config::server::ServerConfig::default().address(), "127.0.0.1:8080");
+    assert_eq!(pool.connection_string, "postgres://sai@db.internal/sai");
+    assert_eq!(pool.max_connections, 4);
+    assert_eq!(pool.acquire_timeout, Duration::from_secs(5));
+}
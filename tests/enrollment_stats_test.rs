@@ -0,0 +1,91 @@
+//! Database-backed tests for the enrollment statistics report.
+//!
+//! Requires a migrated database in `DATABASE_URL`; run manually with
+//! `cargo test --test enrollment_stats_test -- --ignored`.
+
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use sai::services::reports::ReportService;
+use uuid::Uuid;
+
+async fn pool() -> sqlx::PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    sqlx::PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+async fn insert_record(
+    pool: &sqlx::PgPool,
+    student_id: Uuid,
+    year: i32,
+    grade: &str,
+    section: &str,
+    withdrawal_reason: Option<&str>,
+) {
+    let enrolled_on = NaiveDate::from_ymd_opt(year, 2, 1).unwrap();
+    let withdrawn_on = withdrawal_reason.map(|_| NaiveDate::from_ymd_opt(year, 7, 15).unwrap());
+
+    sqlx::query(
+        "INSERT INTO student_year_records (student_id, academic_year, grade, section, status,
+                                           enrolled_on, withdrawn_on, withdrawal_reason)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+    )
+    .bind(student_id)
+    .bind(year)
+    .bind(grade)
+    .bind(section)
+    .bind(if withdrawal_reason.is_some() { "withdrawn" } else { "active" })
+    .bind(enrolled_on)
+    .bind(withdrawn_on)
+    .bind(withdrawal_reason)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_two_years_with_known_counts() {
+    let pool = pool().await;
+    // A grade name of its own keeps other test data out of the sections under test
+    let grade = format!("T-{}", &Uuid::new_v4().simple().to_string()[..8]);
+    let reason = format!("Mudanza {}", grade);
+    let students: Vec<Uuid> = (0..6).map(|_| Uuid::new_v4()).collect();
+
+    // 2023: three students in A, two in B
+    for s in &students[..3] {
+        insert_record(&pool, *s, 2023, &grade, "A", None).await;
+    }
+    for s in &students[3..5] {
+        insert_record(&pool, *s, 2023, &grade, "B", None).await;
+    }
+    // 2024: A keeps its three (one withdraws) and gains one; B is left empty
+    insert_record(&pool, students[0], 2024, &grade, "A", None).await;
+    insert_record(&pool, students[1], 2024, &grade, "A", None).await;
+    insert_record(&pool, students[2], 2024, &grade, "A", Some(&reason)).await;
+    insert_record(&pool, students[5], 2024, &grade, "A", None).await;
+
+    let service = ReportService::new(Arc::new(pool));
+    let stats = service.enrollment_stats(2024).await.unwrap();
+
+    assert!(!stats.partial);
+    assert_eq!(stats.as_of, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+
+    let sections: Vec<_> = stats.sections.iter().filter(|s| s.grade == grade).collect();
+    assert_eq!(sections.len(), 2);
+
+    let a = sections[0];
+    assert_eq!(a.section, "A");
+    assert_eq!((a.total, a.active, a.withdrawn), (4, 3, 1));
+    assert_eq!((a.new_students, a.returning_students), (1, 3));
+    assert_eq!((a.previous_year_total, a.change), (3, 1));
+
+    let b = sections[1];
+    assert_eq!(b.section, "B");
+    assert_eq!((b.total, b.active, b.withdrawn), (0, 0, 0));
+    assert_eq!((b.previous_year_total, b.change), (2, -2));
+
+    let withdrawals = stats.withdrawal_reasons.iter().find(|r| r.reason == reason).unwrap();
+    assert_eq!(withdrawals.count, 1);
+}
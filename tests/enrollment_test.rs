@@ -0,0 +1,91 @@
+//! One live enrollment per student, course and year, enforced by
+//! `enrollments_active_student_course_year_key`.
+//!
+//! Every test gets its own schema, so they run in parallel. Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test enrollment_test -- --ignored`.
+
+use std::sync::Arc;
+
+use actix_web::{http::StatusCode, ResponseError};
+use sai::models::enrollment::{EnrollmentStatus, NewEnrollment};
+use sai::models::Enrollment;
+use sai::routes::response::ApiError;
+use sai::services::{EnrollmentService, ServiceError};
+use sai::testing::{fixtures, TestDb};
+use uuid::Uuid;
+
+fn new_enrollment(student_id: Uuid, course_id: Uuid) -> NewEnrollment {
+    NewEnrollment {
+        student_id,
+        course_id,
+        academic_year: 2025,
+        status: Some(EnrollmentStatus::Active),
+        notes: None,
+        payment_info: None,
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_duplicate_enrollment_is_conflict() {
+    let db = TestDb::new().await;
+    let service = EnrollmentService::new(Arc::new(db.pool.clone()));
+    let (user, _) = fixtures::student_with_user().create(&db.pool).await;
+    let course = fixtures::course().name("Matemática I").create(&db.pool).await;
+
+    service.enroll(new_enrollment(user.id, course.id)).await.unwrap();
+    let error = service.enroll(new_enrollment(user.id, course.id)).await.unwrap_err();
+
+    assert!(matches!(
+        &error,
+        ServiceError::AlreadyEnrolled { student_id, course_id, .. } if *student_id == user.id && *course_id == course.id
+    ));
+    let response = ApiError::from(error);
+    assert_eq!(response.status_code(), StatusCode::CONFLICT);
+    assert_eq!(response.message, "Student is already enrolled in Matemática I");
+
+    // Another year is a new enrollment
+    let next_year = NewEnrollment { academic_year: 2026, ..new_enrollment(user.id, course.id) };
+    assert!(service.enroll(next_year).await.is_ok());
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_reenrollment_after_withdrawal() {
+    let db = TestDb::new().await;
+    let service = EnrollmentService::new(Arc::new(db.pool.clone()));
+    let (user, _) = fixtures::student_with_user().create(&db.pool).await;
+    let course = fixtures::course().create(&db.pool).await;
+
+    let first = service.enroll(new_enrollment(user.id, course.id)).await.unwrap();
+    Enrollment::withdraw(&db.pool, first.id, Some("Cambio de turno".to_string())).await.unwrap();
+
+    let second = service.enroll(new_enrollment(user.id, course.id)).await.unwrap();
+
+    assert_ne!(second.id, first.id);
+    assert_eq!(second.status, EnrollmentStatus::Active);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_concurrent_enrollments_resolved_by_index() {
+    let db = TestDb::new().await;
+    let service = EnrollmentService::new(Arc::new(db.pool.clone()));
+    let (user, _) = fixtures::student_with_user().create(&db.pool).await;
+    let course = fixtures::course().create(&db.pool).await;
+
+    let (first, second) = futures::join!(
+        service.enroll(new_enrollment(user.id, course.id)),
+        service.enroll(new_enrollment(user.id, course.id)),
+    );
+
+    let results = [first, second];
+    assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+    assert!(results
+        .iter()
+        .any(|result| matches!(result, Err(ServiceError::AlreadyEnrolled { .. }))));
+    db.teardown().await;
+}
@@ -0,0 +1,59 @@
+//! Feature flag overrides stored in `feature_flags`, through the `sai::testing` harness.
+//!
+//! Needs `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test feature_flags_test -- --ignored`.
+
+use std::sync::Arc;
+
+use sai::config::FeaturesConfig;
+use sai::models::audit_log::NewAuditLogEntry;
+use sai::services::features::{Feature, FeatureFlags};
+use sai::testing::TestDb;
+
+fn flags(db: &TestDb) -> FeatureFlags {
+    FeatureFlags::new(Arc::new(db.pool.clone()), &FeaturesConfig::default())
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_override_is_stored_and_loaded_by_other_instances() {
+    let db = TestDb::new().await;
+    let first = flags(&db);
+    let second = flags(&db);
+
+    let states = first
+        .update(&[(Feature::PaymentsGateway, Some(true))], NewAuditLogEntry::default())
+        .await
+        .unwrap();
+
+    assert!(first.is_enabled(Feature::PaymentsGateway));
+    assert!(states.iter().any(|state| state.name == "payments_gateway" && state.enabled && state.overridden));
+    assert!(!second.is_enabled(Feature::PaymentsGateway), "not loaded yet");
+    second.load_overrides().await.unwrap();
+    assert!(second.is_enabled(Feature::PaymentsGateway));
+
+    let audited: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM audit_logs WHERE action = 'features.update'")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(audited, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_null_restores_configured_value() {
+    let db = TestDb::new().await;
+    let flags = flags(&db);
+
+    flags.update(&[(Feature::Webhooks, Some(true))], NewAuditLogEntry::default()).await.unwrap();
+    flags.update(&[(Feature::Webhooks, None)], NewAuditLogEntry::default()).await.unwrap();
+
+    assert!(!flags.is_enabled(Feature::Webhooks));
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM feature_flags")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+    assert_eq!(stored, 0);
+    db.teardown().await;
+}
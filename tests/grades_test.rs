@@ -0,0 +1,220 @@
+//! Database-backed tests for the grade endpoints.
+//!
+//! Requires a migrated database in `DATABASE_URL`; run manually with
+//! `cargo test --test grades_test -- --ignored`.
+
+use std::sync::Arc;
+
+use sai::models::grade::{GradeBand, GradingScale};
+use sai::services::grades::{GradeService, GradeViewer};
+use sai::services::ServiceError;
+use uuid::Uuid;
+
+async fn pool() -> sqlx::PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    sqlx::PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+async fn insert_user(pool: &sqlx::PgPool, role: &str) -> (Uuid, String) {
+    let id = Uuid::new_v4();
+    let document_id = id.simple().to_string()[..10].to_string();
+
+    sqlx::query(
+        "INSERT INTO users (id, document_id, full_name, email, birth_date, role, created_at, updated_at)
+         VALUES ($1, $2, $3, $4, '2010-01-01', $5::user_role, NOW(), NOW())",
+    )
+    .bind(id)
+    .bind(&document_id)
+    .bind(format!("Usuario {}", &document_id[..4]))
+    .bind(format!("{}@example.com", id.simple()))
+    .bind(role)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    (id, document_id)
+}
+
+async fn insert_teacher(pool: &sqlx::PgPool) -> Uuid {
+    let (id, document_id) = insert_user(pool, "Teacher").await;
+
+    sqlx::query(
+        "INSERT INTO teachers (user_id, professional_id, specialization, education_level, hire_date)
+         VALUES ($1, $2, 'Ciencias', 'Licenciatura', '2020-02-01')",
+    )
+    .bind(id)
+    .bind(format!("MEC-{}", document_id))
+    .execute(pool)
+    .await
+    .unwrap();
+
+    id
+}
+
+/// Student whose guardian is identified by `guardian_document_id`
+async fn insert_student(pool: &sqlx::PgPool, guardian_document_id: &str) -> Uuid {
+    let (id, document_id) = insert_user(pool, "Student").await;
+
+    sqlx::query(
+        "INSERT INTO students (user_id, enrollment_number, current_grade, section, academic_year, guardian_info, status)
+         VALUES ($1, $2, '1', 'A', 2025, $3, 'active')",
+    )
+    .bind(id)
+    .bind(format!("M-{}", document_id))
+    .bind(serde_json::json!({
+        "name": "Encargado",
+        "relationship": "Padre",
+        "document_id": guardian_document_id,
+        "phone": "0981123456"
+    }))
+    .execute(pool)
+    .await
+    .unwrap();
+
+    id
+}
+
+async fn insert_course(pool: &sqlx::PgPool, code: &str, teacher_id: Uuid) -> Uuid {
+    let id = Uuid::new_v4();
+
+    sqlx::query(
+        "INSERT INTO courses (id, code, name, grade_level, credits, teacher_id, academic_year, schedule)
+         VALUES ($1, $2, $3, '1', 4, $4, 2025, '[]')",
+    )
+    .bind(id)
+    .bind(format!("{}-{}", code, &id.simple().to_string()[..6]))
+    .bind(format!("Curso {}", code))
+    .bind(teacher_id)
+    .execute(pool)
+    .await
+    .unwrap();
+
+    id
+}
+
+async fn enroll(pool: &sqlx::PgPool, student_id: Uuid, course_id: Uuid) -> Uuid {
+    sqlx::query_scalar(
+        "INSERT INTO enrollments (student_id, course_id, academic_year, status)
+         VALUES ($1, $2, 2025, 'active') RETURNING id",
+    )
+    .bind(student_id)
+    .bind(course_id)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn assess(pool: &sqlx::PgPool, enrollment_id: Uuid, course_id: Uuid, period: i16, score: f64, weight: f64) {
+    sqlx::query(
+        "INSERT INTO assessments (enrollment_id, course_id, assessment_type, title, score, max_score, weight,
+                                  assessment_date, is_final, period)
+         VALUES ($1, $2, 'exam', 'Examen', $3, 50, $4, NOW(), false, $5)",
+    )
+    .bind(enrollment_id)
+    .bind(course_id)
+    .bind(score)
+    .bind(weight)
+    .bind(period)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+struct Family {
+    teacher_id: Uuid,
+    parent_id: Uuid,
+    student_id: Uuid,
+    courses: Vec<Uuid>,
+}
+
+/// A student with three courses assessed in two periods, and their parent
+async fn seed_family(pool: &sqlx::PgPool) -> Family {
+    let teacher_id = insert_teacher(pool).await;
+    let (parent_id, parent_document) = insert_user(pool, "Parent").await;
+    let student_id = insert_student(pool, &parent_document).await;
+
+    let mut courses = Vec::new();
+    for code in ["MAT", "CAS", "HIS"] {
+        let course_id = insert_course(pool, code, teacher_id).await;
+        let enrollment_id = enroll(pool, student_id, course_id).await;
+        // Period 1: 40/50 and 50/50 with weights 1 and 3 → 95%
+        assess(pool, enrollment_id, course_id, 1, 40.0, 1.0).await;
+        assess(pool, enrollment_id, course_id, 1, 50.0, 3.0).await;
+        // Period 2: 30/50 → 60%
+        assess(pool, enrollment_id, course_id, 2, 30.0, 1.0).await;
+        courses.push(course_id);
+    }
+
+    Family { teacher_id, parent_id, student_id, courses }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_student_with_three_courses_in_two_periods() {
+    let pool = pool().await;
+    let family = seed_family(&pool).await;
+    let service = GradeService::with_scale(Arc::new(pool), GradingScale::default());
+
+    let courses = service
+        .student_grades(GradeViewer::Parent(family.parent_id), family.student_id, Some(2025), None)
+        .await
+        .unwrap();
+
+    assert_eq!(courses.len(), 3);
+    for course in &courses {
+        assert_eq!(course.periods.len(), 2);
+        let first = course.periods[0].average.as_ref().unwrap();
+        assert_eq!((course.periods[0].assessment_count, first.percentage, first.grade), (2, 95.0, 5));
+        assert_eq!(course.periods[1].average.as_ref().unwrap().label, "Aceptable");
+        assert!(course.final_grade.is_none());
+    }
+
+    let second_only = service
+        .student_grades(GradeViewer::Staff, family.student_id, Some(2025), Some(2))
+        .await
+        .unwrap();
+    assert!(second_only.iter().all(|c| c.periods.len() == 1 && c.periods[0].period == 2));
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_sibling_parent_is_forbidden() {
+    let pool = pool().await;
+    let family = seed_family(&pool).await;
+    let other_family = seed_family(&pool).await;
+    let service = GradeService::with_scale(Arc::new(pool), GradingScale::default());
+
+    let result = service
+        .student_grades(GradeViewer::Parent(other_family.parent_id), family.student_id, None, None)
+        .await;
+
+    assert!(matches!(result, Err(ServiceError::AuthorizationError(_))));
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_course_view_uses_configured_scale() {
+    let pool = pool().await;
+    let family = seed_family(&pool).await;
+    let scale = GradingScale::new(vec![
+        GradeBand { min_percentage: 70.0, grade: 2, label: "Aprobado".to_string() },
+        GradeBand { min_percentage: 0.0, grade: 1, label: "Reprobado".to_string() },
+    ])
+    .unwrap();
+    let service = GradeService::with_scale(Arc::new(pool), scale);
+
+    let students = service
+        .course_grades(GradeViewer::Teacher(family.teacher_id), family.courses[0], None)
+        .await
+        .unwrap();
+
+    assert_eq!(students.len(), 1);
+    let labels: Vec<_> = students[0].periods.iter().map(|p| p.average.as_ref().unwrap().label.as_str()).collect();
+    assert_eq!(labels, vec!["Aprobado", "Reprobado"]);
+
+    let other_teacher = service
+        .course_grades(GradeViewer::Teacher(Uuid::new_v4()), family.courses[0], None)
+        .await;
+    assert!(matches!(other_teacher, Err(ServiceError::AuthorizationError(_))));
+}
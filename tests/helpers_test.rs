@@ -0,0 +1,86 @@
+//! `db::helpers` against a real database: injection payloads in identifiers are
+//! rejected before any SQL runs, and payloads in values are only ever compared.
+//!
+//! Requires `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test helpers_test -- --ignored`.
+
+use sai::db::helpers::{self, Filter, HelperError, Table};
+use sai::db::{Migration, MIGRATIONS};
+use sai::models::Role;
+use sai::testing::{fixtures, TestDb};
+
+fn users_table() -> &'static [Migration] {
+    let end = MIGRATIONS
+        .iter()
+        .position(|m| m.version == "20250313_create_users_table")
+        .expect("users migration");
+    &MIGRATIONS[..=end]
+}
+
+async fn users(db: &TestDb) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&db.pool).await.unwrap()
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_exists_and_count() {
+    let db = TestDb::with_migrations(users_table()).await;
+    let admin = fixtures::user().role(Role::Admin).create(&db.pool).await;
+    fixtures::user().create(&db.pool).await;
+
+    assert!(helpers::record_exists(&db.pool, Table::Users, "email", admin.email.as_str()).await.unwrap());
+    assert!(helpers::record_exists(&db.pool, Table::Users, "id", admin.id).await.unwrap());
+    assert!(!helpers::record_exists(&db.pool, Table::Users, "email", "nadie@example.com").await.unwrap());
+
+    assert_eq!(helpers::count_records(&db.pool, Table::Users, &[]).await.unwrap(), 2);
+    let admins = [Filter::eq("role", "Admin"), Filter::is_not_null("email")];
+    assert_eq!(helpers::count_records(&db.pool, Table::Users, &admins).await.unwrap(), 1);
+    assert_eq!(
+        helpers::count_records(&db.pool, Table::Users, &[Filter::is_null("phone")]).await.unwrap(),
+        2
+    );
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_injection_in_identifiers_is_rejected() {
+    let db = TestDb::with_migrations(users_table()).await;
+    fixtures::user().create(&db.pool).await;
+
+    for column in ["id = id OR 1=1 --", "id\" = \"id", "email; DROP TABLE users", "full_name)--", "Email"] {
+        let exists = helpers::record_exists(&db.pool, Table::Users, column, "x").await;
+        assert!(matches!(exists, Err(HelperError::UnknownColumn { .. })), "{}", column);
+
+        let count = helpers::count_records(&db.pool, Table::Users, &[Filter::is_null(column)]).await;
+        assert!(matches!(count, Err(HelperError::UnknownColumn { .. })), "{}", column);
+    }
+    // A well-formed name that is not a column of this table
+    assert!(matches!(
+        helpers::record_exists(&db.pool, Table::Users, "password_hash", "x").await,
+        Err(HelperError::UnknownColumn { .. })
+    ));
+    assert!(matches!(
+        "users; DROP TABLE users".parse::<Table>(),
+        Err(HelperError::UnknownTable(_))
+    ));
+
+    assert_eq!(users(&db).await, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_injection_in_values_is_only_compared() {
+    let db = TestDb::with_migrations(users_table()).await;
+    fixtures::user().create(&db.pool).await;
+
+    for payload in ["' OR '1'='1", "x'); DROP TABLE users; --", "\\' OR 1=1 --"] {
+        assert!(!helpers::record_exists(&db.pool, Table::Users, "email", payload).await.unwrap());
+        let count = helpers::count_records(&db.pool, Table::Users, &[Filter::eq("full_name", payload)]).await;
+        assert_eq!(count.unwrap(), 0);
+    }
+
+    assert_eq!(users(&db).await, 1);
+    db.teardown().await;
+}
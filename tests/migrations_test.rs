@@ -0,0 +1,117 @@
+//! Database-backed tests for the migration runner.
+//!
+//! Each test creates a scratch database next to the one in `DATABASE_URL`
+//! and drops it afterwards; run manually with
+//! `cargo test --test migrations_test -- --ignored`.
+
+use std::str::FromStr;
+
+use sai::config::Environment;
+use sai::db::{self, Migration, MigrationStatus};
+use sqlx::postgres::{PgConnectOptions, PgPool};
+use uuid::Uuid;
+
+const SCRATCH_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: "20250101_create_widgets",
+        sql: "CREATE TABLE widgets (id SERIAL PRIMARY KEY, name TEXT NOT NULL);",
+    },
+    Migration {
+        version: "20250102_add_widget_color",
+        sql: "ALTER TABLE widgets ADD COLUMN color TEXT;
+              CREATE INDEX idx_widgets_color ON widgets(color);",
+    },
+];
+
+struct ScratchDb {
+    admin: PgPool,
+    name: String,
+    pool: PgPool,
+}
+
+impl ScratchDb {
+    async fn create() -> Self {
+        dotenv::dotenv().ok();
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let admin = PgPool::connect(&url).await.expect("Failed to connect to database");
+
+        let name = format!("sai_migrations_{}", Uuid::new_v4().simple());
+        sqlx::query(&format!("CREATE DATABASE {}", name))
+            .execute(&admin)
+            .await
+            .expect("Failed to create scratch database");
+
+        let options = PgConnectOptions::from_str(&url).unwrap().database(&name);
+        let pool = PgPool::connect_with(options).await.expect("Failed to connect to scratch database");
+
+        Self { admin, name, pool }
+    }
+
+    async fn drop(self) {
+        self.pool.close().await;
+        sqlx::query(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", self.name))
+            .execute(&self.admin)
+            .await
+            .unwrap();
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_fresh_apply_then_idempotent_rerun() {
+    let scratch = ScratchDb::create().await;
+
+    let first = db::apply_migrations(&scratch.pool, SCRATCH_MIGRATIONS, false).await.unwrap();
+    assert_eq!(first.applied_now, vec!["20250101_create_widgets", "20250102_add_widget_color"]);
+    assert_eq!(first.already_applied, 0);
+    assert!(first.pending_after.is_empty());
+
+    // The second migration's column exists, so both scripts really ran
+    sqlx::query("INSERT INTO widgets (name, color) VALUES ('a', 'red')")
+        .execute(&scratch.pool)
+        .await
+        .unwrap();
+
+    let second = db::apply_migrations(&scratch.pool, SCRATCH_MIGRATIONS, false).await.unwrap();
+    assert!(second.applied_now.is_empty());
+    assert_eq!(second.already_applied, 2);
+    assert!(second.pending_after.is_empty());
+
+    scratch.drop().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_dry_run_lists_without_applying() {
+    let scratch = ScratchDb::create().await;
+
+    db::apply_migrations(&scratch.pool, &SCRATCH_MIGRATIONS[..1], false).await.unwrap();
+    let summary = db::apply_migrations(&scratch.pool, SCRATCH_MIGRATIONS, true).await.unwrap();
+
+    assert_eq!(summary.applied_now, vec!["20250102_add_widget_color"]);
+    assert_eq!(summary.pending_after, vec!["20250102_add_widget_color"]);
+    assert_eq!(summary.already_applied, 1);
+
+    let status = db::migration_status_for(&scratch.pool, SCRATCH_MIGRATIONS).await.unwrap();
+    assert_eq!(status.pending, vec!["20250102_add_widget_color"]);
+
+    scratch.drop().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_production_refuses_pending_migrations() {
+    let scratch = ScratchDb::create().await;
+
+    db::apply_migrations(&scratch.pool, &SCRATCH_MIGRATIONS[..1], false).await.unwrap();
+    let status: MigrationStatus = db::migration_status_for(&scratch.pool, SCRATCH_MIGRATIONS).await.unwrap();
+
+    assert!(db::check_startup_migrations(&status, Environment::Production, false).is_err());
+    assert!(db::check_startup_migrations(&status, Environment::Production, true).is_ok());
+
+    db::apply_migrations(&scratch.pool, SCRATCH_MIGRATIONS, false).await.unwrap();
+    let status = db::migration_status_for(&scratch.pool, SCRATCH_MIGRATIONS).await.unwrap();
+    assert!(db::check_startup_migrations(&status, Environment::Production, false).is_ok());
+
+    scratch.drop().await;
+}
@@ -0,0 +1,106 @@
+//! Paginated listings of the services, through the `sai::testing` harness.
+//!
+//! Needs `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test pagination_test -- --ignored`.
+
+use std::sync::Arc;
+
+use actix_web::web;
+use sai::services::courses::CourseService;
+use sai::services::students::StudentService;
+use sai::services::teachers::TeacherService;
+use sai::services::users::{UserPagination, UserService};
+use sai::testing::{fixtures, TestDb};
+
+#[actix_rt::test]
+#[ignore]
+async fn test_students_second_page() {
+    let db = TestDb::new().await;
+    for _ in 0..3 {
+        fixtures::student_with_user().create(&db.pool).await;
+    }
+    let service = StudentService::new(web::Data::new(db.pool.clone()));
+
+    let all = service.get_all_students(None, 1, 3).await.unwrap();
+    let page = service.get_all_students(None, 2, 1).await.unwrap();
+
+    assert_eq!((page.total, page.page, page.page_size, page.total_pages), (3, 2, 1, 3));
+    assert_eq!(page.data.len(), 1);
+    assert_eq!(page.data[0].user_id, all.data[1].user_id);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_teachers_second_page() {
+    let db = TestDb::new().await;
+    for _ in 0..3 {
+        fixtures::teacher_with_user().create(&db.pool).await;
+    }
+    let service = TeacherService::new(Arc::new(db.pool.clone()));
+
+    let all = service.get_all_teachers(None, 1, 3).await.unwrap();
+    let page = service.get_all_teachers(None, 2, 1).await.unwrap();
+
+    assert_eq!((page.total, page.page, page.page_size, page.total_pages), (3, 2, 1, 3));
+    assert_eq!(page.data.len(), 1);
+    assert_eq!(page.data[0].user_id, all.data[1].user_id);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_courses_second_page() {
+    let db = TestDb::new().await;
+    for name in ["Biología", "Historia", "Matemática"] {
+        fixtures::course().name(name).create(&db.pool).await;
+    }
+    let service = CourseService::new(Arc::new(db.pool.clone()));
+
+    let page = service.get_all_courses(2, 1).await.unwrap();
+
+    // Courses are listed by name
+    assert_eq!((page.total, page.page, page.page_size, page.total_pages), (3, 2, 1, 3));
+    assert_eq!(page.data.len(), 1);
+    assert_eq!(page.data[0].name, "Historia");
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_users_second_page() {
+    let db = TestDb::new().await;
+    for _ in 0..3 {
+        fixtures::user().create(&db.pool).await;
+    }
+    let service = UserService::new(Arc::new(db.pool.clone()));
+
+    let all = service
+        .get_all_users(None, UserPagination::Offset { page: 1, per_page: 3 })
+        .await
+        .unwrap();
+    let page = service
+        .get_all_users(None, UserPagination::Offset { page: 2, per_page: 1 })
+        .await
+        .unwrap();
+
+    assert_eq!(page.total, 3);
+    assert!(page.has_more);
+    assert_eq!(page.users.len(), 1);
+    assert_eq!(page.users[0].id, all.users[1].id);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_page_past_the_end_is_empty() {
+    let db = TestDb::new().await;
+    fixtures::course().create(&db.pool).await;
+    let service = CourseService::new(Arc::new(db.pool.clone()));
+
+    let page = service.get_all_courses(5, 10).await.unwrap();
+
+    assert!(page.data.is_empty());
+    assert_eq!((page.total, page.total_pages), (1, 1));
+    db.teardown().await;
+}
@@ -0,0 +1,86 @@
+//! Query metrics: a slow query is logged and sampled under its operation.
+//!
+//! Requires `DATABASE_URL`; run it manually with
+//! `cargo test --test query_metrics_test -- --ignored`.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use sai::db::metrics;
+use sqlx::postgres::PgPoolOptions;
+
+/// Keeps every warning logged by this test binary
+struct CapturedLogs(Mutex<Vec<String>>);
+
+impl Log for CapturedLogs {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGS: CapturedLogs = CapturedLogs(Mutex::new(Vec::new()));
+
+fn capture_logs() {
+    let _ = log::set_logger(&LOGS);
+    log::set_max_level(LevelFilter::Warn);
+}
+
+fn operation_stats(operation: &str) -> Option<metrics::QueryStats> {
+    metrics::query_stats().into_iter().find(|stats| stats.operation == operation)
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_slow_query_is_logged_and_sampled() {
+    dotenv::dotenv().ok();
+    capture_logs();
+    metrics::set_slow_query_threshold(Duration::from_millis(50));
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new().max_connections(1).connect(&url).await.unwrap();
+
+    let sql = "SELECT pg_sleep(0.2), 'secret@example.com' AS email";
+    metrics::timed_query("test.pg_sleep", sql, sqlx::query(sql).execute(&pool)).await.unwrap();
+
+    let stats = operation_stats("test.pg_sleep").expect("no sample for test.pg_sleep");
+    assert_eq!(stats.durations.count, 1);
+    assert_eq!(stats.durations.slow, 1);
+    assert!(stats.durations.total_ms >= 200.0);
+
+    let logs = LOGS.0.lock().unwrap();
+    let warning = logs
+        .iter()
+        .find(|line| line.starts_with("Slow query test.pg_sleep took"))
+        .unwrap_or_else(|| panic!("no slow-query warning in {:?}", logs));
+    assert!(warning.contains("(threshold 50 ms): SELECT pg_sleep(0.2), '?' AS email"));
+    assert!(!warning.contains("secret@example.com"));
+
+    let text = metrics::render_query_prometheus(&metrics::query_stats());
+    assert!(text.contains("sai_db_query_duration_seconds_count{operation=\"test.pg_sleep\"} 1\n"));
+    assert!(text.contains("sai_db_slow_queries_total{operation=\"test.pg_sleep\"} 1\n"));
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_fast_query_is_sampled_without_warning() {
+    dotenv::dotenv().ok();
+    capture_logs();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let pool = PgPoolOptions::new().max_connections(1).connect(&url).await.unwrap();
+
+    let sql = "SELECT 1";
+    metrics::timed_query("test.select_one", sql, sqlx::query(sql).execute(&pool)).await.unwrap();
+
+    let stats = operation_stats("test.select_one").expect("no sample for test.select_one");
+    assert_eq!(stats.durations.count, 1);
+    assert_eq!(stats.durations.slow, 0);
+    assert!(!LOGS.0.lock().unwrap().iter().any(|line| line.starts_with("Slow query test.select_one")));
+}
@@ -0,0 +1,149 @@
+//! Refresh tokens stored in `refresh_tokens`, through the `sai::testing` harness.
+//!
+//! Needs `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test refresh_token_test -- --ignored`.
+
+use chrono::Duration;
+
+use sai::models::authentication::{Authentication, NewAuthentication, RefreshToken, Rotation};
+use sai::models::User;
+use sai::testing::{fixtures, TestDb};
+
+async fn account(db: &TestDb) -> (User, Authentication) {
+    let user = fixtures::user().create(&db.pool).await;
+    let account = Authentication::create(
+        &db.pool,
+        NewAuthentication { user_id: user.id, password: "clave-segura".to_string() },
+    )
+    .await
+    .unwrap();
+    (user, account)
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_only_the_hash_is_stored() {
+    let db = TestDb::new().await;
+    let (user, _) = account(&db).await;
+
+    let (token, stored) = RefreshToken::issue(&db.pool, user.id, None, Duration::days(7)).await.unwrap();
+
+    assert_eq!(stored.user_id, user.id);
+    assert_eq!(stored.token_hash, RefreshToken::hash(&token));
+    assert_ne!(stored.token_hash, token);
+    assert!(stored.is_active());
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_rotation_invalidates_the_old_token() {
+    let db = TestDb::new().await;
+    let (user, before) = account(&db).await;
+    let (token, first) = RefreshToken::issue(&db.pool, user.id, None, Duration::days(7)).await.unwrap();
+
+    let Rotation::Rotated { token: rotated, stored, token_version } =
+        RefreshToken::rotate(&db.pool, &token, None, Duration::days(7)).await.unwrap()
+    else {
+        panic!("token not rotated");
+    };
+    assert_ne!(rotated, token);
+    assert_eq!(stored.user_id, user.id);
+    assert_eq!(stored.family_id, first.family_id);
+    assert_eq!(token_version, before.token_version + 1);
+
+    let Rotation::Rotated { token_version, .. } = RefreshToken::rotate(&db.pool, &rotated, None, Duration::days(7)).await.unwrap()
+    else {
+        panic!("rotated token not accepted");
+    };
+    let after = Authentication::find_by_user_id(&db.pool, user.id).await.unwrap();
+    assert_eq!(after.token_version, before.token_version + 2);
+    assert_eq!(token_version, after.token_version);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_reusing_a_rotated_token_revokes_its_family() {
+    let db = TestDb::new().await;
+    let (user, _) = account(&db).await;
+    let (token, _) = RefreshToken::issue(&db.pool, user.id, None, Duration::days(7)).await.unwrap();
+    let (other_session, _) = RefreshToken::issue(&db.pool, user.id, None, Duration::days(7)).await.unwrap();
+    let Rotation::Rotated { token: rotated, .. } = RefreshToken::rotate(&db.pool, &token, None, Duration::days(7)).await.unwrap()
+    else {
+        panic!("token not rotated");
+    };
+    let before = Authentication::find_by_user_id(&db.pool, user.id).await.unwrap();
+
+    // A token can only be exchanged once; the replay closes the session it came from
+    match RefreshToken::rotate(&db.pool, &token, None, Duration::days(7)).await.unwrap() {
+        Rotation::Reused { user_id, token_version } => {
+            assert_eq!(user_id, user.id);
+            assert_eq!(token_version, before.token_version + 1);
+        }
+        other => panic!("reuse not detected: {:?}", other),
+    }
+    assert!(matches!(
+        RefreshToken::rotate(&db.pool, &rotated, None, Duration::days(7)).await.unwrap(),
+        Rotation::Reused { .. }
+    ));
+
+    // Sessions started by other logins are not part of the family
+    assert!(matches!(
+        RefreshToken::rotate(&db.pool, &other_session, None, Duration::days(7)).await.unwrap(),
+        Rotation::Rotated { .. }
+    ));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_expired_and_unknown_tokens_are_rejected() {
+    let db = TestDb::new().await;
+    let (user, _) = account(&db).await;
+    let (expired, _) = RefreshToken::issue(&db.pool, user.id, None, Duration::seconds(-1)).await.unwrap();
+
+    assert!(matches!(RefreshToken::rotate(&db.pool, &expired, None, Duration::days(7)).await.unwrap(), Rotation::Invalid));
+    assert!(matches!(RefreshToken::rotate(&db.pool, "desconocido", None, Duration::days(7)).await.unwrap(), Rotation::Invalid));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_revoke_all_only_touches_the_user() {
+    let db = TestDb::new().await;
+    let (user, _) = account(&db).await;
+    let (other, _) = account(&db).await;
+    let (first, _) = RefreshToken::issue(&db.pool, user.id, None, Duration::days(7)).await.unwrap();
+    let (second, _) = RefreshToken::issue(&db.pool, user.id, None, Duration::days(7)).await.unwrap();
+    let (kept, _) = RefreshToken::issue(&db.pool, other.id, None, Duration::days(7)).await.unwrap();
+
+    assert!(RefreshToken::revoke(&db.pool, &first).await.unwrap());
+    assert!(!RefreshToken::revoke(&db.pool, &first).await.unwrap());
+    assert_eq!(RefreshToken::revoke_all_for_user(&db.pool, user.id).await.unwrap(), 1);
+
+    assert!(matches!(RefreshToken::rotate(&db.pool, &second, None, Duration::days(7)).await.unwrap(), Rotation::Reused { .. }));
+    assert!(matches!(RefreshToken::rotate(&db.pool, &kept, None, Duration::days(7)).await.unwrap(), Rotation::Rotated { .. }));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_rotation_keeps_the_institution_of_the_session() {
+    let db = TestDb::new().await;
+    let (user, _) = account(&db).await;
+    let (token, _) = RefreshToken::issue(&db.pool, user.id, Some("colegio_a"), Duration::days(7)).await.unwrap();
+
+    // Another institution or none does not match, and the token stays usable
+    for institution in [None, Some("colegio_b")] {
+        let rotation = RefreshToken::rotate(&db.pool, &token, institution, Duration::days(7)).await.unwrap();
+        assert!(matches!(rotation, Rotation::Invalid));
+    }
+
+    let Rotation::Rotated { stored, .. } = RefreshToken::rotate(&db.pool, &token, Some("colegio_a"), Duration::days(7)).await.unwrap()
+    else {
+        panic!("expected a rotation");
+    };
+    assert_eq!(stored.institution.as_deref(), Some("colegio_a"));
+    db.teardown().await;
+}
@@ -0,0 +1,248 @@
+//! Savepoints: a failing item only rolls back itself in lenient mode, strict mode
+//! still aborts the whole transaction.
+//!
+//! The helper tests only need the users table. The enrollment and assessment tests
+//! need the full schema (see the note in docs/database.md). Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test savepoint_test -- --ignored`.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use sai::db::{seed, DbError, Migration, MIGRATIONS};
+use sai::models::assessment::{Assessment, AssessmentType, NewAssessment};
+use sai::models::audit_log::NewAuditLogEntry;
+use sai::models::User;
+use sai::services::batch::{BatchItemStatus, BatchRequest};
+use sai::services::enrollments::EnrollmentService;
+use sai::testing::{fixtures, TestDb};
+use uuid::Uuid;
+
+fn users_table() -> &'static [Migration] {
+    let end = MIGRATIONS
+        .iter()
+        .position(|m| m.version == "20250313_create_users_table")
+        .expect("users migration");
+    &MIGRATIONS[..=end]
+}
+
+async fn user_count(db: &TestDb) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&db.pool).await.unwrap()
+}
+
+/// Three users, the second of which reuses the first one's email
+fn users_with_duplicate() -> Vec<sai::models::user::CreateUserDto> {
+    vec![
+        fixtures::user().email("uno@example.com").dto(),
+        fixtures::user().email("uno@example.com").dto(),
+        fixtures::user().email("tres@example.com").dto(),
+    ]
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_failing_item_rolls_back_only_itself() {
+    let db = TestDb::with_migrations(users_table()).await;
+
+    let results = sai::tx!(&db.pool, |tx| {
+        let mut results = Vec::new();
+        for dto in users_with_duplicate() {
+            results.push(sai::savepoint!(tx, "item", |sp| { User::create(&mut **sp, dto).await }).await);
+        }
+        Ok::<_, DbError>(results)
+    })
+    .await
+    .unwrap();
+
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(DbError::Conflict(_))));
+    assert!(results[2].is_ok());
+    assert_eq!(user_count(&db).await, 2);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_strict_mode_aborts_everything() {
+    let db = TestDb::with_migrations(users_table()).await;
+
+    let result = sai::tx!(&db.pool, |tx| {
+        for dto in users_with_duplicate() {
+            User::create(&mut **tx, dto).await?;
+        }
+        Ok::<_, DbError>(())
+    })
+    .await;
+
+    assert!(matches!(result, Err(DbError::Conflict(_))));
+    assert_eq!(user_count(&db).await, 0);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_invalid_savepoint_name_is_rejected() {
+    let db = TestDb::with_migrations(users_table()).await;
+
+    let result = sai::tx!(&db.pool, |tx| {
+        sai::savepoint!(tx, "item; DROP TABLE users", |sp| {
+            sqlx::query("SELECT 1").execute(&mut **sp).await.map(|_| ())
+        })
+        .await
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(user_count(&db).await, 0);
+    db.teardown().await;
+}
+
+/// Seeded school plus three enrollments, the second of which cannot be deleted
+async fn school_with_held_enrollment() -> (TestDb, Vec<Uuid>) {
+    let db = TestDb::new().await;
+    seed::seed_demo_school(&db.pool).await.unwrap();
+    let ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM enrollments ORDER BY id LIMIT 3")
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE enrollment_holds (enrollment_id UUID REFERENCES enrollments(id))")
+        .execute(&db.pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO enrollment_holds VALUES ($1)").bind(ids[1]).execute(&db.pool).await.unwrap();
+    (db, ids)
+}
+
+async fn remaining(db: &TestDb, ids: &[Uuid]) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM enrollments WHERE id = ANY($1)")
+        .bind(ids)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap()
+}
+
+/// IDs of the enrollments with an `enrollment.delete` audit event
+async fn audited(db: &TestDb) -> Vec<Uuid> {
+    sqlx::query_scalar("SELECT entity_id FROM audit_logs WHERE action = 'enrollment.delete' ORDER BY entity_id")
+        .fetch_all(&db.pool)
+        .await
+        .unwrap()
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_lenient_batch_delete_keeps_the_other_deletions() {
+    let (db, ids) = school_with_held_enrollment().await;
+    let service = EnrollmentService::new(Arc::new(db.pool.clone()));
+
+    let actor = fixtures::user().create(&db.pool).await;
+    let audit = NewAuditLogEntry { actor_id: Some(actor.id), ..Default::default() };
+
+    let result = service.batch_delete(BatchRequest { ids: ids.clone(), strict: false }, audit).await.unwrap();
+
+    assert!(result.committed);
+    let statuses: Vec<_> = result.outcomes.iter().map(|o| o.status).collect();
+    assert_eq!(statuses, [BatchItemStatus::Applied, BatchItemStatus::Failed, BatchItemStatus::Applied]);
+    assert_eq!(remaining(&db, &ids).await, 1);
+
+    // Only the deleted enrollments are audited, attributed to the caller
+    let mut deleted = vec![ids[0], ids[2]];
+    deleted.sort();
+    assert_eq!(audited(&db).await, deleted);
+    let actors: Vec<Option<Uuid>> = sqlx::query_scalar("SELECT actor_id FROM audit_logs WHERE action = 'enrollment.delete'")
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+    assert!(actors.iter().all(|id| *id == Some(actor.id)));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_strict_batch_delete_rolls_back_everything() {
+    let (db, ids) = school_with_held_enrollment().await;
+    let service = EnrollmentService::new(Arc::new(db.pool.clone()));
+
+    let result = service
+        .batch_delete(BatchRequest { ids: ids.clone(), strict: true }, NewAuditLogEntry::default())
+        .await
+        .unwrap();
+
+    assert!(!result.committed);
+    assert!(result.outcomes.iter().all(|o| o.status == BatchItemStatus::RolledBack));
+    assert_eq!(remaining(&db, &ids).await, 3);
+    assert!(audited(&db).await.is_empty());
+    db.teardown().await;
+}
+
+fn new_assessment(enrollment_id: Uuid, course_id: Uuid, title: &str) -> NewAssessment {
+    NewAssessment {
+        enrollment_id,
+        course_id,
+        assessment_type: AssessmentType::Quiz,
+        title: title.to_string(),
+        description: None,
+        score: 7.0,
+        max_score: 10.0,
+        weight: 10.0,
+        assessment_date: Utc::now(),
+        is_final: false,
+        period: 1,
+        comments: None,
+    }
+}
+
+/// Three new assessments; the second one points to a course that does not exist
+async fn assessments_with_missing_course(db: &TestDb) -> Vec<NewAssessment> {
+    let (enrollment_id, course_id): (Uuid, Uuid) =
+        sqlx::query_as("SELECT id, course_id FROM enrollments ORDER BY id LIMIT 1")
+            .fetch_one(&db.pool)
+            .await
+            .unwrap();
+    vec![
+        new_assessment(enrollment_id, course_id, "Savepoint 1"),
+        new_assessment(enrollment_id, Uuid::new_v4(), "Savepoint 2"),
+        new_assessment(enrollment_id, course_id, "Savepoint 3"),
+    ]
+}
+
+async fn savepoint_assessments(db: &TestDb) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM assessments WHERE title LIKE 'Savepoint %'")
+        .fetch_one(&db.pool)
+        .await
+        .unwrap()
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_lenient_assessment_batch_keeps_valid_items() {
+    let db = TestDb::new().await;
+    seed::seed_demo_school(&db.pool).await.unwrap();
+    let assessments = assessments_with_missing_course(&db).await;
+
+    let results = sai::tx!(&db.pool, |tx| {
+        Ok::<_, DbError>(Assessment::create_batch_lenient(tx, assessments).await)
+    })
+    .await
+    .unwrap();
+
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(DbError::Conflict(_))));
+    assert!(results[2].is_ok());
+    assert_eq!(savepoint_assessments(&db).await, 2);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_strict_assessment_batch_creates_nothing() {
+    let db = TestDb::new().await;
+    seed::seed_demo_school(&db.pool).await.unwrap();
+    let assessments = assessments_with_missing_course(&db).await;
+
+    let result = sai::tx!(&db.pool, |tx| { Assessment::create_batch(tx, assessments).await }).await;
+
+    assert!(matches!(result, Err(DbError::Conflict(_))));
+    assert_eq!(savepoint_assessments(&db).await, 0);
+    db.teardown().await;
+}
@@ -0,0 +1,77 @@
+//! Demo seed data: idempotency, created counts and `unseed`, through the `sai::testing` harness.
+//!
+//! Needs the full schema (see the note in docs/database.md) and `TEST_DATABASE_URL`;
+//! run with `cargo test --features testing --test seed_test -- --ignored`.
+
+use sai::db::seed::{self, SeedSummary};
+use sai::testing::{fixtures, TestDb};
+
+async fn count(db: &TestDb, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+        .fetch_one(&db.pool)
+        .await
+        .unwrap()
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_demo_school_counts() {
+    let db = TestDb::new().await;
+
+    let summary = seed::seed_demo_school(&db.pool).await.unwrap();
+
+    assert_eq!(
+        summary,
+        SeedSummary {
+            users: 10,
+            teachers: 3,
+            students: 6,
+            classrooms: 3,
+            courses: 3,
+            enrollments: 18,
+            assessments: 18,
+            payments: 60,
+        }
+    );
+    assert_eq!(count(&db, "users").await, 10);
+    assert_eq!(count(&db, "payments").await, 60);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_seeding_twice_creates_nothing_new() {
+    let db = TestDb::new().await;
+
+    seed::seed_demo_school(&db.pool).await.unwrap();
+    let rows = count(&db, "seed_rows").await;
+    let second = seed::seed_demo_school(&db.pool).await.unwrap();
+
+    assert_eq!(second, SeedSummary::default());
+    assert_eq!(count(&db, "seed_rows").await, rows);
+    assert_eq!(seed::seed_minimal(&db.pool).await.unwrap(), SeedSummary::default());
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_unseed_removes_only_seeded_rows() {
+    let db = TestDb::new().await;
+    let bystander = fixtures::user().create(&db.pool).await;
+    seed::seed_demo_school(&db.pool).await.unwrap();
+    let tagged = count(&db, "seed_rows").await;
+
+    let deleted = seed::unseed(&db.pool).await.unwrap();
+
+    assert_eq!(deleted as i64, tagged);
+    assert_eq!(count(&db, "seed_rows").await, 0);
+    assert_eq!(count(&db, "users").await, 1);
+    assert_eq!(count(&db, "courses").await, 0);
+    assert_eq!(count(&db, "payments").await, 0);
+    let remaining: uuid::Uuid = sqlx::query_scalar("SELECT id FROM users").fetch_one(&db.pool).await.unwrap();
+    assert_eq!(remaining, bystander.id);
+
+    // Seeding again after unseed starts from scratch
+    assert_eq!(seed::seed_minimal(&db.pool).await.unwrap().users, 1);
+    db.teardown().await;
+}
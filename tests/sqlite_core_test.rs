@@ -0,0 +1,262 @@
+//! Core CRUD against the SQLite backend.
+//!
+//! Each test runs on its own in-memory database, so no server is needed and the
+//! tests are not ignored. Run with `cargo test --features db-sqlite --test sqlite_core_test`.
+
+use chrono::{NaiveDate, Utc};
+use sai::db::sqlite::{self, SqlitePool, SQLITE_MIGRATIONS};
+use sai::models::{
+    assessment::{AssessmentType, NewAssessment},
+    attendance::{AttendanceStatus, NewAttendance},
+    course::CreateCourseDto,
+    enrollment::{EnrollmentStatus, NewEnrollment},
+    payment::NewPayment,
+    student::CreateStudentDto,
+    user::CreateUserDto,
+    Course, GuardianInfo, PaymentStatus, Role, ScheduleSlot, Student, StudentStatus, User,
+};
+use uuid::Uuid;
+
+async fn pool() -> SqlitePool {
+    let pool = sqlite::connect("sqlite::memory:").await.unwrap();
+    sqlite::migrate(&pool).await.unwrap();
+    pool
+}
+
+fn user_dto(email: &str, role: Role) -> CreateUserDto {
+    CreateUserDto {
+        document_id: Uuid::new_v4().simple().to_string()[..10].to_string(),
+        full_name: "María Benítez".to_string(),
+        email: email.to_string(),
+        phone: Some("0981 123 456".to_string()),
+        address: None,
+        birth_date: NaiveDate::from_ymd_opt(2010, 5, 14).unwrap(),
+        role,
+    }
+}
+
+async fn student(pool: &SqlitePool, email: &str) -> Student {
+    let user = sqlite::create_user(pool, user_dto(email, Role::Student)).await.unwrap();
+    sqlite::create_student(
+        pool,
+        CreateStudentDto {
+            user_id: user.id,
+            enrollment_number: format!("2025-{}", &user.document_id[..4]),
+            current_grade: "8vo".to_string(),
+            section: "A".to_string(),
+            academic_year: 2025,
+            guardian_info: Some(GuardianInfo {
+                name: "Rosa Benítez".to_string(),
+                relationship: "madre".to_string(),
+                document_id: "3456789".to_string(),
+                email: None,
+                phone: "0982 654 321".to_string(),
+                weekly_summary: false,
+            }),
+            status: StudentStatus::Active,
+        },
+    )
+    .await
+    .unwrap()
+}
+
+async fn course(pool: &SqlitePool, code: &str, name: &str) -> Course {
+    sqlite::create_course(
+        pool,
+        CreateCourseDto {
+            code: code.to_string(),
+            name: name.to_string(),
+            description: Some(format!("{} para octavo grado", name)),
+            grade_level: "8vo".to_string(),
+            credits: 4.0,
+            teacher_id: None,
+            academic_year: 2025,
+            schedule: vec![ScheduleSlot {
+                day_of_week: 1,
+                start_time: "07:00".to_string(),
+                end_time: "08:20".to_string(),
+                classroom: "Aula 3".to_string(),
+            }],
+        },
+    )
+    .await
+    .unwrap()
+}
+
+fn enrollment(student: &Student, course: &Course) -> NewEnrollment {
+    NewEnrollment {
+        student_id: student.user_id,
+        course_id: course.id,
+        academic_year: 2025,
+        status: Some(EnrollmentStatus::Active),
+        notes: None,
+        payment_info: None,
+    }
+}
+
+#[actix_rt::test]
+async fn test_migrations_apply_once() {
+    let pool = sqlite::connect("sqlite::memory:").await.unwrap();
+
+    let first = sqlite::migrate(&pool).await.unwrap();
+    let second = sqlite::migrate(&pool).await.unwrap();
+
+    assert_eq!(first.applied_now.len(), SQLITE_MIGRATIONS.len());
+    assert!(second.applied_now.is_empty());
+    assert_eq!(second.already_applied, SQLITE_MIGRATIONS.len());
+}
+
+#[actix_rt::test]
+async fn test_users_and_students_round_trip() {
+    let pool = pool().await;
+
+    let admin = sqlite::create_user(&pool, user_dto("admin@colegio.edu.py", Role::Admin)).await.unwrap();
+    let found: User = sqlite::find_user_by_email(&pool, "admin@colegio.edu.py").await.unwrap().unwrap();
+    assert_eq!(found.id, admin.id);
+    assert!(matches!(found.role, Role::Admin));
+    assert_eq!(found.birth_date, NaiveDate::from_ymd_opt(2010, 5, 14).unwrap());
+
+    let created = student(&pool, "maria@colegio.edu.py").await;
+    let found = sqlite::find_student(&pool, created.user_id).await.unwrap().unwrap();
+    assert_eq!(found.enrollment_number, created.enrollment_number);
+    assert_eq!(found.guardian_info.unwrap().name, "Rosa Benítez");
+    assert!(matches!(found.status, StudentStatus::Active));
+
+    // Emails are unique
+    let duplicate = sqlite::create_user(&pool, user_dto("admin@colegio.edu.py", Role::Teacher)).await;
+    assert!(duplicate.is_err());
+    assert!(sqlite::find_user(&pool, Uuid::new_v4()).await.unwrap().is_none());
+}
+
+#[actix_rt::test]
+async fn test_enrollments_attendance_and_grades() {
+    let pool = pool().await;
+    let maria = student(&pool, "maria@colegio.edu.py").await;
+    let teacher = sqlite::create_user(&pool, user_dto("prof@colegio.edu.py", Role::Teacher)).await.unwrap();
+    let math = course(&pool, "MAT-8", "Matemática").await;
+    assert_eq!(sqlite::find_course(&pool, math.id).await.unwrap().unwrap().schedule.len(), 1);
+
+    let enrolled = sqlite::create_enrollment(&pool, &enrollment(&maria, &math)).await.unwrap();
+    assert_eq!(enrolled.status, EnrollmentStatus::Active);
+    assert!(sqlite::create_enrollment(&pool, &enrollment(&maria, &math)).await.is_err());
+    assert_eq!(sqlite::enrollments_for_student(&pool, maria.user_id).await.unwrap().len(), 1);
+
+    let today = Utc::now().date_naive();
+    let attendance = sqlite::record_attendance(
+        &pool,
+        &NewAttendance {
+            student_id: maria.user_id,
+            course_id: math.id,
+            date: today,
+            status: AttendanceStatus::Late,
+            notes: None,
+            minutes_late: Some(10),
+            recorded_by: teacher.id,
+        },
+    )
+    .await
+    .unwrap();
+    let taken = sqlite::attendance_for_course(&pool, math.id, today).await.unwrap();
+    assert_eq!(taken.len(), 1);
+    assert_eq!(taken[0].id, attendance.id);
+    assert!(matches!(taken[0].status, AttendanceStatus::Late));
+    assert_eq!(taken[0].minutes_late, Some(10));
+
+    let other = student(&pool, "jose@colegio.edu.py").await;
+    sqlite::record_attendance(
+        &pool,
+        &NewAttendance {
+            student_id: other.user_id,
+            course_id: math.id,
+            date: today,
+            status: AttendanceStatus::Excused,
+            notes: Some("Certificado médico".to_string()),
+            minutes_late: None,
+            recorded_by: teacher.id,
+        },
+    )
+    .await
+    .unwrap();
+    let taken = sqlite::attendance_for_course(&pool, math.id, today).await.unwrap();
+    assert_eq!(taken.iter().filter(|record| record.status == AttendanceStatus::Excused).count(), 1);
+
+    let grade = |score: f64| NewAssessment {
+        enrollment_id: enrolled.id,
+        course_id: math.id,
+        assessment_type: AssessmentType::Exam,
+        title: "Primer parcial".to_string(),
+        description: None,
+        score,
+        max_score: 20.0,
+        weight: 0.3,
+        assessment_date: Utc::now(),
+        is_final: false,
+        period: 1,
+        comments: None,
+    };
+    sqlite::create_assessment(&pool, grade(17.5)).await.unwrap();
+    assert!(sqlite::create_assessment(&pool, grade(25.0)).await.is_err());
+    let grades = sqlite::assessments_for_enrollment(&pool, enrolled.id).await.unwrap();
+    assert_eq!(grades.len(), 1);
+    assert_eq!(grades[0].score, 17.5);
+    assert!(matches!(grades[0].assessment_type, AssessmentType::Exam));
+}
+
+#[actix_rt::test]
+async fn test_payments() {
+    let pool = pool().await;
+    let maria = student(&pool, "maria@colegio.edu.py").await;
+    let payment = |student_id: Uuid| NewPayment {
+        student_id,
+        concept: "Cuota marzo".to_string(),
+        amount: 350_000.0,
+        currency: None,
+        payment_date: Utc::now(),
+        payment_method: "efectivo".to_string(),
+        status: Some(PaymentStatus::Completed),
+        receipt_number: Some("001-001-0000123".to_string()),
+        payer_ruc: None,
+        notes: None,
+    };
+
+    let created = sqlite::create_payment(&pool, &payment(maria.user_id)).await.unwrap();
+
+    assert_eq!(created.currency, "PYG");
+    let payments = sqlite::payments_for_student(&pool, maria.user_id).await.unwrap();
+    assert_eq!(payments.len(), 1);
+    assert_eq!(payments[0].status, PaymentStatus::Completed);
+    assert_eq!(payments[0].amount, 350_000.0);
+    // Foreign keys are enforced
+    assert!(sqlite::create_payment(&pool, &payment(Uuid::new_v4())).await.is_err());
+}
+
+#[actix_rt::test]
+async fn test_similar_courses_without_pg_trgm() {
+    let pool = pool().await;
+    let math = course(&pool, "MAT-8", "Matemática").await;
+    course(&pool, "MAT-9", "Matemática Aplicada").await;
+    course(&pool, "HIS-8", "Historia").await;
+
+    let similar = sqlite::find_similar_courses(&pool, math.id, 0.3).await.unwrap();
+
+    assert_eq!(similar.len(), 1);
+    assert_eq!(similar[0].0.code, "MAT-9");
+    assert!(similar[0].1 > 0.3 && similar[0].1 < 1.0);
+}
+
+#[actix_rt::test]
+async fn test_enrollment_status_decoding_is_strict() {
+    use sqlx::Row;
+
+    let pool = pool().await;
+
+    for status in EnrollmentStatus::ALL {
+        let row = sqlx::query("SELECT $1 AS status").bind(status).fetch_one(&pool).await.unwrap();
+        assert_eq!(row.get::<String, _>("status"), status.as_str());
+        assert_eq!(row.try_get::<EnrollmentStatus, _>("status").unwrap(), status);
+    }
+
+    // A typo is a decode error, not a silently active enrollment
+    let row = sqlx::query("SELECT 'actve' AS status").fetch_one(&pool).await.unwrap();
+    assert!(row.try_get::<EnrollmentStatus, _>("status").is_err());
+}
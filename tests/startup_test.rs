@@ -0,0 +1,73 @@
+//! Database findings of `sai::startup::self_check` against a `sai::testing`
+//! schema: pending migrations and the demo admin password.
+//!
+//! Needs `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test startup_test -- --ignored`.
+
+use sai::config::{AppConfig, Environment};
+use sai::db::{self, seed};
+use sai::startup::{self_check, Severity};
+use sai::testing::TestDb;
+
+/// Configuration without findings of its own
+fn secure(environment: Environment) -> AppConfig {
+    let mut config = AppConfig { environment, ..Default::default() };
+    config.jwt.secret = Some("0123456789abcdef0123456789abcdef".to_string());
+    config.jwt.demo_login = Some(false);
+    config
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_migrated_database_has_no_findings() {
+    let db = TestDb::new().await;
+
+    let check = self_check(&secure(Environment::Production), &db.pool).await;
+
+    assert!(check.findings.is_empty(), "{:?}", check);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_pending_migrations() {
+    let (last, applied) = db::MIGRATIONS.split_last().unwrap();
+    let db = TestDb::with_migrations(applied).await;
+
+    let check = self_check(&secure(Environment::Production), &db.pool).await;
+
+    let finding = check.findings.iter().find(|finding| finding.code == "pending_migrations").unwrap();
+    assert_eq!(finding.severity, Severity::Warn);
+    assert!(finding.message.contains(last.version), "{}", finding.message);
+    assert!(!check.blocks_serving(Environment::Production));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_default_admin_password_blocks_production() {
+    let db = TestDb::new().await;
+    seed::seed(&db.pool, seed::SeedSet::Minimal).await.unwrap();
+
+    let check = self_check(&secure(Environment::Staging), &db.pool).await;
+    assert!(check.has("default_admin_password"));
+    assert!(!check.blocks_serving(Environment::Staging));
+
+    let check = self_check(&secure(Environment::Production), &db.pool).await;
+    assert_eq!(check.errors().map(|finding| finding.code).collect::<Vec<_>>(), vec!["default_admin_password"]);
+    assert!(check.blocks_serving(Environment::Production));
+
+    // Once the admin picks another password the finding goes away
+    sqlx::query(
+        "UPDATE authentications SET password_hash = $1
+         WHERE user_id = (SELECT id FROM users WHERE email = $2)",
+    )
+    .bind(sai::utils::password::hash_password("otra-clave-segura").unwrap())
+    .bind(seed::ADMIN_EMAIL)
+    .execute(&db.pool)
+    .await
+    .unwrap();
+    let check = self_check(&secure(Environment::Production), &db.pool).await;
+    assert!(check.findings.is_empty(), "{:?}", check);
+    db.teardown().await;
+}
@@ -0,0 +1,82 @@
+//! Database-backed tests for the session settings applied by `DbManager`: timeouts, time zone
+//! and application name.
+//!
+//! Run manually with `cargo test --test statement_timeout_test -- --ignored`.
+
+use std::time::Duration;
+
+use sai::db::{DbConfig, DbManager, RetryPolicy, TlsConfig};
+use sai::services::{with_timeout, ServiceError};
+
+/// Single-connection pool so every query below reuses the same session
+async fn manager(statement_timeout: Duration) -> DbManager {
+    dotenv::dotenv().ok();
+    let config = DbConfig {
+        connection_string: std::env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
+        max_connections: 1,
+        acquire_timeout: Duration::from_secs(5),
+        read_replica_url: None,
+        retry: RetryPolicy { max_attempts: 1, ..Default::default() },
+        statement_timeout: Some(statement_timeout),
+        idle_in_transaction_timeout: Some(Duration::from_secs(5)),
+        tls: TlsConfig::from_env(),
+        slow_acquire_threshold: Duration::ZERO,
+        slow_query_threshold: Duration::ZERO,
+        application_name: Some("sai".to_string()),
+        time_zone: Some("America/Asuncion".to_string()),
+        statement_cache_capacity: 0,
+        password: None,
+    };
+    DbManager::new(config).await.expect("Failed to connect to database")
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_session_timeouts_are_applied() {
+    let db = manager(Duration::from_millis(1500)).await;
+
+    let statement: String = sqlx::query_scalar("SHOW statement_timeout").fetch_one(db.get_pool()).await.unwrap();
+    let idle: String = sqlx::query_scalar("SHOW idle_in_transaction_session_timeout")
+        .fetch_one(db.get_pool())
+        .await
+        .unwrap();
+
+    assert_eq!(statement, "1500ms");
+    assert_eq!(idle, "5s");
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_connection_identity_is_applied() {
+    let db = manager(Duration::from_secs(5)).await;
+
+    let application_name: String = sqlx::query_scalar("SHOW application_name").fetch_one(db.get_pool()).await.unwrap();
+    let time_zone: String = sqlx::query_scalar("SHOW TimeZone").fetch_one(db.get_pool()).await.unwrap();
+
+    assert_eq!(application_name, "sai");
+    assert_eq!(time_zone, "America/Asuncion");
+
+    // Without a statement cache the same query still runs repeatedly
+    for _ in 0..2 {
+        let value: i32 = sqlx::query_scalar("SELECT $1").bind(7).fetch_one(db.get_pool()).await.unwrap();
+        assert_eq!(value, 7);
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_statement_timeout_surfaces_as_timeout_error() {
+    let db = manager(Duration::from_millis(100)).await;
+
+    let result = with_timeout(
+        Duration::from_secs(5),
+        sqlx::query("SELECT pg_sleep(2)").execute(db.get_pool()),
+    )
+    .await;
+    assert!(matches!(result, Err(ServiceError::Timeout(_))), "unexpected result: {:?}", result.err());
+
+    // The cancelled statement leaves the pooled connection usable
+    let value: i32 = sqlx::query_scalar("SELECT 1").fetch_one(db.get_pool()).await.unwrap();
+    assert_eq!(value, 1);
+    assert_eq!(db.get_pool().size(), 1);
+}
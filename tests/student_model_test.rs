@@ -0,0 +1,150 @@
+//! `Student::find_all` and `Student::count` filters against a real database.
+//!
+//! Every test gets its own schema, so they run in parallel. Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test student_model_test -- --ignored`.
+
+use sai::db::DbError;
+use sai::models::student::StudentFilter;
+use sai::models::{Student, StudentStatus};
+use sai::testing::{fixtures, TestDb};
+
+/// Listed user IDs and the count for the same filter
+async fn listed(db: &TestDb, filter: impl Fn() -> StudentFilter) -> (Vec<uuid::Uuid>, i64) {
+    let students = Student::find_all(&db.pool, filter(), None, None).await.unwrap();
+    let total = Student::count(&db.pool, &filter()).await.unwrap();
+
+    (students.into_iter().map(|student| student.user_id).collect(), total)
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_user_id() {
+    let db = TestDb::new().await;
+    let (user, _) = fixtures::student_with_user().create(&db.pool).await;
+    fixtures::student_with_user().create(&db.pool).await;
+
+    let (ids, total) = listed(&db, || StudentFilter { user_id: Some(user.id), ..Default::default() }).await;
+
+    assert_eq!(ids, vec![user.id]);
+    assert_eq!(total, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_academic_year() {
+    let db = TestDb::new().await;
+    let (old, _) = fixtures::student_with_user().academic_year(2023).create(&db.pool).await;
+    fixtures::student_with_user().academic_year(2024).create(&db.pool).await;
+
+    let (ids, total) = listed(&db, || StudentFilter { academic_year: Some(2023), ..Default::default() }).await;
+
+    assert_eq!(ids, vec![old.id]);
+    assert_eq!(total, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_status() {
+    let db = TestDb::new().await;
+    fixtures::student_with_user().create(&db.pool).await;
+    let (suspended, _) = fixtures::student_with_user()
+        .status(StudentStatus::Suspended)
+        .create(&db.pool)
+        .await;
+
+    let (ids, total) = listed(&db, || StudentFilter {
+        status: Some(StudentStatus::Suspended),
+        ..Default::default()
+    })
+    .await;
+
+    assert_eq!(ids, vec![suspended.id]);
+    assert_eq!(total, 1);
+
+    let students = Student::find_all(&db.pool, StudentFilter::default(), None, None).await.unwrap();
+    assert!(students.iter().any(|student| student.status == StudentStatus::Active));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_guardian_name() {
+    let db = TestDb::new().await;
+    let (child, _) = fixtures::student_with_user().guardian("María Giménez").create(&db.pool).await;
+    fixtures::student_with_user().guardian("Rosa Benítez").create(&db.pool).await;
+    fixtures::student_with_user().create(&db.pool).await;
+
+    let (ids, total) = listed(&db, || StudentFilter {
+        guardian_name: Some("giménez".to_string()),
+        ..Default::default()
+    })
+    .await;
+
+    assert_eq!(ids, vec![child.id]);
+    assert_eq!(total, 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_student_without_guardian_stores_sql_null() {
+    let db = TestDb::new().await;
+    let (user, student) = fixtures::student_with_user().create(&db.pool).await;
+
+    let is_null: bool = sqlx::query_scalar("SELECT guardian_info IS NULL FROM students WHERE user_id = $1")
+        .bind(user.id)
+        .fetch_one(&db.pool)
+        .await
+        .unwrap();
+
+    assert!(is_null);
+    assert!(student.guardian_info.is_none());
+    let listed = Student::find_all(&db.pool, StudentFilter::default(), None, None).await.unwrap();
+    assert!(listed[0].guardian_info.is_none());
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_corrupted_guardian_info_is_a_decode_error() {
+    let db = TestDb::new().await;
+    let (user, _) = fixtures::student_with_user().guardian("María Giménez").create(&db.pool).await;
+    sqlx::query(r#"UPDATE students SET guardian_info = '{"name": 42}' WHERE user_id = $1"#)
+        .bind(user.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+    let result = Student::find_all(&db.pool, StudentFilter::default(), None, None).await;
+
+    assert!(matches!(result, Err(DbError::Serialization(_))), "{:?}", result);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_combined_filters_and_pagination() {
+    let db = TestDb::new().await;
+    for _ in 0..3 {
+        fixtures::student_with_user().academic_year(2025).create(&db.pool).await;
+    }
+    fixtures::student_with_user()
+        .academic_year(2025)
+        .status(StudentStatus::Withdrawn)
+        .create(&db.pool)
+        .await;
+
+    let filter = || StudentFilter {
+        academic_year: Some(2025),
+        status: Some(StudentStatus::Active),
+        ..Default::default()
+    };
+    let page = Student::find_all(&db.pool, filter(), Some(2), Some(2)).await.unwrap();
+
+    assert_eq!(page.len(), 1);
+    assert_eq!(Student::count(&db.pool, &filter()).await.unwrap(), 3);
+    db.teardown().await;
+}
@@ -0,0 +1,79 @@
+//! `Teacher::find_all` and `Teacher::count` filters against a real database.
+//!
+//! Every test gets its own schema, so they run in parallel. Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test teacher_model_test -- --ignored`.
+
+use sai::models::teacher::TeacherFilter;
+use sai::models::{Teacher, TeacherStatus};
+use sai::testing::{fixtures, TestDb};
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_subject() {
+    let db = TestDb::new().await;
+    let (physics, _) = fixtures::teacher_with_user()
+        .subjects(&["Física", "Química"])
+        .create(&db.pool)
+        .await;
+    fixtures::teacher_with_user().subjects(&["Historia"]).create(&db.pool).await;
+
+    let filter = |subject: &str| TeacherFilter { subject: Some(subject.to_string()), ..Default::default() };
+
+    let teachers = Teacher::find_all(&db.pool, filter("Química"), None, None).await.unwrap();
+    assert_eq!(teachers.len(), 1);
+    assert_eq!(teachers[0].user_id, physics.id);
+    assert_eq!(teachers[0].subjects, vec!["Física", "Química"]);
+    assert_eq!(Teacher::count(&db.pool, filter("Química")).await.unwrap(), 1);
+
+    // A subject nobody teaches, and one with quotes that must not break the JSON
+    for subject in ["Latín", "Física \"avanzada\""] {
+        assert!(Teacher::find_all(&db.pool, filter(subject), None, None).await.unwrap().is_empty());
+        assert_eq!(Teacher::count(&db.pool, filter(subject)).await.unwrap(), 0);
+    }
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_status_and_specialization() {
+    let db = TestDb::new().await;
+    let (on_leave, _) = fixtures::teacher_with_user()
+        .specialization("Ciencias Naturales")
+        .status(TeacherStatus::OnLeave)
+        .create(&db.pool)
+        .await;
+    fixtures::teacher_with_user().specialization("Ciencias Naturales").create(&db.pool).await;
+    fixtures::teacher_with_user().status(TeacherStatus::OnLeave).create(&db.pool).await;
+
+    let filter = || TeacherFilter {
+        specialization: Some("ciencias".to_string()),
+        status: Some(TeacherStatus::OnLeave),
+        ..Default::default()
+    };
+    let teachers = Teacher::find_all(&db.pool, filter(), None, None).await.unwrap();
+
+    assert_eq!(teachers.len(), 1);
+    assert_eq!(teachers[0].user_id, on_leave.id);
+    assert_eq!(teachers[0].status, TeacherStatus::OnLeave);
+    assert_eq!(Teacher::count(&db.pool, filter()).await.unwrap(), 1);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_pagination() {
+    let db = TestDb::new().await;
+    for _ in 0..5 {
+        fixtures::teacher_with_user().create(&db.pool).await;
+    }
+
+    let first = Teacher::find_all(&db.pool, TeacherFilter::default(), Some(2), None).await.unwrap();
+    let last = Teacher::find_all(&db.pool, TeacherFilter::default(), Some(2), Some(4)).await.unwrap();
+
+    assert_eq!(first.len(), 2);
+    assert_eq!(last.len(), 1);
+    assert!(first.iter().all(|teacher| teacher.user_id != last[0].user_id));
+    assert_eq!(Teacher::count(&db.pool, TeacherFilter::default()).await.unwrap(), 5);
+    db.teardown().await;
+}
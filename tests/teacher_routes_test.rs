@@ -0,0 +1,154 @@
+//! Database-backed tests for `/api/teachers`.
+//!
+//! Requires a migrated database in `DATABASE_URL`; run manually with
+//! `cargo test --test teacher_routes_test -- --ignored`.
+
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+use sai::models::teacher::{CreateTeacherWithUserDto, TeacherFilter};
+use sai::models::{TeacherStatus, User};
+use sai::services::teachers::TeacherService;
+use uuid::Uuid;
+
+async fn pool() -> sqlx::PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    sqlx::PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+/// Teacher with unique identifiers so tests can run against a shared database
+fn new_teacher(subjects: &[&str], status: TeacherStatus) -> CreateTeacherWithUserDto {
+    let tag = Uuid::new_v4().simple().to_string();
+
+    CreateTeacherWithUserDto {
+        document_id: tag[..10].to_string(),
+        full_name: "Docente de Prueba".to_string(),
+        email: format!("docente-{}@example.com", tag),
+        phone: None,
+        address: None,
+        birth_date: NaiveDate::from_ymd_opt(1985, 6, 1).unwrap(),
+        professional_id: format!("MEC-{}", &tag[..8]),
+        specialization: "Ciencias".to_string(),
+        hire_date: NaiveDate::from_ymd_opt(2024, 2, 15).unwrap(),
+        education_level: "Licenciatura".to_string(),
+        subjects: subjects.iter().map(|s| s.to_string()).collect(),
+        status,
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_subject() {
+    let service = TeacherService::new(Arc::new(pool().await));
+    let subject = format!("Astronomía {}", Uuid::new_v4());
+    let (_, teacher) = service
+        .create_teacher_with_user(new_teacher(&[&subject, "Física"], TeacherStatus::Active))
+        .await
+        .unwrap();
+    service
+        .create_teacher_with_user(new_teacher(&["Física"], TeacherStatus::Active))
+        .await
+        .unwrap();
+
+    let filter = TeacherFilter {
+        subject: Some(subject),
+        ..Default::default()
+    };
+    let page = service.get_all_teachers(Some(filter), 1, 20).await.unwrap();
+
+    assert_eq!(page.total, 1);
+    assert_eq!(page.data.len(), 1);
+    assert_eq!(page.data[0].user_id, teacher.user_id);
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_status() {
+    let service = TeacherService::new(Arc::new(pool().await));
+    let (_, teacher) = service
+        .create_teacher_with_user(new_teacher(&["Historia"], TeacherStatus::OnLeave))
+        .await
+        .unwrap();
+
+    let filter = TeacherFilter {
+        status: Some(TeacherStatus::OnLeave),
+        ..Default::default()
+    };
+    // Every matching teacher on one page, since the database is shared
+    let teachers = service.get_all_teachers(Some(filter.clone()), 1, u32::MAX).await.unwrap().data;
+    let total = service.count_teachers(Some(filter)).await.unwrap();
+
+    assert!(teachers.iter().all(|t| t.status == TeacherStatus::OnLeave));
+    assert!(teachers.iter().any(|t| t.user_id == teacher.user_id));
+    assert_eq!(total, teachers.len() as i64);
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_create_with_user_is_atomic() {
+    let pool = pool().await;
+    let service = TeacherService::new(Arc::new(pool.clone()));
+    let (user, teacher) = service
+        .create_teacher_with_user(new_teacher(&["Química"], TeacherStatus::Active))
+        .await
+        .unwrap();
+    assert_eq!(user.id, teacher.user_id);
+
+    // Same professional ID with a new user: the teacher insert fails, so the
+    // user created earlier in the transaction must be rolled back
+    let mut duplicate = new_teacher(&["Química"], TeacherStatus::Active);
+    duplicate.professional_id = teacher.professional_id.clone();
+    let email = duplicate.email.clone();
+
+    assert!(service.create_teacher_with_user(duplicate).await.is_err());
+    assert!(User::find_by_email(&pool, &email).await.unwrap().is_none());
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_delete_keeps_teacher_as_terminated() {
+    let service = TeacherService::new(Arc::new(pool().await));
+    let (_, teacher) = service
+        .create_teacher_with_user(new_teacher(&["Música"], TeacherStatus::Active))
+        .await
+        .unwrap();
+
+    service.delete_teacher(teacher.user_id).await.unwrap();
+
+    let teacher = service.get_teacher_by_id(teacher.user_id).await.unwrap();
+    assert_eq!(teacher.status, TeacherStatus::Terminated);
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_csv_import_creates_accounts_and_skips_known_professional_ids() {
+    let pool = pool().await;
+    let service = TeacherService::new(Arc::new(pool.clone()));
+    let (_, existing) = service
+        .create_teacher_with_user(new_teacher(&["Inglés"], TeacherStatus::Active))
+        .await
+        .unwrap();
+
+    let tag = Uuid::new_v4().simple().to_string();
+    let ci: String = tag.chars().filter(char::is_ascii_digit).chain("1234567".chars()).take(7).collect();
+    let csv = format!(
+        "cedula,apellidos,nombres,fecha_nacimiento,email,telefono,especialidad,nivel_educacion,registro_profesional,materias\n\
+         {ci},Benítez,Ana,1985-03-15,ana-{tag}@example.com,,Matemática,Licenciatura,CSV-{tag},Álgebra;Geometría\n\
+         2345678,Giménez,Carlos,1980-01-02,carlos-{tag}@example.com,,Inglés,Licenciatura,{pid},Inglés\n",
+        ci = ci,
+        tag = &tag[..8],
+        pid = existing.professional_id,
+    );
+
+    let result = service.import_from_csv(csv.as_bytes()).await.unwrap();
+
+    assert_eq!(result.created, 1);
+    assert_eq!(result.skipped, 1);
+    assert!(result.errors.is_empty());
+
+    let user = User::find_by_email(&pool, &format!("ana-{}@example.com", &tag[..8])).await.unwrap().unwrap();
+    let auth = sai::models::Authentication::find_by_user_id(&pool, user.id).await.unwrap();
+    assert!(auth.must_change_password);
+    assert!(auth.verify_password(&format!("{}@SAI", sai::utils::format_ci(&ci))));
+}
@@ -0,0 +1,198 @@
+//! Schema-per-tenant isolation, runtime provisioning and the missing-schema error.
+//!
+//! Tenant schemas are database-wide, so each test provisions tenants with
+//! random slugs and drops them at the end. Only the users table is migrated
+//! (see the baseline schema note in docs/database.md). Requires
+//! `TEST_DATABASE_URL`; run with `cargo test --test tenant_test -- --ignored`.
+
+use actix_web::{http::StatusCode, test, App};
+use chrono::Utc;
+use jsonwebtoken::{encode, Header};
+use sai::config::AppConfig;
+use sai::db::tenant::{self, TenantError, TenantId, TenantPool};
+use sai::db::{DbPool, DbPools, Migration, MIGRATIONS};
+use sai::AppState;
+use serde_json::json;
+use sqlx::postgres::PgPoolOptions;
+use uuid::Uuid;
+
+fn users_table() -> &'static [Migration] {
+    let end = MIGRATIONS
+        .iter()
+        .position(|m| m.version == "20250313_create_users_table")
+        .expect("users migration");
+    &MIGRATIONS[..=end]
+}
+
+async fn pool() -> DbPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    PgPoolOptions::new().max_connections(5).connect(&url).await.unwrap()
+}
+
+fn random_tenant() -> TenantId {
+    TenantId::parse(&format!("t{}", &Uuid::new_v4().simple().to_string()[..12])).unwrap()
+}
+
+async fn provisioned(pool: &DbPool) -> TenantId {
+    let tenant = random_tenant();
+    tenant::provision_with(pool, &tenant, users_table()).await.unwrap();
+    tenant
+}
+
+async fn drop_tenants(pool: &DbPool, tenants: &[&TenantId]) {
+    for tenant in tenants {
+        sqlx::query(&format!("DROP SCHEMA IF EXISTS {} CASCADE", tenant.schema()))
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
+async fn insert_user(tenants: &TenantPool, email: &str, full_name: &str) {
+    let mut tx = tenants.begin().await.unwrap();
+    sqlx::query(
+        "INSERT INTO users (id, document_id, full_name, email, birth_date, role, created_at, updated_at) \
+         VALUES ($1, $2, $3, $4, '1990-01-01', 'Teacher', now(), now())",
+    )
+    .bind(Uuid::new_v4())
+    .bind(&Uuid::new_v4().simple().to_string()[..20])
+    .bind(full_name)
+    .bind(email)
+    .execute(&mut *tx)
+    .await
+    .unwrap();
+    tx.commit().await.unwrap();
+}
+
+async fn full_names(tenants: &TenantPool) -> Vec<String> {
+    let mut tx = tenants.begin().await.unwrap();
+    let names = sqlx::query_scalar("SELECT full_name FROM users ORDER BY full_name")
+        .fetch_all(&mut *tx)
+        .await
+        .unwrap();
+    tx.commit().await.unwrap();
+    names
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_same_named_rows_stay_isolated() {
+    let pool = pool().await;
+    let (a, b) = (provisioned(&pool).await, provisioned(&pool).await);
+    let pool_a = TenantPool::new(pool.clone(), Some(a.clone()));
+    let pool_b = TenantPool::new(pool.clone(), Some(b.clone()));
+
+    // Same email in both: the unique constraint is per schema
+    insert_user(&pool_a, "direccion@colegio.edu.py", "Directora A").await;
+    insert_user(&pool_b, "direccion@colegio.edu.py", "Directora B").await;
+    insert_user(&pool_b, "secretaria@colegio.edu.py", "Secretaria B").await;
+
+    assert_eq!(full_names(&pool_a).await, vec!["Directora A"]);
+    assert_eq!(full_names(&pool_b).await, vec!["Directora B", "Secretaria B"]);
+
+    // SET LOCAL ended with each transaction: the pool's connections are back on the default path
+    let path: String = sqlx::query_scalar("SHOW search_path").fetch_one(&pool).await.unwrap();
+    assert!(!path.contains(&a.schema()) && !path.contains(&b.schema()), "{}", path);
+    drop_tenants(&pool, &[&a, &b]).await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_provisioning_a_tenant_at_runtime() {
+    let pool = pool().await;
+    let existing = provisioned(&pool).await;
+    let third = random_tenant();
+    let third_pool = TenantPool::new(pool.clone(), Some(third.clone()));
+    assert!(third_pool.begin().await.is_err());
+
+    let summary = tenant::provision_with(&pool, &third, users_table()).await.unwrap();
+
+    assert_eq!(summary.applied_now.len(), users_table().len());
+    assert!(summary.pending_after.is_empty());
+    insert_user(&third_pool, "admin@nuevo.edu.py", "Admin Nuevo").await;
+    assert_eq!(full_names(&third_pool).await, vec!["Admin Nuevo"]);
+    let tenants = tenant::list_tenants(&pool).await.unwrap();
+    assert!(tenants.contains(&existing) && tenants.contains(&third));
+
+    // Re-provisioning only applies what is pending
+    let again = tenant::provision_with(&pool, &third, users_table()).await.unwrap();
+    assert!(again.applied_now.is_empty());
+    drop_tenants(&pool, &[&existing, &third]).await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_missing_schema_is_reported() {
+    let pool = pool().await;
+    let tenant = random_tenant();
+
+    let error = TenantPool::new(pool, Some(tenant.clone())).begin().await.unwrap_err();
+
+    assert!(matches!(&error, TenantError::MissingSchema(slug) if slug == tenant.slug()));
+    assert!(error.to_string().contains("has no schema; provision it first"));
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_tenant_tables_never_fall_back_to_public() {
+    let pool = pool().await;
+    let tenant = provisioned(&pool).await;
+    let tenant_pool = tenant::connect(&pool, &tenant).await.unwrap();
+
+    // `courses` exists in `public` but was not migrated into the tenant
+    let error = sqlx::query_scalar::<_, i64>("SELECT count(*) FROM courses").fetch_one(&tenant_pool).await.unwrap_err();
+    assert!(error.to_string().contains("relation \"courses\" does not exist"), "{}", error);
+
+    let mut tx = TenantPool::new(pool.clone(), Some(tenant.clone())).begin().await.unwrap();
+    assert!(sqlx::query("SELECT count(*) FROM courses").fetch_one(&mut *tx).await.is_err());
+    drop(tx);
+
+    tenant_pool.close().await;
+    drop_tenants(&pool, &[&tenant]).await;
+}
+
+/// Admin token whose `institution` claim names `tenant`
+fn admin_token(tenant: &TenantId) -> String {
+    let keys = AppConfig::from_env().jwt.keys().unwrap();
+    let now = Utc::now().timestamp();
+    let claims = json!({ "sub": Uuid::new_v4(), "role": "admin", "institution": tenant.slug(), "iat": now, "exp": now + 3600 });
+
+    encode(&Header::new(keys.algorithm), &claims, &keys.encoding).unwrap()
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_requests_are_served_from_the_schema_of_their_institution() {
+    let pool = pool().await;
+    let (a, b) = (provisioned(&pool).await, provisioned(&pool).await);
+    insert_user(&TenantPool::new(pool.clone(), Some(a.clone())), "direccion@a.edu.py", "Directora A").await;
+    insert_user(&TenantPool::new(pool.clone(), Some(b.clone())), "direccion@b.edu.py", "Directora B").await;
+    let state = AppState::with_pool(pool.clone(), AppConfig::from_env());
+    let pools = DbPools::primary_only(pool.clone());
+    let app = test::init_service(
+        App::new()
+            .configure(|cfg| sai::routes::configure_app_data_with_state(cfg, &pools, &state))
+            .service(sai::routes::configure()),
+    )
+    .await;
+    let users_of = |tenant: &TenantId| {
+        test::TestRequest::get()
+            .uri("/api/admin/users")
+            .insert_header(("Authorization", format!("Bearer {}", admin_token(tenant))))
+            .to_request()
+    };
+
+    for (tenant, name) in [(&a, "Directora A"), (&b, "Directora B")] {
+        let body: serde_json::Value = test::call_and_read_body_json(&app, users_of(tenant)).await;
+        let names: Vec<_> = body["data"]["data"].as_array().unwrap().iter().map(|u| u["full_name"].clone()).collect();
+        assert_eq!(names, vec![json!(name)], "{}", body);
+    }
+
+    let unknown = random_tenant();
+    let resp = test::call_service(&app, users_of(&unknown)).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "unknown_institution");
+    drop_tenants(&pool, &[&a, &b]).await;
+}
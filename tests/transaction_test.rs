@@ -0,0 +1,108 @@
+//! Database-backed tests for `tx!` / `db::helpers::transaction`.
+//!
+//! Each test works on its own scratch table; run manually with
+//! `cargo test --test transaction_test -- --ignored`.
+
+use std::panic::AssertUnwindSafe;
+
+use futures::FutureExt;
+use sai::tx;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+async fn pool() -> PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+struct ScratchTable {
+    name: String,
+}
+
+impl ScratchTable {
+    async fn create(pool: &PgPool) -> Self {
+        let name = format!("tx_test_{}", Uuid::new_v4().simple());
+        sqlx::query(&format!("CREATE TABLE {} (value TEXT NOT NULL)", name))
+            .execute(pool)
+            .await
+            .unwrap();
+        Self { name }
+    }
+
+    fn insert(&self) -> String {
+        format!("INSERT INTO {} (value) VALUES ($1)", self.name)
+    }
+
+    async fn values(&self, pool: &PgPool) -> Vec<String> {
+        sqlx::query_scalar(&format!("SELECT value FROM {} ORDER BY value", self.name))
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn drop(self, pool: &PgPool) {
+        sqlx::query(&format!("DROP TABLE {}", self.name)).execute(pool).await.unwrap();
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_commits_on_ok() {
+    let pool = pool().await;
+    let table = ScratchTable::create(&pool).await;
+    let (insert, values) = (table.insert(), ["a", "b"]);
+
+    let inserted = tx!(&pool, |tx| {
+        for value in values {
+            sqlx::query(&insert).bind(value).execute(&mut **tx).await?;
+        }
+        Ok::<_, sqlx::Error>(values.len())
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(inserted, 2);
+    assert_eq!(table.values(&pool).await, vec!["a", "b"]);
+    table.drop(&pool).await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_rolls_back_on_err() {
+    let pool = pool().await;
+    let table = ScratchTable::create(&pool).await;
+    let insert = table.insert();
+
+    let result = tx!(&pool, |tx| {
+        sqlx::query(&insert).bind("a").execute(&mut **tx).await?;
+        Err::<(), _>(sqlx::Error::RowNotFound)
+    })
+    .await;
+
+    assert!(matches!(result, Err(sqlx::Error::RowNotFound)));
+    assert!(table.values(&pool).await.is_empty());
+    table.drop(&pool).await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_rolls_back_on_panic() {
+    let pool = pool().await;
+    let table = ScratchTable::create(&pool).await;
+    let insert = table.insert();
+
+    let outcome = AssertUnwindSafe(async {
+        let _: Result<(), sqlx::Error> = tx!(&pool, |tx| {
+            sqlx::query(&insert).bind("a").execute(&mut **tx).await?;
+            panic!("handler bug")
+        })
+        .await;
+    })
+    .catch_unwind()
+    .await;
+
+    assert!(outcome.is_err());
+    assert!(table.values(&pool).await.is_empty());
+    table.drop(&pool).await;
+}
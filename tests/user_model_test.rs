@@ -0,0 +1,173 @@
+//! `User` model against a real database, through the `sai::testing` harness.
+//!
+//! Every test gets its own schema, so they run in parallel. Requires
+//! `TEST_DATABASE_URL`; run with
+//! `cargo test --features testing --test user_model_test -- --ignored`.
+
+use std::sync::Arc;
+
+use sai::db::{DbError, Migration, MIGRATIONS};
+use sai::models::user::UserFilter;
+use sai::models::{Role, User};
+use sai::services::users::{UserError, UserService};
+use sai::testing::{fixtures, TestDb};
+use sai::utils::validation::normalize_email;
+
+/// The users table is all these tests need
+fn users_table() -> &'static [Migration] {
+    let end = MIGRATIONS
+        .iter()
+        .position(|m| m.version == "20250313_create_users_table")
+        .expect("users migration");
+    &MIGRATIONS[..=end]
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_create_then_find_by_email() {
+    let db = TestDb::with_migrations(users_table()).await;
+
+    let created = fixtures::user()
+        .full_name("Ana Benítez")
+        .role(Role::Secretary)
+        .create(&db.pool)
+        .await;
+    let found = User::find_by_email(&db.pool, &created.email).await.unwrap().expect("user not found");
+
+    assert_eq!(found.id, created.id);
+    assert_eq!(found.full_name, "Ana Benítez");
+    assert_eq!(found.role, Role::Secretary);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_find_by_unknown_email_is_none() {
+    let db = TestDb::with_migrations(users_table()).await;
+
+    fixtures::user().create(&db.pool).await;
+
+    assert!(User::find_by_email(&db.pool, "nadie@example.com").await.unwrap().is_none());
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_schemas_are_isolated() {
+    // The same email in two databases: the unique constraint only applies per schema
+    let (first, second) = (
+        TestDb::with_migrations(users_table()).await,
+        TestDb::with_migrations(users_table()).await,
+    );
+
+    fixtures::user().email("repetido@example.com").create(&first.pool).await;
+    fixtures::user().email("repetido@example.com").create(&second.pool).await;
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users").fetch_one(&first.pool).await.unwrap();
+    assert_eq!(count, 1);
+    first.teardown().await;
+    second.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_duplicate_email_is_a_conflict() {
+    let db = TestDb::with_migrations(users_table()).await;
+    fixtures::user().email("repetido@example.com").create(&db.pool).await;
+
+    let duplicate = User::create(&db.pool, fixtures::user().email("repetido@example.com").dto()).await;
+
+    assert!(matches!(duplicate, Err(DbError::Conflict(_))));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_filter_by_role() {
+    let db = TestDb::with_migrations(users_table()).await;
+    let first = fixtures::user().role(Role::Teacher).create(&db.pool).await;
+    let second = fixtures::user().role(Role::Teacher).create(&db.pool).await;
+    fixtures::user().role(Role::Admin).create(&db.pool).await;
+    fixtures::user().role(Role::Parent).create(&db.pool).await;
+
+    let filter = || UserFilter { role: Some(Role::Teacher), ..Default::default() };
+    let teachers = User::find_all(&db.pool, filter(), None, None).await.unwrap();
+
+    let mut ids: Vec<_> = teachers.iter().map(|user| user.id).collect();
+    ids.sort();
+    let mut expected = vec![first.id, second.id];
+    expected.sort();
+    assert_eq!(ids, expected);
+    assert!(teachers.iter().all(|user| user.role == Role::Teacher));
+    assert_eq!(User::count(&db.pool, filter()).await.unwrap(), 2);
+
+    let cursor = User::find_all_cursor(&db.pool, &filter(), None, 10).await.unwrap();
+    assert_eq!(cursor.len(), 2);
+    let students = UserFilter { role: Some(Role::Student), ..Default::default() };
+    assert_eq!(User::count(&db.pool, students).await.unwrap(), 0);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_listing_keeps_each_role() {
+    let db = TestDb::with_migrations(users_table()).await;
+    let director = fixtures::user().full_name("Carmen Ortiz").role(Role::Director).create(&db.pool).await;
+    fixtures::user().role(Role::Student).create(&db.pool).await;
+
+    let filter = UserFilter { full_name: Some("Carmen".to_string()), ..Default::default() };
+    let users = User::find_all(&db.pool, filter, None, None).await.unwrap();
+
+    assert_eq!(users.len(), 1);
+    assert_eq!(users[0].id, director.id);
+    assert_eq!(users[0].role, Role::Director);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_unknown_role_is_a_decode_error() {
+    let db = TestDb::with_migrations(users_table()).await;
+    fixtures::user().role(Role::Teacher).create(&db.pool).await;
+
+    // A label the application does not know, e.g. added by hand to the type
+    sqlx::query("ALTER TYPE user_role ADD VALUE 'Janitor'").execute(&db.pool).await.unwrap();
+    let janitor = fixtures::user().role(Role::Teacher).create(&db.pool).await;
+    sqlx::query("UPDATE users SET role = 'Janitor' WHERE id = $1")
+        .bind(janitor.id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+    let listed = User::find_all(&db.pool, UserFilter::default(), None, None).await;
+    assert!(matches!(listed, Err(DbError::Serialization(_))), "{:?}", listed);
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_case_variant_email_is_a_duplicate() {
+    let db = TestDb::with_migrations(users_table()).await;
+    let service = UserService::new(Arc::new(db.pool.clone()));
+
+    let first = service.create_user(fixtures::user().email(" Juan@Colegio.EDU.py").dto()).await.unwrap();
+    assert_eq!(first.email, "juan@colegio.edu.py");
+
+    let second = service.create_user(fixtures::user().email("juan@colegio.edu.py").dto()).await;
+    assert!(matches!(second, Err(UserError::EmailAlreadyExists)));
+    // The login lookup normalizes the same way
+    let found = User::find_by_email(&db.pool, &normalize_email("JUAN@colegio.edu.py", true)).await.unwrap();
+    assert_eq!(found.map(|user| user.id), Some(first.id));
+    db.teardown().await;
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_missing_user_is_not_found() {
+    let db = TestDb::with_migrations(users_table()).await;
+
+    let deleted = User::delete(&db.pool, uuid::Uuid::new_v4()).await;
+
+    assert!(matches!(deleted, Err(DbError::NotFound(_))));
+    db.teardown().await;
+}
@@ -0,0 +1,270 @@
+//! `/api/users` and `/api/admin/users`, both served by `UserService`.
+//!
+//! Requires a migrated database in `DATABASE_URL`; run manually with
+//! `cargo test --test user_routes_test -- --ignored`.
+
+use actix_web::{http::StatusCode, test, App};
+use chrono::Utc;
+use jsonwebtoken::{encode, Header};
+use sai::config::AppConfig;
+use sai::db::DbPools;
+use sai::AppState;
+use serde_json::json;
+use uuid::Uuid;
+
+async fn pool() -> sqlx::PgPool {
+    dotenv::dotenv().ok();
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    sqlx::PgPool::connect(&url).await.expect("Failed to connect to database")
+}
+
+/// Signs an admin token with the keys the app under test is configured with
+fn admin_token() -> String {
+    let keys = AppConfig::default().jwt.keys().unwrap();
+    let now = Utc::now().timestamp();
+    let claims = json!({ "sub": Uuid::new_v4().to_string(), "role": "admin", "iat": now, "exp": now + 3600 });
+
+    format!("Bearer {}", encode(&Header::new(keys.algorithm), &claims, &keys.encoding).unwrap())
+}
+
+macro_rules! app {
+    () => {{
+        let state = AppState::with_pool(pool().await, AppConfig::default());
+        let pools = DbPools::primary_only(state.db_pool.clone());
+        test::init_service(
+            App::new()
+                .configure(|cfg| sai::routes::configure_app_data_with_state(cfg, &pools, &state))
+                .service(sai::routes::configure()),
+        )
+        .await
+    }};
+}
+
+/// Body of a new user whose name, email and document are unique to `tag`
+fn new_user(tag: &str) -> serde_json::Value {
+    json!({
+        "document_id": document_id(tag),
+        "full_name": format!("Usuario {}", tag),
+        "email": format!("usuario-{}@example.com", tag),
+        "birth_date": "1990-04-12",
+        "role": "Secretary"
+    })
+}
+
+/// 8-digit cédula derived from `tag`, so each tag gets its own document
+fn document_id(tag: &str) -> String {
+    (10_000_000 + u64::from_str_radix(&tag[tag.len() - 12..], 16).unwrap() % 90_000_000).to_string()
+}
+
+fn tag() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+/// `PATCH /api/users/{id}` with a merge-patch body
+fn merge_patch(id: &str, body: serde_json::Value) -> test::TestRequest {
+    test::TestRequest::patch()
+        .uri(&format!("/api/users/{}", id))
+        .insert_header(("Content-Type", "application/merge-patch+json"))
+        .set_payload(body.to_string())
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_user_lifecycle() {
+    let app = app!();
+    let tag = tag();
+
+    let req = test::TestRequest::post().uri("/api/users").set_json(new_user(&tag)).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    let created: serde_json::Value = test::read_body_json(resp).await;
+    let id = created["data"]["id"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get().uri(&format!("/api/users?search={}", tag)).to_request();
+    let page: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(page["data"]["total"], 1);
+    assert_eq!(page["data"]["data"][0]["id"], id.as_str());
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/users/{}", id))
+        .set_json(json!({ "phone": "0981123456" }))
+        .to_request();
+    let updated: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(updated["data"]["phone"], "0981123456");
+    assert_eq!(updated["data"]["full_name"], format!("Usuario {}", tag));
+
+    let req = merge_patch(&id, json!({ "phone": null })).to_request();
+    let patched: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert!(patched["data"]["phone"].is_null());
+
+    let req = test::TestRequest::delete().uri(&format!("/api/users/{}", id)).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NO_CONTENT);
+
+    for req in [
+        test::TestRequest::get().uri(&format!("/api/users/{}", id)),
+        test::TestRequest::delete().uri(&format!("/api/users/{}", id)),
+        test::TestRequest::put().uri(&format!("/api/users/{}", id)).set_json(json!({ "full_name": "Nadie" })),
+    ] {
+        assert_eq!(test::call_service(&app, req.to_request()).await.status(), StatusCode::NOT_FOUND);
+    }
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_patch_merges_only_the_fields_sent() {
+    let app = app!();
+    let tag = tag();
+
+    let mut body = new_user(&tag);
+    body["phone"] = json!("0981123456");
+    body["address"] = json!("Asunción");
+    let req = test::TestRequest::post().uri("/api/users").set_json(body).to_request();
+    let created: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let id = created["data"]["id"].as_str().unwrap().to_string();
+
+    // null clears the phone; the absent address and name keep their values
+    let req = merge_patch(&id, json!({ "phone": null })).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let patched: serde_json::Value = test::read_body_json(resp).await;
+    assert!(patched["data"]["phone"].is_null());
+    assert_eq!(patched["data"]["address"], "Asunción");
+    assert_eq!(patched["data"]["full_name"], format!("Usuario {}", tag));
+
+    let req = merge_patch(&id, json!({ "address": "Encarnación" })).to_request();
+    let patched: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(patched["data"]["address"], "Encarnación");
+    assert!(patched["data"]["phone"].is_null());
+
+    // A type error or null on a required field changes nothing
+    for body in [json!({ "birth_date": 5 }), json!({ "full_name": null }), json!({ "email": null })] {
+        let resp = test::call_service(&app, merge_patch(&id, body).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    // A plain JSON body is not a merge-patch document
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/users/{}", id))
+        .set_json(json!({ "address": null }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let req = test::TestRequest::get().uri(&format!("/api/users/{}", id)).to_request();
+    let user: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(user["data"]["address"], "Encarnación");
+    assert_eq!(user["data"]["email"], format!("usuario-{}@example.com", tag));
+
+    let resp = test::call_service(&app, merge_patch(&Uuid::new_v4().to_string(), json!({ "phone": null })).to_request()).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_duplicates_and_invalid_fields_rejected() {
+    let app = app!();
+    let (first, second) = (tag(), tag());
+
+    for body in [new_user(&first), new_user(&second)] {
+        let req = test::TestRequest::post().uri("/api/users").set_json(body).to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    let mut same_email = new_user(&tag());
+    same_email["email"] = new_user(&first)["email"].clone();
+    let req = test::TestRequest::post().uri("/api/users").set_json(same_email).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["message"], "Email already exists");
+
+    // Taking another user's document on update
+    let req = test::TestRequest::get().uri(&format!("/api/users?search={}", second)).to_request();
+    let page: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    let second_id = page["data"]["data"][0]["id"].as_str().unwrap().to_string();
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/users/{}", second_id))
+        .set_json(json!({ "document_id": new_user(&first)["document_id"] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+
+    // Keeping its own email is not a conflict
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/users/{}", second_id))
+        .set_json(json!({ "email": new_user(&second)["email"] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let mut invalid = new_user(&tag());
+    invalid["email"] = json!("not-an-email");
+    let req = test::TestRequest::post().uri("/api/users").set_json(invalid).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+
+    let mut invalid = new_user(&tag());
+    invalid["document_id"] = json!("ADMIN-1a2b3c4d");
+    let req = test::TestRequest::post().uri("/api/users").set_json(invalid).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::BAD_REQUEST);
+}
+
+#[actix_rt::test]
+#[ignore]
+async fn test_admin_scope_uses_the_same_service() {
+    let app = app!();
+    let prefix = tag();
+
+    for i in 0..3 {
+        let body = new_user(&format!("{}{}", prefix, i));
+        let req = test::TestRequest::post()
+            .uri("/api/admin/users")
+            .insert_header(("Authorization", admin_token()))
+            .set_json(body)
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CREATED);
+    }
+
+    // Walk the cursor pages of the search
+    let mut seen = Vec::new();
+    let mut uri = format!("/api/admin/users?search={}&per_page=2", prefix);
+    loop {
+        let req = test::TestRequest::get().uri(&uri).insert_header(("Authorization", admin_token())).to_request();
+        let page: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(page["data"]["total"], 3);
+        seen.extend(page["data"]["data"].as_array().unwrap().iter().map(|user| user["id"].as_str().unwrap().to_string()));
+
+        match page["data"]["next_cursor"].as_str() {
+            Some(cursor) => uri = format!("/api/admin/users?search={}&per_page=2&cursor={}", prefix, cursor),
+            None => break,
+        }
+    }
+    assert_eq!(seen.len(), 3);
+
+    let (id, other) = (&seen[0], &seen[1]);
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/admin/users/{}", other))
+        .insert_header(("Authorization", admin_token()))
+        .to_request();
+    let other: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(other["data"]["id"], seen[1].as_str());
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/admin/users/{}", id))
+        .insert_header(("Authorization", admin_token()))
+        .set_json(json!({ "email": other["data"]["email"] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::CONFLICT);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/admin/users/{}", id))
+        .insert_header(("Authorization", admin_token()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/admin/users/{}", id))
+        .insert_header(("Authorization", admin_token()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+
+    // Without a token the admin scope answers 401
+    let req = test::TestRequest::get().uri(&format!("/api/admin/users/{}", Uuid::new_v4())).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+}